@@ -78,6 +78,8 @@ impl Default for Options {
             receiver_max_sources_sync_attempts: 3,
             receiver_neighbours_rotate_min_period: Duration::from_millis(60000),
             receiver_neighbours_rotate_max_period: Duration::from_millis(120000),
+            receiver_partition_timeout: Duration::from_millis(60000),
+            receiver_resync_period: Duration::from_millis(10000),
         }
     }
 }
@@ -1025,6 +1027,8 @@ impl CatchainProcessor {
         metrics_dumper.add_derivative_metric("receiver_out_queries.total".to_string());
         metrics_dumper.add_derivative_metric("receiver_in_queries.total".to_string());
         metrics_dumper.add_derivative_metric("receiver_in_broadcasts".to_string());
+        metrics_dumper.add_derivative_metric("receiver_resyncs".to_string());
+        metrics_dumper.add_derivative_metric("receiver_recoveries".to_string());
         metrics_dumper.add_derivative_metric("db_get_txs".to_string());
         metrics_dumper.add_derivative_metric("db_put_txs".to_string());
         metrics_dumper.add_derivative_metric("catchain_main_loop_iterations".to_string());
@@ -1724,6 +1728,18 @@ impl CatchainProcessor {
         }
     }
 
+    fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        log::trace!("Catchain idle timeout is changed to {:?}", idle_timeout);
+
+        self.options.idle_timeout = idle_timeout;
+
+        //wake up earlier if the timeout is shrunk
+
+        if !self.active_process && self.receiver_started {
+            self.set_next_block_generation_time(SystemTime::now() + idle_timeout);
+        }
+    }
+
     fn processed_block(
         &mut self,
         payload: BlockPayloadPtr,
@@ -2315,6 +2331,7 @@ impl CatchainProcessor {
             allow_unsafe_self_blocks_resync,
             Some(metrics_receiver),
         )?;
+        receiver.borrow_mut().set_options(&options);
 
         //catchain processor creation
 
@@ -2548,6 +2565,12 @@ impl Catchain for CatchainImpl {
         });
     }
 
+    fn set_idle_timeout(&self, idle_timeout: Duration) {
+        self.post_closure(move |processor: &mut CatchainProcessor| {
+            processor.set_idle_timeout(idle_timeout);
+        });
+    }
+
     /*
         Network access interface
     */
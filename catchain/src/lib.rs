@@ -214,6 +214,13 @@ pub struct Options {
 
     /// Receiver: max time for catchain neighbours rotation
     pub receiver_neighbours_rotate_max_period: std::time::Duration,
+
+    /// Receiver: time without blocks from other sources after which the receiver
+    /// resynchronizes with all sources (zero disables resync)
+    pub receiver_partition_timeout: std::time::Duration,
+
+    /// Receiver: period of resync attempts until blocks from other sources are received
+    pub receiver_resync_period: std::time::Duration,
 }
 
 /// Catchain log replay options
@@ -483,6 +490,9 @@ pub trait Receiver {
     /// Catchain options
     fn get_options(&self) -> &Options;
 
+    /// Update catchain options
+    fn set_options(&mut self, options: &Options);
+
     /// Get number of sources
     fn get_sources_count(&self) -> usize;
 
@@ -879,6 +889,9 @@ pub trait Catchain: Send + Sync {
         enable_batching_mode: bool,
     );
 
+    /// Change idle timeout (delay of block creation when there is nothing to process)
+    fn set_idle_timeout(&self, idle_timeout: std::time::Duration);
+
     /// Send broadcast
     fn send_broadcast(&self, payload: BlockPayloadPtr);
 
@@ -78,6 +78,8 @@ pub(crate) struct ReceiverImpl {
     in_messages_counter: metrics::Counter, //incoming messages counter
     out_messages_counter: metrics::Counter, //outgoing messages counter
     in_broadcasts_counter: metrics::Counter, //incoming broadcasts counter
+    resyncs_counter: metrics::Counter, //resyncs after network partition counter
+    recoveries_counter: metrics::Counter, //recoveries after network partition counter
     pending_in_db: i32,            //blocks pending to read from DB
     db: Option<DatabasePtr>,       //database with (BlockHash, Payload)
     read_db: bool,                 //flag to indicate receiver is in reading DB mode
@@ -90,6 +92,9 @@ pub(crate) struct ReceiverImpl {
     next_sync_time: SystemTime,      //time to do next sync with a random neighbour
     next_neighbours_rotate_time: SystemTime, //time to change neighbours
     initial_sync_complete_time: SystemTime, //time to finish initial synchronization
+    last_peer_block_time: SystemTime, //time of the last delivered block of other sources
+    next_resync_time: SystemTime,     //time of the next resync attempt after network partition
+    partitioned_since: Option<SystemTime>, //time since which no blocks of other sources are delivered (if resync is started)
     rng: rand::rngs::ThreadRng,      //random generator
     get_pending_deps_call_id: u64, //unique ID for calling get_pending_deps (to cut off duplications during the blocks graph traverse)
 }
@@ -136,6 +141,10 @@ impl Receiver for ReceiverImpl {
         &self.options
     }
 
+    fn set_options(&mut self, options: &Options) {
+        self.options = *options;
+    }
+
     /*
         Work with sources
     */
@@ -522,6 +531,10 @@ impl Receiver for ReceiverImpl {
             },
         );
 
+        if block.get_source_id() != self.local_idx {
+            self.peer_block_delivered();
+        }
+
         //prepare and send message with a new block to current overlay neighbours
 
         let mut receiver_addresses = Vec::new();
@@ -711,12 +724,30 @@ impl Receiver for ReceiverImpl {
                     self.initial_sync_complete_time =
                         now + Duration::from_secs(INFINITE_INITIAL_SYNC_COMPLETE_TIME_SECS);
                     self.started = true;
+                    self.last_peer_block_time = now;
 
                     self.notify_on_started();
                 }
             }
         }
 
+        //resync after network partition
+
+        let partition_timeout = self.options.receiver_partition_timeout;
+
+        if self.started && partition_timeout > Duration::default() && self.get_sources_count() > 1 {
+            let resync_time = self.last_peer_block_time + partition_timeout;
+
+            if now >= resync_time && now >= self.next_resync_time {
+                self.resync();
+
+                self.next_resync_time = now + self.options.receiver_resync_period;
+            }
+
+            self.set_next_awake_time(resync_time);
+            self.set_next_awake_time(self.next_resync_time);
+        }
+
         //update awake time
 
         self.set_next_awake_time(self.initial_sync_complete_time);
@@ -1531,6 +1562,75 @@ impl ReceiverImpl {
         }
     }
 
+    /*
+        Recovery after network partition
+    */
+
+    fn resync(&mut self) {
+        instrument!();
+
+        if self.partitioned_since.is_none() {
+            log::warn!(
+                "Receiver {}: no blocks from other sources for {:.3}s, resynchronizing",
+                self.incarnation.to_hex_string(),
+                self.last_peer_block_time.elapsed().unwrap_or_default().as_secs_f64(),
+            );
+
+            self.partitioned_since = Some(self.last_peer_block_time);
+        }
+
+        self.resyncs_counter.increment(1);
+
+        //new neighbours instead of the unresponsive ones
+
+        self.choose_neighbours();
+
+        //request differences from all sources
+
+        let mut adnl_ids = Vec::new();
+
+        for source_id in 0..self.get_sources_count() {
+            if source_id == self.local_idx {
+                continue;
+            }
+
+            let source = self.get_source(source_id);
+
+            if source.borrow().is_blamed() {
+                continue;
+            }
+
+            adnl_ids.push(source.borrow().get_adnl_id().clone());
+
+            self.synchronize_with(source);
+        }
+
+        //re-announce the last local block (other sources may miss it)
+
+        let last_sent_block = self.last_sent_block.clone();
+
+        if last_sent_block.borrow().get_height() > 0 && adnl_ids.len() > 0 {
+            let serialized_block_with_payload =
+                last_sent_block.borrow_mut().get_serialized_block_with_payload().clone();
+
+            self.send_block_update_event_multicast(&adnl_ids, &serialized_block_with_payload);
+        }
+    }
+
+    fn peer_block_delivered(&mut self) {
+        self.last_peer_block_time = SystemTime::now();
+
+        if let Some(partitioned_since) = self.partitioned_since.take() {
+            self.recoveries_counter.increment(1);
+
+            log::info!(
+                "Receiver {}: blocks from other sources are received again after {:.3}s",
+                self.incarnation.to_hex_string(),
+                partitioned_since.elapsed().unwrap_or_default().as_secs_f64(),
+            );
+        }
+    }
+
     /*
         Sources synchronization
     */
@@ -2443,6 +2543,8 @@ impl ReceiverImpl {
         let out_messages_counter = metrics_receiver.sink().register_counter(&"receiver_out_messages".into());
         let in_messages_counter = metrics_receiver.sink().register_counter(&"receiver_in_messages".into());
         let in_broadcasts_counter = metrics_receiver.sink().register_counter(&"receiver_in_broadcasts".into());
+        let resyncs_counter = metrics_receiver.sink().register_counter(&"receiver_resyncs".into());
+        let recoveries_counter = metrics_receiver.sink().register_counter(&"receiver_recoveries".into());
 
         let sources_count = ids.len();
 
@@ -2533,6 +2635,8 @@ impl ReceiverImpl {
             out_messages_counter: out_messages_counter,
             in_messages_counter: in_messages_counter,
             in_broadcasts_counter: in_broadcasts_counter,
+            resyncs_counter,
+            recoveries_counter,
             pending_in_db: 0,
             db: None,
             read_db: false,
@@ -2546,6 +2650,9 @@ impl ReceiverImpl {
             next_neighbours_rotate_time: now,
             initial_sync_complete_time: now
                 + Duration::from_secs(INFINITE_INITIAL_SYNC_COMPLETE_TIME_SECS),
+            last_peer_block_time: now,
+            next_resync_time: now,
+            partitioned_since: None,
             rng: rand::thread_rng(),
             get_pending_deps_call_id: 0,
         };
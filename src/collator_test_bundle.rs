@@ -13,6 +13,7 @@
 
 use crate::{
     block::BlockStuff, engine_traits::{EngineAlloc, EngineOperations}, 
+    ext_messages::DeferredRempMessage,
     shard_state::ShardStateStuff, types::top_block_descr::TopBlockDescrStuff,
     validator::{
         accept_block::create_top_shard_block_description, BlockCandidate,
@@ -1183,6 +1184,7 @@ impl CollatorTestBundle {
 
     pub fn candidate(&self) -> Option<&BlockCandidate> { self.candidate.as_ref() }
     pub fn set_notes(&mut self, notes: String) { self.index.notes = notes }
+    pub fn set_collator_config(&mut self, config: CollatorConfig) { self.collator_config = config }
 
     fn get_messages(&self, remp: bool) -> Result<Vec<(Arc<Message>, UInt256)>> {
         let remp_enabled = self.states
@@ -1321,6 +1323,14 @@ impl EngineOperations for CollatorTestBundle {
         self.get_messages(true)
     }
 
+    fn get_remp_queue_depth(&self, _shard: &ShardIdent) -> Result<(usize, usize)> {
+        let messages = self.get_messages(true)?;
+        let bytes = messages.iter()
+            .map(|(message, _)| message.write_to_bytes().map_or(0, |data| data.len()))
+            .sum();
+        Ok((messages.len(), bytes))
+    }
+
     fn finalize_remp_messages(
         &self,
         _block: BlockIdExt,
@@ -1331,6 +1341,18 @@ impl EngineOperations for CollatorTestBundle {
         Ok(())
     }
 
+    fn set_remp_deferred_messages(
+        &self,
+        _shard: &ShardIdent,
+        _deferred: Vec<(UInt256, DeferredRempMessage)>
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_remp_deferred_message(&self, _id: &UInt256) -> Option<DeferredRempMessage> {
+        None
+    }
+
     async fn check_remp_duplicate(&self, _message_id: &UInt256) -> Result<RempDuplicateStatus> {
         Ok(RempDuplicateStatus::Fresh(UInt256::default()))
     }
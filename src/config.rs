@@ -11,7 +11,7 @@
 * limitations under the License.
 */
 
-use crate::network::node_network::NodeNetwork;
+use crate::{ext_messages::MAX_EXTERNAL_MESSAGE_SIZE, network::node_network::NodeNetwork};
 
 use adnl::{
     client::AdnlClientConfigJson,
@@ -31,7 +31,7 @@ use ton_api::{
         dht::node::Node as DhtNodeConfig, pub_::publickey::Ed25519
     }
 };
-use ton_block::{BlockIdExt, ShardIdent};
+use ton_block::{BlockIdExt, BlockLimits, ConfigParamEnum, ConfigParams, ParamLimits, ShardIdent};
 #[cfg(feature="external_db")]
 use ton_block::{BASE_WORKCHAIN_ID, MASTERCHAIN_ID};
 use ton_types::{
@@ -39,6 +39,10 @@ use ton_types::{
     Result, UInt256
 };
 
+#[cfg(test)]
+#[path = "tests/test_config.rs"]
+mod tests;
+
 #[macro_export]
 macro_rules! key_option_public_key {
     ($key: expr) => {
@@ -84,12 +88,18 @@ pub struct CollatorConfig {
     pub optimistic_clean_percentage_points: u32,
     pub max_secondary_clean_timeout_percentage_points: u32,
     pub max_collate_threads: u32,
+    pub parallel_execution: bool, // false - transactions are executed one by one
+    pub work_cache: bool, // keep states and config between collations of a shard
+    pub validation_threads: u32, // 0 - validation is performed in the engine's runtime
+    pub validation_task_threads: u32, // 0 - unlimited
     pub retry_if_empty: bool,
     pub finalize_empty_after_ms: u32,
     pub empty_collation_sleep_ms: u32,
     pub external_messages_timeout_percentage_points: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_messages_maximum_queue_length: Option<u32>, // None - unlimited
+    pub max_ext_messages_per_account: u32, // 0 - unlimited
+    pub remp_block_fill_percent: u32, // part of block limits which REMP messages may fill
 }
 impl Default for CollatorConfig {
     fn default() -> Self {
@@ -100,11 +110,222 @@ impl Default for CollatorConfig {
             optimistic_clean_percentage_points: 1000, // 1.000 = 100% = 150ms
             max_secondary_clean_timeout_percentage_points: 350, // 0.350 = 35% = 350ms
             max_collate_threads: 10,
+            parallel_execution: true,
+            work_cache: false,
+            validation_threads: 0,
+            validation_task_threads: 0,
             retry_if_empty: false,
             finalize_empty_after_ms: 800,
             empty_collation_sleep_ms: 100,
             external_messages_timeout_percentage_points: 100, // 0.1 = 10% = 100ms
             external_messages_maximum_queue_length: None,
+            max_ext_messages_per_account: 0,
+            remp_block_fill_percent: 100,
+        }
+    }
+}
+
+impl CollatorConfig {
+    /// Values of older options, which were accepted before, are clamped with a warning;
+    /// invalid values of newer options are rejected
+    pub fn check(&mut self) -> Result<()> {
+        if self.cutoff_timeout_ms == 0 {
+            log::warn!("collator_config: cutoff_timeout_ms can't have zero value, 1 is used");
+            self.cutoff_timeout_ms = 1;
+        }
+        if self.cutoff_timeout_ms > self.stop_timeout_ms {
+            log::warn!("collator_config: cutoff_timeout_ms {} should be <= stop_timeout_ms {}, {} is used",
+                self.cutoff_timeout_ms, self.stop_timeout_ms, self.stop_timeout_ms);
+            self.cutoff_timeout_ms = self.stop_timeout_ms;
+        }
+        if self.max_collate_threads == 0 {
+            log::warn!("collator_config: max_collate_threads can't have zero value, 1 is used");
+            self.max_collate_threads = 1;
+        }
+        if self.finalize_empty_after_ms > self.stop_timeout_ms {
+            log::warn!("collator_config: finalize_empty_after_ms {} should be <= stop_timeout_ms {}, {} is used",
+                self.finalize_empty_after_ms, self.stop_timeout_ms, self.stop_timeout_ms);
+            self.finalize_empty_after_ms = self.stop_timeout_ms;
+        }
+        for (name, value) in [
+            ("clean_timeout_percentage_points", &mut self.clean_timeout_percentage_points),
+            ("optimistic_clean_percentage_points", &mut self.optimistic_clean_percentage_points),
+            ("max_secondary_clean_timeout_percentage_points", &mut self.max_secondary_clean_timeout_percentage_points),
+            ("external_messages_timeout_percentage_points", &mut self.external_messages_timeout_percentage_points),
+        ] {
+            if *value > 1000 {
+                log::warn!("collator_config: {} {} should be <= 1000, 1000 is used", name, value);
+                *value = 1000;
+            }
+        }
+        if self.remp_block_fill_percent == 0 || self.remp_block_fill_percent > 100 {
+            fail!("remp_block_fill_percent should be in range 1..=100");
+        }
+        Ok(())
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ParamLimitsOverride {
+    pub underload: u32,
+    pub soft_limit: u32,
+    pub hard_limit: u32,
+}
+
+impl ParamLimitsOverride {
+    fn check(&self, name: &str) -> Result<()> {
+        if self.underload > self.soft_limit || self.soft_limit > self.hard_limit {
+            fail!("{}: underload {}, soft_limit {} and hard_limit {} should be non-decreasing",
+                name, self.underload, self.soft_limit, self.hard_limit);
+        }
+        if self.hard_limit == 0 {
+            fail!("{}: hard_limit can't have zero value", name);
+        }
+        Ok(())
+    }
+
+    fn to_param_limits(&self) -> Result<ParamLimits> {
+        ParamLimits::with_limits(self.underload, self.soft_limit, self.hard_limit)
+    }
+}
+
+// Local overrides of fees and limits of a workchain. They are intended for private networks only,
+// where the operator controls the zerostate and all the validators: blocks collated with
+// the overrides are valid only for validators having the same overrides.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkchainOverrides {
+    // Gas prices, the same units as in config params 20/21
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_gas_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flat_gas_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flat_gas_price: Option<u64>,
+    // Block limits, the same as in config params 22/23
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_bytes: Option<ParamLimitsOverride>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_gas: Option<ParamLimitsOverride>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_lt_delta: Option<ParamLimitsOverride>,
+    // Message size limits, the same as in config param 43
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_msg_bits: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_msg_cells: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ext_msg_size: Option<u32>,
+}
+
+impl WorkchainOverrides {
+
+    pub fn check(&self) -> Result<()> {
+        for (name, limits) in [
+            ("block_bytes", &self.block_bytes),
+            ("block_gas", &self.block_gas),
+            ("block_lt_delta", &self.block_lt_delta),
+        ] {
+            if let Some(limits) = limits {
+                limits.check(name)?;
+            }
+        }
+        if let (Some(flat_gas_limit), Some(gas_limit)) = (self.flat_gas_limit, self.gas_limit) {
+            if flat_gas_limit > gas_limit {
+                fail!("flat_gas_limit {} should be <= gas_limit {}", flat_gas_limit, gas_limit);
+            }
+        }
+        if let (Some(gas_limit), Some(block_gas_limit)) = (self.gas_limit, self.block_gas_limit) {
+            if gas_limit > block_gas_limit {
+                fail!("gas_limit {} should be <= block_gas_limit {}", gas_limit, block_gas_limit);
+            }
+        }
+        if self.max_msg_bits == Some(0) || self.max_msg_cells == Some(0) || self.max_ext_msg_size == Some(0) {
+            fail!("message size limits can't have zero value");
+        }
+        Ok(())
+    }
+
+    fn overrides_gas(&self) -> bool {
+        self.gas_price.is_some() || self.gas_limit.is_some() || self.block_gas_limit.is_some() ||
+            self.flat_gas_limit.is_some() || self.flat_gas_price.is_some()
+    }
+
+    fn overrides_block_limits(&self) -> bool {
+        self.block_bytes.is_some() || self.block_gas.is_some() || self.block_lt_delta.is_some()
+    }
+
+    fn overrides_size_limits(&self) -> bool {
+        self.max_msg_bits.is_some() || self.max_msg_cells.is_some() || self.max_ext_msg_size.is_some()
+    }
+
+    // Replaces corresponding config params (masterchain or basechain ones) with the overrides
+    pub fn apply(&self, config: &mut ConfigParams, is_masterchain: bool) -> Result<()> {
+        if self.overrides_gas() {
+            let mut gas = config.gas_prices(is_masterchain)?;
+            gas.gas_price = self.gas_price.unwrap_or(gas.gas_price);
+            gas.gas_limit = self.gas_limit.unwrap_or(gas.gas_limit);
+            gas.block_gas_limit = self.block_gas_limit.unwrap_or(gas.block_gas_limit);
+            gas.flat_gas_limit = self.flat_gas_limit.unwrap_or(gas.flat_gas_limit);
+            gas.flat_gas_price = self.flat_gas_price.unwrap_or(gas.flat_gas_price);
+            gas.max_gas_threshold = gas.calc_max_gas_threshold();
+            config.set_config(if is_masterchain {
+                ConfigParamEnum::ConfigParam20(gas)
+            } else {
+                ConfigParamEnum::ConfigParam21(gas)
+            })?;
+        }
+        if self.overrides_block_limits() {
+            let limits = config.block_limits(is_masterchain)?;
+            let override_or = |param: &Option<ParamLimitsOverride>, current: &ParamLimits| {
+                match param {
+                    Some(param) => param.to_param_limits(),
+                    None => Ok(current.clone())
+                }
+            };
+            let limits = BlockLimits::with_limits(
+                override_or(&self.block_bytes, limits.bytes())?,
+                override_or(&self.block_gas, limits.gas())?,
+                override_or(&self.block_lt_delta, limits.lt_delta())?,
+            );
+            config.set_config(if is_masterchain {
+                ConfigParamEnum::ConfigParam22(limits)
+            } else {
+                ConfigParamEnum::ConfigParam23(limits)
+            })?;
+        }
+        if self.overrides_size_limits() {
+            let mut size_limits = config.size_limits_config()?;
+            size_limits.max_msg_bits = self.max_msg_bits.unwrap_or(size_limits.max_msg_bits);
+            size_limits.max_msg_cells = self.max_msg_cells.unwrap_or(size_limits.max_msg_cells);
+            size_limits.max_ext_msg_size = self.max_ext_msg_size.unwrap_or(size_limits.max_ext_msg_size);
+            config.set_config(ConfigParamEnum::ConfigParam43(size_limits))?;
+        }
+        if self.overrides_gas() || self.overrides_block_limits() {
+            // overrides are combined with the network config, so the result is checked as a whole
+            let gas = config.gas_prices(is_masterchain)?;
+            let limits = config.block_limits(is_masterchain)?;
+            if gas.gas_limit > gas.block_gas_limit {
+                fail!("overridden gas_limit {} exceeds block_gas_limit {}", gas.gas_limit, gas.block_gas_limit);
+            }
+            if gas.block_gas_limit > limits.gas().hard_limit() as u64 {
+                fail!("overridden block_gas_limit {} exceeds hard limit of block gas {}",
+                    gas.block_gas_limit, limits.gas().hard_limit());
+            }
+        }
+        Ok(())
+    }
+
+    // Maximal size of external message in REMP with respect to the override
+    pub fn max_ext_message_size(&self, max_size: usize) -> usize {
+        match self.max_ext_msg_size {
+            Some(size) => max_size.min(size as usize),
+            None => max_size
         }
     }
 }
@@ -144,6 +365,10 @@ pub struct TonNodeConfig {
     boot_from_zerostate: Option<bool>,
     internal_db_path: Option<String>,
     validation_countdown_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_prestart_sec: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    consensus_catchain_db_path: Option<String>,
     unsafe_catchain_patches_path: Option<String>,
     #[serde(skip_serializing)]
     ip_address: Option<String>,
@@ -173,6 +398,24 @@ pub struct TonNodeConfig {
     #[serde(default = "RempConfig::default")]
     remp: RempConfig,
     #[serde(default)]
+    ext_messages_broadcast: ExtMessagesBroadcastConfig,
+    #[serde(default)]
+    catchain_recovery: CatchainRecoveryConfig,
+    #[serde(default)]
+    catchain_relay: CatchainRelayConfig,
+    #[serde(default)]
+    channels_keepalive: ChannelsKeepaliveConfig,
+    #[serde(default)]
+    validator_network: ValidatorNetworkConfig,
+    #[serde(default)]
+    transfer_cache: TransferCacheConfig,
+    #[serde(default)]
+    broadcast_dedup: BroadcastDedupConfig,
+    #[serde(default)]
+    bandwidth_limits: BandwidthLimitsConfig,
+    #[serde(default)]
+    answers_compression: AnswersCompressionConfig,
+    #[serde(default)]
     restore_db: bool,
     #[serde(default)]
     low_memory_mode: bool,
@@ -186,6 +429,8 @@ pub struct TonNodeConfig {
     states_cache_mode: ShardStatesCacheMode,
     #[serde(default = "default_states_cache_cleanup_diff")]
     states_cache_cleanup_diff: u32,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    workchain_overrides: HashMap<i32, WorkchainOverrides>,
 }
 
 pub struct TonNodeGlobalConfig(TonNodeGlobalConfigJson);
@@ -273,6 +518,7 @@ pub struct ExternalDbConfig {
     pub raw_block_proof_producer: KafkaProducerConfig,
     pub chain_range_producer: KafkaProducerConfig,
     pub remp_statuses_producer: KafkaProducerConfig,
+    pub remp_status_transitions_producer: KafkaProducerConfig,
     pub shard_hashes_producer: KafkaProducerConfig,
     pub bad_blocks_storage: String,
 }
@@ -284,6 +530,40 @@ pub struct RempConfig {
     service_enabled: Option<bool>,
     message_queue_max_len: Option<usize>,
     max_incoming_broadcast_delay_millis: Option<u32>,
+    catchain_transcripts: Option<usize>,
+    max_message_size: Option<usize>,
+    persistent_message_cache: Option<bool>,
+    persistent_replay_protection: Option<bool>,
+    message_cache_max_messages: Option<usize>,
+    message_cache_max_bytes: Option<usize>,
+    message_expiry_window_sec: Option<u32>,
+    native_catchain_payload: Option<bool>,
+    broadcast_rejects: Option<bool>,
+    source_rate_limit: Option<u32>,
+    shard_rate_limit: Option<u32>,
+    catchain_queue_capacity: Option<usize>,
+    compress_catchain_payload: Option<bool>,
+    max_catchain_payload_size: Option<usize>,
+    verify_catchain_records: Option<bool>,
+    message_cache_gc_interval_ms: Option<u64>,
+    message_status_history: Option<bool>,
+    catchain_start_attempts: Option<u32>,
+    catchain_restart_timeout_sec: Option<u64>,
+    catchain_transition_timeout_sec: Option<u64>,
+    catchain_db_retention_sec: Option<u64>,
+    catchain_db_path: Option<String>,
+    catchain_max_idle_timeout_ms: Option<u64>,
+    catchain_idle_rounds: Option<u32>,
+    priority_accounts: Option<Vec<String>>,
+    replay_protection_code_hashes: Option<Vec<String>>,
+    prioritize_by_import_fee: Option<bool>,
+    status_observer: Option<bool>,
+    client_queue_max_len: Option<usize>,
+    sign_receipts: Option<bool>,
+    forward_to_next_set: Option<bool>,
+    acceptance_policy: Option<RempAcceptancePolicyConfig>,
+    masterchain_catchain: Option<RempCatchainProfileConfig>,
+    shardchain_catchain: Option<RempCatchainProfileConfig>,
 }
 
 impl RempConfig {
@@ -295,6 +575,40 @@ impl RempConfig {
             service_enabled: None,
             message_queue_max_len: None,
             max_incoming_broadcast_delay_millis: None,
+            catchain_transcripts: None,
+            max_message_size: None,
+            persistent_message_cache: None,
+            persistent_replay_protection: None,
+            message_cache_max_messages: None,
+            message_cache_max_bytes: None,
+            message_expiry_window_sec: None,
+            native_catchain_payload: None,
+            broadcast_rejects: None,
+            source_rate_limit: None,
+            shard_rate_limit: None,
+            catchain_queue_capacity: None,
+            compress_catchain_payload: None,
+            max_catchain_payload_size: None,
+            verify_catchain_records: None,
+            message_cache_gc_interval_ms: None,
+            message_status_history: None,
+            catchain_start_attempts: None,
+            catchain_restart_timeout_sec: None,
+            catchain_transition_timeout_sec: None,
+            catchain_db_retention_sec: None,
+            catchain_db_path: None,
+            catchain_max_idle_timeout_ms: None,
+            catchain_idle_rounds: None,
+            priority_accounts: None,
+            replay_protection_code_hashes: None,
+            prioritize_by_import_fee: None,
+            status_observer: None,
+            client_queue_max_len: None,
+            sign_receipts: None,
+            forward_to_next_set: None,
+            acceptance_policy: None,
+            masterchain_catchain: None,
+            shardchain_catchain: None,
         }
     }
 
@@ -312,11 +626,195 @@ impl RempConfig {
 
     pub fn get_max_incoming_broadcast_delay_millis(&self) -> u32 { self.max_incoming_broadcast_delay_millis.unwrap_or(1000) }
 
-    pub fn get_catchain_options(&self) -> Option<catchain::Options> {
+    pub fn get_catchain_transcripts(&self) -> usize {
+        self.catchain_transcripts.unwrap_or(0)
+    }
+
+    // Can't exceed the limit for external messages in blocks
+    pub fn get_max_message_size(&self) -> usize {
+        self.max_message_size
+            .unwrap_or(MAX_EXTERNAL_MESSAGE_SIZE)
+            .min(MAX_EXTERNAL_MESSAGE_SIZE)
+    }
+
+    pub fn is_persistent_message_cache(&self) -> bool {
+        self.persistent_message_cache.unwrap_or(false)
+    }
+
+    pub fn is_persistent_replay_protection(&self) -> bool {
+        self.persistent_replay_protection.unwrap_or(true)
+    }
+
+    pub fn get_message_cache_max_messages(&self) -> Option<usize> {
+        self.message_cache_max_messages
+    }
+
+    pub fn get_message_cache_max_bytes(&self) -> Option<usize> {
+        self.message_cache_max_bytes
+    }
+
+    pub fn get_message_expiry_window_sec(&self) -> Option<u32> {
+        self.message_expiry_window_sec
+    }
+
+    pub fn is_native_catchain_payload(&self) -> bool {
+        self.native_catchain_payload.unwrap_or(false)
+    }
+
+    pub fn is_broadcast_rejects(&self) -> bool {
+        self.broadcast_rejects.unwrap_or(false)
+    }
+
+    pub fn get_source_rate_limit(&self) -> Option<u32> {
+        self.source_rate_limit
+    }
+
+    pub fn get_shard_rate_limit(&self) -> Option<u32> {
+        self.shard_rate_limit
+    }
+
+    pub fn get_catchain_queue_capacity(&self) -> usize {
+        self.catchain_queue_capacity.unwrap_or(100000).max(1)
+    }
+
+    pub fn is_compress_catchain_payload(&self) -> bool {
+        self.compress_catchain_payload.unwrap_or(false)
+    }
+
+    pub fn get_max_catchain_payload_size(&self, shard: &ShardIdent) -> usize {
+        self.get_catchain_profile(shard)
+            .and_then(|profile| profile.max_catchain_payload_size)
+            .or(self.max_catchain_payload_size)
+            // one record of the largest external message, as blocks carried before batching
+            .unwrap_or(64 << 10)
+    }
+
+    pub fn is_verify_catchain_records(&self) -> bool {
+        self.verify_catchain_records.unwrap_or(false)
+    }
+
+    pub fn get_message_cache_gc_interval_ms(&self) -> Option<u64> {
+        self.message_cache_gc_interval_ms.filter(|interval| *interval > 0)
+    }
+
+    pub fn is_message_status_history(&self) -> bool {
+        self.message_status_history.unwrap_or(false)
+    }
+
+    pub fn get_catchain_start_attempts(&self) -> u32 {
+        self.catchain_start_attempts.unwrap_or(5).max(1)
+    }
+
+    pub fn get_catchain_restart_timeout_sec(&self) -> Option<u64> {
+        self.catchain_restart_timeout_sec.filter(|timeout| *timeout > 0)
+    }
+
+    pub fn get_catchain_transition_timeout_sec(&self) -> Option<u64> {
+        Some(self.catchain_transition_timeout_sec.unwrap_or(600)).filter(|timeout| *timeout > 0)
+    }
+
+    pub fn get_catchain_db_retention_sec(&self) -> u64 {
+        self.catchain_db_retention_sec.unwrap_or(60)
+    }
+
+    /// Directory of REMP catchain DBs, `rmq` in DB root by default
+    pub fn get_catchain_db_path(&self, db_root: &str) -> String {
+        match &self.catchain_db_path {
+            Some(path) => path.clone(),
+            None => format!("{}/rmq", db_root)
+        }
+    }
+
+    /// Upper bound of REMP catchain idle timeout on quiet shards (None - pacing is disabled)
+    pub fn get_catchain_max_idle_timeout(&self) -> Option<std::time::Duration> {
+        self.catchain_max_idle_timeout_ms.map(std::time::Duration::from_millis)
+    }
+
+    pub fn get_catchain_idle_rounds(&self) -> u32 {
+        self.catchain_idle_rounds.unwrap_or(10).max(1)
+    }
+
+    // Base idle timeout of REMP catchain sessions for the masterchain or shardchains
+    fn get_catchain_idle_timeout_ms(&self, masterchain: bool) -> u64 {
+        let profile = if masterchain {
+            self.masterchain_catchain.as_ref()
+        } else {
+            self.shardchain_catchain.as_ref()
+        };
+        profile.and_then(|profile| profile.idle_timeout_ms).unwrap_or(5000)
+    }
+
+    pub fn check(&self) -> Result<()> {
+        if let Some(max_idle_timeout_ms) = self.catchain_max_idle_timeout_ms {
+            for (name, masterchain) in [("masterchain_catchain", true), ("shardchain_catchain", false)] {
+                let idle_timeout_ms = self.get_catchain_idle_timeout_ms(masterchain);
+                if max_idle_timeout_ms < idle_timeout_ms {
+                    fail!(
+                        "catchain_max_idle_timeout_ms {} can't be less than idle_timeout_ms {} of {}",
+                        max_idle_timeout_ms, idle_timeout_ms, name
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_priority_accounts(&self) -> &[String] {
+        self.priority_accounts.as_deref().unwrap_or(&[])
+    }
+
+    pub fn get_replay_protection_code_hashes(&self) -> &[String] {
+        self.replay_protection_code_hashes.as_deref().unwrap_or(&[])
+    }
+
+    pub fn is_prioritize_by_import_fee(&self) -> bool {
+        self.prioritize_by_import_fee.unwrap_or(false)
+    }
+
+    pub fn is_status_observer(&self) -> bool {
+        self.status_observer.unwrap_or(false)
+    }
+
+    pub fn get_client_queue_max_len(&self) -> usize {
+        self.client_queue_max_len.unwrap_or(0)
+    }
+
+    pub fn is_sign_receipts(&self) -> bool {
+        self.sign_receipts.unwrap_or(false)
+    }
+
+    pub fn is_forward_to_next_set(&self) -> bool {
+        self.forward_to_next_set.unwrap_or(false)
+    }
+
+    pub fn get_acceptance_policy(&self) -> Option<&RempAcceptancePolicyConfig> {
+        self.acceptance_policy.as_ref()
+    }
+
+    fn get_catchain_profile(&self, shard: &ShardIdent) -> Option<&RempCatchainProfileConfig> {
+        if shard.is_masterchain() {
+            self.masterchain_catchain.as_ref()
+        } else {
+            self.shardchain_catchain.as_ref()
+        }
+    }
+
+    /// Catchain options of REMP session for the shard, with overrides of its profile
+    pub fn get_catchain_options(&self, shard: &ShardIdent) -> Option<catchain::Options> {
         if self.is_service_enabled() {
             let mut opts = catchain::Options::default();
-            opts.idle_timeout = std::time::Duration::from_secs(5);
+            opts.idle_timeout = std::time::Duration::from_millis(
+                self.get_catchain_idle_timeout_ms(shard.is_masterchain())
+            );
             opts.max_deps = 2;
+            // Quiet REMP sessions have no blocks of other validators for long (see
+            // `catchain_max_idle_timeout_ms`), it is not a partition to resync after
+            opts.receiver_partition_timeout = std::time::Duration::default();
+            if let Some(profile) = self.get_catchain_profile(shard) {
+                if let Some(max_deps) = profile.max_deps {
+                    opts.max_deps = max_deps;
+                }
+            }
 
             Some(opts)
         } else {
@@ -330,6 +828,246 @@ impl RempConfig {
 
 }
 
+/// Filters of external messages entering REMP, see `DefaultRempAcceptancePolicy`
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, Clone)]
+pub struct RempAcceptancePolicyConfig {
+    // Only messages to these workchains are accepted (all if not set)
+    pub workchains: Option<Vec<i32>>,
+    // Message body must start with ABI `time` and `expire` header
+    pub require_abi_header: Option<bool>,
+    // Messages to accounts with lesser balance (in nanotokens) are rejected
+    pub min_dst_balance: Option<u64>,
+}
+
+/// Overrides of REMP catchain options for sessions of masterchain or shardchains
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, Clone)]
+pub struct RempCatchainProfileConfig {
+    // Timeout of catchain main loop processing, i.e. period of producing blocks
+    pub idle_timeout_ms: Option<u64>,
+    // Maximum number of dependencies of a catchain block
+    pub max_deps: Option<u32>,
+    // Overrides `max_catchain_payload_size` of REMP config
+    pub max_catchain_payload_size: Option<usize>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct ExtMessagesBroadcastConfig {
+    // Accept external messages received via public overlay broadcasts
+    pub enabled: bool,
+    // Maximum number of messages accepted from one source per period, 0 - unlimited
+    pub max_messages_per_source: u32,
+    pub rate_limit_period_ms: u64,
+}
+
+impl Default for ExtMessagesBroadcastConfig {
+    fn default() -> Self {
+        ExtMessagesBroadcastConfig {
+            enabled: true,
+            max_messages_per_source: 0,
+            rate_limit_period_ms: 1000,
+        }
+    }
+}
+
+impl ExtMessagesBroadcastConfig {
+    pub fn check(&self) -> Result<()> {
+        if self.max_messages_per_source > 0 && self.rate_limit_period_ms == 0 {
+            fail!("rate_limit_period_ms can't have zero value when max_messages_per_source is set");
+        }
+        Ok(())
+    }
+}
+
+/// Resync of validator session catchains after network partition
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct CatchainRecoveryConfig {
+    // No blocks from other validators during the time starts resync, 0 - no resync
+    pub partition_timeout_ms: u64,
+    pub resync_period_ms: u64,
+}
+
+impl Default for CatchainRecoveryConfig {
+    fn default() -> Self {
+        CatchainRecoveryConfig {
+            partition_timeout_ms: 60000,
+            resync_period_ms: 10000,
+        }
+    }
+}
+
+impl CatchainRecoveryConfig {
+    pub fn check(&self) -> Result<()> {
+        if self.partition_timeout_ms > 0 && self.resync_period_ms == 0 {
+            fail!("resync_period_ms can't have zero value when partition_timeout_ms is set");
+        }
+        Ok(())
+    }
+}
+
+/// Relaying of catchain messages through other session members
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct CatchainRelayConfig {
+    // Validator session catchains
+    pub consensus: bool,
+    // REMP catchains
+    pub remp: bool,
+    // Messages to a peer silent for this time after our message are relayed too
+    pub relay_after_ms: u64,
+}
+
+impl Default for CatchainRelayConfig {
+    fn default() -> Self {
+        CatchainRelayConfig {
+            consensus: false,
+            remp: false,
+            relay_after_ms: 3000,
+        }
+    }
+}
+
+impl CatchainRelayConfig {
+    pub fn check(&self) -> Result<()> {
+        if (self.consensus || self.remp) && self.relay_after_ms == 0 {
+            fail!("relay_after_ms can't have zero value when relaying is enabled");
+        }
+        Ok(())
+    }
+}
+
+/// Keepalive queries to peers of validator session and REMP catchains
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct ChannelsKeepaliveConfig {
+    pub enabled: bool,
+    // Period of keepalive queries, it is the timeout of the query as well
+    pub period_ms: u64,
+    // Unanswered keepalives in a row to re-establish the channel, 0 - never
+    pub reestablish_after_failures: u32,
+}
+
+impl Default for ChannelsKeepaliveConfig {
+    fn default() -> Self {
+        ChannelsKeepaliveConfig {
+            enabled: false,
+            period_ms: 5000,
+            reestablish_after_failures: 3,
+        }
+    }
+}
+
+impl ChannelsKeepaliveConfig {
+    pub fn check(&self) -> Result<()> {
+        if self.enabled && self.period_ms == 0 {
+            fail!("period_ms can't have zero value when keepalive is enabled");
+        }
+        Ok(())
+    }
+}
+
+/// Separate local address for validator overlays (multi-homing)
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct ValidatorNetworkConfig {
+    // "ip:port" to bind, None - validator overlays use the address of adnl_node
+    pub ip_address: Option<String>,
+}
+
+impl ValidatorNetworkConfig {
+    pub fn check(&self) -> Result<()> {
+        if let Some(ip_address) = &self.ip_address {
+            if let Err(e) = ip_address.parse::<std::net::SocketAddrV4>() {
+                fail!("ip_address {} of validator_network is wrong: {}", ip_address, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parts of interrupted archive and persistent state downloads kept to resume them
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct TransferCacheConfig {
+    pub ttl_sec: u64,
+    // Memory for parts of all interrupted downloads, 0 - downloads are not resumed
+    pub memory_limit_bytes: usize,
+}
+
+impl Default for TransferCacheConfig {
+    fn default() -> Self {
+        TransferCacheConfig {
+            ttl_sec: 300,
+            memory_limit_bytes: 1 << 30,
+        }
+    }
+}
+
+/// Deduplication of public overlay broadcasts by payload hash
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct BroadcastDedupConfig {
+    // Memory for hashes of recent payloads, 0 - no deduplication
+    pub memory_limit_bytes: usize,
+}
+
+impl Default for BroadcastDedupConfig {
+    fn default() -> Self {
+        BroadcastDedupConfig {
+            memory_limit_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// Upload and download limits in bytes per second, 0 - no limit
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct BandwidthLimitsConfig {
+    pub upload_bytes_per_sec: u64,
+    pub download_bytes_per_sec: u64,
+    // public (shard) overlays
+    pub public_upload_bytes_per_sec: u64,
+    pub public_download_bytes_per_sec: u64,
+    // private (validator) overlays
+    pub private_upload_bytes_per_sec: u64,
+}
+
+/// zstd compression of large answers to public overlay queries
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct AnswersCompressionConfig {
+    pub enabled: bool,
+    // Smaller answers are sent as is
+    pub threshold_bytes: usize,
+    pub level: i32,
+}
+
+impl Default for AnswersCompressionConfig {
+    fn default() -> Self {
+        AnswersCompressionConfig {
+            enabled: true,
+            threshold_bytes: 4096,
+            level: 3,
+        }
+    }
+}
+
+impl AnswersCompressionConfig {
+    // smaller answers hardly shrink, compression is not worth the CPU
+    const MIN_THRESHOLD_BYTES: usize = 1024;
+
+    pub fn check(&self) -> Result<()> {
+        if self.threshold_bytes < Self::MIN_THRESHOLD_BYTES {
+            fail!("threshold_bytes can't be less than {}", Self::MIN_THRESHOLD_BYTES);
+        }
+        if !(1..=19).contains(&self.level) {
+            fail!("level must be in range 1..=19");
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default, serde::Deserialize, serde::Serialize, Clone)]
 #[serde(default)]
 pub struct CollatorTestBundlesConfig {
@@ -488,6 +1226,17 @@ impl TonNodeConfig {
         // }
 
         config_json.connectivity_check_config.check()?;
+        config_json.ext_messages_broadcast.check()?;
+        config_json.catchain_recovery.check()?;
+        config_json.catchain_relay.check()?;
+        config_json.channels_keepalive.check()?;
+        config_json.validator_network.check()?;
+        config_json.answers_compression.check()?;
+        config_json.collator_config.check()?;
+        config_json.remp.check()?;
+        for (workchain_id, overrides) in config_json.workchain_overrides.iter() {
+            overrides.check().map_err(|e| error!("workchain_overrides of {}: {}", workchain_id, e))?;
+        }
 
         config_json.configs_dir = configs_dir.to_string();
         config_json.file_name = json_file_name.to_string();
@@ -541,6 +1290,14 @@ impl TonNodeConfig {
         self.validation_countdown_mode.clone()
     }
 
+    pub fn session_prestart_sec(&self) -> u32 {
+        self.session_prestart_sec.unwrap_or(0)
+    }
+
+    pub fn consensus_catchain_db_path(&self) -> Option<String> {
+        self.consensus_catchain_db_path.clone()
+    }
+
     pub fn gc_archives_life_time_hours(&self) -> Option<u32> {
         match &self.gc {
             Some(gc) => {
@@ -601,6 +1358,33 @@ impl TonNodeConfig {
     pub fn remp_config(&self) -> &RempConfig {
         &self.remp
     }
+    pub fn ext_messages_broadcast_config(&self) -> &ExtMessagesBroadcastConfig {
+        &self.ext_messages_broadcast
+    }
+    pub fn catchain_recovery_config(&self) -> &CatchainRecoveryConfig {
+        &self.catchain_recovery
+    }
+    pub fn catchain_relay_config(&self) -> &CatchainRelayConfig {
+        &self.catchain_relay
+    }
+    pub fn channels_keepalive_config(&self) -> &ChannelsKeepaliveConfig {
+        &self.channels_keepalive
+    }
+    pub fn validator_network_config(&self) -> &ValidatorNetworkConfig {
+        &self.validator_network
+    }
+    pub fn transfer_cache_config(&self) -> &TransferCacheConfig {
+        &self.transfer_cache
+    }
+    pub fn broadcast_dedup_config(&self) -> &BroadcastDedupConfig {
+        &self.broadcast_dedup
+    }
+    pub fn bandwidth_limits_config(&self) -> &BandwidthLimitsConfig {
+        &self.bandwidth_limits
+    }
+    pub fn answers_compression_config(&self) -> &AnswersCompressionConfig {
+        &self.answers_compression
+    }
     pub fn restore_db(&self) -> bool {
         self.restore_db
     }
@@ -628,6 +1412,9 @@ impl TonNodeConfig {
     pub fn collator_config(&self) -> &CollatorConfig {
         &self.collator_config
     }
+    pub fn workchain_overrides(&self) -> &HashMap<i32, WorkchainOverrides> {
+        &self.workchain_overrides
+    }
  
     pub fn load_global_config(&self) -> Result<TonNodeGlobalConfig> {
         let name = self.ton_global_config_name.as_ref().ok_or_else(
@@ -1601,7 +2388,12 @@ pub struct ValidatorManagerConfig {
     pub unsafe_resync_catchains: HashSet<u32>,
     /// Maps catchain_seqno to block_seqno and unsafe rotation id
     pub unsafe_catchain_rotates: HashMap<u32, (u32, u32)>,
-    pub no_countdown_for_zerostate: bool
+    pub no_countdown_for_zerostate: bool,
+    /// Seconds before activation the next sessions are pre-started at (0 -- not pre-started)
+    pub session_prestart_sec: u32,
+    pub catchain_recovery: CatchainRecoveryConfig,
+    /// Directory of validator sessions' catchain DBs (`catchains` in DB root if not set)
+    pub consensus_catchain_db_path: Option<String>
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -1619,9 +2411,11 @@ struct ValidatorManagerConfigImpl {
 
 impl Display for ValidatorManagerConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "validation countdown mode: {}; update interval: {} ms; resync: [{}]; rotates: [{}]",
+        write!(f, "validation countdown mode: {}; update interval: {} ms; session prestart: {} sec; \
+            resync: [{}]; rotates: [{}]",
             if self.no_countdown_for_zerostate { "except-zerostate" } else { "always" },
             self.update_interval.as_millis(),
+            self.session_prestart_sec,
             self.unsafe_resync_catchains.iter().map(|n| format!("{} ", n)).collect::<String>(),
             self.unsafe_catchain_rotates.iter().map(
                 |(cc, (blk, uid))| format!("({},{})=>{} ",cc,blk,uid)
@@ -1677,6 +2471,13 @@ impl ValidatorManagerConfig {
         validator_config
     }
 
+    pub fn get_consensus_catchain_db_path(&self, db_root: &str) -> String {
+        match &self.consensus_catchain_db_path {
+            Some(path) => path.clone(),
+            None => format!("{}/catchains", db_root)
+        }
+    }
+
     pub fn check_unsafe_catchain_rotation(&self, block_seqno_opt: Option<u32>, catchain_seqno: u32) -> Option<u32> {
         if let Some(blk) = block_seqno_opt {
             match self.unsafe_catchain_rotates.get(&catchain_seqno) {
@@ -1696,7 +2497,10 @@ impl Default for ValidatorManagerConfig {
             update_interval: Duration::from_secs(3),
             unsafe_resync_catchains: HashSet::new(),
             unsafe_catchain_rotates: HashMap::new(),
-            no_countdown_for_zerostate: false
+            no_countdown_for_zerostate: false,
+            session_prestart_sec: 0,
+            catchain_recovery: CatchainRecoveryConfig::default(),
+            consensus_catchain_db_path: None
         }
     }
 }
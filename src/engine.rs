@@ -14,10 +14,14 @@
 use crate::{
     block::{BlockStuff, BlockIdExtExtention}, block_proof::BlockProofStuff, boot,
     engine_traits::{
-        ExternalDb, EngineAlloc, EngineOperations,
-        OverlayOperations, PrivateOverlayOperations, Server,
+        ExternalDb, EngineAlloc, EngineOperations, GcDryRunReport,
+        OverlayOperations, PrivateOverlayOperations, RempDuplicateStatus, Server,
+    },
+    error::NodeError,
+    ext_messages::{
+        BroadcastRateLimiter, MessagesPool, EXT_MESSAGES_TRACE_TARGET, RempMessagesPool,
+        create_ext_message_with_limit
     },
-    ext_messages::{MessagesPool, EXT_MESSAGES_TRACE_TARGET, RempMessagesPool},
     full_node::{
         apply_block::{self, apply_block},
         shard_client::{
@@ -45,7 +49,12 @@ use crate::{
     types::awaiters_pool::AwaitersPool,
     validator::{
         candidate_db::{CandidateDb, CandidateDbPool},
+        collator_cache::CollatorWorkCache,
+        consensus_stats::ConsensusReport,
+        out_msg_queue::OutQueueCheckpoint,
         remp_service::RempService,
+        validation_hooks::{default_validation_hooks, ValidationHooks},
+        validation_pool::ValidationPool,
         validator_manager::{start_validator_manager, ValidationStatus},
     }
 };
@@ -68,6 +77,7 @@ use crate::{
 #[cfg(feature = "telemetry")]
 use adnl::telemetry::{Metric, MetricBuilder, TelemetryItem, TelemetryPrinter};
 use catchain::SessionId;
+use validator_session::{LatencyStat, ValidatorBlockCandidate};
 use overlay::QueriesConsumer;
 use std::{
     ops::Deref, sync::{Arc, atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering, AtomicU64}},
@@ -94,8 +104,8 @@ use ton_types::HashmapType;
 #[cfg(feature = "telemetry")]
 use ton_types::Cell;
 use crate::config::{
-    CollatorConfig, CollatorTestBundlesGeneralConfig, KafkaConsumerConfig,
-    TonNodeConfig, ValidatorManagerConfig
+    CollatorConfig, CollatorTestBundlesGeneralConfig, ExtMessagesBroadcastConfig, 
+    KafkaConsumerConfig, TonNodeConfig, ValidatorManagerConfig, WorkchainOverrides
 };
 
 #[cfg(feature = "slashing")]
@@ -123,6 +133,9 @@ pub struct Engine {
     remp_client: Option<Arc<RempClient>>,
     remp_service: Option<Arc<RempService>>,
     remp_messages: Option<Arc<RempMessagesPool>>,
+    remp_max_message_size: usize,
+    ext_messages_broadcast_config: ExtMessagesBroadcastConfig,
+    ext_messages_broadcast_limiter: BroadcastRateLimiter,
 
     zero_state_id: BlockIdExt,
     init_mc_block_id: BlockIdExt,
@@ -141,14 +154,21 @@ pub struct Engine {
 
     test_bundles_config: CollatorTestBundlesGeneralConfig,
     collator_config: CollatorConfig,
+    workchain_overrides: HashMap<i32, WorkchainOverrides>,
+    validation_pool: Option<Arc<ValidationPool>>,
+    collator_work_cache: Option<Arc<CollatorWorkCache>>,
+    validation_hooks: Option<Arc<dyn ValidationHooks>>,
  
     shard_states_keeper: Arc<ShardStatesKeeper>,
     processed_workchain: Option<i32>,
 
     // None - queue calculating is in progress
     split_queues_cache: lockfree::map::Map<BlockIdExt, Option<(OutMsgQueue, OutMsgQueue, HashSet<UInt256>)>>,
+    out_queue_checkpoints: lockfree::map::Map<ShardIdent, OutQueueCheckpoint>,
     validation_status: Arc<AtomicU8>,
     last_validation_time: lockfree::map::Map<ShardIdent, u64>,
+    session_latency_stats: lockfree::map::Map<ShardIdent, LatencyStat>,
+    session_consensus_reports: lockfree::map::Map<ShardIdent, ConsensusReport>,
     last_collation_time: lockfree::map::Map<ShardIdent, u64>,
     #[cfg(feature = "slashing")]
     validated_block_stats_sender: crossbeam_channel::Sender<ValidatedBlockStat>,
@@ -599,6 +619,8 @@ impl Engine {
 
         let archives_life_time = general_config.gc_archives_life_time_hours();
         let remp_config = general_config.remp_config().clone();
+        let remp_max_message_size = remp_config.get_max_message_size();
+        let ext_messages_broadcast_config = general_config.ext_messages_broadcast_config().clone();
         let cells_lifetime_sec = general_config.cells_gc_config().cells_lifetime_sec;
         let enable_shard_state_persistent_gc = general_config.enable_shard_state_persistent_gc();
         let skip_saving_persistent_states = general_config.skip_saving_persistent_states();
@@ -615,11 +637,26 @@ impl Engine {
         };
         let control_config = general_config.control_server()?;
         let collator_config = general_config.collator_config().clone();
+        let workchain_overrides = general_config.workchain_overrides().clone();
+        for (workchain_id, overrides) in workchain_overrides.iter() {
+            log::warn!("Fees and limits of workchain {} are overridden: {:?}", workchain_id, overrides);
+        }
         let low_memory_mode = general_config.low_memory_mode();
         let boot_from_zerostate = general_config.boot_from_zerostate();
         let global_config = general_config.load_global_config()?;
         let test_bundles_config = general_config.test_bundles_config().clone();
         let external_messages_maximum_queue_length = collator_config.external_messages_maximum_queue_length;
+        let validation_pool = if collator_config.validation_threads > 0 {
+            log::info!("Creating validation pool with {} threads", collator_config.validation_threads);
+            Some(Arc::new(ValidationPool::new(collator_config.validation_threads)?))
+        } else {
+            None
+        };
+        let collator_work_cache = if collator_config.work_cache {
+            Some(Arc::new(CollatorWorkCache::new()))
+        } else {
+            None
+        };
 
         let network = NodeNetwork::new(
             general_config,
@@ -733,7 +770,12 @@ impl Engine {
         )?;
 
         let remp_client = if remp_config.is_client_enabled() {
-            let remp_client = Arc::new(RempClient::new(network.public_overlay_key()?.id().data().into()));
+            let mut remp_client = RempClient::new(network.public_overlay_key()?.id().data().into());
+            if remp_config.is_status_observer() {
+                remp_client = remp_client.with_status_observer();
+            }
+            remp_client = remp_client.with_queue_max_len(remp_config.get_client_queue_max_len());
+            let remp_client = Arc::new(remp_client);
             network.remp().set_receipts_subscriber(remp_client.clone())?;
             Some(remp_client)
         } else {
@@ -745,7 +787,10 @@ impl Engine {
             let remp_service = Arc::new(RempService::new());
             network.remp().set_messages_subscriber(remp_service.clone())?;
             #[cfg(feature = "telemetry")]
-            network.remp().set_telemetry(remp_core_telemetry.clone())?;
+            if let Err(e) = network.remp().set_telemetry(remp_core_telemetry.clone()) {
+                remp_core_telemetry.set_backend_failure(format!("cannot set REMP network telemetry: {}", e));
+            }
+            network.remp().set_sign_receipts(remp_config.is_sign_receipts());
             network.remp().start()?;
             (Some(remp_service), Some(Arc::new(RempMessagesPool::new())))
         } else {
@@ -799,6 +844,12 @@ impl Engine {
             remp_client,
             remp_service,
             remp_messages,
+            remp_max_message_size,
+            ext_messages_broadcast_limiter: BroadcastRateLimiter::new(
+                ext_messages_broadcast_config.max_messages_per_source,
+                ext_messages_broadcast_config.rate_limit_period_ms
+            ),
+            ext_messages_broadcast_config,
             stopper,
             zero_state_id,
             init_mc_block_id,
@@ -815,11 +866,18 @@ impl Engine {
             remp_capability: AtomicBool::new(false),
             test_bundles_config,
             collator_config,
+            workchain_overrides,
+            validation_pool,
+            collator_work_cache,
+            validation_hooks: default_validation_hooks(),
             shard_states_keeper: shard_states_keeper.clone(),
             processed_workchain,
             split_queues_cache: lockfree::map::Map::new(),
+            out_queue_checkpoints: lockfree::map::Map::new(),
             validation_status: Arc::new(AtomicU8::new(0)),
             last_validation_time: lockfree::map::Map::new(),
+            session_latency_stats: lockfree::map::Map::new(),
+            session_consensus_reports: lockfree::map::Map::new(),
             last_collation_time: lockfree::map::Map::new(),
             #[cfg(feature = "slashing")]
             validated_block_stats_sender,
@@ -1015,6 +1073,22 @@ impl Engine {
         &self.collator_config
     }
 
+    pub fn workchain_overrides(&self, workchain_id: i32) -> Option<&WorkchainOverrides> {
+        self.workchain_overrides.get(&workchain_id)
+    }
+
+    pub fn validation_pool(&self) -> Option<Arc<ValidationPool>> {
+        self.validation_pool.clone()
+    }
+
+    pub fn collator_work_cache(&self) -> Option<Arc<CollatorWorkCache>> {
+        self.collator_work_cache.clone()
+    }
+
+    pub fn validation_hooks(&self) -> Option<Arc<dyn ValidationHooks>> {
+        self.validation_hooks.clone()
+    }
+
     #[cfg(feature = "telemetry")]
     pub fn full_node_telemetry(&self) -> &FullNodeTelemetry {
         &self.full_node_telemetry
@@ -1074,6 +1148,30 @@ impl Engine {
         self.last_validation_time.remove(shard);
     }
 
+    pub fn session_latency_stats(&self) -> &lockfree::map::Map<ShardIdent, LatencyStat> {
+        &self.session_latency_stats
+    }
+
+    pub fn set_session_latency_stat(&self, shard: ShardIdent, stat: LatencyStat) {
+        self.session_latency_stats.insert(shard, stat);
+    }
+
+    pub fn remove_session_latency_stat(&self, shard: &ShardIdent) {
+        self.session_latency_stats.remove(shard);
+    }
+
+    pub fn session_consensus_reports(&self) -> &lockfree::map::Map<ShardIdent, ConsensusReport> {
+        &self.session_consensus_reports
+    }
+
+    pub fn set_session_consensus_report(&self, shard: ShardIdent, report: ConsensusReport) {
+        self.session_consensus_reports.insert(shard, report);
+    }
+
+    pub fn remove_session_consensus_report(&self, shard: &ShardIdent) {
+        self.session_consensus_reports.remove(shard);
+    }
+
     pub fn last_collation_time(&self) -> &lockfree::map::Map<ShardIdent, u64> {
         &self.last_collation_time
     }
@@ -1111,6 +1209,10 @@ impl Engine {
         self.remp_capability.load(Ordering::Relaxed)
     }
 
+    pub fn remp_max_message_size(&self) -> usize {
+        self.remp_max_message_size
+    }
+
     pub fn set_remp_capability(&self, value: bool) {
         self.remp_capability.store(value, Ordering::Relaxed);
     }
@@ -1119,6 +1221,10 @@ impl Engine {
         self.candidate_db.get_db(session_id)
     }
 
+    pub fn find_block_candidate(&self, root_hash: &UInt256) -> Option<Arc<ValidatorBlockCandidate>> {
+        self.candidate_db.find(root_hash)
+    }
+
     pub fn destroy_candidate_table(&self, session_id: &SessionId) -> Result<bool> {
         self.candidate_db.destroy_db(session_id)
     }
@@ -1129,6 +1235,10 @@ impl Engine {
         &self.split_queues_cache
     }
 
+    pub fn out_queue_checkpoints(&self) -> &lockfree::map::Map<ShardIdent, OutQueueCheckpoint> {
+        &self.out_queue_checkpoints
+    }
+
     pub fn processed_workchain(&self) -> Option<i32> {
         self.processed_workchain
     }
@@ -1459,6 +1569,17 @@ impl Engine {
         }
         log::debug!("Split queues cache GC: total {total}, removed {removed}");
 
+        // checkpoints of shards which are not collated anymore refer to old neighbors
+        for guard in &self.out_queue_checkpoints {
+            let mut outdated = false;
+            for id in guard.val().neighbors.iter() {
+                outdated |= self.shard_states_keeper.allow_state_gc(id)?;
+            }
+            if outdated {
+                self.out_queue_checkpoints.remove(guard.key());
+            }
+        }
+
         Ok(())
     }
 
@@ -1545,7 +1666,7 @@ impl Engine {
                     Ok(Some((broadcast, src))) => {
                         match broadcast {
                             Broadcast::TonNode_BlockBroadcast(broadcast) => {
-                                self.clone().process_block_broadcast(broadcast, src);
+                                self.clone().process_block_broadcast(broadcast, src, client.clone());
                             }
                             Broadcast::TonNode_QueueUpdateBroadcast(broadcast) => {
                                 self.clone().process_queue_update_broadcast(broadcast, src);
@@ -1557,7 +1678,7 @@ impl Engine {
                                 log::trace!("TonNode_IhrMessageBroadcast from {}: {:?}", src, broadcast);
                             }
                             Broadcast::TonNode_NewShardBlockBroadcast(broadcast) => {
-                                self.clone().process_new_shard_block_broadcast(broadcast, src);
+                                self.clone().process_new_shard_block_broadcast(broadcast, src, client.clone());
                             }
                             Broadcast::TonNode_ConnectivityCheckBroadcast(broadcast) => {
                                 self.network.clone().process_connectivity_broadcast(broadcast);
@@ -1710,7 +1831,12 @@ impl Engine {
         Ok(())
     }
 
-    fn process_block_broadcast(self: Arc<Self>, broadcast: BlockBroadcast, src: Arc<KeyId>) {
+    fn process_block_broadcast(
+        self: Arc<Self>,
+        broadcast: BlockBroadcast,
+        src: Arc<KeyId>,
+        client: Arc<dyn FullNodeOverlayClient>
+    ) {
         // because of ALL blocks-broadcasts received in one task - spawn for each block
         log::trace!("Processing block broadcast {}", broadcast.id);
         let engine = self.clone() as Arc<dyn EngineOperations>;
@@ -1723,10 +1849,14 @@ impl Engine {
             }
             match process_block_broadcast(&engine, &broadcast).await {
                 Err(e) => {
-                    log::error!("Error while processing block broadcast {} from {}: {:?}", broadcast.id, src, e)
+                    log::error!("Error while processing block broadcast {} from {}: {:?}", broadcast.id, src, e);
+                    if NodeError::is_protocol_violation(&e) {
+                        client.broadcast_checked(&src, false);
+                    }
                 }
                 Ok(_block_opt) => {
                     log::trace!("Processed block broadcast {} from {}", broadcast.id, src);
+                    client.broadcast_checked(&src, true);
 
                     #[cfg(feature = "slashing")]
                     if broadcast.id.shard().is_masterchain() {
@@ -1780,17 +1910,40 @@ impl Engine {
 
     async fn process_ext_msg_broadcast(&self, broadcast: ExternalMessageBroadcast, src: Arc<KeyId>) {
         let remp = self.remp_capability();
+        let bytes_len = broadcast.message.data.0.len();
         // just add to list
-        if !self.is_validator() {
+        if !self.ext_messages_broadcast_config.enabled {
+            log::trace!(
+                target: EXT_MESSAGES_TRACE_TARGET,
+                "Skipped ext message broadcast {}bytes from {}: DISABLED BY CONFIG",
+                bytes_len, src
+            );
+        } else if !self.is_validator() {
             log::trace!(
                 target: EXT_MESSAGES_TRACE_TARGET,
                 "Skipped ext message broadcast {}bytes from {}: NOT A VALIDATOR",
-                broadcast.message.data.0.len(), src
+                bytes_len, src
+            );
+        } else if !self.ext_messages_broadcast_limiter.check(&src, now_duration().as_millis() as u64) {
+            log::debug!(
+                target: EXT_MESSAGES_TRACE_TARGET,
+                "Skipped ext message broadcast {}bytes from {}: RATE LIMIT EXCEEDED",
+                bytes_len, src
             );
         } else {
-            let bytes_len = broadcast.message.data.0.len();
             let result = if remp {
-                self.push_message_to_remp(broadcast.message.data).await
+                match self.check_ext_msg_broadcast_duplicate(&broadcast.message.data.0).await {
+                    Ok(true) => {
+                        log::trace!(
+                            target: EXT_MESSAGES_TRACE_TARGET,
+                            "Skipped ext message broadcast {}bytes from {}: ALREADY IN MESSAGE CACHE",
+                            bytes_len, src
+                        );
+                        return
+                    }
+                    Ok(false) => self.push_message_to_remp(broadcast.message.data).await,
+                    Err(e) => Err(e)
+                }
             } else {
                 self.external_messages().new_message_raw(&broadcast.message.data.0, self.now())
             };
@@ -1813,7 +1966,21 @@ impl Engine {
         }
     }
 
-    fn process_new_shard_block_broadcast(self: Arc<Self>, broadcast: NewShardBlockBroadcast, src: Arc<KeyId>) {
+    // Returns true if the message is already known to REMP message cache
+    async fn check_ext_msg_broadcast_duplicate(&self, data: &[u8]) -> Result<bool> {
+        let (id, _message) = create_ext_message_with_limit(data, self.remp_max_message_size)?;
+        match self.check_remp_duplicate(&id).await? {
+            RempDuplicateStatus::Absent => Ok(false),
+            _ => Ok(true)
+        }
+    }
+
+    fn process_new_shard_block_broadcast(
+        self: Arc<Self>,
+        broadcast: NewShardBlockBroadcast,
+        src: Arc<KeyId>,
+        client: Arc<dyn FullNodeOverlayClient>
+    ) {
         let id = broadcast.block.block.clone();
         if self.is_validator() {
             log::trace!("Processing new shard block broadcast {} from {}", id, src);
@@ -1822,11 +1989,15 @@ impl Engine {
                     match self.clone().process_new_shard_block(broadcast).await {
                         Err(e) => {
                             log::error!("Error while processing new shard block broadcast {} from {}: {}", id, src, e);
+                            if NodeError::is_protocol_violation(&e) {
+                                client.broadcast_checked(&src, false);
+                            }
                             #[cfg(feature = "telemetry")]
                             self.full_node_telemetry().bad_top_block_broadcast();
                         }
                         Ok(id) => {
                             log::trace!("Processed new shard block broadcast {} from {}", id, src);
+                            client.broadcast_checked(&src, true);
                             #[cfg(feature = "telemetry")]
                             self.full_node_telemetry().good_top_block_broadcast(&id);
                         }
@@ -2190,9 +2361,48 @@ impl Engine {
         last_keyblock: &Arc<BlockHandle>,
         mc_state: &ShardStateStuff
     ) -> Result<()> {
+        if let Some(keyblock) = Self::find_archives_gc_block(engine, last_keyblock, mc_state)? {
+            log::info!("start gc for archives..");
+            engine.db.archive_gc(&keyblock).await?;
+            log::info!("finish gc for archives.");
+        }
+        Ok(())
+    }
+
+    pub async fn gc_dry_run_report(&self) -> Result<GcDryRunReport> {
+        let mut report = GcDryRunReport::default();
+        let mc_block_id = self.load_last_applied_mc_block_id()?
+            .ok_or_else(|| error!("Cannot load last applied mc block id"))?;
+        let mc_state = self.load_state(&mc_block_id).await?;
+        if let Some(last_key_block) = &mc_state.shard_state_extra()?.last_key_block {
+            let last_key_block = BlockIdExt::from_ext_blk(last_key_block.clone());
+            let last_key_block = self.load_block_handle(&last_key_block)?.ok_or_else(
+                || error!("Cannot load handle for last key block {}", last_key_block)
+            )?;
+            report.archives_gc_block = Self::find_archives_gc_block(self, &last_key_block, &mc_state)?;
+            if let Some(keyblock) = &report.archives_gc_block {
+                report.archives = self.db.archive_gc_report(keyblock).await?;
+            }
+        }
+        let calc_ttl = |t| {
+            let ttl = self.persistent_state_ttl(t, boot::PSS_PERIOD_BITS);
+            (ttl, ttl <= self.now())
+        };
+        report.persistent_states = self.db.shard_state_persistent_gc_report(
+            calc_ttl, self.zero_state_id()
+        ).await?;
+        Ok(report)
+    }
+
+    // Finds the last key block archives before which may be deleted
+    fn find_archives_gc_block(
+        engine: &Engine,
+        last_keyblock: &Arc<BlockHandle>,
+        mc_state: &ShardStateStuff
+    ) -> Result<Option<BlockIdExt>> {
         let mut gc_max_date = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
         match &engine.archives_life_time {
-            None => return Ok(()),
+            None => return Ok(None),
             Some(life_time) => {
                 match gc_max_date.checked_sub(Duration::from_secs((life_time * 3600) as u64)) {
                     Some(date) => {
@@ -2213,7 +2423,7 @@ impl Engine {
         let prev_blocks = &mc_state.shard_state_extra()?.prev_blocks;
         loop {
             match prev_blocks.get_prev_key_block(keyblock.id().seq_no() - 1)? {
-                None => return Ok(()),
+                None => return Ok(None),
                 Some(prev_keyblock) => {
                     let prev_keyblock = BlockIdExt::from_ext_blk(prev_keyblock);
                     let prev_keyblock = engine.load_block_handle(&prev_keyblock)?.ok_or_else(
@@ -2241,16 +2451,13 @@ impl Engine {
                                         "gc for archives: found block (gen time: {}, seq_no: {}), gc max date: {}",
                                         &gen_time, keyblock.id().seq_no(), &gc_max_date
                                     );
-                                    log::info!("start gc for archives..");
-                                    engine.db.archive_gc(keyblock.id()).await?;
-                                    log::info!("finish gc for archives.");
-                                    return Ok(());
+                                    return Ok(Some(keyblock.id().clone()));
                                 }
                             }
                         }
                     }
                     if prev_keyblock.id().seq_no() == 0 {
-                        return Ok(());
+                        return Ok(None);
                     }
                     keyblock = prev_keyblock;
                 }
@@ -2497,10 +2704,13 @@ pub async fn run(
     let consumer_config = node_config.kafka_consumer_config();
     let control_server_config = node_config.control_server()?;
     let remp_config = node_config.remp_config().clone();
-    let vm_config = ValidatorManagerConfig::read_configs(
+    let mut vm_config = ValidatorManagerConfig::read_configs(
         node_config.unsafe_catchain_patches_files(),
         node_config.validation_countdown_mode()
     );
+    vm_config.session_prestart_sec = node_config.session_prestart_sec();
+    vm_config.catchain_recovery = node_config.catchain_recovery_config().clone();
+    vm_config.consensus_catchain_db_path = node_config.consensus_catchain_db_path();
     let wc_from_config = node_config.workchain();
     let remp_client_pool = node_config.remp_config().remp_client_pool();
     let configs_dir = node_config.build_config_path("");
@@ -2513,6 +2723,8 @@ pub async fn run(
         #[cfg(feature = "telemetry")]
         telemetry_logger(engine.clone());
 
+        start_sync_statistics_reporter(engine.clone());
+
         // Console service - run first to allow console to connect to generate new keys
         // while node is looking for net
         if let Some(config) = control_server_config {
@@ -2532,7 +2744,8 @@ pub async fn run(
         start_external_broadcast_process(engine.clone(), &consumer_config)?;
 
         let full_node_service = FullNodeOverlayService::new(
-            Arc::clone(&engine) as Arc<dyn EngineOperations>
+            Arc::clone(&engine) as Arc<dyn EngineOperations>,
+            engine.network()
         );
         let full_node_service: Arc<dyn QueriesConsumer> = Arc::new(full_node_service);
 
@@ -2677,6 +2890,21 @@ pub async fn run(
     }
 }
 
+// Disk fsync and WAL sync latencies from RocksDB statistics,
+// exported as metrics "db_fsync_time" and "db_wal_sync_time"
+fn start_sync_statistics_reporter(engine: Arc<Engine>) {
+    const SYNC_STATISTICS_INTERVAL_MS: u64 = 10_000;
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(SYNC_STATISTICS_INTERVAL_MS)).await;
+            if engine.check_stop() {
+                break;
+            }
+            engine.db().report_sync_statistics();
+        }
+    });
+}
+
 #[cfg(feature = "telemetry")]
 fn telemetry_logger(engine: Arc<Engine>) {
     tokio::spawn(async move {
@@ -2756,6 +2984,15 @@ fn telemetry_logger(engine: Arc<Engine>) {
                 "Validator's telemetry:\n{}",
                 engine.validator_telemetry().report()
             );
+            if engine.session_consensus_reports().iter().next().is_some() {
+                let mut report = String::new();
+                for item in engine.session_consensus_reports().iter() {
+                    report.push_str(&format!("shard {}, rounds {} (skipped {}), {}\n",
+                        item.key(), item.val().stats.rounds, item.val().stats.skipped, item.val().session
+                    ));
+                }
+                log::debug!(target: "telemetry", "Validator sessions' telemetry:\n{}", report);
+            }
             log::debug!(
                 target: "telemetry",
                 "Full node service's telemetry:\n{}",
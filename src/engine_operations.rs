@@ -13,9 +13,9 @@
 
 use crate::{
     block::BlockStuff, block_proof::BlockProofStuff, 
-    config::{CollatorTestBundlesGeneralConfig, CollatorConfig}, engine::{Engine, EngineFlags},
+    config::{CollatorTestBundlesGeneralConfig, CollatorConfig, WorkchainOverrides}, engine::{Engine, EngineFlags},
     engine_traits::{
-        ChainRange, EngineAlloc, EngineOperations, PrivateOverlayOperations, Server,
+        ChainRange, EngineAlloc, EngineOperations, GcDryRunReport, PrivateOverlayOperations, Server,
         RempCoreInterface, RempDuplicateStatus
     },
     error::NodeError,
@@ -25,9 +25,19 @@ use crate::{
     },
     shard_state::ShardStateStuff,
     types::top_block_descr::{TopBlockDescrStuff, TopBlockDescrId},
-    ext_messages::{create_ext_message, EXT_MESSAGES_TRACE_TARGET},
+    ext_messages::{
+        check_ext_message_size, create_ext_message, create_ext_message_with_limit,
+        DeferredRempMessage, EXT_MESSAGES_TRACE_TARGET
+    },
     jaeger,
+    network::{remp::{RempDeferredNotice, RempStatusQuery}, send_queue::QosClass, transfer_cache::TransferProgress},
     validator::{
+        collator_cache::CollatorWorkCache,
+        consensus_stats::ConsensusReport,
+        message_cache::{RempMessageStatusFilter, RempStatusTransition},
+        out_msg_queue::OutQueueCheckpoint,
+        validation_hooks::ValidationHooks,
+        validation_pool::ValidationPool,
         validator_manager::ValidationStatus,
         validator_utils::validatordescr_to_catchain_node,
     }, shard_states_keeper::PinnedShardStateGuard
@@ -63,7 +73,7 @@ use ton_block::{
 #[cfg(feature="workchains")]
 use ton_block::{BASE_WORKCHAIN_ID, INVALID_WORKCHAIN_ID};
 use ton_types::{error, fail, KeyId, KeyOption, Result, UInt256};
-use validator_session::{BlockHash, SessionId, ValidatorBlockCandidate};
+use validator_session::{BlockHash, LatencyStat, SessionId, ValidatorBlockCandidate};
 
 #[async_trait::async_trait]
 impl EngineOperations for Engine {
@@ -138,6 +148,30 @@ impl EngineOperations for Engine {
         self.remove_last_validation_time(shard)
     }
 
+    fn session_latency_stats(&self) -> &lockfree::map::Map<ShardIdent, LatencyStat> {
+        self.session_latency_stats()
+    }
+
+    fn set_session_latency_stat(&self, shard: ShardIdent, stat: LatencyStat) {
+        self.set_session_latency_stat(shard, stat)
+    }
+
+    fn remove_session_latency_stat(&self, shard: &ShardIdent) {
+        self.remove_session_latency_stat(shard)
+    }
+
+    fn session_consensus_reports(&self) -> &lockfree::map::Map<ShardIdent, ConsensusReport> {
+        self.session_consensus_reports()
+    }
+
+    fn set_session_consensus_report(&self, shard: ShardIdent, report: ConsensusReport) {
+        self.set_session_consensus_report(shard, report)
+    }
+
+    fn remove_session_consensus_report(&self, shard: &ShardIdent) {
+        self.remove_session_consensus_report(shard)
+    }
+
     fn last_collation_time(&self) -> &lockfree::map::Map<ShardIdent, u64> {
         self.last_collation_time()
     }
@@ -160,14 +194,16 @@ impl EngineOperations for Engine {
         overlay_short_id : &Arc<PrivateOverlayShortId>,
         nodes_public_keys : &Vec<CatchainNode>,
         listener : CatchainOverlayListenerPtr,
-        _log_replay_listener: CatchainOverlayLogReplayListenerPtr
+        _log_replay_listener: CatchainOverlayLogReplayListenerPtr,
+        qos: QosClass,
     ) -> Result<Arc<dyn CatchainOverlay + Send>> {
         self.validator_network().create_catchain_client(
             validator_list_id,
             overlay_short_id,
             nodes_public_keys,
             listener,
-            _log_replay_listener
+            _log_replay_listener,
+            qos,
         )
     }
 
@@ -335,6 +371,10 @@ impl EngineOperations for Engine {
         self.destroy_candidate_table(session_id)
     }
 
+    fn find_block_candidate(&self, root_hash: &BlockHash) -> Option<Arc<ValidatorBlockCandidate>> {
+        Engine::find_block_candidate(self, root_hash)
+    }
+
     async fn apply_block_internal(
         self: Arc<Self>, 
         handle: &Arc<BlockHandle>, 
@@ -433,20 +473,24 @@ impl EngineOperations for Engine {
 
         let overlay = self.get_full_node_overlay(overlay_wc, SHARD_FULL).await?;
 
-        let data = crate::full_node::state_helper::download_persistent_state(
+        let progress = TransferProgress::default();
+        let download = crate::full_node::state_helper::download_persistent_state(
             handle.id(),
             queue_for_wc,
             master_id,
             overlay.deref(),
             active_peers,
             attempts,
+            &progress,
             &|| {
                 if self.check_stop() {
                     fail!("Persistent state downloading was stopped")
                 }
                 Ok(())
             }
-        ).await?;
+        );
+        let descr = format!("persistent state {}", handle.id());
+        let data = progress.report(&descr, download).await?;
 
         let state = self.shard_states_keeper().check_and_store_state(
             handle, root_hash, data, self.low_memory_mode()).await?;
@@ -705,6 +749,16 @@ impl EngineOperations for Engine {
         Ok(())
     }
 
+    async fn process_remp_status_transition_in_ext_db(
+        &self,
+        transition: &RempStatusTransition
+    ) -> Result<()> {
+        for db in self.ext_db() {
+            db.process_remp_status_transition(transition).await?;
+        }
+        Ok(())
+    }
+
     async fn process_full_state_in_ext_db(&self, state: &Arc<ShardStateStuff>)-> Result<()> {
         for db in self.ext_db() {
             db.process_full_state(state).await?;
@@ -731,6 +785,18 @@ impl EngineOperations for Engine {
         Ok(())
     }
 
+    async fn process_remp_msg_sub_status_in_ext_db(
+        &self,
+        id: &UInt256,
+        source: &Arc<KeyId>,
+        sub_status: &str,
+    ) -> Result<()> {
+        for db in self.ext_db() {
+            db.process_remp_msg_sub_status(id, source, sub_status).await?;
+        }
+        Ok(())
+    }
+
     async fn download_next_key_blocks_ids(
         &self, 
         block_id: &BlockIdExt, 
@@ -792,10 +858,17 @@ impl EngineOperations for Engine {
 
         let remp_way = self.remp_capability();
         if remp_way {
+            check_ext_message_size(message_data.len(), self.remp_max_message_size())?;
+            // the node is a validator too, its message cache knows the messages of its shards
+            if self.remp_service().is_some() {
+                if !matches!(self.check_remp_duplicate(&id).await?, RempDuplicateStatus::Absent) {
+                    fail!("external message {:x} is already known to REMP", id)
+                }
+            }
             self.remp_client()
                 .ok_or_else(|| error!("redirect_external_message: remp client is not set"))?
                 .clone()
-                .process_remp_message(message_data.into(), id.clone());
+                .process_remp_message(message_data.into(), id.clone())?;
             log::debug!(
                 target: EXT_MESSAGES_TRACE_TARGET,
                 "Redirected external message {:x} to REMP",
@@ -836,6 +909,32 @@ impl EngineOperations for Engine {
         }
     }
 
+    async fn send_service_message(&self, message_data: &[u8], id: UInt256) -> Result<()> {
+        let remp_core = match self.remp_service() {
+            Some(remp_service) if self.remp_capability() => remp_service.remp_core_interface().ok(),
+            _ => None
+        };
+        let remp_core = match remp_core {
+            Some(remp_core) => remp_core,
+            None => return self.redirect_external_message(message_data, id).await
+        };
+        if !self.check_sync().await? {
+            fail!("Can't process service message because node is out of sync");
+        }
+        let (real_id, message) = create_ext_message(message_data)?;
+        if real_id != id {
+            fail!("Given service message id {:x} is not equal calculated one {:x}", id, real_id);
+        }
+        self.remp_messages()?.add_service_message(id.clone(), self.now());
+        remp_core.process_service_message(id.clone(), message).await?;
+        log::debug!(
+            target: EXT_MESSAGES_TRACE_TARGET,
+            "Service message {:x} is pushed to REMP",
+            id,
+        );
+        Ok(())
+    }
+
     async fn get_archive_id(&self, mc_seq_no: u32) -> Option<u64> {
         self.db().get_archive_id(mc_seq_no).await
     }
@@ -847,10 +946,11 @@ impl EngineOperations for Engine {
     async fn download_archive(
         &self, 
         masterchain_seqno: u32,
-        active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>
+        active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>,
+        progress: &TransferProgress
     ) -> Result<Option<Vec<u8>>> {
         let client = self.get_masterchain_overlay().await?;
-        client.download_archive(masterchain_seqno, active_peers).await
+        client.download_archive(masterchain_seqno, active_peers, progress).await
     }
 
     async fn send_block_broadcast(&self, broadcast: BlockBroadcast) -> Result<()> {
@@ -982,6 +1082,23 @@ impl EngineOperations for Engine {
     ) -> Result<()> {
         self.remp_messages()?.finalize_messages(block, accepted, rejected, ignored)
     }
+    fn set_remp_deferred_messages(
+        &self,
+        shard: &ShardIdent,
+        deferred: Vec<(UInt256, DeferredRempMessage)>
+    ) -> Result<()> {
+        self.remp_messages()?.set_deferred(shard, deferred);
+        Ok(())
+    }
+    fn get_remp_deferred_message(&self, id: &UInt256) -> Option<DeferredRempMessage> {
+        self.remp_messages().ok()?.get_deferred(id)
+    }
+    fn is_remp_service_message(&self, id: &UInt256) -> bool {
+        self.remp_messages().map_or(false, |pool| pool.is_service_message(id))
+    }
+    fn get_remp_deferred_messages(&self) -> Result<Vec<(UInt256, DeferredRempMessage)>> {
+        Ok(self.remp_messages()?.deferred_iter().collect())
+    }
     fn finalize_remp_messages_as_ignored(&self, block_id: &BlockIdExt)
     -> Result<()> {
         self.remp_messages()?.finalize_remp_messages_as_ignored(block_id)
@@ -997,8 +1114,68 @@ impl EngineOperations for Engine {
             .check_remp_duplicate(message_id)
     }
 
+    fn get_remp_queue_depth(&self, shard: &ShardIdent) -> Result<(usize, usize)> {
+        Ok(self.remp_service()
+            .ok_or_else(|| error!("Can't get REMP queue depth because remp service was not set"))?
+            .remp_core_interface()?
+            .get_queue_depth(shard))
+    }
+
+    fn list_remp_catchain_transcripts(&self) -> Result<Vec<(UInt256, bool, usize)>> {
+        Ok(self.remp_service()
+            .ok_or_else(|| error!("Can't list catchain transcripts because remp service was not set"))?
+            .remp_core_interface()?
+            .list_catchain_transcripts())
+    }
+
+    fn export_remp_catchain_transcript(&self, session_id: &UInt256, offset: usize, limit: usize) -> Result<String> {
+        self.remp_service()
+            .ok_or_else(|| error!("Can't export catchain transcript because remp service was not set"))?
+            .remp_core_interface()?
+            .export_catchain_transcript(session_id, offset, limit)
+    }
+
+    fn export_remp_catchain_propagation(&self) -> Result<String> {
+        self.remp_service()
+            .ok_or_else(|| error!("Can't export catchain propagation because remp service was not set"))?
+            .remp_core_interface()?
+            .export_catchain_propagation()
+    }
+
+    fn export_remp_message_history(&self, message_id: &UInt256) -> Result<String> {
+        self.remp_service()
+            .ok_or_else(|| error!("Can't export message history because remp service was not set"))?
+            .remp_core_interface()?
+            .export_message_history(message_id)
+    }
+
+    fn dump_remp_message_cache(
+        &self,
+        shard: Option<&ShardIdent>,
+        status: RempMessageStatusFilter
+    ) -> Result<String> {
+        self.remp_service()
+            .ok_or_else(|| error!("Can't dump message cache because remp service was not set"))?
+            .remp_core_interface()?
+            .dump_message_cache(shard, status)
+    }
+
+    async fn inspect_remp_catchain_session(&self, queue_id: &UInt256, restart: bool) -> Result<String> {
+        self.remp_service()
+            .ok_or_else(|| error!("Can't inspect catchain session because remp service was not set"))?
+            .remp_core_interface()?
+            .inspect_catchain_session(queue_id, restart).await
+    }
+
+    async fn list_remp_catchain_sessions(&self) -> Result<String> {
+        self.remp_service()
+            .ok_or_else(|| error!("Can't list catchain sessions because remp service was not set"))?
+            .remp_core_interface()?
+            .list_catchain_sessions().await
+    }
+
     async fn push_message_to_remp(&self, data: ton_api::ton::bytes) -> Result<()> {
-        let (id, _message) = create_ext_message(&data.0)?;
+        let (id, _message) = create_ext_message_with_limit(&data.0, self.remp_max_message_size())?;
         let remp_message = ton_api::ton::ton_node::rempmessage::RempMessage {
             message: data,
             id: id.clone(),
@@ -1015,6 +1192,10 @@ impl EngineOperations for Engine {
         Engine::remp_capability(self)
     }
 
+    fn remp_max_message_size(&self) -> usize {
+        Engine::remp_max_message_size(self)
+    }
+
     // Get current list of new shard blocks with respect to last mc block.
     // If given mc_seq_no is not equal to last mc seq_no - function fails.
     async fn get_shard_blocks(
@@ -1049,10 +1230,30 @@ impl EngineOperations for Engine {
         Engine::collator_config(self)
     }
 
+    fn workchain_overrides(&self, workchain_id: i32) -> Option<&WorkchainOverrides> {
+        Engine::workchain_overrides(self, workchain_id)
+    }
+
+    fn validation_pool(&self) -> Option<Arc<ValidationPool>> {
+        Engine::validation_pool(self)
+    }
+
+    fn collator_work_cache(&self) -> Option<Arc<CollatorWorkCache>> {
+        Engine::collator_work_cache(self)
+    }
+
+    fn validation_hooks(&self) -> Option<Arc<dyn ValidationHooks>> {
+        Engine::validation_hooks(self)
+    }
+
     fn db_root_dir(&self) -> Result<&str> {
         self.db().db_root_dir()
     }
 
+    async fn gc_dry_run_report(&self) -> Result<GcDryRunReport> {
+        Engine::gc_dry_run_report(self).await
+    }
+
     fn produce_chain_ranges_enabled(&self) -> bool {
         self.ext_db().iter().any(|ext_db| ext_db.process_chain_range_enabled())
     }
@@ -1061,6 +1262,10 @@ impl EngineOperations for Engine {
         self.ext_db().iter().any(|ext_db| ext_db.process_shard_hashes_enabled())
     }
 
+    fn produce_remp_status_transitions_enabled(&self) -> bool {
+        self.ext_db().iter().any(|ext_db| ext_db.process_remp_status_transitions_enabled())
+    }
+
     #[cfg(feature = "telemetry")]
     fn full_node_telemetry(&self) -> &FullNodeTelemetry {
         Engine::full_node_telemetry(self)
@@ -1138,6 +1343,10 @@ impl EngineOperations for Engine {
         self.network().remp().send_message(to, message)
     }
 
+    fn send_remp_forwarded_message(&self, to: Arc<KeyId>, message: &RempMessage) -> Result<()> {
+        self.network().remp().send_forwarded_message(to, message)
+    }
+
     /*async fn sign_and_send_remp_receipt(&self, to: Arc<KeyId>, receipt: RempReceipt) -> Result<()> {
         let validators: Vec<CatchainNode> = self.load_actual_config_params().await?
             .validator_set()?.list()
@@ -1172,7 +1381,41 @@ impl EngineOperations for Engine {
         if key.id().data() != receipt.source_id().as_slice() {
             fail!("given source_id {} is not correspond to key {}", hex::encode(receipt.source_id().as_slice()), hex::encode(key.id().data()))
         }
-        self.network().remp().combine_and_send_receipt(to, receipt, adnl_id).await
+        self.network().remp().combine_and_send_receipt(to, receipt, adnl_id, key).await
+    }
+
+    async fn send_remp_statuses(&self, to: Arc<KeyId>, statuses: Vec<(UInt256, RempMessageStatus)>) -> Result<()> {
+        let validators: Vec<CatchainNode> = self.load_actual_config_params().await?
+            .validator_set()?.list()
+            .iter().map(|vd| validatordescr_to_catchain_node(vd)).collect();
+        let (key, adnl_id) = self.network
+            .get_validator_key(&validators).await?
+            .ok_or_else(|| error!("Can't get validator's key"))?;
+        let timestamp = self.now_ms() as i64;
+        for (message_id, status) in statuses {
+            let receipt = ton_api::ton::ton_node::rempreceipt::RempReceipt {
+                message_id,
+                status,
+                timestamp,
+                source_id: UInt256::from(key.id().data())
+            }.into_boxed();
+            self.network().remp().combine_and_send_receipt(to.clone(), receipt, adnl_id.clone(), key.clone()).await?;
+        }
+        Ok(())
+    }
+
+    fn send_remp_status_query(&self, to: Arc<KeyId>, query: &RempStatusQuery) -> Result<()> {
+        self.network().remp().send_status_query(to, query)
+    }
+
+    fn send_remp_deferred_notice(&self, to: Arc<KeyId>, notice: &RempDeferredNotice) -> Result<()> {
+        self.network().remp().send_deferred_notice(to, notice)
+    }
+
+    fn observe_remp_message_status(&self, message_id: &UInt256) -> Result<String> {
+        self.remp_client()
+            .ok_or_else(|| error!("Can't observe message status because remp client is not set"))?
+            .observe_message_status(message_id)
     }
 
     fn sign_remp_receipt(&self, receipt: &RempReceipt) -> Result<Vec<u8>> {
@@ -1254,6 +1497,14 @@ impl EngineOperations for Engine {
         }
         None
     }
+
+    fn set_out_queue_checkpoint(&self, shard: &ShardIdent, checkpoint: OutQueueCheckpoint) {
+        self.out_queue_checkpoints().insert(shard.clone(), checkpoint);
+    }
+
+    fn get_out_queue_checkpoint(&self, shard: &ShardIdent) -> Option<OutQueueCheckpoint> {
+        self.out_queue_checkpoints().get(shard).map(|guard| guard.val().clone())
+    }
 }
 
 async fn redirect_external_message(
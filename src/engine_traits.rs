@@ -14,11 +14,23 @@
 use crate::{
     block::BlockStuff,
     block_proof::BlockProofStuff, config::TonNodeConfig, internal_db::BlockResult,
-    engine::EngineFlags,
-    network::{control::ControlServer, full_node_client::FullNodeOverlayClient},
+    engine::EngineFlags, ext_messages::{DeferredRempMessage, MAX_EXTERNAL_MESSAGE_SIZE},
+    network::{
+        control::ControlServer, full_node_client::FullNodeOverlayClient, remp::{RempDeferredNotice, RempStatusQuery},
+        send_queue::QosClass, transfer_cache::TransferProgress,
+    },
     shard_state::ShardStateStuff,
-    types::top_block_descr::{TopBlockDescrStuff, TopBlockDescrId},
-    validator::validator_manager::ValidationStatus, engine::now_duration, shard_states_keeper::PinnedShardStateGuard,
+    types::{state_snapshot::StateSnapshot, top_block_descr::{TopBlockDescrStuff, TopBlockDescrId}},
+    validator::{
+        collator_cache::CollatorWorkCache,
+        consensus_stats::ConsensusReport,
+        message_cache::{RempMessageStatusFilter, RempStatusTransition},
+        out_msg_queue::OutQueueCheckpoint,
+        validation_hooks::ValidationHooks,
+        validation_pool::ValidationPool,
+        validator_manager::ValidationStatus
+    },
+    engine::now_duration, shard_states_keeper::PinnedShardStateGuard,
 };
 #[cfg(feature = "slashing")]
 use crate::validator::slashing::ValidatedBlockStat;
@@ -39,7 +51,9 @@ use overlay::{
     BroadcastSendInfo, OverlayId, OverlayShortId, QueriesConsumer, PrivateOverlayShortId
 };
 use std::{collections::HashSet, sync::{Arc, atomic::AtomicU64}};
-use storage::{StorageAlloc, block_handle_db::BlockHandle};
+use storage::{
+    StorageAlloc, archives::{ArchivesGcReport, GcTotals}, block_handle_db::BlockHandle
+};
 #[cfg(feature = "telemetry")]
 use storage::StorageTelemetry;
 use ton_api::ton::ton_node::{
@@ -50,9 +64,9 @@ use ton_block::{
     AccountIdPrefixFull, BlockIdExt, Message, ShardIdent, ShardAccount,
     MASTERCHAIN_ID, Deserializable, ConfigParams, OutMsgQueue
 };
-use ton_types::{error, AccountId, KeyId, KeyOption, Result, UInt256};
-use validator_session::{BlockHash, SessionId, ValidatorBlockCandidate};
-use crate::config::{CollatorConfig, CollatorTestBundlesGeneralConfig};
+use ton_types::{error, fail, AccountId, KeyId, KeyOption, Result, UInt256};
+use validator_session::{BlockHash, LatencyStat, SessionId, ValidatorBlockCandidate};
+use crate::config::{CollatorConfig, CollatorTestBundlesGeneralConfig, WorkchainOverrides};
 
 #[cfg(feature = "telemetry")]
 pub struct EngineTelemetry {
@@ -104,6 +118,8 @@ pub trait OverlayOperations : Sync + Send {
 
 #[async_trait::async_trait]
 pub trait PrivateOverlayOperations: Sync + Send {
+    // Peers already added for another list with the same local key are not added again,
+    // so their channels survive validator list change
     async fn set_validator_list(
         &self, 
         validator_list_id: UInt256,
@@ -120,7 +136,8 @@ pub trait PrivateOverlayOperations: Sync + Send {
         overlay_short_id : &Arc<PrivateOverlayShortId>,
         nodes_public_keys : &Vec<CatchainNode>,
         listener : CatchainOverlayListenerPtr,
-        _log_replay_listener: CatchainOverlayLogReplayListenerPtr
+        _log_replay_listener: CatchainOverlayLogReplayListenerPtr,
+        qos: QosClass,
     ) -> Result<Arc<dyn CatchainOverlay + Send>>;
 
     fn stop_catchain_client(&self, overlay_short_id: &Arc<PrivateOverlayShortId>);
@@ -161,6 +178,30 @@ pub trait EngineOperations : Sync + Send {
         unimplemented!()
     }
 
+    fn session_latency_stats(&self) -> &lockfree::map::Map<ShardIdent, LatencyStat> {
+        unimplemented!()
+    }
+
+    fn set_session_latency_stat(&self, shard: ShardIdent, stat: LatencyStat) {
+        unimplemented!()
+    }
+
+    fn remove_session_latency_stat(&self, shard: &ShardIdent) {
+        unimplemented!()
+    }
+
+    fn session_consensus_reports(&self) -> &lockfree::map::Map<ShardIdent, ConsensusReport> {
+        unimplemented!()
+    }
+
+    fn set_session_consensus_report(&self, shard: ShardIdent, report: ConsensusReport) {
+        unimplemented!()
+    }
+
+    fn remove_session_consensus_report(&self, shard: &ShardIdent) {
+        unimplemented!()
+    }
+
     fn last_collation_time(&self) -> &lockfree::map::Map<ShardIdent, u64> {
         unimplemented!()
     }
@@ -208,7 +249,8 @@ pub trait EngineOperations : Sync + Send {
         overlay_short_id : &Arc<PrivateOverlayShortId>,
         nodes_public_keys : &Vec<CatchainNode>,
         listener : CatchainOverlayListenerPtr,
-        _log_replay_listener: CatchainOverlayLogReplayListenerPtr
+        _log_replay_listener: CatchainOverlayLogReplayListenerPtr,
+        qos: QosClass,
     ) -> Result<Arc<dyn CatchainOverlay + Send>> {
         unimplemented!()
     }
@@ -310,6 +352,10 @@ pub trait EngineOperations : Sync + Send {
     fn destroy_block_candidates(&self, session_id: &SessionId) -> Result<bool> {
         unimplemented!()
     }
+    // Looks for the candidate in candidates of all the current sessions
+    fn find_block_candidate(&self, root_hash: &BlockHash) -> Option<Arc<ValidatorBlockCandidate>> {
+        None
+    }
     async fn find_mc_block_by_seq_no(&self, seqno: u32) -> Result<Arc<BlockHandle>> {
         unimplemented!()
     }
@@ -413,6 +459,16 @@ pub trait EngineOperations : Sync + Send {
         unimplemented!()
     }
 
+    // Sub-status of the message ignored by collator (e.g. "queued, position N"), got from validator
+    async fn process_remp_msg_sub_status_in_ext_db(
+        &self,
+        id: &UInt256,
+        source: &Arc<KeyId>,
+        sub_status: &str,
+    ) -> Result<()> {
+        unimplemented!()
+    }
+
     async fn process_chain_range_in_ext_db(
         &self,
         chain_range: &ChainRange)
@@ -427,6 +483,13 @@ pub trait EngineOperations : Sync + Send {
         unimplemented!()
     }
 
+    async fn process_remp_status_transition_in_ext_db(
+        &self,
+        transition: &RempStatusTransition
+    ) -> Result<()> {
+        unimplemented!()
+    }
+
     // This function WAITS the shard account belonging to the shard's last committed state.
     async fn load_account(
         self: Arc<Self>,
@@ -484,6 +547,25 @@ pub trait EngineOperations : Sync + Send {
     async fn load_and_pin_state(&self, block_id: &BlockIdExt) -> Result<PinnedShardStateGuard> {
         unimplemented!()
     }
+    // Pins masterchain state (by default the one processed by shard client); shard states 
+    // it refers to are pinned by the snapshot on demand. Reads made via the snapshot are consistent.
+    // It is prohibited to use any cell from the snapshot's states after its disposal.
+    async fn load_and_pin_state_snapshot(
+        &self, 
+        mc_block_id: Option<&BlockIdExt>
+    ) -> Result<StateSnapshot> {
+        let mc_block_id = match mc_block_id {
+            Some(id) => id.clone(),
+            None => self.load_shard_client_mc_block_id()?
+                .ok_or_else(|| error!("Cannot load shard_client_mc_block_id!"))?
+                .as_ref().clone()
+        };
+        if !mc_block_id.shard().is_masterchain() {
+            fail!("Snapshot can be pinned only by masterchain block, but {} is given", mc_block_id)
+        }
+        let mc_state = self.load_and_pin_state(&mc_block_id).await?;
+        Ok(StateSnapshot::with_mc_state(mc_state))
+    }
     async fn load_persistent_state_size(&self, block_id: &BlockIdExt) -> Result<u64> {
         unimplemented!()
     }
@@ -633,6 +715,26 @@ pub trait EngineOperations : Sync + Send {
     fn dequeue_remp_message_status(&self) -> Result<Option<(UInt256, Arc<Message>, RempMessageStatus)>> {
         unimplemented!()
     }
+    fn set_remp_deferred_messages(
+        &self,
+        shard: &ShardIdent,
+        deferred: Vec<(UInt256, DeferredRempMessage)>
+    ) -> Result<()> {
+        unimplemented!()
+    }
+    fn get_remp_deferred_message(&self, id: &UInt256) -> Option<DeferredRempMessage> {
+        unimplemented!()
+    }
+    fn get_remp_deferred_messages(&self) -> Result<Vec<(UInt256, DeferredRempMessage)>> {
+        unimplemented!()
+    }
+    fn is_remp_service_message(&self, id: &UInt256) -> bool {
+        false
+    }
+    // (count, total size) of REMP messages waiting for collation in the shard
+    fn get_remp_queue_depth(&self, shard: &ShardIdent) -> Result<(usize, usize)> {
+        unimplemented!()
+    }
 
     // Utils
 
@@ -692,10 +794,45 @@ pub trait EngineOperations : Sync + Send {
         unimplemented!()
     }
 
+    // Local overrides of fees and limits for the workchain (private networks only)
+    fn workchain_overrides(&self, _workchain_id: i32) -> Option<&WorkchainOverrides> {
+        None
+    }
+
+    // Config params with local overrides for the workchain applied;
+    // collator, validator and REMP checks must use them instead of the raw params
+    fn config_params_with_overrides(&self, config: &ConfigParams, workchain_id: i32) -> Result<ConfigParams> {
+        let mut config = config.clone();
+        if let Some(overrides) = self.workchain_overrides(workchain_id) {
+            overrides.apply(&mut config, workchain_id == MASTERCHAIN_ID)?;
+        }
+        Ok(config)
+    }
+
+    // None - validation is performed in the common runtime
+    fn validation_pool(&self) -> Option<Arc<ValidationPool>> {
+        None
+    }
+
+    // None - collator loads states and config from scratch every time
+    fn collator_work_cache(&self) -> Option<Arc<CollatorWorkCache>> {
+        None
+    }
+
+    // None - candidates are checked by the core rules only
+    fn validation_hooks(&self) -> Option<Arc<dyn ValidationHooks>> {
+        None
+    }
+
     fn db_root_dir(&self) -> Result<&str> {
         Ok(TonNodeConfig::DEFAULT_DB_ROOT)
     }
 
+    // What GC would delete with the current settings, nothing is deleted
+    async fn gc_dry_run_report(&self) -> Result<GcDryRunReport> {
+        unimplemented!()
+    }
+
     fn produce_chain_ranges_enabled(&self) -> bool {
         unimplemented!()
     }
@@ -704,6 +841,10 @@ pub trait EngineOperations : Sync + Send {
         unimplemented!()
     }
 
+    fn produce_remp_status_transitions_enabled(&self) -> bool {
+        unimplemented!()
+    }
+
     fn adjust_states_gc_interval(&self, interval_ms: u32) {
         unimplemented!()
     }
@@ -739,6 +880,13 @@ pub trait EngineOperations : Sync + Send {
         unimplemented!()
     }
 
+    // Validator's own service message (slashing report, election request, config vote): 
+    // it is pushed directly to REMP of the node, skipping client side limits, and collated
+    // with priority. Without REMP it is redirected as usual external message.
+    async fn send_service_message(&self, message_data: &[u8], id: UInt256) -> Result<()> {
+        unimplemented!()
+    }
+
     // Remp
 
     fn send_remp_message(&self, to: Arc<KeyId>, message: &RempMessage) -> Result<()> {
@@ -749,6 +897,31 @@ pub trait EngineOperations : Sync + Send {
         unimplemented!()
     }
 
+    // Pending message is forwarded to a member of the next validator set
+    fn send_remp_forwarded_message(&self, to: Arc<KeyId>, message: &RempMessage) -> Result<()> {
+        unimplemented!()
+    }
+
+    // Answer to status query: receipts with statuses of messages known by the validator
+    async fn send_remp_statuses(&self, to: Arc<KeyId>, statuses: Vec<(UInt256, RempMessageStatus)>) -> Result<()> {
+        unimplemented!()
+    }
+
+    fn send_remp_status_query(&self, to: Arc<KeyId>, query: &RempStatusQuery) -> Result<()> {
+        unimplemented!()
+    }
+
+    // Message is reported as ignored by collator because it is deferred to the next blocks
+    fn send_remp_deferred_notice(&self, to: Arc<KeyId>, notice: &RempDeferredNotice) -> Result<()> {
+        unimplemented!()
+    }
+
+    // Statuses of the message got from validators (in JSON); the message is observed
+    // by REMP client further
+    fn observe_remp_message_status(&self, message_id: &UInt256) -> Result<String> {
+        unimplemented!()
+    }
+
     fn sign_remp_receipt(&self, receipt: &RempReceipt) -> Result<Vec<u8>> {
         unimplemented!()
     }
@@ -757,6 +930,38 @@ pub trait EngineOperations : Sync + Send {
         unimplemented!()
     }
 
+    fn list_remp_catchain_transcripts(&self) -> Result<Vec<(UInt256, bool, usize)>> {
+        unimplemented!()
+    }
+
+    fn export_remp_catchain_transcript(&self, session_id: &UInt256, offset: usize, limit: usize) -> Result<String> {
+        unimplemented!()
+    }
+
+    fn export_remp_catchain_propagation(&self) -> Result<String> {
+        unimplemented!()
+    }
+
+    fn export_remp_message_history(&self, message_id: &UInt256) -> Result<String> {
+        unimplemented!()
+    }
+
+    fn dump_remp_message_cache(
+        &self,
+        shard: Option<&ShardIdent>,
+        status: RempMessageStatusFilter
+    ) -> Result<String> {
+        unimplemented!()
+    }
+
+    async fn inspect_remp_catchain_session(&self, queue_id: &UInt256, restart: bool) -> Result<String> {
+        unimplemented!()
+    }
+
+    async fn list_remp_catchain_sessions(&self) -> Result<String> {
+        unimplemented!()
+    }
+
     async fn push_message_to_remp(&self, data: ton_api::ton::bytes) -> Result<()> {
         unimplemented!()
     }
@@ -765,6 +970,20 @@ pub trait EngineOperations : Sync + Send {
         false 
     }
 
+    // Maximal size of external message accepted by REMP (advertised to clients)
+    fn remp_max_message_size(&self) -> usize {
+        MAX_EXTERNAL_MESSAGE_SIZE
+    }
+
+    // Maximal size of external message to the workchain accepted by REMP
+    fn remp_max_message_size_for_workchain(&self, workchain_id: i32) -> usize {
+        let max_size = self.remp_max_message_size();
+        match self.workchain_overrides(workchain_id) {
+            Some(overrides) => overrides.max_ext_message_size(max_size),
+            None => max_size
+        }
+    }
+
     async fn update_validators(
         &self,
         to_resolve: Vec<CatchainNode>,
@@ -798,7 +1017,8 @@ pub trait EngineOperations : Sync + Send {
     async fn download_archive(
         &self, 
         masterchain_seqno: u32,
-        active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>
+        active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>,
+        progress: &TransferProgress
     ) -> Result<Option<Vec<u8>>> {
         unimplemented!()
     }
@@ -897,6 +1117,13 @@ pub trait EngineOperations : Sync + Send {
         unimplemented!();
     }
 
+    fn set_out_queue_checkpoint(&self, shard: &ShardIdent, checkpoint: OutQueueCheckpoint) {
+    }
+
+    fn get_out_queue_checkpoint(&self, shard: &ShardIdent) -> Option<OutQueueCheckpoint> {
+        None
+    }
+
 }
 
 pub struct ChainRange {
@@ -920,12 +1147,20 @@ pub trait ExternalDb : Sync + Send {
     async fn process_chain_range(&self, range: &ChainRange) -> Result<()>;
     fn process_shard_hashes_enabled(&self) -> bool;
     async fn process_shard_hashes(&self, shard_hashes: &[BlockIdExt]) -> Result<()>;
+    fn process_remp_status_transitions_enabled(&self) -> bool;
+    async fn process_remp_status_transition(&self, transition: &RempStatusTransition) -> Result<()>;
     async fn process_remp_msg_status(
         &self,
         id: &UInt256,
         status: &RempReceipt,
         signature: &[u8]
     ) -> Result<()>;
+    async fn process_remp_msg_sub_status(
+        &self,
+        id: &UInt256,
+        source: &Arc<KeyId>,
+        sub_status: &str
+    ) -> Result<()>;
 }
 
 pub enum Server {
@@ -934,6 +1169,14 @@ pub enum Server {
     KafkaConsumer(stream_cancel::Trigger)
 }
 
+/// Candidates for GC: archives before `archives_gc_block` and expired persistent states
+#[derive(Debug, Default)]
+pub struct GcDryRunReport {
+    pub archives_gc_block: Option<BlockIdExt>,
+    pub archives: ArchivesGcReport,
+    pub persistent_states: GcTotals,
+}
+
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq)]
 pub enum RempDuplicateStatus {
     /// No such message in queue
@@ -949,5 +1192,27 @@ pub enum RempDuplicateStatus {
 #[async_trait::async_trait]
 pub trait RempCoreInterface: Sync + Send {
     async fn process_incoming_message(&self, message_id: UInt256, message: Message, source: Arc<KeyId>) -> Result<()>;
+    // Validator's own service message: it is not delayed as broadcast, the rest is as for incoming message
+    async fn process_service_message(&self, message_id: UInt256, message: Message) -> Result<()>;
+    // Message forwarded by validator of the previous set: duplicates of recently forwarded
+    // messages are dropped, the rest is as for incoming message
+    async fn process_forwarded_message(&self, message_id: UInt256, message: Message, source: Arc<KeyId>) -> Result<()>;
     fn check_remp_duplicate(&self, message_id: &UInt256) -> Result<RempDuplicateStatus>;
+    fn get_message_status(&self, message_id: &UInt256) -> Result<Option<RempMessageStatus>>;
+    // (session id, finished, blocks count) for each recorded REMP catchain transcript
+    fn list_catchain_transcripts(&self) -> Vec<(UInt256, bool, usize)>;
+    // Page of the transcript's blocks (in JSON), see `CatchainTranscript::to_json`
+    fn export_catchain_transcript(&self, session_id: &UInt256, offset: usize, limit: usize) -> Result<String>;
+    // Per source blocks propagation delays (in JSON) for each recorded transcript
+    fn export_catchain_propagation(&self) -> Result<String>;
+    // Status changes of the message (in JSON), if status history is enabled
+    fn export_message_history(&self, message_id: &UInt256) -> Result<String>;
+    // (count, total size) of messages with `New` status to accounts of the shard
+    fn get_queue_depth(&self, shard: &ShardIdent) -> (usize, usize);
+    // Messages of the cache (in JSON), optionally only ones to the shard and/or with the status
+    fn dump_message_cache(&self, shard: Option<&ShardIdent>, status: RempMessageStatusFilter) -> Result<String>;
+    // Brief states of all REMP catchain sessions (in JSON)
+    async fn list_catchain_sessions(&self) -> Result<String>;
+    // Status of REMP catchain session (in JSON), optionally after restart of the session
+    async fn inspect_catchain_session(&self, queue_id: &UInt256, restart: bool) -> Result<String>;
 }
@@ -23,8 +23,17 @@ pub enum NodeError {
     ValidatorReject(String),
     #[fail(display = "{}", 0)]
     ValidatorSoftReject(String),
+    // data got from a peer are proven to violate the protocol (malformed, badly signed)
+    #[fail(display = "Protocol violation: {}", 0)]
+    ProtocolViolation(String),
     #[cfg(feature = "external_db")]
     #[fail(display = "{}", 0)]
     #[allow(dead_code)]
     Other(String),
 }
+
+impl NodeError {
+    pub fn is_protocol_violation(error: &failure::Error) -> bool {
+        matches!(error.downcast_ref::<NodeError>(), Some(NodeError::ProtocolViolation(_)))
+    }
+}
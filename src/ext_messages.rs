@@ -11,13 +11,14 @@
 * limitations under the License.
 */
 
-use crate::engine::now_duration;
+use crate::{engine::now_duration, validator::deferred_dispatch::deferred_sub_status};
 use adnl::common::{add_unbound_object_to_map, add_unbound_object_to_map_with_update};
+use dashmap::DashMap;
 use lockfree::map::Map;
 use std::sync::{Arc, atomic::{AtomicU64, Ordering, AtomicU32}};
 use ton_api::ton::ton_node::{RempMessageStatus, RempMessageLevel};
 use ton_block::{Deserializable, ShardIdent, Message, AccountIdPrefixFull, BlockIdExt};
-use ton_types::{Result, types::UInt256, fail, read_boc};
+use ton_types::{KeyId, Result, types::UInt256, fail, read_boc};
 
 #[cfg(test)]
 #[path = "tests/test_ext_messages.rs"]
@@ -27,7 +28,8 @@ const MESSAGE_LIFETIME: u32 = 600; // seconds
 const MESSAGE_MAX_GENERATIONS: u8 = 3;
 
 const MAX_EXTERNAL_MESSAGE_DEPTH: u16 = 512;
-const MAX_EXTERNAL_MESSAGE_SIZE: usize = 65535;
+const MAX_EXTERNAL_MESSAGE_CELLS: usize = 8192;
+pub const MAX_EXTERNAL_MESSAGE_SIZE: usize = 65535;
 
 pub const EXT_MESSAGES_TRACE_TARGET: &str = "ext_messages";
 
@@ -396,31 +398,157 @@ impl Iterator for MessagePoolIter {
     }
 }
 
+// Number of tracked sources after which stale rate limit windows are dropped
+const RATE_LIMITER_CLEANUP_THRESHOLD: usize = 10_000;
+
+/// Fixed-window per-source limiter for external messages received via broadcasts
+pub struct BroadcastRateLimiter {
+    max_messages: u32, // 0 - unlimited
+    period_ms: u64,
+    // source -> (window start ms, messages in window)
+    windows: DashMap<Arc<KeyId>, (u64, u32)>,
+}
+
+impl BroadcastRateLimiter {
+
+    pub fn new(max_messages: u32, period_ms: u64) -> Self {
+        Self {
+            max_messages,
+            period_ms,
+            windows: DashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_messages > 0
+    }
+
+    /// Registers message from `source` and returns false if the source exceeded its limit
+    pub fn check(&self, source: &Arc<KeyId>, now_ms: u64) -> bool {
+        if !self.is_enabled() {
+            return true
+        }
+        if self.windows.len() > RATE_LIMITER_CLEANUP_THRESHOLD {
+            self.cleanup(now_ms);
+        }
+        let mut window = self.windows.entry(source.clone()).or_insert((now_ms, 0));
+        let (start, count) = window.value_mut();
+        if *start + self.period_ms <= now_ms {
+            *start = now_ms;
+            *count = 0;
+        }
+        if *count >= self.max_messages {
+            return false
+        }
+        *count += 1;
+        true
+    }
+
+    pub fn cleanup(&self, now_ms: u64) {
+        self.windows.retain(|_, (start, _)| *start + self.period_ms > now_ms);
+    }
+
+    pub fn tracked_sources(&self) -> usize {
+        self.windows.len()
+    }
+}
+
 pub fn create_ext_message(data: &[u8]) -> Result<(UInt256, Message)> {
+    create_ext_message_with_limit(data, MAX_EXTERNAL_MESSAGE_SIZE)
+}
+
+/// Reason of external message validation failure. Its code starts the error text
+/// (`<code>: <details>`), so reasons of REMP rejects can be told apart by clients
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtMessageRejectReason {
+    TooLarge,
+    BadBoc,
+    BigCells,
+    TooManyCells,
+    NonZeroLevel,
+    TooDeep,
+    BadHeader,
+    Anycast,
+}
 
-    if data.len() > MAX_EXTERNAL_MESSAGE_SIZE {
-        fail!("External message is too large: {}", data.len())
+impl ExtMessageRejectReason {
+    const ALL: [ExtMessageRejectReason; 8] = [
+        Self::TooLarge, Self::BadBoc, Self::BigCells, Self::TooManyCells,
+        Self::NonZeroLevel, Self::TooDeep, Self::BadHeader, Self::Anycast
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::TooLarge => "message_too_large",
+            Self::BadBoc => "message_bad_boc",
+            Self::BigCells => "message_big_cells",
+            Self::TooManyCells => "message_too_many_cells",
+            Self::NonZeroLevel => "message_nonzero_level",
+            Self::TooDeep => "message_too_deep",
+            Self::BadHeader => "message_bad_header",
+            Self::Anycast => "message_anycast",
+        }
     }
 
-    let read_result = read_boc(&data)?;
-    if read_result.header.big_cells_count > 0 {
-        fail!("External message contains big cells")
+    /// Reason of validation failure by the error text (None for other errors)
+    pub fn from_error_text(text: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|reason| {
+            text.strip_prefix(reason.code()).map_or(false, |rest| rest.starts_with(':'))
+        })
+    }
+
+    fn fail<T>(self, details: String) -> Result<T> {
+        fail!("{}: {}", self.code(), details)
     }
-    let root = read_result.withdraw_single_root()?;
+}
+
+pub fn check_ext_message_size(len: usize, max_size: usize) -> Result<()> {
+    if len > max_size {
+        ExtMessageRejectReason::TooLarge.fail(format!("External message is too large: {} (max {})", len, max_size))?
+    }
+    Ok(())
+}
+
+pub fn create_ext_message_with_limit(data: &[u8], max_size: usize) -> Result<(UInt256, Message)> {
+
+    check_ext_message_size(data.len(), max_size.min(MAX_EXTERNAL_MESSAGE_SIZE))?;
+
+    let read_result = match read_boc(&data) {
+        Ok(read_result) => read_result,
+        Err(e) => ExtMessageRejectReason::BadBoc.fail(format!("Cannot read external message: {}", e))?
+    };
+    if read_result.header.big_cells_count > 0 {
+        ExtMessageRejectReason::BigCells.fail("External message contains big cells".to_string())?
+    }
+    if read_result.header.cells_count > MAX_EXTERNAL_MESSAGE_CELLS {
+        ExtMessageRejectReason::TooManyCells.fail(format!(
+            "External message has too many cells: {} (max {})",
+            read_result.header.cells_count, MAX_EXTERNAL_MESSAGE_CELLS
+        ))?
+    }
+    let root = match read_result.withdraw_single_root() {
+        Ok(root) => root,
+        Err(e) => ExtMessageRejectReason::BadBoc.fail(format!("External message must have one root: {}", e))?
+    };
     if root.level() != 0 {
-        fail!("External message must have zero level, but has {}", root.level())
+        ExtMessageRejectReason::NonZeroLevel.fail(format!("External message must have zero level, but has {}", root.level()))?
     }
     if root.repr_depth() >= MAX_EXTERNAL_MESSAGE_DEPTH {
-        fail!("External message {:x} is too deep: {}", root.repr_hash(), root.repr_depth())
+        ExtMessageRejectReason::TooDeep.fail(format!("External message {:x} is too deep: {}", root.repr_hash(), root.repr_depth()))?
     }
-    let message = Message::construct_from_cell(root.clone())?;
+    let message = match Message::construct_from_cell(root.clone()) {
+        Ok(message) => message,
+        Err(e) => ExtMessageRejectReason::BadHeader.fail(format!("Cannot parse external message {:x}: {}", root.repr_hash(), e))?
+    };
     if let Some(header) = message.ext_in_header() {
         if header.dst.rewrite_pfx().is_some() {
-            fail!("External inbound message {:x} contains anycast info - it is not supported", root.repr_hash())
+            ExtMessageRejectReason::Anycast.fail(format!(
+                "External inbound message {:x} contains anycast info - it is not supported", root.repr_hash()
+            ))?
         }
         Ok((root.repr_hash(), message))
     } else {
-        fail!("External inbound message {:x} doesn't have proper header", root.repr_hash())
+        ExtMessageRejectReason::BadHeader.fail(format!("External inbound message {:x} doesn't have proper header", root.repr_hash()))
     }
 }
 
@@ -461,9 +589,24 @@ pub fn is_finally_accepted(status: &RempMessageStatus) -> bool {
     }
 }
 
+/// Message deferred by collator to the next blocks because of too many messages to its account
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeferredRempMessage {
+    pub shard: ShardIdent,
+    pub account: UInt256,
+    pub position: u32,
+    pub queue_len: u32,
+}
+
+/// Time service message keeps its priority, if it is not finalized by collator
+pub const REMP_SERVICE_MESSAGE_TTL: u32 = 600;
+
 pub struct RempMessagesPool {
     messages: Map<UInt256, Arc<Message>>,
     statuses_queue: lockfree::queue::Queue<(UInt256, Arc<Message>, RempMessageStatus)>,
+    deferred: Map<UInt256, DeferredRempMessage>,
+    // validator's own service messages (id -> expiration time)
+    service: Map<UInt256, u32>,
 }
 
 impl RempMessagesPool {
@@ -472,7 +615,50 @@ impl RempMessagesPool {
         Self {
             messages: Map::new(),
             statuses_queue: lockfree::queue::Queue::new(),
+            deferred: Map::new(),
+            service: Map::new(),
+        }
+    }
+
+    // Service messages are collated before other messages and are not limited per account
+    pub fn add_service_message(&self, id: UInt256, now: u32) {
+        for guard in self.service.iter() {
+            if *guard.val() <= now {
+                self.service.remove(guard.key());
+            }
+        }
+        self.service.insert(id, now + REMP_SERVICE_MESSAGE_TTL);
+    }
+
+    pub fn is_service_message(&self, id: &UInt256) -> bool {
+        self.service.get(id).is_some()
+    }
+
+    // Deferred messages are reported as ignored by collator and returned to collation
+    // queue, so their positions are kept to collate them first in the next block.
+    // Positions from the previous collation of the shard are replaced.
+    pub fn set_deferred(&self, shard: &ShardIdent, deferred: Vec<(UInt256, DeferredRempMessage)>) {
+        for guard in self.deferred.iter() {
+            if &guard.val().shard == shard {
+                self.deferred.remove(guard.key());
+            }
         }
+        for (id, info) in deferred {
+            log::debug!(
+                target: EXT_MESSAGES_TRACE_TARGET,
+                "remp message {:x} to account {:x} is deferred: {}",
+                id, info.account, deferred_sub_status(info.position)
+            );
+            self.deferred.insert(id, info);
+        }
+    }
+
+    pub fn get_deferred(&self, id: &UInt256) -> Option<DeferredRempMessage> {
+        self.deferred.get(id).map(|guard| guard.val().clone())
+    }
+
+    pub fn deferred_iter(&self) -> impl Iterator<Item = (UInt256, DeferredRempMessage)> + '_ {
+        self.deferred.iter().map(|guard| (guard.key().clone(), guard.val().clone()))
     }
 
     pub fn new_message(&self, id: UInt256, message: Arc<Message>) -> Result<()> {
@@ -516,6 +702,13 @@ impl RempMessagesPool {
         rejected: Vec<(UInt256, String)>,
         ignored: Vec<UInt256>,
     ) -> Result<()> {
+        for id in accepted.iter().chain(rejected.iter().map(|(id, _)| id)).chain(ignored.iter()) {
+            self.deferred.remove(id);
+        }
+        // ignored service messages are returned to collation queue and keep priority
+        for id in accepted.iter().chain(rejected.iter().map(|(id, _)| id)) {
+            self.service.remove(id);
+        }
         for id in accepted {
             if let Some(pair) = self.messages.remove(&id) {
                 self.statuses_queue.push((
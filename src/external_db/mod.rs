@@ -81,6 +81,9 @@ pub fn create_external_db(
         write_raw_block_proof: kafka_producer::KafkaProducer::new(config.raw_block_proof_producer)?,
         write_chain_range: kafka_producer::KafkaProducer::new(config.chain_range_producer)?,
         write_remp_statuses: kafka_producer::KafkaProducer::new(config.remp_statuses_producer)?,
+        write_remp_status_transitions: kafka_producer::KafkaProducer::new(
+            config.remp_status_transitions_producer
+        )?,
         write_shard_hashes: kafka_producer::KafkaProducer::new(config.shard_hashes_producer)?,
     };
     if writers.write_shard_hashes.enabled() && control_id.is_none() {
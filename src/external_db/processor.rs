@@ -13,7 +13,8 @@
 
 use crate::{
     block::BlockStuff, block_proof::BlockProofStuff, engine_traits::{ChainRange, ExternalDb},
-    error::NodeError, external_db::WriteData, shard_state::ShardStateStuff
+    error::NodeError, external_db::WriteData, shard_state::ShardStateStuff,
+    validator::message_cache::RempStatusTransition
 };
 
 use std::{
@@ -32,10 +33,10 @@ use ton_block::{
 use ton_types::{
     write_boc,
     types::UInt256, HashmapIterator,
-    AccountId, Cell, Result, SliceData, HashmapType,
+    AccountId, Cell, KeyId, Result, SliceData, HashmapType,
     fail, BuilderData,
 };
-use ton_api::ton::ton_node::RempReceipt;
+use ton_api::ton::ton_node::{RempMessageStatus, RempReceipt};
 use serde::Serialize;
 use serde_json::{Map, Value};
 
@@ -87,6 +88,35 @@ impl From<&BlockIdExt> for ShardHashData {
     }
 }
 
+#[derive(Clone, Debug, Serialize)]
+struct RempStatusTransitionData {
+    pub message_id: String,
+    pub old_status: Option<String>,
+    pub new_status: String,
+    // Block the new status refers to (if any)
+    pub block_id: Option<ShardHashData>,
+    pub timestamp: u64,
+}
+
+impl From<&RempStatusTransition> for RempStatusTransitionData {
+    fn from(value: &RempStatusTransition) -> Self {
+        let block_id = match &value.new_status {
+            RempMessageStatus::TonNode_RempAccepted(status) => Some(&status.block_id),
+            RempMessageStatus::TonNode_RempRejected(status) => Some(&status.block_id),
+            RempMessageStatus::TonNode_RempIgnored(status) => Some(&status.block_id),
+            _ => None
+        };
+        Self {
+            message_id: value.message_id.to_hex_string(),
+            old_status: value.old_status.as_ref().map(|status| status.to_string()),
+            new_status: value.new_status.to_string(),
+            // Statuses not bound to a block have default block id
+            block_id: block_id.filter(|id| **id != BlockIdExt::default()).map(ShardHashData::from),
+            timestamp: value.timestamp_ms,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 struct ShardHashesData {
     pub shards: Vec<ShardHashData>,
@@ -111,6 +141,7 @@ pub struct Writers<T: 'static + WriteData> {
     pub write_raw_block_proof: T,
     pub write_chain_range: T,
     pub write_remp_statuses: T,
+    pub write_remp_status_transitions: T,
     pub write_shard_hashes: T,
 }
 
@@ -929,6 +960,38 @@ impl<T: WriteData> ExternalDb for Processor<T> {
         Ok(())
     }
 
+    async fn process_remp_msg_sub_status(
+        &self,
+        id: &UInt256,
+        source: &Arc<KeyId>,
+        sub_status: &str
+    ) -> Result<()> {
+        let key = format!("{:x}", id);
+        let val = format!("{:#}", serde_json::json!({
+            "message_id": key,
+            "source_id": hex::encode(source.data()),
+            "sub_status": sub_status,
+        }));
+        self.writers.write_remp_statuses.write_data(key, val, None, None).await?;
+        Ok(())
+    }
+
+    fn process_remp_status_transitions_enabled(&self) -> bool {
+        self.writers.write_remp_status_transitions.enabled()
+    }
+
+    async fn process_remp_status_transition(&self, transition: &RempStatusTransition) -> Result<()> {
+        if self.writers.write_remp_status_transitions.enabled() {
+            self.writers.write_remp_status_transitions.write_data(
+                transition.message_id.to_hex_string(),
+                serde_json::to_string(&RempStatusTransitionData::from(transition))?,
+                None,
+                None
+            ).await?;
+        }
+        Ok(())
+    }
+
     fn process_shard_hashes_enabled(&self) -> bool {
         self.writers.write_shard_hashes.enabled()
     }
@@ -13,7 +13,8 @@
 
 use crate::{
     block::BlockStuff, block_proof::BlockProofStuff, collator_test_bundle::create_engine_allocated,
-    engine_traits::ChainRange, shard_state::ShardStateStuff, external_db::processor::Writers
+    engine_traits::ChainRange, shard_state::ShardStateStuff, external_db::processor::Writers,
+    validator::message_cache::RempStatusTransition
 };
 #[cfg(feature = "telemetry")]
 use crate::collator_test_bundle::create_engine_telemetry;
@@ -21,7 +22,8 @@ use crate::collator_test_bundle::create_engine_telemetry;
 use super::*;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use ton_block::{HashmapAugType, OutMsg, AccountBlock};
-use ton_types::HashmapType;
+use ton_api::ton::ton_node::RempMessageStatus;
+use ton_types::{HashmapType, UInt256};
 
 #[derive(Clone)]
 struct TestWriter {
@@ -156,6 +158,7 @@ async fn test_external_db_processor_async(
         write_raw_block_proof: TestWriter::new(enabled, write_data),
         write_chain_range: TestWriter::new(enabled, write_data),
         write_remp_statuses: TestWriter::new(enabled, write_data),
+        write_remp_status_transitions: TestWriter::new(enabled, write_data),
         write_shard_hashes: TestWriter::new(enabled, write_data),
     };
 
@@ -250,6 +253,16 @@ async fn test_external_db_processor_async(
     p.process_shard_hashes(&[block.id().clone()]).await?;
     assert_eq!(enabled as usize, writers.write_shard_hashes.records.load(Ordering::Relaxed));
 
+    let transition = RempStatusTransition {
+        message_id: UInt256::rand(),
+        old_status: None,
+        new_status: RempMessageStatus::TonNode_RempNew,
+        timestamp_ms: 1,
+    };
+    assert_eq!(p.process_remp_status_transitions_enabled(), enabled);
+    p.process_remp_status_transition(&transition).await?;
+    assert_eq!(enabled as usize, writers.write_remp_status_transitions.records.load(Ordering::Relaxed));
+
     if let Some(ss) = ss {
         assert_eq!(accounts_count, writers.write_shard_hashes.records.load(Ordering::Relaxed));
 
@@ -0,0 +1,110 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{block::BlockStuff, engine_traits::EngineOperations, shard_state::ShardStateStuff};
+
+use std::sync::Arc;
+use ton_block::{
+    Block, BlockIdExt, Deserializable, MsgAddressInt, Serializable, ShardAccount, ShardIdent,
+    ShardStateUnsplit
+};
+use ton_types::{error, write_boc, AccountId, BuilderData, Cell, MerkleProof, Result, UsageTree};
+
+#[cfg(test)]
+#[path = "../tests/test_account_proof.rs"]
+mod tests;
+
+/// Account state together with Merkle proofs, which allow a client trusting the reference
+/// masterchain block to check the state without a lite-server. Root of `proof` refers to:
+/// - proof of the state hash in the masterchain block (via state update);
+/// - for shard accounts: proof of the shard block id in the masterchain state and
+/// proof of the state hash in the shard block;
+/// - proof of the account in the state (the account cell is included in full).
+pub struct AccountWithProof {
+    pub mc_block_id: BlockIdExt,
+    pub shard_block_id: BlockIdExt,
+    pub account: Option<ShardAccount>,
+    pub proof: Cell,
+}
+
+impl AccountWithProof {
+    pub fn proof_boc(&self) -> Result<Vec<u8>> {
+        write_boc(&self.proof)
+    }
+}
+
+async fn load_block(engine: &dyn EngineOperations, id: &BlockIdExt) -> Result<BlockStuff> {
+    let handle = engine.load_block_handle(id)?
+        .ok_or_else(|| error!("Cannot load handle for block {}", id))?;
+    engine.load_block(&handle).await
+}
+
+fn prove_block_state(block: &BlockStuff) -> Result<Cell> {
+    let usage_tree = UsageTree::with_root(block.root_cell().clone());
+    Block::construct_from_cell(usage_tree.root_cell())?.read_state_update()?;
+    MerkleProof::create(block.root_cell(), |h| usage_tree.contains(h))?.serialize()
+}
+
+fn prove_shard(mc_state: &ShardStateStuff, shard: &ShardIdent) -> Result<Cell> {
+    let usage_tree = UsageTree::with_params(mc_state.root_cell().clone(), true);
+    ShardStateUnsplit::construct_from_cell(usage_tree.root_cell())?
+        .read_custom()?
+        .ok_or_else(|| error!("State {} doesn't contain masterchain extra", mc_state.block_id()))?
+        .shards()
+        .get_shard(shard)?
+        .ok_or_else(|| error!("Shard {} is not found in state {}", shard, mc_state.block_id()))?;
+    MerkleProof::create(mc_state.root_cell(), |h| usage_tree.contains(h))?.serialize()
+}
+
+fn prove_account(state: &ShardStateStuff, account_id: &AccountId) -> Result<Cell> {
+    let usage_tree = UsageTree::with_params(state.root_cell().clone(), true);
+    let account_hash = ShardStateUnsplit::construct_from_cell(usage_tree.root_cell())?
+        .read_accounts()?
+        .account(account_id)?
+        .map(|account| account.account_cell().repr_hash());
+    MerkleProof::create_with_subtrees(
+        state.root_cell(),
+        |h| usage_tree.contains(h),
+        |h| Some(h) == account_hash.as_ref()
+    )?.serialize()
+}
+
+/// Finds account in the states referred by the last masterchain block processed by shard client
+/// and builds proofs for it
+pub async fn account_with_proof(
+    engine: &Arc<dyn EngineOperations>,
+    addr: &MsgAddressInt
+) -> Result<AccountWithProof> {
+    let snapshot = engine.load_and_pin_state_snapshot(None).await?;
+    let mc_state = snapshot.mc_state().state();
+    let state = snapshot.state_for_account(engine.as_ref(), addr).await?;
+    let state = state.state();
+
+    let mut proof = BuilderData::new();
+    let mc_block = load_block(engine.as_ref(), mc_state.block_id()).await?;
+    proof.checked_append_reference(prove_block_state(&mc_block)?)?;
+    if !state.block_id().shard().is_masterchain() {
+        proof.checked_append_reference(prove_shard(mc_state, state.block_id().shard())?)?;
+        let block = load_block(engine.as_ref(), state.block_id()).await?;
+        proof.checked_append_reference(prove_block_state(&block)?)?;
+    }
+    let account_id = addr.address();
+    proof.checked_append_reference(prove_account(state, &account_id)?)?;
+
+    Ok(AccountWithProof {
+        mc_block_id: mc_state.block_id().clone(),
+        shard_block_id: state.block_id().clone(),
+        account: state.shard_account(&account_id)?,
+        proof: proof.into_cell()?,
+    })
+}
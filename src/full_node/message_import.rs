@@ -0,0 +1,90 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::engine_traits::EngineOperations;
+
+use std::{collections::BTreeMap, path::Path, sync::Arc, time::Duration};
+use ton_block::{Deserializable, Message};
+use ton_types::{base64_decode, read_single_root_boc, Result, UInt256};
+
+#[cfg(test)]
+#[path = "../tests/test_message_import.rs"]
+mod tests;
+
+pub const MESSAGE_IMPORTS_DIR: &str = "message_imports";
+
+/// Summary of bulk import: count of messages passed to the external messages pipeline
+/// and counts of rejected ones per reason
+#[derive(Debug, Default, PartialEq)]
+pub struct MessageImportReport {
+    pub accepted: u32,
+    pub rejected: BTreeMap<String, u32>,
+}
+
+impl MessageImportReport {
+    fn reject(&mut self, reason: impl ToString) {
+        *self.rejected.entry(reason.to_string()).or_default() += 1;
+    }
+}
+
+/// Decodes one line of import file: base64 encoded BOC of inbound external message
+fn parse_message(line: &str) -> std::result::Result<(UInt256, Vec<u8>), &'static str> {
+    let data = base64_decode(line).map_err(|_| "invalid base64")?;
+    let root = read_single_root_boc(&data).map_err(|_| "invalid boc")?;
+    let message = Message::construct_from_cell(root.clone()).map_err(|_| "invalid message")?;
+    if message.ext_in_header().is_none() {
+        return Err("not an inbound external message")
+    }
+    Ok((root.repr_hash(), data))
+}
+
+/// Reads file with base64 encoded BOCs of external messages (one per line, empty lines
+/// are skipped) and sends them the same way as messages got from clients, at most
+/// `rate` messages per second (0 - unlimited).
+pub async fn import_external_messages(
+    engine: &Arc<dyn EngineOperations>,
+    path: &Path,
+    rate: u32
+) -> Result<MessageImportReport> {
+    let file = tokio::fs::read_to_string(path).await?;
+    let mut report = MessageImportReport::default();
+    let start = tokio::time::Instant::now();
+    let now = std::time::Instant::now();
+    let lines = file.lines().map(str::trim).filter(|line| !line.is_empty());
+    for (i, line) in lines.enumerate() {
+        if rate != 0 {
+            let delay = Duration::from_secs(i as u64) / rate;
+            tokio::time::sleep_until(start + delay).await;
+        }
+        let (id, data) = match parse_message(line) {
+            Ok(message) => message,
+            Err(reason) => {
+                report.reject(reason);
+                continue
+            }
+        };
+        match engine.redirect_external_message(&data, id.clone()).await {
+            Ok(()) => report.accepted += 1,
+            Err(e) => {
+                log::warn!("Imported external message {:x} is rejected: {}", id, e);
+                report.reject(e)
+            }
+        }
+    }
+    log::info!(
+        "External messages imported from {}: {} accepted, {} rejected, TIME {}ms",
+        path.display(), report.accepted, report.rejected.values().sum::<u32>(),
+        now.elapsed().as_millis()
+    );
+    Ok(report)
+}
@@ -18,3 +18,6 @@ pub mod shard_client;
 pub mod telemetry;
 pub mod counters;
 pub mod remp_client;
+pub mod state_diff;
+pub mod account_proof;
+pub mod message_import;
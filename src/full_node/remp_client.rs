@@ -15,10 +15,14 @@ use crate::{
     engine_traits::EngineOperations,
     validator::validator_utils::get_adnl_id,
     shard_state::ShardStateStuff,
-    ext_messages::{create_ext_message, is_finally_rejected, is_finally_accepted},
-    validator::validator_utils::validatordescr_to_catchain_node,
+    ext_messages::{
+        check_ext_message_size, create_ext_message_with_limit, is_finally_rejected, is_finally_accepted
+    },
+    validator::{deferred_dispatch::deferred_sub_status, validator_utils::validatordescr_to_catchain_node},
     block::BlockStuff,
-    network::remp::RempReceiptsSubscriber,
+    network::remp::{
+        RempDeferredNotice, RempReceiptsSubscriber, RempStatusQuery, SignedCombinedReceipt, MAX_STATUS_QUERY_MESSAGES
+    },
     types::{
         shard_blocks_observer::ShardBlocksObserver,
         mpmc_channel::MpmcChannel,
@@ -28,6 +32,7 @@ use crate::{
 use crate::full_node::telemetry::ReceiptTelemetry;
 
 use adnl::common::add_unbound_object_to_map_with_update;
+use dashmap::DashMap;
 use std::{
     cmp::max, collections::{HashSet, HashMap}, 
     sync::{Arc, atomic::{AtomicU64, AtomicU32, AtomicBool, Ordering}},
@@ -38,19 +43,23 @@ use ton_api::{
     ton::ton_node::{RempMessage, RempMessageLevel, RempMessageStatus, RempReceipt}
 };
 use ton_block::{
-    Message, ShardIdent, FutureSplitMerge, ShardAccount, BlockIdExt, ValidatorDescr, MASTERCHAIN_ID,
+    ConfigParams, Message, ShardIdent, FutureSplitMerge, ShardAccount, BlockIdExt, ValidatorDescr, MASTERCHAIN_ID,
     HashmapAugType
 };
 use ton_executor::{
     BlockchainConfig, OrdinaryTransactionExecutor, TransactionExecutor, ExecuteParams,
 };
-use ton_types::{error, fail, AccountId, base64_encode, KeyId, Result, UInt256};
+use ton_types::{
+    error, fail, AccountId, base64_encode, Ed25519KeyOption, KeyId, KeyOption, Result, UInt256
+};
 
 const HANGED_MESSAGE_TIMEOUT_MS: u64 = 20_000;
 const TIME_BEFORE_DIE_MS: u64 = 100_000;
 const MESSAGES_WORKER_TIMEOUT_MS: u64 = 50;
 const MESSAGES_RESEND_TIMEOUT_MS: u64 = 2000;
 const NEXT_SET_LAG: u32 = 15; // include next set if it is activating during
+const OBSERVER_QUERY_PERIOD_MS: u64 = 2000;
+const OBSERVED_MESSAGE_TIMEOUT_MS: u64 = 600_000; // since the last request of the status
 
 #[derive(Default)]
 pub struct RempClient {
@@ -62,12 +71,25 @@ pub struct RempClient {
     skip_run_local: bool,
     mc_cc_seqno: AtomicU32,
     msg_channel: MpmcChannel<(UInt256, Vec<u8>)>,
+    status_observer: bool,
+    // 0 - unlimited
+    queue_max_len: usize,
+    observed: DashMap<UInt256, ObservedMessage>,
+    observer_validators: std::sync::Mutex<Vec<Arc<KeyId>>>,
+}
+
+// Message sent by other node, its statuses are queried from validators
+struct ObservedMessage {
+    requested_at: u64,
+    statuses: HashMap<Arc<KeyId>, RempMessageStatus>,
+    // sub-statuses of messages ignored by collators, e.g. "queued, position N"
+    sub_statuses: HashMap<Arc<KeyId>, String>,
 }
 
 #[derive(Clone)]
 pub struct ValidatorInfo {
     got_receipt_from: Arc<AtomicBool>,
-    //pub_key: Arc<dyn KeyOption>,
+    pub_key: Arc<dyn KeyOption>,
 }
 
 pub struct RempMessageHistory {
@@ -82,11 +104,15 @@ pub struct RempMessageHistory {
 
 #[async_trait::async_trait]
 impl RempReceiptsSubscriber for RempClient {
-    async fn new_remp_receipt(&self, receipt: RempReceipt, source: &Arc<KeyId>) -> Result<()> {
-        //let sign_bytes = format!("{}...", hex::encode(&receipt.signature().0[0..6]));
+    async fn new_remp_receipt(
+        &self,
+        receipt: RempReceipt,
+        source: &Arc<KeyId>,
+        signed: Option<&SignedCombinedReceipt>
+    ) -> Result<()> {
         let id = receipt.message_id().clone();
         log::info!("Processing REMP receipt for {} from {}", id, source);
-        self.process_remp_receipt(receipt, source).await
+        self.process_remp_receipt(receipt, source, signed).await
             .map_err(|e| {
                 log::error!("Error while processing REMP receipt for {:x} from {}: {}", id, source, e);
                 e
@@ -94,6 +120,15 @@ impl RempReceiptsSubscriber for RempClient {
         log::info!("Processed REMP receipt for {}  from {}", id, source);
         Ok(())
     }
+
+    async fn deferred_remp_message(&self, notice: RempDeferredNotice, source: &Arc<KeyId>) -> Result<()> {
+        let id = notice.message_id.clone();
+        self.process_deferred_notice(notice, source).await
+            .map_err(|e| {
+                log::error!("Error while processing REMP deferred notice for {:x} from {}: {}", id, source, e);
+                e
+            })
+    }
 }
 
 impl RempClient {
@@ -118,6 +153,18 @@ impl RempClient {
         }
     }
 
+    /// Statuses of messages sent by other nodes may be observed (see `observe_message_status`)
+    pub fn with_status_observer(mut self) -> Self {
+        self.status_observer = true;
+        self
+    }
+
+    /// New messages are rejected while `queue_max_len` messages wait for processing
+    pub fn with_queue_max_len(mut self, queue_max_len: usize) -> Self {
+        self.queue_max_len = queue_max_len;
+        self
+    }
+
     pub async fn start(
         self: Arc<Self>,
         engine: Arc<dyn EngineOperations>,
@@ -131,6 +178,16 @@ impl RempClient {
 
         // resolve current validators
         let (last_mc_block_id, validators) = self.resolve_validators().await?;
+        self.set_observer_validators(&validators);
+
+        if self.status_observer {
+            let s = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = s.status_observer_worker().await {
+                    log::error!("FATAL error in status_observer_worker: {:?}", e)
+                }
+            });
+        }
 
         let self1 = self.clone();
         tokio::spawn(async move {
@@ -166,20 +223,36 @@ impl RempClient {
         Ok(())
     }
 
+    /// Queues the message; fails if it is already being processed or the queue is full,
+    /// so the sender may retry with another node
     pub fn process_remp_message(
         self: Arc<Self>,
         raw_message: Vec<u8>,
         id: UInt256,
-    ) {
+    ) -> Result<()> {
+        if self.messages.get(&id).is_some() {
+            fail!("message {:x} is already in processing", id);
+        }
+        if self.queue_max_len > 0 && self.msg_channel.len() >= self.queue_max_len {
+            metrics::increment_counter!("remp_client_queue_rejects");
+            fail!(
+                "REMP client queue is full ({} messages), message {:x} is rejected",
+                self.queue_max_len, id
+            );
+        }
         match self.msg_channel.send((id.clone(), raw_message)) {
             Ok(_) => log::trace!("process_remp_message: {:x} was sent to the channel", id),
-            Err(_) => log::error!("process_remp_message: can't send {:x} to the channel", id),
+            Err(_) => {
+                log::error!("process_remp_message: can't send {:x} to the channel", id);
+                fail!("can't send message {:x} to the REMP client channel", id)
+            }
         }
         #[cfg(feature = "telemetry")]
         if let Some(engine) = self.engine.get() {
             engine.remp_client_telemetry().register_got_message();
             engine.remp_client_telemetry().in_channel(self.msg_channel.len());
         }
+        Ok(())
     }
 
     async fn process_remp_message_worker(
@@ -233,6 +306,72 @@ impl RempClient {
         &self.messages
     }
 
+    /// Statuses of the message got from validators (JSON, by validators' ADNL ids).
+    /// The message is observed (its statuses are queried from validators) until
+    /// OBSERVED_MESSAGE_TIMEOUT_MS after the last call.
+    pub fn observe_message_status(&self, message_id: &UInt256) -> Result<String> {
+        if !self.status_observer {
+            fail!("REMP status observer is not enabled")
+        }
+        let engine = self.engine.get().ok_or_else(|| error!("engine was not set"))?;
+        let mut observed = self.observed.entry(message_id.clone())
+            .or_insert_with(|| ObservedMessage {
+                requested_at: 0,
+                statuses: HashMap::new(),
+                sub_statuses: HashMap::new()
+            });
+        observed.requested_at = engine.now_ms();
+        let statuses = observed.statuses.iter()
+            .map(|(validator, status)| {
+                let status = match observed.sub_statuses.get(validator) {
+                    Some(sub_status) => format!("{:?}, {}", status, sub_status),
+                    None => format!("{:?}", status)
+                };
+                (validator.to_string(), status.into())
+            })
+            .collect::<serde_json::Map<String, serde_json::Value>>();
+        Ok(format!("{:#}", serde_json::Value::from(statuses)))
+    }
+
+    fn set_observer_validators(&self, validators: &HashSet<ValidatorDescr>) {
+        if self.status_observer {
+            *self.observer_validators.lock().unwrap() = validators.iter().map(get_adnl_id).collect();
+        }
+    }
+
+    async fn status_observer_worker(&self) -> Result<()> {
+
+        let engine = self.engine.get().ok_or_else(|| error!("engine was not set"))?;
+
+        loop {
+            tokio::time::sleep(Duration::from_millis(OBSERVER_QUERY_PERIOD_MS)).await;
+            if engine.check_stop() {
+                return Ok(())
+            }
+
+            let now = engine.now_ms();
+            self.observed.retain(|_, observed| observed.requested_at + OBSERVED_MESSAGE_TIMEOUT_MS > now);
+            let ids = self.observed.iter()
+                .filter(|observed| !observed.statuses.values().any(is_finally_accepted))
+                .map(|observed| observed.key().clone())
+                .collect::<Vec<_>>();
+            if ids.is_empty() {
+                continue
+            }
+
+            let validators = self.observer_validators.lock().unwrap().clone();
+            for chunk in ids.chunks(MAX_STATUS_QUERY_MESSAGES) {
+                let query = RempStatusQuery { message_ids: chunk.to_vec() };
+                for validator in &validators {
+                    if let Err(e) = engine.send_remp_status_query(validator.clone(), &query) {
+                        log::warn!("Can't send REMP status query to {}: {}", validator, e);
+                    }
+                }
+            }
+            log::trace!("status_observer_worker: queried statuses of {} messages", ids.len());
+        }
+    }
+
     async fn messages_worker(&self) -> Result<()> {
 
         let engine = self.engine.get().ok_or_else(|| error!("engine was not set"))?;
@@ -421,7 +560,39 @@ impl RempClient {
         Ok(())
     }
 
-    async fn process_remp_receipt(&self, receipt: RempReceipt, source: &Arc<KeyId>) -> Result<()> {
+    // Message is ignored by the validator's collator, but it is kept in the queue
+    // and will be collated in the next blocks
+    async fn process_deferred_notice(&self, notice: RempDeferredNotice, source: &Arc<KeyId>) -> Result<()> {
+        let sub_status = deferred_sub_status(notice.position);
+        log::debug!("REMP message {:x} is deferred by {}: {}", notice.message_id, source, sub_status);
+
+        if self.messages.get(&notice.message_id).is_none() {
+            if let Some(mut observed) = self.observed.get_mut(&notice.message_id) {
+                if !self.observer_validators.lock().unwrap().contains(source) {
+                    fail!("Got sub-status of observed message {:x} from unknown validator {}", notice.message_id, source)
+                }
+                observed.sub_statuses.insert(source.clone(), sub_status);
+                return Ok(())
+            }
+        }
+
+        let guard = self.messages.get(&notice.message_id)
+            .ok_or_else(
+                || error!("Got deferred notice for unknown message with id {:x} from {}", notice.message_id, source)
+            )?;
+        if guard.val().validators.get(source).is_none() {
+            fail!("Message {:x} doesn't have validator {} in their set", notice.message_id, source)
+        }
+        let engine = self.engine.get().ok_or_else(|| error!("engine was not set"))?;
+        engine.process_remp_msg_sub_status_in_ext_db(&notice.message_id, source, &sub_status).await
+    }
+
+    async fn process_remp_receipt(
+        &self,
+        receipt: RempReceipt,
+        source: &Arc<KeyId>,
+        signed: Option<&SignedCombinedReceipt>
+    ) -> Result<()> {
         #[cfg(feature = "telemetry")]
         let engine = self.engine.get().ok_or_else(|| error!("engine was not set"))?;
         #[cfg(feature = "telemetry")]
@@ -434,24 +605,40 @@ impl RempClient {
         
         let message_id = receipt.message_id().clone();
 
+        if self.messages.get(&message_id).is_none() {
+            if let Some(mut observed) = self.observed.get_mut(&message_id) {
+                if !self.observer_validators.lock().unwrap().contains(source) {
+                    fail!("Got status of observed message {:x} from unknown validator {}", message_id, source)
+                }
+                // deferred notice may come before the ignored status itself
+                if !matches!(receipt.status(), RempMessageStatus::TonNode_RempIgnored(_)) {
+                    observed.sub_statuses.remove(source);
+                }
+                observed.statuses.insert(source.clone(), receipt.status().clone());
+                return Ok(())
+            }
+        }
+
         let guard = self.messages.get(&message_id)
             .ok_or_else(
                 || error!("Got receipt for unknown message with id {:x} from {}", message_id, source)
             )?;
         let message = guard.val();
 
-        // self.check_receipt_signature(
-        //     &signed_receipt,
-        //     source,
-        //     receipt.source_id(),
-        //     message
-        // ).map_err(|e| error!("Failed to check receipt's signature: {}", e))?;
-
         let validator_info = message.validators.get(source)
             .ok_or_else(|| error!(
                 "Message {:x} doesn't have validator {} in their set",
                 message.message.id(), source
             ))?;
+        // Signed package is kept as the receipt's signature: it is a proof of the status
+        let signature = match signed {
+            Some(signed) => {
+                Self::check_receipt_signature(signed, &receipt, source, validator_info)
+                    .map_err(|e| error!("Failed to check receipt's signature: {}", e))?;
+                signed.serialize()
+            }
+            None => vec!()
+        };
         validator_info.got_receipt_from.store(true, Ordering::Relaxed);
 
         let rejected = is_finally_rejected(receipt.status());
@@ -467,9 +654,6 @@ impl RempClient {
         #[cfg(feature = "telemetry")]
         let status_short_name = remp_status_short_name(&receipt);
 
-        //let signature = signed_receipt.only().signature.0.try_into()
-        //    .map_err(|_| error!("signed_receipt.signature has invalid length"))?;
-
         #[cfg(feature = "telemetry")]
         let rt = ReceiptTelemetry {
             status: status_short_name,
@@ -480,7 +664,7 @@ impl RempClient {
         self.new_processing_status(
             &message_id, 
             receipt, 
-            vec!(), //signature, 
+            signature,
             die_soon, 
             #[cfg(feature = "telemetry")]
             Some(rt)
@@ -621,7 +805,7 @@ impl RempClient {
             fail!("Can't process REMP message because node is out of sync");
         }
 
-        let (real_id, message) = create_ext_message(&raw_message)?;
+        let (real_id, message) = create_ext_message_with_limit(&raw_message, engine.remp_max_message_size())?;
         if real_id != id {
             fail!("Given message id {:x} is not equal calculated one {:x}", id, real_id);
         }
@@ -636,6 +820,7 @@ impl RempClient {
                 }
             }
         }
+        check_ext_message_size(raw_message.len(), engine.remp_max_message_size_for_workchain(dst_wc))?;
 
         let dst_address = message.int_dst_account_id()
             .ok_or_else(|| error!("Can't get standart destination address from message"))?;
@@ -644,7 +829,8 @@ impl RempClient {
 
         let last_mc_state = engine.load_last_applied_mc_state().await?;
         if !self.skip_run_local {
-            Self::run_local(&account, &last_mc_state, message, &id, engine.now())?;
+            let config = engine.config_params_with_overrides(last_mc_state.config_params()?, dst_wc)?;
+            Self::run_local(&account, &last_mc_state, config, message, &id, engine.now())?;
         }
 
         // Build RempMessage struct
@@ -826,7 +1012,7 @@ impl RempClient {
                 if !validators.contains_key(&key) {
                     let val = ValidatorInfo {
                         got_receipt_from: Arc::new(AtomicBool::new(false)),
-                        //pub_key: Ed25519KeyOption::from_public_key(v.public_key.key_bytes()),
+                        pub_key: Ed25519KeyOption::from_public_key(v.public_key.key_bytes()),
                     };
                     validators.insert(key, val);
                 }
@@ -848,15 +1034,14 @@ impl RempClient {
     fn run_local(
         account: &ShardAccount,
         last_mc_state: &ShardStateStuff,
+        config: ConfigParams,
         message: Message,
         id: &UInt256,
         block_utime: u32,
     ) -> Result<()> {
         log::trace!("run_local {:x}", id);
 
-        let last_mc_state_extra = last_mc_state.state()?.read_custom()?
-            .ok_or_else(|| error!("State for {} doesn't have McStateExtra", last_mc_state.block_id()))?;
-        let config = BlockchainConfig::with_config(last_mc_state_extra.config)?;
+        let config = BlockchainConfig::with_config(config)?;
 
         let params = ExecuteParams {
             state_libs: last_mc_state.state()?.libraries().clone().inner(),
@@ -1015,6 +1200,7 @@ impl RempClient {
                  // TODO support callback
                 engine.update_validators(to_resolve, to_delete).await?;
                 *validators = new;
+                self.set_observer_validators(validators);
                 log::trace!("resolve_validators  key block {}  done", block.id());
             } else {
                 log::trace!("resolve_validators  no one change  key block {}", block.id());
@@ -1023,34 +1209,21 @@ impl RempClient {
         Ok(())
     }
 
-    /*fn check_receipt_signature(
-        &self,
-        signed_receipt: &RempSignedReceipt,
+    fn check_receipt_signature(
+        signed: &SignedCombinedReceipt,
+        receipt: &RempReceipt,
         source_adnl_id: &Arc<KeyId>,
-        source_sign_key_id: &UInt256,
-        message: &RempMessageHistory
+        validator_info: &ValidatorInfo,
     ) -> Result<()> {
-        let validator_info = message.validators.get(source_adnl_id)
-            .ok_or_else(|| error!(
-                "Message {:x} doesn't have validator {} in their set",
-                message.message.id(), source_adnl_id
-            ))?;
-
-        if source_sign_key_id.as_slice() != validator_info.pub_key.id().data() {
+        if receipt.source_id().as_slice() != validator_info.pub_key.id().data() {
             fail!(
-                "Receipt for message {} from {} was signed wrong key {:x} (we know {})",
-                message.message.id(), source_adnl_id,
-                source_sign_key_id, validator_info.pub_key.id()
+                "Receipt for message {:x} from {} was signed wrong key {:x} (we know {})",
+                receipt.message_id(), source_adnl_id,
+                receipt.source_id(), validator_info.pub_key.id()
             );
         }
-
-        // check receipt's signature
-        validator_info.pub_key.verify(signed_receipt.receipt(), signed_receipt.signature())?;
-
-        validator_info.got_receipt_from.store(true, Ordering::Relaxed);
-
-        Ok(())
-    }*/
+        signed.verify(validator_info.pub_key.as_ref())
+    }
 }
 
 #[cfg(feature = "telemetry")]
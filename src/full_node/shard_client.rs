@@ -561,7 +561,9 @@ pub async fn process_block_broadcast(
             broadcast.id(),
             proof_data,
             !is_master
-        )?;
+        ).map_err(|e| NodeError::ProtocolViolation(
+            format!("Malformed proof in broadcast with block {}: {}", broadcast.id(), e)
+        ))?;
         let (virt_block, _) = proof.virtualize_block()?;
         let block_info = virt_block.read_info()?;
         let prev_key_block_seqno = block_info.prev_key_block_seqno();
@@ -592,7 +594,10 @@ pub async fn process_block_broadcast(
 
         proof_opt = Some(proof);
 
-        block = BlockStuff::deserialize_block_checked(broadcast.id().clone(), broadcast.data())?;
+        block = BlockStuff::deserialize_block_checked(broadcast.id().clone(), broadcast.data())
+            .map_err(|e| NodeError::ProtocolViolation(
+                format!("Malformed block in broadcast {}: {}", broadcast.id(), e)
+            ))?;
     } else {
         fail!("Invalid block or queue broadcast {} - it doesn't have both proof and target_wc",
             broadcast.id());
@@ -698,7 +703,9 @@ fn validate_brodcast(
     }
 
     // extract signatures - build ton_block::BlockSignaturesPure
-    let blk_pure_signatures = broadcast.extract_signatures()?;
+    let blk_pure_signatures = broadcast.extract_signatures().map_err(|e| NodeError::ProtocolViolation(
+        format!("Malformed signatures in broadcast with block {}: {}", block_id, e)
+    ))?;
 
     // Check signatures
     let checked_data = ton_block::Block::build_data_for_sign(
@@ -707,7 +714,7 @@ fn validate_brodcast(
     );
     let total_weight: u64 = subset.validators.iter().map(|v| v.weight).sum();
     let weight = check_crypto_signatures(&blk_pure_signatures, &subset.validators, &checked_data)
-        .map_err(|err| NodeError::InvalidData(
+        .map_err(|err| NodeError::ProtocolViolation(
             format!("Bad signatures in broadcast with block {}: {}", block_id, err)
         ))?;
 
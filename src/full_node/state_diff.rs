@@ -0,0 +1,240 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{
+    engine_traits::EngineOperations,
+    network::control_queries::{ExportStateDiff, ImportStateDiff, MAX_STATE_DIFF_PART},
+    shard_state::ShardStateStuff
+};
+
+use std::{io::SeekFrom, path::{Path, PathBuf}, sync::Arc};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use ton_block::{BlockIdExt, Deserializable, MerkleUpdate, Serializable};
+use ton_types::{
+    error, fail, read_single_root_boc, write_boc, BuilderData, Cell, Result, UInt256
+};
+
+#[cfg(test)]
+#[path = "../tests/test_state_diff.rs"]
+mod tests;
+
+pub const STATE_DIFFS_DIR: &str = "state_diffs";
+
+/// Difference between two states of a shard: Merkle update from the base state (a persistent
+/// state both nodes have) to the target state. It is much smaller than the full target
+/// state, so a lagging node of the same operator may be recovered by importing the diff
+/// exported by an up-to-date node instead of downloading the whole state.
+pub struct StateDiff {
+    base_id: BlockIdExt,
+    target_id: BlockIdExt,
+    update: MerkleUpdate,
+}
+
+impl StateDiff {
+
+    pub fn with_roots(
+        base_id: BlockIdExt,
+        base_root: &Cell,
+        target_id: BlockIdExt,
+        target_root: &Cell
+    ) -> Result<Self> {
+        if base_id.shard() != target_id.shard() {
+            fail!("State diff can't be built between different shards: {} and {}", base_id, target_id)
+        }
+        if base_id.seq_no() >= target_id.seq_no() {
+            fail!("State diff target {} must be newer than base {}", target_id, base_id)
+        }
+        let update = MerkleUpdate::create(base_root, target_root)?;
+        Ok(Self { base_id, target_id, update })
+    }
+
+    pub fn base_id(&self) -> &BlockIdExt {
+        &self.base_id
+    }
+
+    pub fn target_id(&self) -> &BlockIdExt {
+        &self.target_id
+    }
+
+    /// Applies diff to the base state root, the result is checked against the target id
+    pub fn apply_to_root(&self, base_root: &Cell) -> Result<Cell> {
+        if base_root.repr_hash() != *self.base_id.root_hash() {
+            fail!("State root {:x} doesn't correspond to diff base {}", base_root.repr_hash(), self.base_id)
+        }
+        let root = self.update.apply_for(base_root)?;
+        if root.repr_hash() != *self.target_id.root_hash() {
+            fail!("State root {:x} calculated from diff doesn't correspond to target {}",
+                root.repr_hash(), self.target_id)
+        }
+        Ok(root)
+    }
+
+    pub fn write_to_bytes(&self) -> Result<Vec<u8>> {
+        let mut root = BuilderData::new();
+        root.checked_append_reference(self.base_id.serialize()?)?;
+        root.checked_append_reference(self.target_id.serialize()?)?;
+        root.checked_append_reference(self.update.serialize()?)?;
+        write_boc(&root.into_cell()?)
+    }
+
+    pub fn construct_from_bytes(data: &[u8]) -> Result<Self> {
+        let root = read_single_root_boc(data)?;
+        if root.references_count() != 3 {
+            fail!("Wrong state diff format: {} references in root", root.references_count())
+        }
+        Ok(Self {
+            base_id: BlockIdExt::construct_from_cell(root.reference(0)?)?,
+            target_id: BlockIdExt::construct_from_cell(root.reference(1)?)?,
+            update: MerkleUpdate::construct_from_cell(root.reference(2)?)?,
+        })
+    }
+
+    pub fn file_name(base_root_hash: &UInt256, target_root_hash: &UInt256) -> String {
+        format!("{:x}_{:x}.boc", base_root_hash, target_root_hash)
+    }
+}
+
+fn find_block_id(engine: &dyn EngineOperations, root_hash: &UInt256) -> Result<BlockIdExt> {
+    engine.find_full_block_id(root_hash)?
+        .ok_or_else(|| error!("Block with root hash {:x} is not found", root_hash))
+}
+
+/// Builds diff between persistent state `base_root_hash` and state `target_root_hash`
+/// and saves it into `state_diffs` directory of the DB. Returns path to the file.
+pub async fn export_state_diff(
+    engine: &Arc<dyn EngineOperations>,
+    base_root_hash: &UInt256,
+    target_root_hash: &UInt256
+) -> Result<PathBuf> {
+    let base_id = find_block_id(engine.as_ref(), base_root_hash)?;
+    let target_id = find_block_id(engine.as_ref(), target_root_hash)?;
+    let base_handle = engine.load_block_handle(&base_id)?
+        .ok_or_else(|| error!("Cannot load handle for block {}", base_id))?;
+    if !base_handle.has_persistent_state() {
+        fail!("State diff base {} must be a persistent state", base_id)
+    }
+    let base = engine.load_state(&base_id).await?;
+    let target = engine.load_state(&target_id).await?;
+
+    let now = std::time::Instant::now();
+    let data = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+        StateDiff::with_roots(
+            base.block_id().clone(), base.root_cell(), target.block_id().clone(), target.root_cell()
+        )?.write_to_bytes()
+    }).await??;
+    log::info!(
+        "State diff {} -> {} built, {} bytes, TIME {}ms",
+        base_id, target_id, data.len(), now.elapsed().as_millis()
+    );
+
+    let path = state_diff_path(engine.as_ref(), base_root_hash, target_root_hash).await?;
+    tokio::fs::write(&path, &data).await?;
+    Ok(path)
+}
+
+async fn state_diff_path(
+    engine: &dyn EngineOperations,
+    base_root_hash: &UInt256,
+    target_root_hash: &UInt256
+) -> Result<PathBuf> {
+    let dir = Path::new(engine.db_root_dir()?).join(STATE_DIFFS_DIR);
+    tokio::fs::create_dir_all(&dir).await?;
+    Ok(dir.join(StateDiff::file_name(base_root_hash, target_root_hash)))
+}
+
+/// Returns part of the diff for the control query. The diff is built by the query
+/// of the first part and is read from the DB for the next ones.
+pub async fn export_state_diff_part(
+    engine: &Arc<dyn EngineOperations>,
+    query: &ExportStateDiff
+) -> Result<Vec<u8>> {
+    let path = if query.offset == 0 {
+        export_state_diff(engine, &query.base_root_hash, &query.target_root_hash).await?
+    } else {
+        state_diff_path(engine.as_ref(), &query.base_root_hash, &query.target_root_hash).await?
+    };
+    let mut file = tokio::fs::File::open(&path).await
+        .map_err(|e| error!("Cannot open state diff {}: {}", path.display(), e))?;
+    file.seek(SeekFrom::Start(query.offset)).await?;
+    let mut part = Vec::new();
+    file.take(query.max_size.min(MAX_STATE_DIFF_PART) as u64).read_to_end(&mut part).await?;
+    Ok(part)
+}
+
+/// Writes part of the diff got by the control query; parts must go in order. The diff is
+/// imported when the last part is written, its state is returned.
+pub async fn import_state_diff_part(
+    engine: &Arc<dyn EngineOperations>,
+    query: &ImportStateDiff
+) -> Result<Option<Arc<ShardStateStuff>>> {
+    let path = state_diff_path(engine.as_ref(), &query.base_root_hash, &query.target_root_hash).await?
+        .with_extension("part");
+    let mut file = if query.offset == 0 {
+        tokio::fs::File::create(&path).await?
+    } else {
+        tokio::fs::OpenOptions::new().append(true).open(&path).await
+            .map_err(|e| error!("State diff {} import is not started: {}", path.display(), e))?
+    };
+    let received = file.metadata().await?.len();
+    if received != query.offset {
+        fail!("State diff part offset {} doesn't match received {} bytes", query.offset, received)
+    }
+    file.write_all(&query.data).await?;
+    file.flush().await?;
+    if !query.last {
+        return Ok(None)
+    }
+    drop(file);
+    let diff_path = path.with_extension("boc");
+    tokio::fs::rename(&path, &diff_path).await?;
+    let state = import_state_diff(engine, &diff_path).await?;
+    if state.block_id().root_hash() != &query.target_root_hash {
+        fail!("Imported state {} is not the requested one {:x}", state.block_id(), query.target_root_hash)
+    }
+    Ok(Some(state))
+}
+
+/// Imports diff from the file: the base persistent state must be present in the node,
+/// the target block must be known (its handle created), the state is stored for it.
+pub async fn import_state_diff(
+    engine: &Arc<dyn EngineOperations>,
+    path: &Path
+) -> Result<Arc<ShardStateStuff>> {
+    let data = tokio::fs::read(path).await?;
+    let diff = StateDiff::construct_from_bytes(&data)?;
+    let target_handle = engine.load_block_handle(diff.target_id())?.ok_or_else(
+        || error!("Block {} is unknown, download it before importing its state", diff.target_id())
+    )?;
+    if target_handle.has_state() {
+        return engine.load_state(diff.target_id()).await
+    }
+    let base = engine.load_state(diff.base_id()).await?;
+
+    let now = std::time::Instant::now();
+    let engine_cloned = engine.clone();
+    let state = tokio::task::spawn_blocking(move || -> Result<Arc<ShardStateStuff>> {
+        let root = diff.apply_to_root(base.root_cell())?;
+        ShardStateStuff::from_state_root_cell(
+            diff.target_id().clone(),
+            root,
+            #[cfg(feature = "telemetry")]
+            engine_cloned.engine_telemetry(),
+            engine_cloned.engine_allocated()
+        )
+    }).await??;
+    log::info!(
+        "State {} imported from diff {}, TIME {}ms",
+        state.block_id(), path.display(), now.elapsed().as_millis()
+    );
+    engine.store_state(&target_handle, state).await
+}
@@ -11,12 +11,260 @@
 * limitations under the License.
 */
 
-use crate::network::full_node_client::FullNodeOverlayClient;
+use crate::network::{
+    full_node_client::FullNodeOverlayClient, neighbours::Neighbour,
+    transfer_cache::TransferProgress
+};
 
-use std::sync::Arc;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque}, sync::{Arc, Mutex}, time::{Duration, Instant}
+};
 use ton_block::BlockIdExt;
 use ton_types::{error, fail, KeyId, Result};
 
+#[cfg(test)]
+#[path = "../tests/test_state_helper.rs"]
+mod tests;
+
+const PART_MAX_SIZE: usize = 1 << 20;
+// Peers to download a state from in parallel and queries to find them
+const MAX_PEERS: usize = 4;
+const MAX_PEER_LOOKUPS: usize = 3 * MAX_PEERS;
+// Peer is not used anymore after the number of consecutive errors
+const MAX_PEER_ERRORS: usize = 10;
+
+/// Schedule of parts of a large object downloaded from several peers at once.
+/// The size of the object is not known in advance: the first part shorter than
+/// the maximum size is the last one. A failed part is returned to the queue,
+/// a part which is downloaded much longer than usual is given to an idle peer too,
+/// the first answer wins. Parts are moved to the object's data as soon as all parts
+/// before them are got, so only the parts downloaded out of order are kept apart.
+struct PartsDownload {
+    part_size: usize,
+    next: usize,
+    retry: VecDeque<usize>,
+    // start time and number of peers downloading the part
+    in_flight: HashMap<usize, (Instant, usize)>,
+    // parts got out of order
+    done: BTreeMap<usize, Vec<u8>>,
+    // data of the parts got from the start and their number
+    data: Vec<u8>,
+    assembled: usize,
+    last: Option<usize>,
+    // average download time of a part, seconds
+    part_time: Option<f64>,
+}
+
+impl PartsDownload {
+
+    fn new(part_size: usize) -> Self {
+        Self {
+            part_size,
+            next: 0,
+            retry: VecDeque::new(),
+            in_flight: HashMap::new(),
+            done: BTreeMap::new(),
+            data: Vec::new(),
+            assembled: 0,
+            last: None,
+            part_time: None,
+        }
+    }
+
+    fn needed(&self, part: usize) -> bool {
+        (part >= self.assembled) && 
+        self.last.map_or(true, |last| part <= last) && 
+        !self.done.contains_key(&part)
+    }
+
+    fn assemble(&mut self) {
+        while self.last.map_or(true, |last| self.assembled <= last) {
+            match self.done.remove(&self.assembled) {
+                Some(part) => {
+                    self.data.extend_from_slice(&part);
+                    self.assembled += 1;
+                }
+                None => break
+            }
+        }
+    }
+
+    /// Part for an idle peer: failed one, next one or a slow one
+    fn take(&mut self, now: Instant) -> Option<usize> {
+        let mut part = None;
+        while let Some(retry) = self.retry.pop_front() {
+            if self.needed(retry) {
+                part = Some(retry);
+                break
+            }
+        }
+        while (self.next < self.assembled) || self.done.contains_key(&self.next) {
+            self.next += 1;
+        }
+        if part.is_none() && self.needed(self.next) {
+            part = Some(self.next);
+            self.next += 1;
+        }
+        if part.is_none() {
+            let slow_time = Duration::from_secs_f64(2.0 * self.part_time?);
+            part = self.in_flight.iter()
+                .filter(|(_, (start, peers))| {
+                    (*peers == 1) && (now.saturating_duration_since(*start) > slow_time)
+                })
+                .min_by_key(|(_, (start, _))| *start)
+                .map(|(part, _)| *part);
+            if let Some(part) = part {
+                log::info!("Part {} is downloaded too long, give it to another peer", part);
+            }
+        }
+        let part = part?;
+        self.in_flight.entry(part).or_insert((now, 0)).1 += 1;
+        Some(part)
+    }
+
+    fn complete(&mut self, part: usize, data: Vec<u8>, elapsed: Duration) {
+        self.in_flight.remove(&part);
+        if !self.needed(part) {
+            return
+        }
+        let elapsed = elapsed.as_secs_f64();
+        self.part_time = Some(self.part_time.map_or(elapsed, |time| 0.8 * time + 0.2 * elapsed));
+        if data.len() < self.part_size {
+            self.last = Some(self.last.map_or(part, |last| last.min(part)));
+        }
+        self.done.insert(part, data);
+        self.assemble();
+    }
+
+    fn fail(&mut self, part: usize) {
+        if let Some((_, peers)) = self.in_flight.get_mut(&part) {
+            *peers -= 1;
+            if *peers > 0 {
+                return
+            }
+            self.in_flight.remove(&part);
+        }
+        if self.needed(part) {
+            self.retry.push_back(part);
+        }
+    }
+
+    /// Adds parts of an interrupted download keyed by offset, returns their size.
+    /// Parts got from the start are saved as one piece at zero offset
+    fn resume(&mut self, saved: BTreeMap<usize, Vec<u8>>) -> usize {
+        let mut size = 0;
+        for (offset, data) in saved {
+            if (offset == 0) && (data.len() > self.part_size) {
+                self.assembled = data.len() / self.part_size;
+                if data.len() % self.part_size != 0 {
+                    self.last = Some(self.assembled);
+                    self.assembled += 1;
+                }
+                size += data.len();
+                self.data = data;
+                continue
+            }
+            if (offset % self.part_size != 0) || (data.len() > self.part_size) {
+                continue
+            }
+            let part = offset / self.part_size;
+            if data.len() < self.part_size {
+                self.last = Some(self.last.map_or(part, |last| last.min(part)));
+            }
+            size += data.len();
+            self.done.insert(part, data);
+        }
+        self.assemble();
+        size
+    }
+
+    /// Downloaded parts keyed by offset
+    fn into_parts(self) -> BTreeMap<usize, Vec<u8>> {
+        let part_size = self.part_size;
+        let mut parts: BTreeMap<_, _> = self.done.into_iter()
+            .map(|(part, data)| (part * part_size, data))
+            .collect();
+        if !self.data.is_empty() {
+            parts.insert(0, self.data);
+        }
+        parts
+    }
+
+    fn is_finished(&self) -> bool {
+        self.last.map_or(false, |last| self.assembled > last)
+    }
+
+    fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+async fn download_parts_worker(
+    id: &BlockIdExt,
+    msg_queue_for: Option<i32>,
+    master_id: &BlockIdExt,
+    overlay: &dyn FullNodeOverlayClient,
+    peer: Arc<Neighbour>,
+    parts: &Mutex<PartsDownload>,
+    progress: &TransferProgress,
+    check_stop: &(dyn Fn() -> Result<()> + Sync + Send),
+) -> Result<()> {
+    let started = Instant::now();
+    let mut attempt = 0;
+    let mut errors = 0;
+    let mut bytes = 0;
+    loop {
+        check_stop()?;
+        let (part, part_size) = {
+            let mut parts = parts.lock().unwrap();
+            if parts.is_finished() {
+                break
+            }
+            (parts.take(Instant::now()), parts.part_size)
+        };
+        let Some(part) = part else {
+            futures_timer::Delay::new(Duration::from_millis(100)).await;
+            continue
+        };
+        let now = Instant::now();
+        let result = overlay.download_persistent_state_part(
+            id, msg_queue_for, master_id, part * part_size, part_size, peer.clone(), attempt
+        ).await;
+        match result {
+            Ok(data) => {
+                log::info!(
+                    "download_persistent_state {}: got part offset: {} from {}",
+                    id.shard(), part * part_size, peer.id()
+                );
+                errors = 0;
+                bytes += data.len();
+                progress.add_received(data.len());
+                parts.lock().unwrap().complete(part, data, now.elapsed());
+            }
+            Err(e) => {
+                parts.lock().unwrap().fail(part);
+                errors += 1;
+                attempt += 1;
+                log::error!(
+                    "download_persistent_state_part {} from {}: {}, errors in a row: {}",
+                    id.shard(), peer.id(), e, errors
+                );
+                if errors >= MAX_PEER_ERRORS {
+                    log::warn!("Stop downloading {} from {}", id.shard(), peer.id());
+                    break
+                }
+                futures_timer::Delay::new(Duration::from_millis(100)).await;
+            }
+        }
+    }
+    log::info!(
+        "download_persistent_state {}: got {} bytes from {}, speed {} KB/sec",
+        id.shard(), bytes, peer.id(), bytes as u64 / 1024 / started.elapsed().as_secs().max(1)
+    );
+    Ok(())
+}
+
 pub async fn download_persistent_state(
     id: &BlockIdExt,
     msg_queue_for: Option<i32>,
@@ -24,12 +272,14 @@ pub async fn download_persistent_state(
     overlay: &dyn FullNodeOverlayClient,
     active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>,
     attempts: Option<usize>,
+    progress: &TransferProgress,
     check_stop: &(dyn Fn() -> Result<()> + Sync + Send),
 ) -> Result<Arc<Vec<u8>>> {
     let mut result = None;
     for _ in 0..10 {
         match download_persistent_state_iter(
-            id, msg_queue_for, master_id, overlay, active_peers, attempts.clone(), check_stop,
+            id, msg_queue_for, master_id, overlay, active_peers, attempts.clone(), progress, 
+            check_stop,
         ).await {
             Err(e) => {
                 log::warn!("download_persistent_state_iter err: {}", e);
@@ -52,6 +302,7 @@ async fn download_persistent_state_iter(
     overlay: &dyn FullNodeOverlayClient,
     active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>,
     mut attempts: Option<usize>,
+    progress: &TransferProgress,
     check_stop: &(dyn Fn() -> Result<()> + Sync + Send),
 ) -> Result<Arc<Vec<u8>>> {
 
@@ -85,54 +336,86 @@ async fn download_persistent_state_iter(
         futures_timer::Delay::new(std::time::Duration::from_millis(100)).await;
     };
 
-    // Download
+    // Download parts from several peers in parallel
     log::info!("download_persistent_state: start: id: {}, master_id: {}", id, master_id);
     let now = std::time::Instant::now();
 
-    let max_size = 1 << 20; // part max size
-    let mut offset = 0;
-    let mut peer_attempt = 0;
-    let mut part_attempt = 0;
-    let mut errors = 0;
-    let mut state_bytes = vec!();
-    loop {
-        check_stop()?;
-        let result = overlay.download_persistent_state_part(
-            id, msg_queue_for, master_id, offset, max_size, peer.clone(), peer_attempt
-        ).await;
-        match result {
-            Ok(next_bytes) => {
-                part_attempt = 0;
-                let len = next_bytes.len();
-                state_bytes.extend_from_slice(&next_bytes);
-                //if (offset / max_size) % 10 == 0 {
-                    log::info!("download_persistent_state {}: got part offset: {}", id.shard(), offset);
-                //}
-                if len < max_size {
-                    log::info!("the total length of {} {} might be {}", descr, id.shard(), offset + len);
-                    break
-                }
-                offset += max_size;
-            }
-            Err(e) => {
-                errors += 1;
-                part_attempt += 1;
-                peer_attempt += 1;
-                log::error!(
-                    "download_persistent_state_part {}: {}, attempt: {}, total errors: {}",
-                    id.shard(), e, part_attempt, errors
-                );
-                if part_attempt > 10 {
-                    fail!("Error download_persistent_state_part after {} attempts: {}", 
-                        part_attempt, e)
-                }
-                futures_timer::Delay::new(std::time::Duration::from_millis(100)).await;
-            }
-        }
+    // States are the same on all nodes, so parts of an interrupted download 
+    // are taken whatever peers they were got from
+    let transfer_id = format!("state {} {:?} of {}", id, msg_queue_for, master_id);
+    let mut parts = PartsDownload::new(PART_MAX_SIZE);
+    progress.add_resumed(parts.resume(overlay.transfer_cache().resume(&transfer_id)));
+    let parts = Mutex::new(parts);
+    let result = download_parts(
+        id, msg_queue_for, master_id, overlay, active_peers, peer, &descr, &parts, progress, 
+        check_stop
+    ).await;
+    if let Err(e) = result {
+        overlay.transfer_cache().save(transfer_id, parts.into_inner().unwrap().into_parts());
+        return Err(e)
     }
+    let state_bytes = parts.into_inner().unwrap().into_data();
+    log::info!("the total length of {} {} is {}", descr, id.shard(), state_bytes.len());
 
     log::info!("download_persistent_state: DOWNLOADED {} {}sec, id: {}, master_id: {} ", 
         descr, now.elapsed().as_secs(), id, master_id);
 
     Ok(Arc::new(state_bytes))
+}
+
+async fn download_parts(
+    id: &BlockIdExt,
+    msg_queue_for: Option<i32>,
+    master_id: &BlockIdExt,
+    overlay: &dyn FullNodeOverlayClient,
+    active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>,
+    peer: Arc<Neighbour>,
+    descr: &str,
+    parts: &Mutex<PartsDownload>,
+    progress: &TransferProgress,
+    check_stop: &(dyn Fn() -> Result<()> + Sync + Send),
+) -> Result<()> {
+    let mut peers = vec!(peer.id().clone());
+    let mut workers = FuturesUnordered::new();
+    workers.push(download_parts_worker(
+        id, msg_queue_for, master_id, overlay, peer, parts, progress, check_stop
+    ));
+    let mut lookups = FuturesUnordered::new();
+    let mut lookups_left = MAX_PEER_LOOKUPS;
+    lookups.push(overlay.check_persistent_state(id, msg_queue_for, master_id, active_peers));
+    loop {
+        if parts.lock().unwrap().is_finished() {
+            break
+        }
+        tokio::select! {
+            Some(result) = lookups.next() => {
+                match result {
+                    Ok(Some(peer)) if !peers.contains(peer.id()) => {
+                        log::info!(
+                            "download_persistent_state {}: one more peer {}", id.shard(), peer.id()
+                        );
+                        peers.push(peer.id().clone());
+                        workers.push(download_parts_worker(
+                            id, msg_queue_for, master_id, overlay, peer, parts, progress, 
+                            check_stop
+                        ));
+                    }
+                    Ok(_) => (),
+                    Err(e) => log::trace!(
+                        "check_persistent_state descr {} {}: {}", descr, id.shard(), e
+                    )
+                }
+                lookups_left -= 1;
+                if (peers.len() < MAX_PEERS) && (lookups_left > 0) {
+                    lookups.push(
+                        overlay.check_persistent_state(id, msg_queue_for, master_id, active_peers)
+                    );
+                }
+            }
+            Some(result) = workers.next() => result?,
+            else => fail!("Can't download {} {}: all peers failed", descr, id.shard())
+        }
+    }
+    log::info!("{} {} is downloaded from {} peers", descr, id.shard(), peers.len());
+    Ok(())
 }
\ No newline at end of file
@@ -26,7 +26,9 @@ use std::{
 };
 use storage::{
     StorageAlloc, TimeChecker,
-    archives::{archive_manager::ArchiveManager, package_entry_id::PackageEntryId},
+    archives::{
+        ArchivesGcReport, GcTotals, archive_manager::ArchiveManager, package_entry_id::PackageEntryId
+    },
     block_handle_db::{self, BlockHandle, BlockHandleDb, BlockHandleStorage}, 
     block_info_db::BlockInfoDb, db::{filedb::FileDb, rocksdb::RocksDb}, 
     node_state_db::NodeStateDb, shard_top_blocks_db::ShardTopBlocksDb, 
@@ -901,12 +903,11 @@ impl InternalDb {
         self.shard_state_persistent_db.get_file_size(id).await
     }
 
-    pub async fn shard_state_persistent_gc(
+    fn find_expired_persistent_states(
         &self,
         calc_ttl: impl Fn(u32) -> (u32, bool),
         zerostate_id: &BlockIdExt,
-    ) -> Result<()> {
-        let _tc = TimeChecker::new(format!("shard_state_persistent_gc"), 5000);
+    ) -> Result<HashSet<BlockIdExt>> {
         let mut for_delete = HashSet::new();
         self.shard_state_persistent_db.for_each_key(&mut |key| {
 
@@ -945,7 +946,16 @@ impl InternalDb {
             }
             Ok(true)
         })?;
+        Ok(for_delete)
+    }
 
+    pub async fn shard_state_persistent_gc(
+        &self,
+        calc_ttl: impl Fn(u32) -> (u32, bool),
+        zerostate_id: &BlockIdExt,
+    ) -> Result<()> {
+        let _tc = TimeChecker::new(format!("shard_state_persistent_gc"), 5000);
+        let for_delete = self.find_expired_persistent_states(calc_ttl, zerostate_id)?;
         for id in for_delete {
             match self.shard_state_persistent_db.delete_file(&id).await {
                 Ok(_) => log::debug!("shard_state_persistent_gc: {:x} deleted", id.root_hash()),
@@ -956,6 +966,20 @@ impl InternalDb {
         Ok(())
     }
 
+    /// Counts persistent states `shard_state_persistent_gc` would delete, nothing is deleted
+    pub async fn shard_state_persistent_gc_report(
+        &self,
+        calc_ttl: impl Fn(u32) -> (u32, bool),
+        zerostate_id: &BlockIdExt,
+    ) -> Result<GcTotals> {
+        let _tc = TimeChecker::new(format!("shard_state_persistent_gc_report"), 5000);
+        let mut totals = GcTotals::default();
+        for id in self.find_expired_persistent_states(calc_ttl, zerostate_id)? {
+            totals.add(self.shard_state_persistent_db.get_file_size(&id).await?);
+        }
+        Ok(totals)
+    }
+
     pub fn store_block_prev1(
         &self, 
         handle: &Arc<BlockHandle>, 
@@ -1131,6 +1155,14 @@ impl InternalDb {
         self.save_full_node_state(LAST_UNNEEDED_KEY_BLOCK, last_unneeded_key_block)
     }
 
+    pub async fn archive_gc_report(
+        &self,
+        last_unneeded_key_block: &BlockIdExt
+    ) -> Result<ArchivesGcReport> {
+        let _tc = TimeChecker::new(format!("archive_gc_report {}", last_unneeded_key_block), 300);
+        self.archive_manager.gc_report(last_unneeded_key_block).await
+    }
+
     pub fn assign_mc_ref_seq_no(
         &self, 
         handle: &Arc<BlockHandle>, 
@@ -1203,6 +1235,10 @@ impl InternalDb {
         Ok(&self.config.db_directory)
     }
 
+    pub fn report_sync_statistics(&self) {
+        self.db.report_sync_statistics()
+    }
+
     pub fn adjust_states_gc_interval(&self, interval_ms: u32) {
         let prev = self.cells_gc_interval.swap(interval_ms, Ordering::Relaxed);
         log::info!("Adjusted states gc interval {} -> {}", prev, interval_ms);
@@ -0,0 +1,128 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::config::BandwidthLimitsConfig;
+use std::{sync::Mutex, time::{Duration, Instant}};
+
+#[cfg(test)]
+#[path = "tests/test_bandwidth.rs"]
+mod tests;
+
+/// Token bucket of `rate` bytes per second with burst of one second of traffic.
+/// Traffic is debited when it is sent or received, so the bucket may go into debt;
+/// the sender then waits until the debt is paid off.
+pub struct TokenBucket {
+    rate: u64,
+    // available tokens (negative - debt) and time of their calculation
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+
+    pub fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            state: Mutex::new((rate as f64, Instant::now())),
+        }
+    }
+
+    /// Debits `bytes` and returns time to wait before the next transfer
+    pub fn reserve(&self, bytes: usize, now: Instant) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let elapsed = now.saturating_duration_since(state.1).as_secs_f64();
+        let tokens = (state.0 + elapsed * self.rate as f64).min(self.rate as f64) - bytes as f64;
+        *state = (tokens, now);
+        if tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-tokens / self.rate as f64)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrafficClass {
+    /// Public (shard) overlays: block sync, archives, states, external messages
+    Public,
+    /// Private (validator) overlays: consensus and REMP catchains
+    Private,
+}
+
+/// Upload and download limits of the node, a limit of 0 means no limit.
+/// Public traffic waits for both the global and its own limits. Private traffic waits
+/// for its own limit only: it is counted in the global limits but never delayed by them,
+/// so archive serving can't starve validator traffic, it gets the rest of the bandwidth.
+pub struct BandwidthLimiter {
+    upload: Option<TokenBucket>,
+    download: Option<TokenBucket>,
+    public_upload: Option<TokenBucket>,
+    public_download: Option<TokenBucket>,
+    private_upload: Option<TokenBucket>,
+}
+
+impl BandwidthLimiter {
+
+    pub fn new(config: &BandwidthLimitsConfig) -> Self {
+        let bucket = |rate| if rate == 0 { None } else { Some(TokenBucket::new(rate)) };
+        Self {
+            upload: bucket(config.upload_bytes_per_sec),
+            download: bucket(config.download_bytes_per_sec),
+            public_upload: bucket(config.public_upload_bytes_per_sec),
+            public_download: bucket(config.public_download_bytes_per_sec),
+            private_upload: bucket(config.private_upload_bytes_per_sec),
+        }
+    }
+
+    pub async fn upload(&self, class: TrafficClass, bytes: usize) {
+        let delay = match class {
+            TrafficClass::Public => Self::reserve(&[&self.upload, &self.public_upload], bytes),
+            TrafficClass::Private => {
+                Self::reserve(&[&self.upload], bytes);
+                Self::reserve(&[&self.private_upload], bytes)
+            }
+        };
+        Self::wait(delay, "upload", class).await
+    }
+
+    pub async fn download(&self, class: TrafficClass, bytes: usize) {
+        let delay = match class {
+            TrafficClass::Public => Self::reserve(&[&self.download, &self.public_download], bytes),
+            TrafficClass::Private => {
+                Self::reserve(&[&self.download], bytes);
+                Duration::ZERO
+            }
+        };
+        Self::wait(delay, "download", class).await
+    }
+
+    fn reserve(buckets: &[&Option<TokenBucket>], bytes: usize) -> Duration {
+        let now = Instant::now();
+        buckets.iter()
+            .filter_map(|bucket| bucket.as_ref().map(|bucket| bucket.reserve(bytes, now)))
+            .max()
+            .unwrap_or_default()
+    }
+
+    async fn wait(delay: Duration, direction: &'static str, class: TrafficClass) {
+        if delay.is_zero() {
+            return
+        }
+        let class = match class {
+            TrafficClass::Public => "public",
+            TrafficClass::Private => "private",
+        };
+        let labels = [("direction", direction), ("class", class)];
+        metrics::histogram!("network_shaping_delay", delay, &labels);
+        tokio::time::sleep(delay).await
+    }
+}
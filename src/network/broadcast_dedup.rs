@@ -0,0 +1,100 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{collections::{HashMap, VecDeque}, sync::Mutex};
+use ton_types::UInt256;
+
+#[cfg(test)]
+#[path = "tests/test_broadcast_dedup.rs"]
+mod tests;
+
+/// LRU set of hashes of broadcast payloads limited by memory budget.
+/// Payloads are checked before deserialization, so a broadcast repeated by several
+/// neighbours is deserialized and processed once.
+pub struct BroadcastDedup {
+    capacity: usize,
+    cache: Mutex<LruHashes>,
+}
+
+#[derive(Default)]
+struct LruHashes {
+    // hash -> stamp of the last use
+    stamps: HashMap<UInt256, u64>,
+    // hashes in order of use, entries with outdated stamps are skipped on eviction
+    order: VecDeque<(UInt256, u64)>,
+    next_stamp: u64,
+}
+
+impl BroadcastDedup {
+    // hash in the map and in the queue, stamps, tables overhead
+    const ENTRY_SIZE: usize = 128;
+
+    /// `memory_limit` in bytes, 0 - deduplication is disabled
+    pub fn new(memory_limit: usize) -> Self {
+        Self {
+            capacity: memory_limit / Self::ENTRY_SIZE,
+            cache: Mutex::new(LruHashes::default()),
+        }
+    }
+
+    /// Returns true if the payload has been seen already
+    pub fn check(&self, data: &[u8]) -> bool {
+        if self.capacity == 0 {
+            return false
+        }
+        let hash = UInt256::calc_file_hash(data);
+        let (duplicate, len) = {
+            let mut cache = self.cache.lock().unwrap();
+            let duplicate = cache.touch(hash);
+            cache.shrink(self.capacity);
+            (duplicate, cache.stamps.len())
+        };
+        if duplicate {
+            metrics::increment_counter!("broadcast_dedup_hits");
+        } else {
+            metrics::increment_counter!("broadcast_dedup_misses");
+        }
+        metrics::gauge!("broadcast_dedup_memory_bytes", (len * Self::ENTRY_SIZE) as f64);
+        duplicate
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().stamps.len()
+    }
+}
+
+impl LruHashes {
+
+    fn touch(&mut self, hash: UInt256) -> bool {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.order.push_back((hash.clone(), stamp));
+        self.stamps.insert(hash, stamp).is_some()
+    }
+
+    fn shrink(&mut self, capacity: usize) {
+        while self.stamps.len() > capacity {
+            match self.order.pop_front() {
+                Some((hash, stamp)) => if self.stamps.get(&hash) == Some(&stamp) {
+                    self.stamps.remove(&hash);
+                }
+                None => break
+            }
+        }
+        // drop outdated entries left by hits, so the queue doesn't grow unbounded
+        if self.order.len() > 2 * capacity.max(1) {
+            let stamps = &self.stamps;
+            self.order.retain(|(hash, stamp)| stamps.get(hash) == Some(stamp));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{path::{Path, PathBuf}, sync::Arc, time::{Duration, SystemTime}};
+use ton_api::ton::ton_node::Capabilities;
+use ton_types::{error, base64_decode, base64_encode, KeyId, Result};
+
+#[cfg(test)]
+#[path = "tests/test_capabilities_log.rs"]
+mod tests;
+
+pub const CAPABILITIES_LOG_FILE_NAME: &str = "neighbours_capabilities.json";
+
+/// Protocol version and capabilities advertised by a peer
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CapabilitiesObservation {
+    pub version: i32,
+    pub capabilities: i64,
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+/// Log of protocol capabilities advertised by overlay neighbours. Observations are kept
+/// for `RETENTION` and periodically saved to a file, so the picture of a mixed fleet
+/// survives node restarts.
+pub struct CapabilitiesLog {
+    peers: lockfree::map::Map<Arc<KeyId>, CapabilitiesObservation>,
+    path: Option<PathBuf>,
+}
+
+impl CapabilitiesLog {
+
+    pub const RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+    pub const SAVE_PERIOD: Duration = Duration::from_secs(60);
+    pub const MAX_PEERS: usize = 10000;
+
+    /// Creates log, loading observations saved in the directory (if any)
+    pub fn with_dir(dir: Option<&str>) -> Self {
+        let ret = Self {
+            peers: lockfree::map::Map::new(),
+            path: dir.map(|dir| Path::new(dir).join(CAPABILITIES_LOG_FILE_NAME)),
+        };
+        if let Err(e) = ret.load() {
+            log::warn!("Can't load neighbours capabilities: {}", e)
+        }
+        ret
+    }
+
+    pub fn observe(&self, peer: &Arc<KeyId>, capabilities: &Capabilities) {
+        self.observe_at(peer, *capabilities.version(), *capabilities.capabilities(), Self::now())
+    }
+
+    pub fn observe_at(&self, peer: &Arc<KeyId>, version: i32, capabilities: i64, now: u64) {
+        let first_seen = match self.peers.get(peer) {
+            Some(old) if old.val().version == version && old.val().capabilities == capabilities => {
+                old.val().first_seen
+            }
+            Some(old) => {
+                log::info!(
+                    "Neighbour {} changed protocol: version {} -> {}, capabilities {:x} -> {:x}",
+                    peer, old.val().version, version, old.val().capabilities, capabilities
+                );
+                now
+            }
+            None => now
+        };
+        self.peers.insert(
+            peer.clone(),
+            CapabilitiesObservation { version, capabilities, first_seen, last_seen: now }
+        );
+    }
+
+    pub fn get(&self, peer: &Arc<KeyId>) -> Option<CapabilitiesObservation> {
+        self.peers.get(peer).map(|guard| guard.val().clone())
+    }
+
+    pub fn count(&self) -> usize {
+        self.peers.iter().count()
+    }
+
+    /// Removes observations older than `RETENTION` and the oldest ones over `MAX_PEERS`
+    pub fn cleanup(&self, now: u64) {
+        let expire = now.saturating_sub(Self::RETENTION.as_secs());
+        let mut actual = Vec::new();
+        for guard in self.peers.iter() {
+            if guard.val().last_seen < expire {
+                self.peers.remove(guard.key());
+            } else {
+                actual.push((guard.val().last_seen, guard.key().clone()));
+            }
+        }
+        if actual.len() > Self::MAX_PEERS {
+            actual.sort_by_key(|(last_seen, _)| *last_seen);
+            for (_, key) in actual.iter().take(actual.len() - Self::MAX_PEERS) {
+                self.peers.remove(key);
+            }
+        }
+    }
+
+    /// Statistics in JSON: for each peer and for each (version, capabilities) pair
+    pub fn to_json(&self) -> String {
+        let mut peers = serde_json::Map::new();
+        let mut summary = std::collections::BTreeMap::<(i32, i64), u32>::new();
+        for guard in self.peers.iter() {
+            let obs = guard.val();
+            *summary.entry((obs.version, obs.capabilities)).or_default() += 1;
+            peers.insert(
+                guard.key().to_string(),
+                serde_json::json!({
+                    "version": obs.version,
+                    "capabilities": format!("{:#x}", obs.capabilities),
+                    "first_seen": obs.first_seen,
+                    "last_seen": obs.last_seen,
+                })
+            );
+        }
+        let summary = summary.into_iter().map(|((version, capabilities), count)| {
+            serde_json::json!({
+                "version": version,
+                "capabilities": format!("{:#x}", capabilities),
+                "peers": count,
+            })
+        }).collect::<Vec<_>>();
+        format!("{:#}", serde_json::json!({ "summary": summary, "peers": peers }))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(())
+        };
+        self.cleanup(Self::now());
+        let peers = self.peers.iter()
+            .map(|guard| (base64_encode(guard.key().data()), guard.val().clone()))
+            .collect::<std::collections::HashMap<_, _>>();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(&peers)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) if path.exists() => path,
+            _ => return Ok(())
+        };
+        let data = std::fs::read_to_string(path)?;
+        let peers: std::collections::HashMap<String, CapabilitiesObservation> =
+            serde_json::from_str(&data)?;
+        for (key, obs) in peers {
+            let key = base64_decode(&key)?;
+            let key: [u8; 32] = key.as_slice().try_into()
+                .map_err(|_| error!("Wrong neighbour key length {}", key.len()))?;
+            self.peers.insert(KeyId::from_data(key), obs);
+        }
+        self.cleanup(Self::now());
+        Ok(())
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs()
+    }
+
+}
@@ -11,7 +11,12 @@
 * limitations under the License.
 */
 
-use crate::network::node_network::NetworkContext;
+use crate::network::{
+    bandwidth::TrafficClass, catchain_delivery::CatchainDelivery,
+    catchain_relay::RelayedMessage, channel_health::ChannelHealth,
+    neighbours::{PROTOCOL_CAPABILITIES, PROTOCOL_VERSION}, node_network::NetworkContext,
+    send_queue::QosClass
+};
 
 use adnl::{
     declare_counted, 
@@ -21,6 +26,7 @@ use adnl::{
     },
     node::AdnlNode,
 };
+use dht::DhtNode;
 use catchain::{
     BlockPayloadPtr, CatchainNode, CatchainOverlay, CatchainOverlayListenerPtr,
     ExternalQueryResponseCallback, PublicKeyHash
@@ -28,16 +34,18 @@ use catchain::{
 use overlay::{OverlayNode, PrivateOverlayShortId, QueriesConsumer};
 use rldp::RldpNode;
 use std::{
-    collections::HashMap, io::Cursor, sync::{Arc, atomic::{self, AtomicBool}}, time::Instant
+    collections::HashMap, io::Cursor, sync::{Arc, atomic::{self, AtomicBool}}, 
+    time::{Duration, Instant}
 };
 #[cfg(feature = "telemetry")]
 use std::sync::atomic::Ordering;
 use ton_api::{
     serialize_boxed, serialize_boxed_append,
-    Deserializer, IntoBoxed, ton::{ ton_node::Broadcast, TLObject }
+    Deserializer, IntoBoxed, 
+    ton::{ rpc::ton_node::GetCapabilities, ton_node::{Broadcast, Capabilities}, TLObject }
 };
 #[cfg(feature = "telemetry")]
-use ton_api::tag_from_boxed_object;
+use ton_api::{tag_from_boxed_object, tag_from_boxed_type};
 use ton_types::{error, fail, KeyId, KeyOption, Result};
 
 declare_counted!(
@@ -46,7 +54,16 @@ declare_counted!(
         overlay_id: Arc<PrivateOverlayShortId>,
         network_context: Arc<NetworkContext>,
         local_validator_key: Arc<dyn KeyOption>,
+        local_adnl_id: Arc<KeyId>,
         validator_keys: HashMap<Arc<KeyId>, Arc<KeyId>>,
+        // public validator keys of peers to check relayed messages
+        relay_keys: HashMap<Arc<KeyId>, Arc<dyn KeyOption>>,
+        // relaying is enabled for the session
+        relay_after_ms: Option<u64>,
+        // keepalives are enabled for the session
+        health: Option<Arc<ChannelHealth>>,
+        delivery: Arc<CatchainDelivery>,
+        qos: QosClass,
         consumer: Arc<CatchainClientConsumer>,
         is_stop: Arc<AtomicBool>
     }
@@ -54,6 +71,8 @@ declare_counted!(
 
 impl CatchainClient {
     const TARGET:  &'static str = "catchain_network";
+    // messages to peers silent for this time after our message are sent twice
+    const RESEND_AFTER_MS: u64 = 1000;
 
     pub (crate) fn new(
         runtime_handle: &tokio::runtime::Handle,
@@ -63,9 +82,11 @@ impl CatchainClient {
         local_adnl_key: &Arc<dyn KeyOption>,
         local_validator_key: Arc<dyn KeyOption>,
         catchain_listener: CatchainOverlayListenerPtr,
+        qos: QosClass,
     ) -> Result<Self> {
 
         let mut keys = HashMap::new();
+        let mut relay_keys = HashMap::new();
         let mut peers = Vec::new();
         let runtime_handle = runtime_handle.clone();
 
@@ -74,6 +95,7 @@ impl CatchainClient {
                 continue;
             }
             keys.insert(node.adnl_id.clone(), node.public_key.id().clone());
+            relay_keys.insert(node.adnl_id.clone(), node.public_key.clone());
             peers.push(node.adnl_id.clone());
         }
 
@@ -82,24 +104,47 @@ impl CatchainClient {
             &overlay_id, &id_local_key
         );
 
-        network_context.overlay.add_private_overlay(
+        network_context.validator_overlay.add_private_overlay(
             Some(runtime_handle.clone()), 
             overlay_id, 
             &local_adnl_key, 
             &peers,
             network_context.broadcast_hops
         )?;
+        let delivery = Arc::new(CatchainDelivery::new(peers.iter().cloned(), Self::RESEND_AFTER_MS));
         let consumer = Arc::new(
-            CatchainClientConsumer::new(overlay_id.clone(), catchain_listener)
+            CatchainClientConsumer::new(overlay_id.clone(), catchain_listener, delivery.clone())
         );
-        network_context.overlay.add_consumer(&overlay_id, consumer.clone())?;
+        network_context.validator_overlay.add_consumer(&overlay_id, consumer.clone())?;
+        let relay = &network_context.catchain_relay;
+        let relay_after_ms = match qos {
+            QosClass::Consensus if relay.consensus => Some(relay.relay_after_ms),
+            QosClass::Remp if relay.remp => Some(relay.relay_after_ms),
+            _ => None
+        };
+        let keepalive = &network_context.channels_keepalive;
+        let health = if keepalive.enabled {
+            let health = ChannelHealth::new(
+                peers.iter().cloned(), 
+                keepalive.reestablish_after_failures
+            );
+            Some(Arc::new(health))
+        } else {
+            None
+        };
 
         let ret = CatchainClient {
             runtime_handle,
             overlay_id: overlay_id.clone(),
             network_context: network_context.clone(),
             local_validator_key: local_validator_key,
+            local_adnl_id: id_local_key.clone(),
             validator_keys: keys,
+            relay_keys,
+            relay_after_ms,
+            health,
+            delivery,
+            qos,
             consumer: consumer,
             is_stop: Arc::new(AtomicBool::new(false)),
             counter: network_context.engine_allocated.catchain_clients.clone().into()
@@ -113,7 +158,8 @@ impl CatchainClient {
     }
 
     pub async fn stop(&self) {
-        if let Err(e) = self.network_context.overlay.delete_private_overlay(&self.overlay_id) {
+        let overlay = &self.network_context.validator_overlay;
+        if let Err(e) = overlay.delete_private_overlay(&self.overlay_id) {
             log::warn!("{:?}", e);
         }
         self.is_stop.store(true, atomic::Ordering::Relaxed);
@@ -124,10 +170,28 @@ impl CatchainClient {
         log::debug!("Overlay {} stopped.", &self.overlay_id);
     }
 
+    fn check_relayed(&self, message: &RelayedMessage) -> Result<()> {
+        if &message.overlay_id != self.overlay_id.data() {
+            fail!("message of another overlay")
+        }
+        match self.relay_keys.get(&message.source) {
+            Some(key) => message.verify(key.as_ref()),
+            None => fail!("unknown source")
+        }
+    }
+
     pub fn catchain_listener(&self) -> &CatchainOverlayListenerPtr {
         &self.consumer.catchain_listener
     }
 
+    pub fn health_json(&self) -> Option<serde_json::Value> {
+        let health = self.health.as_ref()?;
+        Some(serde_json::json!({
+            "overlay": self.overlay_id.to_string(),
+            "peers": health.to_json()
+        }))
+    }
+
     pub fn validator_keys(&self) -> &HashMap<Arc<KeyId>, Arc<KeyId>> {
         &self.validator_keys
     }
@@ -137,37 +201,111 @@ impl CatchainClient {
         receiver_id: &PublicKeyHash,
         message: &BlockPayloadPtr
     ) -> Result<()> {
-        let overlay = self.network_context.overlay.clone();
+        if self.is_stop.load(atomic::Ordering::Relaxed) {
+            log::warn!("Overlay {} was stopped!", &self.overlay_id);
+            return Ok(())
+        }
+        self.network_context.send_queues.send(
+            receiver_id, self.qos, &self.overlay_id, message, &self.is_stop
+        );
+        Ok(())
+    }
+
+    // Sends the message to the peer silent for too long through another member of the session
+    fn relay_to_silent(
+        &self,
+        receiver_id: &PublicKeyHash,
+        message: &BlockPayloadPtr
+    ) -> Result<()> {
+        match self.relay_after_ms {
+            Some(relay_after_ms) if self.delivery.lag_ms(receiver_id) >= relay_after_ms => (),
+            _ => return Ok(())
+        }
+        if self.is_stop.load(atomic::Ordering::Relaxed) {
+            return Ok(())
+        }
+        let relay = match self.delivery.pick_relay(receiver_id) {
+            Some(relay) => relay,
+            None => {
+                log::trace!(target: Self::TARGET, "no relay for message to {}", receiver_id);
+                return Ok(())
+            }
+        };
+        log::trace!(
+            target: Self::TARGET, "relay message to silent peer {} via {}", receiver_id, relay
+        );
+        metrics::increment_counter!("catchain_relayed_messages_sent");
+        let relayed = RelayedMessage::sign(
+            self.overlay_id.data(),
+            self.local_adnl_id.clone(),
+            receiver_id.clone(),
+            relay,
+            message.data().0.clone(),
+            self.local_validator_key.as_ref()
+        )?;
+        self.broadcast_relayed(relayed);
+        Ok(())
+    }
+
+    fn broadcast_relayed(&self, relayed: RelayedMessage) {
+        let relayed = relayed.serialize();
         let overlay_id = self.overlay_id.clone();
-        let msg = message.clone();
-        let receiver = receiver_id.clone();
-        let is_stop_state = self.is_stop.clone();
-        self.runtime_handle.spawn(async move {
-            let is_stop = is_stop_state.load(atomic::Ordering::Relaxed);
-            if is_stop {
-                log::warn!("Overlay {} was stopped!", &overlay_id);
-                return;
+        let overlay = self.network_context.validator_overlay.clone();
+        let local_validator_key = self.local_validator_key.clone();
+        self.runtime_handle.spawn(
+            async move {
+                let msg = TaggedByteSlice {
+                    object: &relayed,
+                    #[cfg(feature = "telemetry")]
+                    tag: 0x80000003 // Relayed catchain message
+                };
+                let result = overlay.broadcast(
+                    &overlay_id,
+                    &msg,
+                    Some(&local_validator_key),
+                    false
+                ).await;
+                log::trace!(target: Self::TARGET, "relay message status: {:?}", result);
             }
-            let buf = &msg.data().0;
-            let tag = if buf.len() < 4 {
-                0 
-            } else {
-                ((buf[3] as u32) << 24) | ((buf[2] as u32) << 16) | 
-                ((buf[1] as u32) <<  8) | ((buf[0] as u32) <<  0)
-            };
-            let msg = TaggedByteSlice {
-                object: buf,
-                #[cfg(feature = "telemetry")]
-                tag: 0x80000001 // Catchain one-way messages 
-            };
-            let answer = overlay.message(&receiver, &msg, &overlay_id).await;
-            log::trace!(
-                target: Self::TARGET, 
-                "<send_message> (overlay: {}, data: {:08x}, key_id: {}): {:?}",
-                &overlay_id, tag, &receiver, &answer
+        );
+    }
+
+    // Relayed message for the node is delivered as if it came from its source directly,
+    // message for which the node is chosen as relay is broadcast once more without relay id
+    fn receive_relayed(&self, mut message: RelayedMessage, listener: &CatchainOverlayListenerPtr) {
+        let is_target = message.target == self.local_adnl_id;
+        let is_relay = message.relay.as_ref() == Some(&self.local_adnl_id);
+        if !is_target && !is_relay {
+            return
+        }
+        if let Err(e) = self.check_relayed(&message) {
+            log::warn!(
+                target: Self::TARGET, "bad relayed message from {}: {}", message.source, e
             );
-        });
-        Ok(())
+            metrics::increment_counter!("catchain_relayed_messages_rejected");
+            return
+        }
+        if !is_target {
+            if self.relay_after_ms.is_some() {
+                log::trace!(
+                    target: Self::TARGET, "pass relayed message from {} to {}",
+                    message.source, message.target
+                );
+                metrics::increment_counter!("catchain_relayed_messages_passed");
+                message.relay = None;
+                self.broadcast_relayed(message);
+            }
+            return
+        }
+        metrics::increment_counter!("catchain_relayed_messages_received");
+        if let Some(listener) = listener.upgrade() {
+            listener.on_message(
+                message.source,
+                &catchain::CatchainFactory::create_block_payload(
+                    ::ton_api::ton::bytes(message.payload)
+                )
+            );
+        }
     }
 
     pub async fn query(
@@ -276,6 +414,65 @@ impl CatchainClient {
                 log::warn!(target: Self::TARGET, "ERROR: {}", e)
             }
         });
+        if let Some(health) = self.health.clone() {
+            runtime_handle.spawn(self.keepalive(health));
+        }
+    }
+
+    async fn keepalive(self: Arc<Self>, health: Arc<ChannelHealth>) {
+        let period_ms = self.network_context.channels_keepalive.period_ms;
+        let query = TaggedTlObject {
+            object: TLObject::new(GetCapabilities),
+            #[cfg(feature = "telemetry")]
+            tag: tag_from_boxed_type::<GetCapabilities>()
+        };
+        loop {
+            tokio::time::sleep(Duration::from_millis(period_ms)).await;
+            if self.is_stop.load(atomic::Ordering::Relaxed) {
+                break
+            }
+            let checks = health.peers().map(
+                |peer| self.check_channel(&health, peer, &query, period_ms)
+            );
+            futures::future::join_all(checks).await;
+        }
+    }
+
+    async fn check_channel(
+        &self,
+        health: &ChannelHealth,
+        peer: &Arc<KeyId>,
+        query: &TaggedTlObject,
+        timeout_ms: u64
+    ) {
+        let overlay = &self.network_context.validator_overlay;
+        let now = Instant::now();
+        match overlay.query(peer, query, &self.overlay_id, Some(timeout_ms)).await {
+            Ok(Some(_)) => {
+                health.on_alive(peer, now.elapsed().as_millis() as u64);
+                return
+            }
+            Ok(None) => (),
+            Err(e) => log::trace!(target: Self::TARGET, "keepalive to {} failed: {}", peer, e)
+        }
+        // Peer which answers catchain traffic is not silent, even if it does not
+        // answer keepalives (e.g. runs an older version)
+        if self.delivery.lag_ms(peer) == 0 || !health.on_failure(peer) {
+            return
+        }
+        log::info!(
+            target: Self::TARGET, 
+            "re-establishing channel to silent peer {} in overlay {}", peer, self.overlay_id
+        );
+        match DhtNode::find_address(&self.network_context.dht, peer).await {
+            Ok(Some((addr, key))) => {
+                if let Err(e) = overlay.add_private_peers(&self.local_adnl_id, vec![(addr, key)]) {
+                    log::warn!(target: Self::TARGET, "can't re-establish channel to {}: {}", peer, e)
+                }
+            }
+            Ok(None) => log::warn!(target: Self::TARGET, "address of {} is not found", peer),
+            Err(e) => log::warn!(target: Self::TARGET, "address of {} is not found: {}", peer, e)
+        }
     }
 
     async fn wait_broadcasts(
@@ -297,6 +494,18 @@ impl CatchainClient {
             match message {
                 Ok(Some(message)) => {
                     log::trace!(target: Self::TARGET, "private overlay broadcast (successed)");
+                    match RelayedMessage::deserialize(&message.data) {
+                        Ok(Some(relayed)) => {
+                            self.receive_relayed(relayed, &catchain_listener);
+                            continue
+                        }
+                        Ok(None) => (),
+                        Err(e) => {
+                            log::warn!(target: Self::TARGET, "bad relayed message: {}", e);
+                            continue
+                        }
+                    }
+                    self.delivery.on_received(&message.recv_from);
                    // let src_id = validator_keys.get(&message.1).ok_or_else(|| error!("unknown key!"))?;
                     if let Some(listener) = catchain_listener.upgrade() {
                         listener.on_broadcast(
@@ -335,6 +544,7 @@ impl CatchainClient {
             match message {
                 Ok(Some((catchain_block_update, validator_session_block_update, source_id)))  => {
                     log::trace!(target: Self::TARGET, "private overlay broadcast ValidatorSession_BlockUpdate (successed)");
+                    self.delivery.on_received(&source_id);
                     let vs_block_update = validator_session_block_update.into_boxed();
                     let block_update = catchain_block_update.into_boxed();
                     if let Some(listener) = catchain_listener.upgrade() {
@@ -343,6 +553,9 @@ impl CatchainClient {
                                 serializer.write_boxed(&block_update)?;
                                 serializer.write_boxed(&vs_block_update)?;
                                 let data = catchain::CatchainFactory::create_block_payload(data);
+                                self.network_context.bandwidth.download(
+                                    TrafficClass::Private, data.data().0.len()
+                                ).await;
                         listener
                             .on_message(
                                 source_id,
@@ -372,10 +585,16 @@ impl CatchainOverlay for CatchainClient {
         message: &BlockPayloadPtr)
     {
         let now = Instant::now();
-        match self.message(receiver_id, message) {
-            Ok(_) => { /*log::trace!("send_message success!");*/ },
-            Err(e) => { log::warn!(target: Self::TARGET, "send_message err: {:?}", e); }
+        for _ in 0..self.delivery.copies(receiver_id) {
+            match self.message(receiver_id, message) {
+                Ok(_) => { /*log::trace!("send_message success!");*/ },
+                Err(e) => { log::warn!(target: Self::TARGET, "send_message err: {:?}", e); }
+            }
+        }
+        if let Err(e) = self.relay_to_silent(receiver_id, message) {
+            log::warn!(target: Self::TARGET, "relay message err: {:?}", e);
         }
+        self.delivery.on_sent(receiver_id);
 
         let elapsed = now.elapsed();
         if elapsed.as_micros() > 500 {
@@ -391,10 +610,21 @@ impl CatchainOverlay for CatchainClient {
         _sender_id: &PublicKeyHash,
         message: &BlockPayloadPtr)
     {
-        for receiver_id in receiver_ids.iter() {
-            if let Err(e) = self.message(receiver_id, message) {
-                log::error!(target: Self::TARGET, "send_message err: {:?}", e);
+        // peers which may have missed our previous messages go first
+        for (receiver_id, copies) in self.delivery.prioritize(receiver_ids) {
+            if copies > 1 {
+                log::trace!(target: Self::TARGET, "resend message to silent peer {}", receiver_id);
+                metrics::increment_counter!("catchain_silent_peer_resends");
+            }
+            for _ in 0..copies {
+                if let Err(e) = self.message(&receiver_id, message) {
+                    log::error!(target: Self::TARGET, "send_message err: {:?}", e);
+                }
+            }
+            if let Err(e) = self.relay_to_silent(&receiver_id, message) {
+                log::error!(target: Self::TARGET, "relay message err: {:?}", e);
             }
+            self.delivery.on_sent(&receiver_id);
         }
     }
 
@@ -411,7 +641,7 @@ impl CatchainOverlay for CatchainClient {
             let timeout = timeout.clone();
             let msg = message.clone();
             let overlay_id = self.overlay_id.clone();
-            let overlay = self.network_context.overlay.clone();
+            let overlay = self.network_context.validator_overlay.clone();
             let is_stop_state = self.is_stop.clone();
             self.runtime_handle.spawn(async move { 
                 let is_stop = is_stop_state.load(atomic::Ordering::Relaxed);
@@ -438,8 +668,8 @@ impl CatchainOverlay for CatchainClient {
         let timeout = timeout.clone();
         let msg = query.clone();
         let overlay_id = self.overlay_id.clone();
-        let rldp = self.network_context.rldp.clone();
-        let overlay = self.network_context.overlay.clone();
+        let rldp = self.network_context.validator_rldp.clone();
+        let overlay = self.network_context.validator_overlay.clone();
         self.runtime_handle.spawn(
             async move { 
                 let result = CatchainClient::query_via_rldp(
@@ -466,7 +696,7 @@ impl CatchainOverlay for CatchainClient {
     ) {
         let msg = payload.clone();
         let overlay_id = self.overlay_id.clone();
-        let overlay = self.network_context.overlay.clone();
+        let overlay = self.network_context.validator_overlay.clone();
         let local_validator_key = self.local_validator_key.clone();
         self.runtime_handle.spawn(
             async move {
@@ -489,6 +719,7 @@ impl CatchainOverlay for CatchainClient {
 
 struct CatchainClientConsumer {
     catchain_listener: CatchainOverlayListenerPtr,
+    delivery: Arc<CatchainDelivery>,
     is_stop: AtomicBool,
     overlay_id: Arc<PrivateOverlayShortId>,
     worker_waiters: Arc<lockfree::map::Map<u128, Arc<Wait<Result<Option<Answer>>>>>>
@@ -497,10 +728,12 @@ struct CatchainClientConsumer {
 impl CatchainClientConsumer {
     fn new(
         overlay_id: Arc<PrivateOverlayShortId>,
-        catchain_listener: CatchainOverlayListenerPtr
+        catchain_listener: CatchainOverlayListenerPtr,
+        delivery: Arc<CatchainDelivery>,
     ) -> Self {
         Self {
             catchain_listener: catchain_listener,
+            delivery,
             is_stop: AtomicBool::new(false),
             overlay_id: overlay_id, 
             worker_waiters: Arc::new(lockfree::map::Map::new())
@@ -519,6 +752,23 @@ impl QueriesConsumer for CatchainClientConsumer {
         }
         let now = Instant::now();
         let id = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+        self.delivery.on_received(peers.other());
+
+        // Keepalive of the channel is answered here, catchain does not know about it
+        let query = match query.downcast::<GetCapabilities>() {
+            Ok(_) => {
+                let answer = Capabilities {
+                    version: PROTOCOL_VERSION,
+                    capabilities: PROTOCOL_CAPABILITIES,
+                };
+                return QueryResult::consume_boxed(
+                    answer.into_boxed(),
+                    #[cfg(feature = "telemetry")]
+                    None
+                )
+            }
+            Err(query) => query
+        };
 
         let data = match serialize_boxed(&query) {
             Ok(query) => query,
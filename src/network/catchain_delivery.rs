@@ -0,0 +1,107 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use rand::seq::IteratorRandom;
+use std::{
+    collections::HashMap, sync::{Arc, atomic::{AtomicU64, Ordering}}, time::Instant
+};
+use ton_types::KeyId;
+
+#[cfg(test)]
+#[path = "tests/test_catchain_delivery.rs"]
+mod tests;
+
+#[derive(Default)]
+struct PeerDeliveryState {
+    // ms since start of the first message sent after the last message received from the peer,
+    // 0 - everything sent was followed by a message from the peer
+    unanswered_since: AtomicU64,
+}
+
+/// Per-peer delivery state of catchain messages of one validator session. Messages are
+/// one-way, so a peer is supposed to have got our blocks (candidates, approvals,
+/// signatures) when it sends something to us, because its catchain blocks are built on
+/// top of the blocks it has received. A peer which stays silent after our message has
+/// probably lost it, and its signature is the one likely missing, so messages are sent
+/// to such peers first and are duplicated to peers silent for longer than `resend_after_ms`.
+pub struct CatchainDelivery {
+    started: Instant,
+    resend_after_ms: u64,
+    peers: HashMap<Arc<KeyId>, PeerDeliveryState>,
+}
+
+impl CatchainDelivery {
+
+    pub fn new(peers: impl IntoIterator<Item = Arc<KeyId>>, resend_after_ms: u64) -> Self {
+        Self {
+            started: Instant::now(),
+            resend_after_ms,
+            peers: peers.into_iter().map(|peer| (peer, PeerDeliveryState::default())).collect(),
+        }
+    }
+
+    pub fn on_sent(&self, peer: &Arc<KeyId>) {
+        if let Some(state) = self.peers.get(peer) {
+            let now = self.now_ms();
+            state.unanswered_since.compare_exchange(0, now, Ordering::Relaxed, Ordering::Relaxed).ok();
+        }
+    }
+
+    pub fn on_received(&self, peer: &Arc<KeyId>) {
+        if let Some(state) = self.peers.get(peer) {
+            state.unanswered_since.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// How long the peer has been silent since our first unanswered message, ms
+    pub fn lag_ms(&self, peer: &Arc<KeyId>) -> u64 {
+        match self.peers.get(peer).map(|state| state.unanswered_since.load(Ordering::Relaxed)) {
+            Some(since) if since > 0 => self.now_ms().saturating_sub(since),
+            _ => 0
+        }
+    }
+
+    /// Number of copies of a message to send to the peer
+    pub fn copies(&self, peer: &Arc<KeyId>) -> usize {
+        if self.resend_after_ms > 0 && self.lag_ms(peer) >= self.resend_after_ms {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Orders receivers of a message: the longest silent peers go first
+    pub fn prioritize(&self, receivers: &[Arc<KeyId>]) -> Vec<(Arc<KeyId>, usize)> {
+        let mut receivers = receivers.iter()
+            .map(|peer| (self.lag_ms(peer), peer))
+            .collect::<Vec<_>>();
+        receivers.sort_by(|(lag1, _), (lag2, _)| lag2.cmp(lag1));
+        receivers.into_iter().map(|(_, peer)| (peer.clone(), self.copies(peer))).collect()
+    }
+
+    /// Random peer which has answered all our messages, to relay messages to the target
+    pub fn pick_relay(&self, target: &Arc<KeyId>) -> Option<Arc<KeyId>> {
+        self.peers.iter()
+            .filter(|(peer, state)| {
+                (*peer != target) && (state.unanswered_since.load(Ordering::Relaxed) == 0)
+            })
+            .map(|(peer, _)| peer)
+            .choose(&mut rand::thread_rng())
+            .cloned()
+    }
+
+    // starts from 1 to leave 0 for "not set"
+    fn now_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64 + 1
+    }
+}
@@ -0,0 +1,112 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::sync::Arc;
+use ton_types::{fail, KeyId, KeyOption, Result};
+
+#[cfg(test)]
+#[path = "tests/test_catchain_relay.rs"]
+mod tests;
+
+const CATCHAIN_RELAY_TAG: u32 = 0x59524343; // "CCRY"
+const ID_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+const HEADER_LEN: usize = 4 + 4 * ID_LEN + SIGNATURE_LEN;
+const NO_RELAY: [u8; ID_LEN] = [0; ID_LEN];
+
+/// Catchain message relayed to its target by another member of the session when direct
+/// connectivity between the source and the target is broken. The message is sent as
+/// private overlay broadcast: tag, overlay id, source, target and relay ADNL ids, signature
+/// of the source validator key and the message itself. The relay chosen by the source
+/// broadcasts the message once more without the relay id, so it reaches the target
+/// via the relay's connections. The signature covers everything except the tag and
+/// the relay id, so relays can't forge or redirect the message, and the overlay id
+/// prevents replaying it in another session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelayedMessage {
+    pub overlay_id: [u8; ID_LEN],
+    pub source: Arc<KeyId>,
+    pub target: Arc<KeyId>,
+    pub relay: Option<Arc<KeyId>>,
+    pub signature: Vec<u8>,
+    pub payload: Vec<u8>,
+}
+
+impl RelayedMessage {
+
+    pub fn sign(
+        overlay_id: &[u8; ID_LEN],
+        source: Arc<KeyId>,
+        target: Arc<KeyId>,
+        relay: Arc<KeyId>,
+        payload: Vec<u8>,
+        key: &dyn KeyOption
+    ) -> Result<Self> {
+        let mut ret = Self {
+            overlay_id: *overlay_id,
+            source,
+            target,
+            relay: Some(relay),
+            signature: vec!(),
+            payload
+        };
+        ret.signature = key.sign(&ret.signed_data())?;
+        Ok(ret)
+    }
+
+    pub fn verify(&self, key: &dyn KeyOption) -> Result<()> {
+        key.verify(&self.signed_data(), &self.signature)
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(HEADER_LEN + self.payload.len());
+        data.extend_from_slice(&CATCHAIN_RELAY_TAG.to_le_bytes());
+        data.extend_from_slice(&self.overlay_id);
+        data.extend_from_slice(self.source.data());
+        data.extend_from_slice(self.target.data());
+        data.extend_from_slice(self.relay.as_ref().map_or(&NO_RELAY, |relay| relay.data()));
+        data.extend_from_slice(&self.signature);
+        data.extend_from_slice(&self.payload);
+        data
+    }
+
+    /// Returns None if data is not a relayed message
+    pub fn deserialize(data: &[u8]) -> Result<Option<Self>> {
+        if data.len() < 4 || data[0..4] != CATCHAIN_RELAY_TAG.to_le_bytes() {
+            return Ok(None)
+        }
+        if data.len() <= HEADER_LEN {
+            fail!("Relayed catchain message has wrong length {}", data.len())
+        }
+        let id = |offset: usize| {
+            let mut id = [0; ID_LEN];
+            id.copy_from_slice(&data[offset..offset + ID_LEN]);
+            id
+        };
+        Ok(Some(Self {
+            overlay_id: id(4),
+            source: KeyId::from_data(id(4 + ID_LEN)),
+            target: KeyId::from_data(id(4 + 2 * ID_LEN)),
+            relay: Some(id(4 + 3 * ID_LEN)).filter(|relay| relay != &NO_RELAY).map(KeyId::from_data),
+            signature: data[4 + 4 * ID_LEN..HEADER_LEN].to_vec(),
+            payload: data[HEADER_LEN..].to_vec(),
+        }))
+    }
+
+    fn signed_data(&self) -> Vec<u8> {
+        let mut data = self.serialize();
+        data.drain(..4);
+        data.drain(3 * ID_LEN..4 * ID_LEN + SIGNATURE_LEN);
+        data
+    }
+}
@@ -0,0 +1,104 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{
+    collections::HashMap, sync::{Arc, atomic::{AtomicU32, AtomicU64, Ordering}}, time::Instant
+};
+use ton_types::KeyId;
+
+#[cfg(test)]
+#[path = "tests/test_channel_health.rs"]
+mod tests;
+
+#[derive(Default)]
+struct PeerHealth {
+    // smoothed roundtrip of keepalive queries, 0 - not measured yet
+    rtt_ms: AtomicU64,
+    // ms since start of the last answered keepalive, 0 - never
+    last_alive_ms: AtomicU64,
+    // unanswered keepalives in a row
+    failures: AtomicU32,
+    reestablished: AtomicU32,
+}
+
+/// Health of ADNL channels to the peers of one catchain session, measured by keepalive
+/// queries. A channel whose keepalives are not answered `reestablish_after` times in a row
+/// is re-established: the address of the peer is searched in DHT and set again.
+pub struct ChannelHealth {
+    started: Instant,
+    reestablish_after: u32,
+    peers: HashMap<Arc<KeyId>, PeerHealth>,
+}
+
+impl ChannelHealth {
+
+    pub fn new(peers: impl IntoIterator<Item = Arc<KeyId>>, reestablish_after: u32) -> Self {
+        Self {
+            started: Instant::now(),
+            reestablish_after,
+            peers: peers.into_iter().map(|peer| (peer, PeerHealth::default())).collect(),
+        }
+    }
+
+    pub fn peers(&self) -> impl Iterator<Item = &Arc<KeyId>> {
+        self.peers.keys()
+    }
+
+    pub fn on_alive(&self, peer: &Arc<KeyId>, rtt_ms: u64) {
+        if let Some(health) = self.peers.get(peer) {
+            let old = health.rtt_ms.load(Ordering::Relaxed);
+            let rtt_ms = if old > 0 { (old + rtt_ms) / 2 } else { rtt_ms.max(1) };
+            health.rtt_ms.store(rtt_ms, Ordering::Relaxed);
+            health.last_alive_ms.store(self.now_ms(), Ordering::Relaxed);
+            health.failures.store(0, Ordering::Relaxed);
+            let labels = [("peer", peer.to_string())];
+            metrics::gauge!("catchain_peer_rtt_ms", rtt_ms as f64, &labels);
+        }
+    }
+
+    /// Returns true if the channel to the peer should be re-established
+    pub fn on_failure(&self, peer: &Arc<KeyId>) -> bool {
+        let Some(health) = self.peers.get(peer) else {
+            return false
+        };
+        let labels = [("peer", peer.to_string())];
+        metrics::increment_counter!("catchain_keepalive_failures", &labels);
+        let failures = health.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.reestablish_after == 0 || failures % self.reestablish_after != 0 {
+            return false
+        }
+        health.reestablished.fetch_add(1, Ordering::Relaxed);
+        metrics::increment_counter!("catchain_channels_reestablished");
+        true
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let now = self.now_ms();
+        let peers = self.peers.iter().map(|(peer, health)| {
+            let last_alive = health.last_alive_ms.load(Ordering::Relaxed);
+            serde_json::json!({
+                "peer": peer.to_string(),
+                "rtt_ms": health.rtt_ms.load(Ordering::Relaxed),
+                "silent_ms": if last_alive > 0 { Some(now - last_alive) } else { None },
+                "failures": health.failures.load(Ordering::Relaxed),
+                "reestablished": health.reestablished.load(Ordering::Relaxed),
+            })
+        }).collect::<Vec<_>>();
+        serde_json::Value::from(peers)
+    }
+
+    // starts from 1 to leave 0 for "not set"
+    fn now_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64 + 1
+    }
+}
@@ -0,0 +1,79 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::config::AnswersCompressionConfig;
+use ton_types::Result;
+
+#[cfg(test)]
+#[path = "tests/test_compression.rs"]
+mod tests;
+
+// Tag of answer compressed with zstd; answers are raw data or TL objects,
+// so the tag is long to make a collision with them practically impossible
+const ZSTD_ANSWER_TAG: &[u8; 8] = b"OVLZSTD1";
+// Decompressed answer can't be larger than RLDP answer limit
+const MAX_DECOMPRESSED_ANSWER_SIZE: usize = 10 << 20;
+
+/// Compression of answers to public overlay queries. Answers are compressed only for
+/// peers which advertised `CAPABILITY_COMPRESSED_ANSWERS`, so old nodes get them as is,
+/// and only for raw download queries, which are sent by RLDP: the client decompresses
+/// RLDP answers only.
+pub struct AnswersCompression {
+    enabled: bool,
+    threshold: usize,
+    level: i32,
+}
+
+impl AnswersCompression {
+
+    pub fn new(config: &AnswersCompressionConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            threshold: config.threshold_bytes,
+            level: config.level,
+        }
+    }
+
+    /// Returns compressed answer or None if it is small or doesn't shrink
+    pub fn compress(&self, data: &[u8]) -> Result<Option<Vec<u8>>> {
+        if !self.enabled || data.len() < self.threshold {
+            return Ok(None)
+        }
+        let mut compressed = ZSTD_ANSWER_TAG.to_vec();
+        compressed.extend_from_slice(&zstd::bulk::compress(data, self.level)?);
+        if compressed.len() >= data.len() {
+            metrics::increment_counter!("overlay_answers_compression_skipped");
+            return Ok(None)
+        }
+        metrics::increment_counter!("overlay_answers_compressed");
+        metrics::counter!(
+            "overlay_answers_compression_saved_bytes", (data.len() - compressed.len()) as u64
+        );
+        Ok(Some(compressed))
+    }
+
+    /// Decompresses the answer if it is compressed; data which looks like compressed one,
+    /// but can't be decompressed, is returned as is
+    pub fn decompress(data: Vec<u8>) -> Vec<u8> {
+        if !data.starts_with(ZSTD_ANSWER_TAG) {
+            return data
+        }
+        match zstd::bulk::decompress(&data[ZSTD_ANSWER_TAG.len()..], MAX_DECOMPRESSED_ANSWER_SIZE) {
+            Ok(decompressed) => decompressed,
+            Err(e) => {
+                log::warn!("Can't decompress answer with compression tag, use as is: {}", e);
+                data
+            }
+        }
+    }
+}
@@ -13,9 +13,23 @@
 
 use crate::{
     collator_test_bundle::CollatorTestBundle, config::{KeyRing, NodeConfigHandler},
-    engine_traits::EngineOperations, engine::Engine, network::node_network::NodeNetwork,
+    engine_traits::EngineOperations, engine::Engine,
+    full_node::{
+        account_proof::account_with_proof,
+        message_import::{import_external_messages, MESSAGE_IMPORTS_DIR},
+        state_diff::{export_state_diff_part, import_state_diff_part}
+    },
+    network::{
+        capabilities_log::CapabilitiesLog, catchain_client::CatchainClient,
+        control_queries::NodeControlQuery,
+        full_node_client::NodeClientOverlay, node_network::NodeNetwork, peer_score::PeerScores
+    },
     shard_states_keeper::PinnedShardStateGuard, 
-    validator::validator_utils::validatordescr_to_catchain_node,
+    validator::{
+        catchain_transcript::MAX_TRANSCRIPT_PAGE_BLOCKS, deferred_dispatch::deferred_sub_status,
+        fabric::{run_collate_dry_run, run_validate_replay}, message_cache::RempMessageStatusFilter,
+        validator_utils::validatordescr_to_catchain_node
+    },
     validating_utils::{supported_version, supported_capabilities}
 };
 
@@ -23,7 +37,9 @@ use adnl::{
     common::{QueryResult, Subscriber, AdnlPeers},
     server::{AdnlServer, AdnlServerConfig}
 };
-use std::sync::Arc;
+use overlay::OverlayShortId;
+use std::{path::Path, sync::{Arc, Mutex}, time::{Duration, Instant}};
+use storage::archives::GcTotals;
 use ton_api::{
     deserialize_boxed, IntoBoxed,
     ton::{
@@ -47,11 +63,31 @@ use ton_api::{
     }
 };
 use ton_block::{
-    BlockIdExt, MsgAddressInt, Serializable, ShardIdent, MASTERCHAIN_ID, MerkleProof, 
-    ShardAccount
+    BlockIdExt, Deserializable, Message, MsgAddressInt, Serializable, ShardIdent, MASTERCHAIN_ID,
+    MerkleProof, ShardAccount
 };
 use ton_block_json::serialize_config_param;
-use ton_types::{error, fail, KeyId, read_single_root_boc, Result, UInt256, AccountId};
+use ton_types::{base64_encode, error, fail, KeyId, read_single_root_boc, Result, UInt256, AccountId};
+use validator_session::{LatencyEvent, LatencyStat};
+
+const LATENCY_STATS_SLOWEST_NODES: usize = 5;
+const NEIGHBOURS_CAPABILITIES_STATS: &str = "neighbours_capabilities";
+const NEIGHBOURS_STATS: &str = "neighbours";
+const CHANNELS_HEALTH_STATS: &str = "channels_health";
+const PEER_SCORES_STATS: &str = "peer_scores";
+const REMP_TRANSCRIPTS_STATS: &str = "remp_transcripts";
+const REMP_TRANSCRIPT_STATS_PREFIX: &str = "remp_transcript:";
+const REMP_DEFERRED_STATS: &str = "remp_deferred";
+const REMP_PROPAGATION_STATS: &str = "remp_propagation";
+const REMP_SESSION_STATS_PREFIX: &str = "remp_session:";
+const REMP_SESSIONS_STATS: &str = "remp_sessions";
+const REMP_MESSAGE_HISTORY_PREFIX: &str = "remp_message_history:";
+const REMP_MESSAGE_STATUS_PREFIX: &str = "remp_message_status:";
+const REMP_CACHE_DUMP_STATS: &str = "remp_cache_dump";
+const GC_DRY_RUN_STATS: &str = "gc_dry_run";
+const ACCOUNT_PROOF_PREFIX: &str = "account_proof:";
+const CONSENSUS_STATS: &str = "consensus_stats";
+const VALIDATOR_SESSION_STATS: &str = "validator_session_stats";
 
 pub struct ControlServer {
     adnl: AdnlServer
@@ -91,11 +127,32 @@ pub enum DataSource {
     Status(Arc<dyn StatusReporter>)
 }
 
+// Elector and config contract addresses are rarely changed, so they are
+// reloaded from the last applied masterchain state once per the interval
+const SERVICE_ADDRESSES_TTL: Duration = Duration::from_secs(60);
+
+struct ServiceAddresses {
+    loaded_at: Instant,
+    elector: Option<AccountId>,
+    config: Option<AccountId>
+}
+
+impl ServiceAddresses {
+    fn is_service(&self, dst: &AccountId) -> bool {
+        self.elector.as_ref() == Some(dst) || self.config.as_ref() == Some(dst)
+    }
+}
+
 struct ControlQuerySubscriber {
     data_source: DataSource,
     key_ring: Arc<dyn KeyRing>,
     config: Arc<NodeConfigHandler>,
-    public_overlay_adnl_id: Option<Arc<KeyId>>
+    public_overlay_adnl_id: Option<Arc<KeyId>>,
+    capabilities_log: Option<Arc<CapabilitiesLog>>,
+    peer_scores: Option<Arc<PeerScores>>,
+    overlay_clients: Option<Arc<lockfree::map::Map<Arc<OverlayShortId>, Arc<NodeClientOverlay>>>>,
+    catchain_clients: Option<Arc<lockfree::map::Map<Arc<OverlayShortId>, Arc<CatchainClient>>>>,
+    service_addresses: Mutex<Option<ServiceAddresses>>
 }
 
 impl ControlQuerySubscriber {
@@ -106,16 +163,28 @@ impl ControlQuerySubscriber {
         config: Arc<NodeConfigHandler>,
         network: Option<&NodeNetwork>,
     ) -> Result<Self> {
-        let key_id = if let Some (network) = network {
-            Some(network.get_key_id_by_tag(NodeNetwork::TAG_OVERLAY_KEY)?)
-        } else {
-            None
-        };
+        let (key_id, capabilities_log, peer_scores, overlay_clients, catchain_clients) = 
+            if let Some (network) = network {
+                (
+                    Some(network.get_key_id_by_tag(NodeNetwork::TAG_OVERLAY_KEY)?),
+                    Some(network.capabilities_log()),
+                    Some(network.peer_scores()),
+                    Some(network.overlay_clients()),
+                    Some(network.catchain_clients())
+                )
+            } else {
+                (None, None, None, None, None)
+            };
         let ret = Self {
             data_source,
             key_ring,
             config,
-            public_overlay_adnl_id: key_id
+            public_overlay_adnl_id: key_id,
+            capabilities_log,
+            peer_scores,
+            overlay_clients,
+            catchain_clients,
+            service_addresses: Mutex::new(None)
         };
         Ok(ret)
     }
@@ -129,12 +198,11 @@ impl ControlQuerySubscriber {
 
     async fn get_all_config_params(&self) -> Result<ConfigInfo> {
         let engine = self.engine()?;
-        let mc_state = engine.load_last_applied_mc_state().await?;
-        let block_id = mc_state.block_id();
-        let config_params = mc_state.config_params()?;
+        let snapshot = engine.load_and_pin_state_snapshot(None).await?;
+        let config_params = snapshot.config_params()?;
         let config_info = ConfigInfo {
             mode: 0,
-            id: block_id.clone(),
+            id: snapshot.mc_block_id().clone(),
             state_proof: ton::bytes(vec!()),
             config_proof: ton::bytes(config_params.write_to_bytes()?)
         };
@@ -143,12 +211,12 @@ impl ControlQuerySubscriber {
 
     async fn get_config_params(&self, param_number: u32) -> Result<ConfigInfo> {
         let engine = self.engine()?;
-        let mc_state = engine.load_last_applied_mc_state().await?;
-        let config_params = mc_state.config_params()?;
+        let snapshot = engine.load_and_pin_state_snapshot(None).await?;
+        let config_params = snapshot.config_params()?;
         let config_param = serialize_config_param(config_params, param_number)?;
         let config_info = ConfigInfo {
             mode: 0,
-            id: mc_state.block_id().clone(),
+            id: snapshot.mc_block_id().clone(),
             state_proof: ton::bytes(vec!()),
             config_proof: ton::bytes(config_param.into_bytes())
         };
@@ -177,27 +245,10 @@ impl ControlQuerySubscriber {
         &self, addr: &MsgAddressInt
     ) -> Result<Option<(ShardAccount, PinnedShardStateGuard)>> {
         let engine = self.engine()?;
-        let state = if addr.is_masterchain() {
-            let mc_block_id = engine.load_last_applied_mc_block_id()?
-                .ok_or_else(|| error!("Cannot load last_applied_mc_block_id!"))?;
-            engine.load_and_pin_state(&mc_block_id).await?
-        } else {
-            let mc_block_id = engine.load_shard_client_mc_block_id()?;
-            let mc_block_id = mc_block_id.ok_or_else(
-                || error!("Cannot load shard_client_mc_block_id!")
-            )?;
-            let mc_state = engine.load_and_pin_state(&mc_block_id).await?;
-            let mut shard_state = None;
-            for id in mc_state.state().top_blocks(addr.workchain_id())? {
-                if id.shard().contains_account(addr.address().clone())? {
-                    shard_state = engine.load_and_pin_state(&id).await.ok();
-                    break;
-                }
-            }
-            shard_state.ok_or_else(
-                || error!("Cannot find actual shard for account {}", addr)
-            )?
-        };
+        // Masterchain and shard accounts are read from the same snapshot,
+        // only the shard containing the account is loaded
+        let snapshot = engine.load_and_pin_state_snapshot(None).await?;
+        let state = snapshot.state_for_account(engine.as_ref(), addr).await?;
         Ok(state.state().shard_account(&addr.address())?.map(|acc| (acc, state)))
     }
 
@@ -281,6 +332,52 @@ impl ControlQuerySubscriber {
         }).to_string()
     }
 
+    // Arguments of cache dump filter: `[<status>[:<workchain>:<shard prefix in hex>]]`
+    fn parse_cache_dump_args(args: &str) -> Result<(RempMessageStatusFilter, Option<ShardIdent>)> {
+        let mut args = args.split(':').filter(|arg| !arg.is_empty());
+        let status = match args.next() {
+            Some(status) => status.parse::<RempMessageStatusFilter>()?,
+            None => RempMessageStatusFilter::All
+        };
+        let shard = match (args.next(), args.next()) {
+            (None, _) => None,
+            (Some(workchain), Some(prefix)) => {
+                let workchain = workchain.parse::<i32>()
+                    .map_err(|e| error!("Wrong workchain {}: {}", workchain, e))?;
+                let prefix = u64::from_str_radix(prefix, 16)
+                    .map_err(|e| error!("Wrong shard prefix {}: {}", prefix, e))?;
+                Some(ShardIdent::with_tagged_prefix(workchain, prefix)?)
+            }
+            (Some(_), None) => fail!("Shard should be given as <workchain>:<shard prefix in hex>")
+        };
+        if args.next().is_some() {
+            fail!("Too many arguments of {}", REMP_CACHE_DUMP_STATS)
+        }
+        Ok((status, shard))
+    }
+
+    // <session id>[:<offset>[:<limit>]]
+    fn parse_transcript_args(args: &str) -> Result<(UInt256, usize, usize)> {
+        let mut args = args.split(':');
+        let session_id = args.next().unwrap_or_default();
+        let session_id = session_id.parse::<UInt256>()
+            .map_err(|e| error!("Wrong catchain session id {}: {}", session_id, e))?;
+        let offset = match args.next() {
+            Some(offset) => offset.parse::<usize>()
+                .map_err(|e| error!("Wrong transcript offset {}: {}", offset, e))?,
+            None => 0
+        };
+        let limit = match args.next() {
+            Some(limit) => limit.parse::<usize>()
+                .map_err(|e| error!("Wrong transcript limit {}: {}", limit, e))?,
+            None => MAX_TRANSCRIPT_PAGE_BLOCKS
+        };
+        if args.next().is_some() {
+            fail!("Too many arguments of {}", REMP_TRANSCRIPT_STATS_PREFIX)
+        }
+        Ok((session_id, offset, limit))
+    }
+
     fn add_stats(stats: &mut Vec<OneStat>, key: impl ToString, value: impl ToString) {
         stats.push(OneStat {
             key: key.to_string(),
@@ -311,6 +408,32 @@ impl ControlQuerySubscriber {
         format!("{:#}", serde_json::Value::from(json_map))
     }
 
+    fn latency_to_json(map: &lockfree::map::Map<ShardIdent, LatencyStat>) -> String {
+        let mut json_map = serde_json::Map::new();
+        for item in map.iter() {
+            let stat = item.val();
+            let mut shard_map = serde_json::Map::new();
+            shard_map.insert("rounds".to_string(), stat.rounds_count.into());
+            for event in LatencyEvent::ALL.iter() {
+                let nodes = stat.slowest_nodes(*event, LATENCY_STATS_SLOWEST_NODES)
+                    .into_iter()
+                    .map(|(key, value)| serde_json::json!({
+                        "node": hex::encode(key.data()),
+                        "count": value.count,
+                        "avg_ms": value.average_ms(),
+                        "max_ms": value.max_ms,
+                        "p50_ms": value.percentile_ms(50),
+                        "p90_ms": value.percentile_ms(90),
+                        "missed": value.missed,
+                    }))
+                    .collect::<Vec<_>>();
+                shard_map.insert(event.name().to_string(), nodes.into());
+            }
+            json_map.insert(item.key().to_string(), shard_map.into());
+        }
+        format!("{:#}", serde_json::Value::from(json_map))
+    }
+
     fn get_shards_time_diff(engine: &Arc<dyn EngineOperations>, now: u32) -> Result<u32> {
         let shard_client_mc_block_id = engine.load_shard_client_mc_block_id()?
             .ok_or_else(|| error!("Cannot load shard_mc_block_id"))?;
@@ -324,6 +447,213 @@ impl ControlQuerySubscriber {
         let mut stats = Vec::new();
         let new_format = filter.is_some();
 
+        if filter == Some(NEIGHBOURS_CAPABILITIES_STATS) {
+            let value = match &self.capabilities_log {
+                Some(capabilities_log) => capabilities_log.to_json(),
+                None => "\"not available\"".to_string()
+            };
+            Self::add_stats(&mut stats, NEIGHBOURS_CAPABILITIES_STATS, value);
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if filter == Some(NEIGHBOURS_STATS) {
+            let value = match &self.overlay_clients {
+                Some(overlay_clients) => {
+                    let overlays = overlay_clients.iter()
+                        .map(|client| client.val().peers().to_json())
+                        .collect::<Vec<_>>();
+                    format!("{:#}", serde_json::Value::from(overlays))
+                }
+                None => "\"not available\"".to_string()
+            };
+            Self::add_stats(&mut stats, NEIGHBOURS_STATS, value);
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if filter == Some(CHANNELS_HEALTH_STATS) {
+            let value = match &self.catchain_clients {
+                Some(catchain_clients) => {
+                    let sessions = catchain_clients.iter()
+                        .filter_map(|client| client.val().health_json())
+                        .collect::<Vec<_>>();
+                    format!("{:#}", serde_json::Value::from(sessions))
+                }
+                None => "\"not available\"".to_string()
+            };
+            Self::add_stats(&mut stats, CHANNELS_HEALTH_STATS, value);
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if filter == Some(PEER_SCORES_STATS) {
+            let value = match &self.peer_scores {
+                Some(peer_scores) => peer_scores.to_json(),
+                None => "\"not available\"".to_string()
+            };
+            Self::add_stats(&mut stats, PEER_SCORES_STATS, value);
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if filter == Some(REMP_TRANSCRIPTS_STATS) {
+            let engine = self.engine()?;
+            let transcripts = engine.list_remp_catchain_transcripts()?
+                .into_iter()
+                .map(|(session_id, finished, blocks)| serde_json::json!({
+                    "session_id": session_id.to_hex_string(),
+                    "finished": finished,
+                    "blocks": blocks,
+                }))
+                .collect::<Vec<_>>();
+            Self::add_stats(
+                &mut stats,
+                REMP_TRANSCRIPTS_STATS,
+                format!("{:#}", serde_json::Value::from(transcripts))
+            );
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if filter == Some(REMP_PROPAGATION_STATS) {
+            let propagation = self.engine()?.export_remp_catchain_propagation()?;
+            Self::add_stats(&mut stats, REMP_PROPAGATION_STATS, propagation);
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if let Some(address) = filter.and_then(|f| f.strip_prefix(ACCOUNT_PROOF_PREFIX)) {
+            let address: MsgAddressInt = address.parse()?;
+            let account = account_with_proof(self.engine()?, &address).await?;
+            let shard_account = account.account.as_ref()
+                .map(|shard_account| shard_account.write_to_bytes())
+                .transpose()?
+                .map(base64_encode);
+            let value = serde_json::json!({
+                "mc_block_id": account.mc_block_id.to_string(),
+                "shard_block_id": account.shard_block_id.to_string(),
+                "shard_account": shard_account,
+                "proof": base64_encode(account.proof_boc()?),
+            });
+            Self::add_stats(&mut stats, "account_proof", format!("{:#}", value));
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if filter == Some(CONSENSUS_STATS) {
+            let mut json_map = serde_json::Map::new();
+            for item in self.engine()?.session_consensus_reports().iter() {
+                let report = item.val();
+                let suggestions = report.suggestions.iter()
+                    .map(|s| serde_json::json!({
+                        "param": s.param,
+                        "current": s.current,
+                        "suggested": s.suggested,
+                    }))
+                    .collect::<Vec<_>>();
+                json_map.insert(item.key().to_string(), serde_json::json!({
+                    "rounds": report.stats.rounds,
+                    "skipped": report.stats.skipped,
+                    "avg_round_ms": report.stats.avg_round_ms(),
+                    "max_round_ms": report.stats.max_round_ms,
+                    "avg_payload": report.stats.avg_payload(),
+                    "max_payload": report.stats.max_payload,
+                    "suggestions": suggestions,
+                }));
+            }
+            Self::add_stats(&mut stats, CONSENSUS_STATS, format!("{:#}", serde_json::Value::from(json_map)));
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if filter == Some(VALIDATOR_SESSION_STATS) {
+            let mut json_map = serde_json::Map::new();
+            for item in self.engine()?.session_consensus_reports().iter() {
+                let report = item.val();
+                let mut session = report.session.to_json();
+                session["rounds"] = report.stats.rounds.into();
+                session["skipped"] = report.stats.skipped.into();
+                json_map.insert(item.key().to_string(), session);
+            }
+            Self::add_stats(&mut stats, VALIDATOR_SESSION_STATS, format!("{:#}", serde_json::Value::from(json_map)));
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if filter == Some(GC_DRY_RUN_STATS) {
+            let report = self.engine()?.gc_dry_run_report().await?;
+            let totals = |totals: &GcTotals| serde_json::json!({
+                "count": totals.count,
+                "bytes": totals.bytes,
+            });
+            let value = serde_json::json!({
+                "archives_gc_block": report.archives_gc_block.map(|id| id.to_string()),
+                "archives": totals(&report.archives.archives),
+                "blocks": totals(&report.archives.blocks),
+                "proofs": totals(&report.archives.proofs),
+                "other": totals(&report.archives.other),
+                "states": totals(&report.persistent_states),
+            });
+            Self::add_stats(&mut stats, GC_DRY_RUN_STATS, format!("{:#}", value));
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if filter == Some(REMP_DEFERRED_STATS) {
+            let mut queues = serde_json::Map::new();
+            let mut messages = serde_json::Map::new();
+            for (id, deferred) in self.engine()?.get_remp_deferred_messages()? {
+                queues.insert(
+                    format!("{}:{:x}", deferred.shard.workchain_id(), deferred.account),
+                    deferred.queue_len.into()
+                );
+                messages.insert(format!("{:x}", id), deferred_sub_status(deferred.position).into());
+            }
+            Self::add_stats(
+                &mut stats,
+                REMP_DEFERRED_STATS,
+                format!("{:#}", serde_json::json!({ "queues": queues, "messages": messages }))
+            );
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if let Some(args) = filter.and_then(|f| f.strip_prefix(REMP_CACHE_DUMP_STATS)) {
+            if args.is_empty() || args.starts_with(':') {
+                let (status, shard) = Self::parse_cache_dump_args(args)?;
+                let dump = self.engine()?.dump_remp_message_cache(shard.as_ref(), status)?;
+                Self::add_stats(&mut stats, REMP_CACHE_DUMP_STATS, dump);
+                return Ok(Stats {stats: stats.into()})
+            }
+        }
+
+        if filter == Some(REMP_SESSIONS_STATS) {
+            let sessions = self.engine()?.list_remp_catchain_sessions().await?;
+            Self::add_stats(&mut stats, REMP_SESSIONS_STATS, sessions);
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if let Some(queue_id) = filter.and_then(|f| f.strip_prefix(REMP_SESSION_STATS_PREFIX)) {
+            let queue_id = queue_id.parse::<UInt256>()
+                .map_err(|e| error!("Wrong REMP queue id {}: {}", queue_id, e))?;
+            let status = self.engine()?.inspect_remp_catchain_session(&queue_id, false).await?;
+            Self::add_stats(&mut stats, "remp_session", status);
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if let Some(message_id) = filter.and_then(|f| f.strip_prefix(REMP_MESSAGE_HISTORY_PREFIX)) {
+            let message_id = message_id.parse::<UInt256>()
+                .map_err(|e| error!("Wrong message id {}: {}", message_id, e))?;
+            let history = self.engine()?.export_remp_message_history(&message_id)?;
+            Self::add_stats(&mut stats, "remp_message_history", history);
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if let Some(message_id) = filter.and_then(|f| f.strip_prefix(REMP_MESSAGE_STATUS_PREFIX)) {
+            let message_id = message_id.parse::<UInt256>()
+                .map_err(|e| error!("Wrong message id {}: {}", message_id, e))?;
+            let statuses = self.engine()?.observe_remp_message_status(&message_id)?;
+            Self::add_stats(&mut stats, "remp_message_status", statuses);
+            return Ok(Stats {stats: stats.into()})
+        }
+
+        if let Some(args) = filter.and_then(|f| f.strip_prefix(REMP_TRANSCRIPT_STATS_PREFIX)) {
+            let (session_id, offset, limit) = Self::parse_transcript_args(args)?;
+            let transcript = self.engine()?.export_remp_catchain_transcript(&session_id, offset, limit)?;
+            Self::add_stats(&mut stats, "remp_transcript", transcript);
+            return Ok(Stats {stats: stats.into()})
+        }
+
         // sync status
         let sync_status = match &self.data_source {
             DataSource::Engine(engine) => engine.get_sync_status(),
@@ -367,6 +697,7 @@ impl ControlQuerySubscriber {
             error!("Public overlay key id didn`t set!")
         )?;
         Self::add_stats(&mut stats, "public_overlay_key_id", format!("\"{}\"", &public_overlay_adnl_id));
+        Self::add_stats(&mut stats, "remp_max_message_size", engine.remp_max_message_size());
 
         if new_format {
             Self::add_stats(&mut stats, "supported_block", supported_version());
@@ -479,6 +810,14 @@ impl ControlQuerySubscriber {
 
         Self::add_stats(&mut stats, "validation_status", format!("\"{:?}\"", engine.validation_status()));
 
+        let value = Self::latency_to_json(engine.session_latency_stats());
+        Self::add_stats(&mut stats, "validation_latency", value);
+
+        #[cfg(feature = "telemetry")] {
+            let value = serde_json::to_value(engine.remp_core_telemetry().health())?;
+            Self::add_stats(&mut stats, "remp_telemetry", format!("{:#}", value));
+        }
+
         Ok(Stats { stats: stats.into() })
 
     }
@@ -558,20 +897,188 @@ impl ControlQuerySubscriber {
     async fn redirect_external_message(&self, message_data: &[u8]) -> Result<Success> {
         let engine = self.engine()?;
         let id = read_single_root_boc(message_data)?.repr_hash();
-        engine.redirect_external_message(message_data, id).await?;
+        if self.is_service_message(message_data).await? {
+            engine.send_service_message(message_data, id).await?;
+        } else {
+            engine.redirect_external_message(message_data, id).await?;
+        }
         Ok(Success::Engine_Validator_Success)
     }
 
+    // Messages sent by the node operator to elector (election requests) or 
+    // config contract (config votes) are service messages of the validator
+    async fn is_service_message(&self, message_data: &[u8]) -> Result<bool> {
+        let message = Message::construct_from_bytes(message_data)?;
+        let dst = match message.ext_in_header() {
+            Some(header) if header.dst.is_masterchain() => header.dst.address(),
+            _ => return Ok(false)
+        };
+        let cached = self.service_addresses.lock().unwrap().as_ref()
+            .filter(|addresses| addresses.loaded_at.elapsed() < SERVICE_ADDRESSES_TTL)
+            .map(|addresses| addresses.is_service(&dst));
+        if let Some(is_service) = cached {
+            return Ok(is_service)
+        }
+        let mc_state = self.engine()?.load_last_applied_mc_state().await?;
+        let config = mc_state.config_params()?;
+        let addresses = ServiceAddresses {
+            loaded_at: Instant::now(),
+            elector: config.elector_address().ok().map(AccountId::from),
+            config: config.config_address().ok().map(AccountId::from)
+        };
+        let is_service = addresses.is_service(&dst);
+        *self.service_addresses.lock().unwrap() = Some(addresses);
+        Ok(is_service)
+    }
+
     fn set_states_gc_interval(&self, interval_ms: u32) -> Result<Success> {
         self.engine()?.adjust_states_gc_interval(interval_ms);
         self.config.store_states_gc_interval(interval_ms);
         Ok(Success::Engine_Validator_Success)
     }
 
+    async fn process_node_control_query(&self, query: NodeControlQuery) -> Result<QueryResult> {
+        match query {
+            NodeControlQuery::ExportStateDiff(query) => {
+                let part = export_state_diff_part(self.engine()?, &query).await?;
+                QueryResult::consume_boxed(
+                    ton::ton_node::data::Data { data: part.into() }.into_boxed(),
+                    #[cfg(feature = "telemetry")]
+                    None
+                )
+            }
+            NodeControlQuery::ImportStateDiff(query) => {
+                let state = import_state_diff_part(self.engine()?, &query).await?;
+                let mut stats = Vec::new();
+                Self::add_stats(&mut stats, "received", query.offset + query.data.len() as u64);
+                if let Some(state) = state {
+                    Self::add_stats(&mut stats, "imported_state", format!("\"{}\"", state.block_id()));
+                }
+                QueryResult::consume_boxed(
+                    Stats {stats: stats.into()}.into_boxed(),
+                    #[cfg(feature = "telemetry")]
+                    None
+                )
+            }
+            NodeControlQuery::RestartRempSession(query) => {
+                let status = self.engine()?.inspect_remp_catchain_session(&query.queue_id, true).await?;
+                let mut stats = Vec::new();
+                Self::add_stats(&mut stats, "remp_session", status);
+                QueryResult::consume_boxed(
+                    Stats {stats: stats.into()}.into_boxed(),
+                    #[cfg(feature = "telemetry")]
+                    None
+                )
+            }
+            NodeControlQuery::MessageImport(query) => {
+                // only files from the DB directory may be imported
+                if query.file_name.contains('/') || query.file_name.contains('\\') || query.file_name == ".." {
+                    fail!("Messages must be given by file name in {} directory", MESSAGE_IMPORTS_DIR)
+                }
+                let engine = self.engine()?;
+                let path = Path::new(engine.db_root_dir()?).join(MESSAGE_IMPORTS_DIR).join(&query.file_name);
+                let report = import_external_messages(engine, &path, query.rate).await?;
+                let value = serde_json::json!({
+                    "accepted": report.accepted,
+                    "rejected": report.rejected,
+                });
+                let mut stats = Vec::new();
+                Self::add_stats(&mut stats, "message_import", format!("{:#}", value));
+                QueryResult::consume_boxed(
+                    Stats {stats: stats.into()}.into_boxed(),
+                    #[cfg(feature = "telemetry")]
+                    None
+                )
+            }
+            NodeControlQuery::CollationDryRun(query) => {
+                let shard = ShardIdent::with_tagged_prefix(query.workchain_id, query.shard_prefix_tagged)?;
+                let report = run_collate_dry_run(shard, self.engine()?.clone()).await?;
+                let remp_rejected = report.remp_rejected.iter()
+                    .map(|(id, reason)| (format!("{:x}", id), serde_json::Value::from(reason.as_str())))
+                    .collect::<serde_json::Map<_, _>>();
+                let value = serde_json::json!({
+                    "block_id": report.block_id.to_string(),
+                    "size": report.size,
+                    "gas_used": report.gas_used,
+                    "time_ms": report.duration_ms,
+                    "in_msg_count": report.in_msg_count,
+                    "out_msg_count": report.out_msg_count,
+                    "execute_count": report.execute_count,
+                    "dequeue_count": report.dequeue_count,
+                    "enqueue_count": report.enqueue_count,
+                    "transit_count": report.transit_count,
+                    "remp_accepted": report.remp_accepted.iter().map(|id| format!("{:x}", id)).collect::<Vec<_>>(),
+                    "remp_rejected": remp_rejected,
+                    "remp_ignored": report.remp_ignored.len(),
+                });
+                let mut stats = Vec::new();
+                Self::add_stats(&mut stats, "collation_dry_run", format!("{:#}", value));
+                QueryResult::consume_boxed(
+                    Stats {stats: stats.into()}.into_boxed(),
+                    #[cfg(feature = "telemetry")]
+                    None
+                )
+            }
+            NodeControlQuery::ValidateReplay(query) => {
+                let report = run_validate_replay(query.root_hash, self.engine()?.clone()).await?;
+                let config = report.config.as_ref().map(|config| serde_json::json!({
+                    "global_version": config.global_version,
+                    "capabilities": config.capabilities,
+                    "config_hash": format!("{:x}", config.config_hash),
+                }));
+                let transactions = report.transactions.iter()
+                    .map(|trans| serde_json::json!({
+                        "account": format!("{:x}", trans.account),
+                        "lt": trans.lt,
+                        "hash": format!("{:x}", trans.hash),
+                        "in_msg": trans.in_msg.as_ref().map(|hash| format!("{:x}", hash)),
+                        "out_msgs": trans.out_msgs,
+                        "error": trans.error,
+                    }))
+                    .collect::<Vec<_>>();
+                let value = serde_json::json!({
+                    "block_id": report.block_id.to_string(),
+                    "source": report.source,
+                    "valid": report.error.is_none(),
+                    "error": report.error,
+                    "time_ms": report.duration_ms,
+                    "config": config,
+                    "transactions": transactions,
+                });
+                let mut stats = Vec::new();
+                Self::add_stats(&mut stats, "validate_replay", format!("{:#}", value));
+                QueryResult::consume_boxed(
+                    Stats {stats: stats.into()}.into_boxed(),
+                    #[cfg(feature = "telemetry")]
+                    None
+                )
+            }
+            NodeControlQuery::PeerScoresReset(query) => {
+                let peer_scores = self.peer_scores.as_ref().ok_or_else(|| error!("Network is not set"))?;
+                let peer = query.peer.map(|peer| KeyId::from_data(*peer.as_array()));
+                let count = peer_scores.reset(peer.as_ref());
+                let mut stats = Vec::new();
+                Self::add_stats(&mut stats, "peer_scores_reset", count.to_string());
+                QueryResult::consume_boxed(
+                    Stats {stats: stats.into()}.into_boxed(),
+                    #[cfg(feature = "telemetry")]
+                    None
+                )
+            }
+        }
+    }
+
     async fn try_consume_query_impl(&self, object: TLObject, _peers: &AdnlPeers) -> Result<QueryResult> {
         log::debug!("recieve object (control server): {:?}", object);
         let query = match object.downcast::<ControlQuery>() {
-            Ok(query) => deserialize_boxed(&query.data[..])?,
+            Ok(query) => match deserialize_boxed(&query.data[..]) {
+                Ok(query) => query,
+                // the node's own queries are parsed only if ton_api doesn't know the query
+                Err(e) => match NodeControlQuery::deserialize(&query.data[..])? {
+                    Some(query) => return self.process_node_control_query(query).await,
+                    None => return Err(e)
+                }
+            },
             Err(object) => return Ok(QueryResult::Rejected(object))
         };
         log::debug!("query (control server): {:?}", query);
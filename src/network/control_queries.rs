@@ -0,0 +1,324 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use ton_types::{error, fail, Result, UInt256};
+
+#[cfg(test)]
+#[path = "tests/test_control_queries.rs"]
+mod tests;
+
+/// Maximal size of a state diff part transferred by one control query
+pub const MAX_STATE_DIFF_PART: u32 = 1 << 20;
+/// Maximal length of a file name in control queries
+pub const MAX_FILE_NAME_LEN: usize = 255;
+
+// Constructor ids are CRC32 of the scheme lines, as for any TL function:
+//
+// engine.validator.exportStateDiff base_root_hash:int256 target_root_hash:int256 offset:long max_size:int = tonNode.Data;
+// engine.validator.importStateDiff base_root_hash:int256 target_root_hash:int256 offset:long data:bytes last:Bool = engine.validator.Stats;
+// engine.validator.restartRempSession queue_id:int256 = engine.validator.Stats;
+// engine.validator.messageImport file_name:string rate:int = engine.validator.Stats;
+// engine.validator.collationDryRun workchain:int shard:long = engine.validator.Stats;
+// engine.validator.validateReplay root_hash:int256 = engine.validator.Stats;
+// engine.validator.peerScoresReset flags:# peer:flags.0?int256 = engine.validator.Stats;
+const EXPORT_STATE_DIFF_ID: u32 = 0x70a5a0dc;
+const IMPORT_STATE_DIFF_ID: u32 = 0x9642a4c3;
+const RESTART_REMP_SESSION_ID: u32 = 0xc9c6b5d9;
+const MESSAGE_IMPORT_ID: u32 = 0x37414b35;
+const COLLATION_DRY_RUN_ID: u32 = 0x5472dae6;
+const VALIDATE_REPLAY_ID: u32 = 0xcb52b8fb;
+const PEER_SCORES_RESET_ID: u32 = 0xe3553073;
+
+const BOOL_TRUE_ID: u32 = 0x997275b5;
+const BOOL_FALSE_ID: u32 = 0xbc799737;
+
+/// Control queries of the node's own TL scheme (see above), which is not a part of
+/// ton_api. They are sent as `data` of `engine.validator.controlQuery` like any other
+/// control query; the control server parses them only if ton_api doesn't know the query.
+/// Unlike stats filters, they are never answered by `getstats` queries.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeControlQuery {
+    ExportStateDiff(ExportStateDiff),
+    ImportStateDiff(ImportStateDiff),
+    RestartRempSession(RestartRempSession),
+    MessageImport(MessageImport),
+    CollationDryRun(CollationDryRun),
+    ValidateReplay(ValidateReplay),
+    PeerScoresReset(PeerScoresReset),
+}
+
+/// Part of state diff between persistent state `base_root_hash` and state `target_root_hash`,
+/// answered with `tonNode.data`. The diff is built by the first query and kept in the DB
+/// for the next parts; the last part is shorter than `max_size`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExportStateDiff {
+    pub base_root_hash: UInt256,
+    pub target_root_hash: UInt256,
+    pub offset: u64,
+    pub max_size: u32,
+}
+
+/// Part of state diff exported by another node of the operator. Parts are sent in order,
+/// the diff is imported after the `last` one; answered with `engine.validator.stats`
+/// (received bytes and imported state id)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportStateDiff {
+    pub base_root_hash: UInt256,
+    pub target_root_hash: UInt256,
+    pub offset: u64,
+    pub data: Vec<u8>,
+    pub last: bool,
+}
+
+/// Stops wedged REMP catchain session and starts it again, answered with
+/// `engine.validator.stats` (status of the restarted session)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RestartRempSession {
+    pub queue_id: UInt256,
+}
+
+/// Sends external messages from file `file_name` in `message_imports` DB directory
+/// through the external messages pipeline, at most `rate` messages per second (0 - unlimited);
+/// answered with `engine.validator.stats` (accepted count and rejected counts per reason)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessageImport {
+    pub file_name: String,
+    pub rate: u32,
+}
+
+/// Collates a block of the shard on top of the last applied masterchain state without
+/// sending the candidate and changing messages statuses; answered with `engine.validator.stats`
+/// (would-be block stats)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CollationDryRun {
+    pub workchain_id: i32,
+    pub shard_prefix_tagged: u64,
+}
+
+/// Re-runs full validation of the block (or candidate of the current sessions) with
+/// root hash `root_hash` against the stored states; answered with `engine.validator.stats`
+/// (result and trace of config and checked transactions)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidateReplay {
+    pub root_hash: UInt256,
+}
+
+/// Forgets overlay scores (and bans) of the peer `peer` or of all peers if it is not set;
+/// answered with `engine.validator.stats` (number of reset scores)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PeerScoresReset {
+    pub peer: Option<UInt256>,
+}
+
+impl NodeControlQuery {
+
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let mut writer = QueryWriter::default();
+        match self {
+            Self::ExportStateDiff(query) => {
+                writer.write_u32(EXPORT_STATE_DIFF_ID);
+                writer.write_uint256(&query.base_root_hash);
+                writer.write_uint256(&query.target_root_hash);
+                writer.write_u64(query.offset);
+                writer.write_u32(query.max_size);
+            }
+            Self::ImportStateDiff(query) => {
+                writer.write_u32(IMPORT_STATE_DIFF_ID);
+                writer.write_uint256(&query.base_root_hash);
+                writer.write_uint256(&query.target_root_hash);
+                writer.write_u64(query.offset);
+                writer.write_bytes(&query.data)?;
+                writer.write_bool(query.last);
+            }
+            Self::RestartRempSession(query) => {
+                writer.write_u32(RESTART_REMP_SESSION_ID);
+                writer.write_uint256(&query.queue_id);
+            }
+            Self::MessageImport(query) => {
+                writer.write_u32(MESSAGE_IMPORT_ID);
+                writer.write_bytes(query.file_name.as_bytes())?;
+                writer.write_u32(query.rate);
+            }
+            Self::CollationDryRun(query) => {
+                writer.write_u32(COLLATION_DRY_RUN_ID);
+                writer.write_i32(query.workchain_id);
+                writer.write_u64(query.shard_prefix_tagged);
+            }
+            Self::ValidateReplay(query) => {
+                writer.write_u32(VALIDATE_REPLAY_ID);
+                writer.write_uint256(&query.root_hash);
+            }
+            Self::PeerScoresReset(query) => {
+                writer.write_u32(PEER_SCORES_RESET_ID);
+                writer.write_u32(query.peer.is_some() as u32);
+                if let Some(peer) = &query.peer {
+                    writer.write_uint256(peer);
+                }
+            }
+        }
+        Ok(writer.data)
+    }
+
+    /// Returns None if data is not a query of the node's own scheme
+    pub fn deserialize(data: &[u8]) -> Result<Option<Self>> {
+        if data.len() < 4 {
+            return Ok(None)
+        }
+        let mut reader = QueryReader { data, pos: 0 };
+        let query = match reader.read_u32()? {
+            EXPORT_STATE_DIFF_ID => Self::ExportStateDiff(ExportStateDiff {
+                base_root_hash: reader.read_uint256()?,
+                target_root_hash: reader.read_uint256()?,
+                offset: reader.read_u64()?,
+                max_size: reader.read_u32()?,
+            }),
+            IMPORT_STATE_DIFF_ID => Self::ImportStateDiff(ImportStateDiff {
+                base_root_hash: reader.read_uint256()?,
+                target_root_hash: reader.read_uint256()?,
+                offset: reader.read_u64()?,
+                data: reader.read_bytes(MAX_STATE_DIFF_PART as usize)?,
+                last: reader.read_bool()?,
+            }),
+            RESTART_REMP_SESSION_ID => Self::RestartRempSession(RestartRempSession {
+                queue_id: reader.read_uint256()?,
+            }),
+            MESSAGE_IMPORT_ID => Self::MessageImport(MessageImport {
+                file_name: reader.read_string(MAX_FILE_NAME_LEN)?,
+                rate: reader.read_u32()?,
+            }),
+            COLLATION_DRY_RUN_ID => Self::CollationDryRun(CollationDryRun {
+                workchain_id: reader.read_i32()?,
+                shard_prefix_tagged: reader.read_u64()?,
+            }),
+            VALIDATE_REPLAY_ID => Self::ValidateReplay(ValidateReplay {
+                root_hash: reader.read_uint256()?,
+            }),
+            PEER_SCORES_RESET_ID => Self::PeerScoresReset(PeerScoresReset {
+                peer: match reader.read_u32()? {
+                    0 => None,
+                    1 => Some(reader.read_uint256()?),
+                    flags => fail!("Wrong flags {:x} in control query", flags)
+                },
+            }),
+            _ => return Ok(None)
+        };
+        reader.finish()?;
+        Ok(Some(query))
+    }
+}
+
+#[derive(Default)]
+struct QueryWriter {
+    data: Vec<u8>,
+}
+
+impl QueryWriter {
+    fn write_bool(&mut self, value: bool) {
+        self.write_u32(if value { BOOL_TRUE_ID } else { BOOL_FALSE_ID });
+    }
+    fn write_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+    fn write_i32(&mut self, value: i32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+    fn write_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+    fn write_uint256(&mut self, value: &UInt256) {
+        self.data.extend_from_slice(value.as_slice());
+    }
+    fn write_bytes(&mut self, value: &[u8]) -> Result<()> {
+        let len = value.len();
+        let header = if len < 254 {
+            self.data.push(len as u8);
+            1
+        } else if len < 1 << 24 {
+            self.data.push(254);
+            self.data.extend_from_slice(&(len as u32).to_le_bytes()[..3]);
+            4
+        } else {
+            fail!("TL bytes field is too long: {} bytes", len)
+        };
+        self.data.extend_from_slice(value);
+        self.data.resize(self.data.len() + (4 - (header + len) % 4) % 4, 0);
+        Ok(())
+    }
+}
+
+struct QueryReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl QueryReader<'_> {
+    fn read_slice(&mut self, len: usize) -> Result<&[u8]> {
+        if self.data.len() - self.pos < len {
+            fail!("Control query is too short: {} bytes", self.data.len())
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+    fn read_bool(&mut self) -> Result<bool> {
+        match self.read_u32()? {
+            BOOL_FALSE_ID => Ok(false),
+            BOOL_TRUE_ID => Ok(true),
+            value => fail!("Wrong Bool constructor {:x} in control query", value)
+        }
+    }
+    fn read_u32(&mut self) -> Result<u32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.read_slice(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+    fn read_i32(&mut self) -> Result<i32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.read_slice(4)?);
+        Ok(i32::from_le_bytes(bytes))
+    }
+    fn read_u64(&mut self) -> Result<u64> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_slice(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+    fn read_uint256(&mut self) -> Result<UInt256> {
+        Ok(UInt256::from_slice(self.read_slice(32)?))
+    }
+    fn read_bytes(&mut self, max_len: usize) -> Result<Vec<u8>> {
+        let (len, header) = match self.read_slice(1)?[0] {
+            254 => {
+                let len = self.read_slice(3)?;
+                (len[0] as usize | (len[1] as usize) << 8 | (len[2] as usize) << 16, 4)
+            }
+            255 => fail!("Wrong length prefix of TL bytes field in control query"),
+            len => (len as usize, 1)
+        };
+        if len > max_len {
+            fail!("Control query field of {} bytes, at most {} are allowed", len, max_len)
+        }
+        let value = self.read_slice(len)?.to_vec();
+        self.read_slice((4 - (header + len) % 4) % 4)?;
+        Ok(value)
+    }
+    fn read_string(&mut self, max_len: usize) -> Result<String> {
+        String::from_utf8(self.read_bytes(max_len)?)
+            .map_err(|e| error!("Wrong string in control query: {}", e))
+    }
+    fn finish(&self) -> Result<()> {
+        if self.pos != self.data.len() {
+            fail!("Control query has {} extra bytes", self.data.len() - self.pos)
+        }
+        Ok(())
+    }
+}
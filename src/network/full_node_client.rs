@@ -13,7 +13,12 @@
 
 use crate::{
     block::BlockStuff, block_proof::BlockProofStuff,
-    network::{neighbours::{Neighbours, Neighbour}, node_network::NetworkContext}, 
+    network::{
+        bandwidth::TrafficClass, compression::AnswersCompression,
+        neighbours::{Neighbours, Neighbour},
+        node_network::NetworkContext, peer_score::PeerEvent,
+        transfer_cache::{TransferCache, TransferProgress}
+    },
     shard_state::ShardStateStuff, types::top_block_descr::TopBlockDescrStuff
 };
 
@@ -23,7 +28,9 @@ use adnl::{
     node::AdnlNode
 };
 use overlay::{BroadcastSendInfo, OverlayShortId, OverlayNode};
-use std::{io::Cursor, time::Instant, sync::Arc, time::Duration, collections::HashSet};
+use std::{
+    io::Cursor, time::Instant, sync::Arc, time::Duration, collections::{BTreeMap, HashSet}
+};
 #[cfg(feature = "telemetry")]
 use std::sync::atomic::Ordering;
 use ton_api::{
@@ -94,9 +101,15 @@ pub trait FullNodeOverlayClient : Sync + Send {
     async fn download_archive(
         &self, 
         mc_seq_no: u32,
-        active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>
+        active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>,
+        progress: &TransferProgress
     ) -> Result<Option<Vec<u8>>>;
     async fn wait_broadcast(&self) -> Result<Option<(Broadcast, Arc<KeyId>)>>;
+    // result of the check of a broadcast got from the peer,
+    // not valid - the broadcast is proven to violate the protocol
+    fn broadcast_checked(&self, peer: &Arc<KeyId>, valid: bool);
+    // parts of interrupted downloads
+    fn transfer_cache(&self) -> &Arc<TransferCache>;
 }
 
 //    #[derive(Clone)]
@@ -438,7 +451,9 @@ impl NodeClientOverlay {
                 now.elapsed(), 
                 answer.len()
             );
-            Ok((answer, peer, roundtrip))
+            peer.add_served_bytes(answer.len());
+            self.network_context.bandwidth.download(TrafficClass::Public, answer.len()).await;
+            Ok((AnswersCompression::decompress(answer), peer, roundtrip))
         } else {
             #[cfg(feature = "telemetry")]
             self.network_context.telemetry.consumed_query(request_str, false, now.elapsed(), 0);
@@ -1006,7 +1021,8 @@ Ok(if key_block {
     async fn download_archive(
         &self, 
         mc_seq_no: u32,
-        active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>
+        active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>,
+        progress: &TransferProgress
     ) -> Result<Option<Vec<u8>>> {
 
         const CHUNK_SIZE: i32 = 1 << 21;
@@ -1030,8 +1046,14 @@ Ok(if key_block {
                 Ok(None)
             },
             ArchiveInfo::TonNode_ArchiveInfo(info) => {
-                let mut result = Vec::new();
-                let mut offset = 0;
+                // Packages of the same archive differ from node to node,
+                // so an interrupted download is resumed from the same peer only
+                let transfer_id = format!("archive {} from {}", info.id, peer.id());
+                let mut result = self.network_context.transfer_cache.resume(&transfer_id)
+                    .remove(&0)
+                    .unwrap_or_default();
+                progress.add_resumed(result.len());
+                let mut offset = result.len() as i64;
                 let mut part_attempt = 0;
                 let mut peer_attempt = 0;
                 loop {
@@ -1045,6 +1067,7 @@ Ok(if key_block {
                     match self.send_rldp_query_raw(&slice, peer.clone(), peer_attempt).await {
                         Ok(mut block_bytes) => {
                             let actual_size = block_bytes.len() as i32;
+                            progress.add_received(block_bytes.len());
                             result.append(&mut block_bytes);
                             if actual_size < CHUNK_SIZE {
                                 active_peers.remove(peer.id());
@@ -1062,6 +1085,10 @@ Ok(if key_block {
                             );
                             if part_attempt > 10 {
                                 active_peers.remove(peer.id());
+                                self.network_context.transfer_cache.save(
+                                    transfer_id, 
+                                    BTreeMap::from([(0, result)])
+                                );
                                 fail!(
                                     "Error download_archive after {} attempts : {}", 
                                     part_attempt, e
@@ -1078,14 +1105,41 @@ Ok(if key_block {
         loop {
             match self.network_context.overlay.wait_for_broadcast(&self.overlay_id).await? {
                 Some(info) => {
-                    let answer: Broadcast = Deserializer::new(
-                        &mut Cursor::new(info.data)
-                    ).read_boxed()?;
-                    break Ok(Some((answer, info.recv_from)))
+                    if self.peers.is_banned(&info.recv_from) {
+                        log::trace!("Skipped broadcast from banned peer {}", info.recv_from);
+                        continue
+                    }
+                    if self.network_context.broadcast_dedup.check(&info.data) {
+                        log::trace!("Skipped duplicate broadcast from {}", info.recv_from);
+                        continue
+                    }
+                    let answer = Deserializer::new(&mut Cursor::new(info.data)).read_boxed();
+                    match answer {
+                        Ok(answer) => break Ok(Some((answer, info.recv_from))),
+                        Err(e) => {
+                            self.peers.peer_event(&info.recv_from, PeerEvent::ProtocolViolation)?;
+                            fail!("Malformed broadcast from {}: {}", info.recv_from, e)
+                        }
+                    }
                 },
                 None => break Ok(None),
             }
         }
     }
 
+    fn broadcast_checked(&self, peer: &Arc<KeyId>, valid: bool) {
+        let event = if valid {
+            PeerEvent::ValidBroadcast
+        } else {
+            PeerEvent::ProtocolViolation
+        };
+        if let Err(e) = self.peers.peer_event(peer, event) {
+            log::warn!("Can't update score of peer {}: {}", peer, e);
+        }
+    }
+
+    fn transfer_cache(&self) -> &Arc<TransferCache> {
+        &self.network_context.transfer_cache
+    }
+
 }
@@ -13,7 +13,12 @@
 
 use crate::{
     engine_traits::EngineOperations, block::make_queue_update_from_block_raw,
-    network::neighbours::{PROTOCOL_CAPABILITIES, PROTOCOL_VERSION}
+    network::{
+        bandwidth::{BandwidthLimiter, TrafficClass}, capabilities_log::CapabilitiesLog,
+        compression::AnswersCompression,
+        neighbours::{CAPABILITY_COMPRESSED_ANSWERS, PROTOCOL_CAPABILITIES, PROTOCOL_VERSION},
+        node_network::NodeNetwork
+    }
 };
 
 use adnl::common::{AdnlPeers, Answer, QueryAnswer, QueryResult, TaggedByteVec, TaggedObject};
@@ -45,13 +50,16 @@ use ton_api::{
     }
 };
 use ton_block::BlockIdExt;
-use ton_types::{fail, Result};
+use ton_types::{fail, KeyId, Result};
 
 // max part size for partially transmitted data like archives and states
 const PART_MAX_SIZE: usize = 1 << 21; 
 
 pub struct FullNodeOverlayService {
     engine: Arc<dyn EngineOperations>,
+    bandwidth: Arc<BandwidthLimiter>,
+    capabilities_log: Arc<CapabilitiesLog>,
+    answers_compression: Arc<AnswersCompression>,
     #[cfg(feature = "telemetry")]
     tag_capabilities: u32,
     #[cfg(feature = "telemetry")]
@@ -60,9 +68,12 @@ pub struct FullNodeOverlayService {
 
 impl FullNodeOverlayService {
 
-    pub fn new(engine: Arc<dyn EngineOperations>) -> Self {
+    pub fn new(engine: Arc<dyn EngineOperations>, network: &NodeNetwork) -> Self {
         Self{
             engine,
+            bandwidth: network.bandwidth_limiter(),
+            capabilities_log: network.capabilities_log(),
+            answers_compression: network.answers_compression(),
             #[cfg(feature = "telemetry")]
             tag_capabilities: tag_from_boxed_type::<CapabilitiesBoxed>(),
             #[cfg(feature = "telemetry")]
@@ -710,9 +721,13 @@ impl FullNodeOverlayService {
         )
     }
 
+    // Answers to raw download queries may be compressed: the queries are always sent
+    // by RLDP, and the client decompresses RLDP answers. Other answers are never compressed,
+    // they may go by ADNL, where the overlay parses the answer itself.
     async fn consume_query_raw<'a, Q, F>(
         &'a self,
         query: TLObject,
+        peer: &Arc<KeyId>,
         consumer: &'a (dyn Fn(&'a Self, Q) -> F + Send + Sync)
     ) -> Result<std::result::Result<QueryResult, TLObject>>
     where
@@ -738,7 +753,7 @@ impl FullNodeOverlayService {
                             self.engine.full_node_service_telemetry().consumed_query(
                                 query_str, true, now.elapsed(), answer.object.len()
                             );
-                            answer
+                            self.compress_answer(answer, peer)?
                         }
                         Err(e) => {
                             #[cfg(feature = "telemetry")]
@@ -757,12 +772,34 @@ impl FullNodeOverlayService {
         )
     }
 
-}
+    // Compresses the answer if the peer supports it
+    fn compress_answer(&self, answer: TaggedByteVec, peer: &Arc<KeyId>) -> Result<TaggedByteVec> {
+        let compress = self.capabilities_log.get(peer).map_or(
+            false,
+            |obs| obs.capabilities & CAPABILITY_COMPRESSED_ANSWERS != 0
+        );
+        if !compress {
+            return Ok(answer)
+        }
+        Ok(match self.answers_compression.compress(&answer.object)? {
+            Some(object) => TaggedByteVec {
+                object,
+                #[cfg(feature = "telemetry")]
+                tag: answer.tag
+            },
+            None => answer
+        })
+    }
 
-#[async_trait::async_trait]
-impl QueriesConsumer for FullNodeOverlayService {
-    #[allow(dead_code)]
-    async fn try_consume_query(
+    // Applies bandwidth limits to the answer
+    async fn finish_answer(&self, result: QueryResult) -> QueryResult {
+        if let QueryResult::Consumed(QueryAnswer::Ready(Some(Answer::Raw(answer)))) = &result {
+            self.bandwidth.upload(TrafficClass::Public, answer.object.len()).await;
+        }
+        result
+    }
+
+    async fn consume_any_query(
         &self, 
         query: TLObject, 
         adnl_peers: &AdnlPeers
@@ -860,6 +897,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadBlock, _>(
             query,
+            adnl_peers.other(),
             &Self::download_block
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -868,6 +906,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadQueueUpdate, _>(
             query,
+            adnl_peers.other(),
             &Self::download_queue_update
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -876,6 +915,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadPersistentState, _>(
             query,
+            adnl_peers.other(),
             &Self::download_persistent_state
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -884,6 +924,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadPersistentStateSlice, _>(
             query,
+            adnl_peers.other(),
             &Self::download_persistent_state_slice
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -892,6 +933,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadPersistentMsgQueueSlice, _>(
             query,
+            adnl_peers.other(),
             &Self::download_persistent_msg_queue_slice
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -900,6 +942,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadZeroState, _>(
             query,
+            adnl_peers.other(),
             &Self::download_zero_state
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -908,6 +951,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadBlockProof, _>(
             query,
+            adnl_peers.other(),
             &Self::download_block_proof
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -916,6 +960,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadKeyBlockProof, _>(
             query,
+            adnl_peers.other(),
             &Self::download_key_block_proof
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -924,6 +969,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadBlockProofLink, _>(
             query,
+            adnl_peers.other(),
             &Self::download_block_proof_link
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -932,6 +978,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<DownloadKeyBlockProofLink, _>(
             query,
+            adnl_peers.other(),
             &Self::download_key_block_proof_link
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -948,6 +995,7 @@ impl QueriesConsumer for FullNodeOverlayService {
 
         let query = match self.consume_query_raw::<GetArchiveSlice, _>(
             query,
+            adnl_peers.other(),
             &Self::get_archive_slice
         ).await? {
             Ok(answer) => return Ok(answer),
@@ -966,3 +1014,15 @@ impl QueriesConsumer for FullNodeOverlayService {
         fail!("Unsupported full node query {:?}", query);
     }
 }
+
+#[async_trait::async_trait]
+impl QueriesConsumer for FullNodeOverlayService {
+    async fn try_consume_query(
+        &self, 
+        query: TLObject, 
+        adnl_peers: &AdnlPeers
+    ) -> Result<QueryResult> {
+        let result = self.consume_any_query(query, adnl_peers).await?;
+        Ok(self.finish_answer(result).await)
+    }
+}
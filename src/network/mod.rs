@@ -11,12 +11,24 @@
 * limitations under the License.
 */
 
+pub mod bandwidth;
+pub mod broadcast_dedup;
+pub mod capabilities_log;
+pub mod compression;
 pub mod catchain_client;
+pub mod catchain_delivery;
+pub mod catchain_relay;
+pub mod channel_health;
+pub mod transfer_cache;
 pub mod node_network;
 pub mod neighbours;
+pub mod peer_cache;
+pub mod peer_score;
 pub mod full_node_client;
 pub mod full_node_service;
 pub mod control;
+pub mod control_queries;
 #[cfg(feature = "telemetry")]
 pub mod telemetry;
-pub mod remp;
\ No newline at end of file
+pub mod remp;
+pub mod send_queue;
\ No newline at end of file
@@ -11,7 +11,10 @@
 * limitations under the License.
 */
 
-use crate::network::node_network::NodeNetwork;
+use crate::network::{
+    capabilities_log::CapabilitiesLog, node_network::NodeNetwork,
+    peer_score::{OverlayPeerScores, PeerEvent},
+};
 
 use adnl::{common::{Query, TaggedTlObject, Wait}, node::{AdnlNode, AddressCache}};
 use dht::DhtNode;
@@ -39,7 +42,8 @@ pub struct Neighbour {
     fail_attempts: AtomicU64,
     fines_points: AtomicU32,
     active_check: AtomicBool,
-    unreliability: AtomicI32
+    unreliability: AtomicI32,
+    served_bytes: AtomicU64
 }
 
 pub struct Neighbours {
@@ -48,6 +52,8 @@ pub struct Neighbours {
     overlay_id: Arc<OverlayShortId>,
     overlay: Arc<OverlayNode>,
     dht: Arc<DhtNode>,
+    capabilities_log: Arc<CapabilitiesLog>,
+    scores: Arc<OverlayPeerScores>,
     fail_attempts: AtomicU64,
     all_attempts: AtomicU64,
     start: Instant,
@@ -58,8 +64,11 @@ pub struct Neighbours {
 
 const CAPABILITY_COMPATIBLE: i64 = 0x01;
 const VERSION_COMPATIBLE: i32 = 2;
+// large answers to queries may be compressed (see AnswersCompression); optional,
+// it is checked by the answering side only and doesn't affect neighbour choice
+pub const CAPABILITY_COMPRESSED_ANSWERS: i64 = 0x02;
 
-pub const PROTOCOL_CAPABILITIES: i64 = CAPABILITY_COMPATIBLE;
+pub const PROTOCOL_CAPABILITIES: i64 = CAPABILITY_COMPATIBLE | CAPABILITY_COMPRESSED_ANSWERS;
 pub const PROTOCOL_VERSION: i32 = VERSION_COMPATIBLE;
 pub const STOP_UNRELIABILITY: i32 = 5;
 pub const FAIL_UNRELIABILITY: i32 = 10;
@@ -83,6 +92,7 @@ impl Neighbour {
             //roundtrip_relax_at: 0,
             //roundtrip_weight: 0.0,
             unreliability: AtomicI32::new(0),
+            served_bytes: AtomicU64::new(0),
         }
     }
 
@@ -94,6 +104,46 @@ impl Neighbour {
     pub fn id(&self) -> &Arc<KeyId> {
         &self.id
     }
+
+    pub fn add_served_bytes(&self, bytes: usize) {
+        self.served_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn failure_rate(&self) -> f64 {
+        let all = self.all_attempts.load(Ordering::Relaxed);
+        if all == 0 {
+            0.0
+        } else {
+            self.fail_attempts.load(Ordering::Relaxed) as f64 / all as f64
+        }
+    }
+
+    // Faster and more successful neighbours are chosen more often
+    fn speed_factor(&self) -> f64 {
+        let roundtrip = self.roundtrip_rldp().or_else(|| self.roundtrip_adnl());
+        let latency = match roundtrip {
+            Some(roundtrip) => (Neighbours::REFERENCE_ROUNDTRIP_MS as f64 / roundtrip as f64)
+                .clamp(0.25, 4.0),
+            None => 1.0
+        };
+        latency * (1.0 - self.failure_rate()).max(0.1)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "peer": self.id.to_string(),
+            "roundtrip_adnl_ms": self.roundtrip_adnl.load(Ordering::Relaxed),
+            "roundtrip_rldp_ms": self.roundtrip_rldp.load(Ordering::Relaxed),
+            "unreliability": self.unreliability.load(Ordering::Relaxed),
+            "queries": self.all_attempts.load(Ordering::Relaxed),
+            "failed_queries": self.fail_attempts.load(Ordering::Relaxed),
+            "failure_rate": self.failure_rate(),
+            "served_bytes": self.served_bytes.load(Ordering::Relaxed),
+            "speed_factor": self.speed_factor(),
+            "proto_version": self.proto_version.load(Ordering::Relaxed),
+            "capabilities": self.capabilities.load(Ordering::Relaxed),
+        })
+    }
     
     pub fn query_success(&self, roundtrip: u64, is_rldp: bool) {
         loop {
@@ -184,10 +234,14 @@ impl Neighbour {
 }
 
 pub const MAX_NEIGHBOURS: usize = 16;
+// neighbours which are not banned, however bad they are
+const MIN_NEIGHBOURS_TO_KEEP: usize = 4;
 
 impl Neighbours {
 
     const DEFAULT_RLDP_ROUNDTRIP_MS: u32 = 2000;
+    // roundtrip of neighbour with usual selection weight
+    const REFERENCE_ROUNDTRIP_MS: u64 = 500;
     const MAX_PINGS: usize = 6;
     const TIMEOUT_PING_MAX_MS: u64 = 1000;
     const TIMEOUT_RELOAD_MAX_SEC: u64 = 30;
@@ -203,6 +257,8 @@ impl Neighbours {
         overlay: &Arc<OverlayNode>,
         overlay_id: Arc<OverlayShortId>,
         default_rldp_roundtrip: &Option<u32>,
+        capabilities_log: Arc<CapabilitiesLog>,
+        scores: Arc<OverlayPeerScores>,
         cancellation_token: Arc<tokio_util::sync::CancellationToken>
     ) -> Result<Self> {
         let default_rldp_roundtrip = default_rldp_roundtrip.unwrap_or(
@@ -213,6 +269,8 @@ impl Neighbours {
             all_peers: lockfree::set::Set::new(),
             overlay: overlay.clone(),
             dht: dht.clone(),
+            capabilities_log,
+            scores,
             overlay_id,
             fail_attempts: AtomicU64::new(0),
             all_attempts: AtomicU64::new(0),
@@ -259,7 +317,7 @@ impl Neighbours {
 
         let (mut iter, mut current) = peers.first();
         while let Some(elem) = current {
-            if self.contains(&elem) {
+            if self.contains(&elem) || self.scores.is_banned(&elem) {
                 current = peers.next(&mut iter);
                 continue;
             }
@@ -317,10 +375,15 @@ impl Neighbours {
             self.cancellation_token.clone(),
             async move {
                 loop {
-                    let sleep_time = rand::thread_rng().gen_range(
-                        Self::TIMEOUT_RELOAD_MIN_SEC,
-                        Self::TIMEOUT_RELOAD_MAX_SEC
-                    );
+                    // neighbours are refreshed faster while there are free or bad ones
+                    let sleep_time = if self.needs_refresh() {
+                        Self::TIMEOUT_RELOAD_MIN_SEC
+                    } else {
+                        rand::thread_rng().gen_range(
+                            Self::TIMEOUT_RELOAD_MIN_SEC,
+                            Self::TIMEOUT_RELOAD_MAX_SEC
+                        )
+                    };
                     tokio::time::sleep(Duration::from_secs(sleep_time)).await;
                     if let Err(e) = self.reload_neighbours(&self.overlay_id).await {
                         log::warn!("reload neighbours err: {:?}", e);
@@ -339,6 +402,25 @@ impl Neighbours {
         )
     }
 
+    fn needs_refresh(&self) -> bool {
+        (self.count() < MAX_NEIGHBOURS) || self.peers.get_iter().any(|neighbour| {
+            neighbour.unreliability.load(Ordering::Relaxed) > STOP_UNRELIABILITY
+        })
+    }
+
+    /// Statistics of neighbours for control server
+    pub fn to_json(&self) -> serde_json::Value {
+        let neighbours = self.peers.get_iter()
+            .map(|neighbour| neighbour.to_json())
+            .collect::<Vec<_>>();
+        serde_json::json!({
+            "overlay": self.overlay_id.to_string(),
+            "queries": self.all_attempts.load(Ordering::Relaxed),
+            "failed_queries": self.fail_attempts.load(Ordering::Relaxed),
+            "neighbours": neighbours,
+        })
+    }
+
     pub async fn reload_neighbours(&self, overlay_id: &Arc<OverlayShortId>) -> Result<()> {
         log::trace!("start reload_neighbours (overlay: {})", overlay_id);
         let neighbours_cache = AddressCache::with_limit((MAX_NEIGHBOURS * 2 + 1) as u32);
@@ -396,6 +478,10 @@ impl Neighbours {
         let this = self.clone();
         tokio::spawn(async move {
             for peer in peers.iter() {
+                if this.scores.is_banned(peer) {
+                    log::debug!("add_new_peers: peer {} is banned", peer);
+                    continue
+                }
                 log::trace!("add_new_peers: searching IP for peer {}...", peer);
                 match DhtNode::find_address(&this.dht, peer).await {
                     Ok(Some((ip, _))) => {
@@ -419,13 +505,18 @@ impl Neighbours {
 
         let mut rng = rand::thread_rng();
         let mut best: Option<Arc<Neighbour>> = None; 
-        let mut sum = 0;
+        let mut sum = 0.0;
         let node_stat = self.fail_attempts.load(Ordering::Relaxed) as f64 / 
             self.all_attempts.load(Ordering::Relaxed) as f64;
 
         log::trace!("Select neighbour for overlay {}", self.overlay_id);
         for neighbour in self.peers.get_iter() {
-            let mut unr = neighbour.unreliability.load(Ordering::Relaxed);
+            if self.scores.is_banned(neighbour.id()) {
+                log::trace!("Neighbour {} is banned", neighbour.id());
+                continue
+            }
+            let mut unr = neighbour.unreliability.load(Ordering::Relaxed) +
+                self.scores.penalty(neighbour.id());
             let version = neighbour.proto_version.load(Ordering::Relaxed);
             let capabilities = neighbour.capabilities.load(Ordering::Relaxed);
             let roundtrip_rldp = neighbour.roundtrip_rldp.load(Ordering::Relaxed);
@@ -439,7 +530,7 @@ impl Neighbours {
             }
             if version < PROTOCOL_VERSION {
                 unr += 4;
-            } else if (version == PROTOCOL_VERSION) && (capabilities < PROTOCOL_CAPABILITIES) {
+            } else if (version == PROTOCOL_VERSION) && (capabilities & CAPABILITY_COMPATIBLE == 0) {
                 unr += 2;
             }
             let labels = [("neighbour", neighbour.id().to_string())];
@@ -470,10 +561,10 @@ impl Neighbours {
                     neighbour.active_check.store(true, Ordering::Relaxed);
                 }
 
-                let w = (1 << (FAIL_UNRELIABILITY - unr)) as i64;
+                let w = (1 << (FAIL_UNRELIABILITY - unr)) as f64 * neighbour.speed_factor();
                 sum += w;
 
-                if rng.gen_range(0, sum) < w {
+                if rng.gen_range(0.0, sum) < w {
                     best = Some(neighbour.clone());
                 }
             }
@@ -496,6 +587,12 @@ impl Neighbours {
         is_register: bool
     ) -> Result<()> {
         log::trace!("update_neighbour_stats");
+        let event = if success {
+            PeerEvent::QuerySuccess
+        } else {
+            PeerEvent::QueryFailure
+        };
+        self.peer_event(peer, event)?;
         let it = &self.peers.get(peer);
         if let Some(neighbour) = it {
             if success {
@@ -522,12 +619,28 @@ impl Neighbours {
         Ok(())
     }
 
+    /// Updates the score of the peer, a banned peer is removed from the overlay.
+    /// The last `MIN_NEIGHBOURS_TO_KEEP` neighbours are never banned
+    pub fn peer_event(&self, peer: &Arc<KeyId>, event: PeerEvent) -> Result<()> {
+        let can_ban = !self.contains(peer) || (self.count() > MIN_NEIGHBOURS_TO_KEEP);
+        if self.scores.record(peer, event, can_ban)? {
+            self.overlay.delete_public_peer(peer, &self.overlay_id)?;
+            self.remove_overlay_peer(peer);
+        }
+        Ok(())
+    }
+
+    pub fn is_banned(&self, peer: &Arc<KeyId>) -> bool {
+        self.scores.is_banned(peer)
+    }
+
     pub fn got_neighbour_capabilities(
         &self, 
         peer: &Arc<KeyId>, 
         _roundtrip: u64, 
         capabilities: &Capabilities
     ) -> Result<()> {
+        self.capabilities_log.observe(peer, capabilities);
         if let Some(it) = &self.peers.get(peer) {
   //          log::trace!("got_neighbour_capabilities: capabilities: {:?}", capabilities);
   //          log::trace!("got_neighbour_capabilities: roundtrip: {} ms", roundtrip);
@@ -13,14 +13,18 @@
 
 use crate::{
     config::{ 
-        ConfigEvent, NodeConfigHandler, NodeConfigSubscriber, TonNodeConfig, 
+        CatchainRelayConfig, ChannelsKeepaliveConfig, ConfigEvent, NodeConfigHandler, NodeConfigSubscriber, TonNodeConfig, 
         ConnectivityCheckBroadcastConfig
     },
     engine_traits::{EngineAlloc, OverlayOperations, PrivateOverlayOperations},
     network::{
+        bandwidth::BandwidthLimiter, broadcast_dedup::BroadcastDedup,
+        capabilities_log::CapabilitiesLog, compression::AnswersCompression,
         catchain_client::CatchainClient,
         full_node_client::{NodeClientOverlay, FullNodeOverlayClient},
-        neighbours::{self, Neighbours}, remp::RempNode,
+        neighbours::{self, Neighbours}, peer_cache::PeerCache, peer_score::PeerScores,
+        remp::RempNode,
+        send_queue::{QosClass, SendQueues}, transfer_cache::TransferCache,
     },
     types::awaiters_pool::AwaitersPool,
 };
@@ -33,7 +37,7 @@ use crate::{
 use adnl::{
     declare_counted, 
     common::{add_counted_object_to_map, CountedObject, Counter, TaggedByteSlice}, 
-    node::AdnlNode
+    node::{AdnlNode, AdnlNodeConfig}
 };
 use catchain::{
     CatchainNode, CatchainOverlay, CatchainOverlayListenerPtr, CatchainOverlayLogReplayListenerPtr
@@ -45,7 +49,7 @@ use overlay::{
 };
 use rldp::RldpNode;
 use std::{
-    convert::TryInto, future::Future, hash::Hash, 
+    collections::HashSet, convert::TryInto, future::Future, hash::Hash, 
     sync::{Arc, atomic::{AtomicI32, AtomicU64, AtomicBool, Ordering}}, 
     time::{Duration, SystemTime}
 };
@@ -65,7 +69,22 @@ pub (crate) struct NetworkContext {
     pub overlay: Arc<OverlayNode>,
     pub rldp: Arc<RldpNode>,
     pub remp: Arc<RempNode>,
+    // ADNL stack of validator overlays, the same as above unless a separate address is set
+    pub validator_adnl: Arc<AdnlNode>,
+    pub validator_dht: Arc<DhtNode>,
+    pub validator_overlay: Arc<OverlayNode>,
+    pub validator_rldp: Arc<RldpNode>,
     pub broadcast_hops: Option<u8>,
+    pub capabilities_log: Arc<CapabilitiesLog>,
+    pub send_queues: Arc<SendQueues>,
+    pub peer_scores: Arc<PeerScores>,
+    pub broadcast_dedup: Arc<BroadcastDedup>,
+    pub peer_cache: Arc<PeerCache>,
+    pub bandwidth: Arc<BandwidthLimiter>,
+    pub answers_compression: Arc<AnswersCompression>,
+    pub catchain_relay: CatchainRelayConfig,
+    pub channels_keepalive: ChannelsKeepaliveConfig,
+    pub transfer_cache: Arc<TransferCache>,
     #[cfg(feature = "telemetry")]
     pub telemetry: Arc<FullNodeNetworkTelemetry>,
     #[cfg(feature = "telemetry")]
@@ -119,7 +138,9 @@ declare_counted!(
         validator_key: Arc<dyn KeyOption>,
         validator_adnl_key: Arc<dyn KeyOption>,
         election_id: usize,
-        connectivity_stat: Arc<Cache<Arc<KeyId>, ConnectivityStat>> // (last short broadcast got, last long -//-)
+        connectivity_stat: Arc<Cache<Arc<KeyId>, ConnectivityStat>>, // (last short broadcast got, last long -//-)
+        // peers with known addresses, they are kept as is for the next lists
+        resolved_peers: Vec<Arc<KeyId>>
     }
 );
 
@@ -164,11 +185,52 @@ impl NodeNetwork {
             overlay.set_broadcast_retransmit(false)
         }
         let rldp = RldpNode::with_adnl_node(adnl.clone(), vec![overlay.clone()])?;
-        let remp = Arc::new(RempNode::new(adnl.clone(), Self::TAG_OVERLAY_KEY)?);
+
+        // Validator overlays are served on their own socket if it is configured. 
+        // Validator ADNL keys are added to this stack, so their addresses in DHT 
+        // are the addresses of the socket.
+        let (validator_adnl, validator_dht, validator_overlay, validator_rldp) = 
+            match &config.validator_network_config().ip_address {
+                Some(ip_address) => {
+                    let (adnl_config, _) = AdnlNodeConfig::with_ip_address_and_private_key_tags(
+                        ip_address,
+                        vec![Self::TAG_DHT_KEY, Self::TAG_OVERLAY_KEY]
+                    )?;
+                    let adnl = AdnlNode::with_config(adnl_config).await?;
+                    if !config.extensions().disable_compression {
+                        adnl.set_options(AdnlNode::OPTION_FORCE_COMPRESSION)
+                    }
+                    let dht = DhtNode::with_adnl_node(adnl.clone(), Self::TAG_DHT_KEY)?;
+                    let overlay = OverlayNode::with_adnl_node_and_zero_state(
+                        adnl.clone(), 
+                        masterchain_zero_state_id.file_hash.as_slice(),
+                        Self::TAG_OVERLAY_KEY
+                    )?;
+                    if config.extensions().disable_broadcast_retransmit {
+                        overlay.set_broadcast_retransmit(false)
+                    }
+                    let rldp = RldpNode::with_adnl_node(adnl.clone(), vec![overlay.clone()])?;
+                    (adnl, dht, overlay, rldp)
+                }
+                None => (adnl.clone(), dht.clone(), overlay.clone(), rldp.clone())
+            };
+        // REMP messages and receipts to validator keys come to the validator stack
+        let remp = Arc::new(
+            RempNode::new(adnl.clone(), validator_adnl.clone(), Self::TAG_OVERLAY_KEY)?
+        );
 
         let nodes = global_config.dht_nodes()?;
         for peer in nodes.iter() {
             dht.add_peer(peer)?;
+            if !Arc::ptr_eq(&dht, &validator_dht) {
+                validator_dht.add_peer(peer)?;
+            }
+        }
+        let peer_cache = Arc::new(PeerCache::with_dir(Some(config.internal_db_path())));
+        for peer in peer_cache.dht_nodes().iter() {
+            if let Err(e) = dht.add_peer(peer) {
+                log::warn!("Can't add cached DHT node: {}", e)
+            }
         }
 
         let masterchain_overlay_short_id = overlay.calc_overlay_short_id(
@@ -183,8 +245,32 @@ impl NodeNetwork {
         NodeNetwork::periodic_store_ip_addr(dht.clone(), overlay_key, None, cancellation_token.clone());
 
         let default_rldp_roundtrip = config.default_rldp_roundtrip();
+        let broadcast_dedup = Arc::new(
+            BroadcastDedup::new(config.broadcast_dedup_config().memory_limit_bytes)
+        );
+        let bandwidth = Arc::new(BandwidthLimiter::new(config.bandwidth_limits_config()));
+        let answers_compression = Arc::new(
+            AnswersCompression::new(config.answers_compression_config())
+        );
+        let catchain_relay = config.catchain_relay_config().clone();
+        let channels_keepalive = config.channels_keepalive_config().clone();
+        let transfer_cache = Arc::new(TransferCache::new(
+            Duration::from_secs(config.transfer_cache_config().ttl_sec),
+            config.transfer_cache_config().memory_limit_bytes
+        ));
+
+        let capabilities_log = Arc::new(CapabilitiesLog::with_dir(Some(config.internal_db_path())));
+        NodeNetwork::periodic_save_capabilities_log(
+            capabilities_log.clone(),
+            cancellation_token.clone()
+        );
 
         NodeNetwork::find_dht_nodes(dht.clone(), cancellation_token.clone());
+        NodeNetwork::periodic_save_peer_cache(
+            dht.clone(),
+            peer_cache.clone(),
+            cancellation_token.clone()
+        );
         let (config_handler, config_handler_context) = NodeConfigHandler::create(
             config, tokio::runtime::Handle::current()
         )?;
@@ -200,13 +286,34 @@ impl NodeNetwork {
             current_set: Arc::new(Cache::new()),
         };
 
+        let send_queues = Arc::new(
+            SendQueues::new(
+                tokio::runtime::Handle::current(), 
+                validator_overlay.clone(), 
+                bandwidth.clone()
+            )
+        );
         let network_context = NetworkContext {
             adnl,
             dht,
             overlay,
             rldp,
             remp,
+            validator_adnl,
+            validator_dht,
+            validator_overlay,
+            validator_rldp,
             broadcast_hops,
+            capabilities_log,
+            send_queues,
+            peer_scores: Arc::new(PeerScores::new()),
+            broadcast_dedup,
+            peer_cache,
+            bandwidth,
+            answers_compression,
+            catchain_relay,
+            channels_keepalive,
+            transfer_cache,
             #[cfg(feature = "telemetry")]
             telemetry: Arc::new(
                 FullNodeNetworkTelemetry::new(FullNodeNetworkTelemetryKind::Client)
@@ -252,6 +359,30 @@ impl NodeNetwork {
 
     }
 
+    pub fn bandwidth_limiter(&self) -> Arc<BandwidthLimiter> {
+        self.network_context.bandwidth.clone()
+    }
+
+    pub fn answers_compression(&self) -> Arc<AnswersCompression> {
+        self.network_context.answers_compression.clone()
+    }
+
+    pub fn overlay_clients(&self) -> Arc<Cache<Arc<OverlayShortId>, Arc<NodeClientOverlay>>> {
+        self.overlays.clone()
+    }
+
+    pub fn catchain_clients(&self) -> Arc<Cache<Arc<OverlayShortId>, Arc<CatchainClient>>> {
+        self.validator_context.private_overlays.clone()
+    }
+
+    pub fn peer_scores(&self) -> Arc<PeerScores> {
+        self.network_context.peer_scores.clone()
+    }
+
+    pub fn capabilities_log(&self) -> Arc<CapabilitiesLog> {
+        self.network_context.capabilities_log.clone()
+    }
+
     pub fn get_key_id_by_tag(&self, tag: usize) -> Result<Arc<KeyId>> {
         let key_id = self.network_context.adnl.key_by_tag(tag)?;
         Ok(key_id.id().clone())
@@ -277,9 +408,16 @@ impl NodeNetwork {
         self.cancellation_token.cancel();
         log::info!("Node network loops stopped. Stopping adnl...");
         self.network_context.adnl.stop().await;
+        if self.has_validator_network() {
+            self.network_context.validator_adnl.stop().await;
+        }
         log::info!("Stopped adnl");
     }
 
+    fn has_validator_network(&self) -> bool {
+        !Arc::ptr_eq(&self.network_context.adnl, &self.network_context.validator_adnl)
+    }
+
     fn try_add_new_elem<K: Hash + Ord + Clone, T: CountedObject>(
         &self,
         cache: &Arc<Cache<K, Arc<T>>>,
@@ -314,6 +452,44 @@ impl NodeNetwork {
         );                                                                         
     }
 
+    fn periodic_save_capabilities_log(
+        capabilities_log: Arc<CapabilitiesLog>,
+        cancellation_token: Arc<tokio_util::sync::CancellationToken>
+    ) {
+        Self::spawn_background_task(
+            cancellation_token,
+            async move {
+                loop {
+                    tokio::time::sleep(CapabilitiesLog::SAVE_PERIOD).await;
+                    if let Err(e) = capabilities_log.save() {
+                        log::warn!("Can't save neighbours capabilities: {}", e)
+                    }
+                }
+            }
+        );
+    }
+
+    fn periodic_save_peer_cache(
+        dht: Arc<DhtNode>,
+        peer_cache: Arc<PeerCache>,
+        cancellation_token: Arc<tokio_util::sync::CancellationToken>
+    ) {
+        Self::spawn_background_task(
+            cancellation_token,
+            async move {
+                loop {
+                    tokio::time::sleep(PeerCache::SAVE_PERIOD).await;
+                    let result = dht.get_known_nodes(PeerCache::MAX_DHT_NODES)
+                        .and_then(|nodes| peer_cache.set_dht_nodes(nodes))
+                        .and_then(|_| peer_cache.save());
+                    if let Err(e) = result {
+                        log::warn!("Can't save known peers: {}", e)
+                    }
+                }
+            }
+        );
+    }
+
     fn periodic_store_ip_addr(
         dht: Arc<DhtNode>,
         node_key: Arc<dyn KeyOption>,
@@ -362,14 +538,16 @@ impl NodeNetwork {
         dht: Arc<DhtNode>,
         overlay: Arc<OverlayNode>,
         overlay_id: Arc<OverlayShortId>,
+        peer_cache: Arc<PeerCache>,
         cancellation_token: Arc<tokio_util::sync::CancellationToken>
     ) {
         Self::spawn_background_task(
             cancellation_token,
             async move {
                 loop {
-                    let got_peers =
-                      Self::add_overlay_peers(&neighbours, &dht, &overlay, &overlay_id).await.
+                    let got_peers = Self::add_overlay_peers(
+                        &neighbours, &dht, &overlay, &overlay_id, &peer_cache
+                    ).await.
                         unwrap_or_else(|e| { log::warn!("add_overlay_peers: {}", e); false } );
                     if got_peers {
                         // Do not sleep, get next one from queue
@@ -387,7 +565,8 @@ impl NodeNetwork {
         neighbours: &Arc<Neighbours>,
         dht: &Arc<DhtNode>,
         overlay: &Arc<OverlayNode>,
-        overlay_id: &Arc<OverlayShortId>
+        overlay_id: &Arc<OverlayShortId>,
+        peer_cache: &PeerCache
     ) -> Result<bool> {
         match overlay.wait_for_peers(&overlay_id).await? {
             None => Ok(false),
@@ -399,6 +578,7 @@ impl NodeNetwork {
                     }
                     if let Some((ip, _)) = DhtNode::find_address(dht, peer_key.id()).await? {
                         overlay.add_public_peer(&ip, peer, overlay_id)?;
+                        peer_cache.add_overlay_node(overlay_id, peer)?;
                         if neighbours.add_overlay_peer(peer_key.id().clone()) {
                             log::trace!("add_overlay_peers: add overlay peer {:?}, address: {}", peer, ip);
                         }
@@ -463,12 +643,44 @@ impl NodeNetwork {
             log::trace!("Node: {:?}, address: {}", node, ip);
             let peer = self.network_context.overlay.add_public_peer(ip, node, overlay_id)?;
             if let Some(peer) = peer {
+                self.network_context.peer_cache.add_overlay_node(overlay_id, node)?;
                 ret.push(peer);
             }
         }
         Ok(ret)
     }
 
+    // Adds overlay peers saved before restart, their addresses are resolved via DHT
+    async fn add_cached_overlay_peers(
+        &self,
+        overlay_id: &Arc<OverlayShortId>
+    ) -> Result<Vec<Arc<KeyId>>> {
+        let nodes = self.network_context.peer_cache.overlay_nodes(overlay_id);
+        let mut searches = Vec::new();
+        for node in nodes {
+            let key: Arc<dyn KeyOption> = (&node.id).try_into()?;
+            let dht = self.network_context.dht.clone();
+            searches.push(async move {
+                (DhtNode::find_address(&dht, key.id()).await, node)
+            });
+        }
+        let mut ret = Vec::new();
+        for (address, node) in futures::future::join_all(searches).await {
+            match address {
+                Ok(Some((ip, _))) => {
+                    let peer = self.network_context.overlay.add_public_peer(&ip, &node, overlay_id)?;
+                    if let Some(peer) = peer {
+                        ret.push(peer);
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => log::debug!("Can't resolve address of cached overlay peer: {}", e)
+            }
+        }
+        log::info!("Added {} cached peers to overlay {}", ret.len(), overlay_id);
+        Ok(ret)
+    }
+
     async fn update_peers(
         &self,
         client_overlay: &Arc<NodeClientOverlay>,
@@ -588,7 +800,11 @@ impl NodeNetwork {
             self.cancellation_token.clone(),
         );
 
-        let peers = self.update_overlay_peers(&overlay_id_short, &mut None).await?;
+        // cached peers are enough to start, others are searched in background
+        let mut peers = self.add_cached_overlay_peers(&overlay_id_short).await?;
+        if peers.is_empty() {
+            peers = self.update_overlay_peers(&overlay_id_short, &mut None).await?;
+        }
         if peers.first().is_none() {
             log::warn!("No nodes were found in overlay {}", &overlay_id_short);
         }
@@ -599,6 +815,8 @@ impl NodeNetwork {
             &self.network_context.overlay,
             overlay_id_short.clone(),
             &self.default_rldp_roundtrip,
+            self.network_context.capabilities_log.clone(),
+            self.network_context.peer_scores.overlay(&overlay_id_short)?,
             self.cancellation_token.clone()
         )?;
 
@@ -625,6 +843,7 @@ impl NodeNetwork {
             self.network_context.dht.clone(), 
             self.network_context.overlay.clone(), 
             overlay_id_short.clone(),
+            self.network_context.peer_cache.clone(),
             self.cancellation_token.clone(),
         );
         log::info!("Started Overlay {}", &overlay_id_short);
@@ -706,8 +925,8 @@ impl NodeNetwork {
     ) {
 
         let dht = self.network_context.dht.clone();
-        let adnl = self.network_context.adnl.clone();
-        let overlay = self.network_context.overlay.clone();
+        let adnl = self.network_context.validator_adnl.clone();
+        let overlay = self.network_context.validator_overlay.clone();
 
         Self::spawn_background_task(
             self.cancellation_token.clone(),
@@ -900,6 +1119,18 @@ impl NodeNetwork {
         self.validator_context.sets_contexts.get(id.val())
     }
 
+    // Peers already added to private overlays of the local key by other validator lists
+    fn established_validator_peers(&self, local_adnl_id: &Arc<KeyId>) -> HashSet<Arc<KeyId>> {
+        let mut peers = HashSet::new();
+        for context in self.validator_context.sets_contexts.iter() {
+            let context = context.val();
+            if context.validator_adnl_key.id() == local_adnl_id {
+                peers.extend(context.resolved_peers.iter().cloned());
+            }
+        }
+        peers
+    }
+
     fn connectivity_broadcasts_sender(self: Arc<Self>) {
         tokio::spawn(async move {
             let mut big_bc_counter = 0_u8;
@@ -967,10 +1198,13 @@ impl NodeNetwork {
             }
             match self.config_handler.get_validator_key(&validator_adnl_key_id).await {
                 Some((adnl_key, _)) => {
-                    let id = self.network_context.adnl.add_key(adnl_key, election_id as usize)?;
+                    let id = self.network_context.validator_adnl.add_key(
+                        adnl_key, 
+                        election_id as usize
+                    )?;
                     NodeNetwork::periodic_store_ip_addr(
-                        self.network_context.dht.clone(),
-                        self.network_context.adnl.key_by_id(&id)?,
+                        self.network_context.validator_dht.clone(),
+                        self.network_context.validator_adnl.key_by_id(&id)?,
                         Some(self.validator_context.actual_local_adnl_keys.clone()),
                         self.cancellation_token.clone(),
                     );
@@ -1028,7 +1262,23 @@ impl OverlayOperations for NodeNetwork {
                 self.network_context.remp.clone(),
                 self.network_context.rldp.clone()
             ]
-        ).await
+        ).await?;
+        if self.has_validator_network() {
+            log::info!(
+                "start validator network: ip: {}", 
+                self.network_context.validator_adnl.ip_address()
+            );
+            AdnlNode::start(
+                &self.network_context.validator_adnl, 
+                vec![
+                    self.network_context.validator_dht.clone(), 
+                    self.network_context.validator_overlay.clone(),
+                    self.network_context.remp.clone(),
+                    self.network_context.validator_rldp.clone()
+                ]
+            ).await?;
+        }
+        Ok(())
     }
 
     async fn get_overlay(
@@ -1080,6 +1330,11 @@ impl PrivateOverlayOperations for NodeNetwork {
     ) -> Result<Option<Arc<dyn KeyOption>>> {
         log::trace!("start set_validator_list validator_list_id: {}", &validator_list_id);
 
+        if let Some(context) = self.validator_context.sets_contexts.get(&validator_list_id) {
+            log::trace!("validator list {} is already set", &validator_list_id);
+            return Ok(Some(context.val().validator_key.clone()))
+        }
+
         let validator_adnl_ids = self.config_handler.get_actual_validator_adnl_ids()?;
         let local_validator = validators.iter().find_map(|val| {
             if !validator_adnl_ids.contains(&val.adnl_id) {
@@ -1096,7 +1351,7 @@ impl PrivateOverlayOperations for NodeNetwork {
                 let (validator_key, election_id) = validator_key_raw.ok_or_else(
                     || error!("validator key not found!") 
                 )?;
-                let validator_adnl_key = match self.network_context.adnl.key_by_id(
+                let validator_adnl_key = match self.network_context.validator_adnl.key_by_id(
                     &validator.adnl_id
                 ) {
                     Ok(adnl_key) => adnl_key,
@@ -1111,7 +1366,7 @@ impl PrivateOverlayOperations for NodeNetwork {
                         ).await? {
                             fail!("can't load and store adnl key (id: {})", &validator.adnl_id);
                         }
-                        self.network_context.adnl.key_by_id(&validator.adnl_id)?
+                        self.network_context.validator_adnl.key_by_id(&validator.adnl_id)?
                     }
                 };
                 (validator_key, validator_adnl_key, election_id as usize)
@@ -1122,6 +1377,10 @@ impl PrivateOverlayOperations for NodeNetwork {
         let mut peers = Vec::new();
         let mut lost_validators = Vec::new();
         let mut peers_ids = Vec::new();
+        let mut resolved_peers = Vec::new();
+        let mut kept_peers = 0;
+        // Only new peers are searched and added, established ones keep their channels
+        let established_peers = self.established_validator_peers(local_validator_adnl_key.id());
 
         let connectivity_stat = Arc::new(Cache::new());
 
@@ -1130,7 +1389,6 @@ impl PrivateOverlayOperations for NodeNetwork {
                 continue;
             }
             peers_ids.push(val.adnl_id.clone());
-            lost_validators.push(val.clone());
             add_counted_object_to_map(
                 &connectivity_stat,
                 val.adnl_id.clone(),
@@ -1149,10 +1407,17 @@ impl PrivateOverlayOperations for NodeNetwork {
                     Ok(ret)
                 }
             )?;
+            if established_peers.contains(&val.adnl_id) {
+                resolved_peers.push(val.adnl_id.clone());
+                kept_peers += 1;
+                continue;
+            }
+            lost_validators.push(val.clone());
             match self.network_context.dht.fetch_address(&val.adnl_id).await {
                 Ok(Some((addr, key))) => {
                     log::info!("addr: {:?}, key: {:x?}", &addr, &key);
                     peers.push((addr, key));
+                    resolved_peers.push(val.adnl_id.clone());
                 },
                 Ok(None) => {
                     log::info!("addr: {:?} skipped.", &val.adnl_id);
@@ -1165,7 +1430,14 @@ impl PrivateOverlayOperations for NodeNetwork {
             }
         }
 
-        self.network_context.overlay.add_private_peers(local_validator_adnl_key.id(), peers)?;
+        log::info!(
+            "validator list {}: {} peers kept, {} peers added",
+            &validator_list_id, kept_peers, peers.len()
+        );
+        self.network_context.validator_overlay.add_private_peers(
+            local_validator_adnl_key.id(), 
+            peers
+        )?;
 
         let context = self.try_add_new_elem(
             &self.validator_context.sets_contexts,
@@ -1177,6 +1449,7 @@ impl PrivateOverlayOperations for NodeNetwork {
                     validator_adnl_key: local_validator_adnl_key.clone(),
                     election_id: election_id.clone(),
                     connectivity_stat: connectivity_stat.clone(),
+                    resolved_peers: resolved_peers.clone(),
                     counter: self.network_context.engine_allocated.validator_sets.clone().into()
                 };
                 #[cfg(feature = "telemetry")]
@@ -1262,9 +1535,16 @@ impl PrivateOverlayOperations for NodeNetwork {
                 self.validator_context.all_validator_peers.remove(peer);
             }
 
-            self.network_context.overlay.delete_private_peers(adnl_key.id(), &removed_peers)?;
+            // peers which are in other lists are not removed
+            self.network_context.validator_overlay.delete_private_peers(
+                adnl_key.id(), 
+                &removed_peers
+            )?;
             self.validator_context.sets_contexts.remove(&validator_list_id);
-            log::trace!("remove validator list (validator key id: {})", &validator_list_id);
+            log::trace!(
+                "remove validator list (validator key id: {}), {} of {} peers removed",
+                &validator_list_id, removed_peers.len(), context.val().validator_peers.len()
+            );
             status = true;
         }
 
@@ -1277,14 +1557,15 @@ impl PrivateOverlayOperations for NodeNetwork {
         overlay_short_id : &Arc<PrivateOverlayShortId>,
         nodes_public_keys : &Vec<CatchainNode>,
         listener : CatchainOverlayListenerPtr,
-        _log_replay_listener: CatchainOverlayLogReplayListenerPtr
+        _log_replay_listener: CatchainOverlayLogReplayListenerPtr,
+        qos: QosClass,
     ) -> Result<Arc<dyn CatchainOverlay + Send>> {
     
         let validator_set_context = self.validator_context.sets_contexts.get(&validator_list_id)
             .ok_or_else(
                 || error!("bad validator_list_id ({})!", validator_list_id.to_hex_string())
             )?;
-        let adnl_key = self.network_context.adnl.key_by_tag(
+        let adnl_key = self.network_context.validator_adnl.key_by_tag(
             validator_set_context.val().election_id
         )?;
 
@@ -1299,7 +1580,8 @@ impl PrivateOverlayOperations for NodeNetwork {
                     nodes_public_keys,
                     &adnl_key,
                     validator_set_context.val().validator_key.clone(),
-                    listener.clone()
+                    listener.clone(),
+                    qos,
                 )?;
                 Ok(Arc::new(ret))
             }
@@ -1309,7 +1591,7 @@ impl PrivateOverlayOperations for NodeNetwork {
             client.clone(),
             &self.runtime_handle,
             overlay_short_id,
-            &self.network_context.overlay,
+            &self.network_context.validator_overlay,
             &client.validator_keys(),
             &client.catchain_listener());
         Ok(client as Arc<dyn CatchainOverlay + Send>)
@@ -0,0 +1,184 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use overlay::OverlayShortId;
+use std::{
+    collections::HashMap, convert::TryInto, io::Cursor, path::{Path, PathBuf},
+    sync::{Arc, Mutex}, time::{Duration, SystemTime}
+};
+use ton_api::{
+    BoxedDeserialize, Deserializer, IntoBoxed, serialize_boxed,
+    ton::{
+        dht::{Node as DhtNodeBoxed, node::Node as DhtNode},
+        overlay::{Node as OverlayNodeBoxed, node::Node as OverlayNode}
+    }
+};
+use ton_types::{base64_decode, base64_encode, KeyOption, Result};
+
+#[cfg(test)]
+#[path = "tests/test_peer_cache.rs"]
+mod tests;
+
+pub const PEER_CACHE_FILE_NAME: &str = "known_peers.json";
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+struct CachedNode {
+    // boxed TL node in base64
+    node: String,
+    last_seen: u64,
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+struct CachedPeers {
+    dht_nodes: Vec<CachedNode>,
+    // overlay short id in base64 -> peer key id in base64 -> node
+    overlays: HashMap<String, HashMap<String, CachedNode>>,
+}
+
+/// DHT nodes and public overlay peers known to the node, periodically saved to a file.
+/// At startup they are used along with the global config's DHT nodes, so a restarted
+/// node rejoins the overlays without a full DHT search. Nodes not seen for `MAX_AGE`
+/// are dropped; addresses of overlay peers are not saved, they are resolved via DHT.
+pub struct PeerCache {
+    peers: Mutex<CachedPeers>,
+    path: Option<PathBuf>,
+}
+
+impl PeerCache {
+
+    pub const MAX_AGE: Duration = Duration::from_secs(24 * 3600);
+    pub const SAVE_PERIOD: Duration = Duration::from_secs(60);
+    pub const MAX_DHT_NODES: usize = 100;
+    pub const MAX_OVERLAY_NODES: usize = 200;
+
+    /// Creates cache, loading peers saved in the directory (if any)
+    pub fn with_dir(dir: Option<&str>) -> Self {
+        let ret = Self {
+            peers: Mutex::new(CachedPeers::default()),
+            path: dir.map(|dir| Path::new(dir).join(PEER_CACHE_FILE_NAME)),
+        };
+        if let Err(e) = ret.load() {
+            log::warn!("Can't load known peers: {}", e)
+        }
+        ret
+    }
+
+    pub fn dht_nodes(&self) -> Vec<DhtNode> {
+        let peers = self.peers.lock().unwrap();
+        peers.dht_nodes.iter().filter_map(|cached| {
+            Self::decode::<DhtNodeBoxed>(&cached.node).map(|node| node.only())
+        }).collect()
+    }
+
+    /// Replaces DHT nodes with the ones known to DHT now
+    pub fn set_dht_nodes(&self, nodes: Vec<DhtNode>) -> Result<()> {
+        let now = Self::now();
+        let mut dht_nodes = Vec::new();
+        for node in nodes.into_iter().take(Self::MAX_DHT_NODES) {
+            let node = base64_encode(serialize_boxed(&node.into_boxed())?);
+            dht_nodes.push(CachedNode { node, last_seen: now });
+        }
+        self.peers.lock().unwrap().dht_nodes = dht_nodes;
+        Ok(())
+    }
+
+    pub fn overlay_nodes(&self, overlay_id: &OverlayShortId) -> Vec<OverlayNode> {
+        let peers = self.peers.lock().unwrap();
+        match peers.overlays.get(&base64_encode(overlay_id.data())) {
+            Some(nodes) => nodes.values().filter_map(|cached| {
+                Self::decode::<OverlayNodeBoxed>(&cached.node).map(|node| node.only())
+            }).collect(),
+            None => Vec::new()
+        }
+    }
+
+    /// Remembers the overlay peer (found by DHT or got from other peers)
+    pub fn add_overlay_node(&self, overlay_id: &OverlayShortId, node: &OverlayNode) -> Result<()> {
+        let key: Arc<dyn KeyOption> = (&node.id).try_into()?;
+        let data = serialize_boxed(&node.clone().into_boxed())?;
+        self.add_overlay_node_raw(
+            overlay_id, base64_encode(key.id().data()), base64_encode(data), Self::now()
+        );
+        Ok(())
+    }
+
+    fn add_overlay_node_raw(&self, overlay_id: &OverlayShortId, key: String, node: String, now: u64) {
+        let mut peers = self.peers.lock().unwrap();
+        let nodes = peers.overlays.entry(base64_encode(overlay_id.data())).or_default();
+        nodes.insert(key, CachedNode { node, last_seen: now });
+        if nodes.len() > Self::MAX_OVERLAY_NODES {
+            let oldest = nodes.iter()
+                .min_by_key(|(_, cached)| cached.last_seen)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                nodes.remove(&oldest);
+            }
+        }
+    }
+
+    /// Removes nodes not seen for `MAX_AGE`
+    fn cleanup(&self, now: u64) {
+        let expire = now.saturating_sub(Self::MAX_AGE.as_secs());
+        let mut peers = self.peers.lock().unwrap();
+        peers.dht_nodes.retain(|cached| cached.last_seen >= expire);
+        for nodes in peers.overlays.values_mut() {
+            nodes.retain(|_, cached| cached.last_seen >= expire);
+        }
+        peers.overlays.retain(|_, nodes| !nodes.is_empty());
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(())
+        };
+        self.cleanup(Self::now());
+        let data = serde_json::to_string_pretty(&*self.peers.lock().unwrap())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) if path.exists() => path,
+            _ => return Ok(())
+        };
+        let data = std::fs::read_to_string(path)?;
+        *self.peers.lock().unwrap() = serde_json::from_str(&data)?;
+        self.cleanup(Self::now());
+        Ok(())
+    }
+
+    fn decode<T: BoxedDeserialize>(data: &str) -> Option<T> {
+        let result = base64_decode(data).and_then(|data| {
+            Deserializer::new(&mut Cursor::new(data)).read_boxed::<T>()
+        });
+        match result {
+            Ok(node) => Some(node),
+            Err(e) => {
+                log::warn!("Can't decode cached node: {}", e);
+                None
+            }
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs()
+    }
+
+}
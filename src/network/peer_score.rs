@@ -0,0 +1,239 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use adnl::common::add_unbound_object_to_map;
+use overlay::OverlayShortId;
+use std::{
+    sync::{Arc, Mutex}, time::{SystemTime, UNIX_EPOCH}
+};
+use ton_types::{error, KeyId, Result};
+
+#[cfg(test)]
+#[path = "tests/test_peer_score.rs"]
+mod tests;
+
+/// Observed behaviour of an overlay peer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerEvent {
+    ValidBroadcast,
+    QuerySuccess,
+    QueryFailure,
+    // proven by the data got from the peer: malformed data, bad signatures
+    ProtocolViolation,
+}
+
+impl PeerEvent {
+    fn points(&self) -> i32 {
+        match self {
+            PeerEvent::ValidBroadcast => 1,
+            PeerEvent::QuerySuccess => 1,
+            PeerEvent::QueryFailure => -1,
+            PeerEvent::ProtocolViolation => -25,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PeerScore {
+    pub score: i32,
+    pub valid_broadcasts: u64,
+    pub query_successes: u64,
+    pub query_failures: u64,
+    pub protocol_violations: u64,
+    // unix time in ms, 0 - not banned
+    pub banned_until: u64,
+    // unix time in ms the negative score is decayed up to
+    pub decayed_at: u64,
+}
+
+impl PeerScore {
+
+    fn decay_periods(&self, now: u64) -> u64 {
+        now.saturating_sub(self.decayed_at) / OverlayPeerScores::DECAY_MS
+    }
+
+    /// Score with the decay up to `now`
+    fn score_at(&self, now: u64) -> i32 {
+        if self.score >= 0 {
+            return self.score
+        }
+        (self.score as i64 + self.decay_periods(now) as i64).min(0) as i32
+    }
+
+    fn decay(&mut self, now: u64) {
+        let periods = self.decay_periods(now);
+        self.score = self.score_at(now);
+        self.decayed_at += periods * OverlayPeerScores::DECAY_MS;
+    }
+}
+
+/// Scores of peers of one overlay. Every event adds or subtracts points, the score is
+/// kept within `MIN_SCORE..=MAX_SCORE`, a negative score recovers by a point per `DECAY_MS`.
+/// A peer with a negative score is chosen for queries less often. A peer whose score falls
+/// to `BAN_SCORE` by a protocol violation is banned for `BAN_MS` (unless the caller can't
+/// afford to lose it): it is not chosen as a neighbour and its broadcasts are ignored.
+/// Failed queries never ban a peer. After the ban the peer starts from zero score.
+#[derive(Default)]
+pub struct OverlayPeerScores {
+    peers: lockfree::map::Map<Arc<KeyId>, Mutex<PeerScore>>,
+}
+
+impl OverlayPeerScores {
+    const MAX_SCORE: i32 = 100;
+    const MIN_SCORE: i32 = -100;
+    const BAN_SCORE: i32 = -50;
+    const BAN_MS: u64 = 600_000;
+    const DECAY_MS: u64 = 60_000;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the peer is banned by this event
+    pub fn record(&self, peer: &Arc<KeyId>, event: PeerEvent, can_ban: bool) -> Result<bool> {
+        self.record_at(peer, event, can_ban, now_ms())
+    }
+
+    pub fn is_banned(&self, peer: &Arc<KeyId>) -> bool {
+        self.is_banned_at(peer, now_ms())
+    }
+
+    /// Additional unreliability of the peer for neighbour choice
+    pub fn penalty(&self, peer: &Arc<KeyId>) -> i32 {
+        self.penalty_at(peer, now_ms())
+    }
+
+    pub fn get(&self, peer: &Arc<KeyId>) -> Option<PeerScore> {
+        self.peers.get(peer).map(|score| score.val().lock().unwrap().clone())
+    }
+
+    pub fn list(&self) -> Vec<(Arc<KeyId>, PeerScore)> {
+        self.peers.iter().map(|score| (score.key().clone(), score.val().lock().unwrap().clone())).collect()
+    }
+
+    /// Forgets scores (and bans) of the peer or of all peers, returns number of reset scores
+    pub fn reset(&self, peer: Option<&Arc<KeyId>>) -> usize {
+        match peer {
+            Some(peer) => self.peers.remove(peer).map_or(0, |_| 1),
+            None => {
+                let mut count = 0;
+                for score in self.peers.iter() {
+                    self.peers.remove(score.key());
+                    count += 1;
+                }
+                count
+            }
+        }
+    }
+
+    fn record_at(&self, peer: &Arc<KeyId>, event: PeerEvent, can_ban: bool, now: u64) -> Result<bool> {
+        add_unbound_object_to_map(&self.peers, peer.clone(), || Ok(Mutex::new(PeerScore::default())))?;
+        let score = match self.peers.get(peer) {
+            Some(score) => score,
+            // reset concurrently
+            None => return Ok(false)
+        };
+        let mut score = score.val().lock().unwrap();
+        match event {
+            PeerEvent::ValidBroadcast => score.valid_broadcasts += 1,
+            PeerEvent::QuerySuccess => score.query_successes += 1,
+            PeerEvent::QueryFailure => score.query_failures += 1,
+            PeerEvent::ProtocolViolation => score.protocol_violations += 1,
+        }
+        if score.banned_until != 0 {
+            if score.banned_until > now {
+                return Ok(false)
+            }
+            score.banned_until = 0;
+            score.score = 0;
+        }
+        score.decay(now);
+        score.score = (score.score + event.points()).clamp(Self::MIN_SCORE, Self::MAX_SCORE);
+        if (event == PeerEvent::ProtocolViolation) && (score.score <= Self::BAN_SCORE) {
+            if !can_ban {
+                log::warn!("Peer {} is not banned to keep the last neighbours, score {}", peer, score.score);
+                return Ok(false)
+            }
+            log::warn!("Peer {} is banned for {}s, score {}", peer, Self::BAN_MS / 1000, score.score);
+            metrics::increment_counter!("overlay_peers_banned");
+            score.banned_until = now + Self::BAN_MS;
+            return Ok(true)
+        }
+        Ok(false)
+    }
+
+    fn is_banned_at(&self, peer: &Arc<KeyId>, now: u64) -> bool {
+        self.peers.get(peer).map_or(false, |score| score.val().lock().unwrap().banned_until > now)
+    }
+
+    fn penalty_at(&self, peer: &Arc<KeyId>, now: u64) -> i32 {
+        match self.peers.get(peer) {
+            Some(score) => (-score.val().lock().unwrap().score_at(now)).max(0) / 10,
+            None => 0
+        }
+    }
+}
+
+/// Peer scores of all overlays of the node
+#[derive(Default)]
+pub struct PeerScores {
+    overlays: lockfree::map::Map<Arc<OverlayShortId>, Arc<OverlayPeerScores>>,
+}
+
+impl PeerScores {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn overlay(&self, overlay_id: &Arc<OverlayShortId>) -> Result<Arc<OverlayPeerScores>> {
+        add_unbound_object_to_map(
+            &self.overlays,
+            overlay_id.clone(),
+            || Ok(Arc::new(OverlayPeerScores::new()))
+        )?;
+        // scores of an overlay are never removed
+        self.overlays.get(overlay_id).map(|scores| scores.val().clone()).ok_or_else(
+            || error!("INTERNAL ERROR: no peer scores for overlay {}", overlay_id)
+        )
+    }
+
+    /// Resets scores of the peer (or of all peers) in all overlays
+    pub fn reset(&self, peer: Option<&Arc<KeyId>>) -> usize {
+        self.overlays.iter().map(|scores| scores.val().reset(peer)).sum()
+    }
+
+    pub fn to_json(&self) -> String {
+        let now = now_ms();
+        let overlays = self.overlays.iter().map(|scores| {
+            let peers = scores.val().list().into_iter().map(|(peer, score)| serde_json::json!({
+                "peer": peer.to_string(),
+                "score": score.score_at(now),
+                "valid_broadcasts": score.valid_broadcasts,
+                "query_successes": score.query_successes,
+                "query_failures": score.query_failures,
+                "protocol_violations": score.protocol_violations,
+                "banned_until_ms": score.banned_until,
+            })).collect::<Vec<_>>();
+            serde_json::json!({
+                "overlay": scores.key().to_string(),
+                "peers": peers,
+            })
+        }).collect::<Vec<_>>();
+        format!("{:#}", serde_json::Value::from(overlays))
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default()
+}
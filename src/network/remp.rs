@@ -16,7 +16,7 @@ use crate::validator::telemetry::RempCoreTelemetry;
 
 use adnl::{common::{AdnlPeers, Subscriber, TaggedByteSlice}, node::AdnlNode};
 use std::{
-    cmp::min, collections::HashMap, sync::{Arc, atomic::{AtomicU64, Ordering}},
+    cmp::min, collections::HashMap, sync::{Arc, atomic::{AtomicBool, AtomicU64, Ordering}},
     time::{Duration, Instant}, ops::Deref,
 };
 use ton_api::{
@@ -30,32 +30,195 @@ use ton_api::{
 use ton_api::tag_from_boxed_type;
 
 use ton_block::BlockIdExt;
-use ton_types::{error, fail, KeyId, Result, UInt256};
+use ton_types::{error, fail, KeyId, KeyOption, Result, UInt256};
 
 #[async_trait::async_trait]
 pub trait RempMessagesSubscriber: Sync + Send {
     async fn new_remp_message(&self, message: RempMessage, source: &Arc<KeyId>) -> Result<()>;
+    // Statuses of known messages are answered with receipts
+    async fn remp_status_query(&self, query: RempStatusQuery, source: &Arc<KeyId>) -> Result<()>;
+    // Message forwarded by validator of the previous set, see `RempForwardedMessage`
+    async fn forwarded_remp_message(&self, message: RempMessage, source: &Arc<KeyId>) -> Result<()>;
 }
 #[async_trait::async_trait]
 pub trait RempReceiptsSubscriber: Sync + Send {
-    async fn new_remp_receipt(&self, receipt: RempReceipt, source: &Arc<KeyId>) -> Result<()>;
+    // `signed` is the signed combined receipt the receipt is got from (if validator signs them)
+    async fn new_remp_receipt(
+        &self,
+        receipt: RempReceipt,
+        source: &Arc<KeyId>,
+        signed: Option<&SignedCombinedReceipt>
+    ) -> Result<()>;
+    // Message is ignored by collator because it is deferred, see `RempDeferredNotice`
+    async fn deferred_remp_message(&self, notice: RempDeferredNotice, source: &Arc<KeyId>) -> Result<()>;
+}
+
+const REMP_STATUS_QUERY_TAG: u32 = 0x51534d52; // "RMSQ"
+pub const MAX_STATUS_QUERY_MESSAGES: usize = 256;
+
+/// Request of statuses of messages, sent by fullnodes not participating in REMP catchains:
+/// tag, count of messages and their ids
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RempStatusQuery {
+    pub message_ids: Vec<UInt256>,
+}
+
+impl RempStatusQuery {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(8 + self.message_ids.len() * 32);
+        data.extend_from_slice(&REMP_STATUS_QUERY_TAG.to_le_bytes());
+        data.extend_from_slice(&(self.message_ids.len() as u32).to_le_bytes());
+        for id in &self.message_ids {
+            data.extend_from_slice(id.as_slice());
+        }
+        data
+    }
+
+    /// Returns None if data is not a status query
+    pub fn deserialize(data: &[u8]) -> Result<Option<Self>> {
+        if data.len() < 8 || data[0..4] != REMP_STATUS_QUERY_TAG.to_le_bytes() {
+            return Ok(None)
+        }
+        let count = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        if count > MAX_STATUS_QUERY_MESSAGES {
+            fail!("REMP status query for {} messages, at most {} are allowed", count, MAX_STATUS_QUERY_MESSAGES)
+        }
+        if data.len() != 8 + count * 32 {
+            fail!("REMP status query for {} messages has wrong length {}", count, data.len())
+        }
+        let message_ids = data[8..].chunks(32).map(UInt256::from_slice).collect();
+        Ok(Some(Self { message_ids }))
+    }
+}
+
+const REMP_FORWARDED_MESSAGE_TAG: u32 = 0x57464d52; // "RMFW"
+
+/// Pending message forwarded by validator to members of the next validator set near the set
+/// rotation: tag and boxed TL-serialized RempMessage
+#[derive(Clone, Debug, PartialEq)]
+pub struct RempForwardedMessage {
+    pub message: RempMessage,
+}
+
+impl RempForwardedMessage {
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let mut data = REMP_FORWARDED_MESSAGE_TAG.to_le_bytes().to_vec();
+        data.extend_from_slice(&serialize_boxed(&self.message)?);
+        Ok(data)
+    }
+
+    /// Returns None if data is not a forwarded message
+    pub fn deserialize(data: &[u8]) -> Result<Option<Self>> {
+        if data.len() < 4 || data[0..4] != REMP_FORWARDED_MESSAGE_TAG.to_le_bytes() {
+            return Ok(None)
+        }
+        let message = deserialize_boxed(&data[4..])?
+            .downcast::<RempMessage>()
+            .map_err(|_| error!("Forwarded REMP message doesn't contain message"))?;
+        Ok(Some(Self { message }))
+    }
+}
+
+const REMP_DEFERRED_NOTICE_TAG: u32 = 0x51444d52; // "RMDQ"
+
+/// Sub-status of the message ignored by collator because it is deferred to the next blocks:
+/// tag, message id and position of the message in its account's queue.
+/// Is sent by validator to the message's fullnode along with the ignored status.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RempDeferredNotice {
+    pub message_id: UInt256,
+    pub position: u32,
+}
+
+impl RempDeferredNotice {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(40);
+        data.extend_from_slice(&REMP_DEFERRED_NOTICE_TAG.to_le_bytes());
+        data.extend_from_slice(self.message_id.as_slice());
+        data.extend_from_slice(&self.position.to_le_bytes());
+        data
+    }
+
+    /// Returns None if data is not a deferred notice
+    pub fn deserialize(data: &[u8]) -> Result<Option<Self>> {
+        if data.len() < 4 || data[0..4] != REMP_DEFERRED_NOTICE_TAG.to_le_bytes() {
+            return Ok(None)
+        }
+        if data.len() != 40 {
+            fail!("REMP deferred notice has wrong length {}", data.len())
+        }
+        Ok(Some(Self {
+            message_id: UInt256::from_slice(&data[4..36]),
+            position: u32::from_le_bytes([data[36], data[37], data[38], data[39]]),
+        }))
+    }
+}
+
+const REMP_SIGNED_RECEIPT_TAG: u32 = 0x52534d52; // "RMSR"
+const SIGNATURE_LEN: usize = 64;
+
+/// Combined receipt signed by validator's key: tag, signature and TL-serialized combined
+/// receipt (message ids, statuses with block ids and timestamps; its source id is the key id).
+/// The whole package is a proof that the validator has reported the statuses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedCombinedReceipt {
+    pub signature: Vec<u8>,
+    pub receipt: Vec<u8>,
+}
+
+impl SignedCombinedReceipt {
+    pub fn sign(receipt: Vec<u8>, key: &dyn KeyOption) -> Result<Self> {
+        let signature = key.sign(&receipt)?;
+        Ok(Self { signature, receipt })
+    }
+
+    pub fn verify(&self, key: &dyn KeyOption) -> Result<()> {
+        key.verify(&self.receipt, &self.signature)
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(4 + SIGNATURE_LEN + self.receipt.len());
+        data.extend_from_slice(&REMP_SIGNED_RECEIPT_TAG.to_le_bytes());
+        data.extend_from_slice(&self.signature);
+        data.extend_from_slice(&self.receipt);
+        data
+    }
+
+    /// Returns None if data is not a signed receipt
+    pub fn deserialize(data: &[u8]) -> Result<Option<Self>> {
+        if data.len() < 4 || data[0..4] != REMP_SIGNED_RECEIPT_TAG.to_le_bytes() {
+            return Ok(None)
+        }
+        if data.len() <= 4 + SIGNATURE_LEN {
+            fail!("Signed REMP receipt has wrong length {}", data.len())
+        }
+        Ok(Some(Self {
+            signature: data[4..4 + SIGNATURE_LEN].to_vec(),
+            receipt: data[4 + SIGNATURE_LEN..].to_vec(),
+        }))
+    }
 }
 
 #[derive(Debug)]
 struct ReceiptStuff {
     pub to: Arc<KeyId>,
     pub receipt: RempReceipt,
-    // pub sign: Vec<u8>,
     pub self_adnl_id: Arc<KeyId>,
+    pub sign_key: Option<Arc<dyn KeyOption>>,
 }
 
+/// Messages are sent by the public overlay key through `adnl`. Receipts are sent by
+/// validator ADNL keys through `validator_adnl`, the stack which owns the keys, and the node
+/// is subscribed to both stacks (they are the same unless validator overlays have their own address)
 pub struct RempNode {
     adnl: Arc<AdnlNode>,
+    validator_adnl: Arc<AdnlNode>,
     local_key: Arc<KeyId>,
     messages_subscriber: tokio::sync::OnceCell<Arc<dyn RempMessagesSubscriber>>,
     receipts_subscriber: tokio::sync::OnceCell<Arc<dyn RempReceiptsSubscriber>>,
     receipts_sender: tokio::sync::OnceCell<tokio::sync::mpsc::UnboundedSender<ReceiptStuff>>,
     receipts_in_channel: Arc<AtomicU64>,
+    sign_receipts: AtomicBool,
     #[cfg(feature = "telemetry")]
     tag_message: u32,
     //#[cfg(feature = "telemetry")]
@@ -65,15 +228,17 @@ pub struct RempNode {
 }
 
 impl RempNode {
-    pub fn new(adnl: Arc<AdnlNode>, key_tag: usize) -> Result<Self> {
+    pub fn new(adnl: Arc<AdnlNode>, validator_adnl: Arc<AdnlNode>, key_tag: usize) -> Result<Self> {
         let local_key = adnl.key_by_tag(key_tag)?.id().clone();
         Ok(Self {
             adnl,
+            validator_adnl,
             local_key,
             messages_subscriber: Default::default(),
             receipts_subscriber: Default::default(),
             receipts_sender: Default::default(),
             receipts_in_channel: Arc::new(AtomicU64::new(0)),
+            sign_receipts: AtomicBool::new(false),
             #[cfg(feature = "telemetry")]
             tag_message: tag_from_boxed_type::<RempMessage>(),
             //#[cfg(feature = "telemetry")]
@@ -87,7 +252,7 @@ impl RempNode {
         let (receipts_sender, receiver) = tokio::sync::mpsc::unbounded_channel();
         self.receipts_sender.set(receipts_sender).map_err(|_| error!("Can't set receipts_sender"))?;
         start_receipts_worker(
-            Arc::new(SendCombinedReceiptByAdnl::new(self.adnl.clone())),
+            Arc::new(SendCombinedReceiptByAdnl::new(self.validator_adnl.clone())),
             receiver,
             self.receipts_in_channel.clone(),
             #[cfg(feature = "telemetry")]
@@ -126,6 +291,46 @@ impl RempNode {
         Ok(())
     }
 
+    pub fn send_status_query(&self, to: Arc<KeyId>, query: &RempStatusQuery) -> Result<()> {
+        let peers = AdnlPeers::with_keys(self.local_key.clone(), to);
+        let tagged_data = TaggedByteSlice {
+            object: &query.serialize(),
+            #[cfg(feature = "telemetry")]
+            tag: self.tag_message
+        };
+        if let Err(e) = self.adnl.send_custom(&tagged_data, &peers) {
+            fail!("Error while sending REMP status query via message: {}", e);
+        }
+        Ok(())
+    }
+
+    pub fn send_forwarded_message(&self, to: Arc<KeyId>, message: &RempMessage) -> Result<()> {
+        let id = message.id().clone();
+        let peers = AdnlPeers::with_keys(self.local_key.clone(), to);
+        let tagged_data = TaggedByteSlice {
+            object: &RempForwardedMessage { message: message.clone() }.serialize()?,
+            #[cfg(feature = "telemetry")]
+            tag: self.tag_message
+        };
+        if let Err(e) = self.adnl.send_custom(&tagged_data, &peers) {
+            fail!("Error while forwarding RempMessage {:x} via message: {}", id, e);
+        }
+        Ok(())
+    }
+
+    pub fn send_deferred_notice(&self, to: Arc<KeyId>, notice: &RempDeferredNotice) -> Result<()> {
+        let peers = AdnlPeers::with_keys(self.local_key.clone(), to);
+        let tagged_data = TaggedByteSlice {
+            object: &notice.serialize(),
+            #[cfg(feature = "telemetry")]
+            tag: self.tag_message
+        };
+        if let Err(e) = self.adnl.send_custom(&tagged_data, &peers) {
+            fail!("Error while sending REMP deferred notice {:x} via message: {}", notice.message_id, e);
+        }
+        Ok(())
+    }
+
     /*pub async fn send_receipt(&self, to: Arc<KeyId>, id: &UInt256, receipt: RempSignedReceipt) -> Result<()> {
         let peers = AdnlPeers::with_keys(self.local_key.clone(), to);
         let query = TaggedTlObject {
@@ -140,17 +345,24 @@ impl RempNode {
         }
     }*/
 
+    /// Combined receipts are signed by `sign_key` (validator's key, the receipt's source id)
+    /// if signing is enabled
     pub async fn combine_and_send_receipt(
         &self, 
         to: Arc<KeyId>, 
         receipt: RempReceipt,
-        //sign: Vec<u8>,
         self_adnl_id: Arc<KeyId>, 
+        sign_key: Arc<dyn KeyOption>,
     ) -> Result<()> {
         let message_id = receipt.message_id().clone();
+        let sign_key = if self.sign_receipts.load(Ordering::Relaxed) {
+            Some(sign_key)
+        } else {
+            None
+        };
         self.receipts_sender
             .get().ok_or_else(|| error!("receipts_sender is not set"))?
-            .send(ReceiptStuff{to, receipt, self_adnl_id})?;
+            .send(ReceiptStuff{to, receipt, self_adnl_id, sign_key})?;
         let in_channel = self.receipts_in_channel.fetch_add(1, Ordering::Relaxed) + 1;
         log::debug!(
             "combine_and_send_receipt: {:x}, channel's load: {}", message_id, in_channel
@@ -162,6 +374,10 @@ impl RempNode {
         Ok(())
     }
 
+    pub fn set_sign_receipts(&self, sign_receipts: bool) {
+        self.sign_receipts.store(sign_receipts, Ordering::Relaxed);
+    }
+
     pub fn set_messages_subscriber(&self, subscriber: Arc<dyn RempMessagesSubscriber>) -> Result<()> {
         self.messages_subscriber.set(subscriber).map_err(|_| error!("Can't set remp messages subscriber"))?;
         Ok(())
@@ -222,6 +438,31 @@ impl Subscriber for RempNode {
     }*/
 
     async fn try_consume_custom(&self, data: &[u8], peers: &AdnlPeers) -> Result<bool> {
+        if let Some(query) = RempStatusQuery::deserialize(data)? {
+            self.messages_subscriber()?.remp_status_query(query, peers.other()).await?;
+            return Ok(true);
+        }
+        if let Some(forwarded) = RempForwardedMessage::deserialize(data)? {
+            self.messages_subscriber()?.forwarded_remp_message(forwarded.message, peers.other()).await?;
+            return Ok(true);
+        }
+        if let Some(notice) = RempDeferredNotice::deserialize(data)? {
+            let subscriber = self.receipts_subscriber.get()
+                .ok_or_else(|| error!("receipts_subscriber is not set"))?;
+            subscriber.deferred_remp_message(notice, peers.other()).await?;
+            return Ok(true);
+        }
+        if let Some(signed) = SignedCombinedReceipt::deserialize(data)? {
+            let combined_receipt = deserialize_boxed(&signed.receipt)?
+                .downcast::<RempCombinedReceipt>()
+                .map_err(|_| error!("Signed REMP receipt doesn't contain combined receipt"))?;
+            let subscriber = self.receipts_subscriber.get()
+                .ok_or_else(|| error!("receipts_subscriber is not set"))?;
+            for r in expand_combined_receipt(&combined_receipt)? {
+                subscriber.new_remp_receipt(r, peers.other(), Some(&signed)).await?;
+            }
+            return Ok(true);
+        }
         match deserialize_boxed(data) {
             Ok(object) => {
                 let object = match object.downcast::<RempCombinedReceipt>() {
@@ -229,7 +470,7 @@ impl Subscriber for RempNode {
                         let subscriber = self.receipts_subscriber.get()
                             .ok_or_else(|| error!("receipts_subscriber is not set"))?;
                         for r in expand_combined_receipt(&combined_receipt)? {
-                            subscriber.new_remp_receipt(r, peers.other()).await?;
+                            subscriber.new_remp_receipt(r, peers.other(), None).await?;
                         }
                         return Ok(true);
                     }
@@ -307,10 +548,10 @@ impl SendCombinedReceipt for SendCombinedReceiptByAdnl {
 
 struct LastReceipt {
     pub receipt: Option<RempReceipt>,
-    //pub sign: Option<Vec<u8>>,
     pub sent_receipts: u32,
     pub updated_at: Instant,
     pub self_adnl_id: Arc<KeyId>,
+    pub sign_key: Option<Arc<dyn KeyOption>>,
 }
 
 fn start_receipts_worker(
@@ -364,7 +605,7 @@ async fn receipts_worker(
             Ok(Some(rs)) => {
                 receipts_in_channel.fetch_sub(1, Ordering::Relaxed);
                 log::debug!("ReceiptsSender::worker: New iteration - receipt for {:x}", rs.receipt.message_id());
-                if add_receipt_to_map(&mut receipts, rs.to, rs.receipt, rs.self_adnl_id)? {
+                if add_receipt_to_map(&mut receipts, rs.to, rs.receipt, rs.self_adnl_id, rs.sign_key)? {
                     #[cfg(feature = "telemetry")] {
                         pending_receipts += 1;
                         telemetry.pending_receipts(pending_receipts);
@@ -396,7 +637,12 @@ async fn receipts_worker(
                         let timeout = node_updated_at.elapsed().as_millis() as u64 > RECEIPTS_SEND_PERIOD_MS;
                         if build_result.is_full_packet || timeout {
                             let now = Instant::now();
-                            sender.send_combined_receipt(&node_id, &data, adnl_id.clone()).await?;
+                            let signed_data = build_result.sign_key.as_ref()
+                                .map(|key| SignedCombinedReceipt::sign(data.clone(), key.as_ref()))
+                                .transpose()?
+                                .map(|signed| signed.serialize());
+                            let data = signed_data.as_ref().unwrap_or(data);
+                            sender.send_combined_receipt(&node_id, data, adnl_id.clone()).await?;
                             log::debug!(
                                 "ReceiptsSender::worker: sent packet (because of {})  {} bytes  {} receipts  to {}  TIME {}ms", 
                                 if timeout { "timeout" } else { "full packet" },
@@ -456,6 +702,7 @@ struct BuildCombinedReceiptResult {
     is_full_packet: bool,
     cleaned_up: u64,
     self_adnl_id: Option<Arc<KeyId>>,
+    sign_key: Option<Arc<dyn KeyOption>>,
 }
 
 fn build_combined_receipt(
@@ -489,12 +736,14 @@ fn build_combined_receipt(
                         result.receipt = Some(receipt);
                         result.receipt_data = Some(receipt_data);
                         result.self_adnl_id = Some(last_receipt.self_adnl_id.clone());
+                        result.sign_key = last_receipt.sign_key.clone();
                     }
                     break;
                 }
                 result.receipt = Some(receipt);
                 result.receipt_data = Some(receipt_data);
                 result.self_adnl_id = Some(last_receipt.self_adnl_id.clone());
+                result.sign_key = last_receipt.sign_key.clone();
             }
         }
     }
@@ -722,15 +971,15 @@ fn add_receipt_to_map(
     receipts: &mut HashMap<Arc<KeyId>, (Instant, HashMap<UInt256, LastReceipt>)>,
     to: Arc<KeyId>,
     receipt: RempReceipt,
-    // sign: Vec<u8>,
     self_adnl_id: Arc<KeyId>,
+    sign_key: Option<Arc<dyn KeyOption>>,
 ) -> Result<bool> {
     let id = receipt.message_id().clone();
     let added_new = if let Some((_, node)) = receipts.get_mut(&to) {
         if let Some(msg) = node.get_mut(receipt.message_id()) {
             msg.receipt = Some(receipt);
-            // msg.sign = Some(sign.as_slice().try_into()?);
             msg.self_adnl_id = self_adnl_id;
+            msg.sign_key = sign_key;
             log::trace!("add_receipt_to_map for msg {:x} to {}: replaced prev receipt", id, to);
             false
         } else {
@@ -738,10 +987,10 @@ fn add_receipt_to_map(
                 receipt.message_id().clone(),
                 LastReceipt {
                     receipt: Some(receipt),
-                    // sign: Some(sign.as_slice().try_into()?),
                     sent_receipts: 0,
                     updated_at: Instant::now(),
                     self_adnl_id,
+                    sign_key,
                 }
             );
             log::trace!("add_receipt_to_map for msg {:x} to {}: added new receipt", id, to);
@@ -753,10 +1002,10 @@ fn add_receipt_to_map(
             receipt.message_id().clone(),
             LastReceipt {
                 receipt: Some(receipt),
-                // sign: Some(sign.as_slice().try_into()?),
                 sent_receipts: 0,
                 updated_at: Instant::now(),
                 self_adnl_id,
+                sign_key,
             }
         );
         log::trace!("add_receipt_to_map for msg {:x}: added new node {}", id, to);
@@ -0,0 +1,212 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::network::bandwidth::{BandwidthLimiter, TrafficClass};
+use adnl::common::TaggedByteSlice;
+use catchain::BlockPayloadPtr;
+use overlay::{OverlayNode, PrivateOverlayShortId};
+use std::{
+    collections::{HashMap, VecDeque}, sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}}
+};
+use ton_types::KeyId;
+
+#[cfg(test)]
+#[path = "tests/test_send_queue.rs"]
+mod tests;
+
+/// Class of validator traffic: messages of a lower class are sent to a peer
+/// only when there are no messages of the higher classes for it.
+/// The oldest messages are dropped on queue overflow (catchain gets the lost blocks
+/// back by its synchronization)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QosClass {
+    /// Validator session catchain
+    Consensus = 0,
+    /// REMP catchain
+    Remp = 1,
+}
+
+impl QosClass {
+    const COUNT: usize = 2;
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            QosClass::Consensus => "consensus",
+            QosClass::Remp => "remp",
+        }
+    }
+}
+
+/// Queues of messages to one peer, one per QoS class
+pub struct PeerSendQueue<T> {
+    queues: [VecDeque<T>; QosClass::COUNT],
+    limits: [usize; QosClass::COUNT],
+}
+
+impl<T> PeerSendQueue<T> {
+
+    pub fn new(consensus_limit: usize, remp_limit: usize) -> Self {
+        Self {
+            queues: [VecDeque::new(), VecDeque::new()],
+            limits: [consensus_limit, remp_limit],
+        }
+    }
+
+    /// Returns false if the oldest message was dropped because of the queue overflow
+    pub fn push(&mut self, class: QosClass, item: T) -> bool {
+        let queue = &mut self.queues[class as usize];
+        let dropped = queue.len() >= self.limits[class as usize];
+        if dropped {
+            queue.pop_front();
+        }
+        queue.push_back(item);
+        !dropped
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.queues.iter_mut().find_map(|queue| queue.pop_front())
+    }
+
+    pub fn len(&self, class: QosClass) -> usize {
+        self.queues[class as usize].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(|queue| queue.is_empty())
+    }
+}
+
+struct OverlayMessage {
+    overlay_id: Arc<PrivateOverlayShortId>,
+    data: BlockPayloadPtr,
+    is_stop: Arc<AtomicBool>,
+}
+
+/// Per-peer send queues of one-way messages of private (validator) overlays.
+/// Every peer has its own bounded queues, so a slow or flooded peer doesn't delay others,
+/// and a flood of REMP messages to a peer doesn't delay consensus messages to it.
+/// A peer has a sending task while its queues are not empty, the queues of an idle
+/// peer are removed.
+pub struct SendQueues {
+    runtime_handle: tokio::runtime::Handle,
+    overlay: Arc<OverlayNode>,
+    bandwidth: Arc<BandwidthLimiter>,
+    peers: Arc<Mutex<HashMap<Arc<KeyId>, PeerSendQueue<OverlayMessage>>>>,
+}
+
+impl SendQueues {
+    const TARGET: &'static str = "catchain_network";
+    const CONSENSUS_QUEUE_LIMIT: usize = 4096;
+    const REMP_QUEUE_LIMIT: usize = 1024;
+
+    pub fn new(
+        runtime_handle: tokio::runtime::Handle,
+        overlay: Arc<OverlayNode>,
+        bandwidth: Arc<BandwidthLimiter>
+    ) -> Self {
+        Self {
+            runtime_handle,
+            overlay,
+            bandwidth,
+            peers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Queues the message; it is not sent if the overlay is stopped before its turn
+    pub fn send(
+        &self,
+        peer: &Arc<KeyId>,
+        class: QosClass,
+        overlay_id: &Arc<PrivateOverlayShortId>,
+        data: &BlockPayloadPtr,
+        is_stop: &Arc<AtomicBool>,
+    ) {
+        let message = OverlayMessage {
+            overlay_id: overlay_id.clone(),
+            data: data.clone(),
+            is_stop: is_stop.clone(),
+        };
+        let mut peers = self.peers.lock().unwrap();
+        // no queue - no sending task for the peer, it is started for the first message
+        let start = !peers.contains_key(peer);
+        let queue = peers.entry(peer.clone()).or_insert_with(|| PeerSendQueue::new(
+            Self::CONSENSUS_QUEUE_LIMIT, Self::REMP_QUEUE_LIMIT
+        ));
+        if !queue.push(class, message) {
+            match class {
+                QosClass::Consensus => log::warn!(
+                    target: Self::TARGET, "{} message to {} dropped: queue is full", class.label(), peer
+                ),
+                QosClass::Remp => log::debug!(
+                    target: Self::TARGET, "{} message to {} dropped: queue is full", class.label(), peer
+                )
+            }
+            let labels = [("class", class.label())];
+            metrics::increment_counter!("validator_send_queue_drops", &labels);
+        }
+        if start {
+            let overlay = self.overlay.clone();
+            let bandwidth = self.bandwidth.clone();
+            let peer = peer.clone();
+            let peers = self.peers.clone();
+            self.runtime_handle.spawn(async move {
+                Self::send_queued(overlay, bandwidth, peer, peers).await
+            });
+        }
+    }
+
+    async fn send_queued(
+        overlay: Arc<OverlayNode>,
+        bandwidth: Arc<BandwidthLimiter>,
+        peer: Arc<KeyId>,
+        peers: Arc<Mutex<HashMap<Arc<KeyId>, PeerSendQueue<OverlayMessage>>>>
+    ) {
+        loop {
+            let message = {
+                let mut peers = peers.lock().unwrap();
+                match peers.get_mut(&peer).and_then(|queue| queue.pop()) {
+                    Some(message) => message,
+                    None => {
+                        // the queue is removed under the same lock it is checked,
+                        // so the next message starts a new task
+                        peers.remove(&peer);
+                        return
+                    }
+                }
+            };
+            if message.is_stop.load(Ordering::Relaxed) {
+                log::warn!("Overlay {} was stopped!", &message.overlay_id);
+                continue
+            }
+            let buf = &message.data.data().0;
+            bandwidth.upload(TrafficClass::Private, buf.len()).await;
+            let tag = if buf.len() < 4 {
+                0
+            } else {
+                ((buf[3] as u32) << 24) | ((buf[2] as u32) << 16) |
+                ((buf[1] as u32) <<  8) | ((buf[0] as u32) <<  0)
+            };
+            let msg = TaggedByteSlice {
+                object: buf,
+                #[cfg(feature = "telemetry")]
+                tag: 0x80000001 // Catchain one-way messages
+            };
+            let answer = overlay.message(&peer, &msg, &message.overlay_id).await;
+            log::trace!(
+                target: Self::TARGET,
+                "<send_message> (overlay: {}, data: {:08x}, key_id: {}): {:?}",
+                &message.overlay_id, tag, &peer, &answer
+            );
+        }
+    }
+}
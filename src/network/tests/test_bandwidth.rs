@@ -0,0 +1,46 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+#[test]
+fn test_token_bucket() {
+    let bucket = TokenBucket::new(1000);
+    let start = Instant::now();
+
+    // burst of one second
+    assert_eq!(bucket.reserve(600, start), Duration::ZERO);
+    assert_eq!(bucket.reserve(400, start), Duration::ZERO);
+    assert_eq!(bucket.reserve(500, start), Duration::from_millis(500));
+
+    // the debt is paid off in half a second
+    assert_eq!(bucket.reserve(0, start + Duration::from_millis(500)), Duration::ZERO);
+    // tokens are not accumulated over the burst
+    assert_eq!(bucket.reserve(1000, start + Duration::from_secs(10)), Duration::ZERO);
+    assert_eq!(bucket.reserve(100, start + Duration::from_secs(10)), Duration::from_millis(100));
+}
+
+#[test]
+fn test_bandwidth_limiter_priority() {
+    let config = BandwidthLimitsConfig {
+        upload_bytes_per_sec: 1000,
+        ..Default::default()
+    };
+    let limiter = BandwidthLimiter::new(&config);
+    // private traffic uses the whole global limit without waiting...
+    assert_eq!(BandwidthLimiter::reserve(&[&limiter.private_upload], 2000), Duration::ZERO);
+    assert_eq!(BandwidthLimiter::reserve(&[&limiter.upload], 2000), Duration::from_secs(1));
+    // ...and public traffic waits for it
+    let delay = BandwidthLimiter::reserve(&[&limiter.upload, &limiter.public_upload], 100);
+    assert!(delay > Duration::from_secs(1));
+}
@@ -0,0 +1,44 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+#[test]
+fn test_broadcast_dedup_lru() {
+    let dedup = BroadcastDedup::new(3 * BroadcastDedup::ENTRY_SIZE);
+    assert!(!dedup.check(b"a"));
+    assert!(!dedup.check(b"b"));
+    assert!(!dedup.check(b"c"));
+    assert!(dedup.check(b"a"));
+    assert_eq!(dedup.len(), 3);
+
+    // "b" is the least recently used one
+    assert!(!dedup.check(b"d"));
+    assert_eq!(dedup.len(), 3);
+    assert!(dedup.check(b"a"));
+    assert!(dedup.check(b"c"));
+    assert!(!dedup.check(b"b"));
+
+    for _ in 0..100 {
+        assert!(dedup.check(b"b"));
+    }
+    assert!(dedup.cache.lock().unwrap().order.len() <= 6);
+}
+
+#[test]
+fn test_broadcast_dedup_disabled() {
+    let dedup = BroadcastDedup::new(0);
+    assert!(!dedup.check(b"a"));
+    assert!(!dedup.check(b"a"));
+    assert_eq!(dedup.len(), 0);
+}
@@ -0,0 +1,57 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+const DB_PATH: &str = "./target/capabilities_log";
+
+#[test]
+fn test_capabilities_log_observe() {
+    let log = CapabilitiesLog::with_dir(None);
+    let peer = KeyId::from_data([1; 32]);
+
+    log.observe_at(&peer, 2, 1, 100);
+    log.observe_at(&peer, 2, 1, 200);
+    assert_eq!(
+        log.get(&peer).unwrap(),
+        CapabilitiesObservation { version: 2, capabilities: 1, first_seen: 100, last_seen: 200 }
+    );
+
+    // protocol upgrade restarts observation
+    log.observe_at(&peer, 2, 3, 300);
+    assert_eq!(log.get(&peer).unwrap().first_seen, 300);
+
+    let json = log.to_json();
+    assert!(json.contains("\"capabilities\": \"0x3\""));
+
+    log.cleanup(300 + CapabilitiesLog::RETENTION.as_secs() + 1);
+    assert_eq!(log.count(), 0);
+}
+
+#[test]
+fn test_capabilities_log_persistence() {
+    std::fs::remove_dir_all(DB_PATH).ok();
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+    let peer1 = KeyId::from_data([1; 32]);
+    let peer2 = KeyId::from_data([2; 32]);
+
+    let log = CapabilitiesLog::with_dir(Some(DB_PATH));
+    log.observe_at(&peer1, 2, 1, now);
+    log.observe_at(&peer2, 3, 7, now);
+    log.save().unwrap();
+
+    let log = CapabilitiesLog::with_dir(Some(DB_PATH));
+    assert_eq!(log.count(), 2);
+    assert_eq!(log.get(&peer2).unwrap().capabilities, 7);
+    std::fs::remove_dir_all(DB_PATH).ok();
+}
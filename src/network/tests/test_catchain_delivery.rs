@@ -0,0 +1,59 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use std::time::Duration;
+
+fn peer(n: u8) -> Arc<KeyId> {
+    KeyId::from_data([n; 32])
+}
+
+#[test]
+fn test_catchain_delivery_priorities() {
+    let delivery = CatchainDelivery::new((1..=3).map(peer), 50);
+    let receivers = vec![peer(1), peer(2), peer(3)];
+    assert_eq!(delivery.prioritize(&receivers), vec![(peer(1), 1), (peer(2), 1), (peer(3), 1)]);
+
+    delivery.on_sent(&peer(3));
+    std::thread::sleep(Duration::from_millis(10));
+    delivery.on_sent(&peer(2));
+    delivery.on_sent(&peer(3));
+    std::thread::sleep(Duration::from_millis(60));
+    delivery.on_sent(&peer(1));
+    delivery.on_received(&peer(1));
+    let prioritized = delivery.prioritize(&receivers);
+    assert_eq!(prioritized, vec![(peer(3), 2), (peer(2), 2), (peer(1), 1)]);
+    assert!(delivery.lag_ms(&peer(3)) > delivery.lag_ms(&peer(2)));
+
+    delivery.on_received(&peer(3));
+    assert_eq!(delivery.lag_ms(&peer(3)), 0);
+    assert_eq!(delivery.copies(&peer(3)), 1);
+    // unknown peers are not tracked
+    delivery.on_sent(&peer(4));
+    assert_eq!(delivery.copies(&peer(4)), 1);
+}
+
+#[test]
+fn test_catchain_delivery_relay() {
+    let delivery = CatchainDelivery::new((1..=3).map(peer), 50);
+    delivery.on_sent(&peer(1));
+    delivery.on_sent(&peer(2));
+    // the only peer which has answered is chosen as relay
+    assert_eq!(delivery.pick_relay(&peer(1)), Some(peer(3)));
+    // target is not its own relay
+    assert_eq!(delivery.pick_relay(&peer(3)), None);
+    delivery.on_received(&peer(2));
+    for _ in 0..10 {
+        assert_ne!(delivery.pick_relay(&peer(2)), Some(peer(1)));
+    }
+}
@@ -0,0 +1,54 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use ton_types::Ed25519KeyOption;
+
+#[test]
+fn test_relayed_message() -> Result<()> {
+    let key = Ed25519KeyOption::generate()?;
+    let other_key = Ed25519KeyOption::generate()?;
+    let message = RelayedMessage::sign(
+        &[1; 32],
+        KeyId::from_data([2; 32]),
+        KeyId::from_data([3; 32]),
+        KeyId::from_data([4; 32]),
+        vec![5, 6, 7],
+        key.as_ref()
+    )?;
+    message.verify(key.as_ref())?;
+    assert!(message.verify(other_key.as_ref()).is_err());
+
+    let data = message.serialize();
+    let restored = RelayedMessage::deserialize(&data)?.unwrap();
+    assert_eq!(restored, message);
+    restored.verify(key.as_ref())?;
+    // Message without payload
+    assert!(RelayedMessage::deserialize(&data[..HEADER_LEN]).is_err());
+
+    // Relay passes the message on without its id, the signature is still valid
+    let mut passed = restored.clone();
+    passed.relay = None;
+    let passed = RelayedMessage::deserialize(&passed.serialize())?.unwrap();
+    assert_eq!(passed.relay, None);
+    passed.verify(key.as_ref())?;
+
+    // Redirected message is not verified
+    let mut redirected = restored.clone();
+    redirected.target = KeyId::from_data([7; 32]);
+    assert!(redirected.verify(key.as_ref()).is_err());
+
+    // Other data is not a relayed message
+    assert_eq!(RelayedMessage::deserialize(&[1, 2, 3, 4, 5, 6, 7, 8])?, None);
+    Ok(())
+}
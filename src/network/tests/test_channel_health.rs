@@ -0,0 +1,44 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+fn peer(n: u8) -> Arc<KeyId> {
+    KeyId::from_data([n; 32])
+}
+
+#[test]
+fn test_channel_health() {
+    let health = ChannelHealth::new((1..=2).map(peer), 3);
+
+    health.on_alive(&peer(1), 100);
+    health.on_alive(&peer(1), 50);
+    let state = &health.peers[&peer(1)];
+    assert_eq!(state.rtt_ms.load(Ordering::Relaxed), 75);
+    assert!(state.last_alive_ms.load(Ordering::Relaxed) > 0);
+
+    // the channel is re-established after each 3 failures in a row
+    assert!(!health.on_failure(&peer(2)));
+    assert!(!health.on_failure(&peer(2)));
+    assert!(health.on_failure(&peer(2)));
+    assert!(!health.on_failure(&peer(2)));
+    health.on_alive(&peer(2), 10);
+    assert!(!health.on_failure(&peer(2)));
+    assert!(!health.on_failure(&peer(2)));
+    assert!(health.on_failure(&peer(2)));
+    assert_eq!(health.peers[&peer(2)].reestablished.load(Ordering::Relaxed), 2);
+
+    // unknown peers are not tracked
+    assert!(!health.on_failure(&peer(3)));
+    assert_eq!(health.to_json().as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,37 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+#[test]
+fn test_answers_compression() {
+    let compression = AnswersCompression::new(&AnswersCompressionConfig::default());
+    let data = vec![7u8; 100_000];
+
+    let compressed = compression.compress(&data).unwrap().unwrap();
+    assert!(compressed.len() < data.len());
+    assert_eq!(AnswersCompression::decompress(compressed), data);
+
+    // small answers are not compressed
+    assert!(compression.compress(&data[..100]).unwrap().is_none());
+    // uncompressed answers are returned as is
+    assert_eq!(AnswersCompression::decompress(data.clone()), data);
+    let mut fake = ZSTD_ANSWER_TAG.to_vec();
+    fake.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(AnswersCompression::decompress(fake.clone()), fake);
+
+    let disabled = AnswersCompression::new(
+        &AnswersCompressionConfig { enabled: false, ..Default::default() }
+    );
+    assert!(disabled.compress(&data).unwrap().is_none());
+}
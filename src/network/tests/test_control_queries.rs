@@ -0,0 +1,122 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use ton_api::{serialize_boxed, ton::rpc::engine::validator::GetStats};
+
+fn check_roundtrip(query: NodeControlQuery) {
+    let data = query.serialize().unwrap();
+    assert_eq!(NodeControlQuery::deserialize(&data).unwrap(), Some(query));
+    // truncated and extended queries are errors
+    assert!(NodeControlQuery::deserialize(&data[..data.len() - 1]).is_err());
+    let mut extended = data.clone();
+    extended.push(0);
+    assert!(NodeControlQuery::deserialize(&extended).is_err());
+}
+
+#[test]
+fn test_state_diff_queries() {
+    check_roundtrip(NodeControlQuery::ExportStateDiff(ExportStateDiff {
+        base_root_hash: UInt256::from([1; 32]),
+        target_root_hash: UInt256::from([2; 32]),
+        offset: 1 << 33,
+        max_size: MAX_STATE_DIFF_PART,
+    }));
+    check_roundtrip(NodeControlQuery::ImportStateDiff(ImportStateDiff {
+        base_root_hash: UInt256::from([1; 32]),
+        target_root_hash: UInt256::from([2; 32]),
+        offset: 100,
+        data: vec![1, 2, 3],
+        last: true,
+    }));
+
+    let too_long = NodeControlQuery::ImportStateDiff(ImportStateDiff {
+        data: vec![0; MAX_STATE_DIFF_PART as usize + 1],
+        ..Default::default()
+    });
+    assert!(NodeControlQuery::deserialize(&too_long.serialize().unwrap()).is_err());
+}
+
+#[test]
+fn test_restart_remp_session_query() {
+    check_roundtrip(NodeControlQuery::RestartRempSession(RestartRempSession {
+        queue_id: UInt256::from([5; 32]),
+    }));
+}
+
+#[test]
+fn test_message_import_query() {
+    check_roundtrip(NodeControlQuery::MessageImport(MessageImport {
+        file_name: "messages.txt".to_string(),
+        rate: 100,
+    }));
+
+    let too_long = NodeControlQuery::MessageImport(MessageImport {
+        file_name: "a".repeat(MAX_FILE_NAME_LEN + 1),
+        rate: 0,
+    });
+    assert!(NodeControlQuery::deserialize(&too_long.serialize().unwrap()).is_err());
+    // file name is not UTF-8
+    let mut data = NodeControlQuery::MessageImport(MessageImport {
+        file_name: "ab".to_string(),
+        rate: 0,
+    }).serialize().unwrap();
+    // constructor id and length prefix of the file name precede it
+    data[5] = 0xff;
+    assert!(NodeControlQuery::deserialize(&data).is_err());
+}
+
+#[test]
+fn test_collation_dry_run_query() {
+    check_roundtrip(NodeControlQuery::CollationDryRun(CollationDryRun {
+        workchain_id: -1,
+        shard_prefix_tagged: 0x8000_0000_0000_0000,
+    }));
+}
+
+#[test]
+fn test_validate_replay_query() {
+    check_roundtrip(NodeControlQuery::ValidateReplay(ValidateReplay {
+        root_hash: UInt256::from([9; 32]),
+    }));
+}
+
+#[test]
+fn test_peer_scores_reset_query() {
+    check_roundtrip(NodeControlQuery::PeerScoresReset(PeerScoresReset {
+        peer: Some(UInt256::from([3; 32])),
+    }));
+    check_roundtrip(NodeControlQuery::PeerScoresReset(PeerScoresReset { peer: None }));
+}
+
+#[test]
+fn test_tl_encoding() {
+    let data = NodeControlQuery::ImportStateDiff(ImportStateDiff {
+        data: vec![7; 300],
+        last: true,
+        ..Default::default()
+    }).serialize().unwrap();
+    assert_eq!(&data[..4], &IMPORT_STATE_DIFF_ID.to_le_bytes());
+    // long bytes field: 254 and 3 bytes of length, padded to 4 bytes
+    let field = 4 + 32 + 32 + 8;
+    assert_eq!(&data[field..field + 4], &[254, 44, 1, 0]);
+    assert_eq!(&data[field + 4 + 300..field + 4 + 300 + 4], &BOOL_TRUE_ID.to_le_bytes());
+    assert_eq!(data.len(), field + 4 + 300 + 4);
+}
+
+#[test]
+fn test_tl_queries_are_not_node_control_queries() {
+    let data = serialize_boxed(&GetStats).unwrap();
+    assert_eq!(NodeControlQuery::deserialize(&data).unwrap(), None);
+    assert_eq!(NodeControlQuery::deserialize(&[1, 2]).unwrap(), None);
+}
@@ -0,0 +1,60 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+const DB_PATH: &str = "./target/peer_cache";
+
+fn overlay_len(cache: &PeerCache, overlay_id: &OverlayShortId) -> usize {
+    let peers = cache.peers.lock().unwrap();
+    peers.overlays.get(&base64_encode(overlay_id.data())).map_or(0, |nodes| nodes.len())
+}
+
+#[test]
+fn test_peer_cache_persistence() {
+    std::fs::remove_dir_all(DB_PATH).ok();
+    let now = PeerCache::now();
+    let overlay1 = OverlayShortId::from_data([1; 32]);
+    let overlay2 = OverlayShortId::from_data([2; 32]);
+
+    let cache = PeerCache::with_dir(Some(DB_PATH));
+    cache.add_overlay_node_raw(&overlay1, "peer1".to_string(), "node1".to_string(), now);
+    cache.add_overlay_node_raw(&overlay1, "peer2".to_string(), "node2".to_string(), now);
+    // stale peer
+    cache.add_overlay_node_raw(
+        &overlay2, "peer3".to_string(), "node3".to_string(), now - PeerCache::MAX_AGE.as_secs() - 1
+    );
+    cache.save().unwrap();
+
+    let cache = PeerCache::with_dir(Some(DB_PATH));
+    assert_eq!(overlay_len(&cache, &overlay1), 2);
+    assert_eq!(overlay_len(&cache, &overlay2), 0);
+    assert!(cache.peers.lock().unwrap().overlays.get(&base64_encode(overlay2.data())).is_none());
+    // undecodable nodes are skipped
+    assert!(cache.overlay_nodes(&overlay1).is_empty());
+    std::fs::remove_dir_all(DB_PATH).ok();
+}
+
+#[test]
+fn test_peer_cache_limit() {
+    let cache = PeerCache::with_dir(None);
+    let overlay = OverlayShortId::from_data([1; 32]);
+    for i in 0..PeerCache::MAX_OVERLAY_NODES as u64 + 10 {
+        cache.add_overlay_node_raw(&overlay, format!("peer{}", i), format!("node{}", i), 1000 + i);
+    }
+    assert_eq!(overlay_len(&cache, &overlay), PeerCache::MAX_OVERLAY_NODES);
+    let peers = cache.peers.lock().unwrap();
+    let nodes = peers.overlays.get(&base64_encode(overlay.data())).unwrap();
+    assert!(nodes.get("peer9").is_none());
+    assert!(nodes.get("peer10").is_some());
+}
@@ -0,0 +1,105 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+#[test]
+fn test_peer_score_ban() {
+    let scores = OverlayPeerScores::new();
+    let peer = KeyId::from_data([1; 32]);
+    let other = KeyId::from_data([2; 32]);
+    let now = 1_000_000;
+
+    for _ in 0..10 {
+        assert!(!scores.record_at(&peer, PeerEvent::QuerySuccess, true, now).unwrap());
+    }
+    assert_eq!(scores.get(&peer).unwrap().score, 10);
+    assert_eq!(scores.penalty_at(&peer, now), 0);
+
+    assert!(!scores.record_at(&peer, PeerEvent::ProtocolViolation, true, now).unwrap());
+    assert!(!scores.record_at(&peer, PeerEvent::ProtocolViolation, true, now).unwrap());
+    assert_eq!(scores.penalty_at(&peer, now), 4);
+    // failed queries never ban
+    for _ in 0..10 {
+        assert!(!scores.record_at(&peer, PeerEvent::QueryFailure, true, now).unwrap());
+    }
+    assert_eq!(scores.get(&peer).unwrap().score, -50);
+    assert!(!scores.is_banned_at(&peer, now));
+    // the peer the caller can't afford to lose is not banned
+    assert!(!scores.record_at(&peer, PeerEvent::ProtocolViolation, false, now).unwrap());
+    assert!(!scores.is_banned_at(&peer, now));
+    assert!(scores.record_at(&peer, PeerEvent::ProtocolViolation, true, now).unwrap());
+    assert!(scores.is_banned_at(&peer, now));
+    assert!(!scores.is_banned_at(&other, now));
+
+    // events during the ban are counted but don't change the score
+    assert!(!scores.record_at(&peer, PeerEvent::ValidBroadcast, true, now + 1).unwrap());
+    let score = scores.get(&peer).unwrap();
+    assert_eq!(score.score, OverlayPeerScores::MIN_SCORE);
+    assert_eq!(score.valid_broadcasts, 1);
+    assert_eq!(score.protocol_violations, 4);
+    assert_eq!(score.query_successes, 10);
+    assert_eq!(score.query_failures, 10);
+
+    // the peer starts from zero after the ban
+    let after_ban = now + OverlayPeerScores::BAN_MS;
+    assert!(!scores.is_banned_at(&peer, after_ban));
+    assert!(!scores.record_at(&peer, PeerEvent::QueryFailure, true, after_ban).unwrap());
+    assert_eq!(scores.get(&peer).unwrap().score, -1);
+}
+
+#[test]
+fn test_peer_score_decay() {
+    let scores = OverlayPeerScores::new();
+    let peer = KeyId::from_data([1; 32]);
+    let now = OverlayPeerScores::DECAY_MS * 100;
+
+    for _ in 0..20 {
+        assert!(!scores.record_at(&peer, PeerEvent::QueryFailure, true, now).unwrap());
+    }
+    assert_eq!(scores.penalty_at(&peer, now), 2);
+
+    // a point is recovered per period, the rest of the period is kept for the next decay
+    let later = now + OverlayPeerScores::DECAY_MS * 10 + OverlayPeerScores::DECAY_MS / 2;
+    assert_eq!(scores.penalty_at(&peer, later), 1);
+    assert!(!scores.record_at(&peer, PeerEvent::QueryFailure, true, later).unwrap());
+    let score = scores.get(&peer).unwrap();
+    assert_eq!(score.score, -11);
+    assert_eq!(score.score_at(later + OverlayPeerScores::DECAY_MS / 2), -10);
+    // negative score recovers up to zero only
+    assert_eq!(score.score_at(later + OverlayPeerScores::DECAY_MS * 100), 0);
+}
+
+#[test]
+fn test_peer_score_reset() {
+    let scores = PeerScores::new();
+    let overlay1 = OverlayShortId::from_data([1; 32]);
+    let overlay2 = OverlayShortId::from_data([2; 32]);
+    let peer = KeyId::from_data([1; 32]);
+    let other = KeyId::from_data([2; 32]);
+
+    let scores1 = scores.overlay(&overlay1).unwrap();
+    let scores2 = scores.overlay(&overlay2).unwrap();
+    assert!(Arc::ptr_eq(&scores1, &scores.overlay(&overlay1).unwrap()));
+
+    scores1.record(&peer, PeerEvent::ProtocolViolation, true).unwrap();
+    scores1.record(&other, PeerEvent::ProtocolViolation, true).unwrap();
+    scores2.record(&peer, PeerEvent::QueryFailure, true).unwrap();
+    assert_eq!(scores1.get(&peer).unwrap().score, -25);
+
+    assert_eq!(scores.reset(Some(&peer)), 2);
+    assert!(scores1.get(&peer).is_none());
+    assert!(scores1.get(&other).is_some());
+    assert_eq!(scores.reset(None), 1);
+    assert!(scores1.list().is_empty());
+}
@@ -1,5 +1,8 @@
 use crate::{
-    network::remp::{RempNode, RempMessagesSubscriber, RempReceiptsSubscriber, ReceiptStuff},
+    network::remp::{
+        RempDeferredNotice, RempForwardedMessage, RempNode, RempMessagesSubscriber, RempReceiptsSubscriber,
+        RempStatusQuery, ReceiptStuff, SignedCombinedReceipt, MAX_STATUS_QUERY_MESSAGES
+    },
     test_helper::{get_adnl_config, init_test_log}, validator::telemetry::RempCoreTelemetry
 };
 
@@ -12,14 +15,14 @@ use ton_api::{
     IntoBoxed,
     ton::ton_node::{RempReceipt, RempCombinedReceipt, RempMessageStatus},
 };
-use ton_types::{fail, KeyId, Result, UInt256};
+use ton_types::{fail, Ed25519KeyOption, KeyId, Result, UInt256};
 
 const KEY_TAG: usize = 0;
 
 async fn init_remp_node(ip: &str) -> Result<(Arc<AdnlNode>, Arc<RempNode>, Arc<RempCoreTelemetry>)> {
     let config = get_adnl_config("target/remp", ip, vec![KEY_TAG], true).await.unwrap();
     let node = AdnlNode::with_config(config).await.unwrap();
-    let remp = Arc::new(RempNode::new(node.clone(), KEY_TAG)?);
+    let remp = Arc::new(RempNode::new(node.clone(), node.clone(), KEY_TAG)?);
     AdnlNode::start(
         &node,
         vec![remp.clone()]
@@ -43,13 +46,27 @@ impl RempMessagesSubscriber for TestRempSubscriber {
         self.got_messages.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
+    async fn remp_status_query(&self, _query: RempStatusQuery, _source: &Arc<KeyId>) -> Result<()> {
+        Ok(())
+    }
+    async fn forwarded_remp_message(&self, _message: ton_api::ton::ton_node::RempMessage, _source: &Arc<KeyId>) -> Result<()> {
+        Ok(())
+    }
 }
 #[async_trait::async_trait]
 impl RempReceiptsSubscriber for TestRempSubscriber {
-    async fn new_remp_receipt(&self, _receipt: RempReceipt, _source: &Arc<KeyId>) -> Result<()> {
+    async fn new_remp_receipt(
+        &self,
+        _receipt: RempReceipt,
+        _source: &Arc<KeyId>,
+        _signed: Option<&SignedCombinedReceipt>
+    ) -> Result<()> {
         self.got_receipts.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
+    async fn deferred_remp_message(&self, _notice: RempDeferredNotice, _source: &Arc<KeyId>) -> Result<()> {
+        Ok(())
+    }
 }
 
 #[tokio::test]
@@ -105,8 +122,10 @@ async fn test_remp_client_compact_protocol() -> Result<()> {
             }
         );
 
-        remp1.combine_and_send_receipt(peer2.clone(), r.clone(), node1.key_by_tag(KEY_TAG).unwrap().id().clone()).await?;
-        remp2.combine_and_send_receipt(peer1.clone(), r.clone(), node2.key_by_tag(KEY_TAG).unwrap().id().clone()).await?;
+        let key1 = node1.key_by_tag(KEY_TAG).unwrap();
+        let key2 = node2.key_by_tag(KEY_TAG).unwrap();
+        remp1.combine_and_send_receipt(peer2.clone(), r.clone(), key1.id().clone(), key1.clone()).await?;
+        remp2.combine_and_send_receipt(peer1.clone(), r.clone(), key2.id().clone(), key2.clone()).await?;
     }
 
     tokio::time::sleep(Duration::from_millis(5000)).await;
@@ -188,7 +207,7 @@ async fn test_remp_receipts_send_worker() -> Result<()> {
         }.into_boxed();
 
         let self_adnl_id = KeyId::from_data([0; 32]);
-        receipts_sender.send(ReceiptStuff{to, receipt, self_adnl_id})?;
+        receipts_sender.send(ReceiptStuff{to, receipt, self_adnl_id, sign_key: None})?;
 
         if n % 100 == 0 {
             tokio::time::sleep(Duration::from_millis(10)).await;
@@ -206,4 +225,69 @@ async fn test_remp_receipts_send_worker() -> Result<()> {
     log::info!("{}", telemetry.report());
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_remp_status_query() {
+    let query = RempStatusQuery { message_ids: vec![UInt256::from([1; 32]), UInt256::from([2; 32])] };
+    let data = query.serialize();
+    assert_eq!(RempStatusQuery::deserialize(&data).unwrap(), Some(query));
+    assert!(RempStatusQuery::deserialize(&data[..data.len() - 1]).is_err());
+    // Other objects are not status queries
+    assert_eq!(RempStatusQuery::deserialize(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap(), None);
+
+    let too_long = RempStatusQuery { message_ids: vec![UInt256::default(); MAX_STATUS_QUERY_MESSAGES + 1] };
+    assert!(RempStatusQuery::deserialize(&too_long.serialize()).is_err());
+}
+
+#[test]
+fn test_remp_deferred_notice() {
+    let notice = RempDeferredNotice { message_id: UInt256::from([3; 32]), position: 5 };
+    let data = notice.serialize();
+    assert_eq!(RempDeferredNotice::deserialize(&data).unwrap(), Some(notice));
+    assert!(RempDeferredNotice::deserialize(&data[..data.len() - 1]).is_err());
+    // Other objects are not deferred notices
+    assert_eq!(RempDeferredNotice::deserialize(&RempStatusQuery::default().serialize()).unwrap(), None);
+}
+
+#[test]
+fn test_signed_combined_receipt() -> Result<()> {
+    let key = Ed25519KeyOption::generate()?;
+    let other_key = Ed25519KeyOption::generate()?;
+    let signed = SignedCombinedReceipt::sign(vec![1, 2, 3], key.as_ref())?;
+    signed.verify(key.as_ref())?;
+    assert!(signed.verify(other_key.as_ref()).is_err());
+
+    let data = signed.serialize();
+    let restored = SignedCombinedReceipt::deserialize(&data)?.unwrap();
+    assert_eq!(restored, signed);
+    restored.verify(key.as_ref())?;
+    // Signature without receipt
+    assert!(SignedCombinedReceipt::deserialize(&data[..68]).is_err());
+
+    // Tampered receipt is not verified
+    let mut tampered = restored.clone();
+    tampered.receipt[0] = 0;
+    assert!(tampered.verify(key.as_ref()).is_err());
+
+    // Other objects are not signed receipts
+    assert_eq!(SignedCombinedReceipt::deserialize(&RempStatusQuery::default().serialize())?, None);
+    Ok(())
+}
+
+#[test]
+fn test_remp_forwarded_message() -> Result<()> {
+    let message = ton_api::ton::ton_node::rempmessage::RempMessage {
+        message: vec![1, 2, 3].into(),
+        id: UInt256::from([7; 32]),
+        timestamp: 0,
+        signature: Vec::new().into()
+    }.into_boxed();
+    let forwarded = RempForwardedMessage { message };
+    let data = forwarded.serialize()?;
+    assert_eq!(RempForwardedMessage::deserialize(&data)?, Some(forwarded));
+    assert!(RempForwardedMessage::deserialize(&data[..data.len() - 1]).is_err());
+    // Other objects are not forwarded messages
+    assert_eq!(RempForwardedMessage::deserialize(&RempStatusQuery::default().serialize())?, None);
+    Ok(())
+}
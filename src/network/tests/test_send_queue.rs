@@ -0,0 +1,51 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+#[test]
+fn test_send_queue_priority() {
+    let mut queue = PeerSendQueue::new(10, 10);
+    assert!(queue.push(QosClass::Remp, 2));
+    assert!(queue.push(QosClass::Consensus, 3));
+    assert!(queue.push(QosClass::Remp, 4));
+    assert!(queue.push(QosClass::Consensus, 5));
+    let mut sent = Vec::new();
+    while let Some(item) = queue.pop() {
+        sent.push(item);
+    }
+    assert_eq!(sent, vec![3, 5, 2, 4]);
+}
+
+#[test]
+fn test_send_queue_drop_policies() {
+    let mut queue = PeerSendQueue::new(5, 2);
+    for i in 0..5 {
+        assert!(queue.push(QosClass::Consensus, i));
+    }
+    // the oldest consensus message is dropped
+    assert!(!queue.push(QosClass::Consensus, 5));
+    assert!(queue.push(QosClass::Remp, 10));
+    assert!(queue.push(QosClass::Remp, 11));
+    // the oldest REMP message is dropped
+    assert!(!queue.push(QosClass::Remp, 12));
+    assert_eq!(queue.len(QosClass::Consensus), 5);
+    assert_eq!(queue.len(QosClass::Remp), 2);
+
+    let mut sent = Vec::new();
+    while let Some(item) = queue.pop() {
+        sent.push(item);
+    }
+    assert_eq!(sent, vec![1, 2, 3, 4, 5, 11, 12]);
+    assert!(queue.is_empty());
+}
@@ -0,0 +1,45 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+fn parts(sizes: &[usize]) -> BTreeMap<usize, Vec<u8>> {
+    let mut offset = 0;
+    let mut parts = BTreeMap::new();
+    for size in sizes {
+        parts.insert(offset, vec![offset as u8; *size]);
+        offset += size;
+    }
+    parts
+}
+
+#[test]
+fn test_transfer_cache() {
+    let cache = TransferCache::new(Duration::from_secs(60), 10);
+    let now = Instant::now();
+
+    // parts which do not fit into the limit are not kept
+    cache.save_at("a".to_string(), parts(&[4, 4, 4]), now);
+    assert_eq!(cache.resume_at("a", now), parts(&[4, 4]));
+    assert!(cache.resume_at("a", now).is_empty());
+
+    // the oldest transfer is evicted to keep a new one
+    cache.save_at("a".to_string(), parts(&[4]), now);
+    cache.save_at("b".to_string(), parts(&[4]), now + Duration::from_secs(1));
+    cache.save_at("c".to_string(), parts(&[4]), now + Duration::from_secs(2));
+    assert!(cache.resume_at("a", now + Duration::from_secs(2)).is_empty());
+    assert_eq!(cache.resume_at("b", now + Duration::from_secs(2)), parts(&[4]));
+
+    // expired transfers are dropped
+    assert!(cache.resume_at("c", now + Duration::from_secs(62)).is_empty());
+}
@@ -0,0 +1,156 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{
+    collections::{BTreeMap, HashMap}, future::Future,
+    sync::{Mutex, atomic::{AtomicU64, Ordering}}, time::{Duration, Instant}
+};
+
+#[cfg(test)]
+#[path = "tests/test_transfer_cache.rs"]
+mod tests;
+
+/// Received parts of interrupted downloads (archives, persistent states) keyed by
+/// transfer id. Parts are kept for a short time, so the next attempt of the download
+/// continues from them instead of starting from zero.
+pub struct TransferCache {
+    ttl: Duration,
+    memory_limit: usize,
+    transfers: Mutex<HashMap<String, SavedTransfer>>,
+}
+
+struct SavedTransfer {
+    saved_at: Instant,
+    // offset -> data
+    parts: BTreeMap<usize, Vec<u8>>,
+    size: usize,
+}
+
+impl TransferCache {
+
+    pub fn new(ttl: Duration, memory_limit: usize) -> Self {
+        Self {
+            ttl,
+            memory_limit,
+            transfers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keeps parts of the interrupted transfer; parts with the least offsets
+    /// are kept if all of them do not fit into the memory limit
+    pub fn save(&self, id: String, parts: BTreeMap<usize, Vec<u8>>) {
+        self.save_at(id, parts, Instant::now())
+    }
+
+    /// Takes saved parts of the transfer out of the cache
+    pub fn resume(&self, id: &str) -> BTreeMap<usize, Vec<u8>> {
+        self.resume_at(id, Instant::now())
+    }
+
+    fn save_at(&self, id: String, mut parts: BTreeMap<usize, Vec<u8>>, now: Instant) {
+        let mut kept = 0;
+        parts.retain(|_, data| {
+            kept += data.len();
+            kept <= self.memory_limit
+        });
+        let size = parts.values().map(|data| data.len()).sum::<usize>();
+        if size == 0 {
+            return
+        }
+        let mut transfers = self.transfers.lock().unwrap();
+        self.remove_expired(&mut transfers, now);
+        transfers.remove(&id);
+        let mut used = transfers.values().map(|transfer| transfer.size).sum::<usize>();
+        while used + size > self.memory_limit {
+            let oldest = transfers.iter()
+                .min_by_key(|(_, transfer)| transfer.saved_at)
+                .map(|(id, _)| id.clone());
+            match oldest.and_then(|oldest| transfers.remove(&oldest)) {
+                Some(transfer) => used -= transfer.size,
+                None => break
+            }
+        }
+        log::info!("Transfer {} is interrupted, {} bytes are kept to resume it", id, size);
+        transfers.insert(id, SavedTransfer { saved_at: now, parts, size });
+        metrics::gauge!("transfer_cache_memory_bytes", (used + size) as f64);
+    }
+
+    fn resume_at(&self, id: &str, now: Instant) -> BTreeMap<usize, Vec<u8>> {
+        let mut transfers = self.transfers.lock().unwrap();
+        self.remove_expired(&mut transfers, now);
+        let Some(transfer) = transfers.remove(id) else {
+            return BTreeMap::new()
+        };
+        log::info!("Transfer {} is resumed from {} saved bytes", id, transfer.size);
+        metrics::counter!("transfer_cache_resumed_bytes", transfer.size as u64);
+        let used = transfers.values().map(|transfer| transfer.size).sum::<usize>();
+        metrics::gauge!("transfer_cache_memory_bytes", used as f64);
+        transfer.parts
+    }
+
+    fn remove_expired(&self, transfers: &mut HashMap<String, SavedTransfer>, now: Instant) {
+        transfers.retain(|_, transfer| now.saturating_duration_since(transfer.saved_at) < self.ttl)
+    }
+}
+
+/// Progress of a download shared between the downloader and its caller
+#[derive(Default)]
+pub struct TransferProgress {
+    received_bytes: AtomicU64,
+    resumed_bytes: AtomicU64,
+}
+
+impl TransferProgress {
+    const LOG_PERIOD: Duration = Duration::from_secs(10);
+
+    pub fn add_received(&self, bytes: usize) {
+        self.received_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_resumed(&self, bytes: usize) {
+        self.resumed_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Bytes got from peers during the download
+    pub fn received_bytes(&self) -> u64 {
+        self.received_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Bytes taken from interrupted attempts
+    pub fn resumed_bytes(&self) -> u64 {
+        self.resumed_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Awaits the download logging its progress periodically
+    pub async fn report<F: Future>(&self, descr: &str, download: F) -> F::Output {
+        let started = Instant::now();
+        tokio::pin!(download);
+        let mut ticker = tokio::time::interval_at(
+            tokio::time::Instant::now() + Self::LOG_PERIOD,
+            Self::LOG_PERIOD
+        );
+        loop {
+            tokio::select! {
+                result = &mut download => break result,
+                _ = ticker.tick() => {
+                    let received = self.received_bytes();
+                    log::info!(
+                        "Downloading {}: {} bytes received, {} bytes resumed, {} KB/sec",
+                        descr, received, self.resumed_bytes(),
+                        received / 1024 / started.elapsed().as_secs().max(1)
+                    );
+                }
+            }
+        }
+    }
+}
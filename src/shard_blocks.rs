@@ -14,6 +14,7 @@
 use crate::{
     types::top_block_descr::{TopBlockDescrStuff, TopBlockDescrId}, engine::Engine, 
     engine_traits::{EngineAlloc, EngineOperations},shard_state::ShardStateStuff,
+    error::NodeError,
 };
 #[cfg(feature = "telemetry")]
 use crate::engine_traits::EngineTelemetry;
@@ -111,7 +112,9 @@ impl ShardBlocksPool {
             let tbd = if self.is_fake {
                 TopBlockDescr::with_id_and_signatures(id.clone(), BlockSignatures::default())
             } else {
-                TopBlockDescr::construct_from_bytes(&data)?
+                TopBlockDescr::construct_from_bytes(&data).map_err(|e| NodeError::ProtocolViolation(
+                    format!("Malformed top block description of {}: {}", id, e)
+                ))?
             };
             Ok(Arc::new(TopBlockDescrStuff::new(tbd, &id, self.is_fake, own)?))
         };
@@ -13,7 +13,7 @@
 
 use crate::{
     block::{BlockIdExtExtention, BlockStuff}, block_proof::BlockProofStuff, boot,
-    engine_traits::EngineOperations
+    engine_traits::EngineOperations, network::transfer_cache::TransferProgress
 };
 
 use adnl::common::Wait;
@@ -349,7 +349,10 @@ async fn download_archive(
     active_peers: &Arc<lockfree::set::Set<Arc<KeyId>>>
 ) -> Result<Option<Vec<u8>>> {
     log::info!(target: "sync", "Requesting archive for MC seq_no = {}", mc_seq_no);
-    match engine.download_archive(mc_seq_no, active_peers).await {
+    let progress = TransferProgress::default();
+    let download = engine.download_archive(mc_seq_no, active_peers, &progress);
+    let descr = format!("archive for MC seq_no = {}", mc_seq_no);
+    match progress.report(&descr, download).await {
         Ok(Some(data)) => {
             log::info!(
                 target: "sync",
@@ -0,0 +1,55 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use crate::collator_test_bundle::create_engine_allocated;
+#[cfg(feature = "telemetry")]
+use crate::collator_test_bundle::create_engine_telemetry;
+use ton_types::{read_single_root_boc, UInt256};
+
+fn load_zerostate() -> Arc<ShardStateStuff> {
+    let bytes = std::fs::read("src/tests/static/zerostate.boc").unwrap();
+    let root = read_single_root_boc(&bytes).unwrap();
+    let id = BlockIdExt::with_params(
+        ShardIdent::masterchain(), 0, root.repr_hash(), UInt256::calc_file_hash(&bytes)
+    );
+    ShardStateStuff::deserialize_zerostate(
+        id,
+        &bytes,
+        #[cfg(feature = "telemetry")]
+        &create_engine_telemetry(),
+        &create_engine_allocated()
+    ).unwrap()
+}
+
+fn account_from_proof(state: &ShardStateStuff, account_id: &AccountId) -> Option<ShardAccount> {
+    let proof = MerkleProof::construct_from_cell(prove_account(state, account_id).unwrap()).unwrap();
+    assert_eq!(proof.hash, state.root_cell().repr_hash());
+    ShardStateUnsplit::construct_from_cell(proof.proof.virtualize(1)).unwrap()
+        .read_accounts().unwrap()
+        .account(account_id).unwrap()
+}
+
+#[test]
+fn test_account_proof() {
+    let state = load_zerostate();
+    let account_id: AccountId = state.config_params().unwrap().config_addr.clone().into();
+    let expected = state.shard_account(&account_id).unwrap().unwrap();
+    let proved = account_from_proof(&state, &account_id).unwrap();
+    assert_eq!(proved.read_account().unwrap(), expected.read_account().unwrap());
+
+    // absence of account is proved too
+    let account_id = AccountId::from([0x55; 32]);
+    assert!(state.shard_account(&account_id).unwrap().is_none());
+    assert!(account_from_proof(&state, &account_id).is_none());
+}
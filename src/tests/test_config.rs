@@ -0,0 +1,182 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use crate::{
+    collator_test_bundle::create_engine_allocated, engine_traits::EngineOperations,
+    shard_state::ShardStateStuff
+};
+#[cfg(feature = "telemetry")]
+use crate::collator_test_bundle::create_engine_telemetry;
+use ton_block::{BASE_WORKCHAIN_ID, MASTERCHAIN_ID};
+use ton_types::read_single_root_boc;
+
+fn load_config_params() -> ConfigParams {
+    let bytes = std::fs::read("src/tests/static/zerostate.boc").unwrap();
+    let root = read_single_root_boc(&bytes).unwrap();
+    let id = BlockIdExt::with_params(
+        ShardIdent::masterchain(), 0, root.repr_hash(), UInt256::calc_file_hash(&bytes)
+    );
+    let state = ShardStateStuff::deserialize_zerostate(
+        id,
+        &bytes,
+        #[cfg(feature = "telemetry")]
+        &create_engine_telemetry(),
+        &create_engine_allocated()
+    ).unwrap();
+    state.config_params().unwrap().clone()
+}
+
+fn basechain_overrides(config: &ConfigParams) -> WorkchainOverrides {
+    let gas = config.gas_prices(false).unwrap();
+    let limits = config.block_limits(false).unwrap();
+    WorkchainOverrides {
+        gas_price: Some(gas.gas_price * 2),
+        block_gas: Some(ParamLimitsOverride {
+            underload: limits.gas().underload() / 2,
+            soft_limit: limits.gas().soft_limit() / 2,
+            hard_limit: limits.gas().hard_limit(),
+        }),
+        max_ext_msg_size: Some(1024),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_workchain_overrides_precedence() {
+    let original = load_config_params();
+    let overrides = basechain_overrides(&original);
+    overrides.check().unwrap();
+
+    let mut config = original.clone();
+    overrides.apply(&mut config, false).unwrap();
+
+    // overridden fields replace values of the network config
+    let gas = config.gas_prices(false).unwrap();
+    let original_gas = original.gas_prices(false).unwrap();
+    assert_eq!(gas.gas_price, original_gas.gas_price * 2);
+    let limits = config.block_limits(false).unwrap();
+    let original_limits = original.block_limits(false).unwrap();
+    assert_eq!(limits.gas().underload(), original_limits.gas().underload() / 2);
+    assert_eq!(limits.gas().soft_limit(), original_limits.gas().soft_limit() / 2);
+    assert_eq!(config.size_limits_config().unwrap().max_ext_msg_size, 1024);
+
+    // the rest is kept as it is in the network config
+    assert_eq!(gas.gas_limit, original_gas.gas_limit);
+    assert_eq!(gas.block_gas_limit, original_gas.block_gas_limit);
+    assert_eq!(gas.flat_gas_limit, original_gas.flat_gas_limit);
+    assert_eq!(gas.flat_gas_price, original_gas.flat_gas_price);
+    assert_eq!(limits.bytes(), original_limits.bytes());
+    assert_eq!(limits.lt_delta(), original_limits.lt_delta());
+    let size_limits = config.size_limits_config().unwrap();
+    let original_size_limits = original.size_limits_config().unwrap();
+    assert_eq!(size_limits.max_msg_bits, original_size_limits.max_msg_bits);
+    assert_eq!(size_limits.max_msg_cells, original_size_limits.max_msg_cells);
+
+    // basechain overrides don't touch masterchain params
+    assert_eq!(config.gas_prices(true).unwrap(), original.gas_prices(true).unwrap());
+    assert_eq!(config.block_limits(true).unwrap(), original.block_limits(true).unwrap());
+
+    // override is the upper bound of the size accepted by REMP
+    assert_eq!(overrides.max_ext_message_size(65535), 1024);
+    assert_eq!(overrides.max_ext_message_size(512), 512);
+    assert_eq!(WorkchainOverrides::default().max_ext_message_size(65535), 65535);
+}
+
+#[test]
+fn test_workchain_overrides_inconsistent_with_config() {
+    let original = load_config_params();
+    let gas = original.gas_prices(false).unwrap();
+
+    // each field is consistent by itself, but not with the network config
+    let overrides = WorkchainOverrides {
+        gas_limit: Some(gas.block_gas_limit + 1),
+        ..Default::default()
+    };
+    overrides.check().unwrap();
+    assert!(overrides.apply(&mut original.clone(), false).is_err());
+
+    let overrides = WorkchainOverrides {
+        gas_limit: Some(10),
+        block_gas_limit: Some(1),
+        ..Default::default()
+    };
+    assert!(overrides.check().is_err());
+}
+
+struct TestEngine {
+    overrides: HashMap<i32, WorkchainOverrides>,
+}
+
+#[async_trait::async_trait]
+impl EngineOperations for TestEngine {
+    fn workchain_overrides(&self, workchain_id: i32) -> Option<&WorkchainOverrides> {
+        self.overrides.get(&workchain_id)
+    }
+}
+
+#[test]
+fn test_workchain_overrides_unconfigured_workchain() {
+    let original = load_config_params();
+    let mut overrides = HashMap::new();
+    overrides.insert(BASE_WORKCHAIN_ID, basechain_overrides(&original));
+    let engine = TestEngine { overrides };
+
+    // params of workchains without overrides are the network ones
+    for workchain_id in [MASTERCHAIN_ID, 1] {
+        let config = engine.config_params_with_overrides(&original, workchain_id).unwrap();
+        assert_eq!(config, original);
+        assert_eq!(engine.remp_max_message_size_for_workchain(workchain_id), engine.remp_max_message_size());
+    }
+    let config = engine.config_params_with_overrides(&original, BASE_WORKCHAIN_ID).unwrap();
+    assert_ne!(config, original);
+    assert_eq!(engine.remp_max_message_size_for_workchain(BASE_WORKCHAIN_ID), 1024);
+
+    // empty overrides change nothing
+    let mut config = original.clone();
+    WorkchainOverrides::default().apply(&mut config, false).unwrap();
+    assert_eq!(config, original);
+}
+
+#[test]
+fn test_collator_config_check() {
+    let mut config = CollatorConfig::default();
+    config.check().unwrap();
+    assert_eq!(config.cutoff_timeout_ms, 1000);
+    assert_eq!(config.max_collate_threads, CollatorConfig::default().max_collate_threads);
+
+    // Values accepted by previous versions are clamped
+    let mut config = CollatorConfig {
+        cutoff_timeout_ms: 2000,
+        stop_timeout_ms: 1500,
+        finalize_empty_after_ms: 3000,
+        max_collate_threads: 0,
+        clean_timeout_percentage_points: 1500,
+        ..Default::default()
+    };
+    config.check().unwrap();
+    assert_eq!(config.cutoff_timeout_ms, 1500);
+    assert_eq!(config.finalize_empty_after_ms, 1500);
+    assert_eq!(config.max_collate_threads, 1);
+    assert_eq!(config.clean_timeout_percentage_points, 1000);
+
+    let mut config = CollatorConfig { cutoff_timeout_ms: 0, ..Default::default() };
+    config.check().unwrap();
+    assert_eq!(config.cutoff_timeout_ms, 1);
+
+    // New option is checked strictly
+    for remp_block_fill_percent in [0, 101] {
+        let mut config = CollatorConfig { remp_block_fill_percent, ..Default::default() };
+        assert!(config.check().is_err());
+    }
+}
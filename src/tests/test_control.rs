@@ -24,14 +24,17 @@ use crate::{
     validator::validator_manager::ValidationStatus, shard_states_keeper::PinnedShardStateGuard,
 };
 #[cfg(feature = "telemetry")]
-use crate::collator_test_bundle::create_engine_telemetry;
+use crate::{
+    collator_test_bundle::create_engine_telemetry,
+    validator::telemetry::{RempCoreTelemetry, RempTelemetryHealth}
+};
 
 use adnl::{
     common::TaggedTlObject, client::{AdnlClient, AdnlClientConfig},
     server::AdnlServerConfig
 };
 use std::{
-    collections::HashMap, fs, ops::Deref, sync::{Arc, atomic::{AtomicBool, Ordering}},
+    collections::HashMap, fs, ops::Deref, sync::{Arc, atomic::{AtomicBool, AtomicU32, Ordering}},
     time::SystemTime
 };
 use storage::block_handle_db::BlockHandle;
@@ -51,12 +54,13 @@ use ton_api::{
 use ton_api::ton::raw::ShardAccountMeta;
 use ton_api::ton::rpc::raw::{GetAccountMetaByBlock, GetShardAccountMeta};
 use ton_block::{
-    Account, BlockIdExt, ConfigParamEnum, ConfigParams, Deserializable,
-    generate_test_account_by_init_code_hash, Message, Serializable, ShardIdent
+    Account, BlockIdExt, ConfigParamEnum, ConfigParams, Deserializable, ExternalInboundMessageHeader,
+    generate_test_account_by_init_code_hash, Message, MsgAddressInt, Serializable, ShardIdent
 };
 use ton_types::{
     error, fail, base64_encode, Ed25519KeyOption, KeyId, KeyOption, Result, UInt256
 };
+use validator_session::LatencyStat;
 
 // key pair for server
 // "pub_key": "cujCRU4rQbSw48yHVHxQtRPhUlbo+BuZggFTQSu04Y8="
@@ -564,6 +568,76 @@ async fn test_control_send_message() {
 
 }
 
+#[tokio::test]
+async fn test_control_send_service_message() {
+
+    struct TestEngine {
+        state: Arc<ShardStateStuff>,
+        state_loads: AtomicU32,
+        service_messages: AtomicU32,
+        other_messages: AtomicU32
+    }
+
+    #[async_trait::async_trait]
+    impl EngineOperations for TestEngine {
+        async fn load_last_applied_mc_state(&self) -> Result<Arc<ShardStateStuff>> {
+            self.state_loads.fetch_add(1, Ordering::Relaxed);
+            Ok(self.state.clone())
+        }
+        async fn send_service_message(&self, _message_data: &[u8], _id: UInt256) -> Result<()> {
+            self.service_messages.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+        async fn redirect_external_message(&self, _message_data: &[u8], _id: UInt256) -> Result<()> {
+            self.other_messages.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    fn ext_message(dst: &UInt256) -> Vec<u8> {
+        let header = ExternalInboundMessageHeader {
+            dst: MsgAddressInt::with_standart(None, -1, dst.clone().into()).unwrap(),
+            ..Default::default()
+        };
+        Message::with_ext_in_header(header).write_to_bytes().unwrap()
+    }
+
+    crate::test_helper::init_test_log();
+    let (_, state) = gen_master_state(
+        None,
+        None,
+        None,
+        &[],
+        #[cfg(feature = "telemetry")]
+        None,
+        None
+    );
+    let config_addr = state.config_params().unwrap().config_address().unwrap();
+    let engine = Arc::new(TestEngine {
+        state,
+        state_loads: AtomicU32::new(0),
+        service_messages: AtomicU32::new(0),
+        other_messages: AtomicU32::new(0)
+    });
+    let (control, mut client, _) = start_control(
+        DataSource::Engine(engine.clone())
+    ).await.unwrap();
+
+    for dst in [&config_addr, &config_addr, &UInt256::from([7; 32])] {
+        let _answer: ton_api::ton::engine::validator::Success = request(
+            &mut client, ton::rpc::lite_server::SendMessage {body: ext_message(dst).into()}
+        ).await.unwrap();
+    }
+    assert_eq!(engine.service_messages.load(Ordering::Relaxed), 2);
+    assert_eq!(engine.other_messages.load(Ordering::Relaxed), 1);
+    // service addresses are taken from the cache after the first message
+    assert_eq!(engine.state_loads.load(Ordering::Relaxed), 1);
+
+    client.shutdown().await.unwrap();
+    control.shutdown().await;
+
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_control_db_restore() {
 
@@ -664,7 +738,10 @@ async fn test_stats() {
         db: InternalDb,
         master_state_id: BlockIdExt,
         master_state: Arc<ShardStateStuff>,
-        last_validation_time: lockfree::map::Map<ShardIdent, u64>
+        last_validation_time: lockfree::map::Map<ShardIdent, u64>,
+        session_latency_stats: lockfree::map::Map<ShardIdent, LatencyStat>,
+        #[cfg(feature = "telemetry")]
+        remp_core_telemetry: RempCoreTelemetry
     }
 
     impl TestEngine {
@@ -708,7 +785,10 @@ async fn test_stats() {
                 db,
                 master_state_id,
                 master_state,
-                last_validation_time: lockfree::map::Map::new()
+                last_validation_time: lockfree::map::Map::new(),
+                session_latency_stats: lockfree::map::Map::new(),
+                #[cfg(feature = "telemetry")]
+                remp_core_telemetry: RempCoreTelemetry::new(1)
             }
         }
     }
@@ -727,6 +807,9 @@ async fn test_stats() {
         fn last_validation_time(&self) -> &lockfree::map::Map<ShardIdent, u64> {
             &self.last_validation_time
         }
+        fn session_latency_stats(&self) -> &lockfree::map::Map<ShardIdent, LatencyStat> {
+            &self.session_latency_stats
+        }
         fn load_block_handle(&self, id: &BlockIdExt) -> Result<Option<Arc<BlockHandle>>> {
             self.db.load_block_handle(id)
         }
@@ -742,6 +825,10 @@ async fn test_stats() {
         fn validation_status(&self) -> ValidationStatus {
             ValidationStatus::Active
         }
+        #[cfg(feature = "telemetry")]
+        fn remp_core_telemetry(&self) -> &RempCoreTelemetry {
+            &self.remp_core_telemetry
+        }
     }
 
     struct Ethalon<'a> {
@@ -778,6 +865,8 @@ async fn test_stats() {
         let supported_capabilities = format!("{}", supported_capabilities());
         let supported_version = format!("{}", supported_version());
         let timediff = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        #[cfg(feature = "telemetry")]
+        let remp_telemetry = format!("{:#}", serde_json::to_value(RempTelemetryHealth::default()).unwrap());
         let mut ethalon_stats = HashMap::new();
         if !new_format {
             add_ethalon(&mut ethalon_stats, "collation_stats", "{}");
@@ -800,6 +889,9 @@ async fn test_stats() {
         add_ethalon(&mut ethalon_stats, "node_version", &node_version);
         add_ethalon(&mut ethalon_stats, "processed_workchain", "\"not specified\"");
         add_ethalon(&mut ethalon_stats, "public_overlay_key_id", &overlay_key);
+        add_ethalon(&mut ethalon_stats, "remp_max_message_size", "65535");
+        #[cfg(feature = "telemetry")]
+        add_ethalon(&mut ethalon_stats, "remp_telemetry", &remp_telemetry);
         add_ethalon(&mut ethalon_stats, "shards_timediff", "timediff");
         if new_format {
             add_ethalon(&mut ethalon_stats, "supported_block", &supported_version);
@@ -811,6 +903,7 @@ async fn test_stats() {
         add_ethalon(&mut ethalon_stats, "timediff", "timediff");
         add_ethalon(&mut ethalon_stats, "tps_10", "0");
         add_ethalon(&mut ethalon_stats, "tps_300", "0");
+        add_ethalon(&mut ethalon_stats, "validation_latency", "{}");
         if !new_format {
             add_ethalon(&mut ethalon_stats, "validation_stats", "{}");
         }
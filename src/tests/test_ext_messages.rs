@@ -98,6 +98,31 @@ fn test_create_ext_message_bad_message_format() {
     create_ext_message(&data).expect_err("it must accept BOC only with external inbound message");
 }
 
+#[test]
+fn test_create_ext_message_reject_reasons() {
+    let reason = |data: &[u8]| {
+        ExtMessageRejectReason::from_error_text(&create_ext_message(data).unwrap_err().to_string())
+    };
+    assert_eq!(reason(&[0; MAX_EXTERNAL_MESSAGE_SIZE + 6]), Some(ExtMessageRejectReason::TooLarge));
+    assert_eq!(reason(&[0; 100]), Some(ExtMessageRejectReason::BadBoc));
+
+    let msg = Message::with_int_header(InternalMessageHeader::default());
+    let data = write_boc(&msg.serialize().unwrap().into()).unwrap();
+    assert_eq!(reason(&data), Some(ExtMessageRejectReason::BadHeader));
+
+    let mut root = BuilderData::new();
+    for _ in 0..600 {
+        let mut new_root = BuilderData::new();
+        new_root.checked_append_reference(root.into_cell().unwrap()).unwrap();
+        root = new_root;
+    }
+    let data = write_boc(&root.into_cell().unwrap()).unwrap();
+    assert_eq!(reason(&data), Some(ExtMessageRejectReason::TooDeep));
+
+    assert_eq!(ExtMessageRejectReason::from_error_text("message_too_large"), None);
+    assert_eq!(ExtMessageRejectReason::from_error_text("some other error"), None);
+}
+
 #[test]
 fn test_create_ext_message() {
     let msg = Message::with_ext_in_header(ExternalInboundMessageHeader::default());
@@ -348,3 +373,47 @@ fn test_external_messages_big_load() {
     assert_eq!(0, count);
     assert!((n as u64) < limit * 3);
 }
+
+#[test]
+fn test_broadcast_rate_limiter() {
+    let limiter = BroadcastRateLimiter::new(3, 1000);
+    let src1 = KeyId::from_data([1; 32]);
+    let src2 = KeyId::from_data([2; 32]);
+    for _ in 0..3 {
+        assert!(limiter.check(&src1, 10_000));
+    }
+    assert!(!limiter.check(&src1, 10_500));
+    assert!(limiter.check(&src2, 10_500));
+    // new window
+    assert!(limiter.check(&src1, 11_000));
+    assert_eq!(limiter.tracked_sources(), 2);
+    limiter.cleanup(12_000);
+    assert_eq!(limiter.tracked_sources(), 0);
+
+    let unlimited = BroadcastRateLimiter::new(0, 1000);
+    for _ in 0..100 {
+        assert!(unlimited.check(&src1, 10_000));
+    }
+    assert_eq!(unlimited.tracked_sources(), 0);
+}
+
+#[test]
+fn test_remp_service_messages() {
+    let pool = RempMessagesPool::new();
+    let id1 = UInt256::from([1; 32]);
+    let id2 = UInt256::from([2; 32]);
+    pool.add_service_message(id1.clone(), 1000);
+    pool.add_service_message(id2.clone(), 1000);
+    assert!(pool.is_service_message(&id1));
+    assert!(!pool.is_service_message(&UInt256::from([3; 32])));
+
+    // ignored message keeps priority, accepted one is forgotten
+    pool.finalize_messages(BlockIdExt::default(), vec!(id1.clone()), vec!(), vec!(id2.clone())).unwrap();
+    assert!(!pool.is_service_message(&id1));
+    assert!(pool.is_service_message(&id2));
+
+    // expired messages are removed when new one is added
+    pool.add_service_message(id1.clone(), 1000 + REMP_SERVICE_MESSAGE_TTL);
+    assert!(pool.is_service_message(&id1));
+    assert!(!pool.is_service_message(&id2));
+}
@@ -0,0 +1,36 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use ton_block::{ExternalInboundMessageHeader, MsgAddressInt, Serializable};
+use ton_types::{base64_encode, SliceData};
+
+#[test]
+fn test_parse_imported_message() {
+    let dst = MsgAddressInt::with_standart(None, 0, SliceData::from(UInt256::from([1; 32]))).unwrap();
+    let message = Message::with_ext_in_header(ExternalInboundMessageHeader::new(Default::default(), dst));
+    let data = message.write_to_bytes().unwrap();
+    let (id, parsed) = parse_message(&base64_encode(&data)).unwrap();
+    assert_eq!(id, message.serialize().unwrap().repr_hash());
+    assert_eq!(parsed, data);
+
+    assert_eq!(parse_message("not base64!").unwrap_err(), "invalid base64");
+    assert_eq!(parse_message(&base64_encode([1, 2, 3])).unwrap_err(), "invalid boc");
+    let internal = Message::default().write_to_bytes().unwrap();
+    assert_eq!(parse_message(&base64_encode(&internal)).unwrap_err(), "not an inbound external message");
+
+    let mut report = MessageImportReport::default();
+    report.reject("invalid boc");
+    report.reject("invalid boc");
+    assert_eq!(report.rejected.get("invalid boc"), Some(&2));
+}
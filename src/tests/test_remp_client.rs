@@ -782,12 +782,12 @@ async fn test_remp_client() -> Result<()> {
     let remp_client = Arc::new(RempClient::with_params(1000, NEXT_BLOCK_TIMEOUT * 10, true, UInt256::rand()));
     remp_client.clone().start(engine.clone(), None).await?;
 
-    remp_client.clone().process_remp_message(msg2.write_to_bytes()?, msg2_id.clone());
+    remp_client.clone().process_remp_message(msg2.write_to_bytes()?, msg2_id.clone())?;
     
     tokio::time::sleep(Duration::from_millis(NEXT_BLOCK_TIMEOUT * 1)).await;
     
-    remp_client.clone().process_remp_message(msg1.write_to_bytes()?, msg1_id.clone());
-    remp_client.clone().process_remp_message(msg3.write_to_bytes()?, msg3_id.clone());
+    remp_client.clone().process_remp_message(msg1.write_to_bytes()?, msg1_id.clone())?;
+    remp_client.clone().process_remp_message(msg3.write_to_bytes()?, msg3_id.clone())?;
 
     remp_client.clone().process_new_block(mc_block_9.unwrap());
 
@@ -874,4 +874,13 @@ async fn test_remp_client() -> Result<()> {
     assert!(remp_client.messages_history().iter().count() == 1);
 
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn test_remp_client_queue_limit() -> Result<()> {
+    let remp_client = Arc::new(RempClient::new(UInt256::rand()).with_queue_max_len(2));
+    remp_client.clone().process_remp_message(vec![1], UInt256::from([1; 32]))?;
+    remp_client.clone().process_remp_message(vec![2], UInt256::from([2; 32]))?;
+    remp_client.clone().process_remp_message(vec![3], UInt256::from([3; 32]))
+        .expect_err("queue is full");
+    Ok(())
+}
@@ -0,0 +1,95 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use ton_block::ShardIdent;
+
+fn build_tree(leaves: &[u32]) -> Cell {
+    let mut root = BuilderData::new();
+    for leaf in leaves {
+        let mut b = BuilderData::new();
+        b.append_u32(*leaf).unwrap();
+        root.checked_append_reference(b.into_cell().unwrap()).unwrap();
+    }
+    root.into_cell().unwrap()
+}
+
+fn block_id(seq_no: u32, root: &Cell) -> BlockIdExt {
+    BlockIdExt::with_params(ShardIdent::masterchain(), seq_no, root.repr_hash(), UInt256::default())
+}
+
+#[test]
+fn test_state_diff_roundtrip() {
+    let base_root = build_tree(&[1, 2, 3]);
+    let target_root = build_tree(&[1, 2, 4]);
+    let base_id = block_id(10, &base_root);
+    let target_id = block_id(20, &target_root);
+
+    let diff = StateDiff::with_roots(base_id.clone(), &base_root, target_id.clone(), &target_root).unwrap();
+    let data = diff.write_to_bytes().unwrap();
+    let diff = StateDiff::construct_from_bytes(&data).unwrap();
+    assert_eq!(diff.base_id(), &base_id);
+    assert_eq!(diff.target_id(), &target_id);
+
+    let root = diff.apply_to_root(&base_root).unwrap();
+    assert_eq!(root.repr_hash(), target_root.repr_hash());
+
+    // diff can't be applied to another state
+    assert!(diff.apply_to_root(&build_tree(&[5])).is_err());
+}
+
+#[test]
+fn test_state_diff_wrong_order() {
+    let base_root = build_tree(&[1]);
+    let target_root = build_tree(&[2]);
+    assert!(StateDiff::with_roots(
+        block_id(20, &base_root), &base_root, block_id(10, &target_root), &target_root
+    ).is_err());
+}
+
+#[tokio::test]
+async fn test_import_state_diff_parts_order() {
+    struct TestEngine;
+    impl EngineOperations for TestEngine {
+        fn db_root_dir(&self) -> Result<&str> {
+            Ok("target/test_state_diff_import")
+        }
+    }
+
+    let engine: Arc<dyn EngineOperations> = Arc::new(TestEngine);
+    let mut query = ImportStateDiff {
+        base_root_hash: UInt256::from([1; 32]),
+        target_root_hash: UInt256::from([2; 32]),
+        offset: 3,
+        data: vec![1, 2, 3],
+        last: false,
+    };
+    // import must be started from the first part
+    let _ = tokio::fs::remove_dir_all("target/test_state_diff_import").await;
+    assert!(import_state_diff_part(&engine, &query).await.is_err());
+
+    query.offset = 0;
+    assert!(import_state_diff_part(&engine, &query).await.unwrap().is_none());
+    // parts can't be skipped or repeated
+    query.offset = 6;
+    assert!(import_state_diff_part(&engine, &query).await.is_err());
+    query.offset = 3;
+    assert!(import_state_diff_part(&engine, &query).await.unwrap().is_none());
+    query.offset = 3;
+    assert!(import_state_diff_part(&engine, &query).await.is_err());
+
+    // the whole received data is not a diff
+    query.offset = 6;
+    query.last = true;
+    assert!(import_state_diff_part(&engine, &query).await.is_err());
+}
@@ -0,0 +1,69 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+#[test]
+fn test_parts_download() {
+    let mut parts = PartsDownload::new(4);
+    let start = Instant::now();
+
+    assert_eq!(parts.take(start), Some(0));
+    assert_eq!(parts.take(start), Some(1));
+    assert_eq!(parts.take(start), Some(2));
+    assert_eq!(parts.take(start), Some(3));
+    // failed part is given to the next peer
+    parts.fail(1);
+    assert_eq!(parts.take(start), Some(1));
+    parts.complete(0, vec![0; 4], Duration::from_secs(1));
+    parts.complete(2, vec![2; 2], Duration::from_secs(1));
+    // the short part is the last one, so the part after it is not needed
+    parts.complete(3, vec![], Duration::from_secs(1));
+    assert!(!parts.is_finished());
+
+    // part 1 is downloaded too long and given to one more peer only
+    assert_eq!(parts.take(start + Duration::from_secs(1)), None);
+    assert_eq!(parts.take(start + Duration::from_secs(3)), Some(1));
+    assert_eq!(parts.take(start + Duration::from_secs(3)), None);
+    // the first answer wins
+    parts.complete(1, vec![1; 4], Duration::from_secs(3));
+    parts.fail(1);
+    assert!(parts.is_finished());
+    assert_eq!(parts.into_data(), [vec![0; 4], vec![1; 4], vec![2; 2]].concat());
+}
+
+#[test]
+fn test_parts_download_resume() {
+    let mut parts = PartsDownload::new(4);
+    let start = Instant::now();
+    parts.complete(0, vec![0; 4], Duration::from_secs(1));
+    parts.complete(1, vec![1; 4], Duration::from_secs(1));
+    parts.complete(3, vec![3; 4], Duration::from_secs(1));
+    let saved = parts.into_parts();
+    // parts got from the start are already joined
+    assert_eq!(saved.keys().copied().collect::<Vec<_>>(), vec![0, 12]);
+    assert_eq!(saved[&0], [vec![0; 4], vec![1; 4]].concat());
+
+    // parts got before are not downloaded again
+    let mut parts = PartsDownload::new(4);
+    assert_eq!(parts.resume(saved), 12);
+    assert_eq!(parts.take(start), Some(2));
+    assert_eq!(parts.take(start), Some(4));
+    parts.complete(2, vec![2; 4], Duration::from_secs(1));
+    parts.complete(4, vec![4; 1], Duration::from_secs(1));
+    assert!(parts.is_finished());
+    assert_eq!(
+        parts.into_data(), 
+        [vec![0; 4], vec![1; 4], vec![2; 4], vec![3; 4], vec![4; 1]].concat()
+    );
+}
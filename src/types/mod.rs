@@ -18,4 +18,5 @@ pub mod limits;
 pub mod messages;
 pub mod lockfree_cache;
 pub mod shard_blocks_observer;
+pub mod state_snapshot;
 pub mod mpmc_channel;
\ No newline at end of file
@@ -0,0 +1,103 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{engine_traits::EngineOperations, shard_states_keeper::PinnedShardStateGuard};
+
+use std::{collections::HashMap, sync::Mutex};
+use ton_block::{BlockIdExt, ConfigParams, MsgAddressInt, ShardAccount};
+use ton_types::{error, AccountId, Result};
+
+#[cfg(test)]
+#[path = "tests/test_state_snapshot.rs"]
+mod tests;
+
+/// Consistent read-only view of the blockchain: masterchain state and shard states
+/// referenced by it. Masterchain state is pinned (protected from GC) while the snapshot
+/// is alive; shard states are loaded and pinned on the first read of their accounts,
+/// so a lookup touches only the shard it needs. Any number of reads made through
+/// the snapshot observe the same blockchain state even if new blocks are applied concurrently.
+pub struct StateSnapshot {
+    mc_state: PinnedShardStateGuard,
+    shard_states: Mutex<HashMap<BlockIdExt, PinnedShardStateGuard>>,
+}
+
+impl StateSnapshot {
+
+    pub fn with_mc_state(mc_state: PinnedShardStateGuard) -> Self {
+        Self { mc_state, shard_states: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn mc_block_id(&self) -> &BlockIdExt {
+        self.mc_state.state().block_id()
+    }
+
+    pub fn mc_state(&self) -> &PinnedShardStateGuard {
+        &self.mc_state
+    }
+
+    pub fn config_params(&self) -> Result<&ConfigParams> {
+        self.mc_state.state().config_params()
+    }
+
+    /// Returns id of the block (referred by the snapshot) whose state contains given account
+    pub fn block_id_for_account(&self, addr: &MsgAddressInt) -> Result<BlockIdExt> {
+        if addr.is_masterchain() {
+            return Ok(self.mc_block_id().clone())
+        }
+        for id in self.mc_state.state().top_blocks(addr.workchain_id())? {
+            if id.shard().contains_account(addr.address().clone())? {
+                return Ok(id)
+            }
+        }
+        Err(error!(
+            "Cannot find actual shard for account {} in snapshot {}", addr, self.mc_block_id()
+        ))
+    }
+
+    /// Returns the pinned state which contains given account,
+    /// the state is loaded only once per snapshot
+    pub async fn state_for_account(
+        &self,
+        engine: &dyn EngineOperations,
+        addr: &MsgAddressInt
+    ) -> Result<PinnedShardStateGuard> {
+        let block_id = self.block_id_for_account(addr)?;
+        if block_id == *self.mc_block_id() {
+            return Ok(self.mc_state.clone())
+        }
+        if let Some(state) = self.shard_states.lock().unwrap().get(&block_id) {
+            return Ok(state.clone())
+        }
+        let state = engine.load_and_pin_state(&block_id).await?;
+        self.shard_states.lock().unwrap().insert(block_id, state.clone());
+        Ok(state)
+    }
+
+    pub async fn find_account(
+        &self,
+        engine: &dyn EngineOperations,
+        addr: &MsgAddressInt
+    ) -> Result<Option<ShardAccount>> {
+        self.state_for_account(engine, addr).await?.state().shard_account(&addr.address())
+    }
+
+    pub async fn find_account_by_id(
+        &self,
+        engine: &dyn EngineOperations,
+        workchain_id: i32,
+        account_id: &AccountId
+    ) -> Result<Option<ShardAccount>> {
+        let addr = MsgAddressInt::with_standart(None, workchain_id as i8, account_id.clone())?;
+        self.find_account(engine, &addr).await
+    }
+}
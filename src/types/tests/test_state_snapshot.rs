@@ -0,0 +1,114 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use crate::{
+    collator_test_bundle::create_engine_allocated,
+    internal_db::state_gc_resolver::AllowStateGcSmartResolver, shard_state::ShardStateStuff
+};
+#[cfg(feature = "telemetry")]
+use crate::collator_test_bundle::create_engine_telemetry;
+use std::sync::Arc;
+use ton_block::ShardIdent;
+use ton_types::{fail, read_single_root_boc, UInt256};
+
+fn load_zerostate() -> Arc<ShardStateStuff> {
+    let bytes = std::fs::read("src/tests/static/zerostate.boc").unwrap();
+    let root = read_single_root_boc(&bytes).unwrap();
+    let id = BlockIdExt::with_params(
+        ShardIdent::masterchain(), 0, root.repr_hash(), UInt256::calc_file_hash(&bytes)
+    );
+    ShardStateStuff::deserialize_zerostate(
+        id,
+        &bytes,
+        #[cfg(feature = "telemetry")]
+        &create_engine_telemetry(),
+        &create_engine_allocated()
+    ).unwrap()
+}
+
+struct TestEngine {
+    mc_state: Arc<ShardStateStuff>,
+    gc_resolver: Arc<AllowStateGcSmartResolver>,
+    fail_shard_states: bool,
+    loaded: Mutex<Vec<BlockIdExt>>,
+}
+
+impl TestEngine {
+    fn new(fail_shard_states: bool) -> Self {
+        Self {
+            mc_state: load_zerostate(),
+            gc_resolver: Arc::new(AllowStateGcSmartResolver::new(10)),
+            fail_shard_states,
+            loaded: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn pin_mc_state(&self) -> PinnedShardStateGuard {
+        PinnedShardStateGuard::new(self.mc_state.clone(), self.gc_resolver.clone()).unwrap()
+    }
+
+    fn loaded(&self) -> Vec<BlockIdExt> {
+        self.loaded.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl EngineOperations for TestEngine {
+    async fn load_and_pin_state(&self, block_id: &BlockIdExt) -> Result<PinnedShardStateGuard> {
+        self.loaded.lock().unwrap().push(block_id.clone());
+        if self.fail_shard_states {
+            fail!("State {} is not loaded", block_id)
+        }
+        // content of shard states doesn't matter for the tests
+        Ok(self.pin_mc_state())
+    }
+}
+
+fn shard_account_addr() -> MsgAddressInt {
+    MsgAddressInt::with_standart(None, 0, AccountId::from([0x55; 32])).unwrap()
+}
+
+#[tokio::test]
+async fn test_state_snapshot_masterchain_account() {
+    // shard states are not needed to read masterchain accounts
+    let engine = TestEngine::new(true);
+    let snapshot = StateSnapshot::with_mc_state(engine.pin_mc_state());
+    let config_addr = snapshot.config_params().unwrap().config_addr.clone();
+    let account = snapshot.find_account_by_id(&engine, -1, &config_addr.clone().into()).await.unwrap();
+    assert!(account.is_some());
+    assert!(engine.loaded().is_empty());
+
+    // broken shard state doesn't affect masterchain reads
+    assert!(snapshot.find_account(&engine, &shard_account_addr()).await.is_err());
+    let account = snapshot.find_account_by_id(&engine, -1, &config_addr.clone().into()).await.unwrap();
+    assert!(account.is_some());
+}
+
+#[tokio::test]
+async fn test_state_snapshot_loads_shard_once() {
+    let engine = TestEngine::new(false);
+    let snapshot = StateSnapshot::with_mc_state(engine.pin_mc_state());
+    let addr = shard_account_addr();
+    let block_id = snapshot.block_id_for_account(&addr).unwrap();
+    assert_eq!(block_id.shard().workchain_id(), 0);
+    assert!(block_id.shard().contains_account(addr.address()).unwrap());
+
+    snapshot.state_for_account(&engine, &addr).await.unwrap();
+    snapshot.state_for_account(&engine, &addr).await.unwrap();
+    assert_eq!(engine.loaded(), vec![block_id]);
+
+    // unknown workchain
+    let addr = MsgAddressInt::with_standart(None, 77, AccountId::from([0x55; 32])).unwrap();
+    assert!(snapshot.block_id_for_account(&addr).is_err());
+}
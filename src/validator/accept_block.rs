@@ -18,15 +18,15 @@ use crate::{
     engine_traits::EngineOperations,
     full_node::apply_block::calc_shard_state,
     types::top_block_descr::TopBlockDescrStuff,
-    validator::validator_utils::check_crypto_signatures,
+    validator::validator_utils::BlockSignaturesAggregator,
     validating_utils::{UNREGISTERED_CHAIN_MAX_LEN, fmt_block_id_short},
 };
 use storage::block_handle_db::BlockHandle;
 use std::{cmp::{max, min}, sync::Arc, ops::Deref, time::Duration, collections::HashSet};
 use ton_block::{
     Block, TopBlockDescr, BlockIdExt, MerkleProof, McShardRecord, CryptoSignaturePair, 
-    Deserializable, BlockSignatures, ValidatorSet, BlockProof, Serializable, BlockSignaturesPure, 
-    ValidatorBaseInfo, OutQueueUpdate
+    Deserializable, BlockSignatures, ValidatorSet, BlockProof, Serializable,
+    OutQueueUpdate
 };
 use ton_types::{error, Result, fail, UInt256, UsageTree, HashmapType};
 use ton_api::ton::ton_node::{
@@ -59,6 +59,24 @@ pub async fn accept_block(
         Ok(block)
     }).transpose()?;
 
+    if let Some(handle) = engine.load_block_handle(&id)? {
+        if handle.is_applied() {
+            log::debug!(
+                target: "validator",
+                "({}): accept block: skipping - block has already applied",
+                block_descr,
+            );
+            return Ok(())
+        }
+    }
+
+    // signatures are checked once, retries of accept block routine reuse them
+    let mut aggregator = BlockSignaturesAggregator::new(&id, &validator_set)?;
+    for signature in signatures {
+        aggregator.add(signature)?;
+    }
+    aggregator.signatures()?;
+
     let mut timeout = 50;
     let mut attempt = 0;
     const MAX_ATTEMPTS: usize = 100;
@@ -67,8 +85,7 @@ pub async fn accept_block(
             &id,
             block_opt.clone(),
             &prev,
-            &validator_set,
-            &signatures,
+            &aggregator,
             &engine,
             is_fake,
             is_fork,
@@ -191,8 +208,7 @@ pub async fn accept_block_routine(
     id: &BlockIdExt,
     block_opt: Option<BlockStuff>,
     prev: &[BlockIdExt],
-    validator_set: &ValidatorSet,
-    signatures: &[CryptoSignaturePair],
+    signatures: &BlockSignaturesAggregator,
     engine: &Arc<dyn EngineOperations>,
     is_fake: bool,
     is_fork:  bool
@@ -262,10 +278,8 @@ pub async fn accept_block_routine(
         &engine
     ).await?;
 
-    let (proof, signatures) = create_new_proof(
-        &block,
-        &validator_set,
-        signatures)?;
+    let signatures = signatures.signatures()?.clone();
+    let proof = create_new_proof_internal(&block, Some(&signatures))?;
 
     handle = engine.store_block_proof(&id, Some(handle), &proof).await?
         .to_non_created()
@@ -386,20 +400,24 @@ pub fn create_new_proof(
     validator_set: &ValidatorSet,
     signatures: &[CryptoSignaturePair]
 ) -> Result<(BlockProofStuff, BlockSignatures)> {
-    create_new_proof_internal(block_stuff, validator_set, signatures, false)
+    let mut aggregator = BlockSignaturesAggregator::new(block_stuff.id(), validator_set)?;
+    for signature in signatures {
+        aggregator.add(signature.clone())?;
+    }
+    let signatures = aggregator.signatures()?.clone();
+    let proof = create_new_proof_internal(block_stuff, Some(&signatures))?;
+    Ok((proof, signatures))
 }
 
 pub fn create_new_proof_link(block_stuff: &BlockStuff) -> Result<BlockProofStuff> {
-    let (proof, _) = create_new_proof_internal(block_stuff, &ValidatorSet::default(), &Vec::new(), true)?;
-    Ok(proof)
+    create_new_proof_internal(block_stuff, None)
 }
 
+// signatures are checked by BlockSignaturesAggregator, None - proof link is created
 fn create_new_proof_internal(
     block_stuff: &BlockStuff,
-    validator_set: &ValidatorSet,
-    signatures: &[CryptoSignaturePair],
-    force_proof_link: bool,
-) -> Result<(BlockProofStuff, BlockSignatures)> {
+    signatures: Option<&BlockSignatures>,
+) -> Result<BlockProofStuff> {
     let id = block_stuff.id();
     let block_descr = fmt_block_id_short(id);
     log::trace!(target: "validator", "({}): create_new_proof", block_descr);
@@ -438,61 +456,27 @@ fn create_new_proof_internal(
         }
     }
 
-    if force_proof_link {
-        let proof = BlockProof {
-            proof_for: id.clone(),
-            root: merkle_proof.serialize()?,
-            signatures: None
-        };
-        return Ok((BlockProofStuff::new(proof, true)?, BlockSignatures::default()))
-    }
-
-    // build BlockSignatures struct
-    let total_weight = validator_set.total_weight();
-    let mut block_signatures_pure = BlockSignaturesPure::with_weight(total_weight);
-    for sign in signatures {
-        block_signatures_pure.add_sigpair(sign.clone())
-    }
-
-    // #[cfg(feature = "fast_finality")]
-    // let cc_seqno = collator_range.start;
-    // #[cfg(not(feature = "fast_finality"))]
-    let cc_seqno = validator_set.catchain_seqno();
-
-    let mut block_signatures = BlockSignatures::with_params(
-        ValidatorBaseInfo::with_params(
-            ValidatorSet::calc_subset_hash_short(validator_set.list(), cc_seqno)?,
-            validator_set.catchain_seqno()
-        ), 
-        block_signatures_pure
-    );
-
-    // check signatures 
-    // TODO make function somewhere, BlockProofStuff contains same code
-
-    let checked_data = ton_block::Block::build_data_for_sign(
-        &id.root_hash,
-        &id.file_hash
-    );
-    let weight = check_crypto_signatures(&block_signatures.pure_signatures, validator_set.list(), &checked_data)
-        .map_err(|e| error!("Error while check signatures for block {}: {}", id, e))?;
-
-    block_signatures.pure_signatures.set_weight(weight);
-
-    if weight * 3 <= total_weight * 2 {
-        fail!("Block {}: too small signatures weight (weight: {}, total: {})", id, weight, total_weight);
-    }
-
+    let signatures = match signatures {
+        Some(signatures) => signatures,
+        None => {
+            let proof = BlockProof {
+                proof_for: id.clone(),
+                root: merkle_proof.serialize()?,
+                signatures: None
+            };
+            return BlockProofStuff::new(proof, true)
+        }
+    };
 
     // Construct proof
     let is_link = !id.shard().is_masterchain();
     let proof = BlockProof {
         proof_for: id.clone(),
         root: merkle_proof.serialize()?,
-        signatures: if !is_link { Some(block_signatures.clone()) } else { None }
+        signatures: if !is_link { Some(signatures.clone()) } else { None }
     };
 
-    Ok((BlockProofStuff::new(proof, is_link)?, block_signatures))
+    BlockProofStuff::new(proof, is_link)
 }
 
 pub fn visit_block_for_proof(block: &Block, id: &BlockIdExt) -> Result<()> {
@@ -112,6 +112,11 @@ impl CandidateDbPool {
         }
     }
 
+    /// looks for candidate in dbs of all sessions
+    pub fn find(&self, root_hash: &BlockHash) -> Option<Arc<ValidatorBlockCandidate>> {
+        self.map.iter().find_map(|db| db.val().load(root_hash).ok())
+    }
+
     /// destroys db for session
     pub fn destroy_db(&self, session_id: &UInt256) -> Result<bool> {
         if let Some(mut removed) = self.map.remove(session_id) {
@@ -1,4 +1,4 @@
-use crate::engine_traits::PrivateOverlayOperations;
+use crate::{engine_traits::PrivateOverlayOperations, network::send_queue::QosClass};
 use overlay::PrivateOverlayShortId;
 use std::sync::Arc;
 use ton_types::{Result, UInt256};
@@ -6,14 +6,16 @@ use validator_session::{PublicKeyHash, CatchainOverlayPtr, CatchainNode};
 
 pub(crate) struct CatchainOverlayManagerImpl {
     network: Arc<dyn PrivateOverlayOperations>,
-    validator_list_id: UInt256
+    validator_list_id: UInt256,
+    qos: QosClass,
 }
 
 impl CatchainOverlayManagerImpl {
-    pub fn new(network: Arc<dyn PrivateOverlayOperations>, validator_list_id: UInt256) -> Self {
+    pub fn new(network: Arc<dyn PrivateOverlayOperations>, validator_list_id: UInt256, qos: QosClass) -> Self {
         Self {
             network,
-            validator_list_id
+            validator_list_id,
+            qos,
         }
     }
 }
@@ -29,7 +31,7 @@ impl catchain::CatchainOverlayManager for CatchainOverlayManagerImpl {
         replay_listener: catchain::CatchainOverlayLogReplayListenerPtr,
     ) -> Result<CatchainOverlayPtr> {
         self.network.create_catchain_client(
-            self.validator_list_id.clone(), overlay_short_id, nodes, listener, replay_listener
+            self.validator_list_id.clone(), overlay_short_id, nodes, listener, replay_listener, self.qos
         )
     }
 
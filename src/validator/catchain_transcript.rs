@@ -0,0 +1,281 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{
+    collections::VecDeque, ops::RangeInclusive,
+    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
+    time::{SystemTime, UNIX_EPOCH}
+};
+use catchain::{BlockPtr, CatchainNode};
+use ton_api::ton::ton_node::RempCatchainRecord;
+use ton_block::ShardIdent;
+use ton_types::{error, Result, UInt256};
+
+#[cfg(test)]
+#[path = "tests/test_catchain_transcript.rs"]
+mod tests;
+
+/// Maximal number of blocks stored in one transcript; later blocks are counted, but not stored
+pub const MAX_TRANSCRIPT_BLOCKS: usize = 200_000;
+/// Maximal number of blocks exported at once, the rest is exported by next pages
+pub const MAX_TRANSCRIPT_PAGE_BLOCKS: usize = 1000;
+
+/// Catchain block description in transcript
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct TranscriptBlock {
+    pub hash: String,
+    pub source_idx: u32,
+    pub prev: Option<String>,
+    pub deps: Vec<String>,
+    pub payload_hash: String,
+    pub created_at_ms: u64,
+    /// Time the block was delivered to the node
+    pub received_at_ms: u64,
+    /// Ids of messages forwarded in the block
+    pub messages: Vec<String>,
+    /// Ids of messages reported as rejected in the block
+    pub rejected: Vec<String>,
+}
+
+impl TranscriptBlock {
+    pub fn with_records(block: &BlockPtr, records: &[RempCatchainRecord]) -> Self {
+        let mut messages = Vec::new();
+        let mut rejected = Vec::new();
+        for record in records {
+            match record {
+                RempCatchainRecord::TonNode_RempCatchainMessage(msg) =>
+                    messages.push(msg.message_id.to_hex_string()),
+                RempCatchainRecord::TonNode_RempCatchainMessageDigest(digest) =>
+                    rejected.extend(digest.messages.iter().map(|ids| ids.id.to_hex_string()))
+            }
+        }
+        Self {
+            hash: block.get_hash().to_hex_string(),
+            source_idx: block.get_source_id(),
+            prev: block.get_prev().map(|prev| prev.get_hash().to_hex_string()),
+            deps: block.get_deps().iter().map(|dep| dep.get_hash().to_hex_string()).collect(),
+            payload_hash: catchain::utils::get_hash(block.get_payload().data()).to_hex_string(),
+            created_at_ms: block.get_creation_time()
+                .duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+            received_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+            messages,
+            rejected,
+        }
+    }
+}
+
+/// Catchain session node description in transcript
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct TranscriptNode {
+    pub public_key_hash: String,
+    pub adnl_id: String,
+}
+
+/// Propagation of blocks of one catchain source to the node: delays between block
+/// creation and delivery (clocks of the nodes are assumed to be synchronized)
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct SourcePropagation {
+    pub source_idx: u32,
+    pub blocks: usize,
+    pub avg_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+#[derive(serde::Serialize)]
+struct PropagationJson {
+    session_id: String,
+    finished: bool,
+    sources: Vec<SourcePropagation>,
+}
+
+#[derive(serde::Serialize)]
+struct TranscriptJson<'a> {
+    session_id: String,
+    shard: String,
+    master_cc_range: (u32, u32),
+    started_at: u64,
+    finished: bool,
+    nodes: &'a [TranscriptNode],
+    blocks_total: usize,
+    blocks_stored: usize,
+    blocks_offset: usize,
+    blocks: &'a [TranscriptBlock],
+}
+
+/// Block DAG of a catchain session (sources, dependencies, payload hashes and timestamps),
+/// in the order the blocks were delivered to the node. Exported in JSON, so that the
+/// ordering of messages in the session may be audited without access to the node DB.
+pub struct CatchainTranscript {
+    session_id: UInt256,
+    shard: ShardIdent,
+    master_cc_range: RangeInclusive<u32>,
+    started_at: u64,
+    nodes: Vec<TranscriptNode>,
+    blocks: Mutex<(usize, Vec<TranscriptBlock>)>,
+    finished: AtomicBool,
+}
+
+impl CatchainTranscript {
+    pub fn new(
+        session_id: UInt256,
+        shard: ShardIdent,
+        master_cc_range: RangeInclusive<u32>,
+        nodes: &[CatchainNode]
+    ) -> Self {
+        let nodes = nodes.iter().map(|node| TranscriptNode {
+            public_key_hash: UInt256::from(node.public_key.id().data()).to_hex_string(),
+            adnl_id: UInt256::from(node.adnl_id.data()).to_hex_string(),
+        }).collect();
+        Self {
+            session_id,
+            shard,
+            master_cc_range,
+            started_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            nodes,
+            blocks: Mutex::new((0, Vec::new())),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    pub fn session_id(&self) -> &UInt256 {
+        &self.session_id
+    }
+
+    pub fn add_block(&self, block: TranscriptBlock) {
+        let mut blocks = self.blocks.lock().unwrap();
+        blocks.0 += 1;
+        if blocks.1.len() < MAX_TRANSCRIPT_BLOCKS {
+            blocks.1.push(block);
+        }
+    }
+
+    pub fn blocks_count(&self) -> usize {
+        self.blocks.lock().unwrap().0
+    }
+
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    /// Per source propagation delays of the stored blocks, ordered by source index
+    pub fn propagation(&self) -> Vec<SourcePropagation> {
+        let blocks = self.blocks.lock().unwrap();
+        let mut sources: Vec<SourcePropagation> = Vec::new();
+        let mut total_delays = Vec::new();
+        for block in blocks.1.iter() {
+            let idx = match sources.binary_search_by_key(&block.source_idx, |s| s.source_idx) {
+                Ok(idx) => idx,
+                Err(idx) => {
+                    let source = SourcePropagation { source_idx: block.source_idx, ..Default::default() };
+                    sources.insert(idx, source);
+                    total_delays.insert(idx, 0);
+                    idx
+                }
+            };
+            let delay = block.received_at_ms.saturating_sub(block.created_at_ms);
+            sources[idx].blocks += 1;
+            sources[idx].max_delay_ms = sources[idx].max_delay_ms.max(delay);
+            total_delays[idx] += delay;
+        }
+        for (source, total) in sources.iter_mut().zip(total_delays) {
+            source.avg_delay_ms = total / source.blocks as u64;
+        }
+        sources
+    }
+
+    /// Transcript with a page of stored blocks: at most `limit` (up to 
+    /// `MAX_TRANSCRIPT_PAGE_BLOCKS`) blocks starting from `offset`
+    pub fn to_json(&self, offset: usize, limit: usize) -> Result<String> {
+        let blocks = self.blocks.lock().unwrap();
+        let start = offset.min(blocks.1.len());
+        let end = start + limit.min(MAX_TRANSCRIPT_PAGE_BLOCKS).min(blocks.1.len() - start);
+        let json = TranscriptJson {
+            session_id: self.session_id.to_hex_string(),
+            shard: self.shard.to_string(),
+            master_cc_range: (*self.master_cc_range.start(), *self.master_cc_range.end()),
+            started_at: self.started_at,
+            finished: self.is_finished(),
+            nodes: &self.nodes,
+            blocks_total: blocks.0,
+            blocks_stored: blocks.1.len(),
+            blocks_offset: start,
+            blocks: &blocks.1[start..end],
+        };
+        Ok(serde_json::to_string(&json)?)
+    }
+}
+
+/// Transcripts of the last `capacity` catchain sessions
+pub struct CatchainTranscriptStore {
+    capacity: usize,
+    transcripts: Mutex<VecDeque<Arc<CatchainTranscript>>>,
+}
+
+impl CatchainTranscriptStore {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            transcripts: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn add(&self, transcript: Arc<CatchainTranscript>) {
+        if !self.is_enabled() {
+            return
+        }
+        let mut transcripts = self.transcripts.lock().unwrap();
+        transcripts.retain(|t| t.session_id() != transcript.session_id());
+        while transcripts.len() >= self.capacity {
+            transcripts.pop_front();
+        }
+        transcripts.push_back(transcript);
+    }
+
+    pub fn get(&self, session_id: &UInt256) -> Option<Arc<CatchainTranscript>> {
+        self.transcripts.lock().unwrap().iter().find(|t| t.session_id() == session_id).cloned()
+    }
+
+    /// (session id, finished, blocks count) of all stored transcripts
+    pub fn list(&self) -> Vec<(UInt256, bool, usize)> {
+        self.transcripts.lock().unwrap().iter()
+            .map(|t| (t.session_id().clone(), t.is_finished(), t.blocks_count()))
+            .collect()
+    }
+
+    /// Blocks propagation (in JSON) for all stored transcripts, for monitoring of REMP health
+    pub fn export_propagation(&self) -> Result<String> {
+        let sessions = self.transcripts.lock().unwrap().iter()
+            .map(|t| PropagationJson {
+                session_id: t.session_id().to_hex_string(),
+                finished: t.is_finished(),
+                sources: t.propagation(),
+            })
+            .collect::<Vec<_>>();
+        Ok(serde_json::to_string(&sessions)?)
+    }
+
+    pub fn export(&self, session_id: &UInt256, offset: usize, limit: usize) -> Result<String> {
+        self.get(session_id)
+            .ok_or_else(|| error!("No transcript for catchain session {:x}", session_id))?
+            .to_json(offset, limit)
+    }
+}
@@ -15,7 +15,7 @@
 
 use crate::{
     engine_traits::EngineOperations,
-    ext_messages::EXT_MESSAGES_TRACE_TARGET,
+    ext_messages::{DeferredRempMessage, EXT_MESSAGES_TRACE_TARGET},
     rng::random::secure_256_bits,
     shard_state::ShardStateStuff,
     types::{
@@ -31,7 +31,8 @@ use crate::{
     },
     validator::{
         BlockCandidate, CollatorSettings, McData,
-        out_msg_queue::{MsgQueueManager, OutMsgQueueInfoStuff}, 
+        deferred_dispatch::{deferred_sub_status, round_robin, DeferredDispatch},
+        out_msg_queue::{MsgQueueManager, OutMsgQueueInfoStuff, OutQueueCheckpoint}, 
         validator_utils::calc_subset_for_masterchain
     },
     CHECK,
@@ -41,7 +42,7 @@ use futures::try_join;
 use rand::Rng;
 use std::{
     cmp::{max, min},
-    collections::{BinaryHeap, HashMap, HashSet},
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet},
     ops::Deref,
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
@@ -74,7 +75,6 @@ pub const SPLIT_MERGE_INTERVAL: u32 = 100;     // split/merge is enabled during
 
 pub const DEFAULT_COLLATE_TIMEOUT: u32 = 2000;
 
-pub const REMP_CUTOFF_LIMIT: u32 = 100;   // percent that remp messages can fill in a block
 
 struct ImportedData {
     mc_state: Arc<ShardStateStuff>,
@@ -218,6 +218,7 @@ struct CollatorData {
     accepted_remp_messages: Vec<UInt256>,
     rejected_remp_messages: Vec<(UInt256, String)>,
     ignored_remp_messages: Vec<UInt256>,
+    deferred_remp_messages: Vec<(UInt256, DeferredRempMessage)>,
     usage_tree: UsageTree,
     imported_visited: HashSet<UInt256>,
 
@@ -287,6 +288,7 @@ impl CollatorData {
             accepted_remp_messages: Default::default(),
             rejected_remp_messages: Default::default(),
             ignored_remp_messages: Default::default(),
+            deferred_remp_messages: Default::default(),
             usage_tree,
             imported_visited: HashSet::new(),
             gen_utime,
@@ -635,19 +637,29 @@ impl CollatorData {
         self.rejected_remp_messages = rejected;
         self.ignored_remp_messages = ignored;
     }
+
+    fn withdraw_deferred_remp_messages(&mut self) -> Vec<(UInt256, DeferredRempMessage)> {
+        std::mem::take(&mut self.deferred_remp_messages)
+    }
 }
 
 struct ExecutionManager {
     changed_accounts: HashMap<
         AccountId, 
         (
-            tokio::sync::mpsc::UnboundedSender<Arc<AsyncMessage>>,
+            tokio::sync::mpsc::UnboundedSender<(u64, Arc<AsyncMessage>)>,
             tokio::task::JoinHandle<Result<ShardAccountStuff>>
         )
     >,
     
-    receive_tr: tokio::sync::mpsc::UnboundedReceiver<Option<(Arc<AsyncMessage>, Result<Transaction>)>>,
-    wait_tr: Arc<Wait<(Arc<AsyncMessage>, Result<Transaction>)>>,
+    receive_tr: tokio::sync::mpsc::UnboundedReceiver<Option<(u64, Arc<AsyncMessage>, Result<Transaction>)>>,
+    wait_tr: Arc<Wait<(u64, Arc<AsyncMessage>, Result<Transaction>)>>,
+    // executed transactions which are not finalized yet, by order of their messages
+    finished: BTreeMap<u64, (Arc<AsyncMessage>, Result<Transaction>)>,
+    // order number of the next message to execute
+    next_dispatched: u64,
+    // order number of the next transaction to finalize
+    next_finalized: u64,
     max_collate_threads: usize,
     libraries: Libraries,
     gen_utime: u32,
@@ -690,6 +702,9 @@ impl ExecutionManager {
             changed_accounts: HashMap::new(),
             receive_tr,
             wait_tr,
+            finished: BTreeMap::new(),
+            next_dispatched: 0,
+            next_finalized: 0,
             max_collate_threads,
             libraries,
             config,
@@ -709,18 +724,21 @@ impl ExecutionManager {
     // waits and finalizes all parallel tasks
     pub async fn wait_transactions(&mut self, collator_data: &mut CollatorData) -> Result<()> {
         log::trace!("{}: wait_transactions", self.collated_block_descr);
-        while self.wait_tr.count() > 0 {
-            self.wait_transaction(collator_data).await?;
+        while self.next_finalized < self.next_dispatched {
+            self.finalize_next_transaction(collator_data).await?;
         }
         self.min_lt.fetch_max(self.max_lt.load(Ordering::Relaxed), Ordering::Relaxed);
         Ok(())
     }
 
-    // checks if a number of parallel transactilns is not too big, waits and finalizes some if needed.
+    // checks if a number of parallel transactions is not too big, waits and finalizes some if needed.
+    // Transactions are finalized in order of their messages, and no more than `max_collate_threads`
+    // of them are left unfinalized, even if more are ready. So block limits are checked at the same
+    // points, and the block doesn't depend on which account's transaction finished first.
     pub async fn check_parallel_transactions(&mut self, collator_data: &mut CollatorData) -> Result<()> {
         log::trace!("{}: check_parallel_transactions", self.collated_block_descr);
-        if self.wait_tr.count() >= self.max_collate_threads {
-            self.wait_transaction(collator_data).await?;
+        while self.next_dispatched - self.next_finalized >= self.max_collate_threads as u64 {
+            self.finalize_next_transaction(collator_data).await?;
         }
         Ok(())
     }
@@ -735,7 +753,7 @@ impl ExecutionManager {
         log::trace!("{}: execute (adding into queue): {:x}", self.collated_block_descr, account_id);
         if let Some((sender, _handle)) = self.changed_accounts.get(&account_id) {
             self.wait_tr.request();
-            sender.send(Arc::new(msg))?;
+            sender.send((self.next_dispatched, Arc::new(msg)))?;
         } else {
             let shard_acc = if let Some(shard_acc) = prev_data.accounts().account(&account_id)? {
                 shard_acc
@@ -750,9 +768,10 @@ impl ExecutionManager {
                 shard_acc,
             )?;
             self.wait_tr.request();
-            sender.send(Arc::new(msg))?;
+            sender.send((self.next_dispatched, Arc::new(msg)))?;
             self.changed_accounts.insert(account_id, (sender, handle));
         }
+        self.next_dispatched += 1;
 
         self.check_parallel_transactions(collator_data).await?;
 
@@ -763,7 +782,7 @@ impl ExecutionManager {
         &self,
         account_addr: AccountId,
         shard_acc: ShardAccount,
-    ) -> Result<(tokio::sync::mpsc::UnboundedSender<Arc<AsyncMessage>>, tokio::task::JoinHandle<Result<ShardAccountStuff>>)> {
+    ) -> Result<(tokio::sync::mpsc::UnboundedSender<(u64, Arc<AsyncMessage>)>, tokio::task::JoinHandle<Result<ShardAccountStuff>>)> {
         log::trace!("{}: start_account_job: {:x}", self.collated_block_descr, account_addr);
 
         let mut shard_acc = ShardAccountStuff::new(
@@ -785,9 +804,9 @@ impl ExecutionManager {
         let min_lt = self.min_lt.clone();
         let max_lt = self.max_lt.clone();
         let libraries = self.libraries.clone().inner();
-        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<Arc<AsyncMessage>>();
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<(u64, Arc<AsyncMessage>)>();
         let handle = tokio::spawn(async move {
-            while let Some(new_msg) = receiver.recv().await {
+            while let Some((order, new_msg)) = receiver.recv().await {
                 log::trace!("{}: new message for {:x}", collated_block_descr, shard_acc.account_addr());
                 let config = config.clone(); // TODO: use Arc
 
@@ -832,7 +851,7 @@ impl ExecutionManager {
                     collated_block_descr, shard_acc.account_addr(), duration);
 
                 max_lt.fetch_max(shard_acc.lt().load(Ordering::Relaxed), Ordering::Relaxed);
-                wait_tr.respond(Some((new_msg, transaction_res)));
+                wait_tr.respond(Some((order, new_msg, transaction_res)));
             }
             Ok(shard_acc)
         });
@@ -865,13 +884,27 @@ impl ExecutionManager {
         executor.execute_with_libs_and_params(msg_opt, account_root, params)
     }
 
-    async fn wait_transaction(&mut self, collator_data: &mut CollatorData) -> Result<()> {
+    // waits for the transaction of the next message in order, results of other messages are kept
+    async fn finalize_next_transaction(&mut self, collator_data: &mut CollatorData) -> Result<()> {
+        let (new_msg, transaction_res) = loop {
+            if let Some(finished) = self.finished.remove(&self.next_finalized) {
+                break finished
+            }
+            self.wait_transaction().await?;
+        };
+        self.next_finalized += 1;
+        self.finalize_transaction(new_msg, transaction_res, collator_data)
+    }
+
+    async fn wait_transaction(&mut self) -> Result<()> {
         log::trace!("{}: wait_transaction", self.collated_block_descr);
-        let wait_op = self.wait_tr.wait(&mut self.receive_tr, false).await;
-        if let Some(Some((new_msg, transaction_res))) = wait_op {
-            self.finalize_transaction(new_msg, transaction_res, collator_data)?;
+        match self.wait_tr.wait(&mut self.receive_tr, false).await {
+            Some(Some((order, new_msg, transaction_res))) => {
+                self.finished.insert(order, (new_msg, transaction_res));
+                Ok(())
+            }
+            _ => fail!("{}: transaction of message {} is lost", self.collated_block_descr, self.next_finalized)
         }
-        Ok(())
     }
 
     fn finalize_transaction(
@@ -960,6 +993,23 @@ impl ExecutionManager {
     }
 }
 
+/// Would-be block of a dry-run collation (see `CollatorSettings::dry_run`)
+pub struct CollationDryRunReport {
+    pub block_id: BlockIdExt,
+    pub size: usize,
+    pub gas_used: u32,
+    pub duration_ms: u32,
+    pub in_msg_count: usize,
+    pub out_msg_count: usize,
+    pub execute_count: usize,
+    pub dequeue_count: usize,
+    pub enqueue_count: usize,
+    pub transit_count: usize,
+    pub remp_accepted: Vec<UInt256>,
+    pub remp_rejected: Vec<(UInt256, String)>,
+    pub remp_ignored: Vec<UInt256>,
+}
+
 pub struct Collator {
     engine: Arc<dyn EngineOperations>,
     shard: ShardIdent,
@@ -1079,7 +1129,20 @@ impl Collator {
         })
     }
 
-    pub async fn collate(mut self) -> Result<(BlockCandidate, ShardStateUnsplit)> {
+    pub async fn collate(self) -> Result<(BlockCandidate, ShardStateUnsplit)> {
+        let (candidate, state, _) = self.collate_ex().await?;
+        Ok((candidate, state))
+    }
+
+    /// Collates block without any side effects: statuses of external and REMP messages
+    /// are not reported, the candidate is not sent anywhere, only its stats are returned
+    pub async fn collate_dry_run(mut self) -> Result<CollationDryRunReport> {
+        self.collator_settings.dry_run = true;
+        let (_, _, report) = self.collate_ex().await?;
+        Ok(report)
+    }
+
+    async fn collate_ex(mut self) -> Result<(BlockCandidate, ShardStateUnsplit, CollationDryRunReport)> {
         log::info!(
             "{}: COLLATE min_mc_seqno = {}, prev_blocks_ids: {} {}",
             self.collated_block_descr,
@@ -1155,6 +1218,26 @@ impl Collator {
             candidate.block_id,
         );
 
+        let (remp_accepted, remp_rejected, remp_ignored) = collator_data.withdraw_remp_msg_statuses();
+        let report = CollationDryRunReport {
+            block_id: candidate.block_id.clone(),
+            size: candidate.data.len(),
+            gas_used: collator_data.block_limit_status.gas_used(),
+            duration_ms: duration,
+            in_msg_count: collator_data.in_msg_count,
+            out_msg_count: collator_data.out_msg_count,
+            execute_count: collator_data.execute_count,
+            dequeue_count: collator_data.dequeue_count,
+            enqueue_count: collator_data.enqueue_count,
+            transit_count: collator_data.transit_count,
+            remp_accepted,
+            remp_rejected,
+            remp_ignored,
+        };
+        if self.collator_settings.dry_run {
+            return Ok((candidate, state, report))
+        }
+
         #[cfg(feature = "log_metrics")]
         report_collation_metrics(
             &self.shard,
@@ -1179,7 +1262,7 @@ impl Collator {
             collator_data.block_limit_status.gas_used()
         );
 
-        Ok((candidate, state))
+        Ok((candidate, state, report))
     }
 
     async fn import_data(&self) -> Result<ImportedData> {
@@ -1241,7 +1324,14 @@ impl Collator {
         self.check_stop_flag()?;
 
         let now = self.init_utime(&mc_data, &prev_data)?;
-        let config = BlockchainConfig::with_config(mc_data.config().clone())?;
+        let cache = self.engine.collator_work_cache();
+        let config_hash = mc_data.config().serialize()?.repr_hash();
+        let config = match cache.and_then(|cache| cache.config(&self.shard, &config_hash)) {
+            Some(config) => config,
+            None => BlockchainConfig::with_config(
+                self.engine.config_params_with_overrides(mc_data.config(), self.shard.workchain_id())?
+            )?
+        };
         let mut collator_data = CollatorData::new(
             now,
             config,
@@ -1306,6 +1396,10 @@ impl Collator {
         log::debug!("{}: do_collate", self.collated_block_descr);
 
         let remp_messages = if is_remp_enabled(self.engine.clone(), mc_data.config()) {
+            if let Ok((count, bytes)) = self.engine.get_remp_queue_depth(&self.shard) {
+                log::debug!("{}: REMP queue depth: {} messages, {} bytes",
+                    self.collated_block_descr, count, bytes);
+            }
             Some(self.engine.get_remp_messages(&self.shard)?)
         } else {
             None
@@ -1351,9 +1445,13 @@ impl Collator {
             self.rand_seed.clone(),
             #[cfg(feature = "signature_with_id")]
             mc_data.state().state()?.global_id(), // Use network global ID as signature ID
-            mc_data.libraries()?.clone(),
+            self.libraries(mc_data)?,
             collator_data.config.clone(),
-            self.engine.collator_config().max_collate_threads as usize,
+            if self.engine.collator_config().parallel_execution {
+                self.engine.collator_config().max_collate_threads as usize
+            } else {
+                1
+            },
             self.collated_block_descr.clone(),
             self.debug,
         )?;
@@ -1467,6 +1565,7 @@ impl Collator {
         // If block is empty - stop collation to try one more time (may be there are some new messages)
         let cc = self.engine.collator_config();
         if !self.after_split &&
+           !self.collator_settings.dry_run &&
            cc.retry_if_empty &&
            (self.started.elapsed().as_millis() as u32) < cc.finalize_empty_after_ms &&
            collator_data.dequeue_count == 0 &&
@@ -1540,7 +1639,14 @@ impl Collator {
         let mut prev_states = vec!();
         let mut prev_ext_blocks_refs = vec![];
         for (i, prev_id) in self.prev_blocks_ids.iter().enumerate() {
-            let prev_state = self.engine.clone().wait_state(prev_id, Some(1_000), true).await?;
+            let cached = self.engine.collator_work_cache().and_then(|cache| cache.state(&self.shard, prev_id));
+            let prev_state = match cached {
+                Some(prev_state) => {
+                    log::trace!("{}: prev state {} is taken from collator cache", self.collated_block_descr, prev_id);
+                    prev_state
+                }
+                None => self.engine.clone().wait_state(prev_id, Some(1_000), true).await?
+            };
 
             let end_lt = prev_state.state()?.gen_lt();
             let ext_block_ref = ExtBlkRef {
@@ -1642,6 +1748,50 @@ impl Collator {
         Ok(())
     }
 
+    // libraries of the previous collation have their cells loaded already
+    fn libraries(&self, mc_data: &McData) -> Result<Libraries> {
+        let libraries = mc_data.libraries()?;
+        if let Some(cache) = self.engine.collator_work_cache() {
+            if let Some(cached) = cache.libraries(&self.shard, &libraries.serialize()?.repr_hash()) {
+                return Ok(cached)
+            }
+        }
+        Ok(libraries.clone())
+    }
+
+    // puts data of the collation to the cache for the next collation of the shard:
+    // prev states (for the case the same block is collated again) and the new state
+    // built from the prev states' cells (for the case the candidate is accepted)
+    fn update_work_cache(
+        &self,
+        mc_data: &McData,
+        prev_data: &PrevData,
+        collator_data: &CollatorData,
+        block_id: &BlockIdExt,
+        new_state_root: Cell,
+        libraries: Libraries,
+    ) -> Result<()> {
+        let cache = match self.engine.collator_work_cache() {
+            Some(cache) => cache,
+            None => return Ok(())
+        };
+        let mut states = prev_data.pure_states.clone();
+        states.push(ShardStateStuff::from_state_root_cell(
+            block_id.clone(),
+            new_state_root,
+            #[cfg(feature = "telemetry")]
+            self.engine.engine_telemetry(),
+            self.engine.engine_allocated()
+        )?);
+        cache.update(
+            &self.shard,
+            states,
+            (mc_data.config().serialize()?.repr_hash(), collator_data.config.clone()),
+            (mc_data.libraries()?.serialize()?.repr_hash(), libraries),
+        );
+        Ok(())
+    }
+
     // create usage tree and recreate prev states with usage tree
     fn create_usage_tree(
         &self, 
@@ -2270,6 +2420,24 @@ impl Collator {
     ) -> Result<()> {
         log::debug!("{}: process_inbound_internal_messages", self.collated_block_descr);
         let mut iter = output_queue_manager.merge_out_queue_iter(&self.shard)?;
+
+        // messages already processed in the prev state, which were walked through 
+        // by the previous collation attempt, are skipped
+        let prev_seqno = self.prev_blocks_ids.iter().map(|id| id.seq_no()).max().unwrap_or_default();
+        let neighbors = output_queue_manager.active_neighbors_ids();
+        let mut checkpoint = match self.engine.get_out_queue_checkpoint(&self.shard) {
+            Some(checkpoint) if checkpoint.is_valid_for(prev_seqno, &neighbors) => {
+                log::debug!("{}: inbound internal messages up to lt {} hash {:x} are skipped",
+                    self.collated_block_descr, checkpoint.lt, checkpoint.hash);
+                iter.skip_upto(checkpoint.lt, checkpoint.hash.clone());
+                collator_data.update_last_proc_int_msg((checkpoint.lt, checkpoint.hash.clone()))?;
+                Some((checkpoint.lt, checkpoint.hash))
+            }
+            _ => None
+        };
+        let mut checkpoint_moved = false;
+        let mut not_processed_found = false;
+
         while let Some(k_v) = iter.next() {
             let (key, enq, created_lt, block_id) = k_v?;
             log::trace!(
@@ -2282,7 +2450,12 @@ impl Collator {
                     "{}: message {:x} has been already processed by us before, skipping",
                     self.collated_block_descr, key.hash
                 );
+                if !not_processed_found {
+                    checkpoint = Some((created_lt, key.hash.clone()));
+                    checkpoint_moved = true;
+                }
             } else {
+                not_processed_found = true;
                 self.check_inbound_internal_message(&key, &enq, created_lt, block_id.shard())
                     .map_err(|err| error!("problem processing internal inbound message \
                         with hash {:x} : {}", key.hash, err))?;
@@ -2316,6 +2489,12 @@ impl Collator {
         }
         // all internal messages are processed
         collator_data.inbound_queues_empty = iter.next().is_none();
+        if let (Some((lt, hash)), true) = (checkpoint, checkpoint_moved) {
+            self.engine.set_out_queue_checkpoint(
+                &self.shard, 
+                OutQueueCheckpoint { prev_seqno, neighbors, lt, hash }
+            );
+        }
         Ok(())
     }
 
@@ -2361,6 +2540,7 @@ impl Collator {
         }
         let finish_time_ms = self.get_external_messages_finish_time_micros();
         log::debug!("{}: process_inbound_external_messages", self.collated_block_descr);
+        let mut dispatch = DeferredDispatch::new(self.engine.collator_config().max_ext_messages_per_account);
         for (msg, msg_id) in self.engine.get_external_messages_iterator(self.shard.clone(), finish_time_ms) {
             let header = msg.ext_in_header()
                 .ok_or_else(|| error!("message {:x} is not external inbound message", msg_id))?;
@@ -2374,10 +2554,16 @@ impl Collator {
                         self.collated_block_descr);
                     break
                 }
-                log::debug!("{}: message {:x} sent to execution", self.collated_block_descr, msg_id);
                 let (_, account_id) = header.dst.extract_std_address(true)?;
-                let msg = AsyncMessage::Ext(msg.deref().clone(), msg_id);
-                exec_manager.execute(account_id, msg, prev_data, collator_data).await?;
+                if let Some(position) = dispatch.dispatch(&account_id, &msg_id) {
+                    // message stays in the pool and will be processed in the next blocks
+                    log::debug!("{}: message {:x} is deferred: {}",
+                        self.collated_block_descr, msg_id, deferred_sub_status(position));
+                } else {
+                    log::debug!("{}: message {:x} sent to execution", self.collated_block_descr, msg_id);
+                    let msg = AsyncMessage::Ext(msg.deref().clone(), msg_id);
+                    exec_manager.execute(account_id, msg, prev_data, collator_data).await?;
+                }
             } else {
                 // usually node collates more than one shard, the message can belong another one,
                 // so we can't postpone it
@@ -2387,8 +2573,14 @@ impl Collator {
             self.check_stop_flag()?;
         }
         exec_manager.wait_transactions(collator_data).await?;
+        if dispatch.deferred_count() > 0 {
+            log::debug!("{}: {} external messages are deferred to the next blocks",
+                self.collated_block_descr, dispatch.deferred_count());
+        }
         let (accepted, rejected) = collator_data.withdraw_ext_msg_statuses();
-        self.engine.complete_external_messages(rejected, accepted)?;
+        if !self.collator_settings.dry_run {
+            self.engine.complete_external_messages(rejected, accepted)?;
+        }
         Ok(())
     }
 
@@ -2401,22 +2593,52 @@ impl Collator {
     ) -> Result<usize> {
         log::trace!("{}: process_remp_messages ({}pcs)", self.collated_block_descr, remp_messages.len());
 
+        // validator's own service messages go first, then messages deferred by 
+        // the previous collation, in their queues order
         remp_messages.sort_by_cached_key(|(_, id)| {
-            calc_remp_msg_ordering_hash(&id, prev_data.pure_states.iter().map(|s| s.block_id()))
+            let deferred_position = self.engine.get_remp_deferred_message(id)
+                .map_or(u32::MAX, |deferred| deferred.position);
+            (
+                !self.engine.is_remp_service_message(id),
+                deferred_position,
+                calc_remp_msg_ordering_hash(&id, prev_data.pure_states.iter().map(|s| s.block_id()))
+            )
         });
         log::trace!("{}: process_remp_messages: sorted {} messages", self.collated_block_descr, remp_messages.len());
 
         let mut ignored = vec!();
         let mut ignore = false;
+
+        // service messages keep going first, the others are taken by destination accounts 
+        // in turn, so one account with a lot of messages can't crowd out the others
+        let mut service = Vec::new();
+        let mut others = Vec::new();
         for (msg, id) in remp_messages.drain(..) {
+            let dst = match msg.ext_in_header() {
+                Some(header) => header.dst.clone(),
+                None => {
+                    log::warn!("{}: process_remp_messages: ignored message {:x}: \
+                        it is not external inbound message", self.collated_block_descr, id);
+                    ignored.push(id);
+                    continue
+                }
+            };
+            if self.engine.is_remp_service_message(&id) {
+                service.push((dst, (msg, id)));
+            } else {
+                others.push((dst, (msg, id)));
+            }
+        }
+
+        let cc = self.engine.collator_config();
+        let mut dispatch = DeferredDispatch::new(cc.max_ext_messages_per_account);
+        for (dst, (msg, id)) in service.into_iter().chain(round_robin(others)) {
             if ignore {
                 ignored.push(id);
                 continue;
             }
-            let header = msg.ext_in_header().ok_or_else(|| error!("remp message {:x} \
-                is not external inbound message", id))?;
-            if self.shard.contains_address(&header.dst)? {
-                if !collator_data.block_limit_status.fits_normal(REMP_CUTOFF_LIMIT) {
+            if self.shard.contains_address(&dst)? {
+                if !collator_data.block_limit_status.fits_normal(cc.remp_block_fill_percent) {
                     log::trace!("{}: block is loaded enough, stop processing remp messages", self.collated_block_descr);
                     ignored.push(id);
                     ignore = true;
@@ -2426,15 +2648,27 @@ impl Collator {
                     ignored.push(id);
                     ignore = true;
                 } else {
-                    let (_, account_id) = header.dst.extract_std_address(true)?;
-                    log::trace!("{}: remp message {:x} sent to execution", self.collated_block_descr, id);
-                    let msg = AsyncMessage::Ext(msg.deref().clone(), id);
-                    exec_manager.execute(account_id, msg, prev_data, collator_data).await?;
+                    let (_, account_id) = dst.extract_std_address(true)?;
+                    // service messages are not limited per account
+                    let deferred = if self.engine.is_remp_service_message(&id) {
+                        None
+                    } else {
+                        dispatch.dispatch(&account_id, &id)
+                    };
+                    if let Some(position) = deferred {
+                        log::trace!("{}: remp message {:x} is deferred: {}",
+                            self.collated_block_descr, id, deferred_sub_status(position));
+                        ignored.push(id);
+                    } else {
+                        log::trace!("{}: remp message {:x} sent to execution", self.collated_block_descr, id);
+                        let msg = AsyncMessage::Ext(msg.deref().clone(), id);
+                        exec_manager.execute(account_id, msg, prev_data, collator_data).await?;
+                    }
                 }
             } else {
                 log::warn!(
                     "{}: process_remp_messages: ignored message {:x} for another shard {}",
-                    self.collated_block_descr, id, header.dst
+                    self.collated_block_descr, id, dst
                 );
                 ignored.push(id);
             }
@@ -2445,6 +2679,12 @@ impl Collator {
         let processed = accepted.len() + rejected.len();
         let accepted = accepted.into_iter().map(|(id, _)| id).collect();
         collator_data.set_remp_msg_statuses(accepted, rejected, ignored);
+        let mut deferred = Vec::new();
+        for (id, account_id, position, queue_len) in dispatch.withdraw_deferred() {
+            let account = UInt256::construct_from(&mut account_id.clone())?;
+            deferred.push((id, DeferredRempMessage { shard: self.shard.clone(), account, position, queue_len }));
+        }
+        collator_data.deferred_remp_messages = deferred;
         Ok(processed)
     }
 
@@ -2831,6 +3071,20 @@ impl Collator {
         extra.rand_seed = self.rand_seed.clone();
         extra.created_by = self.created_by.clone();
 
+        // new state for the collator cache is built from pure cells of the prev state
+        let new_state_root = match self.engine.collator_work_cache() {
+            Some(_) if !self.collator_settings.dry_run => {
+                match state_update.apply_for(&prev_data.state_root) {
+                    Ok(root) => Some(root),
+                    Err(e) => {
+                        log::warn!("{}: can't build new state for collator cache: {}", self.collated_block_descr, e);
+                        None
+                    }
+                }
+            }
+            _ => None
+        };
+
         // construct block
         let new_block = Block::with_out_queue_updates(
             mc_data.state().state()?.global_id(),
@@ -2857,9 +3111,18 @@ impl Collator {
         // }
         // !!!! DEBUG !!!!
 
-        if is_remp_enabled(self.engine.clone(), mc_data.config()) {
+        if let Some(new_state_root) = new_state_root {
+            self.update_work_cache(
+                mc_data, prev_data, collator_data, &block_id, new_state_root, exec_manager.libraries.clone()
+            )?;
+        }
+
+        // statuses of dry-run collation are left for its report
+        if is_remp_enabled(self.engine.clone(), mc_data.config()) && !self.collator_settings.dry_run {
             let (accepted, rejected, ignored) = collator_data.withdraw_remp_msg_statuses();
             self.engine.finalize_remp_messages(block_id.clone(), accepted, rejected, ignored)?;
+            let deferred = collator_data.withdraw_deferred_remp_messages();
+            self.engine.set_remp_deferred_messages(&self.shard, deferred)?;
         }
 
         self.check_stop_flag()?;
@@ -0,0 +1,132 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::shard_state::ShardStateStuff;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, atomic::{AtomicU64, Ordering}},
+};
+use ton_block::{BlockIdExt, Libraries, ShardIdent};
+use ton_executor::BlockchainConfig;
+use ton_types::UInt256;
+
+#[cfg(test)]
+#[path = "tests/test_collator_cache.rs"]
+mod tests;
+
+/// Data kept between consecutive collations of a shard. The next collation takes
+/// prev states (with account cells already loaded by the previous collation), parsed
+/// config and libraries from the cache instead of reading them from the DB again.
+/// Collator puts the state of its own candidate to the cache, so if the candidate
+/// is accepted the next block is collated on top of it without loading the state.
+/// Entries are replaced on every collation of the shard, so states which are not
+/// prev for the last collation (e.g. after another collator's candidate is accepted)
+/// are dropped; config and libraries are replaced when their root hash is changed.
+#[derive(Default)]
+pub struct CollatorWorkCache {
+    shards: Mutex<HashMap<ShardIdent, ShardWorkCache>>,
+    state_hits: AtomicU64,
+    state_misses: AtomicU64,
+    config_hits: AtomicU64,
+    config_misses: AtomicU64,
+}
+
+#[derive(Default)]
+struct ShardWorkCache {
+    states: Vec<Arc<ShardStateStuff>>,
+    // root hash of masterchain config params and the config with workchain overrides applied
+    config: Option<(UInt256, BlockchainConfig)>,
+    libraries: Option<(UInt256, Libraries)>,
+}
+
+/// Cache hits and misses since the node start
+#[derive(Debug, Default, PartialEq)]
+pub struct CollatorWorkCacheStats {
+    pub state_hits: u64,
+    pub state_misses: u64,
+    pub config_hits: u64,
+    pub config_misses: u64,
+}
+
+impl CollatorWorkCache {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self, shard: &ShardIdent, block_id: &BlockIdExt) -> Option<Arc<ShardStateStuff>> {
+        let state = self.shards.lock().unwrap().get(shard).and_then(
+            |cache| cache.states.iter().find(|state| state.block_id() == block_id).cloned()
+        );
+        if state.is_some() {
+            self.state_hits.fetch_add(1, Ordering::Relaxed);
+            metrics::increment_counter!("collator_cache_state_hits");
+        } else {
+            self.state_misses.fetch_add(1, Ordering::Relaxed);
+            metrics::increment_counter!("collator_cache_state_misses");
+        }
+        state
+    }
+
+    pub fn config(&self, shard: &ShardIdent, config_hash: &UInt256) -> Option<BlockchainConfig> {
+        let config = self.shards.lock().unwrap().get(shard).and_then(|cache| match &cache.config {
+            Some((hash, config)) if hash == config_hash => Some(config.clone()),
+            _ => None
+        });
+        if config.is_some() {
+            self.config_hits.fetch_add(1, Ordering::Relaxed);
+            metrics::increment_counter!("collator_cache_config_hits");
+        } else {
+            self.config_misses.fetch_add(1, Ordering::Relaxed);
+            metrics::increment_counter!("collator_cache_config_misses");
+        }
+        config
+    }
+
+    pub fn libraries(&self, shard: &ShardIdent, libraries_hash: &UInt256) -> Option<Libraries> {
+        self.shards.lock().unwrap().get(shard).and_then(|cache| match &cache.libraries {
+            Some((hash, libraries)) if hash == libraries_hash => Some(libraries.clone()),
+            _ => None
+        })
+    }
+
+    /// Replaces cached data of the shard by the data of the last collation.
+    /// Data of the parent or child shards (before split or merge) is dropped.
+    pub fn update(
+        &self,
+        shard: &ShardIdent,
+        states: Vec<Arc<ShardStateStuff>>,
+        config: (UInt256, BlockchainConfig),
+        libraries: (UInt256, Libraries),
+    ) {
+        log::trace!(
+            "collator cache {}: states {:?}", shard, states.iter().map(|s| s.block_id()).collect::<Vec<_>>()
+        );
+        let mut shards = self.shards.lock().unwrap();
+        shards.retain(|cached, _| !cached.intersect_with(shard));
+        shards.insert(shard.clone(), ShardWorkCache {
+            states,
+            config: Some(config),
+            libraries: Some(libraries),
+        });
+    }
+
+    pub fn stats(&self) -> CollatorWorkCacheStats {
+        CollatorWorkCacheStats {
+            state_hits: self.state_hits.load(Ordering::Relaxed),
+            state_misses: self.state_misses.load(Ordering::Relaxed),
+            config_hits: self.config_hits.load(Ordering::Relaxed),
+            config_misses: self.config_misses.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::validator::session_stats::SessionStats;
+use validator_session::SessionOptions;
+
+#[cfg(test)]
+#[path = "tests/test_consensus_stats.rs"]
+mod tests;
+
+// Suggestions are not made for sessions with less rounds
+const MIN_ROUNDS_FOR_SUGGESTIONS: u32 = 100;
+// Skipped rounds (percent) above which attempts are considered too short
+const SKIPPED_ROUNDS_THRESHOLD_PCT: u64 = 10;
+// Block payload (percent of limit) above which the limit is considered too tight
+const PAYLOAD_THRESHOLD_PCT: u64 = 90;
+
+/// Rounds statistics of a validator session: durations of rounds, sizes of committed
+/// blocks and count of skipped rounds (no block is committed in time)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConsensusStats {
+    pub rounds: u32,
+    pub skipped: u32,
+    pub total_round_ms: u64,
+    pub max_round_ms: u64,
+    pub total_payload: u64,
+    pub max_payload: u64,
+    last_round_end_ms: u64,
+}
+
+impl ConsensusStats {
+
+    pub fn new(now_ms: u64) -> Self {
+        Self { last_round_end_ms: now_ms, ..Default::default() }
+    }
+
+    fn round_finished(&mut self, now_ms: u64) {
+        let duration = now_ms.saturating_sub(self.last_round_end_ms);
+        self.last_round_end_ms = now_ms;
+        self.rounds += 1;
+        self.total_round_ms += duration;
+        self.max_round_ms = self.max_round_ms.max(duration);
+    }
+
+    pub fn round_committed(&mut self, now_ms: u64, payload: usize) {
+        self.round_finished(now_ms);
+        self.total_payload += payload as u64;
+        self.max_payload = self.max_payload.max(payload as u64);
+    }
+
+    pub fn round_skipped(&mut self, now_ms: u64) {
+        self.round_finished(now_ms);
+        self.skipped += 1;
+    }
+
+    pub fn avg_round_ms(&self) -> u64 {
+        self.total_round_ms.checked_div(self.rounds as u64).unwrap_or_default()
+    }
+
+    pub fn avg_payload(&self) -> u64 {
+        let committed = (self.rounds - self.skipped) as u64;
+        self.total_payload.checked_div(committed).unwrap_or_default()
+    }
+
+    /// Suggested adjustments of consensus config (param 29) options:
+    /// - `attempt_duration` is increased by half if too many rounds are skipped;
+    /// - `max_block_bytes` is increased by half if committed blocks come close to the limit.
+    pub fn suggestions(&self, opts: &SessionOptions) -> Vec<ConsensusSuggestion> {
+        let mut result = Vec::new();
+        if self.rounds < MIN_ROUNDS_FOR_SUGGESTIONS {
+            return result
+        }
+        if self.skipped as u64 * 100 > self.rounds as u64 * SKIPPED_ROUNDS_THRESHOLD_PCT {
+            let current = opts.round_attempt_duration.as_secs();
+            result.push(ConsensusSuggestion {
+                param: "attempt_duration",
+                current,
+                suggested: (current + current / 2).max(current + 1),
+            });
+        }
+        let max_block_bytes = opts.max_block_size as u64;
+        if self.max_payload * 100 > max_block_bytes * PAYLOAD_THRESHOLD_PCT {
+            result.push(ConsensusSuggestion {
+                param: "max_block_bytes",
+                current: max_block_bytes,
+                suggested: max_block_bytes + max_block_bytes / 2,
+            });
+        }
+        result
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsensusSuggestion {
+    pub param: &'static str,
+    pub current: u64,
+    pub suggested: u64,
+}
+
+/// Session statistics together with suggestions made for options of the session
+/// and work of the local validator in the session
+#[derive(Clone, Debug)]
+pub struct ConsensusReport {
+    pub stats: ConsensusStats,
+    pub suggestions: Vec<ConsensusSuggestion>,
+    pub session: SessionStats,
+}
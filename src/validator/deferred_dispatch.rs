@@ -0,0 +1,104 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{collections::{HashMap, VecDeque}, hash::Hash};
+use ton_types::{AccountId, UInt256};
+
+#[cfg(test)]
+#[path = "tests/test_deferred_dispatch.rs"]
+mod tests;
+
+/// Per-account dispatch of external messages within one collated block. At most `limit`
+/// messages are dispatched to an account, the rest are put to the account's deferred
+/// queue and are collated in the next blocks (in the queue order), instead of being
+/// executed one after another until the collation timeout is hit.
+pub struct DeferredDispatch {
+    limit: u32,
+    dispatched: HashMap<AccountId, u32>,
+    deferred: HashMap<AccountId, Vec<UInt256>>,
+}
+
+impl DeferredDispatch {
+
+    // limit 0 - messages are never deferred
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            dispatched: HashMap::new(),
+            deferred: HashMap::new(),
+        }
+    }
+
+    /// Returns None if the message may be dispatched, or its position (starting from 1)
+    /// in the account's deferred queue
+    pub fn dispatch(&mut self, account_id: &AccountId, msg_id: &UInt256) -> Option<u32> {
+        if self.limit == 0 {
+            return None
+        }
+        let dispatched = self.dispatched.entry(account_id.clone()).or_insert(0);
+        if *dispatched < self.limit {
+            *dispatched += 1;
+            return None
+        }
+        let queue = self.deferred.entry(account_id.clone()).or_default();
+        queue.push(msg_id.clone());
+        Some(queue.len() as u32)
+    }
+
+    pub fn deferred_count(&self) -> usize {
+        self.deferred.values().map(|queue| queue.len()).sum()
+    }
+
+    /// (message id, account id, position, account's queue length) of all deferred messages
+    pub fn withdraw_deferred(&mut self) -> Vec<(UInt256, AccountId, u32, u32)> {
+        let mut result = Vec::new();
+        for (account_id, queue) in self.deferred.drain() {
+            let len = queue.len() as u32;
+            for (i, msg_id) in queue.into_iter().enumerate() {
+                result.push((msg_id, account_id.clone(), i as u32 + 1, len));
+            }
+        }
+        result
+    }
+}
+
+/// Reorders messages so that destination accounts take turns: first messages of all accounts
+/// (in order of the accounts' first appearance), then the second ones and so on. Order of
+/// messages to one account is kept, so an account with a lot of messages can't crowd out
+/// the others when the block gets full.
+pub fn round_robin<K: Clone + Eq + Hash, T>(messages: Vec<(K, T)>) -> Vec<(K, T)> {
+    let total = messages.len();
+    let mut index = HashMap::new();
+    let mut queues: Vec<VecDeque<(K, T)>> = Vec::new();
+    for (key, msg) in messages {
+        let i = *index.entry(key.clone()).or_insert_with(|| {
+            queues.push(VecDeque::new());
+            queues.len() - 1
+        });
+        queues[i].push_back((key, msg));
+    }
+    let mut result = Vec::with_capacity(total);
+    while result.len() < total {
+        for queue in queues.iter_mut() {
+            if let Some(msg) = queue.pop_front() {
+                result.push(msg);
+            }
+        }
+    }
+    result
+}
+
+/// Sub-status of a message waiting in a deferred queue
+pub fn deferred_sub_status(position: u32) -> String {
+    format!("queued, position {}", position)
+}
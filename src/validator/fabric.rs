@@ -15,13 +15,20 @@ use std::{
     sync::Arc,
     time::SystemTime,
 };
-use super::validator_utils::{validator_query_candidate_to_validator_block_candidate, pairvec_to_cryptopair_vec, get_first_block_seqno_after_prevs};
+use super::validator_utils::{
+    validator_query_candidate_to_validator_block_candidate, pairvec_to_cryptopair_vec, 
+    get_first_block_seqno_after_prevs, compute_validator_set_cc
+};
 use crate::{
     collator_test_bundle::CollatorTestBundle, engine_traits::EngineOperations, 
-    validator::{CollatorSettings, validate_query::ValidateQuery, collator}, validating_utils::{fmt_next_block_descr_from_next_seqno, fmt_next_block_descr}
+    validator::{
+        CollatorSettings, validate_query::ValidateQuery, collator,
+        validation_trace::{ValidationReplayReport, ValidationTrace, VALIDATE_REPLAY_TRACE_TARGET}
+    },
+    validating_utils::{fmt_next_block_descr_from_next_seqno, fmt_next_block_descr}
 };
 use ton_block::{BlockIdExt, ShardIdent, ValidatorSet, Deserializable};
-use ton_types::{Result, UInt256};
+use ton_types::{error, Result, UInt256};
 use validator_session::{ValidatorBlockCandidate, BlockPayloadPtr, PublicKeyHash, PublicKey};
 
 #[allow(dead_code)]
@@ -77,7 +84,7 @@ pub async fn run_validate_query(
 
     let test_bundles_config = &engine.test_bundles_config().validator;
     let validator_result = if !test_bundles_config.is_enable() {
-        ValidateQuery::new(
+        let query = ValidateQuery::new(
             shard.clone(),
             min_masterchain_block_id.seq_no(),
             prev,
@@ -86,7 +93,11 @@ pub async fn run_validate_query(
             engine.clone(),
             false,
             true,
-        ).try_validate().await
+        );
+        match engine.validation_pool() {
+            Some(pool) => pool.run(query.try_validate()).await,
+            None => query.try_validate().await
+        }
     } else {
         let query = ValidateQuery::new(
             shard.clone(),
@@ -98,7 +109,10 @@ pub async fn run_validate_query(
             false,
             true,
         );
-        let validator_result = query.try_validate().await;
+        let validator_result = match engine.validation_pool() {
+            Some(pool) => pool.run(query.try_validate()).await,
+            None => query.try_validate().await
+        };
         if let Err(err) = &validator_result {
             let err_str = err.to_string();
             if test_bundles_config.need_to_build_for(&err_str) {
@@ -261,3 +275,121 @@ pub async fn run_collate_query (
         }
     }
 }
+
+// Collates block of the shard on top of the last applied masterchain state without 
+// sending the candidate anywhere and without reporting messages statuses
+pub async fn run_collate_dry_run(
+    shard: ShardIdent,
+    engine: Arc<dyn EngineOperations>,
+) -> Result<collator::CollationDryRunReport> {
+    let mc_state = engine.load_last_applied_mc_state().await?;
+    let mc_state_extra = mc_state.shard_state_extra()?;
+    let (prev, cc_seqno) = if shard.is_masterchain() {
+        (mc_state.block_id().clone(), mc_state_extra.validator_info.catchain_seqno)
+    } else {
+        let prev = mc_state.top_blocks(shard.workchain_id())?.into_iter()
+            .find(|id| id.shard() == &shard)
+            .ok_or_else(|| error!("Shard {} is not found in the last masterchain state {}", 
+                shard, mc_state.block_id()))?;
+        (prev, mc_state_extra.shards().calc_shard_cc_seqno(&shard)?)
+    };
+    let mut cc_seqno_delta = 0;
+    let nodes = compute_validator_set_cc(&mc_state, &shard, prev.seq_no() + 1, cc_seqno, &mut cc_seqno_delta)?;
+    let set = ValidatorSet::with_cc_seqno(0, 0, 0, cc_seqno_delta, nodes)?;
+
+    let collator = collator::Collator::new(
+        shard,
+        mc_state.block_id().seq_no(),
+        vec!(prev),
+        set,
+        UInt256::default(),
+        engine,
+        None,
+        CollatorSettings::default()
+    )?;
+    collator.collate_dry_run().await
+}
+
+// Re-runs validation of the block (or the block candidate of one of the current sessions,
+// including rejected ones) given by root hash against the stored states, with the trace 
+// of config and checked transactions
+pub async fn run_validate_replay(
+    root_hash: UInt256,
+    engine: Arc<dyn EngineOperations>,
+) -> Result<ValidationReplayReport> {
+    let (candidate, source) = match engine.find_full_block_id(&root_hash)? {
+        Some(block_id) => {
+            let handle = engine.load_block_handle(&block_id)?
+                .ok_or_else(|| error!("Cannot load handle for block {}", block_id))?;
+            let data = engine.load_block_raw(&handle).await?;
+            // collated data of applied blocks is not stored
+            let candidate = super::BlockCandidate {
+                block_id,
+                data,
+                collated_data: Vec::new(),
+                collated_file_hash: UInt256::default(),
+                created_by: UInt256::default(),
+            };
+            (candidate, "block_db")
+        }
+        None => {
+            let stored = engine.find_block_candidate(&root_hash)
+                .ok_or_else(|| error!("Neither block nor candidate {:x} is found", root_hash))?;
+            let data = stored.data.data().to_vec();
+            let info = ton_block::Block::construct_from_bytes(&data)?.read_info()?;
+            let candidate = super::BlockCandidate {
+                block_id: BlockIdExt::with_params(
+                    info.shard().clone(), info.seq_no(), stored.id.root_hash.clone(), stored.id.file_hash.clone()
+                ),
+                data,
+                collated_data: stored.collated_data.data().to_vec(),
+                collated_file_hash: stored.collated_file_hash.clone(),
+                created_by: UInt256::from(stored.public_key.pub_key()?),
+            };
+            (candidate, "candidate_db")
+        }
+    };
+
+    let block = ton_block::Block::construct_from_bytes(&candidate.data)?;
+    let info = block.read_info()?;
+    let prev = info.read_prev_ids()?;
+    let shard = candidate.block_id.shard().clone();
+    // validator set is computed the same way as it is checked by validation
+    let ref_mc_block_id = match info.read_master_ref()? {
+        Some(master_ref) => master_ref.master.master_block_id().1,
+        None => prev[0].clone()
+    };
+    let mc_state = engine.load_state(&ref_mc_block_id).await?;
+    let mc_state_extra = mc_state.shard_state_extra()?;
+    let cc_seqno = if shard.is_masterchain() {
+        mc_state_extra.validator_info.catchain_seqno
+    } else {
+        mc_state_extra.shards().calc_shard_cc_seqno(&shard)?
+    };
+    let mut cc_seqno_delta = 0;
+    let nodes = compute_validator_set_cc(&mc_state, &shard, info.seq_no(), cc_seqno, &mut cc_seqno_delta)?;
+    let set = ValidatorSet::with_cc_seqno(0, 0, 0, cc_seqno_delta, nodes)?;
+
+    let block_id = candidate.block_id.clone();
+    let trace = Arc::new(ValidationTrace::default());
+    let mut query = ValidateQuery::new(
+        shard,
+        info.min_ref_mc_seqno(),
+        prev,
+        candidate,
+        set,
+        engine.clone(),
+        false,
+        true,
+    );
+    query.set_trace(trace.clone());
+    log::info!(target: VALIDATE_REPLAY_TRACE_TARGET, "replaying validation of {} from {}", block_id, source);
+    let started = std::time::Instant::now();
+    let result = match engine.validation_pool() {
+        Some(pool) => pool.run(query.try_validate()).await,
+        None => query.try_validate().await
+    };
+    let duration_ms = started.elapsed().as_millis() as u64;
+    log::info!(target: VALIDATE_REPLAY_TRACE_TARGET, "validation of {} replayed: {:?}", block_id, result);
+    Ok(trace.report(block_id, source, duration_ms, result.err().map(|e| e.to_string())))
+}
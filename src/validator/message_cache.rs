@@ -13,14 +13,16 @@
 
 use std::{
     cmp::max, 
-    collections::HashSet,
+    collections::{HashSet, VecDeque},
     fmt, fmt::{Display, Formatter},
     ops::RangeInclusive,
-    sync::{Arc, atomic::{AtomicU32, Ordering, Ordering::Relaxed}},
-    time::{Duration, SystemTime}
+    str::FromStr,
+    sync::{Arc, atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering, Ordering::Relaxed}},
+    time::{Duration, SystemTime, UNIX_EPOCH}
 };
 use lockfree::map::Map;
 use dashmap::{DashMap, DashSet};
+use tokio::sync::{mpsc, watch};
 
 #[cfg(feature = "telemetry")]
 use adnl::telemetry::Metric;
@@ -28,8 +30,8 @@ use adnl::telemetry::Metric;
 use crate::{
     engine_traits::RempDuplicateStatus,
     ext_messages::{
-        get_level_and_level_change, get_level_numeric_value, is_finally_accepted, 
-        is_finally_rejected
+        create_ext_message, get_level_and_level_change, get_level_numeric_value, is_finally_accepted,
+        is_finally_rejected, ExtMessageRejectReason, MAX_EXTERNAL_MESSAGE_SIZE
     },
     validator::{
         remp_manager::RempSessionStats,
@@ -38,18 +40,22 @@ use crate::{
 };
 
 use catchain::serialize_tl_boxed_object;
+use storage::{
+    remp_messages_db::{RempMessageEntry, RempMessagesDb},
+    remp_replay_db::{RempReplayDb, RempReplayEntry}
+};
 
 use ton_api::{
     IntoBoxed,
     ton::ton_node::{
-        rempmessagestatus::{RempAccepted, RempIgnored},
-        RempMessageStatus, RempMessageLevel
+        rempmessagestatus::{RempAccepted, RempIgnored, RempRejected},
+        RempCatchainRecord, RempMessageStatus, RempMessageLevel
     }
 };
 
 use ton_block::{
     Deserializable, Message, Serializable, MsgAddressInt, MsgAddrStd, 
-    ExternalInboundMessageHeader, BlockIdExt, UnixTime32
+    ExternalInboundMessageHeader, AccountIdPrefixFull, BlockIdExt, ShardIdent, UnixTime32
 };
 use ton_types::{error, fail, KeyId, SliceData, Result, UInt256};
 
@@ -57,6 +63,136 @@ use ton_types::{error, fail, KeyId, SliceData, Result, UInt256};
 #[path = "tests/test_message_cache.rs"]
 mod tests;
 
+pub const MESSAGE_CACHE_SATURATED_ERROR: &str = "REMP message cache is saturated";
+
+// REMP catchain record fields besides the message: ids, source and master cc
+const MAX_RMQ_RECORD_OVERHEAD: usize = 1024;
+
+// Messages with `New` status are counted by buckets of destination addresses: buckets are
+// fixed (unlike shards, which are split and merged) and counted for a shard if their prefix
+// belongs to it; shards deeper than the bucket get counters of the whole bucket
+const PENDING_BUCKET_BITS: u32 = 16;
+
+fn pending_bucket(message: &Message) -> Option<(i32, u64)> {
+    let prefix = AccountIdPrefixFull::prefix(message.dst_ref()?).ok()?;
+    let mask = !(u64::MAX >> PENDING_BUCKET_BITS);
+    Some((prefix.workchain_id, prefix.prefix & mask))
+}
+
+// Status label of cached messages gauge
+fn status_kind(status: &RempMessageStatus) -> &'static str {
+    match status {
+        RempMessageStatus::TonNode_RempNew => "new",
+        RempMessageStatus::TonNode_RempSentToValidators(_) => "sent_to_validators",
+        RempMessageStatus::TonNode_RempAccepted(_) => "accepted",
+        RempMessageStatus::TonNode_RempRejected(_) => "rejected",
+        RempMessageStatus::TonNode_RempIgnored(_) => "ignored",
+        RempMessageStatus::TonNode_RempTimeout => "timeout",
+        _ /*RempMessageStatus::TonNode_RempDuplicate*/ => "duplicate",
+    }
+}
+
+/// Status of a message rejected because there is no room for it in the message cache
+pub fn message_cache_saturated_status() -> RempMessageStatus {
+    RempMessageStatus::TonNode_RempRejected(RempRejected {
+        level: RempMessageLevel::TonNode_RempQueue,
+        block_id: Default::default(),
+        error: MESSAGE_CACHE_SATURATED_ERROR.to_string()
+    })
+}
+
+// Max count of entries in status history of a message
+pub const MAX_STATUS_HISTORY_LEN: usize = 32;
+
+/// Message cache operation, which changed status of a message
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RempStatusOrigin {
+    // Message (or its header only) is added to cache
+    Added,
+    // Status is set by message queue (collation results, rejects, forwarding)
+    Updated,
+    // Status is merged with one got from catchain or masterchain block
+    Merged,
+    // Status accepted by collator is changed to ignored
+    Collator,
+}
+
+impl fmt::Display for RempStatusOrigin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let origin = match self {
+            RempStatusOrigin::Added => "added",
+            RempStatusOrigin::Updated => "updated",
+            RempStatusOrigin::Merged => "merged",
+            RempStatusOrigin::Collator => "collator",
+        };
+        write!(f, "{}", origin)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RempStatusHistoryEntry {
+    pub timestamp_ms: u64,
+    pub status: RempMessageStatus,
+    pub origin: RempStatusOrigin,
+}
+
+/// Status change of a cached message (`old_status` is None for new messages)
+#[derive(Clone, Debug, PartialEq)]
+pub struct RempStatusTransition {
+    pub message_id: UInt256,
+    pub old_status: Option<RempMessageStatus>,
+    pub new_status: RempMessageStatus,
+    pub timestamp_ms: u64,
+}
+
+/// Selection of messages by status for cache dump
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RempMessageStatusFilter {
+    All,
+    // Neither finally accepted nor finally rejected
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+impl RempMessageStatusFilter {
+    pub fn matches(&self, status: &RempMessageStatus) -> bool {
+        match self {
+            RempMessageStatusFilter::All => true,
+            RempMessageStatusFilter::Pending =>
+                !is_finally_accepted(status) && !is_finally_rejected(status),
+            RempMessageStatusFilter::Accepted => is_finally_accepted(status),
+            RempMessageStatusFilter::Rejected => is_finally_rejected(status),
+        }
+    }
+}
+
+impl FromStr for RempMessageStatusFilter {
+    type Err = ton_types::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "all" => Ok(RempMessageStatusFilter::All),
+            "pending" => Ok(RempMessageStatusFilter::Pending),
+            "accepted" => Ok(RempMessageStatusFilter::Accepted),
+            "rejected" => Ok(RempMessageStatusFilter::Rejected),
+            _ => fail!("Unknown message status filter {}, expected all, pending, accepted or rejected", s)
+        }
+    }
+}
+
+/// Message cache record, see `MessageCache::dump_messages`
+#[derive(Clone, Debug)]
+pub struct RempMessageDumpEntry {
+    pub master_cc: u32,
+    pub message_id: UInt256,
+    pub message_uid: UInt256,
+    pub status: RempMessageStatus,
+    // Absent if only message header is known
+    pub message: Option<Arc<RmqMessage>>,
+    pub collation_attempts: Vec<u32>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RmqMessage {
     pub message: Arc<Message>,
@@ -72,8 +208,10 @@ impl RmqMessage {
         return Ok(RmqMessage { message, message_id, message_uid, source_key, source_idx, timestamp: Self::timestamp_now()? })
     }
 
+    /// Message got from REMP catchain is validated the same way as one got from client
     pub fn from_rmq_record(record: &ton_api::ton::ton_node::rempcatchainrecord::RempCatchainMessage) -> Result<Self> {
-        let message= Arc::new(Message::construct_from_bytes(&record.message)?);
+        let (_, message) = create_ext_message(&record.message)?;
+        let message = Arc::new(message);
         Ok(RmqMessage {
             message: message.clone(),
             message_id: record.message_id.clone(),
@@ -99,11 +237,20 @@ impl RmqMessage {
         }
     }
 
+    /// Size of the serialized message, counted against the message cache capacity
+    pub fn size(&self) -> usize {
+        self.message.write_to_bytes().map(|data| data.len()).unwrap_or_default()
+    }
+
     pub fn has_no_source_key(&self) -> bool {
         self.source_key.data().to_vec().iter().all(|x| *x == 0)
     }
 
     pub fn deserialize(raw: &ton_api::ton::bytes) -> Result<ton_api::ton::ton_node::RempCatchainRecord> {
+        // Record is the message with a small header, so oversized records are not even parsed
+        if raw.len() > MAX_EXTERNAL_MESSAGE_SIZE + MAX_RMQ_RECORD_OVERHEAD {
+            fail!("{}: REMP catchain record is too large: {}", ExtMessageRejectReason::TooLarge.code(), raw.len())
+        }
         let rmq_record: ton_api::ton::ton_node::RempCatchainRecord = catchain::utils::deserialize_tl_boxed_object(&raw)?;
         Ok(rmq_record)
     }
@@ -211,6 +358,12 @@ pub struct MessageCacheSession {
     messages: Map<UInt256, Arc<RmqMessage>>,
     message_events: LockfreeMapSet<UInt256, u32>, //Map<UInt256, Vec<UnixTime32>>,
     message_status: DashMap<UInt256, RempMessageStatus>,
+    // (last use tick, message size) -- for eviction of least recently used messages
+    message_usage: DashMap<UInt256, (u64, usize)>,
+    usage_tick: AtomicU64,
+    bytes: AtomicUsize,
+    // (count, bytes) of messages with `New` status by destination bucket, see `pending_bucket`
+    pending_new: DashMap<(i32, u64), (usize, usize)>,
 
     blocks_processed: DashSet<BlockIdExt>
 }
@@ -239,17 +392,25 @@ impl MessageCacheSession {
                 fail!("Message with id {:x} changed its header from {} to {}", msg_id, old_hdr, msg_hdr);
             }
         }
+        let tick = self.usage_tick.fetch_add(1, Relaxed);
+        self.message_usage.entry(msg_id.clone()).or_insert((tick, 0));
         Ok(())
     }
 
-    fn insert_message(&self, msg: Arc<RmqMessage>, msg_hdr: Arc<RempMessageHeader>) -> Result<()> {
+    fn insert_message(&self, msg: Arc<RmqMessage>, msg_hdr: Arc<RempMessageHeader>, size: usize) -> Result<()> {
         if msg.message_uid != msg_hdr.message_uid || msg.message_id != msg_hdr.message_id {
             fail!("Message with id {:x} and uid {:x} and its header {} have different uids or ids", msg.message_id, msg.message_uid, msg_hdr);
         }
 
         self.insert_message_header(&msg.message_id, msg_hdr)?;
         match self.messages.insert(msg.message_id.clone(), msg.clone()) {
-            None => Ok(()),
+            None => {
+                if let Some(mut usage) = self.message_usage.get_mut(&msg.message_id) {
+                    usage.1 = size;
+                }
+                self.bytes.fetch_add(size, Relaxed);
+                Ok(())
+            }
             Some(prev) if *prev.val() == msg => Ok(()),
             Some(p) => fail!("Different messages for same id {:x}, replacing {} with {}",
                 p.key(), p.val(), msg
@@ -261,6 +422,97 @@ impl MessageCacheSession {
         self.message_headers.contains_key(msg_id)
     }
 
+    fn touch(&self, msg_id: &UInt256) {
+        let tick = self.usage_tick.fetch_add(1, Relaxed);
+        if let Some(mut usage) = self.message_usage.get_mut(msg_id) {
+            usage.0 = tick;
+        }
+    }
+
+    /// Ids of messages with final statuses, least recently used first
+    fn list_evictable(&self) -> Vec<UInt256> {
+        let mut ids = self.message_usage.iter()
+            .map(|usage| (usage.value().0, usage.key().clone()))
+            .collect::<Vec<_>>();
+        ids.retain(|(_, id)| self.message_status.get(id).map_or(false, |status|
+            is_finally_accepted(status.value()) || is_finally_rejected(status.value())
+        ));
+        ids.sort();
+        ids.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Updates `remp_message_cache_messages` gauge: count of cached messages by status kind
+    /// and destination workchain (shards are split and merged while messages are cached,
+    /// so messages are not counted per shard; header-only messages have unknown workchain)
+    fn count_message(&self, msg_id: &UInt256, status: &RempMessageStatus, delta: f64) {
+        let workchain = self.messages.get(msg_id)
+            .and_then(|m| m.val().message.dst_ref().map(|dst| dst.workchain_id().to_string()))
+            .unwrap_or_else(|| "unknown".to_owned());
+        metrics::increment_gauge!(
+            "remp_message_cache_messages", delta,
+            "status" => status_kind(status), "workchain" => workchain
+        );
+        if let RempMessageStatus::TonNode_RempNew = status {
+            self.count_pending_new(msg_id, delta > 0.0);
+        }
+    }
+
+    /// Updates counters of messages with `New` status (header-only messages are not counted:
+    /// their destination is unknown)
+    fn count_pending_new(&self, msg_id: &UInt256, added: bool) {
+        let (bucket, size) = match self.messages.get(msg_id) {
+            Some(m) => match pending_bucket(&m.val().message) {
+                Some(bucket) => (bucket, m.val().size()),
+                None => return
+            },
+            None => return
+        };
+        let mut entry = self.pending_new.entry(bucket).or_insert((0, 0));
+        if added {
+            entry.0 += 1;
+            entry.1 += size;
+        } else {
+            entry.0 = entry.0.saturating_sub(1);
+            entry.1 = entry.1.saturating_sub(size);
+        }
+    }
+
+    /// Count and total size of messages with `New` status to accounts of the shard
+    fn pending_new_messages(&self, shard: &ShardIdent) -> (usize, usize) {
+        let mut result = (0, 0);
+        for entry in self.pending_new.iter() {
+            let (workchain_id, prefix) = *entry.key();
+            if shard.contains_full_prefix(&AccountIdPrefixFull { workchain_id, prefix }) {
+                result.0 += entry.value().0;
+                result.1 += entry.value().1;
+            }
+        }
+        result
+    }
+
+    fn count_status_change(&self, msg_id: &UInt256, old: &RempMessageStatus, new: &RempMessageStatus) {
+        if status_kind(old) != status_kind(new) {
+            self.count_message(msg_id, old, -1.0);
+            self.count_message(msg_id, new, 1.0);
+        }
+    }
+
+    /// Removes message from the session, returns its size
+    fn remove_message(&self, msg_id: &UInt256) -> Result<usize> {
+        if let Some(status) = self.message_status.get(msg_id).map(|s| s.value().clone()) {
+            self.count_message(msg_id, &status, -1.0);
+        }
+        if let Some((_, header)) = self.message_headers.remove(msg_id) {
+            self.ids_for_uid.remove_from_set(&header.message_uid, msg_id)?;
+        }
+        self.messages.remove(msg_id);
+        self.message_status.remove(msg_id);
+        self.message_events.remove(msg_id);
+        let size = self.message_usage.remove(msg_id).map_or(0, |(_, (_, size))| size);
+        self.bytes.fetch_sub(size, Relaxed);
+        Ok(size)
+    }
+
     fn starts_before_block(&self, blk: &BlockIdExt) -> bool {
         for inf in &self.inf_shards {
             if inf.shard().intersect_with(blk.shard()) {
@@ -285,6 +537,7 @@ impl MessageCacheSession {
             None => fail!("Changing status to {}: no message {:x} in message cache session {}", new_status, message_id, self),
             Some(old) => {
                 log::trace!(target: "remp", "Message {:x}: changing status {} => {}", message_id, old, new_status);
+                self.count_status_change(message_id, &old, &new_status);
                 return Ok(old)
             }
         }
@@ -299,6 +552,7 @@ impl MessageCacheSession {
             Some(status) => {
                 let old_status = status.value().clone();
                 *status.value_mut() = status_updater(&old_status);
+                self.count_status_change(message_id, &old_status, status.value());
                 Ok((old_status, status.value().clone()))
             }
         }
@@ -343,12 +597,49 @@ impl MessageCacheSession {
         self.message_headers.iter().map(|v| v.key().clone()).collect()
     }
 
+    fn dump_messages(
+        &self,
+        shard: Option<&ShardIdent>,
+        status_filter: RempMessageStatusFilter,
+        result: &mut Vec<RempMessageDumpEntry>
+    ) -> Result<()> {
+        for header in self.message_headers.iter() {
+            let message_id = header.key();
+            let status = match self.message_status.get(message_id) {
+                Some(status) if status_filter.matches(status.value()) => status.value().clone(),
+                _ => continue
+            };
+            let message = self.messages.get(message_id).map(|m| m.val().clone());
+            if let Some(shard) = shard {
+                // Destination of header-only messages is unknown
+                match message.as_ref().and_then(|m| m.message.dst_ref()) {
+                    Some(dst) if shard.contains_address(dst)? => (),
+                    _ => continue
+                }
+            }
+            let mut collation_attempts = self.message_events.get_set(message_id);
+            collation_attempts.sort();
+            result.push(RempMessageDumpEntry {
+                master_cc: self.master_cc,
+                message_id: message_id.clone(),
+                message_uid: header.value().message_uid.clone(),
+                status,
+                message,
+                collation_attempts,
+            });
+        }
+        Ok(())
+    }
+
     fn gc_all(&self) -> RempSessionStats {
         let mut stats = RempSessionStats::default();
         for id in self.list_ids() {
             stats.total += 1;
 
             log::debug!(target: "remp", "Removing old message: {}", self.message_info(&id));
+            if let Some(status) = self.message_status.get(&id).map(|s| s.value().clone()) {
+                self.count_message(&id, &status, -1.0);
+            }
 
             match (self.messages.get(&id), self.message_status.get(&id)) {
                 (Some(_m),Some(status)) => {
@@ -377,6 +668,10 @@ impl MessageCacheSession {
             message_events: LockfreeMapSet::default(),
             messages: Map::default(),
             message_status: DashMap::default(),
+            message_usage: DashMap::default(),
+            usage_tick: AtomicU64::new(0),
+            bytes: AtomicUsize::new(0),
+            pending_new: DashMap::default(),
             inf_shards: HashSet::from_iter(inf_shards.into_iter()),
             blocks_processed: DashSet::default(),
         }
@@ -390,6 +685,29 @@ pub struct MessageCache {
     master_cc_seqno_lwb: AtomicU32, // Minimal actual master_cc_seqno
     master_cc_seqno_curr: AtomicU32, // Current (that is, maximal) master_cc_seqno
 
+    persistent_db: Option<RempMessagesDb>,
+
+    // Capacity: messages with final statuses are evicted (oldest master cc first, then least
+    // recently used) to make room for new ones; if there are no such messages, new ones are rejected
+    max_messages: Option<usize>,
+    max_bytes: Option<usize>,
+
+    // Subscriptions to status changes; senders are dropped when messages leave the cache
+    subscribers: DashMap<UInt256, watch::Sender<RempMessageStatus>>,
+
+    // Last status changes of messages (if enabled); removed together with sessions
+    status_history: Option<DashMap<UInt256, VecDeque<RempStatusHistoryEntry>>>,
+
+    // All status changes are sent here (if set) to be exported to external DB
+    status_transitions: Option<mpsc::UnboundedSender<RempStatusTransition>>,
+
+    // Messages accepted by shardchain or masterchain during the expiry window (by uid), if DB
+    // is set: they are kept in DB, so their replays are rejected after restart of the node,
+    // when the messages themselves are not in cache anymore
+    replay_digests: DashMap<UInt256, RempReplayEntry>,
+    replay_db: Option<RempReplayDb>,
+    replay_window_sec: AtomicU32,
+
     #[cfg(feature = "telemetry")]
     cache_size_metric: Arc<Metric>,
 }
@@ -413,23 +731,266 @@ impl MessageCache {
         result
     }
 
+    /// Count and total size of messages with `New` status to accounts of the shard,
+    /// i.e. depth of REMP queue for the collator of the shard
+    pub fn pending_new_messages(&self, shard: &ShardIdent) -> (usize, usize) {
+        let mut result = (0, 0);
+        for cc in self.get_master_cc_stored_range() {
+            if let Some(s) = self.sessions.get(&cc) {
+                let (count, bytes) = s.val().pending_new_messages(shard);
+                result.0 += count;
+                result.1 += bytes;
+            }
+        }
+        result
+    }
+
+    fn all_messages_bytes(&self) -> usize {
+        self.get_master_cc_stored_range()
+            .filter_map(|cc| self.sessions.get(&cc).map(|s| s.val().bytes.load(Relaxed)))
+            .sum()
+    }
+
+    /// Checks whether a message of `size` bytes fits into cache capacity;
+    /// evicts messages with final statuses if needed
+    pub fn make_room(&self, size: usize) -> bool {
+        if self.max_messages.is_none() && self.max_bytes.is_none() {
+            return true
+        }
+        let fits = |count: usize, bytes: usize|
+            self.max_messages.map_or(true, |max| count < max) &&
+            self.max_bytes.map_or(true, |max| bytes + size <= max);
+        let mut count = self.all_messages_count();
+        let mut bytes = self.all_messages_bytes();
+        if fits(count, bytes) {
+            return true
+        }
+
+        let mut evicted = 0;
+        'sessions: for cc in self.get_master_cc_stored_range() {
+            let session = match self.sessions.get(&cc) {
+                Some(session) => session.val().clone(),
+                None => continue
+            };
+            for id in session.list_evictable() {
+                match session.remove_message(&id) {
+                    Ok(removed) => {
+                        self.subscribers.remove(&id);
+                        count -= 1;
+                        bytes -= removed;
+                        evicted += 1;
+                    }
+                    Err(e) => log::error!(target: "remp", "Cannot evict message {:x}: {}", id, e)
+                }
+                if fits(count, bytes) {
+                    break 'sessions
+                }
+            }
+        }
+        if evicted > 0 {
+            log::debug!(target: "remp", "{} messages evicted from message cache", evicted);
+            metrics::counter!("remp_message_cache_evicted", evicted);
+            #[cfg(feature = "telemetry")]
+            self.cache_size_metric.update(count as u64);
+        }
+        fits(count, bytes)
+    }
+
     /// Returns new message status, if it worths reporting (final statuses do not need to be reported)
     pub fn update_message_status(&self, message_id: &UInt256, new_status: RempMessageStatus) -> Result<Option<RempMessageStatus>> {
         let session = self.get_session_for_message(message_id).ok_or_else(
             || error!("Cannot find message {:x} to change its status to {:?}", message_id, new_status)
         )?;
 
+        let old_status = session.update_message_status(message_id, new_status.clone())?;
+        session.touch(message_id);
+        self.persist_message(&session, message_id)?;
+        self.record_status(message_id, Some(&old_status), &new_status, RempStatusOrigin::Updated);
+        self.notify_subscribers(message_id, &new_status);
+
         if let RempMessageStatus::TonNode_RempAccepted(acc_new) = &new_status {
             if acc_new.level == RempMessageLevel::TonNode_RempMasterchain {
-                session.update_message_status(message_id, new_status.clone())?;
                 return Ok(None)
             }
         }
-
-        session.update_message_status(message_id, new_status.clone())?;
         Ok(Some(new_status))
     }
 
+    /// Subscribes to status changes of the message: the receiver gets current status
+    /// and all further ones; it is closed when the message is removed from cache
+    pub fn subscribe(&self, message_id: &UInt256) -> Result<watch::Receiver<RempMessageStatus>> {
+        let status = self.get_message_status(message_id)?
+            .ok_or_else(|| error!("Cannot subscribe to message {:x}: no message in cache", message_id))?;
+        let sender = self.subscribers.entry(message_id.clone())
+            .or_insert_with(|| watch::channel(status).0);
+        Ok(sender.subscribe())
+    }
+
+    fn notify_subscribers(&self, message_id: &UInt256, status: &RempMessageStatus) {
+        let unsubscribed = match self.subscribers.get(message_id) {
+            Some(sender) => sender.send(status.clone()).is_err(),
+            None => return
+        };
+        if unsubscribed {
+            self.subscribers.remove_if(message_id, |_, sender| sender.is_closed());
+        }
+    }
+
+    fn record_status(
+        &self,
+        message_id: &UInt256,
+        old_status: Option<&RempMessageStatus>,
+        status: &RempMessageStatus,
+        origin: RempStatusOrigin
+    ) {
+        self.remember_accepted(message_id, status);
+        if self.status_history.is_none() && self.status_transitions.is_none() {
+            return
+        }
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        if let Some(sender) = &self.status_transitions {
+            let transition = RempStatusTransition {
+                message_id: message_id.clone(),
+                old_status: old_status.cloned(),
+                new_status: status.clone(),
+                timestamp_ms,
+            };
+            if sender.send(transition).is_err() {
+                log::warn!(target: "remp", "Cannot export status transition of message {:x}", message_id);
+            }
+        }
+        let history = match &self.status_history {
+            Some(history) => history,
+            None => return
+        };
+        let mut entries = history.entry(message_id.clone()).or_default();
+        if entries.len() >= MAX_STATUS_HISTORY_LEN {
+            entries.pop_front();
+        }
+        entries.push_back(RempStatusHistoryEntry { timestamp_ms, status: status.clone(), origin });
+    }
+
+    /// Keeps replay protection record for message accepted by shardchain or masterchain
+    fn remember_accepted(&self, message_id: &UInt256, status: &RempMessageStatus) {
+        let db = match &self.replay_db {
+            Some(db) => db,
+            None => return
+        };
+        let block_id = match status {
+            RempMessageStatus::TonNode_RempAccepted(RempAccepted { level: RempMessageLevel::TonNode_RempShardchain, block_id, .. }) |
+            RempMessageStatus::TonNode_RempAccepted(RempAccepted { level: RempMessageLevel::TonNode_RempMasterchain, block_id, .. }) =>
+                block_id,
+            _ => return
+        };
+        let uid = match self.get_message_uid(message_id) {
+            Ok(Some(uid)) => uid,
+            _ => return
+        };
+        let entry = RempReplayEntry {
+            message_id: message_id.clone(),
+            block_id: block_id.clone(),
+            accepted_at: UnixTime32::now().as_u32(),
+        };
+        if let Err(e) = db.put_entry(&uid, &entry) {
+            log::error!(target: "remp", "Cannot store replay protection record of message {:x}: {}", message_id, e);
+        }
+        self.replay_digests.insert(uid, entry);
+    }
+
+    /// Removes replay protection records older than expiry window
+    fn gc_replay_digests(&self) {
+        let window = self.replay_window_sec.load(Relaxed);
+        if window == 0 {
+            return
+        }
+        let expired_before = UnixTime32::now().as_u32().saturating_sub(window);
+        let mut expired = Vec::new();
+        self.replay_digests.retain(|uid, entry| {
+            if entry.accepted_at < expired_before {
+                expired.push(uid.clone());
+                false
+            } else {
+                true
+            }
+        });
+        if let Some(db) = &self.replay_db {
+            for uid in expired.iter() {
+                if let Err(e) = db.remove_entry(uid) {
+                    log::error!(target: "remp", "Cannot remove replay protection record {:x}: {}", uid, e);
+                }
+            }
+        }
+    }
+
+    /// Status changes of the message (oldest first); None if history is disabled
+    /// or the message is unknown
+    pub fn get_status_history(&self, message_id: &UInt256) -> Option<Vec<RempStatusHistoryEntry>> {
+        self.status_history.as_ref()?
+            .get(message_id)
+            .map(|entries| entries.iter().cloned().collect())
+    }
+
+    /// Subscribers of messages without final status get Timeout status
+    /// before the messages are removed together with their session
+    fn notify_timeout(&self, session: &MessageCacheSession) {
+        for entry in session.message_status.iter() {
+            if !is_finally_accepted(entry.value()) && !is_finally_rejected(entry.value()) {
+                self.notify_subscribers(entry.key(), &RempMessageStatus::TonNode_RempTimeout);
+            }
+        }
+    }
+
+    /// Writes message with its current status to persistent DB (if enabled)
+    fn persist_message(&self, session: &MessageCacheSession, message_id: &UInt256) -> Result<()> {
+        let db = match &self.persistent_db {
+            Some(db) => db,
+            None => return Ok(())
+        };
+        let uid = session.message_headers.get(message_id)
+            .ok_or_else(|| error!("No header for message {:x}, {}", message_id, session))?
+            .value().message_uid.clone();
+        let status = session.message_status.get(message_id)
+            .ok_or_else(|| error!("No status for message {:x}, {}", message_id, session))?
+            .value().clone();
+        let message = match session.messages.get(message_id) {
+            Some(m) => Some(RmqMessage::serialize(&m.val().as_rmq_record(session.master_cc))?.0),
+            None => None
+        };
+        let entry = RempMessageEntry { uid, status: serialize_tl_boxed_object!(&status).0, message };
+        db.put_entry(session.master_cc, message_id, &entry)
+    }
+
+    /// Restores messages of the session from persistent DB (if enabled): the session is
+    /// created again after restart of the node
+    fn load_persistent_messages(&self, session: &MessageCacheSession) -> Result<usize> {
+        let db = match &self.persistent_db {
+            Some(db) => db,
+            None => return Ok(0)
+        };
+        let mut count = 0;
+        db.for_each_entry(session.master_cc, &mut |message_id, entry| {
+            let status: RempMessageStatus =
+                catchain::utils::deserialize_tl_boxed_object(&entry.status.into())?;
+            let header = RempMessageHeader::new_arc(&message_id, &entry.uid);
+            session.message_status.insert(message_id.clone(), status.clone());
+            match entry.message {
+                None => session.insert_message_header(&message_id, header)?,
+                Some(data) => match RmqMessage::deserialize(&data.into())? {
+                    RempCatchainRecord::TonNode_RempCatchainMessage(record) => {
+                        let message = Arc::new(RmqMessage::from_rmq_record(&record)?);
+                        let size = message.size();
+                        session.insert_message(message, header, size)?
+                    }
+                    _ => fail!("Wrong persistent record of message {:x}, {}", message_id, session)
+                }
+            }
+            session.count_message(&message_id, &status, 1.0);
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
     fn get_session_for_message(&self, message_id: &UInt256) -> Option<Arc<MessageCacheSession>> {
         let range = self.get_master_cc_stored_range();
         for cc in range {
@@ -520,9 +1081,16 @@ impl MessageCache {
             fail!("Inconsistent message cache contents: message {} present in cache, although should not", message_id)
         }
 
+        let size = message.size();
+        if !self.make_room(size) {
+            metrics::increment_counter!("remp_message_cache_rejected");
+            fail!("{}: cannot insert message {}", MESSAGE_CACHE_SATURATED_ERROR, message)
+        }
+
         session.message_status.insert(message_id.clone(), status.clone());
-        session.insert_message(message, message_header)?;
-        Ok(())
+        session.insert_message(message, message_header, size)?;
+        session.count_message(&message_id, status, 1.0);
+        self.persist_message(&session, &message_id)
     }
 
     fn insert_message_header(&self, session: Arc<MessageCacheSession>, message_header: Arc<RempMessageHeader>, status: &RempMessageStatus) -> Result<()> {
@@ -531,9 +1099,15 @@ impl MessageCache {
             fail!("Inconsistent message cache contents: message header {:x} present in cache, although should not", message_id)
         }
 
+        if !self.make_room(0) {
+            metrics::increment_counter!("remp_message_cache_rejected");
+            fail!("{}: cannot insert message header {}", MESSAGE_CACHE_SATURATED_ERROR, message_header)
+        }
+
         session.message_status.insert(message_id.clone(), status.clone());
         session.insert_message_header(&message_id, message_header)?;
-        Ok(())
+        session.count_message(&message_id, status, 1.0);
+        self.persist_message(&session, &message_id)
     }
 
     /// Inserts message with given status, if it is not there
@@ -565,11 +1139,18 @@ impl MessageCache {
                     None => self.insert_message_header( session, header, &status_if_new)?,
                     Some(message) => self.insert_message(session, message, header, &status_if_new)?
                 };
+                self.record_status(message_id, None, &status_if_new, RempStatusOrigin::Added);
                 Ok((None, status_if_new))
             },
             Some(session) => {
                 let (old_status, final_status) =
                     session.alter_message_status(&message_id, |old| status_updater(old,&status_if_new))?;
+                session.touch(message_id);
+                if old_status != final_status {
+                    self.persist_message(&session, message_id)?;
+                    self.record_status(message_id, Some(&old_status), &final_status, RempStatusOrigin::Merged);
+                    self.notify_subscribers(message_id, &final_status);
+                }
                 Ok((Some(old_status), final_status))
             },
         }
@@ -591,6 +1172,12 @@ impl MessageCache {
             old_status.clone()
         })?;
 
+        session.touch(msg_id);
+        if before != after {
+            self.persist_message(&session, msg_id)?;
+            self.record_status(msg_id, Some(&before), &after, RempStatusOrigin::Collator);
+            self.notify_subscribers(msg_id, &after);
+        }
         Ok(before != after)
     }
 
@@ -672,6 +1259,12 @@ impl MessageCache {
             Some((_lvl, d @ RempDuplicateStatus::Fresh(_))) => d,
         };
 
+        // Message with same uid may be accepted before restart of the node (or before the
+        // accepted message is evicted from cache)
+        if let Some(entry) = self.replay_digests.get(&uid) {
+            return Ok(RempDuplicateStatus::Duplicate(entry.block_id.clone(), uid, entry.message_id.clone()))
+        }
+
         // Check whether message_id is minimal among other messages with same uid
         match self.get_lower_id_for_uid(&message_id, &uid)? {
             None => Ok(fresh_duplicate_status),
@@ -686,6 +1279,22 @@ impl MessageCache {
         }
     }
 
+    /// Messages of all stored sessions (oldest first), optionally only ones to accounts of
+    /// the shard and/or with statuses of the kind
+    pub fn dump_messages(
+        &self,
+        shard: Option<&ShardIdent>,
+        status_filter: RempMessageStatusFilter
+    ) -> Result<Vec<RempMessageDumpEntry>> {
+        let mut result = Vec::new();
+        for cc in self.get_master_cc_stored_range() {
+            if let Some(session) = self.sessions.get(&cc) {
+                session.val().dump_messages(shard, status_filter, &mut result)?;
+            }
+        }
+        Ok(result)
+    }
+
     pub fn message_stats(&self) -> String {
         format!("All REMP messages count = {}", self.all_messages_count())
     }
@@ -717,10 +1326,20 @@ impl MessageCache {
             master_cc, start_time.as_u32(), inf_blocks
         );
 
-        if let Some(_old) = self.sessions.insert(master_cc, Arc::new(MessageCacheSession::new(master_cc, start_time, inf_blocks))) {
+        let session = Arc::new(MessageCacheSession::new(master_cc, start_time, inf_blocks));
+        if let Some(_old) = self.sessions.insert(master_cc, session.clone()) {
             fail!("MessageCacheSession {} is created in parallel!", master_cc)
         }
         self.master_cc_seqno_stored.fetch_min(master_cc, Relaxed);
+
+        let loaded = self.load_persistent_messages(&session)?;
+        if loaded > 0 {
+            log::info!(target: "remp", "MessageCacheSession {}: {} messages loaded from persistent DB",
+                master_cc, loaded
+            );
+            #[cfg(feature = "telemetry")]
+            self.cache_size_metric.update(self.all_messages_count() as u64);
+        }
         Ok(())
     }
 
@@ -843,6 +1462,7 @@ impl MessageCache {
             }
         };
 
+        self.replay_window_sec.store(rp_guarantee.as_secs() as u32, Relaxed);
         let new_range = new_lwb..=new_current_master_cc;
         self.set_master_cc_range(&new_range)?;
         Ok(new_range)
@@ -879,6 +1499,7 @@ impl MessageCache {
         for cc_to_remove in gc_lwb..actual_cc {
             if let Some(session) = self.sessions.remove(&cc_to_remove) {
                 log::debug!(target: "remp", "Removing & gc MessageCacheSession {}", session.val());
+                self.notify_timeout(session.val());
                 stats.add(&session.val().gc_all());
 
                 #[cfg(feature = "telemetry")]
@@ -886,6 +1507,17 @@ impl MessageCache {
             }
             self.master_cc_seqno_stored.store(cc_to_remove+1, Relaxed);
         }
+        self.subscribers.retain(|id, _| self.get_session_for_message(id).is_some());
+        if let Some(history) = &self.status_history {
+            history.retain(|id, _| self.get_session_for_message(id).is_some());
+        }
+
+        if let Some(db) = &self.persistent_db {
+            if let Err(e) = db.remove_before(actual_cc) {
+                log::error!(target: "remp", "Cannot remove old messages from persistent DB: {}", e);
+            }
+        }
+        self.gc_replay_digests();
 
         stats
     }
@@ -900,8 +1532,57 @@ impl MessageCache {
             master_cc_seqno_stored: AtomicU32::new(u32::MAX),
             master_cc_seqno_lwb: AtomicU32::new(1),
             master_cc_seqno_curr: AtomicU32::new(0),
+            persistent_db: None,
+            max_messages: None,
+            max_bytes: None,
+            subscribers: DashMap::default(),
+            status_history: None,
+            status_transitions: None,
+            replay_digests: DashMap::default(),
+            replay_db: None,
+            replay_window_sec: AtomicU32::new(0),
             #[cfg(feature = "telemetry")]
             cache_size_metric,
         }
     }
+
+    /// Limits count and total size of messages in cache (None - unlimited)
+    pub fn with_capacity(mut self, max_messages: Option<usize>, max_bytes: Option<usize>) -> Self {
+        self.max_messages = max_messages;
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Status changes of messages are recorded (at most `MAX_STATUS_HISTORY_LEN` last ones)
+    pub fn with_status_history(mut self) -> Self {
+        self.status_history = Some(DashMap::default());
+        self
+    }
+
+    /// Each status change of messages is sent to `sender`
+    pub fn with_status_transitions(mut self, sender: mpsc::UnboundedSender<RempStatusTransition>) -> Self {
+        self.status_transitions = Some(sender);
+        self
+    }
+
+    /// Messages and their statuses are also written to `db` and restored from it
+    /// when their sessions are created
+    pub fn with_persistent_db(mut self, db: RempMessagesDb) -> Self {
+        self.persistent_db = Some(db);
+        self
+    }
+
+    /// Replay protection records are written to `db`; stored records are loaded immediately
+    pub fn with_replay_db(mut self, db: RempReplayDb) -> Self {
+        let loaded = db.for_each_entry(&mut |uid, entry| {
+            self.replay_digests.insert(uid, entry);
+            Ok(())
+        });
+        match loaded {
+            Ok(()) => log::info!(target: "remp", "{} replay protection records are loaded", self.replay_digests.len()),
+            Err(e) => log::error!(target: "remp", "Cannot load replay protection records: {}", e)
+        }
+        self.replay_db = Some(db);
+        self
+    }
 }
@@ -12,13 +12,19 @@
 */
 
 pub mod validate_query;
-mod fabric;
+pub mod validation_hooks;
+pub mod validation_pool;
+pub mod validation_trace;
+pub mod fabric;
 mod log_parser;
 pub mod accept_block;
 pub mod catchain_overlay;
+pub mod catchain_transcript;
 mod reliable_message_queue;
 pub mod remp_catchain;
 pub mod remp_manager;
+pub mod remp_rate_limit;
+pub mod remp_acceptance;
 pub mod remp_block_parser;
 mod validator_group;
 pub mod validator_utils;
@@ -28,6 +34,10 @@ pub mod sessions_computing;
 pub mod message_cache;
 pub mod candidate_db;
 pub mod collator;
+pub mod collator_cache;
+pub mod consensus_stats;
+pub mod session_stats;
+pub mod deferred_dispatch;
 pub mod out_msg_queue;
 mod out_msg_queue_cleaner;
 mod mutex_wrapper;
@@ -36,6 +46,8 @@ pub mod remp_service;
 pub mod telemetry;
 #[cfg(feature = "slashing")]
 pub mod slashing;
+#[cfg(feature = "slashing")]
+pub mod slashing_evidence;
 
 use std::sync::Arc;
 use ton_types::{Result, UInt256, error};
@@ -59,6 +71,9 @@ pub struct CollatorSettings {
     pub want_split: Option<bool>,
     pub want_merge: Option<bool>,
     pub is_fake: bool,
+    // collation without side effects, see `Collator::collate_dry_run`
+    #[serde(default, skip_serializing)]
+    pub dry_run: bool,
 }
 
 impl CollatorSettings {
@@ -743,6 +743,26 @@ impl OutMsgQueueInfoStuff {
     }
 }
 
+/// Position of inbound internal messages processing kept between collation attempts
+/// of a shard. All messages of the neighbors' queues up to (`lt`, `hash`) are already
+/// processed in state `prev_seqno` of the shard (and so in all the next states), so the
+/// next attempt skips them without parsing. Neighbors' queues may get messages with
+/// smaller lt in new blocks (transit ones), so the position is valid only while the 
+/// neighbors' blocks are the same.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutQueueCheckpoint {
+    pub prev_seqno: u32,
+    pub neighbors: Vec<BlockIdExt>,
+    pub lt: u64,
+    pub hash: UInt256,
+}
+
+impl OutQueueCheckpoint {
+    pub fn is_valid_for(&self, prev_seqno: u32, neighbors: &[BlockIdExt]) -> bool {
+        prev_seqno >= self.prev_seqno && self.neighbors == neighbors
+    }
+}
+
 pub struct MsgQueueManager {
 // Unused
 //    shard: ShardIdent,
@@ -1410,6 +1430,9 @@ impl MsgQueueManager {
 // Unused
 //    pub fn shard(&self) -> &ShardIdent { &self.shard }
     pub fn neighbors(&self) -> &Vec<OutMsgQueueInfoStuff> { &self.neighbors }
+    pub fn active_neighbors_ids(&self) -> Vec<BlockIdExt> {
+        self.neighbors.iter().filter(|nb| !nb.is_disabled()).map(|nb| nb.block_id().clone()).collect()
+    }
 // Unused
 //    pub fn neighbor(&self, shard: &ShardIdent) -> Option<&OutMsgQueueInfoStuff> {
 //        for nb in &self.neighbors {
@@ -1483,6 +1506,8 @@ impl<T: Eq> PartialOrd for RootRecord<T> {
 pub struct MsgQueueMergerIterator<T> {
     // store branches descending by lt and hash because Vec works like Stack
     roots: Vec<RootRecord<T>>,
+    // messages up to this lt and hash are skipped without parsing
+    skip_upto: Option<(u64, UInt256)>,
 }
 
 impl MsgQueueMergerIterator<BlockIdExt> {
@@ -1508,7 +1533,7 @@ impl MsgQueueMergerIterator<BlockIdExt> {
             roots.sort();
             debug_assert!(roots.first().unwrap().lt >= roots.last().unwrap().lt);
         }
-        Ok(Self { roots })
+        Ok(Self { roots, skip_upto: None })
     }
 }
 
@@ -1519,11 +1544,21 @@ impl MsgQueueMergerIterator<u8> {
         if let Some(cell) = out_queue.data() {
             roots.push(RootRecord::from_cell(cell, out_queue.bit_len(), 0)?);
         }
-        Ok(Self { roots })
+        Ok(Self { roots, skip_upto: None })
     }
 }
 
 impl<T: Clone + Eq> MsgQueueMergerIterator<T> {
+    /// Skips messages with (created lt, hash) less or equal to given ones
+    pub fn skip_upto(&mut self, lt: u64, hash: UInt256) {
+        self.skip_upto = Some((lt, hash));
+    }
+    fn skipped(&self, root: &RootRecord<T>) -> bool {
+        match &self.skip_upto {
+            Some((lt, hash)) => root.lt < *lt || (root.lt == *lt && &root.key.data()[12..44] <= &hash.as_slice()[..]),
+            None => false
+        }
+    }
     fn insert(&mut self, root: RootRecord<T>) {
         let idx = self.roots.binary_search(&root).unwrap_or_else(|x| x);
         self.roots.insert(idx, root);
@@ -1532,6 +1567,9 @@ impl<T: Clone + Eq> MsgQueueMergerIterator<T> {
     fn next_item(&mut self) -> Result<Option<(OutMsgQueueKey, MsgEnqueueStuff, u64, T)>> {
         while let Some(mut root) = self.roots.pop() {
             if root.bit_len == 0 {
+                if self.skipped(&root) {
+                    continue
+                }
                 let key = OutMsgQueueKey::construct_from_cell(root.key.into_cell()?)?;
                 let enq = MsgEnqueueStuff::construct_from(&mut root.cursor, root.lt)?;
                 return Ok(Some((key, enq, root.lt, root.id)))
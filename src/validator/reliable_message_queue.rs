@@ -25,21 +25,23 @@ use ton_api::ton::ton_node::{
     RempMessageStatus, RempMessageLevel,
     rempmessagestatus::{RempAccepted, RempIgnored, RempRejected}, RempCatchainRecord
 };
-use ton_block::{ShardIdent, Message, BlockIdExt, ValidatorDescr};
-use ton_types::{UInt256, Result, fail};
+use ton_block::{ShardIdent, Message, BlockIdExt, MsgAddressInt, ValidatorDescr};
+use ton_types::{UInt256, Result, error, fail};
 use catchain::{PrivateKey, PublicKey};
 use crate::{
     engine_traits::EngineOperations,
     ext_messages::{get_level_and_level_change, is_finally_accepted, is_finally_rejected},
+    network::remp::RempDeferredNotice,
     validator::{
         mutex_wrapper::MutexWrapper,
-        message_cache::RmqMessage,
+        message_cache::{message_cache_saturated_status, RmqMessage},
         remp_manager::RempManager,
         remp_block_parser::{process_block_messages_by_blockid, BlockProcessor},
         remp_catchain::{RempCatchainInfo, RempCatchainInstance},
         sessions_computing::GeneralSessionInfo,
-        validator_utils::ValidatorListHash
-    }
+        validator_utils::{get_abi_message_time, ValidatorListHash, WalletReplayPolicy}
+    },
+    types::state_snapshot::StateSnapshot
 };
 use failure::err_msg;
 use ton_api::ton::ton_node::rempcatchainrecord::{RempCatchainMessage, RempCatchainMessageDigest};
@@ -47,6 +49,75 @@ use ton_api::ton::ton_node::RempMessageStatus::TonNode_RempRejected;
 use crate::block::BlockIdExtExtention;
 use crate::engine_traits::RempDuplicateStatus;
 
+/// Replay protection timestamps of wallets, which are destinations of messages 
+/// collected for one collation round. State snapshot is pinned on the first message to 
+/// a known wallet, and each account is read from it at most once.
+struct WalletReplayTimestamps<'a> {
+    policy: &'a WalletReplayPolicy,
+    engine: &'a dyn EngineOperations,
+    snapshot: Option<StateSnapshot>,
+    failed: bool,
+    timestamps: HashMap<MsgAddressInt, Option<u64>>,
+}
+
+impl<'a> WalletReplayTimestamps<'a> {
+    fn new(policy: &'a WalletReplayPolicy, engine: &'a dyn EngineOperations) -> Self {
+        Self { policy, engine, snapshot: None, failed: false, timestamps: HashMap::new() }
+    }
+
+    /// Fresh message with ABI time not greater than replay protection timestamp 
+    /// of the destination wallet has already been executed, so it is Duplicate
+    async fn check_message(&mut self, message: &RmqMessage, uid: UInt256) -> RempDuplicateStatus {
+        if !self.policy.is_enabled() || self.failed {
+            return RempDuplicateStatus::Fresh(uid)
+        }
+        let (msg_time, dst) = match (get_abi_message_time(&message.message), message.message.dst_ref()) {
+            (Some(msg_time), Some(dst)) => (msg_time, dst),
+            _ => return RempDuplicateStatus::Fresh(uid)
+        };
+        let stored_time = match self.timestamps.get(dst) {
+            Some(stored_time) => *stored_time,
+            None => match self.read_timestamp(dst).await {
+                Ok(stored_time) => {
+                    self.timestamps.insert(dst.clone(), stored_time);
+                    stored_time
+                }
+                Err(e) => {
+                    log::warn!(target: "remp",
+                        "Cannot read replay protection timestamp of {}, wallets are not checked \
+                        till the next collation: {}", dst, e
+                    );
+                    self.failed = true;
+                    None
+                }
+            }
+        };
+        match (stored_time, self.snapshot.as_ref()) {
+            (Some(stored_time), Some(snapshot)) if msg_time <= stored_time => {
+                log::trace!(target: "remp",
+                    "Message {:x} time {} is not greater than replay protection timestamp {} of {}",
+                    message.message_id, msg_time, stored_time, dst
+                );
+                RempDuplicateStatus::Duplicate(
+                    snapshot.mc_block_id().clone(), uid, message.message_id.clone()
+                )
+            }
+            _ => RempDuplicateStatus::Fresh(uid)
+        }
+    }
+
+    async fn read_timestamp(&mut self, addr: &MsgAddressInt) -> Result<Option<u64>> {
+        if self.snapshot.is_none() {
+            self.snapshot = Some(self.engine.load_and_pin_state_snapshot(None).await?);
+        }
+        let snapshot = self.snapshot.as_ref().ok_or_else(|| error!("INTERNAL ERROR: no snapshot"))?;
+        match snapshot.find_account(self.engine, addr).await? {
+            Some(shard_account) => Ok(self.policy.account_replay_timestamp(&shard_account.read_account()?)),
+            None => Ok(None)
+        }
+    }
+}
+
 #[derive(Debug,PartialEq,Eq,PartialOrd,Ord,Clone)]
 enum MessageQueueStatus { Created, Starting, Active, Stopping }
 const RMQ_STOP_POLLING_INTERVAL: Duration = Duration::from_millis(50);
@@ -142,7 +213,7 @@ impl MessageQueue {
         engine: Arc<dyn EngineOperations>, manager: Arc<RempManager>, info: Arc<RempCatchainInfo>, local_key: PrivateKey
     ) -> Result<Arc<Self>> {
         let queue = Arc::new(Self::create(engine, manager, info)?);
-        queue.clone().start(local_key.clone()).await?;
+        queue.clone().start(local_key.clone(), None).await?;
         Ok(queue)
     }
 
@@ -175,7 +246,29 @@ impl MessageQueue {
         }
     }
 
+    /// If the message is ignored by collator because it is deferred to the next blocks,
+    /// the fullnode also gets its position in the account's queue
+    fn send_deferred_notice_to_fullnode(&self, rmq_message: &RmqMessage) {
+        let deferred = match self.engine.get_remp_deferred_message(&rmq_message.message_id) {
+            Some(deferred) => deferred,
+            None => return
+        };
+        if rmq_message.has_no_source_key() {
+            return
+        }
+        let notice = RempDeferredNotice {
+            message_id: rmq_message.message_id.clone(),
+            position: deferred.position
+        };
+        if let Err(e) = self.engine.send_remp_deferred_notice(rmq_message.source_key.clone(), &notice) {
+            log::warn!(target: "remp", "RMQ {}: cannot send deferred notice for {}: {}", self, rmq_message, e)
+        }
+    }
+
     pub fn update_status_send_response(&self, msgid: &UInt256, message: Arc<RmqMessage>, new_status: RempMessageStatus) {
+        if is_finally_rejected(&new_status) && self.remp_manager.options.is_broadcast_rejects() {
+            self.broadcast_reject(&message);
+        }
         match self.remp_manager.message_cache.update_message_status(&msgid, new_status.clone()) {
             Ok(Some(final_status)) => self.send_response_to_fullnode(message.clone(), final_status),
             Ok(None) => (), // Send nothing, no status update is requested
@@ -186,6 +279,25 @@ impl MessageQueue {
         }
     }
 
+    /// Other validators learn about the reject (including rejects of duplicates) before
+    /// the digest is sent in a catchain block
+    fn broadcast_reject(&self, message: &RmqMessage) {
+        let master_cc = match self.remp_manager.message_cache.get_message_with_status_cc(&message.message_id) {
+            Ok(Some((_, _, cc))) => cc,
+            _ => return
+        };
+        let mut digest = RempCatchainMessageDigest::default();
+        digest.masterchain_seqno = master_cc as i32;
+        digest.messages.0.push(ton_api::ton::ton_node::rempcatchainmessageids::RempCatchainMessageIds {
+            id: message.message_id.clone(),
+            uid: message.message_uid.clone()
+        });
+        let record = RempCatchainRecord::TonNode_RempCatchainMessageDigest(digest);
+        if let Err(e) = self.catchain_instance.broadcast(&record) {
+            log::warn!(target: "remp", "RMQ {}: cannot broadcast reject of message {:x}: {}", self, message.message_id, e);
+        }
+    }
+
     pub async fn update_status_send_response_by_id(&self, msgid: &UInt256, new_status: RempMessageStatus) -> Result<Arc<RmqMessage>> {
         let message = self.get_message(msgid)?;
         match &message {
@@ -201,12 +313,14 @@ impl MessageQueue {
         }
     }
 
-    pub async fn start (self: Arc<MessageQueue>, local_key: PrivateKey) -> Result<()> {
+    /// Starts queue catchain; if `activate_at` (UNIX time) is given, the catchain is created
+    /// not earlier than at that time, and stopping the queue before it cancels the start
+    pub async fn start (self: Arc<MessageQueue>, local_key: PrivateKey, activate_at: Option<u32>) -> Result<()> {
         self.set_queue_status(MessageQueueStatus::Created, MessageQueueStatus::Starting).await?;
         log::trace!(target: "remp", "RMQ {}: starting", self);
         
         let catchain_instance_res = self.remp_manager.catchain_store.start_catchain(
-            self.engine.clone(), self.remp_manager.clone(), self.catchain_info.clone(), local_key
+            self.engine.clone(), self.remp_manager.clone(), self.catchain_info.clone(), local_key, activate_at
         ).await;
 
         match catchain_instance_res {
@@ -230,21 +344,24 @@ impl MessageQueue {
                 match q.status {
                     MessageQueueStatus::Created  => { q.status = MessageQueueStatus::Stopping; Ok((false, true)) },
                     MessageQueueStatus::Starting => Ok((false, false)),
-                    MessageQueueStatus::Stopping => fail!("RMQ {}: cannot stop queue with 'Stopping' status", self),
+                    MessageQueueStatus::Stopping => Ok((false, true)),
                     MessageQueueStatus::Active   => { q.status = MessageQueueStatus::Stopping; Ok((true, true)) },
                 }
             }).await?;
 
             if do_stop {
                 log::trace!(target: "remp", "RMQ {}: stopping catchain", self);
-                return self.remp_manager.catchain_store.stop_catchain(&self.catchain_info.queue_id).await;
+                return self.remp_manager.catchain_store.detach_catchain(&self.catchain_info.queue_id).await;
             }
             if do_break {
-                log::trace!(target: "remp", "RMQ {}: catchain is not started -- skip stopping (catchain_instance present: {})",
+                log::trace!(target: "remp", "RMQ {}: catchain is not started or already stopped -- skip stopping (catchain_instance present: {})",
                     self, self.catchain_instance.is_session_active()
                 );
                 return Ok(());
             }
+            if self.remp_manager.catchain_store.cancel_scheduled_catchain(&self.catchain_info.queue_id).await {
+                log::trace!(target: "remp", "RMQ {}: scheduled catchain start is cancelled", self);
+            }
             log::trace!(target: "remp", "RMQ {}: waiting for catchain to stop it", self);
             tokio::time::sleep(RMQ_STOP_POLLING_INTERVAL).await
         }
@@ -521,6 +638,9 @@ impl MessageQueue {
     /// Prepare messages for collation - to be called just before collator invocation.
     pub async fn collect_messages_for_collation (&self) -> Result<()> {
         log::trace!(target: "remp", "RMQ {}: collecting messages for collation", self);
+        let mut replay_timestamps = WalletReplayTimestamps::new(
+            &self.remp_manager.wallet_replay_policy, self.engine.as_ref()
+        );
         let mut cnt = 0;
         while let Some((msgid, _timestamp)) = self.queues.execute_sync(|x| x.take_first_for_collation()).await? {
             let (status, message) = match self.remp_manager.message_cache.get_message_with_status(&msgid) {
@@ -554,7 +674,11 @@ impl MessageQueue {
                 }
             };
 
-            match self.remp_manager.message_cache.check_message_duplicates(&message.message_id)? {
+            let duplicate_status = match self.remp_manager.message_cache.check_message_duplicates(&message.message_id)? {
+                RempDuplicateStatus::Fresh(uid) => replay_timestamps.check_message(&message, uid).await,
+                duplicate_status => duplicate_status
+            };
+            match duplicate_status {
                 RempDuplicateStatus::Absent => fail!("Message {:x} is present in cache, but check_message_duplicates = Absent", &message.message_id),
                 RempDuplicateStatus::Fresh(_) =>
                     log::trace!(target: "remp", "Point 5. RMQ {}: sending message {:x} to collator queue", self, message.message_id),
@@ -674,7 +798,8 @@ impl MessageQueue {
                     },
                     RempMessageStatus::TonNode_RempIgnored(i) if i.level == RempMessageLevel::TonNode_RempCollator => {
                         // Part 2. All messages, ignored by collator itself are also a subject for re-collation
-                        self.update_status_send_response_by_id(&collator_result.message_id, status.clone()).await?;
+                        let message = self.update_status_send_response_by_id(&collator_result.message_id, status.clone()).await?;
+                        self.send_deferred_notice_to_fullnode(&message);
                         log::trace!(target: "remp", 
                             "Point 6. RMQ {}: message {:x} ignored by collator, returning to collation queue", 
                             self, collator_result.message_id
@@ -820,6 +945,7 @@ pub struct RmqQueueManager {
     shard: ShardIdent,
     cur_queue: Option<Arc<MessageQueue>>,
     next_queues: DashMap<UInt256, Arc<RempCatchainInfo>>,
+    prestarted_queues: DashMap<UInt256, Arc<MessageQueue>>,
     local_public_key: PublicKey
 }
 
@@ -837,6 +963,7 @@ impl RmqQueueManager {
             shard: shard.clone(),
             cur_queue: None,
             next_queues: DashMap::new(), //MutexWrapper::new(vec!(), format!("Next queues for {}", shard.clone())),
+            prestarted_queues: DashMap::new(),
             local_public_key: local_public_key.clone(),
             //status: MessageQueueStatus::Active
         };
@@ -905,6 +1032,29 @@ impl RmqQueueManager {
         Ok(())
     }
 
+    /// Starts catchains of the next queues in advance, so they are ready when the next sessions
+    /// become current; the started queues are taken by `forward_messages`
+    pub async fn prestart_next_queues(&self, local_key: PrivateKey) {
+        if !self.remp_manager.options.is_service_enabled() {
+            return;
+        }
+
+        for info in self.get_next_queues().iter() {
+            if self.prestarted_queues.contains_key(&info.queue_id) {
+                continue
+            }
+            log::info!(target: "remp", "RMQ {}: pre-starting next queue {}", self, info);
+            match MessageQueue::create_and_start(
+                self.engine.clone(), self.remp_manager.clone(), info.clone(), local_key.clone()
+            ).await {
+                Err(e) => log::warn!(target: "remp", "RMQ {}: cannot pre-start next queue {}: `{}`", self, info, e),
+                Ok(queue) => {
+                    self.prestarted_queues.insert(info.queue_id.clone(), queue);
+                }
+            }
+        }
+    }
+
     pub async fn forward_messages(&self, new_cc_range: &RangeInclusive<u32>, local_key: PrivateKey) {
         if !self.remp_manager.options.is_service_enabled() {
             return;
@@ -939,6 +1089,11 @@ impl RmqQueueManager {
 
             let mut next_queues = Vec::new();
             for info in next_queue_infos.iter() {
+                if let Some((_, prestarted)) = self.prestarted_queues.remove(&info.queue_id) {
+                    log::debug!(target: "remp", "RMQ {}: {} is pre-started", self, info);
+                    next_queues.push(prestarted);
+                    continue
+                }
                 log::debug!(target: "remp", "RMQ {}: Starting {}", self, info);
 
                 match MessageQueue::create_and_start(
@@ -1026,7 +1181,10 @@ impl RmqQueueManager {
                     }
 
                     for new in next_queues.iter() {
-                        if let Err(x) = new.catchain_instance.pending_messages_queue_send(message.as_rmq_record(message_cc)) {
+                        let record = message
+                            .new_with_updated_source_idx(new.catchain_info.local_idx as u32)
+                            .as_rmq_record(message_cc);
+                        if let Err(x) = new.catchain_instance.pending_messages_queue_send(record) {
                             log::error!(target: "remp",
                             "Point 5a. RMQ {}: message {:x} cannot be put to new queue {}: `{}`",
                             self, msgid, new, x
@@ -1085,7 +1243,7 @@ impl RmqQueueManager {
 
     pub async fn start(&self, local_key: PrivateKey) -> Result<()> {
         if let Some(cur_queue) = &self.cur_queue {
-            cur_queue.clone().start(local_key).await
+            cur_queue.clone().start(local_key, None).await
         }
         else {
             log::warn!(target: "remp", "Cannot start RMQ queue for {} -- no current queue",
@@ -1188,14 +1346,44 @@ impl RmqQueueManager {
 
             let mut cnt = 0;
             let mut cnt_rejected_overload = 0;
+            let mut cnt_rejected_by_policy = 0;
             'a: loop {
                 match self.remp_manager.poll_incoming(&self.shard).await {
                     (Some(rmq_message), _) => {
-                        if let Some((overload_message, status)) = cur_queue.is_queue_overloaded().await {
+                        if let Err(e) = self.remp_manager.check_acceptance(&rmq_message).await {
+                            log::debug!(target: "remp", "Point 3. RMQ {}: message {} is rejected by acceptance policy: {}", self, rmq_message, e);
+                            metrics::increment_counter!("remp_rejected_by_policy");
+                            let rejected = RempRejected {
+                                level: RempMessageLevel::TonNode_RempQueue,
+                                block_id: BlockIdExt::default(),
+                                error: format!("message is not accepted by REMP policy: {}", e)
+                            };
+                            cur_queue.send_response_to_fullnode(rmq_message, RempMessageStatus::TonNode_RempRejected(rejected));
+                            cnt_rejected_by_policy+=1;
+                        }
+                        else if let Some((overload_message, status)) = cur_queue.is_queue_overloaded().await {
                             log::warn!(target: "remp", "Point 3. RMQ {}: {}, ignoring incoming message {}", self, overload_message, rmq_message);
                             cur_queue.send_response_to_fullnode(rmq_message, status);
                             cnt_rejected_overload+=1;
                         }
+                        else if !self.remp_manager.check_shard_rate(&self.shard) {
+                            log::warn!(target: "remp", "Point 3. RMQ {}: rate limit is exceeded, rejecting incoming message {}", self, rmq_message);
+                            #[cfg(feature = "telemetry")]
+                            self.engine.remp_core_telemetry().rate_limited_by_shard();
+                            let rejected = RempRejected {
+                                level: RempMessageLevel::TonNode_RempQueue,
+                                block_id: BlockIdExt::default(),
+                                error: format!("rate limit of messages for shard {} is exceeded", self.shard)
+                            };
+                            cur_queue.send_response_to_fullnode(rmq_message, RempMessageStatus::TonNode_RempRejected(rejected));
+                            cnt_rejected_overload+=1;
+                        }
+                        else if !self.remp_manager.message_cache.make_room(rmq_message.size()) {
+                            log::warn!(target: "remp", "Point 3. RMQ {}: message cache is saturated, rejecting incoming message {}", self, rmq_message);
+                            metrics::increment_counter!("remp_message_cache_rejected");
+                            cur_queue.send_response_to_fullnode(rmq_message, message_cache_saturated_status());
+                            cnt_rejected_overload+=1;
+                        }
                         else if let Err(e) = self.put_message_to_rmq(rmq_message.clone()).await {
                             log::warn!(target: "remp", "Point 3. Error sending RMQ {} message {:?}: {}; returning back to incoming queue",
                                 self, rmq_message, e
@@ -1222,8 +1410,8 @@ impl RmqQueueManager {
             }
 
             log::trace!(target: "remp",
-                "RMQ {} manager: finished polling incoming messages, {} messages processed, {} messages rejected due to overload",
-                self, cnt, cnt_rejected_overload
+                "RMQ {} manager: finished polling incoming messages, {} messages processed, {} messages rejected due to overload, {} by policy",
+                self, cnt, cnt_rejected_overload, cnt_rejected_by_policy
             );
         }
         else {
@@ -0,0 +1,96 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{
+    config::RempAcceptancePolicyConfig, engine_traits::EngineOperations,
+    validator::validator_utils::get_abi_message_time
+};
+
+use std::{collections::HashSet, sync::Arc};
+use ton_block::Message;
+use ton_types::{error, fail, Result};
+
+#[cfg(test)]
+#[path = "tests/test_remp_acceptance.rs"]
+mod tests;
+
+/// Filter of external messages entering REMP. It is called before the message is put
+/// to the message queue, so the message is rejected early; the error is sent to the client
+/// as the reason of reject.
+#[async_trait::async_trait]
+pub trait RempAcceptancePolicy: Sync + Send {
+    async fn check_message(&self, message: &Message) -> Result<()>;
+}
+
+/// Policy configured by `remp.acceptance_policy` of node config: workchains allowlist,
+/// ABI `time`/`expire` header of the body and minimal balance of destination account
+pub struct DefaultRempAcceptancePolicy {
+    engine: Arc<dyn EngineOperations>,
+    workchains: Option<HashSet<i32>>,
+    require_abi_header: bool,
+    min_dst_balance: Option<u64>,
+}
+
+impl DefaultRempAcceptancePolicy {
+
+    pub fn new(engine: Arc<dyn EngineOperations>, config: &RempAcceptancePolicyConfig) -> Self {
+        Self {
+            engine,
+            workchains: config.workchains.as_ref().map(|wcs| wcs.iter().cloned().collect()),
+            require_abi_header: config.require_abi_header.unwrap_or(false),
+            min_dst_balance: config.min_dst_balance,
+        }
+    }
+
+    // Checks which don't need account state
+    fn check_header(&self, message: &Message) -> Result<()> {
+        if let Some(workchains) = &self.workchains {
+            let wc = message.dst_workchain_id()
+                .ok_or_else(|| error!("message has no destination workchain"))?;
+            if !workchains.contains(&wc) {
+                fail!("messages to workchain {} are not accepted", wc)
+            }
+        }
+        if self.require_abi_header && get_abi_message_time(message).is_none() {
+            fail!("message body has no valid ABI time and expire header")
+        }
+        Ok(())
+    }
+
+    async fn check_dst_balance(&self, message: &Message) -> Result<()> {
+        let min_balance = match self.min_dst_balance {
+            Some(min_balance) => min_balance,
+            None => return Ok(())
+        };
+        let dst_wc = message.dst_workchain_id()
+            .ok_or_else(|| error!("message has no destination workchain"))?;
+        let dst_address = message.int_dst_account_id()
+            .ok_or_else(|| error!("message has no standard destination address"))?;
+        let (account, _shard) = self.engine.clone().load_account(dst_wc, dst_address).await
+            .map_err(|e| error!("cannot check balance of destination account: {}", e))?;
+        let balance = account.read_account()?.balance()
+            .map_or(0, |balance| balance.grams.as_u128());
+        if balance < min_balance as u128 {
+            fail!("balance {} of destination account is less than required {}", balance, min_balance)
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl RempAcceptancePolicy for DefaultRempAcceptancePolicy {
+    async fn check_message(&self, message: &Message) -> Result<()> {
+        self.check_header(message)?;
+        self.check_dst_balance(message).await
+    }
+}
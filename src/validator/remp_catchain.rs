@@ -11,14 +11,22 @@
 * limitations under the License.
 */
 
-use std::{collections::{HashMap, HashSet}, fmt, sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
+use std::{
+    cmp::Ordering as CmpOrdering,
+    collections::{BinaryHeap, HashMap, HashSet}, fmt,
+    path::{Path, PathBuf}, sync::{Arc, Mutex, atomic::{AtomicU32, AtomicU64, Ordering}},
+    time::{Duration, SystemTime, UNIX_EPOCH}
+};
 use std::fmt::{Display, Formatter};
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, str::FromStr};
+use dashmap::DashSet;
+use storage::{db::rocksdb::RocksDb, remp_sessions_db::{RempSessionEntry, RempSessionsDb}};
 
 use crate::{
-    engine_traits::EngineOperations,
+    config::RempConfig, engine_traits::EngineOperations, network::send_queue::QosClass,
     validator::{
         catchain_overlay::CatchainOverlayManagerImpl, message_cache::RmqMessage,
+        catchain_transcript::{CatchainTranscript, CatchainTranscriptStore, TranscriptBlock},
         sessions_computing::GeneralSessionInfo,
         mutex_wrapper::MutexWrapper, remp_manager::RempManager,
         validator_utils::{
@@ -35,12 +43,25 @@ use catchain::{
     PublicKey, PublicKeyHash
 };
 use ton_api::{
-    IntoBoxed, ton::ton_node::RempCatchainRecord
+    IntoBoxed,
+    ton::ton_node::{
+        RempCatchainRecord, RempMessageStatus, rempcatchainrecord::RempCatchainMessageDigest
+    }
 };
-use ton_block::ValidatorDescr;
-use ton_types::{error, fail, KeyId, Result, UInt256};
+use ton_block::{Deserializable, Message, MsgAddressInt, ShardIdent, ValidatorDescr};
+use ton_types::{base64_encode_url_safe, error, fail, KeyId, Result, UInt256};
+
+#[cfg(test)]
+#[path = "tests/test_remp_catchain.rs"]
+mod tests;
 
 const REMP_CATCHAIN_START_POLLING_INTERVAL: Duration = Duration::from_millis(50);
+// Scheduled sessions check their activation time and cancellation with this interval
+const REMP_CATCHAIN_ACTIVATION_POLLING_INTERVAL: Duration = Duration::from_secs(1);
+const REMP_CATCHAIN_START_BACKOFF: Duration = Duration::from_secs(1);
+const REMP_CATCHAIN_MAX_START_BACKOFF: Duration = Duration::from_secs(60);
+// Timeout of dead session detection is doubled after each restart, at most 2^6 times
+const REMP_CATCHAIN_MAX_RESTART_BACKOFF_SHIFT: u32 = 6;
 
 fn get_remp_catchain_record_info(r: &RempCatchainRecord) -> String {
     match r {
@@ -51,28 +72,319 @@ fn get_remp_catchain_record_info(r: &RempCatchainRecord) -> String {
     }
 }
 
+fn unix_time_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+/// Time left until activation (UNIX time, seconds); None if the activation time has come
+fn activation_delay(activate_at: u32, now_ms: u64) -> Option<Duration> {
+    let activate_at_ms = activate_at as u64 * 1000;
+    if activate_at_ms > now_ms {
+        Some(Duration::from_millis(activate_at_ms - now_ms))
+    } else {
+        None
+    }
+}
+
+/// Total size of files in the directory and its subdirectories; unreadable entries are skipped
+fn dir_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0
+    };
+    entries.filter_map(|entry| entry.ok()).map(|entry| {
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0
+        }
+    }).sum()
+}
+
+// Native RMQ block payload is TL-serialized (constructor ids are CRC32 of the scheme lines):
+//   remp.catchain.blockPayload producer_idx:int timestamp_ms:long records:(vector bytes) = remp.catchain.BlockPayload;
+//   remp.catchain.blockPayloadCompressed data:bytes = remp.catchain.BlockPayload;
+// `data` of the compressed payload is bare remp.catchain.blockPayload compressed with zstd.
+// Legacy payloads are boxed validator_session.blockUpdate.
+const RMQ_BLOCK_PAYLOAD_ID: u32 = 0x78b67b77;
+const RMQ_BLOCK_PAYLOAD_COMPRESSED_ID: u32 = 0x16fde0ee;
+const MAX_DECOMPRESSED_PAYLOAD_SIZE: usize = 16 << 20;
+const PAYLOAD_COMPRESSION_LEVEL: i32 = 3;
+// Serialized message record is larger than the message by its ids and TL headers
+const MAX_RECORD_OVERHEAD: usize = 1024;
+// Serialized size of one message in reject digest
+const DIGEST_MESSAGE_IDS_SIZE: usize = 64;
+
+/// Writer of TL primitives used by native RMQ block payload
+#[derive(Default)]
+struct TlWriter {
+    data: Vec<u8>,
+}
+
+impl TlWriter {
+    fn write_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+    fn write_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+    fn write_bytes(&mut self, value: &[u8]) -> Result<()> {
+        let len = value.len();
+        let header = if len < 254 {
+            self.data.push(len as u8);
+            1
+        } else if len < 1 << 24 {
+            self.data.push(254);
+            self.data.extend_from_slice(&(len as u32).to_le_bytes()[..3]);
+            4
+        } else {
+            fail!("TL bytes field is too long: {} bytes", len)
+        };
+        self.data.extend_from_slice(value);
+        self.data.resize(self.data.len() + (4 - (header + len) % 4) % 4, 0);
+        Ok(())
+    }
+}
+
+/// Reader of TL primitives used by native RMQ block payload; lengths given by
+/// the data are checked before anything is allocated for them
+struct TlReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TlReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+    fn read_slice(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.remaining() < len {
+            fail!("RMQ block payload is too short: {} bytes needed, {} remain", len, self.remaining())
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+    fn read_u32(&mut self) -> Result<u32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.read_slice(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+    fn read_u64(&mut self) -> Result<u64> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_slice(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+    fn read_bytes(&mut self, max_len: usize) -> Result<&'a [u8]> {
+        let (len, header) = match self.read_slice(1)?[0] {
+            254 => {
+                let len = self.read_slice(3)?;
+                (len[0] as usize | (len[1] as usize) << 8 | (len[2] as usize) << 16, 4)
+            }
+            255 => fail!("Wrong length prefix of TL bytes field"),
+            len => (len as usize, 1)
+        };
+        if len > max_len {
+            fail!("RMQ block payload field of {} bytes, at most {} are allowed", len, max_len)
+        }
+        let value = self.read_slice(len)?;
+        self.read_slice((4 - (header + len) % 4) % 4)?;
+        Ok(value)
+    }
+    fn finish(&self) -> Result<()> {
+        if self.remaining() != 0 {
+            fail!("Extra {} bytes after RMQ block payload", self.remaining())
+        }
+        Ok(())
+    }
+}
+
+/// Payload of REMP catchain block: batch of TL-serialized RempCatchainRecords
+#[derive(Debug, Default, PartialEq)]
+pub struct RmqBlockPayload {
+    pub producer_idx: u32,
+    pub timestamp_ms: u64,
+    pub records: Vec<ton_api::ton::bytes>,
+}
+
+impl RmqBlockPayload {
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let mut writer = TlWriter::default();
+        writer.write_u32(RMQ_BLOCK_PAYLOAD_ID);
+        self.serialize_bare(&mut writer)?;
+        Ok(writer.data)
+    }
+
+    pub fn serialize_compressed(&self) -> Result<Vec<u8>> {
+        let mut body = TlWriter::default();
+        self.serialize_bare(&mut body)?;
+        let mut writer = TlWriter::default();
+        writer.write_u32(RMQ_BLOCK_PAYLOAD_COMPRESSED_ID);
+        writer.write_bytes(&zstd::bulk::compress(&body.data, PAYLOAD_COMPRESSION_LEVEL)?)?;
+        Ok(writer.data)
+    }
+
+    fn serialize_bare(&self, writer: &mut TlWriter) -> Result<()> {
+        writer.write_u32(self.producer_idx);
+        writer.write_u64(self.timestamp_ms);
+        writer.write_u32(self.records.len() as u32);
+        for record in &self.records {
+            writer.write_bytes(&record.0)?;
+        }
+        Ok(())
+    }
+
+    /// Decodes native payload or legacy one (records in signatures of validator session
+    /// Commit messages), which is sent by nodes without `native_catchain_payload` option.
+    /// Records of native payload larger than `max_record_size` are malformed.
+    pub fn deserialize(data: &[u8], source_idx: u32, max_record_size: usize) -> Result<Self> {
+        let mut reader = TlReader::new(data);
+        match reader.read_u32() {
+            Ok(RMQ_BLOCK_PAYLOAD_ID) => {
+                let payload = Self::deserialize_bare(&mut reader, max_record_size)?;
+                reader.finish()?;
+                Ok(payload)
+            }
+            Ok(RMQ_BLOCK_PAYLOAD_COMPRESSED_ID) => {
+                let compressed = reader.read_bytes(reader.remaining())?;
+                reader.finish()?;
+                let data = zstd::bulk::decompress(compressed, MAX_DECOMPRESSED_PAYLOAD_SIZE)?;
+                let mut reader = TlReader::new(&data);
+                let payload = Self::deserialize_bare(&mut reader, max_record_size)?;
+                reader.finish()?;
+                Ok(payload)
+            }
+            _ => Self::deserialize_legacy(data, source_idx)
+        }
+    }
+
+    fn deserialize_bare(reader: &mut TlReader, max_record_size: usize) -> Result<Self> {
+        let producer_idx = reader.read_u32()?;
+        let timestamp_ms = reader.read_u64()?;
+        let count = reader.read_u32()? as usize;
+        // each record takes at least 4 bytes
+        if count > reader.remaining() / 4 {
+            fail!("RMQ block payload of {} bytes can't contain {} records", reader.remaining(), count)
+        }
+        let mut records = Vec::with_capacity(count);
+        for _ in 0..count {
+            records.push(reader.read_bytes(max_record_size)?.to_vec().into());
+        }
+        Ok(Self { producer_idx, timestamp_ms, records })
+    }
+
+    fn deserialize_legacy(data: &[u8], source_idx: u32) -> Result<Self> {
+        let payload: ::ton_api::ton::validator_session::BlockUpdate =
+            catchain::utils::deserialize_tl_boxed_object(&data.to_vec().into())?;
+        let ::ton_api::ton::validator_session::BlockUpdate::ValidatorSession_BlockUpdate(payload) = payload;
+        let mut records = Vec::new();
+        for action in payload.actions.0 {
+            match action {
+                ::ton_api::ton::validator_session::round::Message::ValidatorSession_Message_Commit(msg) =>
+                    records.push(msg.signature),
+                _ => log::error!(target: "remp", "Legacy RMQ payload from {}: only Commit messages are expected", source_idx)
+            }
+        }
+        Ok(Self { producer_idx: source_idx, timestamp_ms: 0, records })
+    }
+
+    fn serialize_legacy(&self) -> ton_api::ton::bytes {
+        let actions = self.records.iter().map(|record|
+            ::ton_api::ton::validator_session::round::validator_session::message::message::Commit {
+                round: 0,
+                candidate: Default::default(),
+                signature: record.clone()
+            }.into_boxed()
+        ).collect::<Vec<_>>();
+        let payload = ::ton_api::ton::validator_session::blockupdate::BlockUpdate {
+            ts: 0,
+            actions: actions.into(),
+            state: 0
+        }.into_boxed();
+        serialize_tl_boxed_object!(&payload)
+    }
+}
+
+// Tag of query of REMP catchain peers about message status
+const RMQ_STATUS_QUERY_TAG: u32 = 0x51514d52; // "RMQQ"
+
+/// Query of REMP catchain peer about status of the message
+#[derive(Debug, PartialEq)]
+pub struct RmqStatusQuery {
+    pub message_id: UInt256,
+}
+
+impl RmqStatusQuery {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut data = RMQ_STATUS_QUERY_TAG.to_le_bytes().to_vec();
+        data.extend_from_slice(self.message_id.as_slice());
+        data
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self> {
+        if data.len() != 36 || data[0..4] != RMQ_STATUS_QUERY_TAG.to_le_bytes() {
+            fail!("Wrong RMQ status query: {}", hex::encode(data))
+        }
+        Ok(Self { message_id: UInt256::from_slice(&data[4..]) })
+    }
+
+    /// Answer is empty if the message is absent, TL-serialized status otherwise
+    pub fn serialize_answer(status: Option<&RempMessageStatus>) -> Vec<u8> {
+        match status {
+            None => Vec::new(),
+            Some(status) => serialize_tl_boxed_object!(status).0
+        }
+    }
+
+    pub fn deserialize_answer(data: &[u8]) -> Result<Option<RempMessageStatus>> {
+        if data.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(catchain::utils::deserialize_tl_boxed_object(&data.to_vec().into())?))
+        }
+    }
+}
+
 pub struct RempCatchainInstanceImpl {
-    pub catchain_ptr: CatchainPtr,
+    // replaced when the session is restarted, channels are kept
+    catchain_ptr: arc_swap::ArcSwap<CatchainPtr>,
 
     pending_messages_queue_receiver: crossbeam_channel::Receiver<RempCatchainRecord>,
     pub pending_messages_queue_sender: crossbeam_channel::Sender<RempCatchainRecord>,
 
+    // records are consumed in catchain delivery order: they are put only by catchain
+    // listener callbacks (blocks and broadcasts), which are called by catchain one by one
     pub rmq_catchain_receiver: crossbeam_channel::Receiver<RempCatchainRecord>,
-    rmq_catchain_sender: crossbeam_channel::Sender<RempCatchainRecord>
+    rmq_catchain_sender: crossbeam_channel::Sender<RempCatchainRecord>,
+
+    // shared with the session, enqueued records reset backed off idle timeout
+    pacer: Arc<Mutex<IdlePacer>>,
 }
 
 impl RempCatchainInstanceImpl {
-    fn new(catchain_ptr: CatchainPtr) -> Self {
+    fn new(catchain_ptr: CatchainPtr, capacity: usize, pacer: Arc<Mutex<IdlePacer>>) -> Self {
         let (pending_messages_queue_sender, pending_messages_queue_receiver) = 
-            crossbeam_channel::unbounded();
+            crossbeam_channel::bounded(capacity);
         let (rmq_catchain_sender, rmq_catchain_receiver) = 
-            crossbeam_channel::unbounded();
+            crossbeam_channel::bounded(capacity);
         Self {
-            catchain_ptr,
+            catchain_ptr: arc_swap::ArcSwap::from_pointee(catchain_ptr),
             pending_messages_queue_sender, pending_messages_queue_receiver,
-            rmq_catchain_sender, rmq_catchain_receiver
+            rmq_catchain_sender, rmq_catchain_receiver,
+            pacer,
         }
     }
+
+    pub fn catchain_ptr(&self) -> CatchainPtr {
+        self.catchain_ptr.load().as_ref().clone()
+    }
+
+    fn replace_catchain_ptr(&self, catchain_ptr: CatchainPtr) -> CatchainPtr {
+        self.catchain_ptr.swap(Arc::new(catchain_ptr)).as_ref().clone()
+    }
 }
 
 pub struct RempCatchainInstance {
@@ -103,18 +415,43 @@ impl RempCatchainInstance {
     }
 
     pub fn get_session(&self) -> Option<CatchainPtr> {
-        self.instance_impl.load().map(|inst| inst.catchain_ptr.clone())
+        self.instance_impl.load().as_ref().map(|inst| inst.catchain_ptr())
+    }
+
+    /// Sends the record via catchain overlay broadcast, bypassing catchain blocks
+    pub fn broadcast(&self, record: &RempCatchainRecord) -> Result<()> {
+        let session = self.get_session()
+            .ok_or_else(|| error!("RMQ {}: catchain session is not initialized, cannot broadcast", self))?;
+        let payload = RmqBlockPayload {
+            producer_idx: self.info.local_idx as u32,
+            timestamp_ms: unix_time_ms(),
+            records: vec![RmqMessage::serialize(record)?]
+        };
+        session.send_broadcast(CatchainFactory::create_block_payload(payload.serialize()?.into()));
+        metrics::increment_counter!("remp_catchain_broadcasts_sent");
+        Ok(())
     }
 
+    /// Fails if the queue is full: the caller should submit the record later
     pub fn pending_messages_queue_send(&self, msg: RempCatchainRecord) -> Result<()> {
         let instance = self.get_instance_impl()?;
-        match instance.pending_messages_queue_sender.send(msg) {
-            Ok(()) => Ok(()),
+        match instance.pending_messages_queue_sender.try_send(msg) {
+            Ok(()) => {
+                // Record is not to wait for the next block with backed off idle timeout
+                if let Some(idle_timeout) = instance.pacer.lock().unwrap().on_enqueue() {
+                    log::debug!(target: "remp", "RMQ {}: idle timeout is reset to {} ms", self, idle_timeout.as_millis());
+                    instance.catchain_ptr().set_idle_timeout(idle_timeout);
+                }
+                Ok(())
+            }
+            Err(crossbeam_channel::TrySendError::Full(_)) => {
+                metrics::increment_counter!("remp_pending_messages_queue_full");
+                fail!("pending_messages_queue_sender: queue is full ({} records)", instance.pending_messages_queue_sender.len())
+            }
             Err(e) => fail!("pending_messages_queue_sender: send error {}", e)
         }
     }
 
-    #[cfg(feature = "telemetry")]
     pub fn pending_messages_queue_len(&self) -> Result<usize> {
         let instance = self.get_instance_impl()?;
         Ok(instance.pending_messages_queue_sender.len())
@@ -143,11 +480,24 @@ impl RempCatchainInstance {
         }
     }
 
+    /// If the queue is full, the oldest record is dropped
     pub fn rmq_catchain_send(&self, msg: RempCatchainRecord) -> Result<()> {
         let instance = self.get_instance_impl()?;
-        match instance.rmq_catchain_sender.send(msg) {
-            Ok(()) => Ok(()),
-            Err(e) => fail!("rmq_cathcain_sender: send error {}", e)
+        let mut record = msg;
+        loop {
+            match instance.rmq_catchain_sender.try_send(record) {
+                Ok(()) => return Ok(()),
+                Err(crossbeam_channel::TrySendError::Full(returned)) => {
+                    if let Ok(dropped_record) = instance.rmq_catchain_receiver.try_recv() {
+                        metrics::increment_counter!("remp_catchain_records_dropped");
+                        log::warn!(target: "remp", "RMQ {}: rmq_catchain queue is full, record {} is dropped",
+                            self, get_remp_catchain_record_info(&dropped_record)
+                        );
+                    }
+                    record = returned;
+                }
+                Err(e) => fail!("rmq_cathcain_sender: send error {}", e)
+            }
         }
     }
 
@@ -162,6 +512,378 @@ impl fmt::Display for RempCatchainInstance {
     }
 }
 
+/// Priority of pending record: kind (rejects, messages to priority accounts,
+/// other messages) and import fee of the message (if messages are ordered by it)
+type RecordPriority = (u8, u128);
+
+/// Order of sending pending records to catchain, when not all of them fit into one block
+pub struct RecordPriorityPolicy {
+    accounts: HashSet<String>,
+    by_import_fee: bool,
+}
+
+impl RecordPriorityPolicy {
+    pub fn new(options: &RempConfig) -> Self {
+        let mut accounts = HashSet::new();
+        for account in options.get_priority_accounts() {
+            match MsgAddressInt::from_str(account) {
+                Ok(address) => { accounts.insert(address.to_string()); }
+                Err(e) => log::error!(target: "remp", "Wrong REMP priority account {}: {}", account, e)
+            }
+        }
+        Self { accounts, by_import_fee: options.is_prioritize_by_import_fee() }
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.accounts.is_empty() || self.by_import_fee
+    }
+
+    pub fn priority(&self, record: &RempCatchainRecord) -> RecordPriority {
+        if !self.is_enabled() {
+            return (0, 0)
+        }
+        let message = match record {
+            RempCatchainRecord::TonNode_RempCatchainMessageDigest(_) => return (2, 0),
+            RempCatchainRecord::TonNode_RempCatchainMessage(message) => message
+        };
+        let header = match Message::construct_from_bytes(&message.message) {
+            Ok(message) => match message.ext_in_header() {
+                Some(header) => header.clone(),
+                None => return (0, 0)
+            },
+            Err(_) => return (0, 0)
+        };
+        let kind = self.accounts.contains(&header.dst.to_string()) as u8;
+        let fee = if self.by_import_fee { header.import_fee.as_u128() } else { 0 };
+        (kind, fee)
+    }
+}
+
+/// Pending record taken from the queue; records of equal priority keep order of arrival
+struct PendingRecord {
+    priority: RecordPriority,
+    seqno: u64,
+    record: RempCatchainRecord,
+}
+
+impl Ord for PendingRecord {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.priority.cmp(&other.priority).then_with(|| other.seqno.cmp(&self.seqno))
+    }
+}
+
+impl PartialOrd for PendingRecord {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for PendingRecord {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for PendingRecord {}
+
+/// Logic of RMQ catchain session, separated from catchain and engine: builds payloads
+/// of outgoing blocks from pending records (Point 3) and decodes records of incoming
+/// blocks and broadcasts (Point 4). `RempCatchain` feeds it with catchain events and
+/// applies the decoded records to the message queue.
+pub struct RmqProcessor {
+    // session name for logs
+    name: String,
+    local_idx: u32,
+    nodes_count: usize,
+    max_message_size: usize,
+    max_payload_size: usize,
+    queue_capacity: usize,
+    verify_records: bool,
+    native_payload: bool,
+    compress_payload: bool,
+    priority_policy: RecordPriorityPolicy,
+    // records taken from pending queue, which are not sent yet (highest priority first)
+    pending_records: Mutex<BinaryHeap<PendingRecord>>,
+    pending_seqno: AtomicU64,
+}
+
+impl RmqProcessor {
+    pub fn new(
+        name: String,
+        local_idx: u32,
+        nodes_count: usize,
+        shard: &ShardIdent,
+        options: &RempConfig
+    ) -> Self {
+        Self {
+            name,
+            local_idx,
+            nodes_count,
+            max_message_size: options.get_max_message_size(),
+            max_payload_size: options.get_max_catchain_payload_size(shard),
+            queue_capacity: options.get_catchain_queue_capacity(),
+            verify_records: options.is_verify_catchain_records(),
+            native_payload: options.is_native_catchain_payload(),
+            compress_payload: options.is_compress_catchain_payload(),
+            priority_policy: RecordPriorityPolicy::new(options),
+            pending_records: Mutex::new(BinaryHeap::new()),
+            pending_seqno: AtomicU64::new(0),
+        }
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.pending_records.lock().unwrap().len()
+    }
+
+    /// Records of incoming payloads larger than this are malformed
+    fn max_record_size(&self) -> usize {
+        self.max_message_size + MAX_RECORD_OVERHEAD
+    }
+
+    /// Reject digests are split so that each part fits `max_record_size`,
+    /// parts of the digest are processed independently
+    fn split_record(&self, record: RempCatchainRecord) -> Vec<RempCatchainRecord> {
+        let max_ids = (self.max_message_size / DIGEST_MESSAGE_IDS_SIZE).max(1);
+        match record {
+            RempCatchainRecord::TonNode_RempCatchainMessageDigest(digest)
+                if digest.messages.0.len() > max_ids =>
+            {
+                digest.messages.0.chunks(max_ids).map(|ids| {
+                    let mut part = RempCatchainMessageDigest::default();
+                    part.masterchain_seqno = digest.masterchain_seqno;
+                    part.messages.0 = ids.to_vec();
+                    RempCatchainRecord::TonNode_RempCatchainMessageDigest(part)
+                }).collect()
+            }
+            record => vec![record]
+        }
+    }
+
+    /// Catchain blocks are signed by their sources, so a message record is genuine if its
+    /// source is the source of the block. Mismatches are rejected if `verify_catchain_records`
+    /// is set (nodes without the option forward messages with source of the previous session).
+    fn is_record_source_valid(&self, record_source_idx: i32, block_source_idx: u32) -> bool {
+        if record_source_idx < 0 || record_source_idx as usize >= self.nodes_count {
+            return false
+        }
+        if record_source_idx as u32 == block_source_idx {
+            return true
+        }
+        metrics::increment_counter!("remp_catchain_records_source_mismatch");
+        log::warn!(target: "remp", "Point 4. RMQ {}: message record in block of {} claims source {}",
+            self, block_source_idx, record_source_idx
+        );
+        !self.verify_records
+    }
+
+    /// Takes pending records (highest priority first) while their total size fits
+    /// `max_catchain_payload_size`; records which don't fit are sent in the next blocks.
+    /// New records are taken from `next_record` until the pending heap is full.
+    fn collect_payload_records(
+        &self,
+        payload: &mut RmqBlockPayload,
+        msg_ids: &mut Vec<String>,
+        mut next_record: impl FnMut() -> Option<RempCatchainRecord>
+    ) {
+        let mut pending = self.pending_records.lock().unwrap();
+        while pending.len() < self.queue_capacity {
+            match next_record() {
+                Some(record) => for record in self.split_record(record) {
+                    pending.push(PendingRecord {
+                        priority: self.priority_policy.priority(&record),
+                        seqno: self.pending_seqno.fetch_add(1, Ordering::Relaxed),
+                        record
+                    })
+                },
+                None => break
+            }
+        }
+        let mut size = 0;
+        while let Some(top) = pending.peek() {
+            let record = match RmqMessage::serialize(&top.record) {
+                Ok(record) => record,
+                Err(e) => {
+                    log::error!(target: "remp", "Point 3. RMQ {}: cannot serialize message {:?}: {}", self, top.record, e);
+                    pending.pop();
+                    continue
+                }
+            };
+            if !payload.records.is_empty() && size + record.0.len() > self.max_payload_size {
+                log::trace!(target: "remp", "Point 3. RMQ {}: payload size limit is reached, {} records deferred to the next block",
+                    self, pending.len()
+                );
+                metrics::increment_counter!("remp_catchain_payload_deferred");
+                break
+            }
+            let msg = match pending.pop() {
+                Some(top) => top.record,
+                None => break
+            };
+            log::trace!(target: "remp", "Point 3. RMQ {} sending message: {:?}, decoded {:?}",
+                self, record.0, msg
+            );
+            size += record.0.len();
+            payload.records.push(record);
+            msg_ids.push(get_remp_catchain_record_info(&msg));
+        }
+    }
+
+    /// Builds payload of the next outgoing block; returns serialized payload
+    /// and descriptions of the records put into it
+    pub fn build_payload(
+        &self,
+        timestamp_ms: u64,
+        next_record: impl FnMut() -> Option<RempCatchainRecord>
+    ) -> Result<(ton_api::ton::bytes, Vec<String>)> {
+        let mut payload = RmqBlockPayload {
+            producer_idx: self.local_idx,
+            timestamp_ms,
+            records: Vec::new()
+        };
+        let mut msg_ids = Vec::new();
+        self.collect_payload_records(&mut payload, &mut msg_ids, next_record);
+        let serialized = if !self.native_payload {
+            payload.serialize_legacy()
+        } else if self.compress_payload {
+            payload.serialize_compressed()?.into()
+        } else {
+            payload.serialize()?.into()
+        };
+        Ok((serialized, msg_ids))
+    }
+
+    /// Decodes records of a block produced by `source_idx`; records which are
+    /// too large, forged or malformed are skipped
+    pub fn decode_block(&self, data: &[u8], source_idx: u32) -> Vec<RempCatchainRecord> {
+        let mut records = Vec::new();
+        let pld = match RmqBlockPayload::deserialize(data, source_idx, self.max_record_size()) {
+            Ok(pld) => pld,
+            Err(e) => {
+                log::error!(target: "remp", "Cannot deserialize RMQ {} message: {}", self, e);
+                return records
+            }
+        };
+        if pld.producer_idx != source_idx {
+            log::warn!(target: "remp", "Point 4. RMQ {}: payload of {} is produced by {}",
+                self, source_idx, pld.producer_idx
+            );
+        }
+        for record in pld.records.iter() {
+            match RmqMessage::deserialize(record) {
+                Ok(RempCatchainRecord::TonNode_RempCatchainMessage(record))
+                    if record.message.0.len() > self.max_message_size =>
+                {
+                    log::error!(target: "remp",
+                        "Point 4. RMQ {}: message {:x} from {} is too large: {} (max {}), skipped",
+                        self, record.message_id, source_idx, record.message.0.len(), self.max_message_size
+                    )
+                },
+                Ok(RempCatchainRecord::TonNode_RempCatchainMessage(record))
+                    if !self.is_record_source_valid(record.source_idx, source_idx) =>
+                {
+                    metrics::increment_counter!("remp_catchain_records_forged");
+                    log::error!(target: "remp",
+                        "Point 4. RMQ {}: message {:x} in block of {} claims source {}, skipped",
+                        self, record.message_id, source_idx, record.source_idx
+                    )
+                },
+                Ok(unpacked_message) => {
+                    log::trace!(target: "remp",
+                        "Point 4. Message received from RMQ {}: {:?}, decoded {:?}",
+                        self, record.0, unpacked_message
+                    );
+                    records.push(unpacked_message)
+                },
+                Err(e) => log::error!(target: "remp", "Cannot deserialize message from RMQ {} {:?}: {}",
+                    self, record.0, e
+                )
+            }
+        }
+        records
+    }
+
+    /// Decodes broadcast: only reject digests are broadcast, other records are skipped
+    pub fn decode_broadcast(&self, data: &[u8], source_id: &PublicKeyHash) -> Vec<RempCatchainRecord> {
+        let payload = match RmqBlockPayload::deserialize(data, u32::MAX, self.max_record_size()) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::error!(target: "remp", "RMQ {}: cannot deserialize broadcast from {}: {}", self, source_id, e);
+                return Vec::new()
+            }
+        };
+        let mut digests = Vec::new();
+        for record in payload.records.iter() {
+            match RmqMessage::deserialize(record) {
+                Ok(digest @ RempCatchainRecord::TonNode_RempCatchainMessageDigest(_)) => digests.push(digest),
+                Ok(other) => log::error!(target: "remp", "RMQ {}: unexpected broadcast record from {}: {}",
+                    self, source_id, get_remp_catchain_record_info(&other)
+                ),
+                Err(e) => log::error!(target: "remp", "RMQ {}: cannot deserialize broadcast record from {}: {}",
+                    self, source_id, e
+                )
+            }
+        }
+        digests
+    }
+}
+
+impl fmt::Display for RmqProcessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Adaptive pacing of catchain blocks: idle timeout is doubled after `idle_rounds` blocks
+/// without records in a row (up to `max_timeout`) and is reset to `base_timeout` by any record
+struct IdlePacer {
+    base_timeout: Duration,
+    max_timeout: Option<Duration>,
+    idle_rounds: u32,
+    current_timeout: Duration,
+    empty_blocks: u32,
+}
+
+impl IdlePacer {
+    fn new(base_timeout: Duration, max_timeout: Option<Duration>, idle_rounds: u32) -> Self {
+        Self { base_timeout, max_timeout, idle_rounds, current_timeout: base_timeout, empty_blocks: 0 }
+    }
+
+    fn reset(&mut self) {
+        self.current_timeout = self.base_timeout;
+        self.empty_blocks = 0;
+    }
+
+    /// Accounts enqueued record; returns base idle timeout if it was backed off
+    fn on_enqueue(&mut self) -> Option<Duration> {
+        self.empty_blocks = 0;
+        if self.current_timeout == self.base_timeout {
+            return None
+        }
+        self.current_timeout = self.base_timeout;
+        Some(self.current_timeout)
+    }
+
+    /// Accounts produced block; returns new idle timeout if it is to be changed
+    fn on_block(&mut self, records: usize) -> Option<Duration> {
+        let max_timeout = self.max_timeout?;
+        if records > 0 {
+            self.empty_blocks = 0;
+            if self.current_timeout == self.base_timeout {
+                return None
+            }
+            self.current_timeout = self.base_timeout;
+            return Some(self.current_timeout)
+        }
+        self.empty_blocks += 1;
+        if self.empty_blocks < self.idle_rounds || self.current_timeout >= max_timeout {
+            return None
+        }
+        self.empty_blocks = 0;
+        self.current_timeout = (self.current_timeout * 2).min(max_timeout);
+        Some(self.current_timeout)
+    }
+}
+
 pub struct RempCatchainInfo {
     pub general_session_info: Arc<GeneralSessionInfo>,
     pub master_cc_range: RangeInclusive<u32>,
@@ -262,24 +984,41 @@ impl RempCatchainInfo {
         *self.master_cc_range.end()
     }
 
+    /// Overlay id of the catchain session, computed the same way as catchain does;
+    /// catchain DB of the session is named after it
+    pub fn catchain_overlay_id(&self) -> Result<UInt256> {
+        let first_block = ton_api::ton::catchain::firstblock::Firstblock {
+            unique_hash: self.queue_id.clone().into(),
+            nodes: self.nodes.iter()
+                .map(|node| catchain::utils::public_key_hash_to_int256(
+                    &catchain::utils::get_public_key_hash(&node.public_key)
+                ))
+                .collect::<Vec<_>>().into()
+        }.into_boxed();
+        catchain::utils::get_overlay_id(&first_block)
+    }
+
     pub fn master_cc_range_info(&self) -> String {
         format!("{}..={}", self.master_cc_range.start(), self.master_cc_range.end())
     }
 
     pub fn is_same_catchain(&self, other: Arc<RempCatchainInfo>) -> bool {
+        self.master_cc_range == other.master_cc_range && self.is_same_session(&other)
+    }
+
+    /// Same catchain session, master cc range aside: the range of a pre-started session
+    /// is known only when the session becomes current
+    pub fn is_same_session(&self, other: &RempCatchainInfo) -> bool {
         let is_same_nodelist = 
             self.nodes.len() == other.nodes.len() &&
             self.nodes.iter().zip (other.nodes.iter()).all(|(a,b)| a.adnl_id == b.adnl_id);
 
-        let general_eq = self.queue_id == other.queue_id &&
+        self.queue_id == other.queue_id &&
             self.general_session_info == other.general_session_info &&
             self.local_idx == other.local_idx &&
             self.local_key_id == other.local_key_id &&
             is_same_nodelist &&
-            self.node_list_id == other.node_list_id &&
-            self.master_cc_range == other.master_cc_range;
-
-        general_eq
+            self.node_list_id == other.node_list_id
     }
 }
 
@@ -294,6 +1033,22 @@ pub struct RempCatchain {
     remp_manager: Arc<RempManager>,
 
     info: Arc<RempCatchainInfo>,
+    transcript: Option<Arc<CatchainTranscript>>,
+
+    // kept to restart the session by operator's request
+    local_key: Mutex<Option<PrivateKey>>,
+    created_at: u64,
+    last_block_received_at: AtomicU64,
+    last_block_sent_at: AtomicU64,
+    restarts: AtomicU32,
+    last_restart_at: AtomicU64,
+    start_failures: AtomicU32,
+    // count of message queues attached to the session
+    attached: AtomicU32,
+    // hashes of received broadcasts, to skip repeated ones
+    broadcasts_received: DashSet<UInt256>,
+    processor: RmqProcessor,
+    pacer: Arc<Mutex<IdlePacer>>,
 
     pub instance: RempCatchainInstance
 }
@@ -302,7 +1057,8 @@ impl RempCatchain {
     pub fn create(
         engine: Arc<dyn EngineOperations>,
         remp_manager: Arc<RempManager>,
-        info: Arc<RempCatchainInfo>
+        info: Arc<RempCatchainInfo>,
+        transcript: Option<Arc<CatchainTranscript>>
     ) -> Result<Self> {
         let node_list_string: String = info.nodes.iter().map(
             |x| format!("{:x}/{:x} ",
@@ -317,9 +1073,35 @@ impl RempCatchain {
             info.master_cc_range.start(), info.master_cc_range.end()
         );
 
+        let base_idle_timeout = remp_manager.options.get_catchain_options(&info.general_session_info.shard)
+            .map_or(Duration::default(), |opts| opts.idle_timeout);
+        let pacer = IdlePacer::new(
+            base_idle_timeout,
+            remp_manager.options.get_catchain_max_idle_timeout(),
+            remp_manager.options.get_catchain_idle_rounds()
+        );
+
         return Ok(Self {
             engine,
             info: info.clone(),
+            transcript,
+            local_key: Mutex::new(None),
+            created_at: unix_time_ms(),
+            last_block_received_at: AtomicU64::new(0),
+            last_block_sent_at: AtomicU64::new(0),
+            restarts: AtomicU32::new(0),
+            last_restart_at: AtomicU64::new(0),
+            start_failures: AtomicU32::new(0),
+            attached: AtomicU32::new(0),
+            broadcasts_received: DashSet::default(),
+            processor: RmqProcessor::new(
+                info.to_string(),
+                info.local_idx as u32,
+                info.nodes.len(),
+                &info.general_session_info.shard,
+                &remp_manager.options
+            ),
+            pacer: Arc::new(Mutex::new(pacer)),
             instance: RempCatchainInstance::new(info.clone()),
             remp_manager
         });
@@ -327,12 +1109,17 @@ impl RempCatchain {
 
     pub async fn start(self: Arc<RempCatchain>, local_key: PrivateKey) -> Result<CatchainPtr> {
         let overlay_manager: CatchainOverlayManagerPtr =
-            Arc::new(CatchainOverlayManagerImpl::new(self.engine.validator_network(), self.info.node_list_id.clone()));
-        let db_root = format!("{}/rmq", self.engine.db_root_dir()?);
+            Arc::new(CatchainOverlayManagerImpl::new(
+                self.engine.validator_network(), self.info.node_list_id.clone(), QosClass::Remp
+            ));
+        let db_root = self.remp_manager.options.get_catchain_db_path(self.engine.db_root_dir()?);
         let db_suffix = "".to_string();
         let allow_unsafe_self_blocks_resync = false;
 
         let message_listener = Arc::downgrade(&self);
+        *self.local_key.lock().unwrap() = Some(local_key.clone());
+        // New catchain starts with idle timeout from options
+        self.pacer.lock().unwrap().reset();
 
         log::info!(target: "remp", "Do starting RMQ Catchain session {} list_id={} with nodes {:?}",
             self,
@@ -340,7 +1127,9 @@ impl RempCatchain {
             self.info.nodes.iter().map(|x| x.adnl_id.to_string()).collect::<Vec<String>>()
         );
 
-        let rmq_catchain_options = self.remp_manager.options.get_catchain_options().ok_or_else(
+        let rmq_catchain_options = self.remp_manager.options.get_catchain_options(
+            &self.info.general_session_info.shard
+        ).ok_or_else(
             || error!("RMQ {}: cannot get REMP catchain options, start is impossible", self)
         )?;
 
@@ -372,53 +1161,95 @@ impl RempCatchain {
         Ok(())
     }
 
-    fn unpack_payload(&self, payload: &BlockPayloadPtr, source_idx: u32) {
-        log::trace!(target: "remp", "RMQ {} unpacking message {:?} from {}", self, payload.data().0, source_idx);
+    /// Starts catchain session; failed attempts are repeated with exponential backoff
+    async fn start_with_retries(self: Arc<RempCatchain>, local_key: PrivateKey) -> Result<CatchainPtr> {
+        let attempts = self.remp_manager.options.get_catchain_start_attempts();
+        let mut backoff = REMP_CATCHAIN_START_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            let err = match self.clone().start(local_key.clone()).await {
+                Ok(catchain_ptr) => return Ok(catchain_ptr),
+                Err(e) => e
+            };
+            self.start_failures.fetch_add(1, Ordering::Relaxed);
+            metrics::increment_counter!("remp_catchain_start_failures");
+            if attempt >= attempts {
+                metrics::increment_counter!("remp_catchain_start_aborted");
+                log::error!(target: "remp", "RMQ {}: cannot start catchain session, {} attempts failed, last error: {}",
+                    self, attempt, err
+                );
+                return Err(err)
+            }
+            log::warn!(target: "remp", "RMQ {}: start attempt {} failed: {}, retrying in {}ms",
+                self, attempt, err, backoff.as_millis()
+            );
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(REMP_CATCHAIN_MAX_START_BACKOFF);
+            attempt += 1;
+        }
+    }
 
-        let pld: Result<::ton_api::ton::validator_session::BlockUpdate> =
-            catchain::utils::deserialize_tl_boxed_object(payload.data());
+    /// Session received no blocks during restart timeout since its start or the last restart;
+    /// the timeout is doubled after each restart
+    fn is_dead(&self, now: u64, restart_timeout_sec: u64) -> bool {
+        let restarts = self.restarts.load(Ordering::Relaxed).min(REMP_CATCHAIN_MAX_RESTART_BACKOFF_SHIFT);
+        let timeout_ms = (restart_timeout_sec * 1000) << restarts;
+        let last_activity = self.created_at
+            .max(self.last_restart_at.load(Ordering::Relaxed))
+            .max(self.last_block_received_at.load(Ordering::Relaxed));
+        now.saturating_sub(last_activity) > timeout_ms
+    }
 
-        match pld {
-            Ok(::ton_api::ton::validator_session::BlockUpdate::ValidatorSession_BlockUpdate(pld)) => {
-                #[cfg(feature = "telemetry")]
-                let mut total = 0;
-                for msgbx in pld.actions.0.iter() {
-                    match msgbx {
-                        ::ton_api::ton::validator_session::round::Message::ValidatorSession_Message_Commit(msg) => {
-                            match RmqMessage::deserialize(&msg.signature) {
-                                Ok(unpacked_message) => {
-                                    #[cfg(feature = "telemetry")] {
-                                        total += 1;
-                                    }
-                                    log::trace!(target: "remp",
-                                        "Point 4. Message received from RMQ {}: {:?}, decoded {:?}, put to rmq_catchain queue",
-                                        self, msg.signature.0, unpacked_message //catchain.received_messages.len()
-                                    );
-                                    if let Err(e) = self.instance.rmq_catchain_send(unpacked_message.clone()) {
-                                        log::error!(
-                                            target: "remp", "Point 4. Cannot put message {:?} from RMQ {} to queue: {}",
-                                            unpacked_message, self, e
-                                        )
-                                    }
-                                },
-                                Err(e) => log::error!(target: "remp", "Cannot deserialize message from RMQ {} {:?}: {}",
-                                    self, msg.signature.0, e
-                                )
-                            }
-                        },
-                        _ => log::error!(target: "remp", "Point 4. RMQ {}: only Commit messages are expected", self)
-                    }
-                };
-                #[cfg(feature = "telemetry")] {
-                    self.engine.remp_core_telemetry().got_from_catchain(&self.info.general_session_info.shard, total, 0);
-                    match self.instance.rmq_catchain_receiver_len() {
-                        Ok(len) => self.engine.remp_core_telemetry().in_channel_to_rmq(&self.info.general_session_info.shard, len),
-                        Err(e) => log::error!(target: "remp", "Point 4. RMQ {}: cannot receive rmq_catchain queue len, `{}`", self, e)
-                    };
-                }
+    fn local_key(&self) -> Result<PrivateKey> {
+        self.local_key.lock().unwrap().clone()
+            .ok_or_else(|| error!("RMQ {}: session was not started, no local key", self))
+    }
+
+    fn status_json(&self, status: &RempCatchainStatus) -> serde_json::Value {
+        let queue_len = |len: Result<usize>| match len {
+            Ok(len) => serde_json::Value::from(len),
+            Err(_) => serde_json::Value::Null
+        };
+        serde_json::json!({
+            "session_id": format!("{:x}", self.info.queue_id),
+            "shard": self.info.general_session_info.shard.to_string(),
+            "master_cc_range": [self.info.master_cc_range.start(), self.info.master_cc_range.end()],
+            "local_idx": self.info.local_idx,
+            "status": status.to_string(),
+            "session_active": self.instance.is_session_active(),
+            "pending_messages_queue": queue_len(self.instance.pending_messages_queue_len()),
+            "rmq_catchain_queue": queue_len(self.instance.rmq_catchain_receiver_len()),
+            "created_at_ms": self.created_at,
+            "last_block_received_at_ms": self.last_block_received_at.load(Ordering::Relaxed),
+            "last_block_sent_at_ms": self.last_block_sent_at.load(Ordering::Relaxed),
+            "pending_records": self.processor.pending_len(),
+            "restarts": self.restarts.load(Ordering::Relaxed),
+            "start_failures": self.start_failures.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Puts records of incoming block to the message queue;
+    /// returns them if transcript is recorded for the session
+    fn unpack_payload(&self, payload: &BlockPayloadPtr, source_idx: u32) -> Vec<RempCatchainRecord> {
+        log::trace!(target: "remp", "RMQ {} unpacking message {:?} from {}", self, payload.data().0, source_idx);
+
+        let records = self.processor.decode_block(&payload.data().0, source_idx);
+        for record in records.iter() {
+            if let Err(e) = self.instance.rmq_catchain_send(record.clone()) {
+                log::error!(
+                    target: "remp", "Point 4. Cannot put message {:?} from RMQ {} to queue: {}",
+                    record, self, e
+                )
             }
-            Err(e) => log::error!(target: "remp", "Cannot deserialize RMQ {} message: {}", self, e)
         }
+        #[cfg(feature = "telemetry")] {
+            self.engine.remp_core_telemetry().got_from_catchain(&self.info.general_session_info.shard, records.len(), 0);
+            match self.instance.rmq_catchain_receiver_len() {
+                Ok(len) => self.engine.remp_core_telemetry().in_channel_to_rmq(&self.info.general_session_info.shard, len),
+                Err(e) => log::error!(target: "remp", "Point 4. RMQ {}: cannot receive rmq_catchain queue len, `{}`", self, e)
+            };
+        }
+        if self.transcript.is_some() { records } else { Vec::new() }
     }
 }
 
@@ -434,42 +1265,50 @@ impl CatchainListener for RempCatchain {
         log::trace!(target: "remp", "Preprocessing RMQ {} Message {:?} from {}",
             self, data.data().0, block.get_source_id()
         );
-        self.unpack_payload(data, block.get_source_id());
+        self.last_block_received_at.store(unix_time_ms(), Ordering::Relaxed);
+        let records = self.unpack_payload(data, block.get_source_id());
+        if let Some(transcript) = &self.transcript {
+            transcript.add_block(TranscriptBlock::with_records(&block, &records));
+        }
     }
 
     fn process_blocks(&self, blocks: Vec<BlockPtr>) {
         log::trace!(target: "remp", "Processing RMQ {}: new external messages, len = {}", self, blocks.len());
 
-        let mut msg_vect: Vec<::ton_api::ton::validator_session::round::Message> = Vec::new();
-        let mut msg_ids: Vec<String> = Vec::new();
-
-        if let Ok(Some(msg)) = self.instance.pending_messages_queue_try_recv() {
-            let msg_body = ::ton_api::ton::validator_session::round::validator_session::message::message::Commit {
-                round: 0,
-                candidate: Default::default(),
-                signature: RmqMessage::serialize(&msg).unwrap()
-            }.into_boxed();
-            log::trace!(target: "remp", "Point 3. RMQ {} sending message: {:?}, decoded {:?}",
-                self, msg_body, msg
-            );
-
-            msg_vect.push(msg_body);
-            msg_ids.push(get_remp_catchain_record_info(&msg));
-        }
-
-        let payload = ::ton_api::ton::validator_session::blockupdate::BlockUpdate {
-            ts: 0, //ts as i64,
-            actions: msg_vect.into(),
-            state: 0 //real_state_hash as i32,
-        }.into_boxed();
-        let serialized_payload = serialize_tl_boxed_object!(&payload);
+        let next_record = || self.instance.pending_messages_queue_try_recv().ok().flatten();
+        let (serialized_payload, msg_ids) = match self.processor.build_payload(unix_time_ms(), next_record) {
+            Ok(built) => built,
+            Err(e) => {
+                log::error!(target: "remp", "Point 3. RMQ {}: cannot serialize payload: {}", self, e);
+                return
+            }
+        };
 
         match &self.instance.get_session() {
             Some(ctchn) => {
+                let pending = self.processor.pending_len() +
+                    self.instance.pending_messages_queue_len().unwrap_or_default();
+                if let Some(idle_timeout) = self.pacer.lock().unwrap().on_block(msg_ids.len() + pending) {
+                    log::debug!(target: "remp", "RMQ {}: idle timeout is changed to {} ms", self, idle_timeout.as_millis());
+                    ctchn.set_idle_timeout(idle_timeout);
+                }
+                #[cfg(feature = "telemetry")]
+                let (payload_bytes, prev_sent_at) =
+                    (serialized_payload.0.len(), self.last_block_sent_at.load(Ordering::Relaxed));
                 ctchn.processed_block(
                     catchain::CatchainFactory::create_block_payload(
                         serialized_payload.clone(),
                     ), false, false);
+                self.last_block_sent_at.store(unix_time_ms(), Ordering::Relaxed);
+                #[cfg(feature = "telemetry")] {
+                    let sent_at = self.last_block_sent_at.load(Ordering::Relaxed);
+                    self.engine.remp_core_telemetry().produced_catchain_block(
+                        &self.info.general_session_info.shard,
+                        (prev_sent_at != 0).then(|| sent_at.saturating_sub(prev_sent_at)),
+                        payload_bytes,
+                        pending
+                    );
+                }
                 log::trace!(target: "remp", "Point 3. RMQ {} sent messages: '{:?}'",
                     self, msg_ids
                 );
@@ -493,13 +1332,42 @@ impl CatchainListener for RempCatchain {
         log::trace!(target: "remp", "MessageQueue {} started", self)
     }
 
-    fn process_broadcast(&self, _source_id: PublicKeyHash, _data: BlockPayloadPtr) {
-        log::trace!(target: "remp", "MessageQueue {} process broadcast", self)
+    fn process_broadcast(&self, source_id: PublicKeyHash, data: BlockPayloadPtr) {
+        log::trace!(target: "remp", "MessageQueue {} process broadcast from {}", self, source_id);
+        if !self.broadcasts_received.insert(UInt256::calc_file_hash(&data.data().0)) {
+            metrics::increment_counter!("remp_catchain_broadcasts_duplicate");
+            return
+        }
+        metrics::increment_counter!("remp_catchain_broadcasts_received");
+        // Only reject digests are broadcast: they are processed as if received in a catchain block
+        for digest in self.processor.decode_broadcast(&data.data().0, &source_id) {
+            if let Err(e) = self.instance.rmq_catchain_send(digest) {
+                log::error!(target: "remp", "RMQ {}: cannot put broadcast digest to queue: {}", self, e)
+            }
+        }
     }
 
-    fn process_query(&self, source_id: PublicKeyHash, data: BlockPayloadPtr, _callback: ExternalQueryResponseCallback) {
+    fn process_query(&self, source_id: PublicKeyHash, data: BlockPayloadPtr, callback: ExternalQueryResponseCallback) {
         let data = data.data();
         log::trace!(target: "remp", "Processing RMQ {} Query {:?} from {}", self, data.0.as_slice(), source_id);
+        let now = std::time::Instant::now();
+        metrics::increment_counter!("remp_catchain_queries");
+        let answer = RmqStatusQuery::deserialize(&data.0).and_then(|query| {
+            let status = self.remp_manager.message_cache.get_message_status(&query.message_id)?;
+            log::trace!(target: "remp", "RMQ {}: status of message {:x} for {}: {:?}",
+                self, query.message_id, source_id, status
+            );
+            Ok(RmqStatusQuery::serialize_answer(status.as_ref()))
+        });
+        match answer {
+            Ok(answer) => callback(Ok(CatchainFactory::create_block_payload(answer.into()))),
+            Err(e) => {
+                log::warn!(target: "remp", "RMQ {}: cannot answer query from {}: {}", self, source_id, e);
+                metrics::increment_counter!("remp_catchain_queries_failed");
+                callback(Err(e))
+            }
+        }
+        metrics::histogram!("remp_catchain_query_time", now.elapsed());
     }
 
     fn set_time(&self, _timestamp: SystemTime) {
@@ -507,15 +1375,40 @@ impl CatchainListener for RempCatchain {
     }
 }
 
+/// Brief state of REMP catchain session, listed for operators
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct RempCatchainSessionInfo {
+    pub queue_id: String,
+    pub shard: String,
+    pub status: String,
+    pub nodes: usize,
+    pub attached: u32,
+    pub uptime_ms: u64,
+}
+
 #[derive(Debug,PartialEq,Eq,PartialOrd,Clone)]
 enum RempCatchainStatus {
-    Created, Starting, Active, ToStop, Stopping
+    Created, Scheduled, Starting, Active, ToStop, Stopping
+}
+
+impl RempCatchainStatus {
+    fn to_persistent(&self) -> u8 {
+        self.clone() as u8
+    }
+
+    fn from_persistent(code: u8) -> Option<Self> {
+        [
+            RempCatchainStatus::Created, RempCatchainStatus::Scheduled, RempCatchainStatus::Starting,
+            RempCatchainStatus::Active, RempCatchainStatus::ToStop, RempCatchainStatus::Stopping
+        ].into_iter().find(|status| status.to_persistent() == code)
+    }
 }
 
 impl Display for RempCatchainStatus {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let s = match self {
             RempCatchainStatus::Created => "created",
+            RempCatchainStatus::Scheduled => "scheduled",
             RempCatchainStatus::Starting => "starting",
             RempCatchainStatus::Active => "active",
             RempCatchainStatus::ToStop => "to-stop",
@@ -525,22 +1418,115 @@ impl Display for RempCatchainStatus {
     }
 }
 
+/// What `start_catchain` does with the session id, depending on the status of the session
+/// with the same id in the store
+#[derive(Debug, PartialEq)]
+enum StartDecision {
+    // No session: it is created and started
+    Create,
+    // Session is being started, or the old session is being stopped: wait until it's done
+    Wait,
+    // Session is active: the instance is shared
+    Attach,
+}
+
+fn start_decision(status: Option<&RempCatchainStatus>) -> Result<StartDecision> {
+    match status {
+        None => Ok(StartDecision::Create),
+        Some(RempCatchainStatus::Created) =>
+            fail!("impossible status {:?}", RempCatchainStatus::Created),
+        Some(RempCatchainStatus::Scheduled) | Some(RempCatchainStatus::Starting) |
+        Some(RempCatchainStatus::ToStop) | Some(RempCatchainStatus::Stopping) => Ok(StartDecision::Wait),
+        Some(RempCatchainStatus::Active) => Ok(StartDecision::Attach),
+    }
+}
+
+/// What `stop_catchain` does with the session, depending on its status and count of
+/// message queues attached to it (zero when the session is stopped regardless of queues)
+#[derive(Debug, PartialEq)]
+enum StopDecision {
+    // Session is absent or is being stopped already: nothing to do
+    Done,
+    // Session is being started: it is stopped after start is finished
+    Wait,
+    // Session waits for its activation time: it is removed without stopping
+    Cancel,
+    // Session is active and shared with other queues: the queue is detached from it
+    Detach,
+    // Session is active (or marked to stop): it is stopped and removed
+    Stop,
+}
+
+fn stop_decision(status: Option<&RempCatchainStatus>, attached: u32) -> StopDecision {
+    match status {
+        None | Some(RempCatchainStatus::Stopping) => StopDecision::Done,
+        Some(RempCatchainStatus::Created) | Some(RempCatchainStatus::Starting) => StopDecision::Wait,
+        Some(RempCatchainStatus::Scheduled) => StopDecision::Cancel,
+        Some(RempCatchainStatus::Active) if attached > 1 => StopDecision::Detach,
+        Some(RempCatchainStatus::Active) | Some(RempCatchainStatus::ToStop) => StopDecision::Stop,
+    }
+}
+
+/// What `restart_catchain` does with the session after stopping and starting its catchain
+#[derive(Debug, PartialEq)]
+enum RestartDecision {
+    // Catchain is restarted: the session is active again
+    Activate,
+    // Catchain is not restarted: the session is removed, so it can be started again
+    Remove,
+}
+
+fn restart_decision(restarted: &Result<()>) -> RestartDecision {
+    match restarted {
+        Ok(_) => RestartDecision::Activate,
+        Err(_) => RestartDecision::Remove,
+    }
+}
+
+/// Session is stuck if it stays in starting or stopping state longer than the timeout
+fn is_stuck_in_transition(status: &RempCatchainStatus, status_since_ms: u64, now_ms: u64, timeout_sec: u64) -> bool {
+    matches!(status, RempCatchainStatus::Starting | RempCatchainStatus::Stopping) &&
+        now_ms.saturating_sub(status_since_ms) > timeout_sec * 1000
+}
+
 struct RempCatchainWrapper {
     info: Arc<RempCatchain>,
-    status: RempCatchainStatus
+    status: RempCatchainStatus,
+    // Time of the last status change, ms
+    status_since: u64,
+    // Sessions with the same id, created one after another, have different generations;
+    // the session is changed (activated, removed) only by operations of its own generation
+    generation: u64
 }
 
 impl RempCatchainWrapper {
-    pub fn create(info: Arc<RempCatchain>) -> Self {
+    pub fn create(info: Arc<RempCatchain>, generation: u64) -> Self {
         RempCatchainWrapper {
             info,
-            status: RempCatchainStatus::Created
+            status: RempCatchainStatus::Created,
+            status_since: unix_time_ms(),
+            generation
+        }
+    }
+
+    fn set_status(&mut self, status: RempCatchainStatus) {
+        self.status = status;
+        self.status_since = unix_time_ms();
+    }
+
+    pub fn set_starting(&mut self) -> Result<()> {
+        if self.status == RempCatchainStatus::Scheduled {
+            self.set_status(RempCatchainStatus::Starting);
+            Ok(())
+        }
+        else {
+            fail!("RempCatchainWrapper {}: cannot set starting, incompatible current status", self)
         }
     }
 
     pub fn set_active(&mut self) -> Result<()> {
         if self.status == RempCatchainStatus::Starting {
-            self.status = RempCatchainStatus::Active;
+            self.set_status(RempCatchainStatus::Active);
             Ok(())
         }
         else {
@@ -551,95 +1537,398 @@ impl RempCatchainWrapper {
 
 impl fmt::Display for RempCatchainWrapper {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}({:?})", self.info, self.status)
+        write!(f, "{}({:?}, generation {})", self.info, self.status, self.generation)
     }
 }
 
 pub struct RempCatchainStore {
     catchains: MutexWrapper<HashMap<UInt256, RempCatchainWrapper>>,
+    transcripts: Arc<CatchainTranscriptStore>,
+    // active sessions without blocks for this time are restarted (None - never)
+    restart_timeout_sec: Option<u64>,
+    // sessions in starting/stopping state for this time are reset by watchdog (None - never)
+    transition_timeout_sec: Option<u64>,
+    next_generation: AtomicU64,
+    // Bookkeeping of running sessions, to clean up sessions left after unclean shutdown
+    sessions_db: Option<RempSessionsDb>,
+    // Directory of catchain DBs of the sessions
+    catchains_db_root: String,
+    // Catchain DBs of stopped sessions (by catchain overlay id) with their stop time, ms
+    finished_dbs: Mutex<HashMap<UInt256, u64>>,
+    db_retention_ms: u64,
 }
 
 impl RempCatchainStore {
-    pub fn new() -> Self {
-        RempCatchainStore { catchains: MutexWrapper::new(HashMap::new(), "CatchainStore".to_string()) }
+    pub fn new(transcripts_count: usize) -> Self {
+        RempCatchainStore {
+            catchains: MutexWrapper::new(HashMap::new(), "CatchainStore".to_string()),
+            transcripts: Arc::new(CatchainTranscriptStore::with_capacity(transcripts_count)),
+            restart_timeout_sec: None,
+            transition_timeout_sec: None,
+            next_generation: AtomicU64::new(0),
+            sessions_db: None,
+            catchains_db_root: String::new(),
+            finished_dbs: Mutex::new(HashMap::new()),
+            db_retention_ms: 0
+        }
+    }
+
+    /// Sessions are recorded in `db`; sessions recorded by the previous run and not started
+    /// again are cleaned up (with their catchain DBs in `catchains_db_root`) by GC, when they
+    /// are older than GC horizon
+    pub fn with_persistent_db(mut self, db: RempSessionsDb, catchains_db_root: String) -> Self {
+        let mut left = 0;
+        if let Err(e) = db.for_each_session(&mut |queue_id, entry| {
+            log::info!(target: "remp", "REMP catchain session {:x}/{} (master cc {}, status {:?}) is left from previous run",
+                queue_id, entry.shard, entry.master_cc_seqno, RempCatchainStatus::from_persistent(entry.status)
+            );
+            left += 1;
+            Ok(())
+        }) {
+            log::error!(target: "remp", "Cannot read REMP sessions DB: {}", e);
+        }
+        log::info!(target: "remp", "{} REMP catchain sessions are left from previous run", left);
+        self.sessions_db = Some(db);
+        self.catchains_db_root = catchains_db_root;
+        self
+    }
+
+    fn persist_session(&self, session_id: &UInt256, cc: &RempCatchainWrapper) {
+        let db = match &self.sessions_db {
+            Some(db) => db,
+            None => return
+        };
+        let result = cc.info.info.catchain_overlay_id().and_then(|catchain_overlay_id| {
+            db.put_session(session_id, &RempSessionEntry {
+                shard: cc.info.info.general_session_info.shard.clone(),
+                master_cc_seqno: cc.info.info.get_master_cc_seqno(),
+                status: cc.status.to_persistent(),
+                catchain_overlay_id
+            })
+        });
+        if let Err(e) = result {
+            log::error!(target: "remp", "Cannot save REMP catchain session {} to DB: {}", cc, e);
+        }
+    }
+
+    fn forget_session(&self, session_id: &UInt256) {
+        if let Some(db) = &self.sessions_db {
+            if let Err(e) = db.remove_session(session_id) {
+                log::error!(target: "remp", "Cannot remove REMP catchain session {:x} from DB: {}", session_id, e);
+            }
+        }
+    }
+
+    /// Removes sessions recorded in DB, which are neither running nor alive and belong to
+    /// master catchains before `gc_horizon`, together with their catchain DBs; newer sessions
+    /// are kept, since they can be resumed by starting them again
+    async fn cleanup_orphaned_sessions(&self, alive_sessions: &HashSet<UInt256>, gc_horizon: u32) -> Result<()> {
+        let db = match &self.sessions_db {
+            Some(db) => db,
+            None => return Ok(())
+        };
+        let mut recorded = Vec::new();
+        db.for_each_session(&mut |queue_id, entry| {
+            recorded.push((queue_id, entry));
+            Ok(())
+        })?;
+        let running = self.catchains.execute_sync(|x| x.keys().cloned().collect::<HashSet<_>>()).await;
+        for (queue_id, entry) in recorded {
+            if running.contains(&queue_id) {
+                continue
+            }
+            if alive_sessions.contains(&queue_id) {
+                log::info!(target: "remp", "REMP catchain session {:x}/{} from previous run is to be resumed",
+                    queue_id, entry.shard
+                );
+                continue
+            }
+            if entry.master_cc_seqno >= gc_horizon {
+                log::trace!(target: "remp", "REMP catchain session {:x}/{} from previous run (master cc {}) is not started yet",
+                    queue_id, entry.shard, entry.master_cc_seqno
+                );
+                continue
+            }
+            let catchain_db = self.catchain_db_path(&entry.catchain_overlay_id);
+            log::warn!(target: "remp", "Cleaning up orphaned REMP catchain session {:x}/{}, master cc {}, catchain DB {}",
+                queue_id, entry.shard, entry.master_cc_seqno, catchain_db.display()
+            );
+            if let Err(e) = RocksDb::destroy_db(&catchain_db) {
+                log::error!(target: "remp", "Cannot remove catchain DB {}: {}", catchain_db.display(), e);
+            }
+            db.remove_session(&queue_id)?;
+        }
+        Ok(())
+    }
+
+    fn catchain_db_path(&self, catchain_overlay_id: &UInt256) -> PathBuf {
+        Path::new(&self.catchains_db_root).join(
+            format!("catchainreceiver{}", base64_encode_url_safe(catchain_overlay_id.as_slice()))
+        )
     }
 
-    pub async fn activate_catchain(&self, session_id: &UInt256) -> Result<()> {
+    /// Catchain DB of the stopped session is removed after the retention time
+    fn finish_catchain_db(&self, catchain: &RempCatchain) {
+        if self.catchains_db_root.is_empty() {
+            return
+        }
+        match catchain.info.catchain_overlay_id() {
+            Ok(overlay_id) => {
+                self.finished_dbs.lock().unwrap().insert(overlay_id, unix_time_ms());
+            }
+            Err(e) => log::error!(target: "remp", "Cannot get catchain overlay id of {}: {}", catchain, e)
+        }
+    }
+
+    /// Removes catchain DBs of sessions stopped more than retention time ago; DBs of
+    /// sessions, which are started again, are kept
+    async fn prune_finished_dbs(&self) {
+        let now = unix_time_ms();
+        let expired = {
+            let mut finished_dbs = self.finished_dbs.lock().unwrap();
+            let expired = finished_dbs.iter()
+                .filter(|(_, stopped_at)| now.saturating_sub(**stopped_at) >= self.db_retention_ms)
+                .map(|(overlay_id, _)| overlay_id.clone())
+                .collect::<Vec<_>>();
+            for overlay_id in expired.iter() {
+                finished_dbs.remove(overlay_id);
+            }
+            expired
+        };
+        if expired.is_empty() {
+            return
+        }
+        let running = self.catchains.execute_sync(|x| {
+            x.values().filter_map(|cc| cc.info.info.catchain_overlay_id().ok()).collect::<HashSet<_>>()
+        }).await;
+        for overlay_id in expired {
+            if running.contains(&overlay_id) {
+                continue
+            }
+            let catchain_db = self.catchain_db_path(&overlay_id);
+            if !catchain_db.exists() {
+                continue
+            }
+            log::info!(target: "remp", "Removing catchain DB {} of finished REMP catchain session", catchain_db.display());
+            match RocksDb::destroy_db(&catchain_db) {
+                Ok(_) => metrics::increment_counter!("remp_catchain_db_pruned"),
+                Err(e) => log::error!(target: "remp", "Cannot remove catchain DB {}: {}", catchain_db.display(), e)
+            }
+        }
+    }
+
+    /// Total size of catchain DBs of the sessions, bytes
+    pub fn rmq_storage_size(&self) -> u64 {
+        if self.catchains_db_root.is_empty() {
+            return 0
+        }
+        dir_size(Path::new(&self.catchains_db_root))
+    }
+
+    /// Catchain DBs of stopped sessions are kept for the time (they are removed while
+    /// garbage collecting sessions)
+    pub fn with_db_retention(mut self, db_retention_sec: u64) -> Self {
+        self.db_retention_ms = db_retention_sec * 1000;
+        self
+    }
+
+    /// Dead sessions are restarted while garbage collecting sessions
+    pub fn with_restart_timeout(mut self, restart_timeout_sec: Option<u64>) -> Self {
+        self.restart_timeout_sec = restart_timeout_sec;
+        self
+    }
+
+    /// Stuck sessions are reset by watchdog while garbage collecting sessions
+    pub fn with_transition_timeout(mut self, transition_timeout_sec: Option<u64>) -> Self {
+        self.transition_timeout_sec = transition_timeout_sec;
+        self
+    }
+
+    pub fn transcripts(&self) -> Arc<CatchainTranscriptStore> {
+        self.transcripts.clone()
+    }
+
+    fn create_transcript(&self, info: &RempCatchainInfo) -> Option<Arc<CatchainTranscript>> {
+        if !self.transcripts.is_enabled() {
+            return None
+        }
+        let transcript = Arc::new(CatchainTranscript::new(
+            info.queue_id.clone(),
+            info.general_session_info.shard.clone(),
+            info.master_cc_range.clone(),
+            &info.nodes
+        ));
+        self.transcripts.add(transcript.clone());
+        Some(transcript)
+    }
+
+    async fn activate_catchain(&self, session_id: &UInt256, generation: u64) -> Result<()> {
         self.catchains.execute_sync(|x| {
             match x.get_mut(&session_id) {
-                Some(cc) => cc.set_active(),
+                Some(cc) if cc.generation == generation => {
+                    cc.set_active()?;
+                    self.persist_session(session_id, cc);
+                    Ok(())
+                }
+                Some(cc) => fail!("REMP Catchain session {} start impossible -- session is replaced", cc),
                 None => fail!("REMP Catchain session {:x} start impossible -- session disappeared", session_id)
             }
         }).await
     }
 
+    /// Removes the session, unless it is already replaced by the session of other generation
+    async fn remove_catchain(&self, session_id: &UInt256, generation: u64) -> bool {
+        self.catchains.execute_sync(|x| {
+            let same_generation = x.get(session_id).map_or(false, |cc| cc.generation == generation);
+            if same_generation {
+                x.remove(session_id);
+                self.forget_session(session_id);
+            }
+            same_generation
+        }).await
+    }
+
+    /// Waits until activation time of scheduled session and marks it as starting;
+    /// fails if the session is stopped (or cancelled) before activation
+    async fn wait_activation(&self, session_id: &UInt256, generation: u64, activate_at: u32) -> Result<()> {
+        loop {
+            let delay = activation_delay(activate_at, unix_time_ms());
+            self.catchains.execute_sync(|x| {
+                match x.get_mut(session_id) {
+                    Some(cc) if cc.status == RempCatchainStatus::Scheduled && cc.generation == generation => {
+                        if delay.is_none() {
+                            cc.set_starting()?;
+                        }
+                        Ok(())
+                    }
+                    Some(cc) =>
+                        fail!("REMP Catchain session {} is cancelled before activation", cc),
+                    None =>
+                        fail!("REMP Catchain session {:x} is cancelled before activation -- session disappeared", session_id)
+                }
+            }).await?;
+            match delay {
+                Some(delay) => tokio::time::sleep(delay.min(REMP_CATCHAIN_ACTIVATION_POLLING_INTERVAL)).await,
+                None => return Ok(())
+            }
+        }
+    }
+
+    /// Cancels session which waits for its activation time; returns false if the session
+    /// is not scheduled (absent or already being started)
+    pub async fn cancel_scheduled_catchain(&self, session_id: &UInt256) -> bool {
+        let cancelled = self.catchains.execute_sync(|x| {
+            let scheduled = x.get(session_id)
+                .map_or(false, |cc| cc.status == RempCatchainStatus::Scheduled);
+            if scheduled {
+                self.forget_session(session_id);
+                x.remove(session_id).map(|cc| cc.info)
+            } else {
+                None
+            }
+        }).await;
+        match cancelled {
+            Some(catchain) => {
+                log::info!(target: "remp", "Scheduled REMP catchain {} is cancelled", catchain);
+                if let Some(transcript) = &catchain.transcript {
+                    transcript.finish();
+                }
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Starts catchain session; if `activate_at` (UNIX time) is in the future, the session
+    /// is registered as scheduled and actually created at the activation time
     pub async fn start_catchain(&self,
                                 engine: Arc<dyn EngineOperations>,
                                 remp_manager: Arc<RempManager>,
                                 to_start: Arc<RempCatchainInfo>,
-                                local_key: PrivateKey
+                                local_key: PrivateKey,
+                                activate_at: Option<u32>
     ) -> Result<Arc<RempCatchainInstanceImpl>> {
         let session_id = &to_start.queue_id;
         log::trace!(target: "remp", "Starting REMP catchain {:x}", session_id);
-
-        let (catchain_info, do_start) = loop {
-            let (cc_status, catchain_info) = self.catchains.execute_sync(|x| {
-                match x.get_mut(&session_id) {
-                    Some(existing @ RempCatchainWrapper{status: RempCatchainStatus::Created, ..}) =>
-                        fail!("REMP Catchain Store: impossible status {:?} for session id {:x}", existing.status, session_id),
-                    Some(existing @ RempCatchainWrapper{status: RempCatchainStatus::Starting, ..}) =>
-                        Ok((existing.status.clone(), existing.info.clone())),
-                    Some(RempCatchainWrapper{status: session_status @ RempCatchainStatus::ToStop, ..}) |
-                    Some(RempCatchainWrapper{status: session_status @ RempCatchainStatus::Stopping, ..}) =>
-                        fail!("REMP Catchain Store: cannot start again stopping session id {:x}, status {}", session_id, session_status),
-                    Some(existing @ RempCatchainWrapper{status: RempCatchainStatus::Active, ..}) => {
-                        if existing.info.info.is_same_catchain(to_start.clone()) {
-                            Ok((existing.status.clone(), existing.info.clone()))
+        let scheduled_at = activate_at.filter(|at| activation_delay(*at, unix_time_ms()).is_some());
+
+        let (catchain_info, generation, do_start) = loop {
+            let (decision, catchain_info, generation) = self.catchains.execute_sync(|x| {
+                let decision = start_decision(x.get(session_id).map(|cc| &cc.status))
+                    .map_err(|e| error!("REMP Catchain Store: session id {:x}: {}", session_id, e))?;
+                match (&decision, x.get(session_id)) {
+                    (StartDecision::Attach, Some(existing)) if !existing.info.info.is_same_session(&to_start) =>
+                        fail!("REMP Catchain Store: adding different catchain {} (to {}) for same session id {:x}",
+                            to_start, existing.info, to_start.queue_id
+                        ),
+                    (_, Some(existing)) => Ok((decision, existing.info.clone(), existing.generation)),
+                    (_, None) => {
+                        let transcript = self.create_transcript(&to_start);
+                        let remp_catchain = Arc::new(RempCatchain::create(
+                            engine.clone(), remp_manager.clone(), to_start.clone(), transcript
+                        )?);
+                        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+                        let mut remp_catchain_wrapper = RempCatchainWrapper::create(remp_catchain.clone(), generation);
+                        remp_catchain_wrapper.set_status(if scheduled_at.is_some() {
+                            RempCatchainStatus::Scheduled
                         } else {
-                            fail!("REMP Catchain Store: adding different catchain {} (to {}) for same session id {:x}",
-                                to_start, existing.info, to_start.queue_id
-                            )
-                        }
-                    }
-                    None => {
-                        let remp_catchain = Arc::new(RempCatchain::create(engine.clone(), remp_manager.clone(), to_start.clone())?);
-                        let mut remp_catchain_wrapper = RempCatchainWrapper::create(remp_catchain.clone());
-                        remp_catchain_wrapper.status = RempCatchainStatus::Starting;
+                            RempCatchainStatus::Starting
+                        });
+                        self.persist_session(session_id, &remp_catchain_wrapper);
                         x.insert(to_start.queue_id.clone(), remp_catchain_wrapper);
-                        Ok((RempCatchainStatus::Created, remp_catchain))
+                        Ok((decision, remp_catchain, generation))
                     }
                 }
             }).await?;
 
-            log::trace!(target: "remp", "REMP catchain {:x} start status: {:?}", session_id, cc_status);
+            log::trace!(target: "remp", "REMP catchain {:x} start decision: {:?}, generation {}",
+                session_id, decision, generation
+            );
 
-            match cc_status {
-                RempCatchainStatus::Created => break (catchain_info, true),
-                RempCatchainStatus::Active => {
+            match decision {
+                StartDecision::Create => break (catchain_info, generation, true),
+                StartDecision::Attach => {
                     log::trace!(target: "remp", "REMP catchain {:x} is already started -- copying instance", session_id);
-                    break (catchain_info, false)
+                    break (catchain_info, generation, false)
                 },
-                RempCatchainStatus::Starting => {
-                    log::warn!(target: "remp", "REMP Catchain session {:x} is being started --- waiting until it's done", session_id);
-                },
-                RempCatchainStatus::ToStop |
-                RempCatchainStatus::Stopping => {
-                    fail!("REMP Catchain session {:x} is being stopped -- cannot start it again", session_id);
-                    //log::warn!(target: "remp", "REMP Catchain session {:x} is being stopped --- waiting until it's done", session_id);
+                StartDecision::Wait => {
+                    log::warn!(target: "remp",
+                        "REMP Catchain session {:x} is being started or stopped --- waiting until it's done", session_id
+                    );
                 }
             }
 
             tokio::time::sleep(REMP_CATCHAIN_START_POLLING_INTERVAL).await;
         };
 
+        catchain_info.attached.fetch_add(1, Ordering::Relaxed);
         if do_start {
+            if let Some(activate_at) = scheduled_at {
+                log::info!(target: "remp", "REMP catchain {:x}/{} is scheduled to start at {}",
+                    session_id, catchain_info.info.general_session_info.shard, activate_at
+                );
+                self.wait_activation(session_id, generation, activate_at).await?;
+            }
             log::trace!(target: "remp", "Actually starting REMP catchain {:x}/{}",
                 session_id, catchain_info.info.general_session_info.shard
             );
-            let catchain_ptr = catchain_info.clone().start(local_key).await?;
-            let instance_impl = Arc::new(RempCatchainInstanceImpl::new(catchain_ptr));
+            let catchain_ptr = match catchain_info.clone().start_with_retries(local_key).await {
+                Ok(catchain_ptr) => catchain_ptr,
+                Err(e) => {
+                    // Session is removed, so it can be started again
+                    self.remove_catchain(session_id, generation).await;
+                    return Err(e)
+                }
+            };
+            let instance_impl = Arc::new(RempCatchainInstanceImpl::new(
+                catchain_ptr, remp_manager.options.get_catchain_queue_capacity(), catchain_info.pacer.clone()
+            ));
             catchain_info.instance.init_instance(instance_impl.clone());
-            self.activate_catchain(session_id).await?;
+            if let Err(e) = self.activate_catchain(session_id, generation).await {
+                // Session is reset by watchdog while starting: its catchain is not needed
+                catchain_info.stop(Some(instance_impl.catchain_ptr())).await?;
+                return Err(e)
+            }
             Ok(instance_impl)
         }
         else {
@@ -647,30 +1936,141 @@ impl RempCatchainStore {
         }
     }
 
+    /// Stops the session and removes it from the store. Stopping is idempotent: absent session
+    /// and session which is being stopped by somebody else are ok; session which is being
+    /// started is stopped after the start is finished.
     pub async fn stop_catchain(&self, session_id: &UInt256) -> Result<()> {
-        log::trace!(target: "remp", "Stopping REMP catchain {:x}", session_id);
-        let to_remove = self.catchains.execute_sync(|x| {
+        self.stop_or_detach_catchain(session_id, false).await
+    }
+
+    /// Detaches message queue from the session: the session is stopped when the last
+    /// attached queue is detached, or if it is marked to stop by GC
+    pub async fn detach_catchain(&self, session_id: &UInt256) -> Result<()> {
+        self.stop_or_detach_catchain(session_id, true).await
+    }
+
+    async fn stop_or_detach_catchain(&self, session_id: &UInt256, detach: bool) -> Result<()> {
+        log::trace!(target: "remp", "Stopping REMP catchain {:x} (detach: {})", session_id, detach);
+        let (to_remove, generation) = loop {
+            let (decision, to_remove) = self.catchains.execute_sync(|x| {
+                let decision = stop_decision(
+                    x.get(session_id).map(|cc| &cc.status),
+                    x.get(session_id).filter(|_| detach).map_or(0, |cc| cc.info.attached.load(Ordering::Relaxed))
+                );
+                let to_remove = match (&decision, x.get_mut(session_id)) {
+                    (StopDecision::Detach, Some(catchain)) => {
+                        catchain.info.attached.fetch_sub(1, Ordering::Relaxed);
+                        None
+                    }
+                    (StopDecision::Stop, Some(catchain)) => {
+                        catchain.set_status(RempCatchainStatus::Stopping);
+                        self.persist_session(session_id, catchain);
+                        Some((catchain.info.clone(), catchain.generation))
+                    }
+                    _ => None
+                };
+                (decision, to_remove)
+            }).await;
+
+            match (decision, to_remove) {
+                (StopDecision::Done, _) => {
+                    log::trace!(target: "remp", "REMP catchain {:x} is already stopped or being stopped", session_id);
+                    return Ok(())
+                }
+                (StopDecision::Detach, _) => {
+                    log::trace!(target: "remp", "REMP catchain {:x} is used by other queues, queue is detached", session_id);
+                    return Ok(())
+                }
+                (StopDecision::Cancel, _) => {
+                    if self.cancel_scheduled_catchain(session_id).await {
+                        return Ok(())
+                    }
+                }
+                (StopDecision::Stop, Some(to_remove)) => break to_remove,
+                (StopDecision::Stop, None) | (StopDecision::Wait, _) => {
+                    log::warn!(target: "remp", "REMP Catchain session {:x} is being started --- stopping it when it's done", session_id);
+                    tokio::time::sleep(REMP_CATCHAIN_START_POLLING_INTERVAL).await;
+                }
+            }
+        };
+
+        let catchain_ptr: Option<CatchainPtr> = to_remove.instance.get_session();
+        log::trace!(target: "remp",
+            "RMQ session removed and being stopped: {:x}, generation {}, catchain_ptr {}",
+            session_id, generation, catchain_ptr.is_some()
+        );
+        let stopped = to_remove.stop(catchain_ptr).await;
+        self.finish_catchain_db(&to_remove);
+        if let Some(transcript) = &to_remove.transcript {
+            transcript.finish();
+        }
+        // Session is removed even if stop failed, otherwise it can never be started again
+        if !self.remove_catchain(session_id, generation).await {
+            log::warn!(target: "remp", "REMP catchain {:x} generation {} is replaced while stopping", session_id, generation);
+        }
+
+        stopped
+    }
+
+    /// Status, channel depths and last activity of the session, in JSON
+    pub async fn get_catchain_status(&self, session_id: &UInt256) -> Result<serde_json::Value> {
+        let (catchain, status) = self.catchains.execute_sync(|x| {
+            x.get(session_id).map(|cc| (cc.info.clone(), cc.status.clone()))
+        }).await.ok_or_else(|| error!("REMP Catchain session {:x} not found!", session_id))?;
+        Ok(catchain.status_json(&status))
+    }
+
+    /// Stops wedged session and starts it again; queued messages are kept.
+    /// If the restart fails, the session is removed and the error is returned.
+    pub async fn restart_catchain(&self, session_id: &UInt256) -> Result<()> {
+        log::warn!(target: "remp", "Restarting REMP catchain {:x}", session_id);
+        let (catchain, generation) = self.catchains.execute_sync(|x| {
             match x.get_mut(session_id) {
                 Some(catchain) => {
-                    if catchain.status != RempCatchainStatus::ToStop {
-                        fail!("REMP Catchain session {} stopping impossible -- session should be in 'ToStop' state", catchain)
+                    if catchain.status != RempCatchainStatus::Active {
+                        fail!("REMP Catchain session {} restart impossible -- session should be active", catchain)
                     }
-                    catchain.status = RempCatchainStatus::Stopping;
-                    Ok(catchain.info.clone())
+                    catchain.set_status(RempCatchainStatus::Starting);
+                    Ok((catchain.info.clone(), catchain.generation))
                 },
                 None => fail!("REMP Catchain session {:x} not found!", session_id)
             }
         }).await?;
 
-        let catchain_ptr: Option<CatchainPtr> = to_remove.instance.get_session();
-        log::trace!(target: "remp",
-            "RMQ session removed and being stopped: {:x}, catchain_ptr {}",
-            session_id, catchain_ptr.is_some()
-        );
-        to_remove.stop(catchain_ptr).await?;
-        self.catchains.execute_sync(|x| x.remove(session_id)).await;
-
-        Ok(())
+        let restart: Result<()> = async {
+            let instance_impl = catchain.instance.get_instance_impl()?;
+            let local_key = catchain.local_key()?;
+            catchain.stop(Some(instance_impl.catchain_ptr())).await?;
+            let catchain_ptr = catchain.clone().start(local_key).await?;
+            instance_impl.replace_catchain_ptr(catchain_ptr);
+            catchain.restarts.fetch_add(1, Ordering::Relaxed);
+            catchain.last_restart_at.store(unix_time_ms(), Ordering::Relaxed);
+            Ok(())
+        }.await;
+        match restart_decision(&restart) {
+            RestartDecision::Activate => {
+                if let Err(e) = self.activate_catchain(session_id, generation).await {
+                    // Session is reset by watchdog while restarting: its catchain is not needed
+                    log::error!(target: "remp", "REMP catchain {:x} cannot be activated after restart: {}", session_id, e);
+                    catchain.stop(catchain.instance.get_session()).await?;
+                    return Err(e)
+                }
+            }
+            RestartDecision::Remove => {
+                log::error!(target: "remp", "REMP catchain {} restart failed, removing the session: {:?}", catchain, restart);
+                if let Err(e) = catchain.stop(catchain.instance.get_session()).await {
+                    log::warn!(target: "remp", "REMP catchain {} cannot be stopped after failed restart: {}", catchain, e);
+                }
+                self.finish_catchain_db(&catchain);
+                if let Some(transcript) = &catchain.transcript {
+                    transcript.finish();
+                }
+                if !self.remove_catchain(session_id, generation).await {
+                    log::warn!(target: "remp", "REMP catchain {:x} generation {} is replaced while restarting", session_id, generation);
+                }
+            }
+        }
+        restart
     }
 
     pub async fn get_catchain_session(&self, session_id: &UInt256) -> Option<CatchainPtr> {
@@ -699,25 +2099,128 @@ impl RempCatchainStore {
         return res;
     }
 
-    pub async fn gc_catchain_sessions(self: Arc<Self>, rt: tokio::runtime::Handle, alive_sessions: HashSet<UInt256>) {
-        let sessions_to_gc = self.catchains.execute_sync(|x| {
+    /// All sessions of the store (including ones being started or stopped), ordered by shard
+    pub async fn list_sessions(&self) -> Vec<RempCatchainSessionInfo> {
+        let now = unix_time_ms();
+        let mut sessions = self.catchains.execute_sync(|x| {
+            x.values().map(|cc| RempCatchainSessionInfo {
+                queue_id: format!("{:x}", cc.info.info.queue_id),
+                shard: cc.info.info.general_session_info.shard.to_string(),
+                status: cc.status.to_string(),
+                nodes: cc.info.info.nodes.len(),
+                attached: cc.info.attached.load(Ordering::Relaxed),
+                uptime_ms: now.saturating_sub(cc.info.created_at),
+            }).collect::<Vec<_>>()
+        }).await;
+        sessions.sort_by(|a, b| (&a.shard, &a.queue_id).cmp(&(&b.shard, &b.queue_id)));
+        sessions
+    }
+
+    /// Removes session stuck in starting or stopping state and force-stops its catchain
+    async fn reset_stuck_catchain(&self, session_id: &UInt256, generation: u64) {
+        let timeout = match self.transition_timeout_sec {
+            Some(timeout) => timeout,
+            None => return
+        };
+        let now = unix_time_ms();
+        let stuck = self.catchains.execute_sync(|x| {
+            let stuck = x.get(session_id).map_or(false, |cc| {
+                cc.generation == generation && is_stuck_in_transition(&cc.status, cc.status_since, now, timeout)
+            });
+            if stuck {
+                self.forget_session(session_id);
+                x.remove(session_id)
+            } else {
+                None
+            }
+        }).await;
+        let stuck = match stuck {
+            Some(stuck) => stuck,
+            None => return
+        };
+        log::error!(target: "remp", "REMP catchain {} is stuck in {} state for {} ms, force stopping it",
+            stuck, stuck.status, now.saturating_sub(stuck.status_since)
+        );
+        metrics::increment_counter!("remp_catchain_stuck_sessions");
+        #[cfg(feature = "telemetry")]
+        stuck.info.engine.remp_core_telemetry().stuck_catchain_session(&stuck.info.info.general_session_info.shard);
+        if let Err(e) = stuck.info.stop(stuck.info.instance.get_session()).await {
+            log::error!(target: "remp", "Cannot stop stuck REMP catchain {}: {}", stuck, e);
+        }
+        self.finish_catchain_db(&stuck.info);
+        if let Some(transcript) = &stuck.info.transcript {
+            transcript.finish();
+        }
+    }
+
+    /// Stops sessions which are not alive, restarts dead ones and resets the stuck ones;
+    /// `gc_horizon` is the lowest master catchain seqno of the actual validator groups
+    pub async fn gc_catchain_sessions(
+        self: Arc<Self>,
+        rt: tokio::runtime::Handle,
+        alive_sessions: HashSet<UInt256>,
+        gc_horizon: Option<u32>
+    ) {
+        let restart_timeout = self.restart_timeout_sec;
+        let transition_timeout = self.transition_timeout_sec;
+        let now = unix_time_ms();
+        let (sessions_to_gc, sessions_to_restart, sessions_stuck) = self.catchains.execute_sync(|x| {
             let mut sessions_to_gc = Vec::new();
+            let mut sessions_to_restart = Vec::new();
+            let mut sessions_stuck = Vec::new();
             for (id,remp_cc) in x.iter_mut() {
-                if !alive_sessions.contains(id) && remp_cc.status < RempCatchainStatus::ToStop {
-                    remp_cc.status = RempCatchainStatus::ToStop;
+                if transition_timeout.map_or(false, |timeout| {
+                    is_stuck_in_transition(&remp_cc.status, remp_cc.status_since, now, timeout)
+                }) {
+                    sessions_stuck.push((id.clone(), remp_cc.generation));
+                }
+                else if !alive_sessions.contains(id) && remp_cc.status < RempCatchainStatus::ToStop {
+                    // Sessions being started are left as is: they are stopped after start
+                    if remp_cc.status == RempCatchainStatus::Active {
+                        remp_cc.set_status(RempCatchainStatus::ToStop);
+                    }
                     sessions_to_gc.push(id.clone());
                 }
+                else if remp_cc.status == RempCatchainStatus::Active &&
+                    restart_timeout.map_or(false, |timeout| remp_cc.info.is_dead(now, timeout))
+                {
+                    sessions_to_restart.push(id.clone());
+                }
             }
-            sessions_to_gc
+            (sessions_to_gc, sessions_to_restart, sessions_stuck)
         }).await;
 
+        for (s, generation) in sessions_stuck {
+            let store = self.clone();
+            rt.spawn(async move {
+                store.reset_stuck_catchain(&s, generation).await;
+            });
+        }
+
+        for s in sessions_to_restart {
+            log::warn!(target: "remp", "REMP catchain {:x} received no blocks for too long, restarting it", s);
+            metrics::increment_counter!("remp_catchain_auto_restarts");
+            let store = self.clone();
+            rt.spawn(async move {
+                if let Err(e) = store.restart_catchain(&s).await {
+                    log::error!(target: "remp", "Cannot restart catchain session {:x}: `{}`", s, e);
+                }
+            });
+        }
+
         rt.spawn( async move {
+            if let Some(gc_horizon) = gc_horizon {
+                if let Err(e) = self.cleanup_orphaned_sessions(&alive_sessions, gc_horizon).await {
+                    log::error!(target: "remp", "Cannot clean up orphaned REMP catchain sessions: {}", e);
+                }
+            }
             log::trace!(target: "remp", "GC catchain sessions: {}", sessions_to_gc.iter().map(|x| format!("{:x} ", x)).collect::<String>());
             for s in sessions_to_gc {
                 if let Err(e) = self.stop_catchain(&s).await {
                     log::error!(target: "remp", "Cannot GC catchain session {}, error while stopping: `{}`", s, e);
                 }
             }
+            self.prune_finished_dbs().await;
             log::trace!(target: "remp", "GC catchain sessions: finished");
         });
     }
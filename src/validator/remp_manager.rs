@@ -15,41 +15,74 @@ use std::{
     fmt, fmt::{Display, Formatter},
     collections::{HashMap, HashSet, VecDeque},
     ops::RangeInclusive,
-    sync::Arc,
+    sync::{Arc, atomic::{AtomicU32, Ordering}},
     time::Duration
 };
 use std::cmp::{max, Reverse};
 use std::collections::BinaryHeap;
 
-use ton_block::{BlockIdExt, CatchainConfig, Message, ShardIdent, UnixTime32};
-use ton_api::ton::ton_node::RempMessageStatus;
+use storage::{
+    db::rocksdb::RocksDb, remp_messages_db::{RempMessagesDb, REMP_MESSAGES_DB_NAME},
+    remp_replay_db::{RempReplayDb, REMP_REPLAY_DB_NAME},
+    remp_sessions_db::{RempSessionsDb, REMP_SESSIONS_DB_NAME}
+};
+use ton_block::{BlockIdExt, CatchainConfig, Message, Serializable, ShardIdent, UnixTime32};
+use ton_api::{IntoBoxed, ton::ton_node::RempMessageStatus};
 use ton_types::{error, fail, KeyId, Result, SliceData, UInt256};
 
 use crate::{
     config::RempConfig,
     engine_traits::{EngineOperations, RempCoreInterface, RempDuplicateStatus},
     validator::{
-        message_cache::{RmqMessage, MessageCache}, mutex_wrapper::MutexWrapper,
-        remp_catchain::RempCatchainStore,
-        validator_utils::{get_message_uid, get_shard_by_message}
+        catchain_transcript::CatchainTranscriptStore,
+        message_cache::{RmqMessage, MessageCache, RempMessageStatusFilter, RempStatusTransition},
+        mutex_wrapper::MutexWrapper,
+        remp_acceptance::{DefaultRempAcceptancePolicy, RempAcceptancePolicy},
+        remp_catchain::RempCatchainStore, remp_rate_limit::RateLimiter,
+        validator_utils::{
+            get_adnl_id, get_message_uid, get_shard_by_message, validatordescr_to_catchain_node,
+            WalletReplayPolicy
+        }
     }
 };
 
+#[cfg(test)]
+#[path = "tests/test_remp_manager.rs"]
+mod tests;
+
 #[cfg(feature = "telemetry")]
 use std::time::Instant;
 use std::time::SystemTime;
+use dashmap::DashMap;
 #[cfg(feature = "telemetry")]
 use adnl::telemetry::Metric;
 use chrono::{DateTime, Utc};
 use crossbeam_channel::TryRecvError;
 use rand::Rng;
 
+// Cache dump returned via control server is truncated to this count of messages
+const MAX_CACHE_DUMP_MESSAGES: usize = 10000;
+// Pending messages are forwarded to the next validator set during this time before its activation
+const NEXT_SET_FORWARDING_WINDOW_SEC: u32 = 120;
+const NEXT_SET_FORWARDING_INTERVAL: Duration = Duration::from_secs(5);
+// Forwarded messages got again during this period are dropped (every validator of the
+// previous set forwards the message)
+const FORWARDED_DEDUP_PERIOD: Duration = Duration::from_secs(600);
+const MAX_FORWARDED_RECEIVED: usize = 100000;
+
 pub struct RempInterfaceQueues {
     message_cache: Arc<MessageCache>,
+    catchain_store: Arc<RempCatchainStore>,
+    catchain_transcripts: Arc<CatchainTranscriptStore>,
     runtime: Arc<tokio::runtime::Handle>,
     pub engine: Arc<dyn EngineOperations>,
     pub incoming_sender: 
         crossbeam_channel::Sender<Arc<RmqMessage>>,
+    // service messages bypass the delayer
+    service_sender: crossbeam_channel::Sender<Arc<RmqMessage>>,
+    source_rate_limiter: Option<RateLimiter<[u8; 32]>>,
+    // Messages forwarded by validators of the previous set, with time of receiving
+    forwarded_received: DashMap<UInt256, std::time::Instant>,
     pub response_receiver: 
         crossbeam_channel::Receiver<(UInt256, Arc<RmqMessage>, RempMessageStatus)>
 }
@@ -221,9 +254,17 @@ impl<T: fmt::Display, Q: RempQueue<T>> RempQueueDispatcher<T,Q> {
         }
     }
 
+    /// Shard is served if its queue or queue of its parent or child is actual: during split
+    /// or merge messages are computed for shards, whose queues are not added yet
+    async fn is_served(&self, shard: &ShardIdent) -> bool {
+        self.actual_queues.execute_sync(
+            |aq| aq.iter().any(|actual| actual.intersect_with(shard))
+        ).await
+    }
+
     /// The function postpone the message until it is requested by poll from proper shard
     async fn reroute_message(&self, msg: Arc<T>, msg_shard: &ShardIdent, required_shard: &ShardIdent) {
-        if self.actual_queues.execute_sync(|aq| aq.contains(&msg_shard)).await {
+        if self.is_served(msg_shard).await {
             log::trace!(target: "remp",
                 "Received {} message for REMP: {}, wrong message shard {}, required/old shard {}; postponed",
                 self.name, msg, msg_shard, required_shard
@@ -307,15 +348,37 @@ impl<T: fmt::Display, Q: RempQueue<T>> RempQueueDispatcher<T,Q> {
         }
     }
 
+    /// Re-sharding after split or merge: messages postponed for parent or child shards
+    /// of the shard, whose queues are not actual, are rerouted according to current shards
+    async fn reshard_pending_msgs(&self, shard: &ShardIdent) {
+        let actual = self.actual_queues.execute_sync(|aq| aq.clone()).await;
+        let stale = self.pending_messages.execute_sync(|msgs| {
+            let shards = msgs.keys()
+                .filter(|s| *s != shard && s.intersect_with(shard) && !actual.contains(s))
+                .cloned()
+                .collect::<Vec<_>>();
+            shards.into_iter().filter_map(|s| msgs.remove(&s).map(|queue| (s, queue))).collect::<Vec<_>>()
+        }).await;
+        for (old_shard, queue) in stale {
+            log::debug!(target: "remp", "REMP {}: rerouting {} messages of shard {} after change of shard {}",
+                self.name, queue.len(), old_shard, shard
+            );
+            self.reroute_messages(&queue, &old_shard).await;
+        }
+    }
+
     pub async fn add_actual_shard(&self, shard: &ShardIdent) {
         log::trace!(target: "remp", "REMP {}: adding actual shard {}", self.name, shard);
         self.actual_queues.execute_sync(|aq| aq.insert(shard.clone())).await;
+        self.reshard_pending_msgs(shard).await;
     }
 
     pub async fn remove_actual_shard(&self, shard: &ShardIdent) -> Option<VecDeque<Arc<T>>> {
         log::trace!(target: "remp", "REMP {}: removing actual shard {}", self.name, shard);
         self.actual_queues.execute_sync(|aq| aq.remove(shard)).await;
-        self.pending_messages.execute_sync(|msgs| msgs.remove(shard)).await
+        let remaining = self.pending_messages.execute_sync(|msgs| msgs.remove(shard)).await;
+        self.reshard_pending_msgs(shard).await;
+        remaining
     }
 }
 
@@ -435,12 +498,17 @@ impl RempSessionStats {
 
 pub struct RempManager {
     pub options: RempConfig,
+    pub wallet_replay_policy: WalletReplayPolicy,
 
     pub catchain_store: Arc<RempCatchainStore>,
     pub message_cache: Arc<MessageCache>,
     incoming_delayer: RempDelayer,
     incoming_dispatcher: RempQueueDispatcher<RmqMessage, RempIncomingQueue>,
     pub collator_receipt_dispatcher: RempQueueDispatcher<CollatorResult, CollatorInterfaceWrapper>,
+    shard_rate_limiter: Option<RateLimiter<ShardIdent>>,
+    acceptance_policy: Option<Arc<dyn RempAcceptancePolicy>>,
+    // Master cc, older messages are removed by background GC task
+    gc_lwb: AtomicU32,
     pub response_sender: crossbeam_channel::Sender<(UInt256, Arc<RmqMessage>, RempMessageStatus)>
 }
 
@@ -451,19 +519,59 @@ impl RempManager {
         let (incoming_sender, incoming_receiver) = crossbeam_channel::unbounded();
         let (delayed_incoming_sender, delayed_incoming_receiver) = crossbeam_channel::unbounded();
         let (response_sender, response_receiver) = crossbeam_channel::unbounded();
-        let message_cache = Arc::new(MessageCache::with_metrics(
+        let mut message_cache = MessageCache::with_metrics(
             #[cfg(feature = "telemetry")]
             engine.remp_core_telemetry().cache_size_metric()
-        ));
+        ).with_capacity(opt.get_message_cache_max_messages(), opt.get_message_cache_max_bytes());
+        if opt.is_message_status_history() {
+            message_cache = message_cache.with_status_history();
+        }
+        if engine.produce_remp_status_transitions_enabled() {
+            let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+            message_cache = message_cache.with_status_transitions(sender);
+            Self::start_status_transitions_export(&runtime, engine.clone(), receiver);
+        }
+        if opt.is_persistent_message_cache() {
+            match Self::open_persistent_db(engine.as_ref()) {
+                Ok(db) => message_cache = message_cache.with_persistent_db(db),
+                Err(e) => log::error!(target: "remp",
+                    "Cannot open REMP messages DB, messages are kept in memory only: {}", e
+                )
+            }
+        }
+        if opt.is_persistent_replay_protection() {
+            match Self::open_replay_db(engine.as_ref()) {
+                Ok(db) => message_cache = message_cache.with_replay_db(db),
+                Err(e) => log::error!(target: "remp",
+                    "Cannot open REMP replay protection DB, it is kept in memory only: {}", e
+                )
+            }
+        }
+        let message_cache = Arc::new(message_cache);
 
         let mut delay_random_rng = rand::thread_rng();
         let delay_random_seed: u64 = delay_random_rng.gen();
         let collator_interface_wrapper = CollatorInterfaceWrapper::new(engine.clone());
+        let mut catchain_store = RempCatchainStore::new(opt.get_catchain_transcripts())
+            .with_restart_timeout(opt.get_catchain_restart_timeout_sec())
+            .with_transition_timeout(opt.get_catchain_transition_timeout_sec())
+            .with_db_retention(opt.get_catchain_db_retention_sec());
+        match Self::open_sessions_db(engine.as_ref(), &opt) {
+            Ok((db, catchains_db_root)) => catchain_store = catchain_store.with_persistent_db(db, catchains_db_root),
+            Err(e) => log::error!(target: "remp",
+                "Cannot open REMP sessions DB, orphaned sessions won't be cleaned up: {}", e
+            )
+        }
+        let catchain_store = Arc::new(catchain_store);
+        let catchain_transcripts = catchain_store.transcripts();
         return (RempManager {
             options: opt.clone(),
-            catchain_store: Arc::new(RempCatchainStore::new()),
+            wallet_replay_policy: WalletReplayPolicy::new(&opt),
+            catchain_store: catchain_store.clone(),
             message_cache: message_cache.clone(),
-            incoming_delayer: RempDelayer::new(delay_random_seed, &opt, incoming_receiver, delayed_incoming_sender),
+            incoming_delayer: RempDelayer::new(
+                delay_random_seed, &opt, incoming_receiver, delayed_incoming_sender.clone()
+            ),
             incoming_dispatcher: RempQueueDispatcher::with_metric(
                 "incoming".to_string(),
                 RempIncomingQueue::new(engine.clone(), delayed_incoming_receiver),
@@ -480,16 +588,57 @@ impl RempManager {
                 #[cfg(feature = "telemetry")]
                 engine.remp_core_telemetry().collator_receipt_mutex_metric()
             ),
+            shard_rate_limiter: opt.get_shard_rate_limit().map(RateLimiter::new),
+            acceptance_policy: opt.get_acceptance_policy().map(|config| {
+                Arc::new(DefaultRempAcceptancePolicy::new(engine.clone(), config)) as Arc<dyn RempAcceptancePolicy>
+            }),
+            gc_lwb: AtomicU32::new(0),
             response_sender: response_sender
         }, RempInterfaceQueues { 
             engine,
             runtime,
             message_cache: message_cache.clone(), 
+            catchain_store,
+            catchain_transcripts,
             incoming_sender, 
+            service_sender: delayed_incoming_sender,
+            source_rate_limiter: opt.get_source_rate_limit().map(RateLimiter::new),
+            forwarded_received: DashMap::new(),
             response_receiver 
         });
     }
 
+    /// Replaces the policy configured by `acceptance_policy` option
+    pub fn with_acceptance_policy(mut self, policy: Arc<dyn RempAcceptancePolicy>) -> Self {
+        self.acceptance_policy = Some(policy);
+        self
+    }
+
+    /// Checks incoming message by acceptance policy (if any); error is the reason of reject
+    pub async fn check_acceptance(&self, message: &RmqMessage) -> Result<()> {
+        match &self.acceptance_policy {
+            Some(policy) => policy.check_message(&message.message).await,
+            None => Ok(())
+        }
+    }
+
+    fn open_persistent_db(engine: &dyn EngineOperations) -> Result<RempMessagesDb> {
+        let db = RocksDb::with_path(engine.db_root_dir()?, REMP_MESSAGES_DB_NAME)?;
+        RempMessagesDb::with_db(db, REMP_MESSAGES_DB_NAME, true)
+    }
+
+    fn open_replay_db(engine: &dyn EngineOperations) -> Result<RempReplayDb> {
+        let db = RocksDb::with_path(engine.db_root_dir()?, REMP_REPLAY_DB_NAME)?;
+        RempReplayDb::with_db(db, REMP_REPLAY_DB_NAME, true)
+    }
+
+    // Returns sessions DB and directory of RMQ catchain DBs
+    fn open_sessions_db(engine: &dyn EngineOperations, opt: &RempConfig) -> Result<(RempSessionsDb, String)> {
+        let db_root = engine.db_root_dir()?;
+        let db = RocksDb::with_path(db_root, REMP_SESSIONS_DB_NAME)?;
+        Ok((RempSessionsDb::with_db(db, REMP_SESSIONS_DB_NAME, true)?, opt.get_catchain_db_path(db_root)))
+    }
+
     pub async fn add_active_shard(&self, shard: &ShardIdent) {
         self.incoming_dispatcher.add_actual_shard(shard).await;
         self.collator_receipt_dispatcher.add_actual_shard(shard).await;
@@ -515,6 +664,22 @@ impl RempManager {
         return self.incoming_dispatcher.poll(shard).await;
     }
 
+    /// Returns false if the rate of messages for the shard is exceeded
+    pub fn check_shard_rate(&self, shard: &ShardIdent) -> bool {
+        match &self.shard_rate_limiter {
+            Some(limiter) if !limiter.check(shard) => {
+                metrics::increment_counter!("remp_rate_limited_by_shard");
+                false
+            }
+            _ => true
+        }
+    }
+
+    /// Count and total size of messages with `New` status to accounts of the shard
+    pub fn get_queue_depth(&self, shard: &ShardIdent) -> (usize, usize) {
+        self.message_cache.pending_new_messages(shard)
+    }
+
     pub async fn return_to_incoming(&self, message: Arc<RmqMessage>, shard: &ShardIdent) {
         self.incoming_dispatcher.return_back(message, shard).await;
     }
@@ -529,6 +694,160 @@ impl RempManager {
         self.message_cache.gc_old_messages(actual_lwb).await
     }
 
+    pub fn is_gc_task_enabled(&self) -> bool {
+        self.options.get_message_cache_gc_interval_ms().is_some()
+    }
+
+    /// Messages older than master cc `actual_lwb` are removed by the next run of GC task
+    pub fn set_gc_lwb(&self, actual_lwb: u32) {
+        self.gc_lwb.store(actual_lwb, Ordering::Relaxed);
+    }
+
+    /// Starts background task, which removes old messages from message cache every
+    /// `message_cache_gc_interval_ms` (if the option is set). The task stops with the manager.
+    // Status transitions are exported in order of changes; the task is finished
+    // when the message cache is dropped
+    fn start_status_transitions_export(
+        runtime: &tokio::runtime::Handle,
+        engine: Arc<dyn EngineOperations>,
+        mut receiver: tokio::sync::mpsc::UnboundedReceiver<RempStatusTransition>
+    ) {
+        runtime.spawn(async move {
+            while let Some(transition) = receiver.recv().await {
+                if let Err(e) = engine.process_remp_status_transition_in_ext_db(&transition).await {
+                    log::error!(target: "remp",
+                        "Cannot export status transition of message {:x}: {}", transition.message_id, e
+                    );
+                }
+            }
+        });
+    }
+
+    pub fn start_gc_task(
+        self: &Arc<Self>,
+        runtime: &tokio::runtime::Handle,
+        #[cfg(feature = "telemetry")]
+        engine: Arc<dyn EngineOperations>
+    ) {
+        let interval = match self.options.get_message_cache_gc_interval_ms() {
+            Some(interval) => Duration::from_millis(interval),
+            None => return
+        };
+        let manager = Arc::downgrade(self);
+        runtime.spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let manager = match manager.upgrade() {
+                    Some(manager) => manager,
+                    None => break
+                };
+                let _stats = manager.run_gc_task_once().await;
+                #[cfg(feature = "telemetry")]
+                engine.remp_core_telemetry().deleted_from_cache(_stats.total);
+            }
+            log::info!(target: "remp", "REMP message cache GC task is stopped");
+        });
+    }
+
+    /// Starts background task, which forwards pending messages of the cache to members of
+    /// the next validator set (not present in the current one) shortly before its activation,
+    /// so messages accepted near the rotation are not lost. The task stops with the manager.
+    pub fn start_next_set_forwarding_task(
+        self: &Arc<Self>,
+        runtime: &tokio::runtime::Handle,
+        engine: Arc<dyn EngineOperations>
+    ) {
+        if !self.options.is_forward_to_next_set() {
+            return
+        }
+        let manager = Arc::downgrade(self);
+        runtime.spawn(async move {
+            // Activation time of the next set and messages already forwarded to it
+            let mut forwarded = (0, HashSet::new());
+            loop {
+                tokio::time::sleep(NEXT_SET_FORWARDING_INTERVAL).await;
+                let manager = match manager.upgrade() {
+                    Some(manager) => manager,
+                    None => break
+                };
+                if let Err(e) = manager.forward_to_next_set_once(engine.as_ref(), &mut forwarded).await {
+                    log::warn!(target: "remp", "Cannot forward REMP messages to the next validator set: {}", e);
+                }
+            }
+            log::info!(target: "remp", "REMP next set forwarding task is stopped");
+        });
+    }
+
+    async fn forward_to_next_set_once(
+        &self,
+        engine: &dyn EngineOperations,
+        forwarded: &mut (u32, HashSet<UInt256>)
+    ) -> Result<usize> {
+        let config = engine.load_actual_config_params().await?;
+        let next_set = config.next_validator_set()?;
+        if next_set.list().is_empty() || engine.now() + NEXT_SET_FORWARDING_WINDOW_SEC < next_set.utime_since() {
+            return Ok(0)
+        }
+        if forwarded.0 != next_set.utime_since() {
+            let to_resolve = next_set.list().iter().map(validatordescr_to_catchain_node).collect();
+            engine.update_validators(to_resolve, vec!()).await?;
+            *forwarded = (next_set.utime_since(), HashSet::new());
+        }
+        let cur_set = config.validator_set()?;
+        let cur_nodes = cur_set.list().iter().map(get_adnl_id).collect::<HashSet<_>>();
+        let targets = next_set.list().iter()
+            .map(get_adnl_id)
+            .filter(|id| !cur_nodes.contains(id))
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            return Ok(0)
+        }
+
+        let mut count = 0;
+        for entry in self.message_cache.dump_messages(None, RempMessageStatusFilter::Pending)? {
+            let message = match &entry.message {
+                Some(message) => message,
+                None => continue
+            };
+            if !forwarded.1.insert(entry.message_id.clone()) {
+                continue
+            }
+            let remp_message = ton_api::ton::ton_node::rempmessage::RempMessage {
+                message: message.message.write_to_bytes()?.into(),
+                id: entry.message_id.clone(),
+                timestamp: 0,
+                signature: Vec::new().into()
+            }.into_boxed();
+            for target in &targets {
+                if let Err(e) = engine.send_remp_forwarded_message(target.clone(), &remp_message) {
+                    log::warn!(target: "remp", "Cannot forward message {:x} to {}: {}", entry.message_id, target, e);
+                }
+            }
+            count += 1;
+        }
+        if count > 0 {
+            metrics::counter!("remp_messages_forwarded_to_next_set", count as u64);
+            log::info!(target: "remp", "{} pending REMP messages are forwarded to {} validators of the next set",
+                count, targets.len()
+            );
+        }
+        Ok(count)
+    }
+
+    async fn run_gc_task_once(&self) -> RempSessionStats {
+        let actual_lwb = self.gc_lwb.load(Ordering::Relaxed);
+        let now = std::time::Instant::now();
+        let stats = self.gc_old_messages(actual_lwb).await;
+        metrics::histogram!("remp_message_cache_gc_time", now.elapsed());
+        metrics::counter!("remp_message_cache_gc_removed", stats.total as u64);
+        if stats.total > 0 {
+            log::info!(target: "remp", "GC old REMP messages (cc < {}): {}, TIME {}ms",
+                actual_lwb, stats, now.elapsed().as_millis()
+            );
+        }
+        stats
+    }
+
     pub fn create_master_cc_session(&self, new_cc_seqno: u32, new_time: UnixTime32, inf_blocks: Vec<BlockIdExt>) -> Result<()> {
         self.message_cache.try_set_master_cc_start_time(new_cc_seqno, new_time, inf_blocks)
     }
@@ -546,8 +865,21 @@ impl RempManager {
         self.message_cache.update_master_cc_ranges(new_cc_seqno, rp_guarantee)
     }
 
+    /// Messages are tracked at least for lifetime of master catchain session (validator set
+    /// rotation period), the window may be extended by `message_expiry_window_sec` option
     pub fn calc_rp_guarantee(&self, config: &CatchainConfig) -> Duration {
-        Duration::from_secs(config.mc_catchain_lifetime as u64)
+        let lifetime = config.mc_catchain_lifetime;
+        match self.options.get_message_expiry_window_sec() {
+            Some(window) if window < lifetime => {
+                log::warn!(target: "remp",
+                    "Message expiry window {} sec is less than master catchain lifetime {} sec, using the latter",
+                    window, lifetime
+                );
+                Duration::from_secs(lifetime as u64)
+            }
+            Some(window) => Duration::from_secs(window as u64),
+            None => Duration::from_secs(lifetime as u64)
+        }
     }
 }
 
@@ -629,6 +961,15 @@ impl RempInterfaceQueues {
 #[async_trait::async_trait]
 impl RempCoreInterface for RempInterfaceQueues {
     async fn process_incoming_message(&self, message_id: UInt256, message: Message, source: Arc<KeyId>) -> Result<()> {
+        // Messages without source (got via broadcast) are limited per shard only
+        if let Some(limiter) = &self.source_rate_limiter {
+            if source.data().iter().any(|x| *x != 0) && !limiter.check(source.data()) {
+                metrics::increment_counter!("remp_rate_limited_by_source");
+                #[cfg(feature = "telemetry")]
+                self.engine.remp_core_telemetry().rate_limited_by_source();
+                fail!("Rate limit of REMP messages from {} is exceeded, message {:x} is not accepted", source, message_id)
+            }
+        }
         let arc_message = Arc::new(message.clone());
 
         // build message
@@ -655,6 +996,42 @@ impl RempCoreInterface for RempInterfaceQueues {
         Ok(())
     }
 
+    async fn process_service_message(&self, message_id: UInt256, message: Message) -> Result<()> {
+        let remp_message = Arc::new(RmqMessage::new (
+            Arc::new(message.clone()),
+            message_id.clone(),
+            get_message_uid(&message),
+            Arc::new(KeyId::from_data([0; 32])),
+            0
+        )?);
+
+        if self.message_cache.get_message(&message_id)?.is_some() {
+            log::trace!(target: "remp",
+                "Point 1. We already know about service message {:x}, no forwarding is necessary",
+                message_id
+            );
+        }
+        else {
+            log::trace!(target: "remp", "Point 1. Adding service message {} to incoming queue", remp_message);
+            self.service_sender.send(remp_message)?;
+        }
+        Ok(())
+    }
+
+    async fn process_forwarded_message(&self, message_id: UInt256, message: Message, source: Arc<KeyId>) -> Result<()> {
+        let now = std::time::Instant::now();
+        if self.forwarded_received.len() > MAX_FORWARDED_RECEIVED {
+            self.forwarded_received.retain(|_, received_at| now.duration_since(*received_at) < FORWARDED_DEDUP_PERIOD);
+        }
+        if self.forwarded_received.insert(message_id.clone(), now).is_some() {
+            metrics::increment_counter!("remp_forwarded_messages_duplicate");
+            log::trace!(target: "remp", "Point 1. Forwarded message {:x} from {} is already received", message_id, source);
+            return Ok(())
+        }
+        metrics::increment_counter!("remp_forwarded_messages_received");
+        self.process_incoming_message(message_id, message, source).await
+    }
+
     fn check_remp_duplicate(&self, message_id: &UInt256) -> Result<RempDuplicateStatus> {
         log::trace!(target: "remp", "RempInterfaceQueues: checking duplicates for {:x}", message_id);
         let res = self.message_cache.check_message_duplicates(message_id);
@@ -668,4 +1045,68 @@ impl RempCoreInterface for RempInterfaceQueues {
         }
         return res
     }
+
+    fn get_message_status(&self, message_id: &UInt256) -> Result<Option<RempMessageStatus>> {
+        self.message_cache.get_message_status(message_id)
+    }
+
+    fn list_catchain_transcripts(&self) -> Vec<(UInt256, bool, usize)> {
+        self.catchain_transcripts.list()
+    }
+
+    fn export_catchain_transcript(&self, session_id: &UInt256, offset: usize, limit: usize) -> Result<String> {
+        self.catchain_transcripts.export(session_id, offset, limit)
+    }
+
+    fn export_catchain_propagation(&self) -> Result<String> {
+        self.catchain_transcripts.export_propagation()
+    }
+
+    fn export_message_history(&self, message_id: &UInt256) -> Result<String> {
+        let history = self.message_cache.get_status_history(message_id).ok_or_else(|| error!(
+            "No status history for message {:x} (message is unknown or history is disabled)", message_id
+        ))?;
+        let entries = history.iter().map(|entry| serde_json::json!({
+            "time": entry.timestamp_ms,
+            "status": entry.status.to_string(),
+            "origin": entry.origin.to_string(),
+        })).collect::<Vec<_>>();
+        Ok(format!("{:#}", serde_json::Value::from(entries)))
+    }
+
+    fn get_queue_depth(&self, shard: &ShardIdent) -> (usize, usize) {
+        self.message_cache.pending_new_messages(shard)
+    }
+
+    fn dump_message_cache(&self, shard: Option<&ShardIdent>, status: RempMessageStatusFilter) -> Result<String> {
+        let messages = self.message_cache.dump_messages(shard, status)?;
+        let entries = messages.iter().take(MAX_CACHE_DUMP_MESSAGES).map(|entry| serde_json::json!({
+            "id": format!("{:x}", entry.message_id),
+            "uid": format!("{:x}", entry.message_uid),
+            "master_cc": entry.master_cc,
+            "status": entry.status.to_string(),
+            "dst": entry.message.as_ref().and_then(|m| m.message.dst_ref()).map(|dst| dst.to_string()),
+            "timestamp": entry.message.as_ref().map(|m| m.timestamp),
+            "source": entry.message.as_ref().map(|m| m.source_key.to_string()),
+            "source_idx": entry.message.as_ref().map(|m| m.source_idx),
+            "collation_attempts": entry.collation_attempts,
+        })).collect::<Vec<_>>();
+        Ok(format!("{:#}", serde_json::json!({
+            "total": messages.len(),
+            "truncated": messages.len() > MAX_CACHE_DUMP_MESSAGES,
+            "messages": entries,
+        })))
+    }
+
+    async fn list_catchain_sessions(&self) -> Result<String> {
+        let sessions = self.catchain_store.list_sessions().await;
+        Ok(format!("{:#}", serde_json::to_value(sessions)?))
+    }
+
+    async fn inspect_catchain_session(&self, queue_id: &UInt256, restart: bool) -> Result<String> {
+        if restart {
+            self.catchain_store.restart_catchain(queue_id).await?;
+        }
+        Ok(format!("{:#}", self.catchain_store.get_catchain_status(queue_id).await?))
+    }
 }
@@ -0,0 +1,73 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use dashmap::DashMap;
+use std::{hash::Hash, time::Instant};
+
+#[cfg(test)]
+#[path = "tests/test_remp_rate_limit.rs"]
+mod tests;
+
+// Idle buckets (filled up) are removed when there are more buckets
+const MAX_BUCKETS: usize = 10000;
+
+/// Token bucket: `rate` tokens per second, at most `rate` tokens are accumulated
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, rate: f64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate);
+        self.updated = now;
+    }
+}
+
+/// Limits rate of messages (per second) for each key separately
+pub struct RateLimiter<K: Eq + Hash + Clone> {
+    rate: u32,
+    buckets: DashMap<K, TokenBucket>,
+}
+
+impl<K: Eq + Hash + Clone> RateLimiter<K> {
+
+    pub fn new(rate: u32) -> Self {
+        Self { rate, buckets: DashMap::new() }
+    }
+
+    /// Returns false if the rate for the key is exceeded
+    pub fn check(&self, key: &K) -> bool {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &K, now: Instant) -> bool {
+        let rate = self.rate as f64;
+        if self.buckets.len() > MAX_BUCKETS {
+            self.buckets.retain(|_, bucket| {
+                bucket.refill(rate, now);
+                bucket.tokens < rate
+            });
+        }
+        let mut bucket = self.buckets.entry(key.clone())
+            .or_insert_with(|| TokenBucket { tokens: rate, updated: now });
+        bucket.refill(rate, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
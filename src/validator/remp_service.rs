@@ -1,11 +1,15 @@
 use crate::{
-    engine_traits::{EngineOperations, RempCoreInterface}, ext_messages::create_ext_message,
-    network::remp::RempMessagesSubscriber,
+    engine_traits::{EngineOperations, RempCoreInterface},
+    ext_messages::{check_ext_message_size, create_ext_message_with_limit, ExtMessageRejectReason},
+    network::remp::{RempDeferredNotice, RempMessagesSubscriber, RempStatusQuery},
 };
 
 use std::{ops::Deref, sync::Arc};
-use ton_api::ton::ton_node::RempMessage;
-use ton_types::{error, fail, KeyId, Result};
+use ton_api::ton::ton_node::{
+    RempMessage, RempMessageLevel, RempMessageStatus, rempmessagestatus::RempRejected
+};
+use ton_block::{BlockIdExt, Message};
+use ton_types::{error, fail, KeyId, Result, UInt256};
 
 #[derive(Default)]
 pub struct RempService {
@@ -30,13 +34,15 @@ impl RempService {
         self.remp_core.get().ok_or_else(|| error!("remp_core was not set")).map(|rci| rci.deref())
     }
 
-    async fn new_remp_message(&self, message: RempMessage, source: &Arc<KeyId>) -> Result<()> {
+    async fn new_remp_message(&self, message: RempMessage, source: &Arc<KeyId>, forwarded: bool) -> Result<()> {
         // TODO send error receipt in case of any error
         let engine = self.engine.get().ok_or_else(|| error!("engine was not set"))?;
         let remp_core = self.remp_core_interface()?;
 
         #[cfg(feature = "telemetry")]
-        engine.remp_core_telemetry().message_from_fullnode();
+        if !forwarded {
+            engine.remp_core_telemetry().message_from_fullnode();
+        }
 
         if !engine.check_sync().await? {
             fail!("Can't process REMP message because validator is out of sync");
@@ -44,20 +50,77 @@ impl RempService {
 
         // deserialise message
         let id = message.id().clone();
-        let (real_id, message) = create_ext_message(&message.message())?;
-        if real_id != id {
-            fail!("Given message id {:x} is not equal calculated one {:x}", id, real_id);
-        }
+        let message = match Self::validate_message(engine.as_ref(), &id, &message) {
+            Ok(message) => message,
+            Err(e) => {
+                // Invalid message is rejected right away, the reason code is sent to the client
+                if let (Some(reason), false) = (ExtMessageRejectReason::from_error_text(&e.to_string()), forwarded) {
+                    metrics::increment_counter!("remp_rejected_invalid", "reason" => reason.code());
+                    let rejected = RempMessageStatus::TonNode_RempRejected(RempRejected {
+                        level: RempMessageLevel::TonNode_RempQueue,
+                        block_id: BlockIdExt::default(),
+                        error: e.to_string()
+                    });
+                    if let Err(e) = engine.send_remp_statuses(source.clone(), vec!((id.clone(), rejected))).await {
+                        log::error!(target: "remp", "Cannot send reject of invalid REMP message {:x} to {}: {}", id, source, e);
+                    }
+                }
+                return Err(e)
+            }
+        };
 
-        log::trace!(target: "remp", "Point 0. Incoming REMP message {:x} received from {}: {:?}",
-            id, source, message
+        log::trace!(target: "remp", "Point 0. Incoming REMP message {:x} received from {} (forwarded: {}): {:?}",
+            id, source, forwarded, message
         );
 
         // push into remp catchain
-        remp_core.process_incoming_message(id, message, source.clone()).await?;
+        if forwarded {
+            remp_core.process_forwarded_message(id, message, source.clone()).await?;
+        } else {
+            remp_core.process_incoming_message(id, message, source.clone()).await?;
+        }
 
         Ok(())
     }
+
+    fn validate_message(engine: &dyn EngineOperations, id: &UInt256, message: &RempMessage) -> Result<Message> {
+        let (real_id, parsed) = create_ext_message_with_limit(
+            &message.message(), engine.remp_max_message_size()
+        )?;
+        if &real_id != id {
+            fail!("Given message id {:x} is not equal calculated one {:x}", id, real_id);
+        }
+        if let Some(dst_wc) = parsed.dst_workchain_id() {
+            check_ext_message_size(
+                message.message().len(), engine.remp_max_message_size_for_workchain(dst_wc)
+            )?;
+        }
+        Ok(parsed)
+    }
+
+    async fn remp_status_query(&self, query: RempStatusQuery, source: &Arc<KeyId>) -> Result<()> {
+        let engine = self.engine.get().ok_or_else(|| error!("engine was not set"))?;
+        let remp_core = self.remp_core_interface()?;
+        let mut statuses = Vec::new();
+        for id in &query.message_ids {
+            if let Some(status) = remp_core.get_message_status(id)? {
+                statuses.push((id.clone(), status));
+            }
+        }
+        log::trace!(target: "remp", "REMP status query from {}: {} of {} messages are known",
+            source, statuses.len(), query.message_ids.len()
+        );
+        if !statuses.is_empty() {
+            engine.send_remp_statuses(source.clone(), statuses).await?;
+        }
+        for id in &query.message_ids {
+            if let Some(deferred) = engine.get_remp_deferred_message(id) {
+                let notice = RempDeferredNotice { message_id: id.clone(), position: deferred.position };
+                engine.send_remp_deferred_notice(source.clone(), &notice)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -67,10 +130,25 @@ impl RempMessagesSubscriber for RempService {
 
         let id = message.id().clone();
         log::trace!(target: "remp", "Point 0. Processing incoming REMP message {:x}", id);
-        match self.new_remp_message(message, source).await {
+        match self.new_remp_message(message, source, false).await {
             Ok(_) => log::trace!(target: "remp", "Point 0. Processed incoming REMP message {:x}", id),
             Err(e) => log::error!(target: "remp", "Point 0. Error processing incoming REMP message {:x}: {}", id, e)
         }
         Ok(())
     }
+
+    async fn forwarded_remp_message(&self, message: RempMessage, source: &Arc<KeyId>) -> Result<()> {
+        let id = message.id().clone();
+        if let Err(e) = self.new_remp_message(message, source, true).await {
+            log::warn!(target: "remp", "Error processing REMP message {:x} forwarded by {}: {}", id, source, e)
+        }
+        Ok(())
+    }
+
+    async fn remp_status_query(&self, query: RempStatusQuery, source: &Arc<KeyId>) -> Result<()> {
+        if let Err(e) = self.remp_status_query(query, source).await {
+            log::error!(target: "remp", "Error processing REMP status query from {}: {}", source, e)
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,153 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{collections::BTreeMap, fmt};
+use ton_types::UInt256;
+
+#[cfg(test)]
+#[path = "tests/test_session_stats.rs"]
+mod tests;
+
+// Reasons of rejects are truncated to the length
+const MAX_REJECT_REASON_LEN: usize = 80;
+// Rejects with reasons above the count are accounted as `other`
+const MAX_REJECT_REASONS: usize = 32;
+const OTHER_REJECT_REASON: &str = "other";
+
+/// Work of the local validator in a validator session: produced, approved and rejected
+/// candidates, committed blocks and time to consensus (from the first candidate of the round
+/// seen by the validator till the commit of the round's block)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SessionStats {
+    pub session_id: UInt256,
+    pub candidates_produced: u32,
+    pub collation_failures: u32,
+    pub approvals_signed: u32,
+    pub candidates_rejected: u32,
+    pub reject_reasons: BTreeMap<String, u32>,
+    pub blocks_committed: u32,
+    pub own_blocks_committed: u32,
+    pub total_consensus_ms: u64,
+    pub max_consensus_ms: u64,
+    // committed blocks with time to consensus measured
+    consensus_measured: u32,
+    // round and time of its first candidate
+    round_started: Option<(u32, u64)>,
+}
+
+impl SessionStats {
+
+    pub fn new(session_id: UInt256) -> Self {
+        Self { session_id, ..Default::default() }
+    }
+
+    fn candidate_seen(&mut self, round: u32, now_ms: u64) {
+        match self.round_started {
+            Some((started_round, _)) if started_round >= round => (),
+            _ => self.round_started = Some((round, now_ms))
+        }
+    }
+
+    pub fn collated(&mut self, round: u32, now_ms: u64, success: bool) {
+        self.candidate_seen(round, now_ms);
+        if success {
+            self.candidates_produced += 1;
+        } else {
+            self.collation_failures += 1;
+        }
+    }
+
+    pub fn validated(&mut self, round: u32, now_ms: u64, reject_reason: Option<&str>) {
+        self.candidate_seen(round, now_ms);
+        match reject_reason {
+            None => self.approvals_signed += 1,
+            Some(reason) => {
+                self.candidates_rejected += 1;
+                let reason = Self::reject_reason_key(reason);
+                let key = if self.reject_reasons.contains_key(&reason) ||
+                    self.reject_reasons.len() < MAX_REJECT_REASONS
+                {
+                    reason
+                } else {
+                    OTHER_REJECT_REASON.to_string()
+                };
+                *self.reject_reasons.entry(key).or_default() += 1;
+            }
+        }
+    }
+
+    pub fn committed(&mut self, round: u32, now_ms: u64, own: bool) {
+        self.blocks_committed += 1;
+        if own {
+            self.own_blocks_committed += 1;
+        }
+        if let Some((started_round, started_ms)) = self.round_started.take() {
+            if started_round == round {
+                let duration = now_ms.saturating_sub(started_ms);
+                self.consensus_measured += 1;
+                self.total_consensus_ms += duration;
+                self.max_consensus_ms = self.max_consensus_ms.max(duration);
+            }
+        }
+    }
+
+    pub fn skipped(&mut self) {
+        self.round_started = None;
+    }
+
+    /// Average time to consensus of committed blocks, ms (blocks committed without
+    /// candidates seen locally are not accounted)
+    pub fn avg_consensus_ms(&self) -> u64 {
+        self.total_consensus_ms.checked_div(self.consensus_measured as u64).unwrap_or_default()
+    }
+
+    // First line of the error, without details which differ from block to block
+    fn reject_reason_key(reason: &str) -> String {
+        let reason = reason.lines().next().unwrap_or_default().trim();
+        match reason.char_indices().nth(MAX_REJECT_REASON_LEN) {
+            Some((pos, _)) => reason[..pos].to_string(),
+            None => reason.to_string()
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "session_id": format!("{:x}", self.session_id),
+            "candidates_produced": self.candidates_produced,
+            "collation_failures": self.collation_failures,
+            "approvals_signed": self.approvals_signed,
+            "candidates_rejected": self.candidates_rejected,
+            "reject_reasons": self.reject_reasons,
+            "blocks_committed": self.blocks_committed,
+            "own_blocks_committed": self.own_blocks_committed,
+            "avg_consensus_ms": self.avg_consensus_ms(),
+            "max_consensus_ms": self.max_consensus_ms,
+        })
+    }
+}
+
+impl fmt::Display for SessionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,
+            "session {:x}: produced {} (failed {}), approved {}, rejected {}, committed {} (own {}), \
+            time to consensus avg {} ms, max {} ms",
+            self.session_id, self.candidates_produced, self.collation_failures, self.approvals_signed,
+            self.candidates_rejected, self.blocks_committed, self.own_blocks_committed,
+            self.avg_consensus_ms(), self.max_consensus_ms
+        )?;
+        for (reason, count) in self.reject_reasons.iter() {
+            write!(f, "\n  rejected {} times: {}", count, reason)?;
+        }
+        Ok(())
+    }
+}
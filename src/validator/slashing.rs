@@ -11,7 +11,10 @@
 * limitations under the License.
 */
 
-use crate::{engine_traits::EngineOperations, shard_state::ShardStateStuff, validator::UInt256};
+use crate::{
+    engine_traits::EngineOperations, shard_state::ShardStateStuff,
+    validator::{UInt256, slashing_evidence::SlashingComplaint}
+};
 use ever_crypto::Ed25519KeyOption;
 use num_bigint::BigUint;
 use rand::Rng;
@@ -239,6 +242,25 @@ impl SlashingManager {
         }
     }
 
+    /// External message to elector with the complaint; None if the complaint's metric
+    /// is not accepted by elector
+    pub fn prepare_complaint_message(
+        complaint: &SlashingComplaint,
+        reporter_privkey: &PrivateKey,
+    ) -> Result<Option<Message>> {
+        let time_now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Self::prepare_slash_validator_message(
+            ELECTOR_ADDRESS.clone(),
+            reporter_privkey,
+            &complaint.validator,
+            complaint.kind.metric_id(),
+            time_now_ms
+        )
+    }
+
     fn convert_to_u256(value: &[u8]) -> TokenValue {
         assert!(value.len() == 32);
         TokenValue::Uint(Uint {
@@ -329,7 +351,7 @@ impl SlashingManager {
                     .slashing_messages
                     .push((message_id.clone(), message.clone()));
 
-                if let Err(err) = engine.redirect_external_message(
+                if let Err(err) = engine.send_service_message(
                                     &serialized_message, message_id.clone()).await 
                 {
                     log::warn!(target: "slashing", "can't send message: {:?}, error: {:?}", message, err);
@@ -0,0 +1,199 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{collections::{HashMap, HashSet}, fmt};
+use ton_types::UInt256;
+use validator_session::{PublicKey, SlashingAggregatedMetric};
+
+#[cfg(test)]
+#[path = "tests/test_slashing_evidence.rs"]
+mod tests;
+
+// Candidates of older rounds are forgotten (double candidates are not detected for them)
+const CANDIDATE_ROUNDS_KEPT: u32 = 8;
+// Evidence records kept for a validator and a kind of misbehavior (all are counted)
+const MAX_EVIDENCE_RECORDS: usize = 16;
+// Rejected candidates of a validator required for complaint
+const MIN_INVALID_CANDIDATES: u32 = 3;
+// Committed blocks required to judge participation
+const MIN_BLOCKS_FOR_PARTICIPATION: u32 = 50;
+// Part of committed blocks (percent) not signed by a validator, above which it is not participating
+const NON_PARTICIPATION_PCT: u64 = 80;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MisbehaviorKind {
+    /// Different candidates of the same round are received from the validator
+    DoubleCandidate,
+    /// Candidates of the validator are rejected by validation
+    InvalidCandidate,
+    /// Validator doesn't sign committed blocks
+    NonParticipation,
+}
+
+impl MisbehaviorKind {
+    /// Metric of elector's `report` function the complaint is submitted on
+    pub fn metric_id(&self) -> SlashingAggregatedMetric {
+        match self {
+            MisbehaviorKind::DoubleCandidate | MisbehaviorKind::InvalidCandidate =>
+                SlashingAggregatedMetric::ValidationScore,
+            MisbehaviorKind::NonParticipation => SlashingAggregatedMetric::CommitsParticipation,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evidence {
+    pub round: u32,
+    pub details: String,
+}
+
+#[derive(Default)]
+struct ValidatorEvidence {
+    records: HashMap<MisbehaviorKind, (u32, Vec<Evidence>)>,
+    unsigned_blocks: u32,
+}
+
+impl ValidatorEvidence {
+    fn add(&mut self, kind: MisbehaviorKind, round: u32, details: String) {
+        let (count, records) = self.records.entry(kind).or_default();
+        *count += 1;
+        if records.len() < MAX_EVIDENCE_RECORDS {
+            records.push(Evidence { round, details });
+        }
+    }
+
+    fn count(&self, kind: MisbehaviorKind) -> u32 {
+        self.records.get(&kind).map_or(0, |(count, _)| *count)
+    }
+}
+
+/// Complaint on the validator, with evidence collected in the session
+#[derive(Clone)]
+pub struct SlashingComplaint {
+    pub session_id: UInt256,
+    pub validator: PublicKey,
+    pub kind: MisbehaviorKind,
+    pub count: u32,
+    pub evidence: Vec<Evidence>,
+}
+
+impl fmt::Display for SlashingComplaint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "session {:x}: validator {} {:?} ({} times)",
+            self.session_id, self.validator.id(), self.kind, self.count
+        )
+    }
+}
+
+/// Evidence of misbehavior of validators, observed by the local validator in a session
+pub struct SessionEvidence {
+    session_id: UInt256,
+    validators: HashMap<UInt256, PublicKey>,
+    evidence: HashMap<UInt256, ValidatorEvidence>,
+    // candidate (root hash) of each source in recent rounds
+    candidates: HashMap<(u32, UInt256), UInt256>,
+    committed_blocks: u32,
+    last_committed_round: u32,
+}
+
+impl SessionEvidence {
+
+    pub fn new(session_id: UInt256, validators: Vec<PublicKey>) -> Self {
+        Self {
+            session_id,
+            validators: validators.into_iter().map(|key| (UInt256::from(key.id().data()), key)).collect(),
+            evidence: HashMap::new(),
+            candidates: HashMap::new(),
+            committed_blocks: 0,
+            last_committed_round: 0,
+        }
+    }
+
+    /// Accounts candidate of the source; returns false if other candidate of the source
+    /// is already received in the round
+    pub fn candidate_received(&mut self, round: u32, source: &UInt256, root_hash: &UInt256) -> bool {
+        self.candidates.retain(|(candidate_round, _), _| candidate_round + CANDIDATE_ROUNDS_KEPT > round);
+        match self.candidates.get(&(round, source.clone())) {
+            Some(known) if known != root_hash => {
+                let details = format!("candidates {:x} and {:x}", known, root_hash);
+                self.evidence.entry(source.clone()).or_default()
+                    .add(MisbehaviorKind::DoubleCandidate, round, details);
+                false
+            }
+            Some(_) => true,
+            None => {
+                self.candidates.insert((round, source.clone()), root_hash.clone());
+                true
+            }
+        }
+    }
+
+    pub fn candidate_rejected(&mut self, round: u32, source: &UInt256, root_hash: &UInt256, reason: &str) {
+        let details = format!("candidate {:x}: {}", root_hash, reason);
+        self.evidence.entry(source.clone()).or_default()
+            .add(MisbehaviorKind::InvalidCandidate, round, details);
+    }
+
+    /// Accounts committed block signed by `signers` (key ids)
+    pub fn block_committed(&mut self, round: u32, signers: &HashSet<UInt256>) {
+        self.committed_blocks += 1;
+        self.last_committed_round = round;
+        for id in self.validators.keys() {
+            if !signers.contains(id) {
+                self.evidence.entry(id.clone()).or_default().unsigned_blocks += 1;
+            }
+        }
+    }
+
+    /// Complaints on validators with enough evidence of misbehavior
+    pub fn complaints(&self) -> Vec<SlashingComplaint> {
+        let mut complaints = Vec::new();
+        for (id, evidence) in self.evidence.iter() {
+            let validator = match self.validators.get(id) {
+                Some(validator) => validator,
+                None => continue
+            };
+            let mut complain = |kind: MisbehaviorKind, count: u32, records: Vec<Evidence>| {
+                complaints.push(SlashingComplaint {
+                    session_id: self.session_id.clone(),
+                    validator: validator.clone(),
+                    kind,
+                    count,
+                    evidence: records,
+                })
+            };
+            for (kind, min_count) in [
+                (MisbehaviorKind::DoubleCandidate, 1),
+                (MisbehaviorKind::InvalidCandidate, MIN_INVALID_CANDIDATES)
+            ] {
+                let count = evidence.count(kind);
+                if count >= min_count {
+                    complain(kind, count, evidence.records[&kind].1.clone());
+                }
+            }
+            if self.committed_blocks >= MIN_BLOCKS_FOR_PARTICIPATION &&
+                evidence.unsigned_blocks as u64 * 100 >= self.committed_blocks as u64 * NON_PARTICIPATION_PCT
+            {
+                let details = format!("{} of {} committed blocks are not signed",
+                    evidence.unsigned_blocks, self.committed_blocks
+                );
+                complain(
+                    MisbehaviorKind::NonParticipation,
+                    evidence.unsigned_blocks,
+                    vec![Evidence { round: self.last_committed_round, details }]
+                );
+            }
+        }
+        complaints
+    }
+}
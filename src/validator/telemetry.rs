@@ -17,7 +17,7 @@ use adnl::{
 };
 use std::{
     time::Duration,
-    sync::{Arc, atomic::{AtomicU32, AtomicUsize, Ordering}},
+    sync::{Arc, Mutex, atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering}},
     cmp::{max, min},
     collections::HashMap,
 };
@@ -352,6 +352,10 @@ struct RempQueueTelemetry {
     pub in_channel_to_rmq: Arc<Metric>,
     pub pending_collation: Arc<Metric>,
     pub rmq_catchain_mutex_awaiting: Arc<Metric>,
+    pub block_production_interval_ms: Arc<Metric>,
+    pub block_payload_bytes: Arc<Metric>,
+    pub pending_at_block_production: Arc<Metric>,
+    pub stuck_catchain_sessions: AtomicUsize,
 }
 
 impl RempQueueTelemetry {
@@ -365,6 +369,10 @@ impl RempQueueTelemetry {
             in_channel_to_rmq: Metric::without_totals("in channel to rmq", average_period_secs),
             pending_collation: Metric::without_totals("pending collation", average_period_secs),
             rmq_catchain_mutex_awaiting: Metric::without_totals("rmq catchain mutex awaiting", average_period_secs),
+            block_production_interval_ms: Metric::without_totals("block production interval, ms", average_period_secs),
+            block_payload_bytes: Metric::without_totals("block payload bytes", average_period_secs),
+            pending_at_block_production: Metric::without_totals("pending at block production", average_period_secs),
+            stuck_catchain_sessions: AtomicUsize::default(),
         }
     }
 }
@@ -382,8 +390,11 @@ pub struct RempCoreTelemetry {
     add_to_cache_attempts: AtomicUsize,
     added_to_cache: AtomicUsize,
     deleted_from_cache: AtomicUsize,
+    rate_limited_by_source: AtomicUsize,
+    rate_limited_by_shard: AtomicUsize,
     
     cache_size: Arc<Metric>,
+    rmq_storage_size: Arc<Metric>,
     incoming_queue_size: Arc<Metric>,
     incoming_mutex_awaiting: Arc<Metric>,
     collator_receipt_queue_size: Arc<Metric>,
@@ -396,6 +407,18 @@ pub struct RempCoreTelemetry {
     combined_receipt_size_bytes: Arc<Metric>,
     combined_receipt_inners: Arc<Metric>,
     combined_receipts_send_rate: Arc<MetricBuilder>,
+
+    // If telemetry backend is failed, samples are dropped (and counted) instead of recording
+    backend_failed: AtomicBool,
+    backend_failure: Mutex<Option<String>>,
+    dropped_samples: AtomicUsize,
+}
+
+/// State of REMP core telemetry backend, reported by control server stats
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+pub struct RempTelemetryHealth {
+    pub failure: Option<String>,
+    pub dropped_samples: usize,
 }
 
 impl RempCoreTelemetry {
@@ -413,7 +436,10 @@ impl RempCoreTelemetry {
             add_to_cache_attempts: AtomicUsize::default(),
             added_to_cache: AtomicUsize::default(),
             deleted_from_cache: AtomicUsize::default(),
+            rate_limited_by_source: AtomicUsize::default(),
+            rate_limited_by_shard: AtomicUsize::default(),
             cache_size: Metric::without_totals("messages cache size", period_sec),
+            rmq_storage_size: Metric::without_totals("rmq storage size", period_sec),
             incoming_queue_size: Metric::without_totals("incoming queue size", period_sec),
             incoming_mutex_awaiting: Metric::without_totals("incoming mutex awaiting", period_sec),
             collator_receipt_queue_size: Metric::without_totals("collator receipt queue size", period_sec),
@@ -436,22 +462,67 @@ impl RempCoreTelemetry {
                 Metric::with_total_amount("combined receipts sending rate", period_sec),
                 Self::PERIOD_MEASURE_NANO
             ),
+            backend_failed: AtomicBool::new(false),
+            backend_failure: Mutex::new(None),
+            dropped_samples: AtomicUsize::default(),
+        }
+    }
+
+    /// Marks telemetry backend as failed: all further samples are dropped
+    pub fn set_backend_failure(&self, error: impl ToString) {
+        self.report_failure(error);
+        self.backend_failed.store(true, Ordering::Relaxed);
+    }
+
+    fn report_failure(&self, error: impl ToString) {
+        let error = error.to_string();
+        log::error!(target: "telemetry", "REMP core telemetry failure: {}", error);
+        if let Ok(mut failure) = self.backend_failure.lock() {
+            *failure = Some(error);
+        }
+    }
+
+    pub fn health(&self) -> RempTelemetryHealth {
+        RempTelemetryHealth {
+            failure: self.backend_failure.lock().ok().and_then(|failure| failure.clone()),
+            dropped_samples: self.dropped_samples.load(Ordering::Relaxed),
+        }
+    }
+
+    fn accepts_samples(&self) -> bool {
+        if self.backend_failed.load(Ordering::Relaxed) {
+            self.dropped_samples.fetch_add(1, Ordering::Relaxed);
+            false
+        } else {
+            true
         }
     }
 
     pub fn message_from_fullnode(&self) {
+        if !self.accepts_samples() {
+            return
+        }
         self.got_from_fullnode.fetch_add(1, Ordering::Relaxed);
     }
 
     pub fn in_channel_from_fullnode(&self, length: usize) {
+        if !self.accepts_samples() {
+            return
+        }
         self.in_channel_from_fullnode.update(length as u64);
     }
 
     pub fn pending_from_fullnode(&self, length: usize) {
+        if !self.accepts_samples() {
+            return
+        }
         self.pending_from_fullnode.update(length as u64);
     }
 
     pub fn rejected_overload_from_fullnode(&self, length: usize) {
+        if !self.accepts_samples() {
+            return
+        }
         self.rejected_overload_from_fullnode.update(length as u64);
     }
 
@@ -500,7 +571,33 @@ impl RempCoreTelemetry {
         );
     }
 
+    /// RMQ catchain block is produced: time since the previous block of the session (None for
+    /// the first one), size of the payload and count of records still waiting to be sent
+    pub fn produced_catchain_block(&self, shard: &ShardIdent, interval_ms: Option<u64>, payload_bytes: usize, pending: usize) {
+        self.update_shard_telemetry(
+            shard,
+            |t| {
+                if let Some(interval_ms) = interval_ms {
+                    t.block_production_interval_ms.update(interval_ms);
+                }
+                t.block_payload_bytes.update(payload_bytes as u64);
+                t.pending_at_block_production.update(pending as u64);
+            }
+        );
+    }
+
+    /// RMQ catchain session is found stuck in starting or stopping state and reset by watchdog
+    pub fn stuck_catchain_session(&self, shard: &ShardIdent) {
+        self.update_shard_telemetry(
+            shard,
+            |t| { t.stuck_catchain_sessions.fetch_add(1, Ordering::Relaxed); }
+        );
+    }
+
     pub fn add_to_cache_attempt(&self, added: bool) {
+        if !self.accepts_samples() {
+            return
+        }
         self.add_to_cache_attempts.fetch_add(1, Ordering::Relaxed);
         if added {
             self.added_to_cache.fetch_add(1, Ordering::Relaxed);
@@ -508,9 +605,26 @@ impl RempCoreTelemetry {
     }
 
     pub fn deleted_from_cache(&self, deleted_messages: usize) {
+        if !self.accepts_samples() {
+            return
+        }
         self.deleted_from_cache.fetch_add(deleted_messages, Ordering::Relaxed);
     }
 
+    pub fn rate_limited_by_source(&self) {
+        if !self.accepts_samples() {
+            return
+        }
+        self.rate_limited_by_source.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn rate_limited_by_shard(&self) {
+        if !self.accepts_samples() {
+            return
+        }
+        self.rate_limited_by_shard.fetch_add(1, Ordering::Relaxed);
+    }
+
     pub fn incoming_queue_size_metric(&self) -> Arc<Metric> {
         self.incoming_queue_size.clone()
     }
@@ -529,9 +643,19 @@ impl RempCoreTelemetry {
 
     #[allow(dead_code)]
     pub fn cache_size(&self, size: usize) {
+        if !self.accepts_samples() {
+            return
+        }
         self.cache_size.update(size as u64);
     }
 
+    pub fn rmq_storage_size(&self, bytes: u64) {
+        if !self.accepts_samples() {
+            return
+        }
+        self.rmq_storage_size.update(bytes);
+    }
+
     pub fn cache_size_metric(&self) -> Arc<Metric> {
         self.cache_size.clone()
     }
@@ -540,31 +664,49 @@ impl RempCoreTelemetry {
         loop {
             if let Some(q) = self.queues.get(shard) {
                 return q.val().rmq_catchain_mutex_awaiting.clone();
-            } else {
-                let _ = add_unbound_object_to_map(&self.queues, shard.clone(),
-                    || Ok(RempQueueTelemetry::new(self.period_sec))).expect("Can't return error");
+            } else if let Err(e) = add_unbound_object_to_map(&self.queues, shard.clone(),
+                || Ok(RempQueueTelemetry::new(self.period_sec))
+            ) {
+                // Metric is not registered: its samples are not reported
+                self.report_failure(format!("cannot create telemetry of shard {}: {}", shard, e));
+                return Metric::without_totals("rmq catchain mutex awaiting", self.period_sec);
             }
         }
     }
 
     pub fn receipts_queue_in(&self, queue_size: u64) {
+        if !self.accepts_samples() {
+            return
+        }
         self.receipts_queue_size.update(queue_size);
         self.receipts_queue_in_rate.update(1);
     }
 
     pub fn receipts_queue_out(&self) {
+        if !self.accepts_samples() {
+            return
+        }
         self.receipts_queue_out_rate.update(1);
     }
 
     pub fn receipts_queue_processing(&self, duration: &Duration) {
+        if !self.accepts_samples() {
+            return
+        }
         self.receipts_queue_processing_ms.update(duration.as_millis() as u64);
     }
 
     pub fn pending_receipts(&self, val: u64) {
+        if !self.accepts_samples() {
+            return
+        }
         self.pending_receipts.update(val);
     }
 
     pub fn combined_receipt_sent(&self, size: u64, inner_receipts: u64) {
+        if !self.accepts_samples() {
+            return
+        }
         self.combined_receipts_send_rate.update(1);
         self.combined_receipt_inners.update(inner_receipts);
         self.combined_receipt_size_bytes.update(size);
@@ -575,10 +717,13 @@ impl RempCoreTelemetry {
         shard: &ShardIdent,
         mut updater: impl FnMut(&RempQueueTelemetry)
     ) {
+        if !self.accepts_samples() {
+            return
+        }
         // We undarstand that teoretically closure might be called more than one time,
         // and `new_messages` might added twice and more, but in practise we usually don't have 
         // concurrent access to one shard.
-        add_unbound_object_to_map_with_update(
+        let result = add_unbound_object_to_map_with_update(
             &self.queues,
             shard.clone(),
             |found| if let Some(found) = found {
@@ -589,7 +734,11 @@ impl RempCoreTelemetry {
                 updater(&t);
                 Ok(Some(t))
             }
-        ).expect("Can't return error");
+        );
+        if let Err(e) = result {
+            self.dropped_samples.fetch_add(1, Ordering::Relaxed);
+            self.report_failure(format!("cannot update telemetry of shard {}: {}", shard, e));
+        }
     }
 
     pub fn report(&self) -> String {
@@ -617,6 +766,8 @@ impl RempCoreTelemetry {
         reset_and_print_single_metric(&self.got_from_fullnode, "got from fullnode", &mut report);
         reset_and_print_metric(&self.in_channel_from_fullnode, &mut report);
         reset_and_print_metric(&self.pending_from_fullnode, &mut report);
+        reset_and_print_single_metric(&self.rate_limited_by_source, "rate limited by source", &mut report);
+        reset_and_print_single_metric(&self.rate_limited_by_shard, "rate limited by shard", &mut report);
 
         for guard in &self.queues {
             let shard_ident = guard.key();
@@ -631,6 +782,10 @@ impl RempCoreTelemetry {
             reset_and_print_metric(&rqt.in_channel_to_rmq, &mut report);
             reset_and_print_metric(&rqt.pending_collation, &mut report);
             reset_and_print_metric(&rqt.rmq_catchain_mutex_awaiting, &mut report);
+            reset_and_print_metric(&rqt.block_production_interval_ms, &mut report);
+            reset_and_print_metric(&rqt.block_payload_bytes, &mut report);
+            reset_and_print_metric(&rqt.pending_at_block_production, &mut report);
+            reset_and_print_single_metric(&rqt.stuck_catchain_sessions, "stuck catchain sessions", &mut report);
         }
 
         let total = reset_and_print_single_metric(&self.add_to_cache_attempts, "add to cache (total)", &mut report);
@@ -639,6 +794,7 @@ impl RempCoreTelemetry {
         reset_and_print_single_metric(&self.deleted_from_cache, "deleted from cache", &mut report);
         
         reset_and_print_metric(&self.cache_size, &mut report);
+        reset_and_print_metric(&self.rmq_storage_size, &mut report);
         reset_and_print_metric(&self.incoming_queue_size, &mut report);
         reset_and_print_metric(&self.incoming_mutex_awaiting, &mut report);
         reset_and_print_metric(&self.collator_receipt_queue_size, &mut report);
@@ -652,7 +808,15 @@ impl RempCoreTelemetry {
         reset_and_print_metric(&self.combined_receipt_inners, &mut report);
         reset_and_print_metric(self.combined_receipts_send_rate.metric(), &mut report);
 
-        report.string().expect("unexpected error while building remp core telemetry report")
+        let health = self.health();
+        if health.failure.is_some() || health.dropped_samples > 0 {
+            report.append(format!("{:<38}{:>5}\n", "dropped samples", health.dropped_samples));
+            if let Some(failure) = health.failure {
+                report.append(format!("backend failure: {}\n", failure));
+            }
+        }
+
+        report.string().unwrap_or_else(|e| format!("Cannot build REMP core telemetry report: {}", e))
     }
 }
 
@@ -0,0 +1,117 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+fn transcript(id: u8) -> Arc<CatchainTranscript> {
+    Arc::new(CatchainTranscript::new(
+        UInt256::from([id; 32]), ShardIdent::masterchain(), 10..=11, &[]
+    ))
+}
+
+fn block(hash: &str, prev: Option<&str>) -> TranscriptBlock {
+    TranscriptBlock {
+        hash: hash.to_string(),
+        source_idx: 1,
+        prev: prev.map(|prev| prev.to_string()),
+        deps: Vec::new(),
+        payload_hash: "00".to_string(),
+        created_at_ms: 1000,
+        received_at_ms: 1100,
+        messages: vec!["11".to_string()],
+        rejected: Vec::new(),
+    }
+}
+
+#[test]
+fn test_catchain_transcript_json() {
+    let t = transcript(1);
+    t.add_block(block("aa", None));
+    t.add_block(block("bb", Some("aa")));
+    t.finish();
+
+    let json: serde_json::Value = serde_json::from_str(&t.to_json(0, 10).unwrap()).unwrap();
+    assert_eq!(json["session_id"], UInt256::from([1; 32]).to_hex_string());
+    assert_eq!(json["master_cc_range"], serde_json::json!([10, 11]));
+    assert_eq!(json["finished"], true);
+    assert_eq!(json["blocks_total"], 2);
+    assert_eq!(json["blocks"][1]["prev"], "aa");
+    assert_eq!(json["blocks"][1]["messages"][0], "11");
+}
+
+#[test]
+fn test_catchain_transcript_pages() {
+    let t = transcript(1);
+    for i in 0..MAX_TRANSCRIPT_PAGE_BLOCKS + 5 {
+        t.add_block(block(&format!("{:x}", i), None));
+    }
+    let page = |offset, limit| -> serde_json::Value {
+        serde_json::from_str(&t.to_json(offset, limit).unwrap()).unwrap()
+    };
+
+    let json = page(0, usize::MAX);
+    assert_eq!(json["blocks_total"], MAX_TRANSCRIPT_PAGE_BLOCKS + 5);
+    assert_eq!(json["blocks_stored"], MAX_TRANSCRIPT_PAGE_BLOCKS + 5);
+    assert_eq!(json["blocks"].as_array().unwrap().len(), MAX_TRANSCRIPT_PAGE_BLOCKS);
+
+    let json = page(MAX_TRANSCRIPT_PAGE_BLOCKS, usize::MAX);
+    assert_eq!(json["blocks_offset"], MAX_TRANSCRIPT_PAGE_BLOCKS);
+    assert_eq!(json["blocks"].as_array().unwrap().len(), 5);
+    assert_eq!(json["blocks"][0]["hash"], format!("{:x}", MAX_TRANSCRIPT_PAGE_BLOCKS));
+
+    let json = page(2, 3);
+    assert_eq!(json["blocks"].as_array().unwrap().len(), 3);
+    assert_eq!(json["blocks"][0]["hash"], "2");
+
+    let json = page(MAX_TRANSCRIPT_PAGE_BLOCKS * 2, 10);
+    assert_eq!(json["blocks_offset"], MAX_TRANSCRIPT_PAGE_BLOCKS + 5);
+    assert!(json["blocks"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_catchain_transcript_store() {
+    let disabled = CatchainTranscriptStore::with_capacity(0);
+    disabled.add(transcript(1));
+    assert!(disabled.list().is_empty());
+
+    let store = CatchainTranscriptStore::with_capacity(2);
+    store.add(transcript(1));
+    store.add(transcript(2));
+    store.add(transcript(3));
+    let ids = store.list().into_iter().map(|(id, _, _)| id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![UInt256::from([2; 32]), UInt256::from([3; 32])]);
+    assert!(store.export(&UInt256::from([1; 32]), 0, 10).is_err());
+    assert!(store.export(&UInt256::from([3; 32]), 0, 10).is_ok());
+}
+
+#[test]
+fn test_catchain_transcript_propagation() {
+    let t = transcript(1);
+    t.add_block(block("aa", None));
+    let mut late = block("bb", Some("aa"));
+    late.received_at_ms = 1300;
+    t.add_block(late);
+    let mut other = block("cc", None);
+    other.source_idx = 0;
+    t.add_block(other);
+
+    assert_eq!(t.propagation(), vec![
+        SourcePropagation { source_idx: 0, blocks: 1, avg_delay_ms: 100, max_delay_ms: 100 },
+        SourcePropagation { source_idx: 1, blocks: 2, avg_delay_ms: 200, max_delay_ms: 300 },
+    ]);
+
+    let store = CatchainTranscriptStore::with_capacity(2);
+    store.add(t);
+    let json: serde_json::Value = serde_json::from_str(&store.export_propagation().unwrap()).unwrap();
+    assert_eq!(json[0]["sources"][1]["max_delay_ms"], 300);
+}
@@ -13,7 +13,7 @@
 
 use super::*;
 use crate::{
-    collator_test_bundle::CollatorTestBundle, engine_traits::EngineOperations, 
+    collator_test_bundle::CollatorTestBundle, config::CollatorConfig, engine_traits::EngineOperations, 
     types::messages::{count_matching_bits, MsgEnvelopeStuff},
     validator::{
         CollatorSettings, collator,
@@ -120,6 +120,63 @@ async fn test_collate_first_block() {
     try_collate_by_bundle(bundle).await.unwrap();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collate_first_block_deterministic() {
+    let mut hashes = Vec::new();
+    for (parallel_execution, max_collate_threads) in [(false, 10), (true, 1), (true, 2), (true, 10)] {
+        let mut bundle = CollatorTestBundle::build_with_zero_state(
+            "src/tests/static/zerostate.boc",
+            &["src/tests/static/basestate0.boc", "src/tests/static/basestate0.boc"]
+        ).await.unwrap();
+        bundle.set_collator_config(CollatorConfig {
+            parallel_execution,
+            max_collate_threads,
+            ..Default::default()
+        });
+        let bundle = Arc::new(bundle);
+        let (block, state) = try_collate_by_engine(
+            bundle.clone(),
+            bundle.block_id().shard().clone(),
+            bundle.prev_blocks_ids().clone(),
+            Some(bundle.created_by().clone()),
+            Some(UInt256::from([1; 32])),
+        ).await.unwrap();
+        hashes.push((block.serialize().unwrap().repr_hash(), state.serialize().unwrap().repr_hash()));
+    }
+    // the same block and state regardless of the number of transactions executed in parallel
+    for hash in &hashes[1..] {
+        assert_eq!(hash, &hashes[0]);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collate_dry_run_first_block() {
+    let bundle = Arc::new(CollatorTestBundle::build_with_zero_state(
+        "src/tests/static/zerostate.boc",
+        &["src/tests/static/basestate0.boc", "src/tests/static/basestate0.boc"]
+    ).await.unwrap());
+    let shard = bundle.block_id().shard().clone();
+    let mc_state = bundle.load_last_applied_mc_state().await.unwrap();
+    let cc_seqno = mc_state.shard_state_extra().unwrap().shards.calc_shard_cc_seqno(&shard).unwrap();
+    let mut cc_seqno_with_delta = 0;
+    let nodes = compute_validator_set_cc(&mc_state, &shard, 1, cc_seqno, &mut cc_seqno_with_delta).unwrap();
+    let collator = collator::Collator::new(
+        shard.clone(),
+        0,
+        bundle.prev_blocks_ids().clone(),
+        ValidatorSet::with_cc_seqno(0, 0, 0, cc_seqno_with_delta, nodes).unwrap(),
+        UInt256::default(),
+        bundle.clone(),
+        None,
+        CollatorSettings::default(),
+    ).unwrap();
+    let report = collator.collate_dry_run().await.unwrap();
+    assert_eq!(report.block_id.shard(), &shard);
+    assert_eq!(report.block_id.seq_no(), 1);
+    assert!(report.size > 0);
+    assert!(report.remp_accepted.is_empty());
+}
+
 // prepare for testing purposes
 fn prepare_test_env_message(
     src_prefix: u64, 
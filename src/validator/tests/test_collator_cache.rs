@@ -0,0 +1,63 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+fn update(cache: &CollatorWorkCache, shard: &ShardIdent, config_hash: u8) {
+    cache.update(
+        shard,
+        Vec::new(),
+        (UInt256::from([config_hash; 32]), BlockchainConfig::default()),
+        (UInt256::from([config_hash; 32]), Libraries::default()),
+    );
+}
+
+#[test]
+fn test_collator_cache_config_invalidation() {
+    let cache = CollatorWorkCache::new();
+    let shard = ShardIdent::with_tagged_prefix(0, 0x8000_0000_0000_0000).unwrap();
+    assert!(cache.config(&shard, &UInt256::from([1; 32])).is_none());
+
+    update(&cache, &shard, 1);
+    assert!(cache.config(&shard, &UInt256::from([1; 32])).is_some());
+    assert!(cache.libraries(&shard, &UInt256::from([1; 32])).is_some());
+    assert!(cache.config(&shard, &UInt256::from([2; 32])).is_none());
+    assert!(cache.libraries(&shard, &UInt256::from([2; 32])).is_none());
+    assert!(cache.state(&shard, &BlockIdExt::default()).is_none());
+
+    assert_eq!(cache.stats(), CollatorWorkCacheStats {
+        state_hits: 0,
+        state_misses: 1,
+        config_hits: 1,
+        config_misses: 2,
+    });
+}
+
+#[test]
+fn test_collator_cache_split() {
+    let cache = CollatorWorkCache::new();
+    let parent = ShardIdent::with_tagged_prefix(0, 0x8000_0000_0000_0000).unwrap();
+    let (left, right) = parent.split().unwrap();
+    let other = ShardIdent::with_tagged_prefix(1, 0x8000_0000_0000_0000).unwrap();
+
+    update(&cache, &parent, 1);
+    update(&cache, &other, 1);
+    update(&cache, &left, 1);
+    assert!(cache.config(&parent, &UInt256::from([1; 32])).is_none());
+    assert!(cache.config(&left, &UInt256::from([1; 32])).is_some());
+    assert!(cache.config(&other, &UInt256::from([1; 32])).is_some());
+
+    update(&cache, &right, 1);
+    assert!(cache.config(&left, &UInt256::from([1; 32])).is_some());
+    assert!(cache.config(&right, &UInt256::from([1; 32])).is_some());
+}
@@ -0,0 +1,54 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use std::time::Duration;
+
+#[test]
+fn test_consensus_stats() {
+    let mut stats = ConsensusStats::new(1000);
+    stats.round_committed(1500, 100);
+    stats.round_skipped(3500);
+    stats.round_committed(4000, 300);
+    assert_eq!(stats.rounds, 3);
+    assert_eq!(stats.skipped, 1);
+    assert_eq!(stats.avg_round_ms(), 1000);
+    assert_eq!(stats.max_round_ms, 2000);
+    assert_eq!(stats.avg_payload(), 200);
+    assert_eq!(stats.max_payload, 300);
+}
+
+#[test]
+fn test_consensus_suggestions() {
+    let mut opts = SessionOptions::default();
+    opts.round_attempt_duration = Duration::from_secs(16);
+    opts.max_block_size = 1000;
+
+    let mut stats = ConsensusStats::new(0);
+    for i in 0..MIN_ROUNDS_FOR_SUGGESTIONS as u64 - 1 {
+        stats.round_skipped(i * 1000);
+    }
+    assert!(stats.suggestions(&opts).is_empty());
+
+    stats.round_committed(100_000, 950);
+    assert_eq!(stats.suggestions(&opts), vec![
+        ConsensusSuggestion { param: "attempt_duration", current: 16, suggested: 24 },
+        ConsensusSuggestion { param: "max_block_bytes", current: 1000, suggested: 1500 },
+    ]);
+
+    let mut stats = ConsensusStats::new(0);
+    for i in 0..MIN_ROUNDS_FOR_SUGGESTIONS as u64 {
+        stats.round_committed(i * 1000, 100);
+    }
+    assert!(stats.suggestions(&opts).is_empty());
+}
@@ -0,0 +1,57 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+#[test]
+fn test_deferred_dispatch_unlimited() {
+    let mut dispatch = DeferredDispatch::new(0);
+    let account = AccountId::from([1; 32]);
+    for i in 0..100 {
+        assert_eq!(dispatch.dispatch(&account, &UInt256::from([i; 32])), None);
+    }
+    assert_eq!(dispatch.deferred_count(), 0);
+}
+
+#[test]
+fn test_deferred_dispatch_per_account() {
+    let mut dispatch = DeferredDispatch::new(2);
+    let hot = AccountId::from([1; 32]);
+    let other = AccountId::from([2; 32]);
+
+    assert_eq!(dispatch.dispatch(&hot, &UInt256::from([1; 32])), None);
+    assert_eq!(dispatch.dispatch(&hot, &UInt256::from([2; 32])), None);
+    assert_eq!(dispatch.dispatch(&hot, &UInt256::from([3; 32])), Some(1));
+    assert_eq!(dispatch.dispatch(&other, &UInt256::from([4; 32])), None);
+    assert_eq!(dispatch.dispatch(&hot, &UInt256::from([5; 32])), Some(2));
+    assert_eq!(dispatch.deferred_count(), 2);
+
+    let mut deferred = dispatch.withdraw_deferred();
+    deferred.sort_by_key(|(_, _, position, _)| *position);
+    assert_eq!(deferred, vec![
+        (UInt256::from([3; 32]), hot.clone(), 1, 2),
+        (UInt256::from([5; 32]), hot, 2, 2),
+    ]);
+    assert_eq!(dispatch.deferred_count(), 0);
+    assert_eq!(deferred_sub_status(2), "queued, position 2");
+}
+
+#[test]
+fn test_round_robin() {
+    let messages = vec![(1, "a1"), (1, "a2"), (1, "a3"), (2, "b1"), (1, "a4"), (3, "c1"), (2, "b2")];
+    assert_eq!(
+        round_robin(messages).into_iter().map(|(_, msg)| msg).collect::<Vec<_>>(),
+        vec!["a1", "b1", "c1", "a2", "b2", "a3", "a4"]
+    );
+    assert!(round_robin(Vec::<(u8, u8)>::new()).is_empty());
+}
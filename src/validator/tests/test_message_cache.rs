@@ -15,14 +15,21 @@ use std::mem::swap;
 use std::sync::Arc;
 use std::time::Duration;
 use openssl::rand::rand_bytes;
+use storage::{
+    db::rocksdb::RocksDb, remp_messages_db::{RempMessagesDb, REMP_MESSAGES_DB_NAME},
+    remp_replay_db::{RempReplayDb, REMP_REPLAY_DB_NAME}
+};
 use rand::{Rng, thread_rng};
 use adnl::telemetry::Metric;
-use ton_api::ton::ton_node::{RempMessageLevel, RempMessageStatus, rempmessagestatus::RempAccepted};
+use ton_api::ton::ton_node::{RempMessageLevel, RempMessageStatus, rempmessagestatus::{RempAccepted, RempRejected}};
 use ton_block::{BlockIdExt, ShardIdent};
 use ton_types::{Result, SliceData, error, UInt256};
 use crate::engine_traits::RempDuplicateStatus;
 use crate::ext_messages::get_level_and_level_change;
-use crate::validator::message_cache::{MessageCache, RmqMessage};
+use crate::validator::message_cache::{
+    MessageCache, RempMessageStatusFilter, RempStatusOrigin, RmqMessage, MAX_STATUS_HISTORY_LEN,
+    MESSAGE_CACHE_SATURATED_ERROR
+};
 use crate::validator::reliable_message_queue::MessageQueue;
 
 //use crate::test_helper::init_test_log;
@@ -402,3 +409,321 @@ pub fn test_message_cache_cc_ranges_and_gc() -> Result<()> {
         Ok(())
     })
 }
+
+#[test]
+pub fn test_message_cache_persistent() -> Result<()> {
+    let open_db = || -> Result<RempMessagesDb> {
+        let db = RocksDb::with_path("target/test", "remp_messages_persistent")?;
+        RempMessagesDb::with_db(db, REMP_MESSAGES_DB_NAME, true)
+    };
+    let new_cache = || -> Result<MessageCache> {
+        Ok(MessageCache::with_metrics(
+            #[cfg(feature = "telemetry")]
+            Metric::without_totals("message_cache cache_size_metric", 0)
+        ).with_persistent_db(open_db()?))
+    };
+    let rt = tokio::runtime::Runtime::new()?;
+    let msg = Arc::new(RmqMessage::make_test_message(&gen_random_body(100)?)?);
+    let accepted = RempMessageStatus::TonNode_RempAccepted(RempAccepted {
+        level: RempMessageLevel::TonNode_RempCollator,
+        block_id: BlockIdExt::with_params(ShardIdent::masterchain(), 1, UInt256::rand(), UInt256::rand()),
+        master_id: BlockIdExt::default()
+    });
+
+    let cache = new_cache()?;
+    cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+    rt.block_on(cache.add_external_message_status(
+        &msg.message_id, &msg.message_uid, Some(msg.clone()),
+        RempMessageStatus::TonNode_RempNew, |_old, new| new.clone(), 1
+    ))?;
+    cache.update_message_status(&msg.message_id, accepted.clone())?;
+    drop(cache);
+
+    // Node restart: message is restored when its session is created
+    let cache = new_cache()?;
+    assert!(cache.get_message(&msg.message_id)?.is_none());
+    cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+    let (restored, status) = cache.get_message_with_status(&msg.message_id)?
+        .ok_or_else(|| error!("Message is not restored"))?;
+    assert_eq!(restored.message, msg.message);
+    assert_eq!(status, accepted);
+
+    cache.try_set_master_cc_start_time(2, 2.into(), vec!())?;
+    cache.update_master_cc_ranges(2, Duration::from_secs(1))?;
+    rt.block_on(cache.gc_old_messages(2));
+    drop(cache);
+
+    let cache = new_cache()?;
+    cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+    assert!(cache.get_message(&msg.message_id)?.is_none());
+    Ok(())
+}
+
+#[test]
+pub fn test_message_cache_capacity() -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let cache = MessageCache::with_metrics(
+        #[cfg(feature = "telemetry")]
+        Metric::without_totals("message_cache cache_size_metric", 0)
+    ).with_capacity(Some(2), None);
+    cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+
+    let add = |msg: &Arc<RmqMessage>| rt.block_on(cache.add_external_message_status(
+        &msg.message_id, &msg.message_uid, Some(msg.clone()),
+        RempMessageStatus::TonNode_RempNew, |_old, new| new.clone(), 1
+    ));
+    let messages = (0..4)
+        .map(|i| Ok(Arc::new(RmqMessage::make_test_message(&gen_random_body(i)?)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    add(&messages[0])?;
+    add(&messages[1])?;
+    cache.update_message_status(&messages[0].message_id, RempMessageStatus::TonNode_RempTimeout)?;
+
+    // Message with final status is evicted to make room for the new one
+    add(&messages[2])?;
+    assert_eq!(cache.all_messages_count(), 2);
+    assert!(cache.get_message(&messages[0].message_id)?.is_none());
+    assert!(cache.get_message(&messages[1].message_id)?.is_some());
+
+    // Nothing to evict: new message is rejected
+    let err = add(&messages[3]).expect_err("Message must be rejected");
+    assert!(err.to_string().contains(MESSAGE_CACHE_SATURATED_ERROR));
+    assert!(!cache.make_room(0));
+    assert!(cache.get_message(&messages[3].message_id)?.is_none());
+    Ok(())
+}
+
+#[test]
+pub fn test_message_cache_subscribe() -> Result<()> {
+    let tb = MessageCacheTestbench::new()?;
+    let msg = Arc::new(RmqMessage::make_test_message(&gen_random_body(100)?)?);
+    let accepted = |level| RempMessageStatus::TonNode_RempAccepted(RempAccepted {
+        level,
+        block_id: BlockIdExt::default(),
+        master_id: BlockIdExt::default()
+    });
+
+    tb.cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    tb.cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+    assert!(tb.cache.subscribe(&msg.message_id).is_err());
+
+    tb.rt.block_on(tb.cache.add_external_message_status(
+        &msg.message_id, &msg.message_uid, Some(msg.clone()),
+        RempMessageStatus::TonNode_RempNew, |_old, new| new.clone(), 1
+    ))?;
+    let mut receiver = tb.cache.subscribe(&msg.message_id)?;
+    assert_eq!(*receiver.borrow(), RempMessageStatus::TonNode_RempNew);
+
+    tb.cache.update_message_status(&msg.message_id, accepted(RempMessageLevel::TonNode_RempCollator))?;
+    assert!(receiver.has_changed().unwrap());
+    assert_eq!(*receiver.borrow_and_update(), accepted(RempMessageLevel::TonNode_RempCollator));
+
+    tb.cache.update_message_status(&msg.message_id, accepted(RempMessageLevel::TonNode_RempMasterchain))?;
+    assert_eq!(*receiver.borrow_and_update(), accepted(RempMessageLevel::TonNode_RempMasterchain));
+
+    // Message expires: subscription is closed
+    for cc in 2..=3 {
+        tb.cache.try_set_master_cc_start_time(cc, cc.into(), vec!())?;
+        let range = tb.cache.update_master_cc_ranges(cc, Duration::from_secs(1))?;
+        tb.rt.block_on(tb.cache.gc_old_messages(*range.start()));
+    }
+    assert!(tb.cache.get_message(&msg.message_id)?.is_none());
+    assert!(receiver.has_changed().is_err());
+    Ok(())
+}
+
+#[test]
+pub fn test_message_cache_gc_timeout() -> Result<()> {
+    let tb = MessageCacheTestbench::new()?;
+    let pending = Arc::new(RmqMessage::make_test_message(&gen_random_body(100)?)?);
+    let rejected = Arc::new(RmqMessage::make_test_message(&gen_random_body(100)?)?);
+    let reject = RempMessageStatus::TonNode_RempRejected(RempRejected {
+        level: RempMessageLevel::TonNode_RempQueue,
+        block_id: BlockIdExt::default(),
+        error: "test".to_string()
+    });
+
+    tb.cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    tb.cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+    for msg in [&pending, &rejected] {
+        tb.rt.block_on(tb.cache.add_external_message_status(
+            &msg.message_id, &msg.message_uid, Some(msg.clone()),
+            RempMessageStatus::TonNode_RempNew, |_old, new| new.clone(), 1
+        ))?;
+    }
+    let pending_receiver = tb.cache.subscribe(&pending.message_id)?;
+    let rejected_receiver = tb.cache.subscribe(&rejected.message_id)?;
+    tb.cache.update_message_status(&rejected.message_id, reject.clone())?;
+
+    // Pending message gets Timeout before removal, final status is kept
+    for cc in 2..=3 {
+        tb.cache.try_set_master_cc_start_time(cc, cc.into(), vec!())?;
+        let range = tb.cache.update_master_cc_ranges(cc, Duration::from_secs(1))?;
+        tb.rt.block_on(tb.cache.gc_old_messages(*range.start()));
+    }
+    assert!(tb.cache.get_message(&pending.message_id)?.is_none());
+    assert_eq!(*pending_receiver.borrow(), RempMessageStatus::TonNode_RempTimeout);
+    assert_eq!(*rejected_receiver.borrow(), reject);
+    Ok(())
+}
+
+#[test]
+pub fn test_message_cache_status_history() -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let cache = MessageCache::with_metrics(
+        #[cfg(feature = "telemetry")]
+        Metric::without_totals("message_cache cache_size_metric", 0)
+    ).with_status_history();
+    cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+
+    let msg = Arc::new(RmqMessage::make_test_message(&gen_random_body(100)?)?);
+    assert!(cache.get_status_history(&msg.message_id).is_none());
+    rt.block_on(cache.add_external_message_status(
+        &msg.message_id, &msg.message_uid, Some(msg.clone()),
+        RempMessageStatus::TonNode_RempNew, |_old, new| new.clone(), 1
+    ))?;
+    for _ in 0..MAX_STATUS_HISTORY_LEN {
+        cache.update_message_status(&msg.message_id, RempMessageStatus::TonNode_RempTimeout)?;
+    }
+    let history = cache.get_status_history(&msg.message_id).unwrap();
+    assert_eq!(history.len(), MAX_STATUS_HISTORY_LEN);
+    // The oldest entry is dropped
+    assert!(history.iter().all(|entry| entry.origin == RempStatusOrigin::Updated));
+    assert!(history.windows(2).all(|w| w[0].timestamp_ms <= w[1].timestamp_ms));
+
+    // History is removed together with the message
+    for cc in 2..=3 {
+        cache.try_set_master_cc_start_time(cc, cc.into(), vec!())?;
+        let range = cache.update_master_cc_ranges(cc, Duration::from_secs(1))?;
+        rt.block_on(cache.gc_old_messages(*range.start()));
+    }
+    assert!(cache.get_status_history(&msg.message_id).is_none());
+    Ok(())
+}
+
+#[test]
+pub fn test_message_cache_dump() -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let cache = MessageCache::with_metrics(
+        #[cfg(feature = "telemetry")]
+        Metric::without_totals("message_cache cache_size_metric", 0)
+    );
+    cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+
+    let pending = Arc::new(RmqMessage::make_test_message(&gen_random_body(100)?)?);
+    let rejected = Arc::new(RmqMessage::make_test_message(&gen_random_body(100)?)?);
+    for msg in [&pending, &rejected] {
+        rt.block_on(cache.add_external_message_status(
+            &msg.message_id, &msg.message_uid, Some(msg.clone()),
+            RempMessageStatus::TonNode_RempNew, |_old, new| new.clone(), 1
+        ))?;
+    }
+    cache.update_message_status(&rejected.message_id, RempMessageStatus::TonNode_RempTimeout)?;
+
+    assert_eq!(cache.dump_messages(None, RempMessageStatusFilter::All)?.len(), 2);
+    let dump = cache.dump_messages(None, RempMessageStatusFilter::Pending)?;
+    assert_eq!(dump.len(), 1);
+    assert_eq!(dump[0].message_id, pending.message_id);
+    assert_eq!(dump[0].message_uid, pending.message_uid);
+    assert_eq!(dump[0].master_cc, 1);
+    assert!(dump[0].message.is_some());
+    let dump = cache.dump_messages(None, RempMessageStatusFilter::Rejected)?;
+    assert_eq!(dump.len(), 1);
+    assert_eq!(dump[0].message_id, rejected.message_id);
+    assert!(cache.dump_messages(None, RempMessageStatusFilter::Accepted)?.is_empty());
+
+    // Test messages are sent to masterchain
+    let masterchain = ShardIdent::masterchain();
+    assert_eq!(cache.dump_messages(Some(&masterchain), RempMessageStatusFilter::All)?.len(), 2);
+    let basechain = ShardIdent::full(0);
+    assert!(cache.dump_messages(Some(&basechain), RempMessageStatusFilter::All)?.is_empty());
+
+    assert_eq!("pending".parse::<RempMessageStatusFilter>()?, RempMessageStatusFilter::Pending);
+    assert!("final".parse::<RempMessageStatusFilter>().is_err());
+    Ok(())
+}
+
+#[test]
+pub fn test_message_cache_pending_new_messages() -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let cache = MessageCache::with_metrics(
+        #[cfg(feature = "telemetry")]
+        Metric::without_totals("message_cache cache_size_metric", 0)
+    );
+    cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+
+    let masterchain = ShardIdent::masterchain();
+    let mut messages = Vec::new();
+    for _ in 0..3 {
+        let msg = Arc::new(RmqMessage::make_test_message(&gen_random_body(100)?)?);
+        rt.block_on(cache.add_external_message_status(
+            &msg.message_id, &msg.message_uid, Some(msg.clone()),
+            RempMessageStatus::TonNode_RempNew, |_old, new| new.clone(), 1
+        ))?;
+        messages.push(msg);
+    }
+    let bytes = messages.iter().map(|msg| msg.size()).sum::<usize>();
+    assert_eq!(cache.pending_new_messages(&masterchain), (3, bytes));
+    assert_eq!(cache.pending_new_messages(&ShardIdent::full(0)), (0, 0));
+
+    // Messages leave the queue when their status changes
+    cache.update_message_status(&messages[0].message_id, RempMessageStatus::TonNode_RempTimeout)?;
+    assert_eq!(cache.pending_new_messages(&masterchain), (2, bytes - messages[0].size()));
+
+    for cc in 2..=3 {
+        cache.try_set_master_cc_start_time(cc, cc.into(), vec!())?;
+        let range = cache.update_master_cc_ranges(cc, Duration::from_secs(1))?;
+        rt.block_on(cache.gc_old_messages(*range.start()));
+    }
+    assert_eq!(cache.pending_new_messages(&masterchain), (0, 0));
+    Ok(())
+}
+
+#[test]
+pub fn test_message_cache_replay_protection() -> Result<()> {
+    let new_cache = || -> Result<MessageCache> {
+        let db = RocksDb::with_path("target/test", "remp_replay_protection")?;
+        Ok(MessageCache::with_metrics(
+            #[cfg(feature = "telemetry")]
+            Metric::without_totals("message_cache cache_size_metric", 0)
+        ).with_replay_db(RempReplayDb::with_db(db, REMP_REPLAY_DB_NAME, true)?))
+    };
+    let rt = tokio::runtime::Runtime::new()?;
+    let msg = Arc::new(RmqMessage::make_test_message(&gen_random_body(100)?)?);
+    let block_id = BlockIdExt::with_params(ShardIdent::masterchain(), 1, UInt256::rand(), UInt256::rand());
+    let accepted = RempMessageStatus::TonNode_RempAccepted(RempAccepted {
+        level: RempMessageLevel::TonNode_RempMasterchain,
+        block_id: block_id.clone(),
+        master_id: block_id.clone()
+    });
+    let add_new = |cache: &MessageCache| rt.block_on(cache.add_external_message_status(
+        &msg.message_id, &msg.message_uid, Some(msg.clone()),
+        RempMessageStatus::TonNode_RempNew, |_old, new| new.clone(), 1
+    ));
+    let duplicate = RempDuplicateStatus::Duplicate(block_id, msg.message_uid.clone(), msg.message_id.clone());
+
+    let cache = new_cache()?;
+    cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+    add_new(&cache)?;
+    assert_eq!(cache.check_message_duplicates(&msg.message_id)?, RempDuplicateStatus::Fresh(msg.message_uid.clone()));
+    cache.update_message_status(&msg.message_id, accepted)?;
+    assert_eq!(cache.check_message_duplicates(&msg.message_id)?, duplicate);
+    drop(cache);
+
+    // Node restart: the message is replayed, but it is already accepted
+    let cache = new_cache()?;
+    cache.try_set_master_cc_start_time(1, 1.into(), vec!())?;
+    cache.update_master_cc_ranges(1, Duration::from_secs(1))?;
+    add_new(&cache)?;
+    assert_eq!(cache.check_message_duplicates(&msg.message_id)?, duplicate);
+    Ok(())
+}
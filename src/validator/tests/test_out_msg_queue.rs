@@ -772,3 +772,34 @@ fn test_clean_queue() {
         || split_filtering_to_shard_and_creating_remaining(queue.clone(), &s0),
     );
 }
+
+#[test]
+fn test_merger_iterator_skip_upto() {
+    let (queue, _) = generate_test_queue(100, 0, vec![]);
+    let collect = |skip_upto: Option<(u64, UInt256)>| {
+        let mut iter = MsgQueueMergerIterator::from_queue(&queue).unwrap();
+        if let Some((lt, hash)) = skip_upto {
+            iter.skip_upto(lt, hash);
+        }
+        iter.map(|k_v| {
+            let (key, _enq, lt, _) = k_v.unwrap();
+            (lt, key.hash)
+        }).collect::<Vec<_>>()
+    };
+    let all = collect(None);
+    assert_eq!(all.len(), 100);
+    assert_eq!(collect(Some(all[41].clone())), all[42..].to_vec());
+    assert_eq!(collect(Some(all[99].clone())), vec![]);
+    assert_eq!(collect(Some((0, UInt256::default()))), all);
+}
+
+#[test]
+fn test_out_queue_checkpoint_validity() {
+    let neighbors = vec![BlockIdExt::with_params(ShardIdent::full(0), 10, UInt256::rand(), UInt256::rand())];
+    let checkpoint = OutQueueCheckpoint { prev_seqno: 10, neighbors: neighbors.clone(), lt: 1, hash: UInt256::rand() };
+    assert!(checkpoint.is_valid_for(10, &neighbors));
+    assert!(checkpoint.is_valid_for(11, &neighbors));
+    assert!(!checkpoint.is_valid_for(9, &neighbors));
+    let other = vec![BlockIdExt::with_params(ShardIdent::full(0), 11, UInt256::rand(), UInt256::rand())];
+    assert!(!checkpoint.is_valid_for(11, &other));
+}
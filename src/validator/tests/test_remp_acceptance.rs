@@ -0,0 +1,54 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use ton_block::{ExternalInboundMessageHeader, MsgAddressInt};
+use ton_types::{BuilderData, SliceData};
+
+struct TestEngine;
+
+#[async_trait::async_trait]
+impl EngineOperations for TestEngine {}
+
+fn create_message(wc: i32, with_abi_header: bool) -> Message {
+    let mut header = ExternalInboundMessageHeader::default();
+    header.dst = MsgAddressInt::with_standart(None, wc as i8, [0x11; 32].into()).unwrap();
+    let mut body = BuilderData::new();
+    body.append_bit_zero().unwrap(); // no signature
+    body.append_bit_zero().unwrap(); // no pubkey
+    if with_abi_header {
+        body.append_u64(1_700_000_000_123).unwrap();
+        body.append_u32(1_700_000_060).unwrap();
+    }
+    body.append_u32(0x12345678).unwrap(); // function id
+    let mut msg = Message::with_ext_in_header(header);
+    msg.set_body(SliceData::load_builder(body).unwrap());
+    msg
+}
+
+#[test]
+fn test_acceptance_policy_header() {
+    let engine = Arc::new(TestEngine);
+    let policy = DefaultRempAcceptancePolicy::new(engine.clone(), &RempAcceptancePolicyConfig::default());
+    policy.check_header(&create_message(-1, false)).unwrap();
+
+    let config = RempAcceptancePolicyConfig {
+        workchains: Some(vec![0]),
+        require_abi_header: Some(true),
+        min_dst_balance: None,
+    };
+    let policy = DefaultRempAcceptancePolicy::new(engine, &config);
+    policy.check_header(&create_message(0, true)).unwrap();
+    assert!(policy.check_header(&create_message(-1, true)).is_err());
+    assert!(policy.check_header(&create_message(0, false)).is_err());
+}
@@ -0,0 +1,489 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+#[test]
+fn test_rmq_block_payload() {
+    let payload = RmqBlockPayload {
+        producer_idx: 3,
+        timestamp_ms: 1700000000000,
+        records: vec![vec![1, 2, 3].into(), vec![].into(), vec![4; 100].into()],
+    };
+    let data = payload.serialize().unwrap();
+    assert_eq!(RmqBlockPayload::deserialize(&data, 3, 100).unwrap(), payload);
+    assert!(RmqBlockPayload::deserialize(&data[..data.len() - 1], 3, 100).is_err());
+    // records are padded as TL bytes
+    assert_eq!(data.len() % 4, 0);
+    // record is larger than allowed
+    assert!(RmqBlockPayload::deserialize(&data, 3, 99).is_err());
+
+    // Legacy payload: producer is the source of block, send time is unknown
+    let legacy = payload.serialize_legacy();
+    let decoded = RmqBlockPayload::deserialize(&legacy.0, 5, 100).unwrap();
+    assert_eq!(decoded, RmqBlockPayload { producer_idx: 5, timestamp_ms: 0, records: payload.records });
+}
+
+#[test]
+fn test_rmq_status_query() {
+    let query = RmqStatusQuery { message_id: UInt256::from([7; 32]) };
+    assert_eq!(RmqStatusQuery::deserialize(&query.serialize()).unwrap(), query);
+    assert!(RmqStatusQuery::deserialize(&[7; 32]).is_err());
+
+    assert_eq!(RmqStatusQuery::deserialize_answer(&RmqStatusQuery::serialize_answer(None)).unwrap(), None);
+    let status = RempMessageStatus::TonNode_RempNew;
+    let answer = RmqStatusQuery::serialize_answer(Some(&status));
+    assert_eq!(RmqStatusQuery::deserialize_answer(&answer).unwrap(), Some(status));
+}
+
+#[test]
+fn test_rmq_block_payload_compressed() {
+    let payload = RmqBlockPayload {
+        producer_idx: 1,
+        timestamp_ms: 1700000000000,
+        records: vec![vec![5; 1000].into(), vec![6; 1000].into()],
+    };
+    let data = payload.serialize_compressed().unwrap();
+    assert!(data.len() < payload.serialize().unwrap().len());
+    assert_eq!(RmqBlockPayload::deserialize(&data, 1, 1000).unwrap(), payload);
+}
+
+#[test]
+fn test_rmq_block_payload_lengths_checked() {
+    let payload = RmqBlockPayload {
+        producer_idx: 1,
+        timestamp_ms: 1700000000000,
+        records: vec![vec![7; 300].into()],
+    };
+    let data = payload.serialize().unwrap();
+    // tag, producer, timestamp, count, then the record's length
+    let record_len_pos = 4 + 4 + 8 + 4;
+    assert_eq!(data[record_len_pos..record_len_pos + 4], [254, 44, 1, 0]);
+
+    // length claimed by the peer exceeds remaining bytes
+    let mut forged = data.clone();
+    forged[record_len_pos + 1..record_len_pos + 4].copy_from_slice(&[0xff, 0xff, 0xff]);
+    assert!(RmqBlockPayload::deserialize(&forged, 1, usize::MAX).is_err());
+
+    // count of records exceeds remaining bytes
+    let mut forged = data.clone();
+    forged[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(RmqBlockPayload::deserialize(&forged, 1, usize::MAX).is_err());
+
+    // extra data after the records
+    let mut forged = data;
+    forged.extend_from_slice(&[0; 4]);
+    assert!(RmqBlockPayload::deserialize(&forged, 1, usize::MAX).is_err());
+}
+
+#[test]
+fn test_rmq_processor_splits_digests() {
+    let processor = make_processor(r#"{ "native_catchain_payload": true, "max_message_size": 640 }"#);
+    let mut digest = RempCatchainMessageDigest::default();
+    digest.masterchain_seqno = 5;
+    for i in 0..25u8 {
+        digest.messages.0.push(ton_api::ton::ton_node::rempcatchainmessageids::RempCatchainMessageIds {
+            id: UInt256::from([i; 32]),
+            uid: UInt256::from([i; 32]),
+        });
+    }
+    let mut queue = vec![RempCatchainRecord::TonNode_RempCatchainMessageDigest(digest.clone())];
+    let (data, msg_ids) = processor.build_payload(1700000000000, || queue.pop()).unwrap();
+    assert_eq!(msg_ids.len(), 3);
+
+    // parts fit the record size limit and contain all the messages
+    let decoded = processor.decode_block(&data.0, 0);
+    assert_eq!(decoded.len(), 3);
+    let mut messages = Vec::new();
+    for record in decoded {
+        match record {
+            RempCatchainRecord::TonNode_RempCatchainMessageDigest(part) => {
+                assert_eq!(part.masterchain_seqno, 5);
+                assert!(part.messages.0.len() <= 10);
+                messages.extend(part.messages.0.into_iter().map(|ids| ids.id));
+            }
+            _ => panic!("digest is expected")
+        }
+    }
+    assert_eq!(messages, digest.messages.0.into_iter().map(|ids| ids.id).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_record_priority_policy() {
+    let make_record = || {
+        let body = ton_types::SliceData::new(vec![1, 2, 3, 0x80]);
+        let message = RmqMessage::make_test_message(&body).unwrap();
+        let dst = message.message.ext_in_header().unwrap().dst.to_string();
+        (message.as_rmq_record(1), dst)
+    };
+    let (other, _) = make_record();
+    let (priority, priority_dst) = make_record();
+    let digest = RempCatchainRecord::TonNode_RempCatchainMessageDigest(Default::default());
+
+    // No priorities: order of arrival
+    let policy = RecordPriorityPolicy::new(&RempConfig::default());
+    assert_eq!(policy.priority(&digest), policy.priority(&priority));
+
+    let options: RempConfig = serde_json::from_str(
+        &format!(r#"{{ "priority_accounts": ["{}", "wrong"] }}"#, priority_dst)
+    ).unwrap();
+    let policy = RecordPriorityPolicy::new(&options);
+    let mut heap = BinaryHeap::new();
+    for (seqno, record) in [&other, &priority, &other, &digest].into_iter().enumerate() {
+        heap.push(PendingRecord { priority: policy.priority(record), seqno: seqno as u64, record: record.clone() });
+    }
+    let order = std::iter::from_fn(|| heap.pop()).map(|r| r.seqno).collect::<Vec<_>>();
+    assert_eq!(order, vec![3, 1, 0, 2]);
+}
+
+#[test]
+fn test_activation_delay() {
+    let now_ms = 1700000000500;
+    assert_eq!(activation_delay(1700000002, now_ms), Some(Duration::from_millis(1500)));
+    assert_eq!(activation_delay(1700000000, now_ms), None);
+    assert_eq!(activation_delay(1699999999, now_ms), None);
+}
+
+#[test]
+fn test_catchain_start_stop_decisions() {
+    use RempCatchainStatus::*;
+    assert_eq!(start_decision(None).unwrap(), StartDecision::Create);
+    assert!(start_decision(Some(&Created)).is_err());
+    for status in [Scheduled, Starting, ToStop, Stopping] {
+        assert_eq!(start_decision(Some(&status)).unwrap(), StartDecision::Wait);
+    }
+    assert_eq!(start_decision(Some(&Active)).unwrap(), StartDecision::Attach);
+
+    assert_eq!(stop_decision(None, 0), StopDecision::Done);
+    assert_eq!(stop_decision(Some(&Created), 0), StopDecision::Wait);
+    assert_eq!(stop_decision(Some(&Scheduled), 0), StopDecision::Cancel);
+    assert_eq!(stop_decision(Some(&Starting), 0), StopDecision::Wait);
+    assert_eq!(stop_decision(Some(&Active), 0), StopDecision::Stop);
+    assert_eq!(stop_decision(Some(&ToStop), 0), StopDecision::Stop);
+    assert_eq!(stop_decision(Some(&Stopping), 0), StopDecision::Done);
+}
+
+#[test]
+fn test_catchain_detach_decisions() {
+    use RempCatchainStatus::*;
+
+    // Two queues share the session: the first one detaches, the last one stops it
+    assert_eq!(start_decision(Some(&Active)).unwrap(), StartDecision::Attach);
+    assert_eq!(stop_decision(Some(&Active), 2), StopDecision::Detach);
+    assert_eq!(stop_decision(Some(&Active), 1), StopDecision::Stop);
+
+    // Session marked to stop by GC is stopped with queues attached
+    assert_eq!(stop_decision(Some(&ToStop), 2), StopDecision::Stop);
+    // GC stops active session regardless of queues
+    assert_eq!(stop_decision(Some(&Active), 0), StopDecision::Stop);
+
+    // Queues detaching from the session being stopped or removed do nothing
+    assert_eq!(stop_decision(Some(&Stopping), 2), StopDecision::Done);
+    assert_eq!(stop_decision(None, 2), StopDecision::Done);
+    // Session being started is waited for
+    assert_eq!(stop_decision(Some(&Starting), 2), StopDecision::Wait);
+}
+
+#[test]
+fn test_catchain_start_stop_interleavings() {
+    use RempCatchainStatus::*;
+
+    // Stop during start: stop waits, second start waits, then stop proceeds
+    let status = Starting;
+    assert_eq!(stop_decision(Some(&status), 0), StopDecision::Wait);
+    assert_eq!(start_decision(Some(&status)).unwrap(), StartDecision::Wait);
+    let status = Active;
+    assert_eq!(stop_decision(Some(&status), 0), StopDecision::Stop);
+
+    // Create while stopping: start waits for removal of the old session,
+    // concurrent stops (gc and queue) are no-ops
+    let status = Stopping;
+    assert_eq!(start_decision(Some(&status)).unwrap(), StartDecision::Wait);
+    assert_eq!(stop_decision(Some(&status), 0), StopDecision::Done);
+    assert_eq!(start_decision(None).unwrap(), StartDecision::Create);
+
+    // Stop after removal is a no-op
+    assert_eq!(stop_decision(None, 0), StopDecision::Done);
+}
+
+#[test]
+fn test_catchain_restart_decisions() {
+    assert_eq!(restart_decision(&Ok(())), RestartDecision::Activate);
+
+    // Failed restart removes the session instead of activating it,
+    // so the next start creates the session again
+    let failed: Result<()> = Err(error!("catchain start failed"));
+    assert_eq!(restart_decision(&failed), RestartDecision::Remove);
+    assert_eq!(start_decision(None).unwrap(), StartDecision::Create);
+    // while stop of the removed session is a no-op
+    assert_eq!(stop_decision(None, 0), StopDecision::Done);
+}
+
+#[test]
+fn test_catchain_status_persistent_code() {
+    for status in [
+        RempCatchainStatus::Created, RempCatchainStatus::Scheduled, RempCatchainStatus::Starting,
+        RempCatchainStatus::Active, RempCatchainStatus::ToStop, RempCatchainStatus::Stopping
+    ] {
+        assert_eq!(RempCatchainStatus::from_persistent(status.to_persistent()), Some(status));
+    }
+    assert_eq!(RempCatchainStatus::from_persistent(100), None);
+}
+
+#[test]
+fn test_is_stuck_in_transition() {
+    let since = 1700000000000;
+    for status in [RempCatchainStatus::Starting, RempCatchainStatus::Stopping] {
+        assert!(!is_stuck_in_transition(&status, since, since + 60000, 60));
+        assert!(is_stuck_in_transition(&status, since, since + 60001, 60));
+    }
+    for status in [RempCatchainStatus::Scheduled, RempCatchainStatus::Active, RempCatchainStatus::ToStop] {
+        assert!(!is_stuck_in_transition(&status, since, since + 600000, 60));
+    }
+}
+
+#[test]
+fn test_dir_size() {
+    const DIR: &str = "target/test_remp_catchain_dir_size";
+    std::fs::remove_dir_all(DIR).ok();
+    assert_eq!(dir_size(Path::new(DIR)), 0);
+    std::fs::create_dir_all(format!("{}/catchainreceiver1", DIR)).unwrap();
+    std::fs::write(format!("{}/catchainreceiver1/000001.sst", DIR), [0u8; 100]).unwrap();
+    std::fs::write(format!("{}/LOG", DIR), [0u8; 20]).unwrap();
+    assert_eq!(dir_size(Path::new(DIR)), 120);
+    std::fs::remove_dir_all(DIR).ok();
+}
+
+fn make_processor(options: &str) -> RmqProcessor {
+    let options: RempConfig = serde_json::from_str(options).unwrap();
+    RmqProcessor::new("test".to_string(), 0, 2, &ShardIdent::masterchain(), &options)
+}
+
+fn make_records() -> Vec<RempCatchainRecord> {
+    let body = ton_types::SliceData::new(vec![1, 2, 3, 0x80]);
+    let mut records = (0..2)
+        .map(|_| RmqMessage::make_test_message(&body).unwrap().as_rmq_record(1))
+        .collect::<Vec<_>>();
+    records.push(RempCatchainRecord::TonNode_RempCatchainMessageDigest(Default::default()));
+    records
+}
+
+fn serialized(records: &[RempCatchainRecord]) -> Vec<Vec<u8>> {
+    records.iter().map(|record| RmqMessage::serialize(record).unwrap().0).collect()
+}
+
+#[test]
+fn test_rmq_processor_block_roundtrip() {
+    for options in [
+        r#"{ "verify_catchain_records": true }"#,
+        r#"{ "verify_catchain_records": true, "native_catchain_payload": true }"#,
+        r#"{ "verify_catchain_records": true, "native_catchain_payload": true, "compress_catchain_payload": true }"#,
+    ] {
+        let processor = make_processor(options);
+        let records = make_records();
+        let mut queue = records.iter().cloned().collect::<std::collections::VecDeque<_>>();
+        let (data, msg_ids) = processor.build_payload(1700000000000, || queue.pop_front()).unwrap();
+        assert_eq!(msg_ids.len(), 3);
+        assert_eq!(processor.pending_len(), 0);
+
+        let block: BlockPayloadPtr = CatchainFactory::create_block_payload(data);
+        let decoded = processor.decode_block(&block.data().0, 0);
+        assert_eq!(serialized(&decoded), serialized(&records));
+
+        // Messages of node 0 in block of node 1 are forged, digests are kept
+        let decoded = processor.decode_block(&block.data().0, 1);
+        assert_eq!(serialized(&decoded), serialized(&records[2..]));
+    }
+}
+
+#[test]
+fn test_rmq_processor_rejects_bad_records() {
+    let records = make_records();
+    let mut queue = records.iter().cloned().collect::<std::collections::VecDeque<_>>();
+    let (data, _) = make_processor(r#"{ "native_catchain_payload": true }"#)
+        .build_payload(1700000000000, || queue.pop_front()).unwrap();
+
+    // Too large messages are skipped
+    let processor = make_processor(r#"{ "max_message_size": 10 }"#);
+    let decoded = processor.decode_block(&data.0, 0);
+    assert_eq!(serialized(&decoded), serialized(&records[2..]));
+
+    // Source mismatch is tolerated without `verify_catchain_records`
+    let processor = make_processor("{}");
+    assert_eq!(processor.decode_block(&data.0, 1).len(), 3);
+
+    // Garbage is skipped
+    assert!(processor.decode_block(&[1, 2, 3, 4, 5], 0).is_empty());
+
+    // Only digests are accepted from broadcasts
+    let source = KeyId::from_data([1; 32]);
+    let decoded = processor.decode_broadcast(&data.0, &source);
+    assert_eq!(serialized(&decoded), serialized(&records[2..]));
+}
+
+#[test]
+fn test_rmq_processor_payload_limit() {
+    let processor = make_processor(r#"{ "native_catchain_payload": true, "max_catchain_payload_size": 1 }"#);
+    let mut queue = make_records().into_iter().collect::<std::collections::VecDeque<_>>();
+    // At least one record is sent in each block, the rest is deferred
+    for pending in (0..3).rev() {
+        let (data, msg_ids) = processor.build_payload(1700000000000, || queue.pop_front()).unwrap();
+        assert_eq!(msg_ids.len(), 1);
+        assert_eq!(processor.pending_len(), pending);
+        assert_eq!(processor.decode_block(&data.0, 0).len(), 1);
+    }
+    let (_, msg_ids) = processor.build_payload(1700000000000, || None).unwrap();
+    assert!(msg_ids.is_empty());
+}
+
+#[test]
+fn test_catchain_profiles() {
+    let options: RempConfig = serde_json::from_str(r#"{
+        "service_enabled": true,
+        "max_catchain_payload_size": 1000,
+        "masterchain_catchain": { "idle_timeout_ms": 1000 },
+        "shardchain_catchain": { "idle_timeout_ms": 200, "max_deps": 4, "max_catchain_payload_size": 5000 }
+    }"#).unwrap();
+    let master = ShardIdent::masterchain();
+    let shard = ShardIdent::with_tagged_prefix(0, 0x8000_0000_0000_0000).unwrap();
+
+    let opts = options.get_catchain_options(&master).unwrap();
+    assert_eq!(opts.idle_timeout, Duration::from_secs(1));
+    assert_eq!(opts.max_deps, 2);
+    assert_eq!(options.get_max_catchain_payload_size(&master), 1000);
+
+    let opts = options.get_catchain_options(&shard).unwrap();
+    assert_eq!(opts.idle_timeout, Duration::from_millis(200));
+    assert_eq!(opts.max_deps, 4);
+    // Partition resync is for validator session catchains only
+    assert_eq!(opts.receiver_partition_timeout, Duration::default());
+    assert_eq!(options.get_max_catchain_payload_size(&shard), 5000);
+
+    let options = RempConfig::default();
+    assert_eq!(options.get_max_catchain_payload_size(&shard), 64 << 10);
+}
+
+#[test]
+fn test_idle_pacer() {
+    let base = Duration::from_millis(100);
+    let mut pacer = IdlePacer::new(base, Some(Duration::from_millis(300)), 2);
+    assert_eq!(pacer.on_block(0), None);
+    assert_eq!(pacer.on_block(0), Some(Duration::from_millis(200)));
+    assert_eq!(pacer.on_block(0), None);
+    assert_eq!(pacer.on_block(0), Some(Duration::from_millis(300)));
+    assert_eq!(pacer.on_block(0), None);
+    assert_eq!(pacer.on_block(0), None);
+    // Traffic returns
+    assert_eq!(pacer.on_block(1), Some(base));
+    assert_eq!(pacer.on_block(1), None);
+    assert_eq!(pacer.on_block(0), None);
+
+    let mut disabled = IdlePacer::new(base, None, 1);
+    assert_eq!(disabled.on_block(0), None);
+    assert_eq!(disabled.on_block(0), None);
+    assert_eq!(disabled.on_enqueue(), None);
+}
+
+#[test]
+fn test_idle_pacer_enqueue() {
+    let base = Duration::from_millis(100);
+    let mut pacer = IdlePacer::new(base, Some(Duration::from_millis(400)), 1);
+    assert_eq!(pacer.on_enqueue(), None);
+    assert_eq!(pacer.on_block(0), Some(Duration::from_millis(200)));
+    assert_eq!(pacer.on_block(0), Some(Duration::from_millis(400)));
+    // Record is enqueued while backed off: base timeout is set at once
+    assert_eq!(pacer.on_enqueue(), Some(base));
+    assert_eq!(pacer.on_enqueue(), None);
+    assert_eq!(pacer.on_block(1), None);
+    // Backing off starts over
+    assert_eq!(pacer.on_block(0), Some(Duration::from_millis(200)));
+}
+
+#[test]
+fn test_catchain_max_idle_timeout_check() {
+    let options: RempConfig = serde_json::from_str(r#"{
+        "catchain_max_idle_timeout_ms": 5000,
+        "masterchain_catchain": { "idle_timeout_ms": 1000 }
+    }"#).unwrap();
+    options.check().unwrap();
+
+    // Default idle timeout of shardchain sessions is 5000 ms
+    let options: RempConfig = serde_json::from_str(r#"{
+        "catchain_max_idle_timeout_ms": 4000,
+        "masterchain_catchain": { "idle_timeout_ms": 1000 }
+    }"#).unwrap();
+    assert!(options.check().is_err());
+
+    let options: RempConfig = serde_json::from_str(r#"{
+        "catchain_max_idle_timeout_ms": 500,
+        "shardchain_catchain": { "idle_timeout_ms": 200 },
+        "masterchain_catchain": { "idle_timeout_ms": 1000 }
+    }"#).unwrap();
+    assert!(options.check().is_err());
+
+    RempConfig::default().check().unwrap();
+}
+
+#[tokio::test]
+async fn test_orphaned_sessions_restart_and_reattach() -> Result<()> {
+    const SESSIONS_DB: &str = "remp_sessions";
+    let root = "target/test_orphaned_sessions";
+    let _ = std::fs::remove_dir_all(root);
+    let entry = |master_cc_seqno: u32, overlay: u8| RempSessionEntry {
+        shard: ShardIdent::masterchain(),
+        master_cc_seqno,
+        status: RempCatchainStatus::Active.to_persistent(),
+        catchain_overlay_id: UInt256::from([overlay; 32])
+    };
+    let resumed = UInt256::from([1; 32]);
+    let obsolete = UInt256::from([2; 32]);
+
+    // Previous run: both sessions are running when the node stops
+    {
+        let db = RocksDb::with_path(root, SESSIONS_DB)?;
+        let sessions_db = RempSessionsDb::with_db(db, SESSIONS_DB, true)?;
+        sessions_db.put_session(&resumed, &entry(10, 1))?;
+        sessions_db.put_session(&obsolete, &entry(5, 2))?;
+    }
+
+    // Restart
+    let db = RocksDb::with_path(root, SESSIONS_DB)?;
+    let store = RempCatchainStore::new(1)
+        .with_persistent_db(RempSessionsDb::with_db(db, SESSIONS_DB, true)?, root.to_string());
+    let catchain_db = store.catchain_db_path(&UInt256::from([1; 32]));
+    std::fs::create_dir_all(&catchain_db)?;
+    let recorded = |store: &RempCatchainStore| -> Result<Vec<UInt256>> {
+        let mut ids = Vec::new();
+        store.sessions_db.as_ref().unwrap().for_each_session(&mut |id, _| { ids.push(id); Ok(()) })?;
+        Ok(ids)
+    };
+
+    // First GC pass: the session of the actual master catchain is not started yet,
+    // it is kept with its catchain DB; the session older than GC horizon is removed
+    store.cleanup_orphaned_sessions(&HashSet::new(), 8).await?;
+    assert_eq!(recorded(&store)?, vec![resumed.clone()]);
+    assert!(catchain_db.exists());
+
+    // The queue is re-attached to the session
+    store.cleanup_orphaned_sessions(&HashSet::from([resumed.clone()]), 11).await?;
+    assert_eq!(recorded(&store)?, vec![resumed.clone()]);
+    assert!(catchain_db.exists());
+
+    // The session is not resumed, and GC horizon has passed it
+    store.cleanup_orphaned_sessions(&HashSet::new(), 11).await?;
+    assert!(recorded(&store)?.is_empty());
+
+    drop(store);
+    let _ = std::fs::remove_dir_all(root);
+    Ok(())
+}
@@ -0,0 +1,115 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use std::sync::Mutex;
+
+struct TestMessage(u32);
+
+impl fmt::Display for TestMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "test message {}", self.0)
+    }
+}
+
+// Incoming messages and current shards of them
+#[derive(Default)]
+struct TestQueue {
+    incoming: Mutex<VecDeque<Arc<TestMessage>>>,
+    shards: Mutex<HashMap<u32, ShardIdent>>,
+}
+
+impl TestQueue {
+    fn set_shard(&self, msg: u32, shard: &ShardIdent) {
+        self.shards.lock().unwrap().insert(msg, shard.clone());
+    }
+}
+
+#[async_trait::async_trait]
+impl RempQueue<TestMessage> for TestQueue {
+    fn receive_message(&self) -> Result<Option<Arc<TestMessage>>> {
+        Ok(self.incoming.lock().unwrap().pop_front())
+    }
+
+    async fn compute_shard(&self, msg: Arc<TestMessage>) -> Result<ShardIdent> {
+        self.shards.lock().unwrap().get(&msg.0).cloned().ok_or_else(|| error!("unknown {}", msg))
+    }
+}
+
+fn make_dispatcher() -> RempQueueDispatcher<TestMessage, TestQueue> {
+    RempQueueDispatcher::with_metric(
+        "test".to_string(),
+        TestQueue::default(),
+        #[cfg(feature = "telemetry")]
+        Metric::without_totals("test queue size", 0),
+        #[cfg(feature = "telemetry")]
+        Metric::without_totals("test mutex awaiting", 0)
+    )
+}
+
+async fn poll_id(dispatcher: &RempQueueDispatcher<TestMessage, TestQueue>, shard: &ShardIdent) -> Option<u32> {
+    dispatcher.poll(shard).await.0.map(|msg| msg.0)
+}
+
+#[tokio::test]
+async fn test_dispatcher_shard_split() {
+    let dispatcher = make_dispatcher();
+    let parent = ShardIdent::full(0);
+    let (left, right) = parent.split().unwrap();
+    dispatcher.add_actual_shard(&parent).await;
+
+    // Message is computed for a child shard before its queue is added: it is kept
+    dispatcher.queue.set_shard(1, &left);
+    dispatcher.queue.incoming.lock().unwrap().push_back(Arc::new(TestMessage(1)));
+    assert_eq!(poll_id(&dispatcher, &parent).await, None);
+
+    // Message of the parent shard is returned back, then the parent is split
+    dispatcher.queue.set_shard(2, &right);
+    dispatcher.return_back(Arc::new(TestMessage(2)), &parent).await;
+    dispatcher.add_actual_shard(&left).await;
+    dispatcher.add_actual_shard(&right).await;
+    let remaining = dispatcher.remove_actual_shard(&parent).await.unwrap();
+    dispatcher.reroute_messages(&remaining, &parent).await;
+
+    assert_eq!(poll_id(&dispatcher, &left).await, Some(1));
+    assert_eq!(poll_id(&dispatcher, &right).await, Some(2));
+    assert_eq!(poll_id(&dispatcher, &right).await, None);
+
+    // Messages of shards not served by the validator are dropped
+    dispatcher.queue.set_shard(3, &ShardIdent::masterchain());
+    dispatcher.queue.incoming.lock().unwrap().push_back(Arc::new(TestMessage(3)));
+    assert_eq!(poll_id(&dispatcher, &left).await, None);
+    let pending = dispatcher.pending_messages.execute_sync(
+        |msgs| msgs.values().map(|queue| queue.len()).sum::<usize>()
+    ).await;
+    assert_eq!(pending, 0);
+}
+
+#[tokio::test]
+async fn test_dispatcher_reshard_pending() {
+    let dispatcher = make_dispatcher();
+    let parent = ShardIdent::full(0);
+    let (left, right) = parent.split().unwrap();
+    let (right_left, _) = right.split().unwrap();
+    dispatcher.add_actual_shard(&parent).await;
+
+    // Postponed for the child shard, which is split further before its queue is added
+    dispatcher.queue.set_shard(1, &right);
+    dispatcher.queue.incoming.lock().unwrap().push_back(Arc::new(TestMessage(1)));
+    assert_eq!(poll_id(&dispatcher, &parent).await, None);
+    dispatcher.queue.set_shard(1, &right_left);
+    dispatcher.add_actual_shard(&left).await;
+    dispatcher.add_actual_shard(&right_left).await;
+
+    assert_eq!(poll_id(&dispatcher, &right_left).await, Some(1));
+}
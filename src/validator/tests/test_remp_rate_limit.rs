@@ -0,0 +1,34 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use std::time::Duration;
+
+#[test]
+fn test_rate_limiter() {
+    let limiter = RateLimiter::new(2);
+    let now = Instant::now();
+    assert!(limiter.check_at(&1, now));
+    assert!(limiter.check_at(&1, now));
+    assert!(!limiter.check_at(&1, now));
+    // Other keys are limited separately
+    assert!(limiter.check_at(&2, now));
+
+    assert!(!limiter.check_at(&1, now + Duration::from_millis(400)));
+    assert!(limiter.check_at(&1, now + Duration::from_millis(600)));
+    // Tokens are not accumulated above the rate
+    let later = now + Duration::from_secs(10);
+    assert!(limiter.check_at(&1, later));
+    assert!(limiter.check_at(&1, later));
+    assert!(!limiter.check_at(&1, later));
+}
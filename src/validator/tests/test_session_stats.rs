@@ -0,0 +1,55 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+#[test]
+fn test_session_stats() {
+    let mut stats = SessionStats::new(UInt256::from([1; 32]));
+    stats.collated(1, 1000, true);
+    stats.validated(1, 1200, None);
+    stats.committed(1, 1600, true);
+    stats.validated(2, 2000, Some("wrong gen_utime\nat block 123"));
+    stats.validated(2, 2100, None);
+    stats.committed(2, 3000, false);
+    stats.collated(3, 4000, false);
+    stats.skipped();
+    // No candidates seen in the round
+    stats.committed(4, 6000, false);
+
+    assert_eq!(stats.candidates_produced, 1);
+    assert_eq!(stats.collation_failures, 1);
+    assert_eq!(stats.approvals_signed, 2);
+    assert_eq!(stats.candidates_rejected, 1);
+    assert_eq!(stats.reject_reasons.get("wrong gen_utime"), Some(&1));
+    assert_eq!(stats.blocks_committed, 3);
+    assert_eq!(stats.own_blocks_committed, 1);
+    assert_eq!(stats.max_consensus_ms, 1000);
+    assert_eq!(stats.avg_consensus_ms(), 800);
+}
+
+#[test]
+fn test_session_stats_reject_reasons_limit() {
+    let mut stats = SessionStats::new(UInt256::default());
+    for i in 0..MAX_REJECT_REASONS + 5 {
+        stats.validated(0, 0, Some(&format!("reason {}", i)));
+    }
+    stats.validated(0, 0, Some("reason 0"));
+    assert_eq!(stats.reject_reasons.len(), MAX_REJECT_REASONS + 1);
+    assert_eq!(stats.reject_reasons.get("reason 0"), Some(&2));
+    assert_eq!(stats.reject_reasons.get(OTHER_REJECT_REASON), Some(&5));
+    assert_eq!(stats.candidates_rejected, MAX_REJECT_REASONS as u32 + 6);
+
+    let long = "x".repeat(MAX_REJECT_REASON_LEN * 2);
+    assert_eq!(SessionStats::reject_reason_key(&long).len(), MAX_REJECT_REASON_LEN);
+}
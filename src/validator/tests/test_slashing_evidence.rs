@@ -0,0 +1,73 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use ever_crypto::Ed25519KeyOption;
+
+fn make_validators(count: u8) -> Vec<PublicKey> {
+    (1..=count).map(|i| Ed25519KeyOption::from_public_key(&[i; 32])).collect()
+}
+
+fn key_id(key: &PublicKey) -> UInt256 {
+    UInt256::from(key.id().data())
+}
+
+#[test]
+fn test_double_and_invalid_candidates() {
+    let validators = make_validators(3);
+    let (v1, v2) = (key_id(&validators[0]), key_id(&validators[1]));
+    let mut evidence = SessionEvidence::new(UInt256::from([7; 32]), validators);
+
+    assert!(evidence.candidate_received(1, &v1, &UInt256::from([1; 32])));
+    assert!(evidence.candidate_received(1, &v1, &UInt256::from([1; 32])));
+    assert!(evidence.candidate_received(1, &v2, &UInt256::from([2; 32])));
+    assert!(!evidence.candidate_received(1, &v1, &UInt256::from([3; 32])));
+    // Next round candidate is not a double one
+    assert!(evidence.candidate_received(2, &v1, &UInt256::from([4; 32])));
+
+    for round in 0..MIN_INVALID_CANDIDATES {
+        assert!(evidence.complaints().iter().all(|c| c.kind != MisbehaviorKind::InvalidCandidate));
+        evidence.candidate_rejected(round, &v2, &UInt256::from([5; 32]), "bad block");
+    }
+
+    let complaints = evidence.complaints();
+    assert_eq!(complaints.len(), 2);
+    let double = complaints.iter().find(|c| c.kind == MisbehaviorKind::DoubleCandidate).unwrap();
+    assert_eq!(key_id(&double.validator), v1);
+    assert_eq!(double.count, 1);
+    assert_eq!(double.evidence[0].round, 1);
+    let invalid = complaints.iter().find(|c| c.kind == MisbehaviorKind::InvalidCandidate).unwrap();
+    assert_eq!(key_id(&invalid.validator), v2);
+    assert_eq!(invalid.count, MIN_INVALID_CANDIDATES);
+    assert_eq!(invalid.kind.metric_id() as u8, SlashingAggregatedMetric::ValidationScore as u8);
+}
+
+#[test]
+fn test_non_participation() {
+    let validators = make_validators(3);
+    let ids = validators.iter().map(key_id).collect::<Vec<_>>();
+    let mut evidence = SessionEvidence::new(UInt256::default(), validators);
+
+    let signers: HashSet<_> = ids[..2].iter().cloned().collect();
+    for round in 0..MIN_BLOCKS_FOR_PARTICIPATION - 1 {
+        evidence.block_committed(round, &signers);
+    }
+    assert!(evidence.complaints().is_empty());
+    evidence.block_committed(MIN_BLOCKS_FOR_PARTICIPATION, &signers);
+
+    let complaints = evidence.complaints();
+    assert_eq!(complaints.len(), 1);
+    assert_eq!(complaints[0].kind, MisbehaviorKind::NonParticipation);
+    assert_eq!(key_id(&complaints[0].validator), ids[2]);
+    assert_eq!(complaints[0].count, MIN_BLOCKS_FOR_PARTICIPATION);
+}
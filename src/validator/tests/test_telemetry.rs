@@ -164,3 +164,21 @@ time, ms (min avg max)           500 549 599
 ");
 }
 
+
+#[test]
+pub fn test_remp_core_telemetry_backend_failure() {
+    let shard = ShardIdent::masterchain();
+    let t = RempCoreTelemetry::new(1);
+    t.got_from_catchain(&shard, 2, 1);
+    assert_eq!(t.health(), RempTelemetryHealth::default());
+
+    t.set_backend_failure("backend is not available");
+    t.got_from_catchain(&shard, 2, 1);
+    t.message_from_fullnode();
+    assert_eq!(t.health(), RempTelemetryHealth {
+        failure: Some("backend is not available".to_string()),
+        dropped_samples: 2
+    });
+    let report = t.report();
+    assert!(report.contains("backend failure: backend is not available"));
+}
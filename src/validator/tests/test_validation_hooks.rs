@@ -0,0 +1,65 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+struct RejectAccountHooks {
+    account: UInt256,
+}
+
+impl ValidationHooks for RejectAccountHooks {
+    fn transaction(&self, _ctx: &ValidationHookContext, account: &UInt256, _transaction: &Transaction) -> Result<()> {
+        if account == &self.account {
+            ton_types::fail!("account {:x} is banned", account)
+        }
+        Ok(())
+    }
+}
+
+fn check_hooks(hooks: &dyn ValidationHooks, capabilities: u64, account: &UInt256, transaction: &Transaction) -> Result<()> {
+    let block_id = BlockIdExt::default();
+    let block = BlockStuff::default();
+    let config = ConfigParams::default();
+    let ctx = ValidationHookContext {
+        block_id: &block_id,
+        block: &block,
+        config: &config,
+        capabilities,
+    };
+    hooks.pre_execute(&ctx)?;
+    hooks.transaction(&ctx, account, transaction)
+}
+
+#[test]
+fn test_validation_hooks_defaults() {
+    let hooks = RejectAccountHooks { account: UInt256::from([1; 32]) };
+    let transaction = Transaction::default();
+    check_hooks(&hooks, 0, &UInt256::from([2; 32]), &transaction).unwrap();
+    check_hooks(&hooks, 0, &UInt256::from([1; 32]), &transaction).expect_err("account must be rejected");
+}
+
+#[cfg(feature = "validation_hooks_example")]
+#[test]
+fn test_example_validation_hooks() {
+    let hooks = ExampleValidationHooks::new(0b110, 1);
+    let account = UInt256::from([1; 32]);
+    let mut transaction = Transaction::default();
+    check_hooks(&hooks, 0b111, &account, &transaction).unwrap();
+    check_hooks(&hooks, 0b011, &account, &transaction).expect_err("capability is missing");
+
+    let msg = ton_block::Message::default();
+    transaction.add_out_message(&msg).unwrap();
+    check_hooks(&hooks, 0b110, &account, &transaction).unwrap();
+    transaction.add_out_message(&msg).unwrap();
+    check_hooks(&hooks, 0b110, &account, &transaction).expect_err("too many messages");
+}
@@ -0,0 +1,93 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use std::{sync::Arc, time::Duration};
+use ton_types::fail;
+
+#[test]
+fn test_validation_pool_zero_threads() {
+    assert!(ValidationPool::new(0).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_validation_pool_limits_parallel_tasks() {
+    let pool = Arc::new(ValidationPool::new(2).unwrap());
+    assert_eq!(pool.threads(), 2);
+
+    let running = Arc::new(AtomicU32::new(0));
+    let max_running = Arc::new(AtomicU32::new(0));
+    let mut tasks = Vec::new();
+    for i in 0..8 {
+        let pool = pool.clone();
+        let running = running.clone();
+        let max_running = max_running.clone();
+        tasks.push(tokio::spawn(async move {
+            pool.run(async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(i)
+            }).await
+        }));
+    }
+    for (i, task) in tasks.into_iter().enumerate() {
+        assert_eq!(task.await.unwrap().unwrap(), i);
+    }
+    assert!(max_running.load(Ordering::SeqCst) <= 2);
+    assert_eq!(pool.queued(), 0);
+    assert_eq!(pool.active(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_validation_pool_cancelled_tasks() {
+    let pool = Arc::new(ValidationPool::new(1).unwrap());
+    let (sender, receiver) = tokio::sync::oneshot::channel::<()>();
+    let busy = {
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            pool.run(async move {
+                receiver.await.ok();
+                Ok(())
+            }).await
+        })
+    };
+    while pool.active() == 0 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    // cancelled while waiting for a free slot
+    let waiting = pool.run(async { Ok(()) });
+    assert!(tokio::time::timeout(Duration::from_millis(20), waiting).await.is_err());
+    assert_eq!(pool.queued(), 0);
+
+    // caller is cancelled while the validation runs: it is still counted as active
+    busy.abort();
+    assert!(busy.await.unwrap_err().is_cancelled());
+    assert_eq!(pool.active(), 1);
+    sender.send(()).unwrap();
+    while pool.active() != 0 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    pool.run(async { Ok(()) }).await.unwrap();
+    assert_eq!(pool.queued(), 0);
+    assert_eq!(pool.active(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_validation_pool_error() {
+    let pool = ValidationPool::new(1).unwrap();
+    let result: Result<()> = pool.run(async { fail!("validation failed") }).await;
+    assert!(result.unwrap_err().to_string().contains("validation failed"));
+}
@@ -0,0 +1,44 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+
+fn transaction(account: u8, lt: u64, error: Option<&str>) -> TransactionTrace {
+    TransactionTrace {
+        account: UInt256::from([account; 32]),
+        lt,
+        hash: UInt256::from([lt as u8; 32]),
+        in_msg: None,
+        out_msgs: 0,
+        error: error.map(|e| e.to_string()),
+    }
+}
+
+#[test]
+fn test_validation_trace_report() {
+    let trace = ValidationTrace::default();
+    trace.transaction(transaction(2, 10, None));
+    trace.transaction(transaction(1, 12, Some("invalid")));
+    trace.transaction(transaction(1, 11, None));
+    let config = ConfigTrace { global_version: 32, capabilities: 0x2e, config_hash: UInt256::from([7; 32]) };
+    trace.config(config.clone());
+
+    let report = trace.report(BlockIdExt::default(), "block_db", 5, Some("invalid".to_string()));
+    assert_eq!(report.config, Some(config));
+    assert_eq!(report.transactions, vec![
+        transaction(1, 11, None),
+        transaction(1, 12, Some("invalid")),
+        transaction(2, 10, None),
+    ]);
+    assert_eq!(report.error.as_deref(), Some("invalid"));
+}
@@ -241,3 +241,120 @@ fn test_create_new_proof_with_workchains() {
     let _proof = create_new_proof_link(&block_stuff).unwrap();
 }
 
+
+#[test]
+fn test_block_signatures_aggregator() {
+    let block_id = BlockIdExt::with_params(
+        ShardIdent::masterchain(), 1, UInt256::from([1; 32]), UInt256::from([2; 32])
+    );
+    let data = ton_block::Block::build_data_for_sign(block_id.root_hash(), block_id.file_hash());
+
+    let mut list = Vec::new();
+    let mut signatures = Vec::new();
+    for _ in 0..4 {
+        let (pvt_key, pub_key) = Ed25519KeyOption::generate_with_json().unwrap();
+        let public_key = SigPubKey::from_bytes(pub_key.pub_key().unwrap()).unwrap();
+        list.push(ValidatorDescr::with_params(public_key, 10, None, None));
+        let pvt_key = Ed25519KeyOption::from_private_key_json(&pvt_key).unwrap();
+        let signature = CryptoSignature::from_bytes(&pvt_key.sign(&data).unwrap()).unwrap();
+        signatures.push(CryptoSignaturePair::with_params(pub_key.id().data().clone().into(), signature));
+    }
+    let validator_set = ValidatorSet::new(0, 100, 1, list).unwrap();
+
+    let mut aggregator = BlockSignaturesAggregator::new(&block_id, &validator_set).unwrap();
+    assert!(!aggregator.add(signatures[0].clone()).unwrap());
+    // repeated signature doesn't add weight
+    assert!(!aggregator.add(signatures[0].clone()).unwrap());
+    assert!(!aggregator.add(signatures[1].clone()).unwrap());
+    assert_eq!(aggregator.weight(), 20);
+    aggregator.signatures().expect_err("weight is not enough");
+
+    // signature of another block
+    let mut bad = signatures[2].clone();
+    bad.sign = signatures[3].sign.clone();
+    aggregator.add(bad).expect_err("signature is bad");
+
+    assert!(aggregator.add(signatures[2].clone()).unwrap());
+    assert!(aggregator.add(signatures[3].clone()).unwrap());
+    assert_eq!(aggregator.weight(), aggregator.total_weight());
+    let signatures = aggregator.signatures().unwrap();
+    assert_eq!(signatures.pure_signatures.count(), 4);
+    assert_eq!(signatures.pure_signatures.weight(), 40);
+}
+
+#[test]
+fn test_get_abi_message_time() {
+    fn create_message(signed: bool, time: u64, expire: u32) -> Message {
+        let mut body = BuilderData::new();
+        if signed {
+            body.append_bit_one().unwrap();
+            body.append_raw(&[0xAA; 64], 512).unwrap();
+        } else {
+            body.append_bit_zero().unwrap();
+        }
+        body.append_bit_zero().unwrap(); // no pubkey
+        body.append_u64(time).unwrap();
+        body.append_u32(expire).unwrap();
+        body.append_u32(0x12345678).unwrap(); // function id
+        let mut msg = Message::with_ext_in_header(ton_block::ExternalInboundMessageHeader::default());
+        msg.set_body(SliceData::load_builder(body).unwrap());
+        msg
+    }
+
+    let time = 1_700_000_000_123;
+    let expire = 1_700_000_060;
+    assert_eq!(get_abi_message_time(&create_message(false, time, expire)), Some(time));
+    assert_eq!(get_abi_message_time(&create_message(true, time, expire)), Some(time));
+    // expire is before time
+    assert_eq!(get_abi_message_time(&create_message(false, time, 1_600_000_000)), None);
+    // not a timestamp
+    assert_eq!(get_abi_message_time(&create_message(false, 12345, expire)), None);
+}
+
+#[test]
+fn test_wallet_replay_policy() {
+    fn create_account(code: &[u8], pubkey: [u8; 32], timestamp: u64) -> Account {
+        let mut data = BuilderData::new();
+        data.append_raw(&pubkey, 256).unwrap();
+        data.append_u64(timestamp).unwrap();
+        let mut state_init = ton_block::StateInit::default();
+        state_init.set_code(BuilderData::with_raw(code.to_vec(), code.len() * 8).unwrap().into_cell().unwrap());
+        state_init.set_data(data.into_cell().unwrap());
+        let address = ton_block::MsgAddressInt::with_standart(None, 0, [0x11; 32].into()).unwrap();
+        Account::active_by_init_code_hash(address, Default::default(), 0, state_init, false).unwrap()
+    }
+
+    let time = 1_700_000_000_123;
+    let wallet = create_account(&[1, 2, 3], [0x22; 32], time);
+    // another contract with data of the same layout
+    let contract = create_account(&[4, 5, 6], [0x22; 32], time);
+    let wallet_code_hash = wallet.get_code().unwrap().repr_hash();
+
+    // disabled by default
+    let policy = WalletReplayPolicy::new(&RempConfig::default());
+    assert!(!policy.is_enabled());
+    assert_eq!(policy.account_replay_timestamp(&wallet), None);
+
+    let options: RempConfig = serde_json::from_str(
+        &format!(r#"{{ "replay_protection_code_hashes": ["{:x}", "wrong"] }}"#, wallet_code_hash)
+    ).unwrap();
+    let policy = WalletReplayPolicy::new(&options);
+    assert!(policy.is_enabled());
+    assert_eq!(policy.account_replay_timestamp(&wallet), Some(time));
+    assert_eq!(policy.account_replay_timestamp(&contract), None);
+    // known code, but data is not of the wallet layout
+    assert_eq!(policy.account_replay_timestamp(&create_account(&[1, 2, 3], [0; 32], time)), None);
+    assert_eq!(policy.account_replay_timestamp(&create_account(&[1, 2, 3], [0x22; 32], 12345)), None);
+    assert_eq!(policy.account_replay_timestamp(&Account::default()), None);
+}
+
+#[test]
+fn test_next_session_activation_time() {
+    assert_eq!(next_session_activation_time(1000, 300, None), 1200);
+    assert_eq!(next_session_activation_time(1200, 300, None), 1500);
+    // next validator set comes into force before rotation
+    assert_eq!(next_session_activation_time(1000, 300, Some(1100)), 1100);
+    assert_eq!(next_session_activation_time(1000, 300, Some(900)), 1000);
+    assert_eq!(next_session_activation_time(1000, 300, Some(1300)), 1200);
+    assert_eq!(next_session_activation_time(1000, 0, None), 1001);
+}
@@ -26,7 +26,11 @@ use crate::{
         supported_version, supported_capabilities, calc_remp_msg_ordering_hash,
         UNREGISTERED_CHAIN_MAX_LEN, fmt_next_block_descr,
     },
-    validator::{out_msg_queue::MsgQueueManager, validator_utils::calc_subset_for_masterchain},
+    validator::{
+        out_msg_queue::MsgQueueManager, validator_utils::calc_subset_for_masterchain,
+        validation_hooks::{ValidationHookContext, ValidationHooks},
+        validation_trace::{ConfigTrace, TransactionTrace, ValidationTrace},
+    },
     CHECK,
 };
 
@@ -168,9 +172,20 @@ struct ValidateBase {
     result: ValidateResult,
 
     next_block_descr: Arc<String>,
+
+    trace: Option<Arc<ValidationTrace>>,
+    hooks: Option<Arc<dyn ValidationHooks>>,
 }
 
 impl ValidateBase {
+    fn hook_context(&self) -> ValidationHookContext {
+        ValidationHookContext {
+            block_id: self.block_id(),
+            block: &self.block,
+            config: &self.config_params,
+            capabilities: self.capabilities,
+        }
+    }
     fn shard(&self) -> &ShardIdent {
         &self.block_id().shard()
     }
@@ -223,6 +238,8 @@ pub struct ValidateQuery {
     engine: Arc<dyn EngineOperations>,
 
     next_block_descr: Arc<String>,
+
+    trace: Option<Arc<ValidationTrace>>,
 }
 
 impl ValidateQuery {
@@ -259,9 +276,15 @@ impl ValidateQuery {
             block_create_count: Default::default(),
 
             next_block_descr,
+            trace: None,
         }
     }
 
+    /// Validation fills the trace with config and checked transactions (see validate replay)
+    pub fn set_trace(&mut self, trace: Arc<ValidationTrace>) {
+        self.trace = Some(trace);
+    }
+
 /*
  * 
  *   INITIAL PARSE & LOAD REQUIRED DATA
@@ -273,6 +296,8 @@ impl ValidateQuery {
         let mut base = ValidateBase::default();
         base.next_block_descr = self.next_block_descr.clone();
         base.is_fake = self.is_fake;
+        base.trace = self.trace.clone();
+        base.hooks = self.engine.validation_hooks();
         base.created_by = self.block_candidate.created_by.clone();
         base.prev_blocks_ids = std::mem::take(&mut self.prev_blocks_ids);
         let block_id = &self.block_candidate.block_id;
@@ -329,13 +354,25 @@ impl ValidateQuery {
     }
 
     async fn init_mc_data(&mut self, base: &mut ValidateBase) -> Result<McData> {
+        // 3. load state(s) corresponding to previous block(s) simultaneously with
+        // the reference masterchain state
+        let engine = self.engine.clone();
+        let prev_blocks_ids = base.prev_blocks_ids.clone();
+        let next_block_descr = self.next_block_descr.clone();
+        let prev_states = futures::future::try_join_all(
+            prev_blocks_ids.iter().enumerate().map(|(i, id)| {
+                log::debug!(target: "validate_query", "({}): load state for prev block {} of {} {}", next_block_descr, i + 1, prev_blocks_ids.len(), id);
+                engine.clone().wait_state(id, Some(1_000), true)
+            })
+        );
+
         // 2. learn latest masterchain state and block id
-        let mc_data = self.get_ref_mc_state(base).await?;
+        let (mc_data, prev_states) = futures::future::try_join(
+            self.get_ref_mc_state(base),
+            prev_states
+        ).await?;
 
-        // 3. load state(s) corresponding to previous block(s)
-        for i in 0..base.prev_blocks_ids.len() {
-            log::debug!(target: "validate_query", "({}): load state for prev block {} of {} {}", self.next_block_descr, i + 1, base.prev_blocks_ids.len(), base.prev_blocks_ids[i]);
-            let prev_state = (self.engine.clone()).wait_state(&base.prev_blocks_ids[i], Some(1_000), true).await?;
+        for prev_state in prev_states {
             if &self.shard == prev_state.shard() && prev_state.state()?.before_split() {
                 reject_query!("cannot accept new unsplit shardchain block for {} \
                     after previous block {} with before_split set", self.shard, prev_state.block_id())
@@ -605,7 +642,8 @@ impl ValidateQuery {
                 while the correct value corresponding to reference masterchain state {} is {}",
                     base.info.prev_key_block_seqno(), mc_state.block_id(), prev_key_block_seqno)
         }
-        self.block_limits = config_params.block_limits(base.shard().is_masterchain())?;
+        self.block_limits = self.engine.config_params_with_overrides(config_params, base.shard().workchain_id())?
+            .block_limits(base.shard().is_masterchain())?;
         if !base.shard().is_masterchain() {
             check_this_shard_mc_info(
                base.shard(),
@@ -3365,7 +3403,8 @@ impl ValidateQuery {
         let (max_trans_lt, _) = acc_block.transactions().get_max(false)?.ok_or_else(|| error!("no maximal transaction"))?;
         let mut new_account = account.clone();
         acc_block.transactions().iterate_slices_with_keys(|lt, trans| {
-            Self::check_one_transaction(
+            let trans_root = trans.reference(0)?;
+            let mut result = Self::check_one_transaction(
                 base,
                 config.clone(),
                 libraries.clone(),
@@ -3373,10 +3412,30 @@ impl ValidateQuery {
                 account_root,
                 &mut new_account,
                 lt,
-                trans.reference(0)?,
+                trans_root.clone(),
                 lt == min_trans_lt,
                 lt == max_trans_lt
-            )
+            );
+            if base.trace.is_none() && base.hooks.is_none() {
+                return result
+            }
+            let trans = Transaction::construct_from_cell(trans_root.clone())?;
+            if let (Ok(_), Some(hooks)) = (&result, &base.hooks) {
+                if let Err(err) = hooks.transaction(&base.hook_context(), account_addr, &trans) {
+                    result = Err(error!("transaction {} rejected by validation hook : {}", lt, err));
+                }
+            }
+            if let Some(trace) = &base.trace {
+                trace.transaction(TransactionTrace {
+                    account: account_addr.clone(),
+                    lt,
+                    hash: trans_root.repr_hash(),
+                    in_msg: trans.in_msg_cell().map(|cell| cell.repr_hash()),
+                    out_msgs: trans.out_msgs.len()?,
+                    error: result.as_ref().err().map(|e| e.to_string()),
+                });
+            }
+            result
         }).map_err(|err| error!("at least one Transaction of account {} is invalid : {}", account_addr.to_hex_string(), err))?;
         if base.shard().is_masterchain() {
             Self::scan_account_libraries(base, account.libraries(), new_account.libraries(), account_addr)
@@ -3841,6 +3900,15 @@ impl ValidateQuery {
         Ok(())
     }
 
+    fn check_new_state_hooks(base: &ValidateBase) -> Result<()> {
+        if let (Some(hooks), Some(next_state)) = (&base.hooks, &base.next_state) {
+            hooks.post_state_check(&base.hook_context(), next_state).map_err(
+                |err| error!("new state rejected by validation hook : {}", err)
+            )?;
+        }
+        Ok(())
+    }
+
     fn check_config_update(base: &ValidateBase) -> Result<()> {
         if base.next_state_extra.config.config_params.count(10000).is_err() {
             reject_query!("new configuration failed to pass letmated validity checks")
@@ -4339,9 +4407,23 @@ impl ValidateQuery {
         self.compute_next_state(&mut base, &mc_data)?;
         self.unpack_prev_state(&mut base)?;
         self.unpack_next_state(&mut base, &mc_data)?;
-        base.config_params = mc_data.state().config_params()?.clone();
+        base.config_params = self.engine.config_params_with_overrides(
+            mc_data.state().config_params()?, base.shard().workchain_id()
+        )?;
         base.capabilities = base.config_params.capabilities();
+        if let Some(trace) = &base.trace {
+            trace.config(ConfigTrace {
+                global_version: base.config_params.global_version(),
+                capabilities: base.capabilities,
+                config_hash: base.config_params.serialize()?.repr_hash(),
+            });
+        }
         Self::load_block_data(&mut base)?;
+        if let Some(hooks) = &base.hooks {
+            hooks.pre_execute(&base.hook_context()).map_err(
+                |err| error!("block candidate rejected by validation hook : {}", err)
+            )?;
+        }
         Ok((base, mc_data))
     }
 
@@ -4351,14 +4433,19 @@ impl ValidateQuery {
 
     async fn run_tasks(&self, tasks: Vec<Box<dyn FnOnce() -> Result<()> + Send + 'static>>) -> Result<()> {
         if self.multithread {
-            let tasks = tasks.into_iter().map(|t| tokio::task::spawn_blocking(t));
-            futures::future::join_all(tasks).await
-                .into_iter().find(|r| match r {
-                    Err(_) => true,
-                    Ok(Err(_)) => true,
-                    Ok(Ok(_)) => false,
-                })
-                .unwrap_or(Ok(Ok(())))??;
+            use futures::StreamExt;
+            // 0 - number of simultaneous checks is limited by the runtime's blocking pool only
+            let limit = match self.engine.collator_config().validation_task_threads {
+                0 => tasks.len().max(1),
+                threads => threads as usize
+            };
+            let mut results = futures::stream::iter(tasks)
+                .map(|t| tokio::task::spawn_blocking(t))
+                .buffer_unordered(limit);
+            // the first failed check rejects the candidate, the rest are not started
+            while let Some(result) = results.next().await {
+                result.map_err(|e| error!("Validation task failed: {}", e))??;
+            }
         } else {
             for task in tasks {
                 task()?;
@@ -4402,6 +4489,8 @@ impl ValidateQuery {
         Self::check_in_msg_descr(base.clone(), manager.clone(), &mut tasks)?;
         Self::check_out_msg_descr(base.clone(), manager.clone(), &mut tasks)?;
         Self::check_transactions(base.clone(), mc_data.libraries()?.clone(), &mut tasks)?;
+        let b = base.clone();
+        Self::add_task(&mut tasks, move || Self::check_queue_updates(&b));
 
         self.run_tasks(tasks).await?;
 
@@ -4416,10 +4505,10 @@ impl ValidateQuery {
         Self::check_all_ticktock_processed(&base)?;
         Self::check_message_processing_order(&mut base)?;
         Self::check_new_state(&mut base, &mc_data, &manager)?;
+        Self::check_new_state_hooks(&base)?;
         Self::check_mc_block_extra(&base, &mc_data)?;
         let sent_rewards = self.check_mc_state_extra(&base, &mc_data)?;
         Self::check_special_messages(&base, &sent_rewards)?;
-        Self::check_queue_updates(&base)?;
 
         Ok(base)
     }
@@ -4440,6 +4529,11 @@ impl ValidateQuery {
         let ratio = gas_used.checked_div(duration).unwrap_or(gas_used);
         log::info!("({}): ASYNC VALIDATED {} TIME {}ms GAS_RATE: {}", self.next_block_descr, base.block_id(), duration, ratio);
 
+        // replayed validation must not affect validator's metrics
+        if base.trace.is_some() {
+            return Ok(())
+        }
+
         let labels = [("shard", base.block_id().shard().to_string())];
         metrics::gauge!("gas_rate_validator", ratio as f64, &labels);
 
@@ -0,0 +1,103 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{block::BlockStuff, shard_state::ShardStateStuff};
+use std::sync::Arc;
+use ton_block::{BlockIdExt, ConfigParams, Transaction};
+use ton_types::{Result, UInt256};
+#[cfg(feature = "validation_hooks_example")]
+use ton_types::fail;
+
+#[cfg(test)]
+#[path = "tests/test_validation_hooks.rs"]
+mod tests;
+
+/// Data of the validated candidate available to the hooks
+pub struct ValidationHookContext<'a> {
+    pub block_id: &'a BlockIdExt,
+    pub block: &'a BlockStuff,
+    // config with workchain overrides applied
+    pub config: &'a ConfigParams,
+    pub capabilities: u64,
+}
+
+/// Additional checks of block candidates. An error returned by any hook rejects the candidate.
+/// Every method does nothing by default, so an implementation overrides only the checks it needs.
+/// Transactions are checked in parallel, so `transaction` is called concurrently and in no
+/// particular order.
+pub trait ValidationHooks: Send + Sync {
+    /// Called when the prev states and the config are loaded, before any candidate check
+    fn pre_execute(&self, _ctx: &ValidationHookContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called for every transaction which passed the check (re-execution)
+    fn transaction(&self, _ctx: &ValidationHookContext, _account: &UInt256, _transaction: &Transaction) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after the new state of the candidate is checked
+    fn post_state_check(&self, _ctx: &ValidationHookContext, _next_state: &ShardStateStuff) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Hooks used by the engine, None - no additional checks
+pub fn default_validation_hooks() -> Option<Arc<dyn ValidationHooks>> {
+    #[cfg(feature = "validation_hooks_example")]
+    return Some(Arc::new(ExampleValidationHooks::new(
+        ton_block::GlobalCapabilities::CapBounceMsgBody as u64,
+        ExampleValidationHooks::DEFAULT_MAX_OUT_MSGS,
+    )));
+    #[cfg(not(feature = "validation_hooks_example"))]
+    None
+}
+
+/// Example of network specific rules: the config must have the required capabilities
+/// and a transaction must not send more than `max_out_msgs` messages
+#[cfg(feature = "validation_hooks_example")]
+pub struct ExampleValidationHooks {
+    required_capabilities: u64,
+    max_out_msgs: usize,
+}
+
+#[cfg(feature = "validation_hooks_example")]
+impl ExampleValidationHooks {
+    pub const DEFAULT_MAX_OUT_MSGS: usize = 255;
+
+    pub fn new(required_capabilities: u64, max_out_msgs: usize) -> Self {
+        Self { required_capabilities, max_out_msgs }
+    }
+}
+
+#[cfg(feature = "validation_hooks_example")]
+impl ValidationHooks for ExampleValidationHooks {
+    fn pre_execute(&self, ctx: &ValidationHookContext) -> Result<()> {
+        let missing = self.required_capabilities & !ctx.capabilities;
+        if missing != 0 {
+            fail!("config of block {} doesn't have required capabilities {:#x}", ctx.block_id, missing)
+        }
+        Ok(())
+    }
+
+    fn transaction(&self, _ctx: &ValidationHookContext, account: &UInt256, transaction: &Transaction) -> Result<()> {
+        let out_msgs = transaction.out_msgs.len()?;
+        if out_msgs > self.max_out_msgs {
+            fail!(
+                "transaction {} of account {:x} sends {} messages, maximum is {}",
+                transaction.logical_time(), account, out_msgs, self.max_out_msgs
+            )
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{
+    future::Future,
+    sync::{Arc, atomic::{AtomicU32, Ordering}},
+    time::Instant,
+};
+use ton_types::{error, Result};
+
+#[cfg(test)]
+#[path = "tests/test_validation_pool.rs"]
+mod tests;
+
+/// Dedicated runtime for block candidates validation. Validation tasks are executed
+/// on a fixed number of threads separated from the engine's runtime (where collation
+/// is performed), so validators handling several shards at once have predictable
+/// validation latency.
+pub struct ValidationPool {
+    runtime: Option<tokio::runtime::Runtime>,
+    permits: Arc<tokio::sync::Semaphore>,
+    threads: u32,
+    queued: Arc<AtomicU32>,
+    active: Arc<AtomicU32>,
+}
+
+/// Keeps a task counted in the gauge while the guard is alive, 
+/// so the counter is correct even if the task is cancelled
+struct CountGuard {
+    counter: Arc<AtomicU32>,
+    gauge: &'static str,
+}
+
+impl CountGuard {
+    fn new(counter: &Arc<AtomicU32>, gauge: &'static str) -> Self {
+        let count = counter.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::gauge!(gauge, count as f64);
+        Self { counter: counter.clone(), gauge }
+    }
+}
+
+impl Drop for CountGuard {
+    fn drop(&mut self) {
+        let count = self.counter.fetch_sub(1, Ordering::Relaxed) - 1;
+        metrics::gauge!(self.gauge, count as f64);
+    }
+}
+
+impl ValidationPool {
+
+    pub fn new(threads: u32) -> Result<Self> {
+        if threads == 0 {
+            return Err(error!("Validation pool can't have zero threads"))
+        }
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .worker_threads(threads as usize)
+            .max_blocking_threads(threads as usize)
+            .thread_name("validation")
+            .thread_stack_size(8 * 1024 * 1024)
+            .build()?;
+        Ok(Self {
+            runtime: Some(runtime),
+            permits: Arc::new(tokio::sync::Semaphore::new(threads as usize)),
+            threads,
+            queued: Arc::new(AtomicU32::new(0)),
+            active: Arc::new(AtomicU32::new(0)),
+        })
+    }
+
+    pub fn threads(&self) -> u32 {
+        self.threads
+    }
+
+    /// Number of validations waiting for a free slot in the pool
+    pub fn queued(&self) -> u32 {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// Number of validations running in the pool
+    pub fn active(&self) -> u32 {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Runs validation on the pool. At most `threads` validations run simultaneously,
+    /// the others wait for a free slot in FIFO order. If the returned future is dropped
+    /// while waiting, the validation is not started; started validation is completed 
+    /// on the pool anyway.
+    pub async fn run<F, T>(&self, validation: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>> + Send + 'static,
+        T: Send + 'static
+    {
+        let runtime = self.runtime.as_ref()
+            .ok_or_else(|| error!("Validation pool is shut down"))?;
+
+        let queued_at = Instant::now();
+        let queued = CountGuard::new(&self.queued, "validation_pool_queued");
+        let permit = self.permits.clone().acquire_owned().await
+            .map_err(|e| error!("Validation pool is closed: {}", e))?;
+        drop(queued);
+        metrics::histogram!("validation_pool_wait_time", queued_at.elapsed());
+
+        let active = CountGuard::new(&self.active, "validation_pool_active");
+        let result = runtime.spawn(async move {
+            let _permit = permit;
+            let _active = active;
+            validation.await
+        }).await;
+
+        result.map_err(|e| error!("Validation task failed: {}", e))?
+    }
+}
+
+impl Drop for ValidationPool {
+    fn drop(&mut self) {
+        // Runtime can't be dropped in async context, so it is shut down without waiting
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
@@ -0,0 +1,85 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::sync::Mutex;
+use ton_block::BlockIdExt;
+use ton_types::UInt256;
+
+#[cfg(test)]
+#[path = "tests/test_validation_trace.rs"]
+mod tests;
+
+pub const VALIDATE_REPLAY_TRACE_TARGET: &str = "validate_replay";
+
+/// Result of the check of one transaction of the validated block
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionTrace {
+    pub account: UInt256,
+    pub lt: u64,
+    pub hash: UInt256,
+    pub in_msg: Option<UInt256>,
+    pub out_msgs: usize,
+    pub error: Option<String>,
+}
+
+/// Config parameters the block was validated with
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigTrace {
+    pub global_version: u32,
+    pub capabilities: u64,
+    pub config_hash: UInt256,
+}
+
+/// Trace of a validation, filled by `ValidateQuery` if it is set with `ValidateQuery::set_trace`.
+/// Transactions are checked in parallel, so they are sorted by account and lt in the report.
+#[derive(Default)]
+pub struct ValidationTrace {
+    config: Mutex<Option<ConfigTrace>>,
+    transactions: lockfree::queue::Queue<TransactionTrace>,
+}
+
+impl ValidationTrace {
+
+    pub fn config(&self, config: ConfigTrace) {
+        log::info!(target: VALIDATE_REPLAY_TRACE_TARGET, "config: {:?}", config);
+        *self.config.lock().unwrap() = Some(config);
+    }
+
+    pub fn transaction(&self, trace: TransactionTrace) {
+        log::info!(target: VALIDATE_REPLAY_TRACE_TARGET, "transaction: {:?}", trace);
+        self.transactions.push(trace);
+    }
+
+    pub fn report(&self, block_id: BlockIdExt, source: &'static str, duration_ms: u64, error: Option<String>) -> ValidationReplayReport {
+        let mut transactions = self.transactions.pop_iter().collect::<Vec<_>>();
+        transactions.sort_by(|a, b| (&a.account, a.lt).cmp(&(&b.account, b.lt)));
+        ValidationReplayReport {
+            block_id,
+            source,
+            duration_ms,
+            error,
+            config: self.config.lock().unwrap().clone(),
+            transactions,
+        }
+    }
+}
+
+/// Result of validation replay of a stored block or block candidate
+pub struct ValidationReplayReport {
+    pub block_id: BlockIdExt,
+    pub source: &'static str,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    pub config: Option<ConfigTrace>,
+    pub transactions: Vec<TransactionTrace>,
+}
@@ -20,7 +20,7 @@ use ton_block::{BlockIdExt, ShardIdent, ValidatorSet, ValidatorDescr};
 use ton_types::{fail, error, Result, UInt256};
 use validator_session::{
     BlockHash, BlockPayloadPtr, CatchainOverlayManagerPtr,
-    SessionId, SessionPtr, SessionListenerPtr, SessionFactory,
+    LatencyStat, SessionId, SessionPtr, SessionListenerPtr, SessionFactory,
     SessionListener, SessionNode, SessionOptions,
     PublicKey, PrivateKey, PublicKeyHash, ValidatorBlockCandidate,
     ValidatorBlockCandidateCallback, ValidatorBlockCandidateDecisionCallback
@@ -39,9 +39,11 @@ use validator_session_listener::{
 use super::*;
 use super::fabric::*;
 use crate::{
-    engine_traits::EngineOperations,
+    engine_traits::EngineOperations, network::send_queue::QosClass,
     validator::{
         catchain_overlay::CatchainOverlayManagerImpl,
+        consensus_stats::{ConsensusReport, ConsensusStats},
+        session_stats::SessionStats,
         mutex_wrapper::MutexWrapper,
         reliable_message_queue::RmqQueueManager,
         remp_manager::RempManager,
@@ -56,6 +58,10 @@ use crate::{
 
 #[cfg(feature = "slashing")]
 use crate::validator::slashing::SlashingManagerPtr;
+#[cfg(feature = "slashing")]
+use crate::validator::{
+    slashing_evidence::{SessionEvidence, SlashingComplaint}, validator_utils::sigpubkey_to_publickey
+};
 //#[cfg(feature = "fast_finality")]
 use crate::validator::validator_utils::get_first_block_seqno_after_prevs;
 // #[cfg(feature = "fast_finality")]
@@ -162,8 +168,10 @@ impl ValidatorGroupImpl {
         let nodes = nodes_res?;
 
         let overlay_manager: CatchainOverlayManagerPtr =
-            Arc::new(CatchainOverlayManagerImpl::new(g.engine.validator_network(), g.validator_list_id.clone()));
-        let db_path = format!("{}/catchains", g.engine.db_root_dir()?);
+            Arc::new(CatchainOverlayManagerImpl::new(
+                g.engine.validator_network(), g.validator_list_id.clone(), QosClass::Consensus
+            ));
+        let db_path = g.catchain_db_path.clone();
         let db_suffix = format!(
             "-{}.{}.{}.{}.", 
             g.shard().workchain_id(),
@@ -343,6 +351,7 @@ pub struct ValidatorGroup {
     engine: Arc<dyn EngineOperations>,
     remp_manager: Option<Arc<RempManager>>,
     validator_set: ValidatorSet,
+    catchain_db_path: String,
     #[allow(dead_code)]
     allow_unsafe_self_blocks_resync: bool,
 
@@ -354,6 +363,15 @@ pub struct ValidatorGroup {
     slashing_manager: SlashingManagerPtr,
     last_validation_time: AtomicU64,
     last_collation_time: AtomicU64,
+    latency_stat: Mutex<LatencyStat>,
+    consensus_stats: Mutex<ConsensusStats>,
+    session_stats: Mutex<SessionStats>,
+    #[cfg(feature = "slashing")]
+    slashing_evidence: Mutex<SessionEvidence>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_millis() as u64
 }
 
 impl ValidatorGroup {
@@ -366,6 +384,7 @@ impl ValidatorGroup {
         config: SessionOptions,
         remp_manager: Option<Arc<RempManager>>,
         engine: Arc<dyn EngineOperations>,
+        catchain_db_path: String,
         allow_unsafe_self_blocks_resync: bool,
         #[cfg(feature = "slashing")]
         slashing_manager: SlashingManagerPtr,
@@ -375,6 +394,11 @@ impl ValidatorGroup {
             session_id.clone(),
         );
         let id = format!("Val. group {} {:x}", general_session_info.shard, session_id);
+        #[cfg(feature = "slashing")]
+        let slashing_evidence = SessionEvidence::new(
+            session_id.clone(),
+            validator_set.list().iter().map(|v| sigpubkey_to_publickey(&v.public_key)).collect()
+        );
         let (listener, receiver) = ValidatorSessionListener::create();
 
         log::trace!(target: "validator", "Creating validator group: {}", id);
@@ -382,10 +406,11 @@ impl ValidatorGroup {
             general_session_info,
             local_key,
             validator_list_id,
-            session_id,
+            session_id: session_id.clone(),
             validator_set,
             config,
             engine,
+            catchain_db_path,
             allow_unsafe_self_blocks_resync,
             remp_manager,
             group_impl: Arc::new(MutexWrapper::new(group_impl, id)),
@@ -394,7 +419,12 @@ impl ValidatorGroup {
             #[cfg(feature = "slashing")]
             slashing_manager,
             last_validation_time: AtomicU64::new(0),
-            last_collation_time: AtomicU64::new(0)
+            last_collation_time: AtomicU64::new(0),
+            latency_stat: Mutex::new(LatencyStat::new()),
+            consensus_stats: Mutex::new(ConsensusStats::new(now_ms())),
+            session_stats: Mutex::new(SessionStats::new(session_id.clone())),
+            #[cfg(feature = "slashing")]
+            slashing_evidence: Mutex::new(slashing_evidence),
         }
     }
 
@@ -408,6 +438,10 @@ impl ValidatorGroup {
         &self.general_session_info.shard
     }
 
+    pub fn catchain_seqno(&self) -> u32 {
+        self.general_session_info.catchain_seqno
+    }
+
     pub fn last_validation_time(&self) -> u64 {
         self.last_validation_time.load(Ordering::Relaxed)
     }
@@ -416,6 +450,27 @@ impl ValidatorGroup {
         self.last_collation_time.load(Ordering::Relaxed)
     }
 
+    /// Round events latency statistics accumulated since the session start
+    pub fn latency_stat(&self) -> LatencyStat {
+        self.latency_stat.lock().unwrap().clone()
+    }
+
+    /// Rounds statistics accumulated since the session start, with suggested adjustments
+    /// of the session options, and work of the local validator in the session
+    pub fn consensus_report(&self) -> ConsensusReport {
+        let stats = self.consensus_stats.lock().unwrap().clone();
+        let suggestions = stats.suggestions(&self.config);
+        let session = self.session_stats.lock().unwrap().clone();
+        ConsensusReport { stats, suggestions, session }
+    }
+
+    /// Complaints on validators of the session, based on evidence of misbehavior
+    /// observed by the local validator
+    #[cfg(feature = "slashing")]
+    pub fn slashing_complaints(&self) -> Vec<SlashingComplaint> {
+        self.slashing_evidence.lock().unwrap().complaints()
+    }
+
     pub fn make_validator_session_callback(&self) -> SessionListenerPtr {
         Arc::downgrade(&self.callback)
     }
@@ -500,6 +555,27 @@ impl ValidatorGroup {
         Ok(())
     }
 
+    /// Registers the next session of the group (with the group's validators as previous ones)
+    /// and starts its REMP catchain in advance
+    pub async fn prestart_next_session(
+        self: Arc<ValidatorGroup>,
+        next_validator_set: &ValidatorSet,
+        new_session_info: Arc<GeneralSessionInfo>,
+        next_master_cc_range: &RangeInclusive<u32>,
+    ) -> Result<()> {
+        if self.get_status().await != ValidatorGroupStatus::Active {
+            return Ok(());
+        }
+        let prev_validators = self.validator_set.list().to_vec();
+        self.clone().add_next_validators(
+            &prev_validators, next_validator_set, new_session_info, next_master_cc_range
+        ).await?;
+        if let Some(rmq) = self.get_reliable_message_queue().await {
+            rmq.prestart_next_queues(self.local_key.clone()).await;
+        }
+        Ok(())
+    }
+
     pub async fn stop(self: Arc<ValidatorGroup>, rt: tokio::runtime::Handle, new_master_cc_range: Option<RangeInclusive<u32>>) -> Result<()> {
         self.set_status(ValidatorGroupStatus::Stopping).await?;
         log::debug!(target: "validator", "Stopping group: {}", self.info().await);
@@ -686,6 +762,7 @@ impl ValidatorGroup {
             }
             None => Err(error!("Min masterchain block id missing")),
         };
+        self.session_stats.lock().unwrap().collated(round, now_ms(), result.is_ok());
 
         let result_message = match &result {
             Ok(_) => {
@@ -760,6 +837,15 @@ impl ValidatorGroup {
             next_block_descr,
             candidate_id, self.info_round(round).await);
 
+        #[cfg(feature = "slashing")]
+        let source_id = UInt256::from(source.id().data());
+        #[cfg(feature = "slashing")]
+        if !self.slashing_evidence.lock().unwrap().candidate_received(round, &source_id, &root_hash) {
+            log::warn!(target: "slashing", "({}): ValidatorGroup::on_candidate: other candidate of {} is already received, {}",
+                next_block_descr, candidate_id, self.info_round(round).await
+            );
+        }
+
         let mut candidate = super::BlockCandidate {
             block_id: BlockIdExt::with_params(self.shard().clone(), 0, root_hash, get_hash(&data.data())),
             data: data.data().to_vec(),
@@ -814,6 +900,13 @@ impl ValidatorGroup {
                 None => Err(failure::err_msg("Min masterchain block id missing")),
             }
         };
+        self.session_stats.lock().unwrap().validated(
+            round, now_ms(), result.as_ref().err().map(|e| e.to_string()).as_deref()
+        );
+        #[cfg(feature = "slashing")]
+        if let Err(e) = &result {
+            self.slashing_evidence.lock().unwrap().candidate_rejected(round, &source_id, &candidate.block_id.root_hash, &e.to_string());
+        }
 
         let result_message = match &result {
             Ok(x) => {
@@ -832,7 +925,16 @@ impl ValidatorGroup {
                     Err(x) => format!("Validation successful, db error `{}`", x)
                 }
             }
-            Err(x) => format!("Validation failed with verdict `{}`", x),
+            Err(x) => {
+                // rejected candidate is kept for post-mortem analysis (validate replay)
+                let vb_candidate = validator_query_candidate_to_validator_block_candidate(
+                    source.clone(), candidate
+                );
+                match self.save_block_candidate(vb_candidate).await {
+                    Ok(()) => format!("Validation failed with verdict `{}`", x),
+                    Err(e) => format!("Validation failed with verdict `{}`, db error `{}`", x, e)
+                }
+            }
         };
         self.group_impl.execute_sync(|group_impl| group_impl.on_candidate_invoked = true).await;
 
@@ -866,6 +968,12 @@ impl ValidatorGroup {
 
         let data_vec = data.data().to_vec();
         let we_generated = source.id() == self.local_key.id();
+        self.consensus_stats.lock().unwrap().round_committed(now_ms(), data_vec.len());
+        self.session_stats.lock().unwrap().committed(round, now_ms(), we_generated);
+        #[cfg(feature = "slashing")]
+        self.slashing_evidence.lock().unwrap().block_committed(
+            round, &sig_set.iter().map(|(id, _)| UInt256::from(id.data())).collect()
+        );
 
         log::info!(target: "validator", 
             "({}): ValidatorGroup::on_block_committed: source {}, data size = {}, {}" ,
@@ -958,6 +1066,8 @@ impl ValidatorGroup {
     }
 
     pub async fn on_block_skipped(&self, round: u32) {
+        self.consensus_stats.lock().unwrap().round_skipped(now_ms());
+        self.session_stats.lock().unwrap().skipped();
         log::info!(
             target: "validator", 
             "({}): ValidatorGroup::on_block_skipped, {}",
@@ -1016,6 +1126,15 @@ impl ValidatorGroup {
         self.slashing_manager.update_statistics(&stat);
     }
 
+    pub fn on_latency_statistics(&self, round: u32, stat: LatencyStat) {
+        log::trace!(
+            target: "validator",
+            "ValidatorGroup::on_latency_statistics session {:x} round {}, stat {:?}",
+            self.session_id, round, stat
+        );
+        self.latency_stat.lock().unwrap().merge(&stat);
+    }
+
 }
 
 impl Drop for ValidatorGroup {
@@ -12,7 +12,7 @@
 */
 
 use crate::{
-    config::{RempConfig, ValidatorManagerConfig},
+    config::{CatchainRecoveryConfig, RempConfig, ValidatorManagerConfig},
     engine::Engine,
     engine_traits::EngineOperations,
     shard_state::ShardStateStuff,
@@ -25,7 +25,7 @@ use crate::{
             get_first_block_seqno_after_prevs,
             get_masterchain_seqno, get_block_info_by_id,
             compute_validator_list_id, get_group_members_by_validator_descrs, 
-            is_remp_enabled, try_calc_subset_for_workchain,
+            is_remp_enabled, next_session_activation_time, try_calc_subset_for_workchain,
             validatordescr_to_catchain_node, validatorset_to_string,
             ValidatorListHash, ValidatorSubsetInfo
         },
@@ -154,7 +154,10 @@ fn get_validator_session_options_hash(opts: &validator_session::SessionOptions)
     (UInt256::calc_file_hash(&serialized.0), serialized)
 }
 
-fn get_session_options(opts: &ConsensusConfig) -> validator_session::SessionOptions {
+fn get_session_options(
+    opts: &ConsensusConfig,
+    recovery: &CatchainRecoveryConfig
+) -> validator_session::SessionOptions {
     let default_opts = validator_session::SessionOptions::default();
 
     validator_session::SessionOptions {
@@ -175,6 +178,8 @@ fn get_session_options(opts: &ConsensusConfig) -> validator_session::SessionOpti
         catchain_receiver_max_sources_sync_attempts: default_opts.catchain_receiver_max_sources_sync_attempts,
         catchain_receiver_neighbours_rotate_min_period: default_opts.catchain_receiver_neighbours_rotate_min_period,
         catchain_receiver_neighbours_rotate_max_period: default_opts.catchain_receiver_neighbours_rotate_max_period,    
+        catchain_receiver_partition_timeout: std::time::Duration::from_millis(recovery.partition_timeout_ms),
+        catchain_receiver_resync_period: std::time::Duration::from_millis(recovery.resync_period_ms),
     }
 }
 
@@ -314,7 +319,14 @@ impl ValidatorManagerImpl {
     ) -> (Self, Option<Arc<RempInterfaceQueues>>) {
         let (remp_manager, remp_interface_queues) = if remp_config.is_service_enabled() {
             let (m, i) = RempManager::create_with_options(engine.clone(), remp_config.clone(), Arc::new(rt.clone()));
-            (Some(Arc::new(m)), Some(Arc::new(i)))
+            let m = Arc::new(m);
+            m.start_gc_task(
+                &rt,
+                #[cfg(feature = "telemetry")]
+                engine.clone()
+            );
+            m.start_next_set_forwarding_task(&rt, engine.clone());
+            (Some(m), Some(Arc::new(i)))
         } else {
             (None, None)
         };
@@ -442,17 +454,25 @@ impl ValidatorManagerImpl {
         Ok(())
     }
 
+    // Lowest master catchain seqno of the actual validator groups
+    async fn min_actual_master_cc_seqno(&self) -> Option<u32> {
+        let mut min_start = None;
+        for vg in self.validator_sessions.iter() {
+            if let Some(vg_range) = vg.1.get_master_cc_range().await {
+                let ms = min_start.unwrap_or(*vg_range.start());
+                min_start = Some(min(ms, *vg_range.start()));
+            }
+        }
+        min_start
+    }
+
     async fn garbage_collect_message_cache(&mut self) {
         if let Some(remp) = &self.remp_manager {
-            let mut min_start = None;
-            for vg in self.validator_sessions.iter() {
-                if let Some(vg_range) = vg.1.get_master_cc_range().await {
-                    let ms = min_start.unwrap_or(*vg_range.start());
-                    min_start = Some(min(ms, *vg_range.start()));
+            if let Some(min_actual) = self.min_actual_master_cc_seqno().await {
+                if remp.is_gc_task_enabled() {
+                    remp.set_gc_lwb(min_actual);
+                    return
                 }
-            }
-
-            if let Some(min_actual) = min_start {
                 let stats = remp.gc_old_messages(min_actual).await;
                 log::info!(target: "remp", "GC old REMP messages (cc < {}): {}", min_actual, stats);
                 #[cfg(feature = "telemetry")]
@@ -474,7 +494,13 @@ impl ValidatorManagerImpl {
             }
         }
 
-        remp.catchain_store.clone().gc_catchain_sessions(self.rt.clone(), active_remp_sessions).await;
+        let gc_horizon = self.min_actual_master_cc_seqno().await;
+        remp.catchain_store.clone().gc_catchain_sessions(self.rt.clone(), active_remp_sessions, gc_horizon).await;
+
+        let rmq_storage_size = remp.catchain_store.rmq_storage_size();
+        metrics::gauge!("remp_catchain_db_bytes", rmq_storage_size as f64);
+        #[cfg(feature = "telemetry")]
+        self.engine.remp_core_telemetry().rmq_storage_size(rmq_storage_size);
     }
 
     async fn garbage_collect(&mut self) {
@@ -499,8 +525,14 @@ impl ValidatorManagerImpl {
                         ValidatorGroupStatus::Stopping => {}
                         ValidatorGroupStatus::Stopped => {
                             if let Some(group) = self.validator_sessions.remove(id) {
+                                #[cfg(feature = "slashing")]
+                                for complaint in group.slashing_complaints() {
+                                    log::warn!(target: "slashing", "Complaint: {}", complaint);
+                                }
                                 if !self.is_active_shard(group.shard()).await {
                                     self.engine.remove_last_validation_time(group.shard());
+                                    self.engine.remove_session_latency_stat(group.shard());
+                                    self.engine.remove_session_consensus_report(group.shard());
                                     self.engine.remove_last_collation_time(group.shard());
                                     if let Some(remp_manager) = &self.remp_manager {
                                         remp_manager.remove_active_shard(group.shard()).await;
@@ -527,7 +559,7 @@ impl ValidatorManagerImpl {
             Some(ConfigParamEnum::ConfigParam29(ccc)) => ccc.consensus_config,
             _ => fail!("no CatchainConfig in config_params"),
         };
-        let session_options = get_session_options(&consensus_config);
+        let session_options = get_session_options(&consensus_config, &self.config.catchain_recovery);
         let (opts_hash, session_options_serialized) = get_validator_session_options_hash(&session_options);
         log::debug!(target: "validator_manager", "SessionOptions from config.29: {:?}", session_options);
         log::debug!(
@@ -629,6 +661,29 @@ impl ValidatorManagerImpl {
         Ok(())
     }
 
+    /// Pre-starts next session of the shard in its current validator group: the session's
+    /// REMP catchain is started before the session becomes current (shards to be split
+    /// or merged are not pre-started)
+    async fn prestart_next_session(
+        &self,
+        next_session_info: Arc<GeneralSessionInfo>,
+        next_validator_set: &ValidatorSet,
+        master_cc_range: &RangeInclusive<u32>
+    ) {
+        let current_group = self.validator_sessions.values().find(|group|
+            group.shard() == &next_session_info.shard &&
+            group.catchain_seqno() + 1 == next_session_info.catchain_seqno
+        );
+        if let Some(group) = current_group {
+            let shard = next_session_info.shard.clone();
+            if let Err(e) = group.clone().prestart_next_session(
+                next_validator_set, next_session_info, master_cc_range
+            ).await {
+                log::warn!(target: "validator_manager", "Cannot pre-start next session for shard {}: {}", shard, e);
+            }
+        }
+    }
+
     async fn compute_prev_sessions_list(
         &self,
         full_validator_set: &ValidatorSet,
@@ -700,7 +755,7 @@ impl ValidatorManagerImpl {
                 for (_, group) in self.validator_sessions.iter() {
                     let status = group.get_status().await;
                     if status == ValidatorGroupStatus::Sync || status == ValidatorGroupStatus::Active {
-                        let path_str: String = self.engine.db_root_dir()?.to_owned()+"/catchains";
+                        let path_str = self.config.get_consensus_catchain_db_path(self.engine.db_root_dir()?);
                         tokio::spawn( async move {
                             if let Err(err) = clear_catchains_cache(path_str).await {
                                 log::warn!("Error clearing catchains cache: {}", err);
@@ -892,6 +947,7 @@ impl ValidatorManagerImpl {
                 let slashing_manager = self.slashing_manager.clone();
                 let remp_manager = self.remp_manager.clone();
                 let allow_unsafe_self_blocks_resync = self.config.unsafe_resync_catchains.contains(&cc_seqno);
+                let catchain_db_path = self.config.get_consensus_catchain_db_path(self.engine.db_root_dir()?);
                 let session = self.validator_sessions.entry(session_id.clone()).or_insert_with(||
                     Arc::new(ValidatorGroup::new(
                         general_session_info.clone(),
@@ -902,6 +958,7 @@ impl ValidatorManagerImpl {
                         session_options,
                         remp_manager,
                         engine,
+                        catchain_db_path,
                         allow_unsafe_self_blocks_resync,
                         #[cfg(feature = "slashing")]
                         slashing_manager,
@@ -1004,7 +1061,7 @@ impl ValidatorManagerImpl {
         let mut future_shards: HashSet<ShardIdent> = HashSet::new();
         // Validator sets for shards that will eventually be started
         let mut our_future_shards: 
-            HashMap<ShardIdent, (ValidatorSubsetInfo, u32, ValidatorListHash)> = HashMap::new();
+            HashMap<ShardIdent, (ValidatorSubsetInfo, u32, ValidatorListHash, u32)> = HashMap::new();
         let mut blocks_before_split: HashSet<BlockIdExt> = HashSet::new();
 
         new_shards.insert(ShardIdent::masterchain(), vec![last_masterchain_block.clone()]);
@@ -1118,6 +1175,10 @@ impl ValidatorManagerImpl {
                 &full_validator_set
             };
 
+            let activation_at = next_session_activation_time(
+                mc_now.as_u32(), cc_lifetime, near_validator_change.then(|| next_validator_set.utime_since())
+            );
+
             let vnext_list_id = match compute_validator_list_id(&future_validator_set.list(), None)? {
                 None => continue,
                 Some(l) => l
@@ -1160,7 +1221,7 @@ impl ValidatorManagerImpl {
                 }
             };
 
-            our_future_shards.insert(ident.clone(), (next_subset, next_cc_seqno, vnext_list_id));
+            our_future_shards.insert(ident.clone(), (next_subset, next_cc_seqno, vnext_list_id, activation_at));
             log::trace!(
                 target: "validator_manager", 
                 "Future shard {}: computing next subset with cc_seqno {} -- done", 
@@ -1189,7 +1250,7 @@ impl ValidatorManagerImpl {
         log::trace!(target: "validator_manager", "Missing sessions started. Current shards:");
 
         // Iterate over future shards and create all future sessions
-        for (ident, (wc, next_cc_seqno, next_val_list_id)) in our_future_shards.iter() {
+        for (ident, (wc, next_cc_seqno, next_val_list_id, activation_at)) in our_future_shards.iter() {
             if let Some(local_id) = self.find_us(&wc.validators) {
                 let new_session_info = Arc::new(GeneralSessionInfo {
                     shard: ident.clone(),
@@ -1204,16 +1265,23 @@ impl ValidatorManagerImpl {
                     true,
                 );
                 gc_validator_sessions.remove(&session_id);
+                let next_validator_set = wc.compute_validator_set(*next_cc_seqno)?;
+                if self.config.session_prestart_sec > 0 &&
+                    *activation_at <= mc_now.as_u32() + self.config.session_prestart_sec
+                {
+                    self.prestart_next_session(new_session_info.clone(), &next_validator_set, &master_cc_range).await;
+                }
                 if !self.validator_sessions.contains_key(&session_id) {
                     let session = Arc::new(ValidatorGroup::new(
                         new_session_info,
                         local_id,
                         session_id.clone(),
                         next_val_list_id.clone(),
-                        wc.compute_validator_set(*next_cc_seqno)?,
+                        next_validator_set,
                         session_options,
                         self.remp_manager.clone(),
                         self.engine.clone(),
+                        self.config.get_consensus_catchain_db_path(self.engine.db_root_dir()?),
                         self.config.unsafe_resync_catchains.contains(next_cc_seqno),
                         #[cfg(feature = "slashing")]
                         self.slashing_manager.clone()
@@ -1280,6 +1348,23 @@ impl ValidatorManagerImpl {
             let status = group.get_status().await;
             if status == ValidatorGroupStatus::Sync || status == ValidatorGroupStatus::Active || status == ValidatorGroupStatus::Stopping {
                 self.engine.set_last_validation_time(group.shard().clone(), group.last_validation_time());
+                self.engine.set_session_latency_stat(group.shard().clone(), group.latency_stat());
+                let report = group.consensus_report();
+                let shard = group.shard().to_string();
+                metrics::gauge!("consensus_round_avg_ms", report.stats.avg_round_ms() as f64, &[("shard", shard.clone())]);
+                metrics::gauge!("consensus_rounds_skipped", report.stats.skipped as f64, &[("shard", shard.clone())]);
+                metrics::gauge!("consensus_payload_max_bytes", report.stats.max_payload as f64, &[("shard", shard.clone())]);
+                metrics::gauge!("validator_session_approvals", report.session.approvals_signed as f64, &[("shard", shard.clone())]);
+                metrics::gauge!("validator_session_rejects", report.session.candidates_rejected as f64, &[("shard", shard.clone())]);
+                metrics::gauge!("validator_session_produced", report.session.candidates_produced as f64, &[("shard", shard.clone())]);
+                metrics::gauge!("validator_session_consensus_avg_ms", report.session.avg_consensus_ms() as f64, &[("shard", shard.clone())]);
+                for suggestion in &report.suggestions {
+                    metrics::gauge!(
+                        "consensus_suggested_value", suggestion.suggested as f64,
+                        &[("shard", shard.clone()), ("param", suggestion.param.to_string())]
+                    );
+                }
+                self.engine.set_session_consensus_report(group.shard().clone(), report);
                 self.engine.set_last_collation_time(group.shard().clone(), group.last_collation_time());
             }
         }
@@ -55,6 +55,10 @@ pub enum ValidationAction {
     OnSlashingStatistics {
         round: u32,
         stat: SlashingValidatorStat,
+    },
+    OnLatencyStatistics {
+        round: u32,
+        stat: LatencyStat,
     }
 }
 
@@ -81,6 +85,8 @@ impl fmt::Display for ValidationAction {
 
             #[cfg(feature = "slashing")]
             ValidationAction::OnSlashingStatistics {round, ..} => write!(f, "OnSlashingStatistics round: {}", round),
+
+            ValidationAction::OnLatencyStatistics {round, ..} => write!(f, "OnLatencyStatistics round: {}", round),
         }
     }
 }
@@ -95,6 +101,7 @@ impl ValidationAction {
             ValidationAction::OnGetApprovedCandidate {..} => None,
             #[cfg(feature = "slashing")]
             ValidationAction::OnSlashingStatistics {round, ..} => Some(round),
+            ValidationAction::OnLatencyStatistics {round, ..} => Some(round),
         }
     }
 }
@@ -203,6 +210,16 @@ impl SessionListener for ValidatorSessionListener {
                 round, stat
             });
     }
+
+    /// Merge round events latency statistics
+    fn on_latency_statistics(&self, round: u32, stat: LatencyStat) {
+        log::trace!(target: "validator", "SessionListener::on_latency_statistics, {}", round);
+        self.do_send_general (
+            None,
+            ValidationAction::OnLatencyStatistics {
+                round, stat
+            });
+    }
 }
 
 impl CatchainReplayListener for ValidatorSessionListener {
@@ -239,7 +256,10 @@ async fn process_validation_action (action: ValidationAction, g: Arc<ValidatorGr
 
         #[cfg(feature = "slashing")]
         ValidationAction::OnSlashingStatistics { round, stat } =>
-            g.on_slashing_statistics(round, stat)
+            g.on_slashing_statistics(round, stat),
+
+        ValidationAction::OnLatencyStatistics { round, stat } =>
+            g.on_latency_statistics(round, stat),
     }
 }
 
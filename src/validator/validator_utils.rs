@@ -12,21 +12,21 @@
 */
 
 use crate::{
-    block::BlockIdExtExtention, engine_traits::EngineOperations, 
+    block::BlockIdExtExtention, config::RempConfig, engine_traits::EngineOperations, 
     shard_state::ShardStateStuff
 };
 
 use catchain::{BlockPayloadPtr, CatchainNode, PublicKey, PublicKeyHash};
-use std::{collections::HashMap, fmt::Debug, hash::Hash, sync::Arc};
+use std::{collections::{HashMap, HashSet}, fmt::Debug, hash::Hash, str::FromStr, sync::Arc};
 use ton_api::ton::engine::validator::validator::groupmember::GroupMember;
 use ton_block::{
-    BlockIdExt, BlockInfo, BlockSignatures, BlockSignaturesPure, ConfigParams, CryptoSignature, 
+    Account, BlockIdExt, BlockInfo, BlockSignatures, BlockSignaturesPure, ConfigParams, CryptoSignature, 
     CryptoSignaturePair, Deserializable, GlobalCapabilities, Message, Serializable, 
     ShardIdent, SigPubKey, UnixTime32, ValidatorBaseInfo, ValidatorDescr, ValidatorSet
 };
 use ton_types::{
     error, fail, BuilderData, HashmapType, Ed25519KeyOption, KeyId, KeyOption, KeyOptionJson, 
-    Result, Sha256, UInt256
+    Result, Sha256, SliceData, UInt256
 };
 use validator_session::SessionNode;
 
@@ -103,6 +103,94 @@ pub fn check_crypto_signatures(signatures: &BlockSignaturesPure, validators_list
     Ok(weight)
 }
 
+/// Validator signatures of a block aggregated into `BlockSignatures` one by one.
+/// Every signature is checked when it is added, so the block is known to have
+/// enough signatures before its proof is built, and the signatures are not checked
+/// again when the proof is rebuilt by retries of the block accept.
+pub struct BlockSignaturesAggregator {
+    block_id: BlockIdExt,
+    data: Vec<u8>,
+    validators: HashMap<Arc<KeyId>, (SigPubKey, u64)>,
+    signatures: BlockSignatures,
+    weight: u64,
+    total_weight: u64,
+}
+
+impl BlockSignaturesAggregator {
+
+    pub fn new(block_id: &BlockIdExt, validator_set: &ValidatorSet) -> Result<Self> {
+        let validators = validator_set.list().iter().map(|descr| {
+            let key = Ed25519KeyOption::from_public_key(descr.public_key.as_slice()).id().clone();
+            (key, (descr.public_key.clone(), descr.weight))
+        }).collect();
+        let cc_seqno = validator_set.catchain_seqno();
+        let signatures = BlockSignatures::with_params(
+            ValidatorBaseInfo::with_params(
+                ValidatorSet::calc_subset_hash_short(validator_set.list(), cc_seqno)?,
+                cc_seqno
+            ),
+            BlockSignaturesPure::with_weight(validator_set.total_weight())
+        );
+        Ok(Self {
+            block_id: block_id.clone(),
+            data: ton_block::Block::build_data_for_sign(&block_id.root_hash, &block_id.file_hash),
+            validators,
+            signatures,
+            weight: 0,
+            total_weight: validator_set.total_weight(),
+        })
+    }
+
+    /// Checks the signature and adds it, returns true if the signatures have enough weight.
+    /// Signatures of validators out of the set and repeated signatures are skipped.
+    pub fn add(&mut self, signature: CryptoSignaturePair) -> Result<bool> {
+        let key = KeyId::from_data(signature.node_id_short.inner());
+        let weight = match self.validators.get(&key) {
+            Some((public_key, weight)) => {
+                if !public_key.verify_signature(&self.data, &signature.sign) {
+                    fail!("Block {}: bad signature from validator with pub_key {}", self.block_id, key)
+                }
+                let weight = *weight;
+                self.validators.remove(&key);
+                weight
+            }
+            None => {
+                log::warn!(
+                    target: "validator", "Block {}: skipped signature of {} out of validator set or repeated",
+                    self.block_id, key
+                );
+                return Ok(self.is_complete())
+            }
+        };
+        self.signatures.pure_signatures.add_sigpair(signature);
+        self.weight += weight;
+        self.signatures.pure_signatures.set_weight(self.weight);
+        Ok(self.is_complete())
+    }
+
+    pub fn weight(&self) -> u64 {
+        self.weight
+    }
+
+    pub fn total_weight(&self) -> u64 {
+        self.total_weight
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.weight * 3 > self.total_weight * 2
+    }
+
+    pub fn signatures(&self) -> Result<&BlockSignatures> {
+        if !self.is_complete() {
+            fail!(
+                "Block {}: too small signatures weight (weight: {}, total: {})",
+                self.block_id, self.weight, self.total_weight
+            )
+        }
+        Ok(&self.signatures)
+    }
+}
+
 pub fn validatordescr_to_catchain_node(descr: &ValidatorDescr) -> CatchainNode {
     catchain::CatchainNode {
         adnl_id: get_adnl_id(descr),
@@ -398,6 +486,17 @@ pub fn get_first_block_seqno_after_prevs(prevs: &Vec<BlockIdExt>) -> Option<u32>
     prevs.iter().map(|blk| blk.seq_no).max().map(|x| x + 1)
 }
 
+/// Estimated time (UNIX) the next sessions of a shard become current: catchains are rotated
+/// when the time crosses a multiple of catchain lifetime, or earlier if the next validator set
+/// comes into force before
+pub fn next_session_activation_time(now: u32, cc_lifetime: u32, next_set_since: Option<u32>) -> u32 {
+    let rotation_at = (now / cc_lifetime.max(1) + 1) * cc_lifetime.max(1);
+    match next_set_since {
+        Some(since) if since < rotation_at => since.max(now),
+        _ => rotation_at
+    }
+}
+
 pub fn get_group_members_by_validator_descrs(iterator: &Vec<ValidatorDescr>, dst: &mut Vec<GroupMember>)  {
     for descr in iterator.iter() {
         let node_id = descr.compute_node_id_short();
@@ -424,6 +523,83 @@ pub fn get_message_uid(msg: &Message) -> UInt256 {
     }
 }
 
+// Replay protection timestamps (ABI `time` header) are in milliseconds
+const ABI_MIN_TIMESTAMP_MS: u64 = 1_500_000_000_000;
+const ABI_MAX_EXPIRE_PERIOD_SEC: u64 = 24 * 3600;
+
+/// Returns `time` header (in ms) of an external message with ABI 2.x body layout
+/// `[signature] [pubkey] time expire function_id` used by ABI-compliant wallets.
+/// None if the body can't be interpreted this way.
+pub fn get_abi_message_time(msg: &Message) -> Option<u64> {
+    msg.ext_in_header()?;
+    let mut body = msg.body()?;
+    if body.get_next_bit().ok()? {
+        body.move_by(512).ok()?;
+    }
+    if body.get_next_bit().ok()? {
+        body.move_by(256).ok()?;
+    }
+    let time = body.get_next_u64().ok()?;
+    let expire = body.get_next_u32().ok()? as u64;
+    if time < ABI_MIN_TIMESTAMP_MS || expire * 1000 < time ||
+        expire * 1000 - time > ABI_MAX_EXPIRE_PERIOD_SEC * 1000
+    {
+        return None
+    }
+    Some(time)
+}
+
+/// Wallets whose replay protection timestamps are trusted by REMP: only contracts with
+/// code from the configured list are known to keep `pubkey timestamp ...` in their data,
+/// any other contract may have data of the same layout by chance.
+pub struct WalletReplayPolicy {
+    code_hashes: HashSet<UInt256>,
+}
+
+impl WalletReplayPolicy {
+    pub fn new(options: &RempConfig) -> Self {
+        let mut code_hashes = HashSet::new();
+        for code_hash in options.get_replay_protection_code_hashes() {
+            match UInt256::from_str(code_hash) {
+                Ok(code_hash) => { code_hashes.insert(code_hash); }
+                Err(e) => log::error!(target: "remp", 
+                    "Wrong REMP replay protection code hash {}: {}", code_hash, e
+                )
+            }
+        }
+        Self { code_hashes }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.code_hashes.is_empty()
+    }
+
+    /// Returns replay protection timestamp (in ms) of the wallet, None if the account 
+    /// is not a known wallet
+    pub fn account_replay_timestamp(&self, account: &Account) -> Option<u64> {
+        if !self.code_hashes.contains(&account.get_code()?.repr_hash()) {
+            return None
+        }
+        get_account_replay_timestamp(account)
+    }
+}
+
+/// Returns replay protection timestamp (in ms) stored in the data of an ABI-compliant
+/// contract with layout `pubkey timestamp ...`. None if the data has another layout.
+/// The layout alone doesn't prove the contract is a wallet, see `WalletReplayPolicy`.
+fn get_account_replay_timestamp(account: &Account) -> Option<u64> {
+    let mut data = SliceData::load_cell(account.get_data()?).ok()?;
+    let pubkey = data.get_next_bytes(32).ok()?;
+    if pubkey.iter().all(|b| *b == 0) {
+        return None
+    }
+    let timestamp = data.get_next_u64().ok()?;
+    if timestamp < ABI_MIN_TIMESTAMP_MS {
+        return None
+    }
+    Some(timestamp)
+}
+
 pub async fn get_masterchain_seqno(engine: Arc<dyn EngineOperations>, mc_state: &ShardStateStuff) -> Result<u32> {
     let mc_state_extra = mc_state.shard_state_extra()?;
     let master_cc_seqno = mc_state_extra.validator_info.catchain_seqno;
@@ -534,6 +710,10 @@ impl<K,V> LockfreeMapSet<K,V> where V: Ord, V: Clone+Debug, K: Clone+Hash+Ord+De
         Ok(())
     }
 
+    pub fn remove(&self, msg_uid: &K) {
+        self.map.remove(msg_uid);
+    }
+
     pub fn get_set(&self, msg_uid: &K) -> Vec<V> {
         match self.map.get(msg_uid) {
             None => Vec::new(),
@@ -14,7 +14,7 @@
 use crate::{
     StorageAlloc,
     archives::{
-        archive_slice::ArchiveSlice, file_maps::{FileDescription, FileMaps}, 
+        ArchivesGcReport, archive_slice::ArchiveSlice, file_maps::{FileDescription, FileMaps}, 
         get_mc_seq_no, package_entry::PackageEntry, 
         package_entry_id::{GetFileNameShort, PackageEntryId, parse_short_filename},
         package_id::PackageId, ARCHIVE_SLICE_SIZE, KEY_ARCHIVE_PACKAGE_SIZE
@@ -106,8 +106,10 @@ impl ArchiveManager {
             .truncate(true)
             .open(&filename).await
             .map_err(|err| error!("{} : {}", err, filename.display()))?;
+        let now = std::time::Instant::now();
         file.write_all(&data).await?;
         file.flush().await?;
+        metrics::histogram!("db_block_write_time", now.elapsed());
 
         Ok(())
     }
@@ -344,6 +346,10 @@ impl ArchiveManager {
         }
     }
 
+    pub async fn gc_report(&self, last_unneeded_key_block: &BlockIdExt) -> Result<ArchivesGcReport> {
+        self.file_maps.files().gc_report(last_unneeded_key_block).await
+    }
+
     async fn get_package_entry<B, U256, PK>(
         &self, 
         handle: &BlockHandle, 
@@ -14,7 +14,7 @@
 use crate::{
     StorageAlloc, 
     archives::{
-        get_mc_seq_no_opt, ARCHIVE_PACKAGE_SIZE, KEY_ARCHIVE_PACKAGE_SIZE,
+        ArchivesGcReport, get_mc_seq_no_opt, ARCHIVE_PACKAGE_SIZE, KEY_ARCHIVE_PACKAGE_SIZE,
         archive_manager::ArchiveManager, package::{Package, read_package_from},
         package_entry::PackageEntry, package_entry_id::{GetFileName, PackageEntryId},
         package_entry_meta::PackageEntryMeta, package_entry_meta_db::PackageEntryMetaDb,
@@ -246,6 +246,23 @@ impl ArchiveSlice {
         Ok(())
     }
 
+    /// Counts entries of all packages of the slice into the report
+    pub async fn gc_report(&self, report: &mut ArchivesGcReport) -> Result<()> {
+        for pi in self.packages.read().await.iter() {
+            for (filename, size) in pi.package().read_entries_info().await? {
+                match PackageEntryId::from_filename(&filename) {
+                    Ok(PackageEntryId::Block(_)) => report.blocks.add(size),
+                    Ok(PackageEntryId::Proof(_)) | Ok(PackageEntryId::ProofLink(_)) => 
+                        report.proofs.add(size),
+                    _ => report.other.add(size)
+                }
+            }
+            report.archives.bytes += pi.package().size();
+        }
+        report.archives.count += 1;
+        Ok(())
+    }
+
     pub async fn destroy_broken(&mut self) -> Result<()> {
         if self.sliced_mode {
             let total_slices = self.package_status_db.get_value::<u32>(&PackageStatusKey::TotalSlices)?;
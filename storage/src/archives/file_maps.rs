@@ -28,7 +28,7 @@ use std::sync::atomic::Ordering;
 use ton_types::{Result, error, fail};
 use ton_block::BlockIdExt;
 
-use super::ARCHIVE_SLICE_SIZE;
+use super::{ArchivesGcReport, ARCHIVE_SLICE_SIZE};
 
 //#[derive(Debug)]
 pub struct FileDescription {
@@ -250,6 +250,18 @@ impl FileMap {
         Ok(())
     }
 
+    /// Reports what `gc` would delete without deleting anything
+    pub async fn gc_report(&self, last_unneeded_key_block: &BlockIdExt) -> Result<ArchivesGcReport> {
+        let slices = self.get_unneeded_entries(last_unneeded_key_block).await;
+        let mut report = ArchivesGcReport::default();
+        for key in slices {
+            if let Some(file_description) = self.get(key).await {
+                file_description.archive_slice().gc_report(&mut report).await?;
+            }
+        }
+        Ok(report)
+    }
+
     pub async fn get(&self, mc_seq_no: u32) -> Option<Arc<FileDescription>> {
         let guard = self.elements.read().await;
         log::trace!(target: "storage", "Searching for file description (elements count = {})", guard.len());
@@ -38,6 +38,29 @@ pub const KEY_ARCHIVE_SIZE: u32 = 10_000_000;
 pub const KEY_ARCHIVE_SLICE_SIZE: u32 = 2_000_000;
 pub const KEY_ARCHIVE_PACKAGE_SIZE: u32 = 200_000;
 
+/// Number and total size in bytes of the items of one kind
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GcTotals {
+    pub count: u64,
+    pub bytes: u64,
+}
+
+impl GcTotals {
+    pub fn add(&mut self, bytes: u64) {
+        self.count += 1;
+        self.bytes += bytes;
+    }
+}
+
+/// What archives GC would delete: archive slices and the package entries they contain
+#[derive(Clone, Debug, Default)]
+pub struct ArchivesGcReport {
+    pub archives: GcTotals,
+    pub blocks: GcTotals,
+    pub proofs: GcTotals,
+    pub other: GcTotals,
+}
+
 fn get_mc_seq_no_opt(block_handle: Option<&BlockHandle>) -> u32 {
     if let Some(handle) = block_handle {
         get_mc_seq_no(handle)
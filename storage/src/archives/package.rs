@@ -11,9 +11,12 @@
 * limitations under the License.
 */
 
-use crate::archives::package_entry::{PackageEntry, PKG_ENTRY_HEADER_SIZE};
+use crate::{
+    archives::package_entry::{PackageEntry, PackageEntryHeader, PKG_ENTRY_HEADER_SIZE},
+    traits::Serializable
+};
 use std::{io::SeekFrom, path::{Path, PathBuf}, sync::atomic::{AtomicU64, Ordering}};
-use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use ton_types::{error, fail, Result};
 
 #[cfg(test)]
@@ -124,6 +127,26 @@ impl Package {
             .ok_or_else(|| error!("Package::read_entry: Unexpected end of file"))
     }
 
+    /// Filenames and sizes (including headers) of all entries, data is skipped, not read
+    pub async fn read_entries_info(&self) -> Result<Vec<(String, u64)>> {
+        let mut file = self.open_file().await?;
+        file.seek(SeekFrom::Start(PKG_HEADER_SIZE as u64)).await?;
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset + PKG_ENTRY_HEADER_SIZE as u64 <= self.size() {
+            let mut buf = [0; PKG_ENTRY_HEADER_SIZE];
+            file.read_exact(&mut buf).await?;
+            let entry_header = PackageEntryHeader::from_slice(&buf)?;
+            let mut buf = vec![0; entry_header.filename_size() as usize];
+            file.read_exact(&mut buf).await?;
+            let filename = String::from_utf8(buf)?;
+            file.seek(SeekFrom::Current(entry_header.data_size() as i64)).await?;
+            offset += entry_header.calc_entry_size();
+            entries.push((filename, entry_header.calc_entry_size()));
+        }
+        Ok(entries)
+    }
+
     pub async fn append_entry(
         &self,
         entry: &PackageEntry,
@@ -29,6 +29,14 @@ impl PackageEntryHeader {
         Self { filename_size, data_size }
     }
 
+    pub const fn filename_size(&self) -> u16 {
+        self.filename_size
+    }
+
+    pub const fn data_size(&self) -> u32 {
+        self.data_size
+    }
+
     pub const fn calc_entry_size(&self) -> u64 {
         PKG_ENTRY_HEADER_SIZE as u64
             + self.filename_size as u64
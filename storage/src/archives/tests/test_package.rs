@@ -72,6 +72,22 @@ async fn test_reading() -> Result<()> {
 
 }
 
+#[tokio::test]
+async fn test_read_entries_info() -> Result<()> {
+
+    let path = PathBuf::from_str(ARCHIVE_00001_GOLD_PATH)?;
+    let package = Package::open(path, true, false).await?;
+    let entries = package.read_entries_info().await?;
+
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].0.starts_with("block_"));
+    assert!(entries[1].0.starts_with("proof_"));
+    assert_eq!(entries[0].1, 8 + entries[0].0.len() as u64 + 3);
+    assert_eq!(entries.iter().map(|(_, size)| size).sum::<u64>(), package.size());
+    Ok(())
+
+}
+
 #[tokio::test]
 async fn test_pkg_reader() -> Result<()> {
 
@@ -20,8 +20,8 @@ use crate::{
 };
 use adnl::common::add_unbound_object_to_map;
 use rocksdb::{
-    BlockBasedOptions, BoundColumnFamily, Cache, DBWithThreadMode, IteratorMode, MultiThreaded, 
-    Options, SnapshotWithThreadMode, WriteBatch
+    BlockBasedOptions, BoundColumnFamily, Cache, DBWithThreadMode, Direction, IteratorMode, 
+    MultiThreaded, Options, SnapshotWithThreadMode, WriteBatch
 };
 use std::{
     fmt::{Debug, Formatter}, ops::Deref, path::Path, sync::{Arc, atomic::{AtomicI32, Ordering}},
@@ -33,13 +33,53 @@ use ton_block::BlockIdExt;
 pub const LAST_UNNEEDED_KEY_BLOCK: &str = "LastUnneededKeyBlockId"; // Latest key block we can delete in archives GC
 pub const NODE_STATE_DB_NAME: &str = "node_state_db";
 
-#[derive(Debug)]
+// RocksDB statistics histograms exported as metrics (in microseconds)
+const SYNC_STATISTICS: [(&str, &str, &str); 2] = [
+    ("rocksdb.db.fsync.micros", "db_fsync_time", "db_fsync_count"),
+    ("rocksdb.wal.file.sync.micros", "db_wal_sync_time", "db_wal_sync_count"),
+];
+
 pub struct RocksDb {
     db: Option<DBWithThreadMode<MultiThreaded>>,
+    // Keeps statistics of the opened DB
+    options: Options,
     locks: lockfree::map::Map<String, AtomicI32>,
     hi_perf_cfs: HashSet<String>
 }
 
+impl Debug for RocksDb {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RocksDb")
+            .field("db", &self.db)
+            .field("hi_perf_cfs", &self.hi_perf_cfs)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct StatisticsHistogram {
+    pub p50: f64,
+    pub p99: f64,
+    pub count: u64,
+}
+
+// Parses histogram line of RocksDB statistics dump like
+// "rocksdb.db.fsync.micros P50 : 1.5 P95 : 2.0 P99 : 3.0 P100 : 4.0 COUNT : 10 SUM : 20"
+pub(crate) fn parse_statistics_histogram(stats: &str, name: &str) -> Option<StatisticsHistogram> {
+    let line = stats.lines().find(|line| line.split_whitespace().next() == Some(name))?;
+    let tokens = line.split_whitespace().collect::<Vec<_>>();
+    let mut histogram = StatisticsHistogram::default();
+    for field in tokens.windows(3).filter(|field| field[1] == ":") {
+        match field[0] {
+            "P50" => histogram.p50 = field[2].parse().ok()?,
+            "P99" => histogram.p99 = field[2].parse().ok()?,
+            "COUNT" => histogram.count = field[2].parse().ok()?,
+            _ => ()
+        }
+    }
+    Some(histogram)
+}
+
 impl RocksDb {
 
     /// Creates new instance with given path
@@ -107,6 +147,7 @@ impl RocksDb {
 
             let db = Self {
                 db: Some(db),
+                options,
                 locks: lockfree::map::Map::new(),
                 hi_perf_cfs,
             };
@@ -243,6 +284,27 @@ impl RocksDb {
         RocksDbTable::with_db(self, family, create_if_not_exist)
     }
 
+    /// Exports fsync and WAL sync timings collected by RocksDB statistics as
+    /// "db_fsync_time" and "db_wal_sync_time" gauges (median and 99th percentile)
+    /// and total number of syncs as "db_fsync_count" and "db_wal_sync_count"
+    pub fn report_sync_statistics(&self) {
+        let stats = match self.statistics() {
+            Some(stats) => stats,
+            None => return
+        };
+        for (statistic, time_metric, count_metric) in SYNC_STATISTICS {
+            if let Some(histogram) = parse_statistics_histogram(&stats, statistic) {
+                metrics::gauge!(time_metric, histogram.p50, "quantile" => "0.5");
+                metrics::gauge!(time_metric, histogram.p99, "quantile" => "0.99");
+                metrics::gauge!(count_metric, histogram.count as f64);
+            }
+        }
+    }
+
+    pub(crate) fn statistics(&self) -> Option<String> {
+        self.options.get_statistics()
+    }
+
     fn db(&self) -> &DBWithThreadMode<MultiThreaded> {
         self.db.as_ref().expect("rocksdb was occasionaly destroyed")
     }
@@ -457,10 +519,30 @@ impl<K: DbKey + Send + Sync> KvcReadable<K> for RocksDbTable {
     }
 
     fn for_each(&self, predicate: &mut dyn FnMut(&[u8], &[u8]) -> Result<bool>) -> Result<bool> {
+        self.iterate(IteratorMode::Start, predicate)
+    }
+
+    fn for_each_from(
+        &self, 
+        from: &[u8], 
+        predicate: &mut dyn FnMut(&[u8], &[u8]) -> Result<bool>
+    ) -> Result<bool> {
+        self.iterate(IteratorMode::From(from, Direction::Forward), predicate)
+    }
+
+}
+
+impl RocksDbTable {
+
+    fn iterate(
+        &self, 
+        mode: IteratorMode, 
+        predicate: &mut dyn FnMut(&[u8], &[u8]) -> Result<bool>
+    ) -> Result<bool> {
         if let Some(lock) = self.db.locks.get(&self.family) {
             let lock = lock.val();
             if lock.fetch_add(1, Ordering::Relaxed) >= 0 {
-                for iter in self.db.iterator_cf(&self.cf()?, IteratorMode::Start) {
+                for iter in self.db.iterator_cf(&self.cf()?, mode) {
                     let (key, value) = iter?;
                     match predicate(key.as_ref(), value.as_ref()) {
                         Ok(false) => {
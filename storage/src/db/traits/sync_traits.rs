@@ -85,6 +85,28 @@ pub trait KvcReadable<K: DbKey + Send + Sync>: Kvc {
 
     /// Iterates over items in key-value collection, running predicate for each key-value pair
     fn for_each(&self, predicate: &mut dyn FnMut(&[u8], &[u8]) -> Result<bool>) -> Result<bool>;
+
+    /// Iterates over items with keys not less than the given one in order of keys
+    fn for_each_from(
+        &self, 
+        from: &[u8], 
+        predicate: &mut dyn FnMut(&[u8], &[u8]) -> Result<bool>
+    ) -> Result<bool> {
+        let mut items = Vec::new();
+        self.for_each(&mut |key, value| {
+            if key >= from {
+                items.push((key.to_vec(), value.to_vec()));
+            }
+            Ok(true)
+        })?;
+        items.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+        for (key, value) in items {
+            if !predicate(&key, &value)? {
+                return Ok(false)
+            }
+        }
+        Ok(true)
+    }
 }
 
 /// Trait for writable key-value collections
@@ -19,7 +19,7 @@ use crate::{
 use crate::StorageTelemetry;
 use std::{
     borrow::Cow, fs::write, io::Cursor, mem::size_of, ops::{Deref, DerefMut}, path::Path,
-    sync::{Arc, atomic::{AtomicU32, Ordering}}, time::Duration
+    sync::{Arc, atomic::{AtomicU32, Ordering}}, time::{Duration, Instant}
 };
 //#[cfg(test)]
 //use std::path::Path;
@@ -29,6 +29,8 @@ use ton_types::{
 };
 
 pub const BROKEN_CELL_BEACON_FILE: &str = "ton_node.broken_cell";
+// Only every N-th read from DB is timed for "db_cell_read_time", cells reading is the hot path
+pub const CELL_READ_TIME_SAMPLE: u32 = 1024;
 
 // FnvHashMap is a standard HashMap with FNV hasher. This hasher is bit faster than default one.
 pub(crate) type CellsCounters = fnv::FnvHashMap<UInt256, u32>;
@@ -157,11 +159,55 @@ fn build_counter_key(cell_id: &[u8]) -> [u8; 33] {
     key
 }
 
+/// Incremental verification of cells DB. Entries are checked by small batches in order of 
+/// keys, so a cell and its counter are checked together; a pass over the whole DB is 
+/// repeated after a pause. Found problems are only reported unless repair is enabled.
+#[derive(Default)]
+pub(crate) struct CellsVerification {
+    // key to continue the pass from, None - the pass is not started
+    cursor: Option<Vec<u8>>,
+    next_pass_at: Option<Instant>,
+    checked: u64,
+    // counters without cells
+    lost_cells: u64,
+    // cells without counters
+    uncounted_cells: u64,
+    // unreachable cells with zero counters
+    unreferenced_cells: u64,
+    // cells deleted by repair
+    freed_cells: u64,
+}
+
+impl CellsVerification {
+
+    pub(crate) fn is_due(&self, now: Instant) -> bool {
+        self.next_pass_at.map_or(true, |next_pass_at| now >= next_pass_at)
+    }
+
+    pub(crate) fn finish_pass(&mut self, now: Instant, pause: Duration) {
+        log::info!(
+            target: TARGET,
+            "DynamicBocDb cells verification: checked {}, lost cells {}, uncounted cells {}, \
+            unreferenced cells {}, freed cells {}",
+            self.checked, self.lost_cells, self.uncounted_cells, self.unreferenced_cells, 
+            self.freed_cells
+        );
+        metrics::gauge!("db_cells_lost", self.lost_cells as f64);
+        metrics::gauge!("db_cells_uncounted", self.uncounted_cells as f64);
+        metrics::gauge!("db_cells_unreferenced", self.unreferenced_cells as f64);
+        *self = Self {
+            next_pass_at: Some(now + pause),
+            ..Self::default()
+        };
+    }
+}
+
 pub struct DynamicBocDb {
     db: Arc<CellDb>,
     db_root_path: String,
     assume_old_cells: bool,
     raw_cells_cache: RawCellsCache,
+    cell_reads: AtomicU32,
     #[cfg(feature = "telemetry")]
     telemetry: Arc<StorageTelemetry>,
     allocated: Arc<StorageAlloc>
@@ -184,6 +230,7 @@ impl DynamicBocDb {
             db_root_path: db_root_path.to_string(),
             assume_old_cells,
             raw_cells_cache,
+            cell_reads: AtomicU32::new(0),
             #[cfg(feature = "telemetry")]
             telemetry,
             allocated
@@ -258,10 +305,12 @@ impl DynamicBocDb {
 
         let now = std::time::Instant::now();
         transaction.commit()?;
+        let elapsed = now.elapsed();
+        metrics::histogram!("db_state_commit_time", elapsed);
         log::debug!(
             target: TARGET,
             "DynamicBocDb::save_boc  {:x}  transaction commit TIME {}", root_id,
-            now.elapsed().as_millis()
+            elapsed.as_millis()
         );
 
         let saved_root = if let Some(c) = visited.get(&root_id).and_then(|vc| vc.cell()) {
@@ -398,6 +447,23 @@ impl DynamicBocDb {
             full_filled_counters,
         )?;
 
+        let deleted = self.commit_visited(&visited)?;
+        let updated = visited.len() - deleted;
+        #[cfg(feature = "telemetry")] {
+            self.telemetry.deleted_cells.update(deleted as u64);
+            self.telemetry.updated_cells.update(updated as u64);
+        }
+
+        log::debug!(
+            target: TARGET,
+            "DynamicBocDb::delete_boc  {:x}  deleted {}  updated {}",
+            root_cell_id, deleted, updated
+        );
+        Ok(())
+    }
+
+    // Writes updated counters, deletes cells with zero counters, returns the number of deleted
+    fn commit_visited(&self, visited: &fnv::FnvHashMap<UInt256, VisitedCell>) -> Result<usize> {
         let mut deleted = 0;
         let mut transaction = self.db.begin_transaction()?;
         for (id, cell) in visited.iter() {
@@ -418,19 +484,120 @@ impl DynamicBocDb {
             }
         }
         transaction.commit()?;
+        Ok(deleted)
+    }
 
-        let updated = visited.len() - deleted;
-        #[cfg(feature = "telemetry")] {
-            self.telemetry.deleted_cells.update(deleted as u64);
-            self.telemetry.updated_cells.update(updated as u64);
+    // Is not thread-safe!
+    /// Checks the next batch of cells DB entries, returns true if the pass over DB is finished.
+    /// Cells with zero counters are unreachable, they are reported and, with `repair`, 
+    /// deleted like roots of deleted states.
+    pub(crate) fn verify_cells_batch(
+        self: &Arc<Self>,
+        verification: &mut CellsVerification,
+        batch_size: usize,
+        repair: bool,
+        cells_counters: &mut Option<CellsCounters>,
+    ) -> Result<bool> {
+        let from = verification.cursor.take().unwrap_or_default();
+        let mut checked = 0;
+        let mut next = None;
+        let mut unreferenced = Vec::new();
+        self.db.for_each_from(&from, &mut |key, value| {
+            if checked == batch_size {
+                next = Some(key.to_vec());
+                return Ok(false)
+            }
+            checked += 1;
+            if key.len() == 33 && key[32] == 0 {
+                let counter = Cursor::new(value).read_le_u32()?;
+                let cell_id = UInt256::from_slice(&key[..32]);
+                if counter == 0 {
+                    verification.unreferenced_cells += 1;
+                    log::warn!(
+                        target: TARGET,
+                        "DynamicBocDb::verify_cells_batch  cell with zero counter {:x}", cell_id
+                    );
+                    unreferenced.push(cell_id);
+                } else if self.db.try_get_raw(&key[..32])?.is_none() {
+                    verification.lost_cells += 1;
+                    log::error!(
+                        target: TARGET,
+                        "DynamicBocDb::verify_cells_batch  counter without cell {:x}", cell_id
+                    );
+                }
+            } else if key.len() == 32 && !self.assume_old_cells {
+                if self.db.try_get_raw(&build_counter_key(key))?.is_none() {
+                    verification.uncounted_cells += 1;
+                    log::warn!(
+                        target: TARGET,
+                        "DynamicBocDb::verify_cells_batch  cell without counter {:x}", 
+                        UInt256::from_slice(key)
+                    );
+                }
+            }
+            Ok(true)
+        })?;
+
+        if repair {
+            let mut freed = 0;
+            for cell_id in unreferenced.iter() {
+                freed += self.free_unreferenced_cell(cell_id, cells_counters)?;
+            }
+            if freed > 0 {
+                log::warn!(
+                    target: TARGET,
+                    "DynamicBocDb::verify_cells_batch  deleted {} unreferenced cells", freed
+                );
+                verification.freed_cells += freed as u64;
+                metrics::counter!("db_cells_freed_by_verification", freed as u64);
+            }
         }
+        verification.checked += checked as u64;
+        metrics::counter!("db_cells_verified", checked as u64);
 
-        log::debug!(
-            target: TARGET,
-            "DynamicBocDb::delete_boc  {:x}  deleted {}  updated {}",
-            root_cell_id, deleted, updated
-        );
-        Ok(())
+        verification.cursor = next;
+        Ok(verification.cursor.is_none())
+    }
+
+    // Deletes the cell with zero counter and its subtree like a root of a deleted state: 
+    // counters of the children are decremented, the children which are not referenced 
+    // anymore are deleted too. Returns the number of deleted cells.
+    fn free_unreferenced_cell(
+        self: &Arc<Self>,
+        cell_id: &UInt256,
+        cells_counters: &mut Option<CellsCounters>,
+    ) -> Result<usize> {
+        // the counter is taken again: the cell could be freed as a child of another one, 
+        // and the cached counter is the actual one
+        let counter = match cells_counters.as_ref().and_then(|cc| cc.get(cell_id)) {
+            Some(counter) => Some(*counter),
+            None => self.db.try_get_raw(&build_counter_key(cell_id.as_slice()))?
+                .map(|raw| Cursor::new(raw.as_ref()).read_le_u32())
+                .transpose()?
+        };
+        if counter != Some(0) {
+            return Ok(0)
+        }
+        if let Some(counters) = cells_counters.as_mut() {
+            counters.remove(cell_id);
+        }
+        let mut visited = fnv::FnvHashMap::default();
+        visited.insert(cell_id.clone(), VisitedCell::with_counter(cell_id.clone(), 0));
+        // references are taken from the cell itself, a counter without cell is just deleted
+        if let Some((cell, _)) = self.db.try_get_cell(cell_id, self, false, false)? {
+            let cell = Cell::with_cell_impl(cell);
+            for i in 0..cell.references_count() {
+                self.delete_cells_recursive(
+                    &cell.reference_repr_hash(i)?,
+                    &mut visited,
+                    cell_id,
+                    &|| Ok(()),
+                    cells_counters,
+                    false,
+                )?;
+            }
+        }
+        self.commit_visited(&visited)
     }
 
     pub(crate) fn load_cell(
@@ -461,8 +628,15 @@ impl DynamicBocDb {
             }
         }
 
+        let sampled = self.cell_reads.fetch_add(1, Ordering::Relaxed) % CELL_READ_TIME_SAMPLE == 0;
+        let now = sampled.then(Instant::now);
         let storage_cell_data = match self.db.get(cell_id) {
-            Ok(data) => data,
+            Ok(data) => {
+                if let Some(now) = now {
+                    metrics::histogram!("db_cell_read_time", now.elapsed());
+                }
+                data
+            }
             Err(e) => {
                 log::error!("FATAL!");
                 log::error!("FATAL! Can't load cell {:x} from db, error: {:?}", cell_id, e);
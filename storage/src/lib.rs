@@ -22,6 +22,9 @@ pub mod dynamic_boc_rc_db;
 pub mod error;
 mod macros; 
 pub mod node_state_db;
+pub mod remp_messages_db;
+pub mod remp_replay_db;
+pub mod remp_sessions_db;
 pub mod shardstate_db_async;
 pub mod traits;
 pub mod types;
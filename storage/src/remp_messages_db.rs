@@ -0,0 +1,101 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{db_impl_base, db::traits::{KvcReadable, KvcWriteable}, traits::Serializable};
+use std::io::{Read, Write};
+use ton_types::{ByteOrderRead, Result, UInt256};
+
+db_impl_base!(RempMessagesDb, KvcWriteable, Vec<u8>);
+
+pub const REMP_MESSAGES_DB_NAME: &str = "remp_messages";
+
+/// Record of REMP message cache: message uid, TL-serialized status and
+/// TL-serialized message record (absent if only message header is known)
+#[derive(Debug, PartialEq)]
+pub struct RempMessageEntry {
+    pub uid: UInt256,
+    pub status: Vec<u8>,
+    pub message: Option<Vec<u8>>,
+}
+
+impl Serializable for RempMessageEntry {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(self.uid.as_slice())?;
+        writer.write_all(&(self.status.len() as u32).to_le_bytes())?;
+        writer.write_all(&self.status)?;
+        if let Some(message) = &self.message {
+            writer.write_all(&(message.len() as u32).to_le_bytes())?;
+            writer.write_all(message)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+        let uid = UInt256::from(reader.read_u256()?);
+        let mut status = vec![0; reader.read_le_u32()? as usize];
+        reader.read_exact(&mut status)?;
+        let mut message = Vec::new();
+        reader.read_to_end(&mut message)?;
+        let message = if message.is_empty() {
+            None
+        } else {
+            Some(message.split_off(4))
+        };
+        Ok(Self { uid, status, message })
+    }
+}
+
+impl RempMessagesDb {
+
+    // Master cc seqno goes first (big endian), so records of one session are adjacent
+    fn key(master_cc: u32, message_id: &UInt256) -> Vec<u8> {
+        let mut key = master_cc.to_be_bytes().to_vec();
+        key.extend_from_slice(message_id.as_slice());
+        key
+    }
+
+    pub fn put_entry(&self, master_cc: u32, message_id: &UInt256, entry: &RempMessageEntry) -> Result<()> {
+        self.put(&Self::key(master_cc, message_id), &entry.to_vec()?)
+    }
+
+    /// Calls `f` with message id and record for all messages of master cc session
+    pub fn for_each_entry(
+        &self,
+        master_cc: u32,
+        f: &mut dyn FnMut(UInt256, RempMessageEntry) -> Result<()>
+    ) -> Result<()> {
+        let prefix = master_cc.to_be_bytes();
+        self.for_each(&mut |key, value| {
+            if key.len() == 36 && key.starts_with(&prefix) {
+                f(UInt256::from_slice(&key[4..]), RempMessageEntry::from_slice(value)?)?;
+            }
+            Ok(true)
+        })?;
+        Ok(())
+    }
+
+    /// Removes records of all master cc sessions before `master_cc`
+    pub fn remove_before(&self, master_cc: u32) -> Result<usize> {
+        let mut keys = Vec::new();
+        self.for_each(&mut |key, _value| {
+            if key.len() == 36 && u32::from_be_bytes([key[0], key[1], key[2], key[3]]) < master_cc {
+                keys.push(key.to_vec());
+            }
+            Ok(true)
+        })?;
+        for key in &keys {
+            self.delete(key)?;
+        }
+        Ok(keys.len())
+    }
+}
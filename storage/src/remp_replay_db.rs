@@ -0,0 +1,68 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{db_impl_base, db::traits::{KvcReadable, KvcWriteable}, traits::Serializable};
+use std::io::{Read, Write};
+use ton_block::BlockIdExt;
+use ton_types::{ByteOrderRead, Result, UInt256};
+
+db_impl_base!(RempReplayDb, KvcWriteable, UInt256);
+
+pub const REMP_REPLAY_DB_NAME: &str = "remp_replay";
+
+/// Replay protection record, stored by message uid: a message with the uid
+/// was accepted by shardchain or masterchain in the block
+#[derive(Clone, Debug, PartialEq)]
+pub struct RempReplayEntry {
+    pub message_id: UInt256,
+    pub block_id: BlockIdExt,
+    // Unix time of acceptance, records are removed after expiry window
+    pub accepted_at: u32,
+}
+
+impl Serializable for RempReplayEntry {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(self.message_id.as_slice())?;
+        self.block_id.serialize(writer)?;
+        writer.write_all(&self.accepted_at.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+        let message_id = UInt256::from(reader.read_u256()?);
+        let block_id = BlockIdExt::deserialize(reader)?;
+        let accepted_at = reader.read_le_u32()?;
+        Ok(Self { message_id, block_id, accepted_at })
+    }
+}
+
+impl RempReplayDb {
+
+    pub fn put_entry(&self, message_uid: &UInt256, entry: &RempReplayEntry) -> Result<()> {
+        self.put(message_uid, &entry.to_vec()?)
+    }
+
+    pub fn remove_entry(&self, message_uid: &UInt256) -> Result<()> {
+        self.delete(message_uid)
+    }
+
+    /// Calls `f` with message uid and record for all stored messages
+    pub fn for_each_entry(&self, f: &mut dyn FnMut(UInt256, RempReplayEntry) -> Result<()>) -> Result<()> {
+        self.for_each(&mut |key, value| {
+            if key.len() == 32 {
+                f(UInt256::from_slice(key), RempReplayEntry::from_slice(value)?)?;
+            }
+            Ok(true)
+        })
+    }
+}
@@ -0,0 +1,73 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{db_impl_base, db::traits::{KvcReadable, KvcWriteable}, traits::Serializable};
+use std::io::{Read, Write};
+use ton_block::ShardIdent;
+use ton_types::{ByteOrderRead, Result, UInt256};
+
+db_impl_base!(RempSessionsDb, KvcWriteable, UInt256);
+
+pub const REMP_SESSIONS_DB_NAME: &str = "remp_sessions";
+
+/// Bookkeeping record of RMQ catchain session: it is kept while the session is running,
+/// so sessions left after unclean shutdown can be found at startup
+#[derive(Debug, PartialEq)]
+pub struct RempSessionEntry {
+    pub shard: ShardIdent,
+    pub master_cc_seqno: u32,
+    pub status: u8,
+    // Catchain DB of the session is named after its overlay id
+    pub catchain_overlay_id: UInt256,
+}
+
+impl Serializable for RempSessionEntry {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.shard.workchain_id().to_le_bytes())?;
+        writer.write_all(&self.shard.shard_prefix_with_tag().to_le_bytes())?;
+        writer.write_all(&self.master_cc_seqno.to_le_bytes())?;
+        writer.write_all(&[self.status])?;
+        writer.write_all(self.catchain_overlay_id.as_slice())?;
+        Ok(())
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+        let workchain_id = reader.read_le_u32()? as i32;
+        let shard = ShardIdent::with_tagged_prefix(workchain_id, reader.read_le_u64()?)?;
+        let master_cc_seqno = reader.read_le_u32()?;
+        let status = reader.read_byte()?;
+        let catchain_overlay_id = UInt256::from(reader.read_u256()?);
+        Ok(Self { shard, master_cc_seqno, status, catchain_overlay_id })
+    }
+}
+
+impl RempSessionsDb {
+
+    pub fn put_session(&self, queue_id: &UInt256, entry: &RempSessionEntry) -> Result<()> {
+        self.put(queue_id, &entry.to_vec()?)
+    }
+
+    pub fn remove_session(&self, queue_id: &UInt256) -> Result<()> {
+        self.delete(queue_id)
+    }
+
+    /// Calls `f` with queue id and record of all stored sessions
+    pub fn for_each_session(&self, f: &mut dyn FnMut(UInt256, RempSessionEntry) -> Result<()>) -> Result<()> {
+        self.for_each(&mut |key, value| {
+            if key.len() == 32 {
+                f(UInt256::from_slice(key), RempSessionEntry::from_slice(value)?)?;
+            }
+            Ok(true)
+        })
+    }
+}
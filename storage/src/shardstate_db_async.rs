@@ -16,7 +16,7 @@ use crate::{
     db::{rocksdb::RocksDbTable, traits::{DbKey, KvcWriteable}},
     dynamic_boc_rc_db::{
         DynamicBocDb, DoneCellsStorageAdapter, OrderedCellsStorageAdapter, CellsCounters, 
-        CellByHashStorageAdapter
+        CellByHashStorageAdapter, CellsVerification
     },
     traits::Serializable,
     TARGET, error::StorageError,
@@ -115,6 +115,17 @@ pub struct CellsDbConfig {
     pub prefill_cells_counters: bool,
     pub cache_cells_counters: bool,
     pub cache_size_bytes: u64,
+    // Entries of cells DB checked at once when there are no states to store or delete,
+    // 0 - no verification
+    #[serde(default = "default_cells_verification_batch")]
+    pub cells_verification_batch: u32,
+    // Unreferenced cells found by the verification are deleted, otherwise they are only reported
+    #[serde(default)]
+    pub cells_verification_repair: bool,
+}
+
+fn default_cells_verification_batch() -> u32 {
+    10_000
 }
 
 impl Default for CellsDbConfig {
@@ -125,6 +136,8 @@ impl Default for CellsDbConfig {
             prefill_cells_counters: false,
             cache_cells_counters: false,
             cache_size_bytes: 1_000_000_000,
+            cells_verification_batch: default_cells_verification_batch(),
+            cells_verification_repair: false,
         }
     }
 }
@@ -148,6 +161,8 @@ impl ShardStateDb {
     const MASK_GC_STARTED: u8 = 0x01;
     const MASK_WORKER: u8 = 0x02;
     const MASK_STOPPED: u8 = 0x80;
+    // pause between passes of cells DB verification
+    const CELLS_VERIFICATION_PAUSE: Duration = Duration::from_secs(3600);
 
     pub fn new(
         db: Arc<RocksDb>,
@@ -560,6 +575,7 @@ impl ShardStateDb {
             Some(cells_counters)
         };
 
+        let mut verification = CellsVerification::default();
         loop {
             if check_stop() {
                 return;
@@ -617,11 +633,39 @@ impl ShardStateDb {
                         let _ = callback.invoke(job, ok).await;
                     }
                 }
+                Err(_) => self.verify_cells(&mut verification, &mut cells_counters),
                 _ => ()
             }
         }
     }
 
+    // Cells DB is verified by the worker when it is idle, 
+    // so the verification does not race with storing and deleting of states
+    fn verify_cells(
+        &self,
+        verification: &mut CellsVerification,
+        cells_counters: &mut Option<CellsCounters>,
+    ) {
+        let batch_size = self.config.cells_verification_batch as usize;
+        let now = std::time::Instant::now();
+        if batch_size == 0 || !verification.is_due(now) {
+            return
+        }
+        let result = tokio::task::block_in_place(|| {
+            self.dynamic_boc_db.verify_cells_batch(
+                verification, 
+                batch_size, 
+                self.config.cells_verification_repair, 
+                cells_counters
+            )
+        });
+        match result {
+            Ok(true) => verification.finish_pass(now, Self::CELLS_VERIFICATION_PAUSE),
+            Ok(false) => (),
+            Err(e) => log::warn!(target: TARGET, "ShardStateDb::verify_cells  {}", e)
+        }
+    }
+
     pub fn put_internal(
         self: Arc<Self>,
         id: &BlockIdExt,
@@ -13,7 +13,11 @@
 
 mod test_block_db;
 mod test_catchain_persistent_db;
+mod test_disk_metrics;
 mod test_dynamic_boc_rc_db;
+mod test_remp_messages_db;
+mod test_remp_replay_db;
+mod test_remp_sessions_db;
 mod test_shardstate_db_async;
 
 pub mod utils {
@@ -0,0 +1,170 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{
+    cell_db::CellDb, db::rocksdb::{parse_statistics_histogram, RocksDb, StatisticsHistogram},
+    dynamic_boc_rc_db::{DynamicBocDb, CELL_READ_TIME_SAMPLE}, tests::utils::*, StorageAlloc
+};
+#[cfg(feature = "telemetry")]
+use crate::StorageTelemetry;
+use metrics::{Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Recorder, SharedString, Unit};
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+use ton_types::Result;
+
+// Keeps values of histograms, the recorder is global for all tests of the crate
+#[derive(Default)]
+struct TestRecorder {
+    histograms: Mutex<HashMap<String, Arc<TestHistogram>>>,
+}
+
+#[derive(Default)]
+struct TestHistogram(Mutex<Vec<f64>>);
+
+impl HistogramFn for TestHistogram {
+    fn record(&self, value: f64) {
+        self.0.lock().unwrap().push(value)
+    }
+}
+
+impl TestRecorder {
+    fn values(&self, name: &str) -> Vec<f64> {
+        self.histograms.lock().unwrap().get(name)
+            .map(|histogram| histogram.0.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Recorder for TestRecorder {
+    fn describe_counter(&self, _key_name: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+    fn describe_gauge(&self, _key_name: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+    fn describe_histogram(&self, _key_name: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+    fn register_counter(&self, _key: &Key) -> Counter {
+        Counter::noop()
+    }
+    fn register_gauge(&self, _key: &Key) -> Gauge {
+        Gauge::noop()
+    }
+    fn register_histogram(&self, key: &Key) -> Histogram {
+        let histogram = self.histograms.lock().unwrap()
+            .entry(key.name().to_string())
+            .or_default()
+            .clone();
+        Histogram::from_arc(histogram)
+    }
+}
+
+fn recorder() -> &'static TestRecorder {
+    lazy_static::lazy_static! {
+        static ref RECORDER: &'static TestRecorder = {
+            let recorder = Box::leak(Box::new(TestRecorder::default()));
+            metrics::set_recorder(recorder).unwrap();
+            recorder
+        };
+    }
+    &RECORDER
+}
+
+#[test]
+fn test_parse_statistics_histogram() {
+    let stats = "rocksdb.db.get.micros P50 : 7.000000 P95 : 9.000000 P99 : 11.000000 P100 : 12.000000 COUNT : 3 SUM : 25\n\
+        rocksdb.db.fsync.micros P50 : 1.500000 P95 : 2.000000 P99 : 3.250000 P100 : 4.000000 COUNT : 10 SUM : 20\n";
+    assert_eq!(
+        parse_statistics_histogram(stats, "rocksdb.db.fsync.micros"),
+        Some(StatisticsHistogram { p50: 1.5, p99: 3.25, count: 10 })
+    );
+    assert_eq!(parse_statistics_histogram(stats, "rocksdb.db"), None);
+    assert_eq!(parse_statistics_histogram(stats, "rocksdb.wal.file.sync.micros"), None);
+    assert_eq!(
+        parse_statistics_histogram("rocksdb.db.fsync.micros P50 : x COUNT : 1", "rocksdb.db.fsync.micros"),
+        None
+    );
+}
+
+#[test]
+fn test_sync_statistics() -> Result<()> {
+    let testname = "test_sync_statistics";
+    let _ = std::fs::remove_dir_all(testname);
+    let db = RocksDb::with_path(testname, testname)?;
+
+    let stats = db.statistics().expect("statistics must be enabled");
+    for name in ["rocksdb.db.fsync.micros", "rocksdb.wal.file.sync.micros"] {
+        assert!(parse_statistics_histogram(&stats, name).is_some(), "no {} in statistics", name);
+    }
+    db.report_sync_statistics();
+
+    drop(db);
+    let _ = std::fs::remove_dir_all(testname);
+    Ok(())
+}
+
+#[test]
+fn test_cell_read_time_sampled() -> Result<()> {
+    let recorder = recorder();
+    let testname = "test_cell_read_time_sampled";
+    let _ = std::fs::remove_dir_all(testname);
+    let db = RocksDb::with_path(testname, testname)?;
+    let boc_db = Arc::new(DynamicBocDb::with_db(
+        Arc::new(CellDb::with_db(db.clone(), testname, true)?),
+        "",
+        false,
+        1_000_000,
+        #[cfg(feature = "telemetry")]
+        Arc::new(StorageTelemetry::default()),
+        Arc::new(StorageAlloc::default()),
+    ));
+    let root = get_test_tree_of_cells();
+    let mut cells_counters = Some(fnv::FnvHashMap::default());
+    boc_db.save_boc(root.clone(), true, &|| Ok(()), &mut cells_counters, false)?;
+
+    // any 2 * N successive reads contain exactly 2 sampled ones
+    let before = recorder.values("db_cell_read_time").len();
+    for _ in 0..2 * CELL_READ_TIME_SAMPLE {
+        boc_db.load_cell(&root.repr_hash(), false)?;
+    }
+    let sampled = recorder.values("db_cell_read_time").len() - before;
+    // other tests of the crate may read cells at the same time
+    assert!(sampled >= 2);
+    assert!(sampled < 2 * CELL_READ_TIME_SAMPLE as usize);
+
+    drop(boc_db);
+    drop(db);
+    let _ = std::fs::remove_dir_all(testname);
+    Ok(())
+}
+
+#[test]
+fn test_state_commit_time() -> Result<()> {
+    let recorder = recorder();
+    let testname = "test_state_commit_time";
+    let _ = std::fs::remove_dir_all(testname);
+    let db = RocksDb::with_path(testname, testname)?;
+    let boc_db = DynamicBocDb::with_db(
+        Arc::new(CellDb::with_db(db.clone(), testname, true)?),
+        "",
+        false,
+        1_000_000,
+        #[cfg(feature = "telemetry")]
+        Arc::new(StorageTelemetry::default()),
+        Arc::new(StorageAlloc::default()),
+    );
+
+    let before = recorder.values("db_state_commit_time").len();
+    let mut cells_counters = Some(fnv::FnvHashMap::default());
+    boc_db.save_boc(get_test_tree_of_cells(), true, &|| Ok(()), &mut cells_counters, false)?;
+    assert_eq!(recorder.values("db_state_commit_time").len(), before + 1);
+
+    drop(boc_db);
+    drop(db);
+    let _ = std::fs::remove_dir_all(testname);
+    Ok(())
+}
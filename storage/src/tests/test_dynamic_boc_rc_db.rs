@@ -12,8 +12,8 @@
 */
 
 use crate::{
-    cell_db::CellDb, db::rocksdb::RocksDb, dynamic_boc_rc_db::DynamicBocDb, 
-    tests::utils::*, StorageAlloc
+    cell_db::CellDb, db::rocksdb::RocksDb, 
+    dynamic_boc_rc_db::{CellsVerification, DynamicBocDb}, tests::utils::*, StorageAlloc
 };
 #[cfg(feature = "telemetry")]
 use crate::StorageTelemetry;
@@ -68,3 +68,61 @@ fn test_dynamic_boc_rc_db() -> Result<()> {
     Ok(())
 }
 
+
+#[test]
+fn test_verify_cells() -> Result<()> {
+
+    let testname = "test_verify_cells";
+    let _ = std::fs::remove_dir_all(testname);
+
+    let db = RocksDb::with_path(testname, testname)?;
+
+    let boc_db = Arc::new(DynamicBocDb::with_db(
+        Arc::new(CellDb::with_db(db.clone(), testname, true)?), 
+        "",
+        false,
+        1_000_000,
+        #[cfg(feature = "telemetry")]
+        Arc::new(StorageTelemetry::default()),
+        Arc::new(StorageAlloc::default()),
+    ));
+
+    let root_cell = get_test_tree_of_cells();
+    let mut cells_counters = None;
+    boc_db.save_boc(root_cell.clone(), true, &|| Ok(()), &mut cells_counters, false)?;
+    let cells_count = boc_db.len()?;
+
+    // unreachable tree: its root has zero counter, some of its cells are shared with the first tree
+    let root_cell_2 = get_another_test_tree_of_cells();
+    boc_db.save_boc(root_cell_2.clone(), true, &|| Ok(()), &mut cells_counters, false)?;
+    let mut counter_key = [0; 33];
+    counter_key[..32].copy_from_slice(root_cell_2.repr_hash().as_slice());
+    boc_db.put_raw(&counter_key, &0_u32.to_le_bytes())?;
+    // zero counter without cell
+    let mut leaked = [0x55; 33];
+    leaked[32] = 0;
+    boc_db.put_raw(&leaked, &0_u32.to_le_bytes())?;
+    let entries_count = boc_db.len()?;
+
+    // the pass is made by batches, nothing is deleted without repair
+    let mut verification = CellsVerification::default();
+    let mut batches = 1;
+    while !boc_db.verify_cells_batch(&mut verification, 3, false, &mut cells_counters)? {
+        batches += 1;
+    }
+    assert_eq!(batches, (entries_count + 2) / 3);
+    assert_eq!(boc_db.len()?, entries_count);
+
+    // the unreachable tree is deleted, the shared cells are kept
+    let mut verification = CellsVerification::default();
+    while !boc_db.verify_cells_batch(&mut verification, 3, true, &mut cells_counters)? { }
+    assert_eq!(boc_db.len()?, cells_count);
+    let loaded_boc = boc_db.load_boc(&root_cell.repr_hash().into(), false)?;
+    assert_eq!(
+        count_tree_unique_cells(loaded_boc), 
+        count_tree_unique_cells(root_cell)
+    );
+
+    let _ = std::fs::remove_dir_all(testname);
+    Ok(())
+}
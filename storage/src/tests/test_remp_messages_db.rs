@@ -0,0 +1,50 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{remp_messages_db::{RempMessageEntry, RempMessagesDb}, traits::Serializable};
+use ton_types::{Result, UInt256};
+
+fn entry(id: u8, message: Option<Vec<u8>>) -> RempMessageEntry {
+    RempMessageEntry { uid: UInt256::from([id; 32]), status: vec![id; 8], message }
+}
+
+#[test]
+fn test_remp_message_entry_serialization() -> Result<()> {
+    for e in [entry(1, None), entry(2, Some(vec![1, 2, 3]))] {
+        assert_eq!(RempMessageEntry::from_slice(&e.to_vec()?)?, e);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_remp_messages_db() -> Result<()> {
+    let db = RempMessagesDb::in_memory();
+    db.put_entry(10, &UInt256::from([1; 32]), &entry(1, None))?;
+    db.put_entry(11, &UInt256::from([2; 32]), &entry(2, Some(vec![5])))?;
+    db.put_entry(11, &UInt256::from([3; 32]), &entry(3, Some(vec![6])))?;
+
+    let mut ids = Vec::new();
+    db.for_each_entry(11, &mut |id, e| {
+        assert_eq!(e.uid, id);
+        ids.push(id);
+        Ok(())
+    })?;
+    ids.sort();
+    assert_eq!(ids, vec![UInt256::from([2; 32]), UInt256::from([3; 32])]);
+
+    assert_eq!(db.remove_before(11)?, 1);
+    let mut count = 0;
+    db.for_each_entry(10, &mut |_, _| { count += 1; Ok(()) })?;
+    assert_eq!(count, 0);
+    Ok(())
+}
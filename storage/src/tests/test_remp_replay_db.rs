@@ -0,0 +1,42 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{remp_replay_db::{RempReplayDb, RempReplayEntry}, traits::Serializable};
+use ton_block::{BlockIdExt, ShardIdent};
+use ton_types::{Result, UInt256};
+
+fn entry(seqno: u32) -> RempReplayEntry {
+    RempReplayEntry {
+        message_id: UInt256::from([seqno as u8; 32]),
+        block_id: BlockIdExt::with_params(
+            ShardIdent::full(0), seqno, UInt256::from([1; 32]), UInt256::from([2; 32])
+        ),
+        accepted_at: 1700000000 + seqno,
+    }
+}
+
+#[test]
+fn test_remp_replay_db() -> Result<()> {
+    let e = entry(10);
+    assert_eq!(RempReplayEntry::from_slice(&e.to_vec()?)?, e);
+
+    let db = RempReplayDb::in_memory();
+    db.put_entry(&UInt256::from([1; 32]), &entry(1))?;
+    db.put_entry(&UInt256::from([2; 32]), &entry(2))?;
+    db.remove_entry(&UInt256::from([1; 32]))?;
+
+    let mut entries = Vec::new();
+    db.for_each_entry(&mut |uid, e| { entries.push((uid, e)); Ok(()) })?;
+    assert_eq!(entries, vec![(UInt256::from([2; 32]), entry(2))]);
+    Ok(())
+}
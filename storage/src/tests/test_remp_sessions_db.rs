@@ -0,0 +1,42 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::{remp_sessions_db::{RempSessionEntry, RempSessionsDb}, traits::Serializable};
+use ton_block::ShardIdent;
+use ton_types::{Result, UInt256};
+
+fn entry(master_cc_seqno: u32, status: u8) -> RempSessionEntry {
+    RempSessionEntry {
+        shard: ShardIdent::with_tagged_prefix(0, 0x4000_0000_0000_0000).unwrap(),
+        master_cc_seqno,
+        status,
+        catchain_overlay_id: UInt256::from([status; 32]),
+    }
+}
+
+#[test]
+fn test_remp_sessions_db() -> Result<()> {
+    let e = entry(10, 3);
+    assert_eq!(RempSessionEntry::from_slice(&e.to_vec()?)?, e);
+
+    let db = RempSessionsDb::in_memory();
+    db.put_session(&UInt256::from([1; 32]), &entry(10, 2))?;
+    db.put_session(&UInt256::from([2; 32]), &entry(11, 3))?;
+    db.put_session(&UInt256::from([1; 32]), &entry(10, 3))?;
+    db.remove_session(&UInt256::from([2; 32]))?;
+
+    let mut sessions = Vec::new();
+    db.for_each_session(&mut |id, e| { sessions.push((id, e)); Ok(()) })?;
+    assert_eq!(sessions, vec![(UInt256::from([1; 32]), entry(10, 3))]);
+    Ok(())
+}
@@ -69,6 +69,8 @@ async fn test_shardstate_db_async() -> Result<()> {
             prefill_cells_counters: false,
             cache_cells_counters: true,
             cache_size_bytes: 10000000,
+            cells_verification_batch: 100,
+            cells_verification_repair: false,
         },
         #[cfg(feature = "telemetry")]
         Arc::new(StorageTelemetry::default()),
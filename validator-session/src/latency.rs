@@ -0,0 +1,257 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::{collections::{HashMap, VecDeque}, fmt::{self, Debug}, time::Duration};
+
+#[cfg(test)]
+#[path = "tests/test_latency.rs"]
+mod tests;
+
+/// Number of the latest measurements of an event kept to compute percentiles
+pub const LATENCY_WINDOW: usize = 100;
+
+/// Public key hash
+pub type PublicKeyHash = ::catchain::PublicKeyHash;
+
+/// Public key
+pub type PublicKey = ::catchain::PublicKey;
+
+/// Round event which latency (from the round start) is measured for each node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(usize)]
+pub enum LatencyEvent {
+    /// Block candidate broadcast has been received from the node (collator)
+    CandidateReceived,
+
+    /// Node has approved block candidate (validation completion)
+    CandidateApproved,
+
+    /// Node's signature for the committed block has arrived
+    BlockSigned,
+
+    /// Number of events
+    EventsCount,
+}
+
+impl LatencyEvent {
+    /// All measured events
+    pub const ALL: [LatencyEvent; LatencyEvent::EventsCount as usize] = [
+        LatencyEvent::CandidateReceived,
+        LatencyEvent::CandidateApproved,
+        LatencyEvent::BlockSigned,
+    ];
+
+    /// Event name for reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            LatencyEvent::CandidateReceived => "candidate_received",
+            LatencyEvent::CandidateApproved => "candidate_approved",
+            LatencyEvent::BlockSigned => "block_signed",
+            LatencyEvent::EventsCount => "unknown",
+        }
+    }
+}
+
+/// Aggregated latency of one event
+#[derive(Clone, Debug, Default)]
+pub struct LatencyValue {
+    /// Number of measurements
+    pub count: u64,
+
+    /// Sum of all measured latencies
+    pub total_ms: u64,
+
+    /// Maximal measured latency
+    pub max_ms: u64,
+
+    /// Number of rounds finished before the event happened
+    pub missed: u64,
+
+    /// The latest measured latencies (ms), oldest first, at most `LATENCY_WINDOW`
+    pub window: VecDeque<u64>,
+}
+
+impl LatencyValue {
+    /// Add measurement
+    pub fn record(&mut self, latency: Duration) {
+        let latency_ms = latency.as_millis() as u64;
+        self.count += 1;
+        self.total_ms += latency_ms;
+        self.max_ms = self.max_ms.max(latency_ms);
+        self.push_to_window(latency_ms);
+    }
+
+    /// Merge measurements, the merged ones are considered the latest
+    pub fn merge(&mut self, value: &LatencyValue) {
+        self.count += value.count;
+        self.total_ms += value.total_ms;
+        self.max_ms = self.max_ms.max(value.max_ms);
+        self.missed += value.missed;
+        for latency_ms in value.window.iter() {
+            self.push_to_window(*latency_ms);
+        }
+    }
+
+    /// Latency (nearest-rank method) not exceeded by `percent` of the latest measurements
+    pub fn percentile_ms(&self, percent: u8) -> Option<u64> {
+        if self.window.is_empty() {
+            return None
+        }
+        let mut sorted = self.window.iter().cloned().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        let rank = (sorted.len() * percent.min(100) as usize + 99) / 100;
+        Some(sorted[rank.max(1) - 1])
+    }
+
+    fn push_to_window(&mut self, latency_ms: u64) {
+        if self.window.len() == LATENCY_WINDOW {
+            self.window.pop_front();
+        }
+        self.window.push_back(latency_ms);
+    }
+
+    /// Average latency
+    pub fn average_ms(&self) -> Option<u64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.total_ms / self.count)
+        }
+    }
+}
+
+/// Latency statistics entry for a node
+#[derive(Clone)]
+pub struct NodeLatency {
+    /// Node public key
+    pub public_key: PublicKey,
+
+    /// Latencies of round events
+    pub events: [LatencyValue; LatencyEvent::EventsCount as usize],
+}
+
+impl NodeLatency {
+    /// New statistics entry
+    pub fn new(public_key: PublicKey) -> Self {
+        Self {
+            public_key,
+            events: Default::default(),
+        }
+    }
+
+    /// Latency of the event
+    pub fn get(&self, event: LatencyEvent) -> &LatencyValue {
+        &self.events[event as usize]
+    }
+
+    /// Merge entry
+    pub fn merge(&mut self, entry: &NodeLatency) {
+        for (src_it, dst_it) in entry.events.iter().zip(self.events.iter_mut()) {
+            dst_it.merge(src_it);
+        }
+    }
+}
+
+impl fmt::Debug for NodeLatency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "NodeLatency(public_key_hash={}, events={:?})",
+            hex::encode(self.public_key.id().data()),
+            self.events
+        )
+    }
+}
+
+/// Per-node latencies of round events
+#[derive(Clone, Default)]
+pub struct LatencyStat {
+    /// Number of rounds covered by statistics
+    pub rounds_count: u64,
+
+    /// Statistics entries for each validator
+    pub nodes: HashMap<PublicKeyHash, NodeLatency>,
+}
+
+impl LatencyStat {
+    /// Create new statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record event latency for the node
+    pub fn record(
+        &mut self,
+        public_key: &PublicKey,
+        event: LatencyEvent,
+        latency: Duration,
+    ) {
+        self.nodes
+            .entry(public_key.id().clone())
+            .or_insert_with(|| NodeLatency::new(public_key.clone()))
+            .events[event as usize]
+            .record(latency);
+    }
+
+    /// Record that the event did not happen for the node till the end of the round
+    pub fn record_missed(&mut self, public_key: &PublicKey, event: LatencyEvent) {
+        self.nodes
+            .entry(public_key.id().clone())
+            .or_insert_with(|| NodeLatency::new(public_key.clone()))
+            .events[event as usize]
+            .missed += 1;
+    }
+
+    /// Clear all statistics
+    pub fn clear(&mut self) {
+        self.rounds_count = 0;
+        self.nodes.clear();
+    }
+
+    /// Merge statistics
+    pub fn merge(&mut self, stat: &LatencyStat) {
+        self.rounds_count += stat.rounds_count;
+        for (pub_key_hash, entry) in stat.nodes.iter() {
+            if let Some(self_entry) = self.nodes.get_mut(pub_key_hash) {
+                self_entry.merge(entry);
+            } else {
+                self.nodes.insert(pub_key_hash.clone(), entry.clone());
+            }
+        }
+    }
+
+    /// Nodes sorted by average event latency (the slowest first)
+    pub fn slowest_nodes(&self, event: LatencyEvent, limit: usize) -> Vec<(PublicKeyHash, LatencyValue)> {
+        let mut result: Vec<(PublicKeyHash, LatencyValue)> = self
+            .nodes
+            .iter()
+            .filter(|(_, entry)| entry.get(event).count > 0 || entry.get(event).missed > 0)
+            .map(|(key, entry)| (key.clone(), entry.get(event).clone()))
+            .collect();
+        result.sort_by(|(_, a), (_, b)| {
+            b.missed.cmp(&a.missed).then_with(|| b.average_ms().cmp(&a.average_ms()))
+        });
+        result.truncate(limit);
+        result
+    }
+}
+
+impl Debug for LatencyStat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kv = self
+            .nodes
+            .iter()
+            .map(|(key, value)| (format!("Id({}): {:?}", hex::encode(key.data()), value)));
+        f.debug_list().entries(kv).finish()
+    }
+}
@@ -16,6 +16,7 @@ const TELEGRAM_NODE_COMPATIBILITY_HASHES_BUG: bool = true; //compatibility with
 
 mod block_candidate;
 mod cache;
+mod latency;
 mod old_round;
 mod round;
 mod round_attempt;
@@ -221,6 +222,15 @@ pub type SessionListenerPtr = Weak<dyn SessionListener + Send + Sync>;
 /// Validator's weight
 pub type ValidatorWeight = catchain::ValidatorWeight;
 
+/// Per-node latencies of round events
+pub type LatencyStat = latency::LatencyStat;
+
+/// Round event which latency is measured
+pub type LatencyEvent = latency::LatencyEvent;
+
+/// Aggregated latency of round event
+pub type LatencyValue = latency::LatencyValue;
+
 /// Slashing validator statistics
 #[cfg(feature="slashing")]
 pub type SlashingValidatorStat = slashing::ValidatorStat;
@@ -276,6 +286,12 @@ pub struct SessionOptions {
     /// Receiver: max time for catchain neighbours rotation
     pub catchain_receiver_neighbours_rotate_max_period: std::time::Duration,
 
+    /// Receiver: time without blocks from other validators before resync (zero disables resync)
+    pub catchain_receiver_partition_timeout: std::time::Duration,
+
+    /// Receiver: period of resync attempts
+    pub catchain_receiver_resync_period: std::time::Duration,
+
     /// Number of block candidates per round
     pub round_candidates: u32,
 
@@ -1264,6 +1280,9 @@ pub trait SessionListener {
     /// Slashing statistics event
     #[cfg(feature="slashing")]
     fn on_slashing_statistics(&self, round: u32, stat: SlashingValidatorStat);
+
+    /// Round events latency statistics (for the finished round)
+    fn on_latency_statistics(&self, _round: u32, _stat: LatencyStat) {}
 }
 
 /// Validator session processor
@@ -1215,6 +1215,8 @@ impl SessionImpl {
             receiver_max_sources_sync_attempts: options.catchain_receiver_max_sources_sync_attempts,
             receiver_neighbours_rotate_min_period: options.catchain_receiver_neighbours_rotate_min_period,
             receiver_neighbours_rotate_max_period: options.catchain_receiver_neighbours_rotate_max_period,
+            receiver_partition_timeout: options.catchain_receiver_partition_timeout,
+            receiver_resync_period: options.catchain_receiver_resync_period,
         };
         let catchain_nodes = ids
             .iter()
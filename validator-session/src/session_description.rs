@@ -47,6 +47,8 @@ impl Default for SessionOptions {
             catchain_receiver_max_sources_sync_attempts: catchain_defaults.receiver_max_sources_sync_attempts,
             catchain_receiver_neighbours_rotate_min_period: catchain_defaults.receiver_neighbours_rotate_min_period,
             catchain_receiver_neighbours_rotate_max_period: catchain_defaults.receiver_neighbours_rotate_max_period,
+            catchain_receiver_partition_timeout: catchain_defaults.receiver_partition_timeout,
+            catchain_receiver_resync_period: catchain_defaults.receiver_resync_period,
             round_candidates: 3,
             next_candidate_delay: Duration::from_millis(2000),
             round_attempt_duration: Duration::from_millis(16000),
@@ -13,7 +13,8 @@
 
 use crate::{
     Any, BlockCandidateSignatureVectorPtr, BlockHash, BlockId, BlockPayloadPtr, BlockSignature,
-    CallbackTaskQueuePtr, CompletionHandlerProcessor, HashType, Merge, MovablePoolObject, 
+    CallbackTaskQueuePtr, CompletionHandlerProcessor, HashType, LatencyEvent, LatencyStat,
+    Merge, MovablePoolObject, 
     PrivateKey, PublicKey, PublicKeyHash, SentBlockPtr, SentBlockWrapper, SessionDescription, 
     SessionFactory, SessionId, SessionListenerPtr, SessionOptions, SessionNode, SessionProcessor,
     SessionProcessorPtr, SessionStatePtr, SessionStateWrapper, SKIP_ROUND_CANDIDATE_BLOCKID, 
@@ -134,6 +135,8 @@ pub(crate) struct SessionProcessorImpl {
     last_process_blocks_warn_dump_time: SystemTime, //last time process blocks latency warning has been printed
     #[cfg(feature="slashing")]
     slashing_stat: SlashingValidatorStat,           //slashing validator statistics
+    latency_stat: LatencyStat,                      //per-node latencies of round events
+    latency_events_registered: HashSet<(u32, usize)>, //(source, event) pairs registered in current round
     last_preprocess_block_time: Vec<SystemTime>, //time of last preprocess block request from a partial validator
     round_duration_histogram: metrics::Histogram, //histogram for round duration
     first_candidate_received: bool, //first candidate for validation has been received in current round
@@ -505,6 +508,22 @@ impl SessionProcessor for SessionProcessorImpl {
                             msg
                         );
 
+                        //update latency statistics
+
+                        match msg {
+                            ton::Message::ValidatorSession_Message_ApprovedBlock(msg)
+                                if msg.round as u32 == self.current_round =>
+                            {
+                                self.record_round_latency(node_source_id, LatencyEvent::CandidateApproved)
+                            }
+                            ton::Message::ValidatorSession_Message_Commit(msg)
+                                if msg.round as u32 == self.current_round =>
+                            {
+                                self.record_round_latency(node_source_id, LatencyEvent::BlockSigned)
+                            }
+                            _ => {}
+                        }
+
                         state = state.apply_action(
                             &mut self.description,
                             node_source_id,
@@ -1296,6 +1315,8 @@ impl SessionProcessor for SessionProcessorImpl {
             return;
         }
 
+        self.record_round_latency(src_idx, LatencyEvent::CandidateReceived);
+
         assert!(!self.pending_approve.contains(&block_id));
         assert!(!self.approved.contains_key(&block_id));
         assert!(!self.pending_reject.contains_key(&block_id));
@@ -1891,6 +1912,21 @@ impl SessionProcessorImpl {
             .duration_since(self.round_started_at)
     }
 
+    fn record_round_latency(&mut self, source_idx: u32, event: LatencyEvent) {
+        if !self.catchain_started {
+            return;
+        }
+
+        if !self.latency_events_registered.insert((source_idx, event as usize)) {
+            return; //only the first event of each kind per round is measured
+        }
+
+        if let Ok(latency) = self.get_latency_from_round_start() {
+            let public_key = self.description.get_source_public_key(source_idx).clone();
+            self.latency_stat.record(&public_key, event, latency);
+        }
+    }
+
     fn new_round(&mut self, round: u32) {
         instrument!();
 
@@ -2102,6 +2138,27 @@ impl SessionProcessorImpl {
                 self.notify_slashing_statistics(self.current_round, self.slashing_stat.clone());
             }
 
+            //update latency statistics
+
+            if self.catchain_started {
+                for i in 0..self.description.get_total_nodes() {
+                    for event in [LatencyEvent::CandidateApproved, LatencyEvent::BlockSigned] {
+                        if !self.latency_events_registered.contains(&(i, event as usize)) {
+                            let public_key = self.description.get_source_public_key(i).clone();
+                            self.latency_stat.record_missed(&public_key, event);
+                        }
+                    }
+                }
+
+                self.latency_stat.rounds_count = 1;
+
+                let latency_stat = std::mem::take(&mut self.latency_stat);
+
+                self.notify_latency_statistics(self.current_round, latency_stat);
+            }
+
+            self.latency_events_registered.clear();
+
             //remove current round block payloads because we have already processed it
 
             self.blocks.remove(&self.current_round);
@@ -3214,6 +3271,31 @@ impl SessionProcessorImpl {
         });
     }
 
+    fn notify_latency_statistics(&self, round: u32, stat: LatencyStat) {
+        check_execution_time!(20000);
+        instrument!();
+
+        log::trace!(
+            "SessionProcessor::notify_latency_statistics: post on_latency_statistics event for further processing"
+        );
+
+        let listener = self.session_listener.clone();
+
+        post_callback_closure(&self.callbacks_task_queue, move || {
+            check_execution_time!(20000);
+
+            if let Some(listener) = listener.upgrade() {
+                log::trace!("SessionProcessor::notify_latency_statistics: on_latency_statistics start");
+
+                listener.on_latency_statistics(round, stat);
+
+                log::trace!(
+                    "SessionProcessor::notify_latency_statistics: on_latency_statistics finish"
+                );
+            }
+        });
+    }
+
     fn notify_candidate(
         &mut self,
         round: u32,
@@ -3597,6 +3679,8 @@ impl SessionProcessorImpl {
             last_process_blocks_warn_dump_time: now,
             #[cfg(feature="slashing")]
             slashing_stat,
+            latency_stat: LatencyStat::new(),
+            latency_events_registered: HashSet::new(),
             last_preprocess_block_time: vec![SystemTime::UNIX_EPOCH; ids.len()],
             active_weight_gauge,
         };
@@ -0,0 +1,105 @@
+/*
+* Copyright (C) 2019-2023 EverX. All Rights Reserved.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::*;
+use ton_types::Ed25519KeyOption;
+
+fn value_of(latencies: &[u64]) -> LatencyValue {
+    let mut value = LatencyValue::default();
+    for latency in latencies {
+        value.record(Duration::from_millis(*latency));
+    }
+    value
+}
+
+#[test]
+fn test_latency_percentiles() {
+    assert_eq!(LatencyValue::default().percentile_ms(50), None);
+
+    let value = value_of(&[10]);
+    assert_eq!(value.percentile_ms(0), Some(10));
+    assert_eq!(value.percentile_ms(100), Some(10));
+
+    // order of measurements doesn't matter
+    let value = value_of(&[100, 20, 90, 10, 80, 30, 70, 40, 60, 50]);
+    assert_eq!(value.percentile_ms(0), Some(10));
+    assert_eq!(value.percentile_ms(10), Some(10));
+    assert_eq!(value.percentile_ms(50), Some(50));
+    assert_eq!(value.percentile_ms(51), Some(60));
+    assert_eq!(value.percentile_ms(90), Some(90));
+    assert_eq!(value.percentile_ms(100), Some(100));
+    assert_eq!(value.percentile_ms(200), Some(100));
+    assert_eq!(value.average_ms(), Some(55));
+    assert_eq!(value.max_ms, 100);
+}
+
+#[test]
+fn test_latency_window() {
+    // old measurements leave the window, but are kept in totals
+    let mut value = value_of(&[1000; LATENCY_WINDOW]);
+    for _ in 0..LATENCY_WINDOW / 2 {
+        value.record(Duration::from_millis(10));
+    }
+    assert_eq!(value.window.len(), LATENCY_WINDOW);
+    assert_eq!(value.percentile_ms(50), Some(10));
+    assert_eq!(value.percentile_ms(51), Some(1000));
+    assert_eq!(value.count, LATENCY_WINDOW as u64 * 3 / 2);
+    assert_eq!(value.max_ms, 1000);
+
+    // merged measurements are the latest ones
+    value.merge(&value_of(&[10; LATENCY_WINDOW]));
+    assert_eq!(value.window.len(), LATENCY_WINDOW);
+    assert_eq!(value.percentile_ms(100), Some(10));
+    assert_eq!(value.count, LATENCY_WINDOW as u64 * 5 / 2);
+    assert_eq!(value.max_ms, 1000);
+}
+
+#[test]
+fn test_latency_stat_merge() {
+    let slow = Ed25519KeyOption::generate().unwrap();
+    let fast = Ed25519KeyOption::generate().unwrap();
+
+    let mut total = LatencyStat::new();
+    for round in 0..3 {
+        let mut stat = LatencyStat::new();
+        stat.rounds_count = 1;
+        stat.record(&slow, LatencyEvent::CandidateApproved, Duration::from_millis(500 + round));
+        stat.record(&fast, LatencyEvent::CandidateApproved, Duration::from_millis(50));
+        if round == 2 {
+            stat.record_missed(&slow, LatencyEvent::BlockSigned);
+        } else {
+            stat.record(&slow, LatencyEvent::BlockSigned, Duration::from_millis(10));
+        }
+        stat.record(&fast, LatencyEvent::BlockSigned, Duration::from_millis(100));
+        total.merge(&stat);
+    }
+    assert_eq!(total.rounds_count, 3);
+
+    let approved = total.slowest_nodes(LatencyEvent::CandidateApproved, 10);
+    assert_eq!(approved.len(), 2);
+    assert_eq!(&approved[0].0, slow.id());
+    assert_eq!(approved[0].1.count, 3);
+    assert_eq!(approved[0].1.percentile_ms(50), Some(501));
+    assert_eq!(approved[0].1.max_ms, 502);
+
+    // missed events go first
+    let signed = total.slowest_nodes(LatencyEvent::BlockSigned, 1);
+    assert_eq!(signed.len(), 1);
+    assert_eq!(&signed[0].0, slow.id());
+    assert_eq!(signed[0].1.missed, 1);
+
+    assert!(total.slowest_nodes(LatencyEvent::CandidateReceived, 10).is_empty());
+    total.clear();
+    assert_eq!(total.rounds_count, 0);
+    assert!(total.nodes.is_empty());
+}